@@ -0,0 +1,470 @@
+//! Packed read-only archive of cooked assets.
+//!
+//! Shipped games load assets from archives instead of
+//! loose artifacts and import pipeline.
+//!
+//! Layout:
+//!
+//! - Header: magic, format version, index offset and index length.
+//! - Blobs: asset data, each either stored as is or LZ4-compressed.
+//! - Index: bincode-encoded list of entries and source lookup table.
+//!
+//! Archive is memory-mapped when opened, so only index is read eagerly.
+//! For the same reason web builds can fetch header and index first
+//! and then stream blobs with HTTP range requests.
+//!
+//! Cooking writes the archive next to the game binary.
+//! Opening it and registering as a loader of [`Assets`](super::Assets)
+//! is done by the game runtime and is not part of this module.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use arcana_names::Ident;
+use futures::future::BoxFuture;
+use hashbrown::HashMap;
+
+use super::{
+    error::{Error, NotFound},
+    id::AssetId,
+    loader::{AssetData, Loader},
+};
+
+const MAGIC: [u8; 8] = *b"ARCNPACK";
+const FORMAT_VERSION: u32 = 1;
+
+/// magic + version + index offset + index length.
+const HEADER_SIZE: usize = 8 + 4 + 8 + 8;
+
+/// LZ4 cannot expand data more than this.
+/// Entries claiming larger size are rejected instead of allocating for them.
+const MAX_LZ4_RATIO: u64 = 255;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+enum Compression {
+    None,
+    Lz4,
+}
+
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+struct Entry {
+    id: AssetId,
+    offset: u64,
+    len: u64,
+    size: u64,
+    compression: Compression,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct SourceEntry {
+    source: String,
+    target: Ident,
+    id: AssetId,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct Index {
+    entries: Vec<Entry>,
+    sources: Vec<SourceEntry>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    #[error("Failed to access archive '{path}'. {error}")]
+    Io {
+        error: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[error("File '{path}' is not an asset archive")]
+    BadMagic { path: PathBuf },
+
+    #[error("Archive '{path}' has unsupported version {version}")]
+    UnsupportedVersion { version: u32, path: PathBuf },
+
+    #[error("Archive '{path}' is corrupted")]
+    Corrupted { path: PathBuf },
+
+    #[error("Failed to decode archive index of '{path}'. {error}")]
+    Index {
+        error: bincode::Error,
+        path: PathBuf,
+    },
+
+    #[error("Failed to decompress asset '{id}'. {error}")]
+    Decompress {
+        error: lz4_flex::block::DecompressError,
+        id: AssetId,
+    },
+
+    #[error("Asset '{id}' is added twice")]
+    Duplicate { id: AssetId },
+}
+
+/// Writes assets into an archive.
+///
+/// Used by cooking step to pack artifacts.
+pub struct ArchiveWriter {
+    file: BufWriter<File>,
+    path: PathBuf,
+    offset: u64,
+    index: Index,
+    ids: HashMap<AssetId, usize>,
+}
+
+impl ArchiveWriter {
+    /// Creates new archive file.
+    /// Existing file is truncated.
+    pub fn create(path: &Path) -> Result<Self, ArchiveError> {
+        let io_error = |error| ArchiveError::Io {
+            error,
+            path: path.to_owned(),
+        };
+
+        let file = File::create(path).map_err(io_error)?;
+        let mut file = BufWriter::new(file);
+
+        // Header is written on finish.
+        file.write_all(&[0; HEADER_SIZE]).map_err(io_error)?;
+
+        Ok(ArchiveWriter {
+            file,
+            path: path.to_owned(),
+            offset: HEADER_SIZE as u64,
+            index: Index::default(),
+            ids: HashMap::new(),
+        })
+    }
+
+    /// Adds asset data to the archive.
+    ///
+    /// Data is compressed if it makes it smaller.
+    pub fn add(&mut self, id: AssetId, data: &[u8]) -> Result<(), ArchiveError> {
+        if self.ids.contains_key(&id) {
+            return Err(ArchiveError::Duplicate { id });
+        }
+
+        let compressed = lz4_flex::block::compress(data);
+
+        let (blob, compression) = if compressed.len() < data.len() {
+            (&compressed[..], Compression::Lz4)
+        } else {
+            (data, Compression::None)
+        };
+
+        self.file
+            .write_all(blob)
+            .map_err(|error| ArchiveError::Io {
+                error,
+                path: self.path.clone(),
+            })?;
+
+        self.ids.insert(id, self.index.entries.len());
+        self.index.entries.push(Entry {
+            id,
+            offset: self.offset,
+            len: blob.len() as u64,
+            size: data.len() as u64,
+            compression,
+        });
+
+        self.offset += blob.len() as u64;
+        Ok(())
+    }
+
+    /// Records that asset imported from `source` as `target` has given ID.
+    /// Allows finding assets by source in shipped game.
+    pub fn add_source(&mut self, source: &str, target: Ident, id: AssetId) {
+        self.index.sources.push(SourceEntry {
+            source: source.to_owned(),
+            target,
+            id,
+        });
+    }
+
+    /// Writes index and header and closes the archive.
+    pub fn finish(mut self) -> Result<(), ArchiveError> {
+        let io_error = |error| ArchiveError::Io {
+            error,
+            path: self.path.clone(),
+        };
+
+        self.index.entries.sort_by_key(|entry| entry.id);
+
+        let index = bincode::serialize(&self.index).expect("Index serialization cannot fail");
+        self.file.write_all(&index).map_err(io_error)?;
+
+        let mut header = [0; HEADER_SIZE];
+        header[0..8].copy_from_slice(&MAGIC);
+        header[8..12].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        header[12..20].copy_from_slice(&self.offset.to_le_bytes());
+        header[20..28].copy_from_slice(&(index.len() as u64).to_le_bytes());
+
+        self.file.seek(SeekFrom::Start(0)).map_err(io_error)?;
+        self.file.write_all(&header).map_err(io_error)?;
+        self.file.flush().map_err(io_error)?;
+        Ok(())
+    }
+}
+
+/// Read-only asset archive.
+///
+/// Implements [`Loader`] so it can be used as asset source
+/// in place of the import pipeline.
+pub struct Archive {
+    map: memmap2::Mmap,
+    entries: Vec<Entry>,
+    sources: HashMap<(String, Ident), AssetId>,
+}
+
+impl Archive {
+    pub fn open(path: &Path) -> Result<Self, ArchiveError> {
+        let file = File::open(path).map_err(|error| ArchiveError::Io {
+            error,
+            path: path.to_owned(),
+        })?;
+
+        // Safety: archives are not expected to be modified while game runs.
+        let map = unsafe { memmap2::Mmap::map(&file) }.map_err(|error| ArchiveError::Io {
+            error,
+            path: path.to_owned(),
+        })?;
+
+        if map.len() < HEADER_SIZE || map[0..8] != MAGIC {
+            return Err(ArchiveError::BadMagic {
+                path: path.to_owned(),
+            });
+        }
+
+        let version = u32::from_le_bytes(map[8..12].try_into().unwrap());
+        if version != FORMAT_VERSION {
+            return Err(ArchiveError::UnsupportedVersion {
+                version,
+                path: path.to_owned(),
+            });
+        }
+
+        let index_offset = u64::from_le_bytes(map[12..20].try_into().unwrap());
+        let index_len = u64::from_le_bytes(map[20..28].try_into().unwrap());
+
+        let index_range = usize::try_from(index_offset)
+            .ok()
+            .zip(usize::try_from(index_len).ok())
+            .and_then(|(offset, len)| Some(offset..offset.checked_add(len)?))
+            .filter(|range| range.end <= map.len())
+            .ok_or_else(|| ArchiveError::Corrupted {
+                path: path.to_owned(),
+            })?;
+
+        let index: Index =
+            bincode::deserialize(&map[index_range]).map_err(|error| ArchiveError::Index {
+                error,
+                path: path.to_owned(),
+            })?;
+
+        for entry in &index.entries {
+            let size_valid = match entry.compression {
+                Compression::None => entry.size == entry.len,
+                Compression::Lz4 => entry.size <= entry.len.saturating_mul(MAX_LZ4_RATIO),
+            };
+
+            if !size_valid
+                || entry
+                    .offset
+                    .checked_add(entry.len)
+                    .map_or(true, |end| end > index_offset)
+            {
+                return Err(ArchiveError::Corrupted {
+                    path: path.to_owned(),
+                });
+            }
+        }
+
+        let sources = index
+            .sources
+            .into_iter()
+            .map(|entry| ((entry.source, entry.target), entry.id))
+            .collect();
+
+        Ok(Archive {
+            map,
+            entries: index.entries,
+            sources,
+        })
+    }
+
+    /// Returns iterator over IDs of all assets in the archive.
+    pub fn ids(&self) -> impl Iterator<Item = AssetId> + '_ {
+        self.entries.iter().map(|entry| entry.id)
+    }
+
+    pub fn contains(&self, id: AssetId) -> bool {
+        self.entry(id).is_some()
+    }
+
+    /// Reads and decompresses asset data.
+    /// Returns `None` if archive does not contain the asset.
+    pub fn read(&self, id: AssetId) -> Option<Result<Box<[u8]>, ArchiveError>> {
+        let entry = self.entry(id)?;
+
+        let start = entry.offset as usize;
+        let blob = &self.map[start..start + entry.len as usize];
+
+        Some(match entry.compression {
+            Compression::None => Ok(blob.into()),
+            Compression::Lz4 => lz4_flex::block::decompress(blob, entry.size as usize)
+                .map(Vec::into_boxed_slice)
+                .map_err(|error| ArchiveError::Decompress { error, id }),
+        })
+    }
+
+    /// Finds ID of the asset imported from the source as specified target.
+    pub fn find(&self, source: &str, target: Ident) -> Option<AssetId> {
+        self.sources.get(&(source.to_owned(), target)).copied()
+    }
+
+    fn entry(&self, id: AssetId) -> Option<&Entry> {
+        let idx = self
+            .entries
+            .binary_search_by_key(&id, |entry| entry.id)
+            .ok()?;
+        Some(&self.entries[idx])
+    }
+}
+
+impl Loader for Archive {
+    fn load<'a>(&'a self, id: AssetId) -> BoxFuture<'a, Result<AssetData, Error>> {
+        let result = match self.read(id) {
+            None => Err(Error::new(NotFound)),
+            Some(Err(err)) => Err(Error::new(err)),
+            Some(Ok(bytes)) => Ok(AssetData { bytes, version: 0 }),
+        };
+        Box::pin(futures::future::ready(result))
+    }
+
+    fn update<'a>(
+        &'a self,
+        _id: AssetId,
+        _version: u64,
+    ) -> BoxFuture<'a, Result<Option<AssetData>, Error>> {
+        // Archive never changes.
+        Box::pin(futures::future::ready(Ok(None)))
+    }
+
+    fn find<'a>(
+        &'a self,
+        source: &'a str,
+        target: Ident,
+    ) -> BoxFuture<'a, Result<Option<AssetId>, Error>> {
+        Box::pin(futures::future::ready(Ok(Archive::find(
+            self, source, target,
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arcana_names::ident;
+
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("arcana-{}-{name}.arcpack", std::process::id()))
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let path = temp_path("roundtrip");
+
+        let a = AssetId::new(1).unwrap();
+        let b = AssetId::new(2).unwrap();
+        let compressible = vec![7u8; 4096];
+        let raw = [1u8, 2, 3];
+
+        let mut writer = ArchiveWriter::create(&path).unwrap();
+        writer.add(b, &compressible).unwrap();
+        writer.add(a, &raw).unwrap();
+        assert!(matches!(
+            writer.add(a, &raw),
+            Err(ArchiveError::Duplicate { .. })
+        ));
+        writer.add_source("image.png", ident!(texture), a);
+        writer.finish().unwrap();
+
+        let archive = Archive::open(&path).unwrap();
+        assert_eq!(archive.ids().collect::<Vec<_>>(), [a, b]);
+        assert_eq!(&*archive.read(a).unwrap().unwrap(), &raw);
+        assert_eq!(&*archive.read(b).unwrap().unwrap(), &compressible[..]);
+        assert!(archive.read(AssetId::new(3).unwrap()).is_none());
+        assert_eq!(archive.find("image.png", ident!(texture)), Some(a));
+        assert_eq!(archive.find("image.png", ident!(mesh)), None);
+
+        drop(archive);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_header() {
+        let path = temp_path("corrupted");
+
+        let mut writer = ArchiveWriter::create(&path).unwrap();
+        writer.add(AssetId::new(1).unwrap(), &[0; 64]).unwrap();
+        writer.finish().unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+
+        // Index offset points past the end of the file.
+        let mut corrupted = bytes.clone();
+        corrupted[12..20].copy_from_slice(&u64::MAX.to_le_bytes());
+        std::fs::write(&path, &corrupted).unwrap();
+        assert!(matches!(
+            Archive::open(&path),
+            Err(ArchiveError::Corrupted { .. })
+        ));
+
+        let mut corrupted = bytes.clone();
+        corrupted[0] = b'X';
+        std::fs::write(&path, &corrupted).unwrap();
+        assert!(matches!(
+            Archive::open(&path),
+            Err(ArchiveError::BadMagic { .. })
+        ));
+
+        let mut corrupted = bytes;
+        corrupted[8..12].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        std::fs::write(&path, &corrupted).unwrap();
+        assert!(matches!(
+            Archive::open(&path),
+            Err(ArchiveError::UnsupportedVersion { .. })
+        ));
+
+        std::fs::write(&path, b"ARCN").unwrap();
+        assert!(matches!(
+            Archive::open(&path),
+            Err(ArchiveError::BadMagic { .. })
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_oversized_entry() {
+        let path = temp_path("oversized");
+
+        let id = AssetId::new(1).unwrap();
+        let mut writer = ArchiveWriter::create(&path).unwrap();
+        writer.add(id, &[0; 64]).unwrap();
+        writer.index.entries[0].size = u64::MAX;
+        writer.finish().unwrap();
+
+        assert!(matches!(
+            Archive::open(&path),
+            Err(ArchiveError::Corrupted { .. })
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
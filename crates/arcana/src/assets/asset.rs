@@ -1,5 +1,7 @@
 use std::{any::Any, future::Future};
 
+use arcana_names::Ident;
+
 use super::{assets::Assets, build::AssetBuilder, error::Error};
 
 /// Asset trait must be implemented for a type to be loaded as an Asset.
@@ -8,6 +10,10 @@ pub trait Asset: Any + Send + Sync + Clone {
     /// If building is not required, this can be Self.
     type Loaded: Any + Send + Sync;
 
+    /// Import target that produces this kind of asset.
+    /// Used to find assets by source path.
+    fn target() -> Ident;
+
     fn load(
         data: Box<[u8]>,
         assets: &Assets,
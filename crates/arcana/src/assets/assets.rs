@@ -6,6 +6,7 @@ use std::{
 };
 
 use amity::flip_queue::FlipQueue;
+use arcana_names::Ident;
 use hashbrown::{HashMap, HashSet};
 use parking_lot::{Mutex, RwLock};
 
@@ -48,6 +49,9 @@ struct AssetsInner {
 
     // Queue of assets to build.
     to_build: FlipQueue<(TypeId, AssetId)>,
+
+    // Queue of assets that were rebuilt with updated data.
+    reloaded: FlipQueue<AssetId>,
 }
 
 impl Assets {
@@ -57,6 +61,7 @@ impl Assets {
                 loaders: loaders.into_iter().collect(),
                 types: RwLock::new(HashMap::new()),
                 to_build: FlipQueue::new(),
+                reloaded: FlipQueue::new(),
             }),
         }
     }
@@ -86,6 +91,32 @@ impl Assets {
         self.typed_entry::<A>(id).poll_asset(id, self, Some(cx))
     }
 
+    /// Finds ID of the asset imported from the source as specified target.
+    pub async fn find(&self, source: &str, target: Ident) -> Result<AssetId, Error> {
+        for loader in self.inner.loaders.iter() {
+            if let Some(id) = loader.find(source, target).await? {
+                return Ok(id);
+            }
+        }
+
+        Err(Error::new(NotFound))
+    }
+
+    /// Drops cached asset.
+    /// Next request for it will load it again.
+    pub fn unload<A>(&self, id: AssetId)
+    where
+        A: Asset,
+    {
+        self.unload_any(TypeId::of::<A>(), id);
+    }
+
+    pub(super) fn unload_any(&self, type_id: TypeId, id: AssetId) {
+        if let Some(typed) = self.typed_get(type_id, id) {
+            typed.unload(id);
+        }
+    }
+
     /// Drops all assets except assets of listed types.
     ///
     /// This function is not intended for game code.
@@ -107,11 +138,37 @@ impl Assets {
         });
     }
 
+    /// Asks loaders whether newer data is available for ready assets.
+    ///
+    /// Updated data is loaded in background and asset is replaced
+    /// in place on next build step.
+    /// Until then old version of the asset is returned.
+    pub fn check_updates(&self) {
+        let types_read = self.inner.types.read();
+        for typed_array in types_read.values() {
+            for typed in typed_array.iter() {
+                typed.clone().check_updates(self);
+            }
+        }
+    }
+
+    /// Returns IDs of assets that were replaced with updated version
+    /// since last call.
+    pub fn take_reloaded(&self) -> Vec<AssetId> {
+        let mut reloaded = Vec::new();
+        self.inner
+            .reloaded
+            .drain_locking(|ids| reloaded.extend(ids));
+        reloaded
+    }
+
     pub fn build_assets(&self, builder: &mut AssetBuilder) {
         self.inner.to_build.drain_locking(|to_build| {
             for (type_id, id) in to_build {
                 if let Some(typed) = self.typed_get(type_id, id) {
-                    typed.build_asset(id, builder);
+                    if typed.build_asset(id, builder) {
+                        self.inner.reloaded.push(id);
+                    }
                 }
             }
         });
@@ -175,7 +232,11 @@ impl Assets {
 }
 
 trait AnyTypedAssets: Any + Send + Sync {
-    fn build_asset(&self, id: AssetId, builder: &mut AssetBuilder);
+    /// Builds loaded asset.
+    /// Returns `true` if ready asset was replaced with updated version.
+    fn build_asset(&self, id: AssetId, builder: &mut AssetBuilder) -> bool;
+    fn check_updates(self: Arc<Self>, assets: &Assets);
+    fn unload(&self, id: AssetId);
     fn cancel(&self);
 }
 
@@ -196,16 +257,28 @@ impl<A> AnyTypedAssets for TypedAssets<A>
 where
     A: Asset,
 {
-    fn build_asset(&self, id: AssetId, builder: &mut AssetBuilder) {
+    fn build_asset(&self, id: AssetId, builder: &mut AssetBuilder) -> bool {
         let mut cache = self.cache.lock();
 
         match cache.remove(&id) {
-            Some(AssetState::Loaded { asset, wakers }) => {
+            Some(AssetState::Loaded {
+                asset,
+                version,
+                wakers,
+            }) => {
                 let result = A::build(asset, builder);
 
                 match result {
                     Ok(asset) => {
-                        cache.insert(id, AssetState::Ready { asset });
+                        cache.insert(
+                            id,
+                            AssetState::Ready {
+                                asset,
+                                version,
+                                update: None,
+                                checking: false,
+                            },
+                        );
                     }
                     Err(error) => {
                         cache.insert(id, AssetState::Error { error });
@@ -215,9 +288,124 @@ where
                 for waker in wakers {
                     waker.wake();
                 }
+                false
+            }
+            Some(AssetState::Ready {
+                asset,
+                version,
+                update: Some((loaded, new_version)),
+                checking,
+            }) => match A::build(loaded, builder) {
+                Ok(asset) => {
+                    cache.insert(
+                        id,
+                        AssetState::Ready {
+                            asset,
+                            version: new_version,
+                            update: None,
+                            checking,
+                        },
+                    );
+                    true
+                }
+                Err(error) => {
+                    // Keep old version of the asset.
+                    tracing::error!("Failed to rebuild updated asset {id}: {error}");
+                    cache.insert(
+                        id,
+                        AssetState::Ready {
+                            asset,
+                            version,
+                            update: None,
+                            checking,
+                        },
+                    );
+                    false
+                }
+            },
+            Some(state) => {
+                // Nothing to build in other states.
+                cache.insert(id, state);
+                false
+            }
+            None => false, // Ignore removed assets.
+        }
+    }
+
+    fn check_updates(self: Arc<Self>, assets: &Assets) {
+        let mut to_check = Vec::new();
+
+        for (id, state) in self.cache.lock().iter_mut() {
+            if let AssetState::Ready {
+                version,
+                update,
+                checking,
+                ..
+            } = state
+            {
+                // Skip assets with pending update.
+                if !*checking && update.is_none() {
+                    *checking = true;
+                    to_check.push((*id, *version));
+                }
+            }
+        }
+
+        if to_check.is_empty() {
+            return;
+        }
+
+        let assets = assets.clone();
+
+        tokio::spawn(async move {
+            for (id, version) in to_check {
+                let result = update_from_any(&assets.inner.loaders[..], id, version).await;
+
+                let loaded = match result {
+                    Ok(None) => None,
+                    Ok(Some(data)) => match A::load(data.bytes, &assets).await {
+                        Ok(loaded) => Some((loaded, data.version)),
+                        Err(error) => {
+                            tracing::error!("Failed to load updated asset {id}: {error}");
+                            None
+                        }
+                    },
+                    Err(error) => {
+                        tracing::error!("Failed to check asset {id} for updates: {error}");
+                        None
+                    }
+                };
+
+                let mut cache = self.cache.lock();
+                let Some(AssetState::Ready {
+                    update, checking, ..
+                }) = cache.get_mut(&id)
+                else {
+                    // Removed or reloaded from scratch.
+                    continue;
+                };
+
+                *checking = false;
+
+                if let Some(loaded) = loaded {
+                    *update = Some(loaded);
+                    drop(cache);
+                    assets.inner.to_build.push((type_id::<A>(), id));
+                }
+            }
+        });
+    }
+
+    fn unload(&self, id: AssetId) {
+        let mut cache = self.cache.lock();
+        match cache.remove(&id) {
+            Some(AssetState::Loading { wakers }) | Some(AssetState::Loaded { wakers, .. }) => {
+                // Loading task will find no entry and drop the result.
+                for waker in wakers {
+                    waker.wake();
+                }
             }
-            Some(_) => {} // Ignore other states.
-            None => {}    // Ignore removed assets.
+            Some(_) | None => {}
         }
     }
 
@@ -243,6 +431,7 @@ enum AssetState<A: Asset> {
     /// Asset will be ready after next initialization phase.
     Loaded {
         asset: A::Loaded,
+        version: u64,
         wakers: Vec<Waker>,
     },
 
@@ -250,7 +439,16 @@ enum AssetState<A: Asset> {
     Error { error: Error },
 
     /// Asset is ready.
-    Ready { asset: A },
+    Ready {
+        asset: A,
+        version: u64,
+
+        /// Updated asset that will replace this one on next build step.
+        update: Option<(A::Loaded, u64)>,
+
+        /// Whether loaders are being asked for updated data.
+        checking: bool,
+    },
 }
 
 struct TypedAssets<A: Asset> {
@@ -283,7 +481,7 @@ where
                     }
                     return Poll::Pending;
                 }
-                AssetState::Ready { asset } => {
+                AssetState::Ready { asset, .. } => {
                     return Poll::Ready(Ok(asset.clone()));
                 }
                 AssetState::Error { error } => {
@@ -328,6 +526,7 @@ where
                         }
                     };
 
+                    let version = data.version;
                     let result = A::load(data.bytes, &assets).await;
 
                     let mut cache = me.cache.lock();
@@ -341,6 +540,7 @@ where
                             Ok(asset) => {
                                 *state = AssetState::Loaded {
                                     asset,
+                                    version,
                                     wakers: std::mem::take(wakers),
                                 };
 
@@ -391,3 +591,17 @@ async fn load_from_any(loaders: &[Box<dyn Loader>], id: AssetId) -> Result<Asset
 
     Err(not_found_error.unwrap_or_else(|| Error::new(NotFound)))
 }
+
+async fn update_from_any(
+    loaders: &[Box<dyn Loader>],
+    id: AssetId,
+    version: u64,
+) -> Result<Option<AssetData>, Error> {
+    for loader in loaders {
+        if let Some(data) = loader.update(id, version).await? {
+            return Ok(Some(data));
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,147 @@
+use std::{future::Future, sync::Arc};
+
+use arcana_names::{ident, Ident};
+use hashbrown::HashMap;
+
+use super::{
+    asset::Asset, assets::Assets, build::AssetBuilder, error::Error, sprite_sheet::SpriteRect,
+};
+
+/// Single atlas image.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct AtlasPageData {
+    pub width: u32,
+    pub height: u32,
+
+    /// Pixels in RGBA8 format, row by row.
+    pub pixels: Vec<u8>,
+}
+
+/// Location of a packed image.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AtlasRegion {
+    /// Name of the packed image.
+    pub name: String,
+
+    /// Index of the page that contains the image.
+    pub page: u32,
+
+    /// Region of the page in pixels.
+    pub rect: SpriteRect,
+
+    /// Region of the page in texture coordinates as `[u0, v0, u1, v1]`.
+    pub uv: [f32; 4],
+}
+
+/// Atlas data as stored by importers.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct AtlasData {
+    pub pages: Vec<AtlasPageData>,
+    pub regions: Vec<AtlasRegion>,
+}
+
+impl AtlasData {
+    /// Encodes atlas into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Atlas serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        bincode::deserialize(data).map_err(Error::new)
+    }
+}
+
+/// Set of images packed into one or more pages.
+///
+/// Sprites that share a page can be drawn in a single batch.
+#[derive(Clone)]
+pub struct Atlas {
+    pub pages: Arc<[mev::Image]>,
+    pub regions: Arc<[AtlasRegion]>,
+    by_name: Arc<HashMap<String, usize>>,
+}
+
+impl Atlas {
+    /// Finds region of the image by name.
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        let index = *self.by_name.get(name)?;
+        Some(&self.regions[index])
+    }
+
+    /// Returns page image that contains the region.
+    pub fn page(&self, region: &AtlasRegion) -> &mev::Image {
+        &self.pages[region.page as usize]
+    }
+}
+
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+enum AtlasError {
+    #[error("Page {0} pixel data does not match its size")]
+    InvalidPixels(usize),
+    #[error("Region {0} is outside of its page")]
+    RegionOutOfBounds(usize),
+}
+
+impl Asset for Atlas {
+    type Loaded = AtlasData;
+
+    fn target() -> Ident {
+        ident!(atlas)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<AtlasData, Error>> + Send {
+        futures::future::ready(load_atlas(&data))
+    }
+
+    fn build(loaded: AtlasData, builder: &mut AssetBuilder) -> Result<Self, Error> {
+        let pages = loaded
+            .pages
+            .iter()
+            .map(|page| builder.new_image_rgba8(page.width, page.height, &page.pixels, "atlas"))
+            .collect::<Result<_, Error>>()?;
+
+        let by_name = loaded
+            .regions
+            .iter()
+            .enumerate()
+            .map(|(index, region)| (region.name.clone(), index))
+            .collect();
+
+        Ok(Atlas {
+            pages,
+            regions: loaded.regions.into(),
+            by_name: Arc::new(by_name),
+        })
+    }
+}
+
+fn load_atlas(data: &[u8]) -> Result<AtlasData, Error> {
+    let atlas = AtlasData::decode(data)?;
+
+    for (index, page) in atlas.pages.iter().enumerate() {
+        if page.pixels.len() != 4 * page.width as usize * page.height as usize {
+            return Err(Error::new(AtlasError::InvalidPixels(index)));
+        }
+    }
+
+    for (index, region) in atlas.regions.iter().enumerate() {
+        let Some(page) = atlas.pages.get(region.page as usize) else {
+            return Err(Error::new(AtlasError::RegionOutOfBounds(index)));
+        };
+
+        let rect = region.rect;
+        if rect.x < 0
+            || rect.y < 0
+            || rect.x as u32 + rect.width > page.width
+            || rect.y as u32 + rect.height > page.height
+        {
+            return Err(Error::new(AtlasError::RegionOutOfBounds(index)));
+        }
+    }
+
+    Ok(atlas)
+}
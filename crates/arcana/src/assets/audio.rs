@@ -0,0 +1,113 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use arcana_names::{ident, Ident};
+
+use super::{asset::Asset, assets::Assets, build::AssetBuilder, error::Error};
+
+/// Audio data as stored by importers.
+///
+/// Samples are 32-bit floats interleaved by channel.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct AudioData {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<f32>,
+}
+
+impl AudioData {
+    /// Encodes audio into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Audio serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        bincode::deserialize(data).map_err(Error::new)
+    }
+}
+
+/// Decoded sound ready to be played.
+#[derive(Clone, Debug)]
+pub struct AudioClip {
+    sample_rate: u32,
+    channels: u16,
+    samples: Arc<[f32]>,
+}
+
+impl AudioClip {
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Samples interleaved by channel.
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Number of sample frames, each containing one sample per channel.
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels as usize
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frames() as f64 / self.sample_rate as f64)
+    }
+}
+
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+enum AudioError {
+    #[error("Audio must have at least one channel")]
+    NoChannels,
+    #[error("Sample rate must not be zero")]
+    ZeroSampleRate,
+    #[error("Sample count {samples} is not a multiple of channel count {channels}")]
+    PartialFrame { samples: usize, channels: u16 },
+}
+
+impl Asset for AudioClip {
+    type Loaded = AudioClip;
+
+    fn target() -> Ident {
+        ident!(audio)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<AudioClip, Error>> + Send {
+        futures::future::ready(load_audio(&data))
+    }
+
+    fn build(loaded: AudioClip, _builder: &mut AssetBuilder) -> Result<Self, Error> {
+        Ok(loaded)
+    }
+}
+
+fn load_audio(data: &[u8]) -> Result<AudioClip, Error> {
+    let audio = AudioData::decode(data)?;
+
+    if audio.channels == 0 {
+        return Err(Error::new(AudioError::NoChannels));
+    }
+
+    if audio.sample_rate == 0 {
+        return Err(Error::new(AudioError::ZeroSampleRate));
+    }
+
+    if audio.samples.len() % audio.channels as usize != 0 {
+        return Err(Error::new(AudioError::PartialFrame {
+            samples: audio.samples.len(),
+            channels: audio.channels,
+        }));
+    }
+
+    Ok(AudioClip {
+        sample_rate: audio.sample_rate,
+        channels: audio.channels,
+        samples: audio.samples.into(),
+    })
+}
@@ -1,4 +1,4 @@
-use super::assets::Assets;
+use super::{assets::Assets, error::Error};
 
 pub struct AssetBuilder {
     device: mev::Device,
@@ -15,6 +15,61 @@ impl AssetBuilder {
         self.needs_flush = true;
         &mut self.encoder
     }
+
+    /// Creates sampled image and fills it with RGBA8 pixels.
+    pub fn new_image_rgba8(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        name: &str,
+    ) -> Result<mev::Image, Error> {
+        let extent = mev::Extent2::new(width, height);
+
+        let image = self
+            .device
+            .new_image(mev::ImageDesc {
+                extent: extent.into(),
+                format: mev::PixelFormat::Rgba8Unorm,
+                usage: mev::ImageUsage::SAMPLED | mev::ImageUsage::TRANSFER_DST,
+                layers: 1,
+                levels: 1,
+                name,
+            })
+            .map_err(Error::new)?;
+
+        let scratch = self
+            .device
+            .new_buffer_init(mev::BufferInitDesc {
+                data: pixels,
+                usage: mev::BufferUsage::TRANSFER_SRC,
+                memory: mev::Memory::Upload,
+                name: "scratch",
+            })
+            .map_err(Error::new)?;
+
+        let mut encoder = self.encoder().copy();
+
+        encoder.init_image(
+            mev::PipelineStages::empty(),
+            mev::PipelineStages::all(),
+            &image,
+        );
+
+        encoder.copy_buffer_to_image(
+            &scratch,
+            0,
+            4 * width as usize,
+            4 * width as usize * height as usize,
+            &image,
+            mev::Offset3::ZERO,
+            extent.to_3d(),
+            0..1,
+            0,
+        );
+
+        Ok(image)
+    }
 }
 
 #[doc(hidden)]
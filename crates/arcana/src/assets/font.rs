@@ -0,0 +1,236 @@
+use std::{future::Future, sync::Arc};
+
+use arcana_names::{ident, Ident};
+use hashbrown::HashMap;
+
+use super::{
+    asset::Asset, assets::Assets, atlas::AtlasPageData, build::AssetBuilder, error::Error,
+    sprite_sheet::SpriteRect,
+};
+
+/// How glyphs are stored in the atlas.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum GlyphAtlasKind {
+    /// Glyph coverage rasterized at fixed size.
+    Bitmap,
+
+    /// Signed distance field.
+    /// Value of 0.5 lies on the glyph outline.
+    /// `spread` is distance in pixels that maps to full value range.
+    Sdf { spread: f32 },
+}
+
+/// Vertical metrics of the font at rasterized size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FontMetrics {
+    /// Size in pixels glyphs were rasterized at.
+    pub size: f32,
+
+    /// Distance from baseline to the top of tallest glyphs.
+    pub ascent: f32,
+
+    /// Distance from baseline to the bottom of lowest glyphs.
+    /// Typically negative.
+    pub descent: f32,
+
+    /// Gap between lines in addition to ascent and descent.
+    pub line_gap: f32,
+}
+
+impl FontMetrics {
+    /// Distance between baselines of consecutive lines.
+    pub fn line_height(&self) -> f32 {
+        self.ascent - self.descent + self.line_gap
+    }
+}
+
+/// Single glyph in the atlas.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Glyph {
+    pub ch: char,
+
+    /// Horizontal advance in pixels.
+    pub advance: f32,
+
+    /// Offset of the glyph image from pen position to its bottom-left corner.
+    /// Y axis points up.
+    pub offset: [f32; 2],
+
+    /// Region of the atlas in pixels.
+    /// Empty for glyphs without image, like space.
+    pub rect: SpriteRect,
+
+    /// Region of the atlas in texture coordinates as `[u0, v0, u1, v1]`.
+    pub uv: [f32; 4],
+}
+
+/// Horizontal kerning adjustment for a pair of characters.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct KerningPair {
+    pub left: char,
+    pub right: char,
+    pub offset: f32,
+}
+
+/// Font data as stored by importers.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct FontData {
+    /// Font family name.
+    pub name: String,
+
+    /// Original font file.
+    /// Kept for consumers that rasterize glyphs on their own, like egui.
+    pub source: Vec<u8>,
+
+    pub kind: GlyphAtlasKind,
+    pub metrics: FontMetrics,
+
+    /// Glyph atlas image.
+    /// Glyph value is stored in alpha channel, color channels are white.
+    pub atlas: AtlasPageData,
+
+    pub glyphs: Vec<Glyph>,
+    pub kerning: Vec<KerningPair>,
+}
+
+impl FontData {
+    /// Encodes font into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Font serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        bincode::deserialize(data).map_err(Error::new)
+    }
+}
+
+struct FontInner {
+    name: String,
+    source: Arc<[u8]>,
+    kind: GlyphAtlasKind,
+    metrics: FontMetrics,
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), f32>,
+}
+
+/// Font with pre-rasterized glyph atlas.
+///
+/// Text renderers use atlas image with glyph metrics.
+/// Original font file is available to register font in egui.
+#[derive(Clone)]
+pub struct Font {
+    pub image: mev::Image,
+    inner: Arc<FontInner>,
+}
+
+impl Font {
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    pub fn kind(&self) -> GlyphAtlasKind {
+        self.inner.kind
+    }
+
+    pub fn metrics(&self) -> &FontMetrics {
+        &self.inner.metrics
+    }
+
+    pub fn glyph(&self, ch: char) -> Option<&Glyph> {
+        self.inner.glyphs.get(&ch)
+    }
+
+    /// Returns kerning adjustment to add to advance of `left` when followed by `right`.
+    pub fn kerning(&self, left: char, right: char) -> f32 {
+        self.inner
+            .kerning
+            .get(&(left, right))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Original font file.
+    pub fn source(&self) -> &[u8] {
+        &self.inner.source
+    }
+
+    /// Returns font data suitable for egui font definitions.
+    pub fn egui_font_data(&self) -> egui::FontData {
+        egui::FontData::from_owned(self.inner.source.to_vec())
+    }
+}
+
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+enum FontError {
+    #[error("Atlas pixel data does not match its size")]
+    InvalidPixels,
+    #[error("Glyph {0:?} is outside of the atlas")]
+    GlyphOutOfBounds(char),
+}
+
+impl Asset for Font {
+    type Loaded = FontData;
+
+    fn target() -> Ident {
+        ident!(font)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<FontData, Error>> + Send {
+        futures::future::ready(load_font(&data))
+    }
+
+    fn build(loaded: FontData, builder: &mut AssetBuilder) -> Result<Self, Error> {
+        let atlas = &loaded.atlas;
+        let image = builder.new_image_rgba8(atlas.width, atlas.height, &atlas.pixels, "font")?;
+
+        let glyphs = loaded
+            .glyphs
+            .into_iter()
+            .map(|glyph| (glyph.ch, glyph))
+            .collect();
+
+        let kerning = loaded
+            .kerning
+            .into_iter()
+            .map(|pair| ((pair.left, pair.right), pair.offset))
+            .collect();
+
+        Ok(Font {
+            image,
+            inner: Arc::new(FontInner {
+                name: loaded.name,
+                source: loaded.source.into(),
+                kind: loaded.kind,
+                metrics: loaded.metrics,
+                glyphs,
+                kerning,
+            }),
+        })
+    }
+}
+
+fn load_font(data: &[u8]) -> Result<FontData, Error> {
+    let font = FontData::decode(data)?;
+
+    let atlas = &font.atlas;
+    if atlas.pixels.len() != 4 * atlas.width as usize * atlas.height as usize {
+        return Err(Error::new(FontError::InvalidPixels));
+    }
+
+    for glyph in &font.glyphs {
+        let rect = glyph.rect;
+        if rect.x < 0
+            || rect.y < 0
+            || rect.x as u32 + rect.width > atlas.width
+            || rect.y as u32 + rect.height > atlas.height
+        {
+            return Err(Error::new(FontError::GlyphOutOfBounds(glyph.ch)));
+        }
+    }
+
+    Ok(font)
+}
@@ -0,0 +1,171 @@
+use std::{any::Any, sync::Arc, task::Poll};
+
+use edict::component::Component;
+use parking_lot::Mutex;
+
+use super::{asset::Asset, assets::Assets, error::Error, id::AssetId, watch::ReloadedAssets};
+
+/// Load state of the asset referenced by [`Handle`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LoadState {
+    /// Asset ID is not yet known.
+    Queued,
+
+    /// Asset is being loaded and built.
+    Loading,
+
+    /// Asset is ready to use.
+    Loaded,
+
+    /// Asset failed to load.
+    Failed,
+}
+
+pub(super) enum HandleState<A> {
+    Queued,
+    Loading { id: AssetId },
+    Loaded { id: AssetId, asset: A },
+    Failed { id: Option<AssetId>, error: Error },
+}
+
+pub(super) struct HandleShared<A> {
+    pub state: Mutex<HandleState<A>>,
+}
+
+/// Typed reference to an asset.
+///
+/// Handles are created by [`AssetServer`](super::AssetServer)
+/// and keep asset loaded while at least one of them is alive.
+/// Once last handle is dropped, asset is unloaded.
+pub struct Handle<A> {
+    shared: Arc<HandleShared<A>>,
+}
+
+impl<A> Clone for Handle<A> {
+    fn clone(&self) -> Self {
+        Handle {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<A> Handle<A>
+where
+    A: Asset,
+{
+    pub(super) fn new(state: HandleState<A>) -> Self {
+        Handle {
+            shared: Arc::new(HandleShared {
+                state: Mutex::new(state),
+            }),
+        }
+    }
+
+    pub(super) fn from_shared(shared: Arc<HandleShared<A>>) -> Self {
+        Handle { shared }
+    }
+
+    pub(super) fn shared(&self) -> &Arc<HandleShared<A>> {
+        &self.shared
+    }
+
+    pub fn state(&self) -> LoadState {
+        match *self.shared.state.lock() {
+            HandleState::Queued => LoadState::Queued,
+            HandleState::Loading { .. } => LoadState::Loading,
+            HandleState::Loaded { .. } => LoadState::Loaded,
+            HandleState::Failed { .. } => LoadState::Failed,
+        }
+    }
+
+    /// Returns asset ID if it is already known.
+    pub fn id(&self) -> Option<AssetId> {
+        self.shared.asset_id()
+    }
+
+    /// Returns asset if it is loaded.
+    pub fn get(&self) -> Option<A> {
+        match *self.shared.state.lock() {
+            HandleState::Loaded { ref asset, .. } => Some(asset.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns error if asset failed to load.
+    pub fn error(&self) -> Option<Error> {
+        match *self.shared.state.lock() {
+            HandleState::Failed { ref error, .. } => Some(error.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.state() == LoadState::Loaded
+    }
+
+    /// Checks if two handles refer to the same load request.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared)
+    }
+}
+
+impl<A> Component for Handle<A>
+where
+    A: Asset,
+{
+    fn name() -> &'static str {
+        "Handle"
+    }
+}
+
+/// Type-erased handle state kept by the server.
+pub(super) trait AnyHandle: Send + Sync {
+    fn as_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+
+    fn asset_id(&self) -> Option<AssetId>;
+
+    /// Moves handle forward when asset is ready
+    /// and refreshes it when asset is reloaded.
+    fn update(&self, assets: &Assets, reloaded: &ReloadedAssets);
+}
+
+impl<A> AnyHandle for HandleShared<A>
+where
+    A: Asset,
+{
+    fn as_any(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> {
+        self
+    }
+
+    fn asset_id(&self) -> Option<AssetId> {
+        match *self.state.lock() {
+            HandleState::Queued => None,
+            HandleState::Loading { id } => Some(id),
+            HandleState::Loaded { id, .. } => Some(id),
+            HandleState::Failed { id, .. } => id,
+        }
+    }
+
+    fn update(&self, assets: &Assets, reloaded: &ReloadedAssets) {
+        let mut state = self.state.lock();
+
+        match *state {
+            HandleState::Loading { id } => match assets.get::<A>(id) {
+                Poll::Pending => {}
+                Poll::Ready(Ok(asset)) => *state = HandleState::Loaded { id, asset },
+                Poll::Ready(Err(error)) => {
+                    *state = HandleState::Failed {
+                        id: Some(id),
+                        error,
+                    }
+                }
+            },
+            HandleState::Loaded { id, ref mut asset } if reloaded.contains(id) => {
+                if let Poll::Ready(Ok(new_asset)) = assets.get::<A>(id) {
+                    *asset = new_asset;
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -88,6 +88,14 @@ pub trait Importer: Send + Sync {
     /// Returns target format importer produces.
     fn target(&self) -> Ident;
 
+    /// Returns version of the importer.
+    ///
+    /// Bump it whenever output of the importer changes.
+    /// Assets imported by older version are re-imported.
+    fn version(&self) -> u32 {
+        0
+    }
+
     /// Returns configuration value for this importer.
     fn config(&self) -> Box<dyn ImportConfig> {
         Box::new(EmptyConfig)
@@ -1,3 +1,4 @@
+use arcana_names::Ident;
 use futures::future::BoxFuture;
 
 use super::{error::Error, id::AssetId};
@@ -26,4 +27,15 @@ pub trait Loader: Send + Sync + 'static {
         id: AssetId,
         version: u64,
     ) -> BoxFuture<'a, Result<Option<AssetData>, Error>>;
+
+    /// Find ID of the asset imported from the source as specified target.
+    /// Returns `Ok(None)` if this loader knows nothing about the source.
+    fn find<'a>(
+        &'a self,
+        source: &'a str,
+        target: Ident,
+    ) -> BoxFuture<'a, Result<Option<AssetId>, Error>> {
+        let _ = (source, target);
+        Box::pin(futures::future::ready(Ok(None)))
+    }
 }
@@ -0,0 +1,113 @@
+use std::future::Future;
+
+use arcana_names::{ident, Ident};
+
+use super::{asset::Asset, assets::Assets, build::AssetBuilder, error::Error, id::AssetId};
+
+/// How alpha channel of the base color is interpreted.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum AlphaMode {
+    /// Alpha is ignored.
+    #[default]
+    Opaque,
+
+    /// Fragments with alpha below cutoff are discarded.
+    Mask,
+
+    /// Alpha is used for blending.
+    Blend,
+}
+
+/// Physically based material.
+///
+/// Mirrors metallic-roughness model of glTF 2.0.
+/// Textures are referenced by asset IDs and are loaded separately.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Material {
+    pub base_color: [f32; 4],
+    pub base_color_texture: Option<AssetId>,
+
+    pub metallic: f32,
+    pub roughness: f32,
+
+    /// Metalness is sampled from blue channel and roughness from green channel.
+    pub metallic_roughness_texture: Option<AssetId>,
+
+    pub normal_texture: Option<AssetId>,
+    pub occlusion_texture: Option<AssetId>,
+
+    pub emissive: [f32; 3],
+    pub emissive_texture: Option<AssetId>,
+
+    pub alpha_mode: AlphaMode,
+
+    /// Used only with [`AlphaMode::Mask`].
+    pub alpha_cutoff: f32,
+
+    pub double_sided: bool,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Material {
+            base_color: [1.0; 4],
+            base_color_texture: None,
+            metallic: 1.0,
+            roughness: 1.0,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive: [0.0; 3],
+            emissive_texture: None,
+            alpha_mode: AlphaMode::Opaque,
+            alpha_cutoff: 0.5,
+            double_sided: false,
+        }
+    }
+}
+
+impl Material {
+    /// Encodes material into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Material serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        bincode::deserialize(data).map_err(Error::new)
+    }
+
+    /// Iterates over all textures referenced by the material.
+    pub fn textures(&self) -> impl Iterator<Item = AssetId> {
+        [
+            self.base_color_texture,
+            self.metallic_roughness_texture,
+            self.normal_texture,
+            self.occlusion_texture,
+            self.emissive_texture,
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+impl Asset for Material {
+    type Loaded = Material;
+
+    fn target() -> Ident {
+        ident!(material)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<Material, Error>> + Send {
+        futures::future::ready(Material::decode(&data))
+    }
+
+    fn build(loaded: Material, _builder: &mut AssetBuilder) -> Result<Self, Error> {
+        Ok(loaded)
+    }
+}
@@ -0,0 +1,226 @@
+use std::{future::Future, sync::Arc};
+
+use arcana_names::{ident, Ident};
+use edict::component::Component;
+
+use super::{asset::Asset, assets::Assets, build::AssetBuilder, error::Error, material::Material};
+
+/// Vertex layout of mesh assets.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    bytemuck::Pod,
+    bytemuck::Zeroable,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[repr(C)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+/// Primitive data as stored by importers.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct PrimitiveData {
+    pub vertices: Vec<Vertex>,
+
+    /// Triangle list indices.
+    pub indices: Vec<u32>,
+
+    pub material: Option<Material>,
+}
+
+/// Mesh data as stored by importers.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct MeshData {
+    pub primitives: Vec<PrimitiveData>,
+}
+
+impl MeshData {
+    /// Encodes mesh into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Mesh serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        bincode::deserialize(data).map_err(Error::new)
+    }
+}
+
+/// Sphere that encloses mesh vertices in mesh space.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: na::Point3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    /// Returns sphere around the vertices.
+    /// Not minimal, centered at the middle of vertices' bounding box.
+    pub fn from_vertices(vertices: &[Vertex]) -> Self {
+        if vertices.is_empty() {
+            return BoundingSphere {
+                center: na::Point3::origin(),
+                radius: 0.0,
+            };
+        }
+
+        let mut min = na::Point3::from(vertices[0].position);
+        let mut max = min;
+        for v in vertices {
+            let p = na::Point3::from(v.position);
+            min = min.inf(&p);
+            max = max.sup(&p);
+        }
+
+        let center = na::center(&min, &max);
+        let radius = vertices
+            .iter()
+            .map(|v| na::distance(&center, &na::Point3::from(v.position)))
+            .fold(0.0, f32::max);
+
+        BoundingSphere { center, radius }
+    }
+
+    /// Returns sphere that encloses both spheres.
+    pub fn merge(&self, other: &BoundingSphere) -> Self {
+        let offset = other.center - self.center;
+        let distance = offset.norm();
+
+        if distance + other.radius <= self.radius {
+            return *self;
+        }
+        if distance + self.radius <= other.radius {
+            return *other;
+        }
+
+        let radius = (distance + self.radius + other.radius) * 0.5;
+        let center = self.center + offset * ((radius - self.radius) / distance);
+        BoundingSphere { center, radius }
+    }
+}
+
+/// Part of the mesh drawn with single material.
+#[derive(Clone)]
+pub struct Primitive {
+    /// Buffer with [`Vertex`] elements.
+    pub vertices: mev::Buffer,
+
+    /// Buffer with `u32` indices.
+    pub indices: mev::Buffer,
+
+    /// Number of indices.
+    pub count: u32,
+
+    pub material: Option<Material>,
+
+    pub bounds: BoundingSphere,
+}
+
+/// Mesh asset with data uploaded to GPU.
+#[derive(Clone)]
+pub struct Mesh {
+    pub primitives: Arc<[Primitive]>,
+
+    /// Bounds of all primitives.
+    pub bounds: BoundingSphere,
+}
+
+impl Component for Mesh {
+    fn name() -> &'static str {
+        "Mesh"
+    }
+}
+
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+enum MeshError {
+    #[error("Index {index} is out of bounds of {count} vertices")]
+    IndexOutOfBounds { index: u32, count: usize },
+    #[error("Index count {0} is not a multiple of 3")]
+    NotTriangles(usize),
+}
+
+impl Asset for Mesh {
+    type Loaded = MeshData;
+
+    fn target() -> Ident {
+        ident!(mesh)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<MeshData, Error>> + Send {
+        futures::future::ready(load_mesh(&data))
+    }
+
+    fn build(loaded: MeshData, builder: &mut AssetBuilder) -> Result<Self, Error> {
+        let primitives = loaded
+            .primitives
+            .into_iter()
+            .map(|primitive| {
+                let vertices = builder
+                    .device()
+                    .new_buffer_init(mev::BufferInitDesc {
+                        data: bytemuck::cast_slice(&primitive.vertices),
+                        usage: mev::BufferUsage::VERTEX,
+                        memory: mev::Memory::Device,
+                        name: "mesh-vertices",
+                    })
+                    .map_err(Error::new)?;
+
+                let indices = builder
+                    .device()
+                    .new_buffer_init(mev::BufferInitDesc {
+                        data: bytemuck::cast_slice(&primitive.indices),
+                        usage: mev::BufferUsage::INDEX,
+                        memory: mev::Memory::Device,
+                        name: "mesh-indices",
+                    })
+                    .map_err(Error::new)?;
+
+                Ok(Primitive {
+                    vertices,
+                    indices,
+                    count: primitive.indices.len() as u32,
+                    material: primitive.material,
+                    bounds: BoundingSphere::from_vertices(&primitive.vertices),
+                })
+            })
+            .collect::<Result<Arc<[Primitive]>, Error>>()?;
+
+        let bounds = primitives
+            .iter()
+            .map(|primitive| primitive.bounds)
+            .reduce(|a, b| a.merge(&b))
+            .unwrap_or(BoundingSphere {
+                center: na::Point3::origin(),
+                radius: 0.0,
+            });
+
+        Ok(Mesh { primitives, bounds })
+    }
+}
+
+fn load_mesh(data: &[u8]) -> Result<MeshData, Error> {
+    let mesh = MeshData::decode(data)?;
+
+    for primitive in &mesh.primitives {
+        if primitive.indices.len() % 3 != 0 {
+            return Err(Error::new(MeshError::NotTriangles(primitive.indices.len())));
+        }
+
+        let count = primitive.vertices.len();
+        if let Some(&index) = primitive.indices.iter().find(|&&i| i as usize >= count) {
+            return Err(Error::new(MeshError::IndexOutOfBounds { index, count }));
+        }
+    }
+
+    Ok(mesh)
+}
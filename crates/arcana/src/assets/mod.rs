@@ -12,6 +12,7 @@
 //!
 //! - Load step happens at runtime when asset is fetched and not found in cache.
 //!   In development, if source change is detected, asset is re-processed before loading.
+//!   [`AssetWatcher`] polls loaders for such changes and reloaded assets are replaced in place.
 //!
 //! - Build step happens after right after load step.
 //!   It is responsible for converting raw asset data into an asset object.
@@ -30,19 +31,45 @@
 //!
 //! Complex assets may also implement `Unfold` trait for unfolding single object into multiple components and other entities.
 
+pub mod archive;
 mod asset;
 mod assets;
+pub mod atlas;
+pub mod audio;
 mod build;
 mod error;
+pub mod font;
+mod handle;
 mod id;
 pub mod import;
 mod loader;
+pub mod material;
+pub mod mesh;
+pub mod params;
+pub mod script;
+mod server;
+pub mod sprite_sheet;
+pub mod tile_map;
+mod watch;
 
 pub use self::{
+    archive::Archive,
     asset::Asset,
     assets::Assets,
+    atlas::Atlas,
+    audio::AudioClip,
     build::{AssetBuildContext, AssetBuilder},
     error::{Error, NotFound},
+    font::Font,
+    handle::{Handle, LoadState},
     id::AssetId,
     loader::{AssetData, Loader},
+    material::Material,
+    mesh::Mesh,
+    params::{ParamSet, Params},
+    script::ScriptModule,
+    server::{resolve_handles, update_asset_server, AssetServer},
+    sprite_sheet::SpriteSheet,
+    tile_map::TileMap,
+    watch::{update_reloaded_assets, AssetWatcher, ReloadedAssets},
 };
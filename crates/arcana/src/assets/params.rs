@@ -0,0 +1,102 @@
+use std::future::Future;
+
+use arcana_names::{ident, Ident, Name};
+use edict::component::Component;
+use hashbrown::HashMap;
+
+use crate::model::{Model, Value};
+
+use super::{asset::Asset, assets::Assets, build::AssetBuilder, error::Error, id::AssetId};
+
+/// Single parameter of a [`ParamSet`].
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Param {
+    pub name: Name,
+    pub model: Model,
+    pub value: Value,
+}
+
+/// Named set of parameter values.
+///
+/// Parameter sets are written in JSON and edited in the editor's parameters tool.
+/// They are bound to jobs of render graphs and to entities with [`Params`] component,
+/// so artists can tweak values without recompiling plugins.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ParamSet {
+    /// Name of the job the parameters are meant for.
+    /// Ed uses it to suggest parameters.
+    #[serde(default)]
+    pub job: Option<Name>,
+
+    #[serde(default)]
+    pub params: Vec<Param>,
+}
+
+impl ParamSet {
+    pub fn from_json(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Parameter set serialization cannot fail")
+    }
+
+    /// Encodes parameter set into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Parameter set serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        bincode::deserialize(data).map_err(Error::new)
+    }
+
+    /// Returns value of the parameter.
+    pub fn get(&self, name: Name) -> Option<&Value> {
+        self.params
+            .iter()
+            .find(|param| param.name == name)
+            .map(|param| &param.value)
+    }
+
+    /// Overrides values of parameters that are present in the set.
+    /// Parameters missing from `params` are not added.
+    pub fn apply(&self, params: &mut HashMap<Name, Value>) {
+        for param in &self.params {
+            if let Some(value) = params.get_mut(&param.name) {
+                *value = param.value.clone();
+            }
+        }
+    }
+}
+
+impl Asset for ParamSet {
+    type Loaded = ParamSet;
+
+    fn target() -> Ident {
+        ident!(param_set)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<ParamSet, Error>> + Send {
+        futures::future::ready(ParamSet::decode(&data))
+    }
+
+    fn build(loaded: ParamSet, _builder: &mut AssetBuilder) -> Result<Self, Error> {
+        Ok(loaded)
+    }
+}
+
+/// Component that binds [`ParamSet`] asset to an entity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Params {
+    pub asset: AssetId,
+}
+
+impl Component for Params {
+    fn name() -> &'static str {
+        "Params"
+    }
+}
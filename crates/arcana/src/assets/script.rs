@@ -0,0 +1,55 @@
+use std::{future::Future, sync::Arc};
+
+use arcana_names::{ident, Ident};
+
+use super::{asset::Asset, assets::Assets, build::AssetBuilder, error::Error};
+
+/// Magic number every WebAssembly binary starts with.
+const WASM_MAGIC: &[u8] = b"\0asm";
+
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+#[error("Script module is not a WebAssembly binary")]
+struct NotWasm;
+
+/// WebAssembly module with gameplay script.
+///
+/// Asset holds module binary as is,
+/// it is compiled by the plugin that runs scripts.
+#[derive(Clone, Debug)]
+pub struct ScriptModule {
+    wasm: Arc<[u8]>,
+}
+
+impl ScriptModule {
+    pub fn new(wasm: &[u8]) -> Result<Self, Error> {
+        if !wasm.starts_with(WASM_MAGIC) {
+            return Err(Error::new(NotWasm));
+        }
+
+        Ok(ScriptModule { wasm: wasm.into() })
+    }
+
+    /// Returns WebAssembly binary of the module.
+    pub fn wasm(&self) -> &[u8] {
+        &self.wasm
+    }
+}
+
+impl Asset for ScriptModule {
+    type Loaded = ScriptModule;
+
+    fn target() -> Ident {
+        ident!(script)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<ScriptModule, Error>> + Send {
+        futures::future::ready(ScriptModule::new(&data))
+    }
+
+    fn build(loaded: ScriptModule, _builder: &mut AssetBuilder) -> Result<Self, Error> {
+        Ok(loaded)
+    }
+}
@@ -0,0 +1,185 @@
+use std::{any::TypeId, sync::Arc};
+
+use edict::{component::Component, query::Entities, world::World};
+use hashbrown::HashMap;
+
+use super::{
+    asset::Asset,
+    assets::Assets,
+    handle::{AnyHandle, Handle, HandleShared, HandleState},
+    id::AssetId,
+    watch::ReloadedAssets,
+};
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum HandleKey {
+    Source(String),
+    Id(AssetId),
+}
+
+/// Loads assets on request and hands out typed [`Handle`]s.
+///
+/// Server keeps track of live handles.
+/// Asset is unloaded when all handles to it are dropped.
+pub struct AssetServer {
+    assets: Assets,
+    handles: HashMap<(TypeId, HandleKey), Arc<dyn AnyHandle>>,
+}
+
+impl AssetServer {
+    pub fn new(assets: Assets) -> Self {
+        AssetServer {
+            assets,
+            handles: HashMap::new(),
+        }
+    }
+
+    pub fn assets(&self) -> &Assets {
+        &self.assets
+    }
+
+    /// Requests asset imported from the source.
+    ///
+    /// Returned handle is queued until asset ID is found.
+    /// Requesting same source again returns handle to the same asset.
+    pub fn load<A>(&mut self, source: &str) -> Handle<A>
+    where
+        A: Asset,
+    {
+        let key = (TypeId::of::<A>(), HandleKey::Source(source.to_owned()));
+
+        if let Some(shared) = self.handles.get(&key) {
+            return typed_handle(shared);
+        }
+
+        let handle = Handle::new(HandleState::Queued);
+        let shared = handle.shared().clone();
+        let assets = self.assets.clone();
+        let source = source.to_owned();
+
+        tokio::spawn(async move {
+            let result = assets.find(&source, A::target()).await;
+
+            let mut state = shared.state.lock();
+            match result {
+                Ok(id) => *state = HandleState::Loading { id },
+                Err(error) => {
+                    tracing::error!("Failed to find asset '{source}': {error}");
+                    *state = HandleState::Failed { id: None, error };
+                }
+            }
+        });
+
+        self.handles.insert(key, handle.shared().clone());
+        handle
+    }
+
+    /// Requests asset by ID.
+    pub fn load_id<A>(&mut self, id: AssetId) -> Handle<A>
+    where
+        A: Asset,
+    {
+        let key = (TypeId::of::<A>(), HandleKey::Id(id));
+
+        if let Some(shared) = self.handles.get(&key) {
+            return typed_handle(shared);
+        }
+
+        let handle = Handle::new(HandleState::Loading { id });
+        self.handles.insert(key, handle.shared().clone());
+        handle
+    }
+
+    /// Updates states of live handles and unloads assets
+    /// that are not referenced anymore.
+    pub fn update(&mut self, reloaded: &ReloadedAssets) {
+        let mut unused = Vec::new();
+
+        self.handles.retain(|(type_id, _), shared| {
+            if Arc::strong_count(shared) == 1 {
+                // Only server holds it.
+                if let Some(id) = shared.asset_id() {
+                    unused.push((*type_id, id));
+                }
+                return false;
+            }
+
+            shared.update(&self.assets, reloaded);
+            true
+        });
+
+        for (type_id, id) in unused {
+            // Same asset may be requested by both source and ID.
+            let in_use = self
+                .handles
+                .iter()
+                .any(|((t, _), shared)| *t == type_id && shared.asset_id() == Some(id));
+
+            if !in_use {
+                self.assets.unload_any(type_id, id);
+            }
+        }
+    }
+}
+
+fn typed_handle<A>(shared: &Arc<dyn AnyHandle>) -> Handle<A>
+where
+    A: Asset,
+{
+    match shared.clone().as_any().downcast::<HandleShared<A>>() {
+        Ok(shared) => Handle::from_shared(shared),
+        Err(_) => unreachable!("Handle is keyed by asset type"),
+    }
+}
+
+/// Updates [`AssetServer`] resource if present.
+pub fn update_asset_server(world: &mut World) {
+    let world = world.local();
+
+    let Some(mut server) = world.get_resource_mut::<AssetServer>() else {
+        return;
+    };
+
+    match world.get_resource::<ReloadedAssets>() {
+        Some(reloaded) => server.update(&reloaded),
+        None => server.update(&ReloadedAssets::new()),
+    }
+}
+
+/// Inserts loaded assets as components to entities with [`Handle`]s.
+///
+/// Components are replaced when asset is reloaded.
+pub fn resolve_handles<A>(world: &mut World)
+where
+    A: Asset + Component,
+{
+    let world = world.local();
+
+    let view = world.view::<(Entities, &Handle<A>)>().without::<A>();
+    for (entity, handle) in view {
+        if let Some(asset) = handle.get() {
+            world.insert_defer(entity, asset);
+        }
+    }
+
+    let Some(reloaded) = world.get_resource::<ReloadedAssets>() else {
+        return;
+    };
+
+    if reloaded.is_empty() {
+        return;
+    }
+
+    let view = world.view::<(Entities, &Handle<A>)>().with::<A>();
+    for (entity, handle) in view {
+        let Some(id) = handle.id() else {
+            continue;
+        };
+
+        if reloaded.contains(id) {
+            if let Some(asset) = handle.get() {
+                world.insert_defer(entity, asset);
+            }
+        }
+    }
+}
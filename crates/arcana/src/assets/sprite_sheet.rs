@@ -0,0 +1,219 @@
+use std::{future::Future, sync::Arc};
+
+use arcana_names::{ident, Ident};
+use edict::component::Component;
+
+use super::{asset::Asset, assets::Assets, build::AssetBuilder, error::Error};
+
+/// Rectangle in atlas or sprite pixels.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub struct SpriteRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Single animation frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct SpriteFrame {
+    /// Region of the atlas occupied by the frame.
+    pub rect: SpriteRect,
+
+    /// Frame duration in milliseconds.
+    pub duration: u32,
+}
+
+/// Order in which frames of the tag are played.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum AnimationDirection {
+    #[default]
+    Forward,
+    Reverse,
+    PingPong,
+}
+
+/// Named range of frames.
+/// Usually represents single animation.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct SpriteTag {
+    pub name: String,
+
+    /// First frame of the tag.
+    pub from: u32,
+
+    /// Last frame of the tag, inclusive.
+    pub to: u32,
+
+    pub direction: AnimationDirection,
+}
+
+/// State of the slice starting from specific frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct SliceKey {
+    /// Frame from which this key is active.
+    pub frame: u32,
+
+    /// Slice bounds in sprite pixels.
+    pub bounds: SpriteRect,
+
+    /// Center part for 9-slice scaling, relative to bounds.
+    pub center: Option<SpriteRect>,
+
+    /// Pivot point, relative to bounds.
+    pub pivot: Option<[i32; 2]>,
+}
+
+/// Named region of the sprite.
+/// Used for hitboxes, attachment points and 9-slice scaling.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct SpriteSlice {
+    pub name: String,
+    pub keys: Vec<SliceKey>,
+}
+
+impl SpriteSlice {
+    /// Returns slice key active at specified frame.
+    pub fn key(&self, frame: u32) -> Option<&SliceKey> {
+        self.keys.iter().rev().find(|key| key.frame <= frame)
+    }
+}
+
+/// Sprite sheet data as stored by importers.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct SpriteSheetData {
+    /// Atlas width in pixels.
+    pub width: u32,
+
+    /// Atlas height in pixels.
+    pub height: u32,
+
+    /// Atlas pixels in RGBA8 format, row by row.
+    pub pixels: Vec<u8>,
+
+    pub frames: Vec<SpriteFrame>,
+    pub tags: Vec<SpriteTag>,
+    pub slices: Vec<SpriteSlice>,
+}
+
+impl SpriteSheetData {
+    /// Encodes sprite sheet into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Sprite sheet serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        bincode::deserialize(data).map_err(Error::new)
+    }
+}
+
+/// Animated sprite with frames packed into single atlas image.
+#[derive(Clone)]
+pub struct SpriteSheet {
+    pub image: mev::Image,
+    pub frames: Arc<[SpriteFrame]>,
+    pub tags: Arc<[SpriteTag]>,
+    pub slices: Arc<[SpriteSlice]>,
+}
+
+impl Component for SpriteSheet {
+    fn name() -> &'static str {
+        "SpriteSheet"
+    }
+}
+
+impl SpriteSheet {
+    pub fn tag(&self, name: &str) -> Option<&SpriteTag> {
+        self.tags.iter().find(|tag| tag.name == name)
+    }
+
+    pub fn slice(&self, name: &str) -> Option<&SpriteSlice> {
+        self.slices.iter().find(|slice| slice.name == name)
+    }
+
+    /// Returns UV rectangle of the frame as `[u0, v0, u1, v1]`.
+    pub fn frame_uv(&self, frame: u32) -> Option<[f32; 4]> {
+        let rect = self.frames.get(frame as usize)?.rect;
+        let extent = self.image.extent().expect_2d();
+        let (width, height) = (extent.width() as f32, extent.height() as f32);
+
+        Some([
+            rect.x as f32 / width,
+            rect.y as f32 / height,
+            (rect.x as f32 + rect.width as f32) / width,
+            (rect.y as f32 + rect.height as f32) / height,
+        ])
+    }
+}
+
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+enum SpriteSheetError {
+    #[error("Atlas pixel data does not match {width}x{height} size")]
+    InvalidPixels { width: u32, height: u32 },
+    #[error("Frame {0} is outside of the atlas")]
+    FrameOutOfBounds(usize),
+    #[error("Tag '{0}' refers to missing frames")]
+    TagOutOfBounds(usize),
+}
+
+impl Asset for SpriteSheet {
+    type Loaded = SpriteSheetData;
+
+    fn target() -> Ident {
+        ident!(sprite_sheet)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<SpriteSheetData, Error>> + Send {
+        futures::future::ready(load_sprite_sheet(&data))
+    }
+
+    fn build(loaded: SpriteSheetData, builder: &mut AssetBuilder) -> Result<Self, Error> {
+        let image =
+            builder.new_image_rgba8(loaded.width, loaded.height, &loaded.pixels, "sprite-sheet")?;
+
+        Ok(SpriteSheet {
+            image,
+            frames: loaded.frames.into(),
+            tags: loaded.tags.into(),
+            slices: loaded.slices.into(),
+        })
+    }
+}
+
+fn load_sprite_sheet(data: &[u8]) -> Result<SpriteSheetData, Error> {
+    let sheet = SpriteSheetData::decode(data)?;
+
+    if sheet.pixels.len() != 4 * sheet.width as usize * sheet.height as usize {
+        return Err(Error::new(SpriteSheetError::InvalidPixels {
+            width: sheet.width,
+            height: sheet.height,
+        }));
+    }
+
+    for (index, frame) in sheet.frames.iter().enumerate() {
+        let rect = frame.rect;
+        if rect.x < 0
+            || rect.y < 0
+            || rect.x as u32 + rect.width > sheet.width
+            || rect.y as u32 + rect.height > sheet.height
+        {
+            return Err(Error::new(SpriteSheetError::FrameOutOfBounds(index)));
+        }
+    }
+
+    for (index, tag) in sheet.tags.iter().enumerate() {
+        if tag.from > tag.to || tag.to as usize >= sheet.frames.len() {
+            return Err(Error::new(SpriteSheetError::TagOutOfBounds(index)));
+        }
+    }
+
+    Ok(sheet)
+}
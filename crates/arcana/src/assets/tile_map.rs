@@ -0,0 +1,197 @@
+use std::{future::Future, sync::Arc};
+
+use arcana_names::{ident, Ident};
+
+use super::{asset::Asset, assets::Assets, build::AssetBuilder, error::Error, id::AssetId};
+
+/// Image with tiles laid out in a grid.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Tileset {
+    pub name: String,
+
+    /// Tileset image.
+    /// `None` if tileset has no image or it failed to resolve.
+    pub texture: Option<AssetId>,
+
+    /// Size of a tile in pixels.
+    pub tile_size: u32,
+
+    /// Number of tiles in a row.
+    pub columns: u32,
+
+    /// Pixels between tiles.
+    pub spacing: u32,
+
+    /// Pixels around the image border.
+    pub padding: u32,
+}
+
+impl Tileset {
+    /// Returns position of the tile in the tileset image in pixels.
+    pub fn tile_origin(&self, tile: u32) -> [u32; 2] {
+        let column = tile % self.columns.max(1);
+        let row = tile / self.columns.max(1);
+        [
+            self.padding + column * (self.tile_size + self.spacing),
+            self.padding + row * (self.tile_size + self.spacing),
+        ]
+    }
+}
+
+/// Single placed tile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Tile {
+    /// Position of the tile in the level in pixels.
+    pub x: i32,
+    pub y: i32,
+
+    /// Tile index in the tileset.
+    pub tile: u32,
+
+    pub flip_x: bool,
+    pub flip_y: bool,
+}
+
+/// Layer of tiles drawn with single tileset.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TileLayer {
+    pub name: String,
+
+    /// Index of the tileset in [`TileMap::tilesets`].
+    pub tileset: Option<usize>,
+
+    pub tiles: Vec<Tile>,
+}
+
+/// Entity placed in the level editor.
+///
+/// Game code decides what to spawn for each kind.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SpawnPoint {
+    /// Entity kind as named in the level editor.
+    pub kind: String,
+
+    /// Name of the layer containing the entity.
+    pub layer: String,
+
+    /// Position of the entity pivot in the level in pixels.
+    pub x: i32,
+    pub y: i32,
+
+    pub width: u32,
+    pub height: u32,
+
+    /// Custom fields with JSON encoded values.
+    pub fields: Vec<(String, String)>,
+}
+
+impl SpawnPoint {
+    /// Returns JSON encoded value of the field.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| &**value)
+    }
+}
+
+/// Single level of the map.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TileLevel {
+    pub name: String,
+
+    /// Level position in the world in pixels.
+    pub x: i32,
+    pub y: i32,
+
+    /// Level size in pixels.
+    pub width: u32,
+    pub height: u32,
+
+    /// Tile layers from bottom to top.
+    pub layers: Vec<TileLayer>,
+
+    pub spawns: Vec<SpawnPoint>,
+}
+
+/// Tile map data as stored by importers.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct TileMapData {
+    pub tilesets: Vec<Tileset>,
+    pub levels: Vec<TileLevel>,
+}
+
+impl TileMapData {
+    /// Encodes tile map into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Tile map serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, Error> {
+        bincode::deserialize(data).map_err(Error::new)
+    }
+}
+
+/// Levels authored in external level editor.
+///
+/// Tileset textures are separate assets and are loaded on demand.
+#[derive(Clone, Debug)]
+pub struct TileMap {
+    pub tilesets: Arc<[Tileset]>,
+    pub levels: Arc<[TileLevel]>,
+}
+
+impl TileMap {
+    pub fn level(&self, name: &str) -> Option<&TileLevel> {
+        self.levels.iter().find(|level| level.name == name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+#[error("Layer '{layer}' of level {level} refers to missing tileset")]
+struct MissingTileset {
+    level: usize,
+    layer: usize,
+}
+
+impl Asset for TileMap {
+    type Loaded = TileMap;
+
+    fn target() -> Ident {
+        ident!(tile_map)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<TileMap, Error>> + Send {
+        futures::future::ready(load_tile_map(&data))
+    }
+
+    fn build(loaded: TileMap, _builder: &mut AssetBuilder) -> Result<Self, Error> {
+        Ok(loaded)
+    }
+}
+
+fn load_tile_map(data: &[u8]) -> Result<TileMap, Error> {
+    let map = TileMapData::decode(data)?;
+
+    for (level_index, level) in map.levels.iter().enumerate() {
+        for (layer_index, layer) in level.layers.iter().enumerate() {
+            if let Some(tileset) = layer.tileset {
+                if tileset >= map.tilesets.len() {
+                    return Err(Error::new(MissingTileset {
+                        level: level_index,
+                        layer: layer_index,
+                    }));
+                }
+            }
+        }
+    }
+
+    Ok(TileMap {
+        tilesets: map.tilesets.into(),
+        levels: map.levels.into(),
+    })
+}
@@ -0,0 +1,82 @@
+use edict::World;
+use gametime::{TimeSpan, TimeStamp};
+
+use super::{assets::Assets, id::AssetId};
+
+/// Periodically asks asset loaders for updated asset data.
+///
+/// Loaders backed by asset store re-import changed sources,
+/// so edits to source files show up in running game.
+pub struct AssetWatcher {
+    interval: TimeSpan,
+    next_check: Option<TimeStamp>,
+}
+
+impl AssetWatcher {
+    pub const fn new(interval: TimeSpan) -> Self {
+        AssetWatcher {
+            interval,
+            next_check: None,
+        }
+    }
+
+    pub fn interval(&self) -> TimeSpan {
+        self.interval
+    }
+
+    pub fn set_interval(&mut self, interval: TimeSpan) {
+        self.interval = interval;
+    }
+
+    /// Checks for updates if interval elapsed since last check.
+    pub fn tick(&mut self, now: TimeStamp, assets: &Assets) {
+        match self.next_check {
+            Some(next_check) if next_check > now => {}
+            _ => {
+                assets.check_updates();
+                self.next_check = Some(now + self.interval);
+            }
+        }
+    }
+}
+
+/// Resource with IDs of assets that were reloaded during last update.
+///
+/// Systems that copy data out of assets should check it
+/// and refresh their copies.
+pub struct ReloadedAssets {
+    ids: Vec<AssetId>,
+}
+
+impl ReloadedAssets {
+    pub const fn new() -> Self {
+        ReloadedAssets { ids: Vec::new() }
+    }
+
+    pub fn contains(&self, id: AssetId) -> bool {
+        self.ids.contains(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = AssetId> + '_ {
+        self.ids.iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+/// Replaces content of [`ReloadedAssets`] resource
+/// with assets reloaded since last call.
+pub fn update_reloaded_assets(world: &mut World) {
+    let Some(assets) = world.get_resource::<Assets>().map(|a| a.clone()) else {
+        return;
+    };
+
+    let ids = assets.take_reloaded();
+
+    match world.get_resource_mut::<ReloadedAssets>() {
+        Some(mut reloaded) => reloaded.ids = ids,
+        None => world.insert_resource(ReloadedAssets { ids }),
+    }
+}
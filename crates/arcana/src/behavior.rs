@@ -0,0 +1,209 @@
+//! Behavior trees.
+//!
+//! Trees are authored in the editor's behavior tree tool
+//! and saved as JSON `.behavior` sources.
+//! Ticking them is up to the `ai` plugin.
+
+use std::future::Future;
+
+use arcana_names::{ident, Ident};
+
+use crate::{
+    assets::{self, Asset, AssetBuilder, Assets},
+    events::EventId,
+    Name,
+};
+
+/// What behavior tree node does.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum BehaviorKind {
+    /// Runs children in order until one fails.
+    Sequence,
+
+    /// Runs children in order until one succeeds.
+    Selector,
+
+    /// Runs all children at once.
+    /// Succeeds when all children succeed and fails when any fails.
+    Parallel,
+
+    /// Swaps success and failure of the child.
+    Invert,
+
+    /// Succeeds when the child finishes, regardless of its result.
+    Succeed,
+
+    /// Restarts the child when it succeeds.
+    /// Fails when the child fails.
+    /// Zero count repeats forever.
+    Repeat { count: u32 },
+
+    /// Keeps running for given number of seconds, then succeeds.
+    Wait { seconds: f32 },
+
+    /// Succeeds if blackboard value under the key is set and is not `false`.
+    Check { key: String },
+
+    /// Calls task registered by game code under this name.
+    Task { name: String },
+
+    /// Emits event for the entity, triggering code graphs that handle it.
+    /// Succeeds immediately.
+    Event { id: EventId, name: Name },
+}
+
+impl BehaviorKind {
+    /// Returns maximum number of children this node can have.
+    /// `None` if unlimited.
+    pub fn max_children(&self) -> Option<usize> {
+        match self {
+            BehaviorKind::Sequence | BehaviorKind::Selector | BehaviorKind::Parallel => None,
+            BehaviorKind::Invert | BehaviorKind::Succeed | BehaviorKind::Repeat { .. } => Some(1),
+            BehaviorKind::Wait { .. }
+            | BehaviorKind::Check { .. }
+            | BehaviorKind::Task { .. }
+            | BehaviorKind::Event { .. } => Some(0),
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        match self {
+            BehaviorKind::Sequence => "Sequence",
+            BehaviorKind::Selector => "Selector",
+            BehaviorKind::Parallel => "Parallel",
+            BehaviorKind::Invert => "Invert",
+            BehaviorKind::Succeed => "Succeed",
+            BehaviorKind::Repeat { .. } => "Repeat",
+            BehaviorKind::Wait { .. } => "Wait",
+            BehaviorKind::Check { .. } => "Check",
+            BehaviorKind::Task { .. } => "Task",
+            BehaviorKind::Event { .. } => "Event",
+        }
+    }
+}
+
+/// Node of behavior tree.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BehaviorNode {
+    pub kind: BehaviorKind,
+
+    /// Indices of children nodes in the tree, in order of execution.
+    #[serde(default)]
+    pub children: Vec<usize>,
+
+    /// Position of the node in the editor.
+    #[serde(default)]
+    pub pos: [f32; 2],
+}
+
+/// Behavior tree asset.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BehaviorTree {
+    /// Index of the root node.
+    /// Tree without root does nothing.
+    #[serde(default)]
+    pub root: Option<usize>,
+
+    #[serde(default)]
+    pub nodes: Vec<BehaviorNode>,
+}
+
+/// Error found by [`BehaviorTree::validate`].
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum BehaviorTreeError {
+    #[error("Node {node} refers to missing node {child}")]
+    MissingNode { node: usize, child: usize },
+
+    #[error("Node {node} has {count} children, but at most {max} are allowed")]
+    TooManyChildren {
+        node: usize,
+        count: usize,
+        max: usize,
+    },
+
+    #[error("Node {node} is reachable more than once")]
+    SharedNode { node: usize },
+
+    #[error("Root node {root} is missing")]
+    MissingRoot { root: usize },
+}
+
+impl BehaviorTree {
+    pub fn from_json(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("BehaviorTree serialization cannot fail")
+    }
+
+    /// Encodes tree into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("BehaviorTree serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, assets::Error> {
+        bincode::deserialize(data).map_err(assets::Error::new)
+    }
+
+    /// Checks that nodes reachable from the root form a tree.
+    /// Nodes not reachable from the root are ignored.
+    pub fn validate(&self) -> Result<(), BehaviorTreeError> {
+        let Some(root) = self.root else {
+            return Ok(());
+        };
+
+        if root >= self.nodes.len() {
+            return Err(BehaviorTreeError::MissingRoot { root });
+        }
+
+        let mut visited = vec![false; self.nodes.len()];
+        let mut stack = vec![root];
+
+        while let Some(node) = stack.pop() {
+            if std::mem::replace(&mut visited[node], true) {
+                return Err(BehaviorTreeError::SharedNode { node });
+            }
+
+            let children = &self.nodes[node].children;
+            if let Some(max) = self.nodes[node].kind.max_children() {
+                if children.len() > max {
+                    return Err(BehaviorTreeError::TooManyChildren {
+                        node,
+                        count: children.len(),
+                        max,
+                    });
+                }
+            }
+
+            for &child in children {
+                if child >= self.nodes.len() {
+                    return Err(BehaviorTreeError::MissingNode { node, child });
+                }
+                stack.push(child);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Asset for BehaviorTree {
+    type Loaded = BehaviorTree;
+
+    fn target() -> Ident {
+        ident!(behavior_tree)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<BehaviorTree, assets::Error>> + Send {
+        futures::future::ready(BehaviorTree::decode(&data))
+    }
+
+    fn build(loaded: BehaviorTree, _builder: &mut AssetBuilder) -> Result<Self, assets::Error> {
+        Ok(loaded)
+    }
+}
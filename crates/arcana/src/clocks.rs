@@ -0,0 +1,161 @@
+//! Time scale, pause and named clocks.
+//!
+//! Instance advances all clocks by the real frame time.
+//! Gameplay clock drives `ClockStep` resource, fixed updates and flows,
+//! so scaling or pausing it slows down or stops the game.
+//! Other clocks may ignore pause and scale, e.g. UI clock keeps animating menus
+//! while the game is paused.
+
+use arcana_names::{ident, Ident};
+use gametime::{ClockRate, ClockStep, TimeSpan, TimeStamp};
+use hashbrown::HashMap;
+
+/// Returns name of the gameplay clock.
+pub fn game_clock() -> Ident {
+    ident!(game)
+}
+
+/// Returns name of the UI clock.
+/// It is not affected by pause or time scale.
+pub fn ui_clock() -> Ident {
+    ident!(ui)
+}
+
+struct NamedClock {
+    rate: ClockRate,
+    step: ClockStep,
+
+    /// Own rate of the clock, multiplied by global scale if clock is scaled.
+    own_rate: f32,
+
+    /// Clock stops while game is paused.
+    pausable: bool,
+
+    /// Clock follows global time scale.
+    scaled: bool,
+}
+
+/// Resource with global time scale, pause flag and named clocks.
+pub struct Clocks {
+    scale: f32,
+    paused: bool,
+    clocks: HashMap<Ident, NamedClock>,
+}
+
+impl Clocks {
+    /// Creates gameplay clock that is paused and scaled
+    /// and UI clock that is neither.
+    pub fn new() -> Self {
+        let mut clocks = Clocks {
+            scale: 1.0,
+            paused: false,
+            clocks: HashMap::new(),
+        };
+
+        clocks.add(game_clock(), true, true);
+        clocks.add(ui_clock(), false, false);
+        clocks
+    }
+
+    /// Returns global time scale.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Sets global time scale.
+    /// Scaled clocks advance `scale` times faster than real time.
+    pub fn set_scale(&mut self, scale: f32) {
+        assert!(scale >= 0.0, "Time scale must be non-negative");
+        self.scale = scale;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pauses or resumes pausable clocks.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Adds new clock or replaces existing one.
+    /// New clock starts at zero.
+    pub fn add(&mut self, name: Ident, pausable: bool, scaled: bool) {
+        let rate = ClockRate::new();
+        let step = ClockStep {
+            now: rate.now(),
+            step: TimeSpan::ZERO,
+        };
+
+        self.clocks.insert(
+            name,
+            NamedClock {
+                rate,
+                step,
+                own_rate: 1.0,
+                pausable,
+                scaled,
+            },
+        );
+    }
+
+    /// Removes the clock.
+    /// Gameplay clock can't be removed.
+    pub fn remove(&mut self, name: Ident) {
+        if name == game_clock() {
+            tracing::error!("Gameplay clock can't be removed");
+            return;
+        }
+
+        self.clocks.remove(&name);
+    }
+
+    /// Sets own rate of the clock.
+    /// It is combined with global time scale for scaled clocks.
+    pub fn set_rate(&mut self, name: Ident, rate: f32) {
+        assert!(rate >= 0.0, "Clock rate must be non-negative");
+
+        match self.clocks.get_mut(&name) {
+            Some(clock) => clock.own_rate = rate,
+            None => tracing::error!("Clock {name} does not exist"),
+        }
+    }
+
+    /// Returns last step of the clock.
+    pub fn step(&self, name: Ident) -> Option<ClockStep> {
+        self.clocks.get(&name).map(|clock| clock.step)
+    }
+
+    /// Returns current time of the clock.
+    pub fn now(&self, name: Ident) -> Option<TimeStamp> {
+        self.clocks.get(&name).map(|clock| clock.step.now)
+    }
+
+    /// Returns names of all clocks.
+    pub fn names(&self) -> impl Iterator<Item = Ident> + '_ {
+        self.clocks.keys().copied()
+    }
+
+    /// Advances all clocks by real time span.
+    /// Returns step of the gameplay clock.
+    pub fn advance(&mut self, span: TimeSpan) -> ClockStep {
+        for clock in self.clocks.values_mut() {
+            let span = if self.paused && clock.pausable {
+                TimeSpan::ZERO
+            } else {
+                span
+            };
+
+            let rate = if clock.scaled {
+                clock.own_rate * self.scale
+            } else {
+                clock.own_rate
+            };
+
+            clock.rate.set_rate(rate);
+            clock.step = clock.rate.step(span);
+        }
+
+        self.clocks[&game_clock()].step
+    }
+}
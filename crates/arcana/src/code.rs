@@ -445,6 +445,10 @@ impl AsyncContinueQueue {
         self.queue.extend(other.queue.drain(..));
     }
 
+    pub fn push(&mut self, c: AsyncContinue) {
+        self.queue.push(c);
+    }
+
     pub fn drain(&mut self) -> impl Iterator<Item = AsyncContinue> + '_ {
         self.queue.drain(..)
     }
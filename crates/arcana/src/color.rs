@@ -0,0 +1,328 @@
+//! Colors and color space conversions.
+//!
+//! [`Color`] is always linear RGB with straight alpha.
+//! This is what shaders operate on and what clear values of sRGB targets expect,
+//! the transfer function is applied by hardware when writing to such targets.
+//! Constructors and accessors name the color space explicitly,
+//! so code never has to guess what a bunch of floats means.
+
+use crate::model::ColorValue;
+
+/// Converts sRGB encoded component to linear.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts linear component to sRGB encoded.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Color in OKLab perceptual color space.
+///
+/// Interpolating in OKLab gives gradients without muddy midpoints.
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Oklab {
+    /// Perceived lightness.
+    pub l: f32,
+
+    /// Green-red axis.
+    pub a: f32,
+
+    /// Blue-yellow axis.
+    pub b: f32,
+}
+
+/// Linear RGB color with straight alpha.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    bytemuck::Pod,
+    bytemuck::Zeroable,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[repr(C)]
+pub struct Color {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub alpha: f32,
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color::WHITE
+    }
+}
+
+impl Color {
+    pub const WHITE: Self = Color::linear(1.0, 1.0, 1.0, 1.0);
+    pub const BLACK: Self = Color::linear(0.0, 0.0, 0.0, 1.0);
+    pub const TRANSPARENT: Self = Color::linear(0.0, 0.0, 0.0, 0.0);
+
+    /// Returns color from linear components.
+    pub const fn linear(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        Color {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    /// Returns opaque color from sRGB encoded components.
+    pub fn srgb(red: f32, green: f32, blue: f32) -> Self {
+        Color::srgba(red, green, blue, 1.0)
+    }
+
+    /// Returns color from sRGB encoded components.
+    /// Alpha is always linear.
+    pub fn srgba(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
+        Color {
+            red: srgb_to_linear(red),
+            green: srgb_to_linear(green),
+            blue: srgb_to_linear(blue),
+            alpha,
+        }
+    }
+
+    /// Returns color from 8-bit sRGB encoded components,
+    /// as found in images and color pickers.
+    pub fn srgba8(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        Color::srgba(
+            red as f32 / 255.0,
+            green as f32 / 255.0,
+            blue as f32 / 255.0,
+            alpha as f32 / 255.0,
+        )
+    }
+
+    /// Returns color from OKLab.
+    pub fn oklab(lab: Oklab, alpha: f32) -> Self {
+        let l = lab.l + 0.396_337_78 * lab.a + 0.215_803_76 * lab.b;
+        let m = lab.l - 0.105_561_346 * lab.a - 0.063_854_17 * lab.b;
+        let s = lab.l - 0.089_484_18 * lab.a - 1.291_485_5 * lab.b;
+
+        let l = l * l * l;
+        let m = m * m * m;
+        let s = s * s * s;
+
+        Color {
+            red: 4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+            green: -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+            blue: -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+            alpha,
+        }
+    }
+
+    pub const fn with_alpha(mut self, alpha: f32) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Returns linear components.
+    pub const fn to_linear(&self) -> [f32; 4] {
+        [self.red, self.green, self.blue, self.alpha]
+    }
+
+    /// Returns sRGB encoded components.
+    pub fn to_srgba(&self) -> [f32; 4] {
+        [
+            linear_to_srgb(self.red),
+            linear_to_srgb(self.green),
+            linear_to_srgb(self.blue),
+            self.alpha,
+        ]
+    }
+
+    /// Returns 8-bit sRGB encoded components.
+    pub fn to_srgba8(&self) -> [u8; 4] {
+        self.to_srgba()
+            .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    /// Returns color in OKLab.
+    pub fn to_oklab(&self) -> Oklab {
+        let l = 0.412_221_46 * self.red + 0.536_332_55 * self.green + 0.051_445_995 * self.blue;
+        let m = 0.211_903_5 * self.red + 0.680_699_5 * self.green + 0.107_396_96 * self.blue;
+        let s = 0.088_302_46 * self.red + 0.281_718_85 * self.green + 0.629_978_7 * self.blue;
+
+        let l = l.cbrt();
+        let m = m.cbrt();
+        let s = s.cbrt();
+
+        Oklab {
+            l: 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+            a: 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+            b: 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+        }
+    }
+
+    /// Interpolates between colors in OKLab.
+    pub fn mix(&self, other: &Color, t: f32) -> Color {
+        let a = self.to_oklab();
+        let b = other.to_oklab();
+
+        let lab = Oklab {
+            l: a.l + (b.l - a.l) * t,
+            a: a.a + (b.a - a.a) * t,
+            b: a.b + (b.b - a.b) * t,
+        };
+
+        Color::oklab(lab, self.alpha + (other.alpha - self.alpha) * t)
+    }
+}
+
+impl From<palette::Srgb> for Color {
+    fn from(c: palette::Srgb) -> Self {
+        Color::srgb(c.red, c.green, c.blue)
+    }
+}
+
+impl From<palette::Srgba> for Color {
+    fn from(c: palette::Srgba) -> Self {
+        Color::srgba(c.red, c.green, c.blue, c.alpha)
+    }
+}
+
+impl From<palette::LinSrgba> for Color {
+    fn from(c: palette::LinSrgba) -> Self {
+        Color::linear(c.red, c.green, c.blue, c.alpha)
+    }
+}
+
+impl From<Color> for palette::Srgba {
+    fn from(c: Color) -> Self {
+        let [r, g, b, a] = c.to_srgba();
+        palette::Srgba::new(r, g, b, a)
+    }
+}
+
+impl From<ColorValue> for Color {
+    fn from(value: ColorValue) -> Self {
+        value.into_srgba().into()
+    }
+}
+
+impl From<Color> for ColorValue {
+    fn from(c: Color) -> Self {
+        ColorValue::Srgba(c.into())
+    }
+}
+
+/// Clear values are linear, hardware encodes them for sRGB targets.
+impl From<Color> for mev::ClearColor {
+    fn from(c: Color) -> Self {
+        mev::ClearColor(c.red, c.green, c.blue, c.alpha)
+    }
+}
+
+impl From<Color> for mev::vec4 {
+    fn from(c: Color) -> Self {
+        mev::vec4(c.red, c.green, c.blue, c.alpha)
+    }
+}
+
+/// `Color32` is sRGB encoded with premultiplied alpha.
+impl From<Color> for egui::Color32 {
+    fn from(c: Color) -> Self {
+        let [r, g, b, a] = c.to_srgba8();
+        egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+    }
+}
+
+impl From<egui::Color32> for Color {
+    fn from(c: egui::Color32) -> Self {
+        let [r, g, b, a] = c.to_srgba_unmultiplied();
+        Color::srgba8(r, g, b, a)
+    }
+}
+
+/// `Rgba` is linear with premultiplied alpha.
+impl From<Color> for egui::Rgba {
+    fn from(c: Color) -> Self {
+        egui::Rgba::from_rgba_unmultiplied(c.red, c.green, c.blue, c.alpha)
+    }
+}
+
+impl From<egui::Rgba> for Color {
+    fn from(c: egui::Rgba) -> Self {
+        let [r, g, b, a] = c.to_rgba_unmultiplied();
+        Color::linear(r, g, b, a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{a} != {b}");
+    }
+
+    #[test]
+    fn test_srgb_transfer() {
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert_close(srgb_to_linear(1.0), 1.0);
+        assert_close(srgb_to_linear(0.5), 0.214_041_14);
+        assert_close(linear_to_srgb(0.214_041_14), 0.5);
+
+        // Both sides of the linear segment.
+        for value in [0.001, 0.04, 0.05, 0.3, 0.9] {
+            assert_close(linear_to_srgb(srgb_to_linear(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_srgba8_roundtrip() {
+        for value in 0..=255 {
+            let color = Color::srgba8(value, value, value, value);
+            assert_eq!(color.to_srgba8(), [value; 4]);
+        }
+    }
+
+    #[test]
+    fn test_oklab() {
+        let lab = Color::WHITE.to_oklab();
+        assert_close(lab.l, 1.0);
+        assert_close(lab.a, 0.0);
+        assert_close(lab.b, 0.0);
+
+        // Reference values for linear sRGB primaries.
+        let lab = Color::linear(1.0, 0.0, 0.0, 1.0).to_oklab();
+        assert_close(lab.l, 0.627_955);
+        assert_close(lab.a, 0.224_863);
+        assert_close(lab.b, 0.125_846);
+
+        let color = Color::linear(0.2, 0.5, 0.8, 0.3);
+        let back = Color::oklab(color.to_oklab(), color.alpha);
+        for (a, b) in color.to_linear().into_iter().zip(back.to_linear()) {
+            assert_close(a, b);
+        }
+    }
+
+    #[test]
+    fn test_mix() {
+        let a = Color::linear(1.0, 0.0, 0.0, 1.0);
+        let b = Color::linear(0.0, 0.0, 1.0, 0.0);
+
+        for (x, y) in a.mix(&b, 0.0).to_linear().into_iter().zip(a.to_linear()) {
+            assert_close(x, y);
+        }
+        for (x, y) in a.mix(&b, 1.0).to_linear().into_iter().zip(b.to_linear()) {
+            assert_close(x, y);
+        }
+        assert_close(a.mix(&b, 0.25).alpha, 0.75);
+    }
+}
@@ -0,0 +1,554 @@
+//! Console commands.
+//!
+//! Plugins register commands in [`Commands`] resource from their init functions.
+//! Editor console and games with egui plugin parse command lines typed by user
+//! and execute them against the world.
+//!
+//! ```ignore
+//! #[arcana::init]
+//! fn init_commands(world: &mut World) {
+//!     add_command(world, Command {
+//!         name: "gravity",
+//!         help: "gravity <value> - sets gravity acceleration",
+//!         run: |world, args| {
+//!             let [value] = args else {
+//!                 return Err(CommandError::Usage("gravity <value>"));
+//!             };
+//!             let value = value.parse().map_err(|_| CommandError::Failed(format!("Invalid value '{value}'")))?;
+//!             world.expect_resource_mut::<Gravity>().0 = value;
+//!             Ok(String::new())
+//!         },
+//!         complete: None,
+//!     });
+//! }
+//! ```
+//!
+//! Built-in `resource` command shows and sets resources
+//! registered with [`reflect_resource`](crate::reflect::reflect_resource).
+//! Ed adds commands that inspect and edit entities of the instance.
+//!
+//! Engine has no prefab assets or toggleable debug passes,
+//! so there are no built-in commands for them.
+//! Plugins that provide such features register their own commands.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use edict::world::World;
+
+use crate::{model::Value, reflect::ReflectedResources};
+
+/// Maximum number of lines kept in console output.
+const OUTPUT_LIMIT: usize = 512;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("Unknown command '{0}'")]
+    Unknown(String),
+
+    #[error("Usage: {0}")]
+    Usage(&'static str),
+
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// Runs command with parsed arguments.
+/// Returns text to print in console.
+pub type CommandFn = fn(&mut World, &[&str]) -> Result<String, CommandError>;
+
+/// Returns candidates for the last argument.
+/// Last argument may be empty if user just typed a space.
+pub type CompleteFn = fn(&World, &[&str]) -> Vec<String>;
+
+#[derive(Clone, Copy)]
+pub struct Command {
+    pub name: &'static str,
+
+    /// One-line description shown by `help`.
+    pub help: &'static str,
+
+    pub run: CommandFn,
+
+    pub complete: Option<CompleteFn>,
+}
+
+/// Registry of console commands.
+pub struct Commands {
+    commands: BTreeMap<&'static str, Command>,
+}
+
+impl Commands {
+    pub fn new() -> Self {
+        let mut commands = Commands {
+            commands: BTreeMap::new(),
+        };
+
+        commands.add(Command {
+            name: "help",
+            help: "help [command] - lists commands or shows help for one",
+            run: help,
+            complete: Some(|world, args| match args {
+                [prefix] => complete_names(world, prefix),
+                _ => Vec::new(),
+            }),
+        });
+
+        commands.add(Command {
+            name: "resource",
+            help:
+                "resource <name> [value] - shows or sets reflected resource value written in JSON",
+            run: resource,
+            complete: Some(|world, args| match args {
+                [prefix] => resource_names(world, prefix),
+                _ => Vec::new(),
+            }),
+        });
+
+        commands
+    }
+
+    /// Adds command replacing one with the same name.
+    pub fn add(&mut self, command: Command) {
+        if self.commands.insert(command.name, command).is_some() {
+            tracing::warn!("Console command '{}' is redefined", command.name);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Command> {
+        self.commands.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Command> + '_ {
+        self.commands.values()
+    }
+}
+
+impl Default for Commands {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn init_commands(world: &mut World) {
+    world.insert_resource(Commands::new());
+}
+
+/// Adds command to the world's registry.
+pub fn add_command(world: &mut World, command: Command) {
+    match world.get_resource_mut::<Commands>() {
+        Some(mut commands) => commands.add(command),
+        None => {
+            let mut commands = Commands::new();
+            commands.add(command);
+            world.insert_resource(commands);
+        }
+    }
+}
+
+/// Splits command line into words.
+/// Words in double quotes may contain spaces.
+pub fn split_args(line: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut rest = line.trim_start();
+
+    while !rest.is_empty() {
+        let (arg, tail) = match rest.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => (quoted, ""),
+            },
+            None => match rest.find(char::is_whitespace) {
+                Some(end) => (&rest[..end], &rest[end..]),
+                None => (rest, ""),
+            },
+        };
+
+        args.push(arg);
+        rest = tail.trim_start();
+    }
+
+    args
+}
+
+/// Parses and executes command line.
+pub fn execute(world: &mut World, line: &str) -> Result<String, CommandError> {
+    let args = split_args(line);
+    let Some((&name, args)) = args.split_first() else {
+        return Ok(String::new());
+    };
+
+    let command = world
+        .get_resource::<Commands>()
+        .and_then(|commands| commands.get(name).copied());
+
+    match command {
+        None => Err(CommandError::Unknown(name.to_owned())),
+        Some(command) => (command.run)(world, args),
+    }
+}
+
+/// Returns lines that complete the last word of the command line.
+pub fn complete(world: &World, line: &str) -> Vec<String> {
+    let args = split_args(line);
+    let ends_with_space = line.ends_with(char::is_whitespace);
+
+    match args.split_first() {
+        None => complete_names(world, ""),
+        Some((prefix, [])) if !ends_with_space => complete_names(world, prefix),
+        Some((&name, args)) => {
+            let Some(complete_args) = world
+                .get_resource::<Commands>()
+                .and_then(|commands| commands.get(name)?.complete)
+            else {
+                return Vec::new();
+            };
+
+            let mut args = args.to_vec();
+
+            // Part of the line that is kept before completed word.
+            let head = if ends_with_space {
+                args.push("");
+                line
+            } else {
+                let last = args.last().unwrap();
+                let start = last.as_ptr() as usize - line.as_ptr() as usize;
+                let head = &line[..start];
+                head.strip_suffix('"').unwrap_or(head)
+            };
+
+            complete_args(world, &args)
+                .into_iter()
+                .map(|candidate| {
+                    if candidate.contains(char::is_whitespace) {
+                        format!("{head}\"{candidate}\" ")
+                    } else {
+                        format!("{head}{candidate} ")
+                    }
+                })
+                .collect()
+        }
+    }
+}
+
+fn complete_names(world: &World, prefix: &str) -> Vec<String> {
+    let Some(commands) = world.get_resource::<Commands>() else {
+        return Vec::new();
+    };
+
+    commands
+        .iter()
+        .filter(|command| command.name.starts_with(prefix))
+        .map(|command| format!("{} ", command.name))
+        .collect()
+}
+
+fn help(world: &mut World, args: &[&str]) -> Result<String, CommandError> {
+    let Some(commands) = world.get_resource::<Commands>() else {
+        return Ok(String::new());
+    };
+
+    match args {
+        [] => Ok(commands
+            .iter()
+            .map(|command| command.help)
+            .collect::<Vec<_>>()
+            .join("\n")),
+        [name] => match commands.get(name) {
+            None => Err(CommandError::Unknown((*name).to_owned())),
+            Some(command) => Ok(command.help.to_owned()),
+        },
+        _ => Err(CommandError::Usage("help [command]")),
+    }
+}
+
+fn resource_names(world: &World, prefix: &str) -> Vec<String> {
+    let Some(resources) = world.get_resource::<ReflectedResources>() else {
+        return Vec::new();
+    };
+
+    resources
+        .names()
+        .filter(|name| name.starts_with(prefix))
+        .map(str::to_owned)
+        .collect()
+}
+
+fn resource(world: &mut World, args: &[&str]) -> Result<String, CommandError> {
+    let (name, value) = match args {
+        [name] => (*name, None),
+        [name, value] => (*name, Some(*value)),
+        _ => return Err(CommandError::Usage("resource <name> [value]")),
+    };
+
+    let reflect = world
+        .get_resource::<ReflectedResources>()
+        .and_then(|resources| resources.get(name).copied())
+        .ok_or_else(|| CommandError::Failed(format!("Resource '{name}' is not reflected")))?;
+
+    match value {
+        None => match reflect.get(world) {
+            None => Err(CommandError::Failed(format!(
+                "Resource '{name}' is missing"
+            ))),
+            Some(value) => {
+                serde_json::to_string(&value).map_err(|err| CommandError::Failed(err.to_string()))
+            }
+        },
+        Some(value) => {
+            let value: Value = serde_json::from_str(value)
+                .map_err(|err| CommandError::Failed(format!("Invalid value: {err}")))?;
+
+            reflect
+                .set(world, &value)
+                .map_err(|err| CommandError::Failed(err.to_string()))?;
+            Ok(String::new())
+        }
+    }
+}
+
+/// Line of console output.
+pub enum ConsoleLine {
+    Input(String),
+    Output(String),
+    Error(String),
+}
+
+/// Console widget.
+///
+/// Executes lines typed by user and keeps output.
+/// Tab completes the last word, Up and Down walk through entered lines.
+pub struct Console {
+    input: String,
+    output: VecDeque<ConsoleLine>,
+
+    /// Previously entered lines.
+    entered: Vec<String>,
+
+    /// Position in `entered` when walking with arrows.
+    recall: Option<usize>,
+
+    /// Candidates shown after ambiguous completion.
+    candidates: Vec<String>,
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console {
+            input: String::new(),
+            output: VecDeque::new(),
+            entered: Vec::new(),
+            recall: None,
+            candidates: Vec::new(),
+        }
+    }
+
+    pub fn print(&mut self, line: ConsoleLine) {
+        if self.output.len() == OUTPUT_LIMIT {
+            self.output.pop_front();
+        }
+        self.output.push_back(line);
+    }
+
+    pub fn clear(&mut self) {
+        self.output.clear();
+    }
+
+    /// Shows console that executes commands registered in the world.
+    pub fn show_world(&mut self, world: &mut World, ui: &mut egui::Ui) {
+        let world = std::cell::RefCell::new(world);
+        self.show(
+            ui,
+            |line| execute(&mut world.borrow_mut(), line),
+            |line| complete(&world.borrow(), line),
+        );
+    }
+
+    /// Shows console with custom command execution and completion.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        mut execute: impl FnMut(&str) -> Result<String, CommandError>,
+        complete: impl Fn(&str) -> Vec<String>,
+    ) {
+        let font = egui::TextStyle::Monospace;
+
+        egui::TopBottomPanel::bottom("console-input").show_inside(ui, |ui| {
+            if !self.candidates.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    for candidate in &self.candidates {
+                        ui.weak(candidate.trim_end());
+                    }
+                });
+            }
+
+            let r = ui.add(
+                egui::TextEdit::singleline(&mut self.input)
+                    .font(font.clone())
+                    .desired_width(f32::INFINITY)
+                    .lock_focus(true)
+                    .hint_text("Type 'help' to list commands"),
+            );
+
+            if r.changed() {
+                self.candidates.clear();
+            }
+
+            // Single line edit loses focus on Enter.
+            if r.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let line = std::mem::take(&mut self.input);
+                self.recall = None;
+                self.candidates.clear();
+                r.request_focus();
+
+                if line.trim().is_empty() {
+                    return;
+                }
+
+                if self.entered.last() != Some(&line) {
+                    self.entered.push(line.clone());
+                }
+
+                let result = execute(&line);
+                self.print(ConsoleLine::Input(line));
+
+                match result {
+                    Ok(output) if output.is_empty() => {}
+                    Ok(output) => self.print(ConsoleLine::Output(output)),
+                    Err(err) => self.print(ConsoleLine::Error(err.to_string())),
+                }
+                return;
+            }
+
+            if !r.has_focus() {
+                return;
+            }
+
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)) {
+                let mut candidates = complete(&self.input);
+                match candidates.len() {
+                    0 => {}
+                    1 => {
+                        self.input = candidates.pop().unwrap();
+                        self.candidates.clear();
+                        move_cursor_to_end(ui, r.id, &self.input);
+                    }
+                    _ => {
+                        let prefix = common_prefix(&candidates);
+                        if prefix.len() > self.input.len() {
+                            self.input = prefix.to_owned();
+                            move_cursor_to_end(ui, r.id, &self.input);
+                        }
+                        self.candidates = candidates;
+                    }
+                }
+            }
+
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp)) {
+                let recall = match self.recall {
+                    None => self.entered.len().checked_sub(1),
+                    Some(idx) => Some(idx.saturating_sub(1)),
+                };
+                if let Some(idx) = recall {
+                    self.recall = Some(idx);
+                    self.input = self.entered[idx].clone();
+                    move_cursor_to_end(ui, r.id, &self.input);
+                }
+            }
+
+            if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown)) {
+                if let Some(idx) = self.recall {
+                    if idx + 1 < self.entered.len() {
+                        self.recall = Some(idx + 1);
+                        self.input = self.entered[idx + 1].clone();
+                    } else {
+                        self.recall = None;
+                        self.input.clear();
+                    }
+                    move_cursor_to_end(ui, r.id, &self.input);
+                }
+            }
+        });
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in &self.output {
+                    let text = match line {
+                        ConsoleLine::Input(line) => {
+                            egui::RichText::new(format!("> {line}")).strong()
+                        }
+                        ConsoleLine::Output(line) => egui::RichText::new(line),
+                        ConsoleLine::Error(line) => {
+                            egui::RichText::new(line).color(ui.visuals().error_fg_color)
+                        }
+                    };
+                    ui.label(text.text_style(font.clone()));
+                }
+            });
+    }
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn move_cursor_to_end(ui: &egui::Ui, id: egui::Id, text: &str) {
+    if let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), id) {
+        let cursor = egui::text::CCursor::new(text.chars().count());
+        state
+            .cursor
+            .set_char_range(Some(egui::text::CCursorRange::one(cursor)));
+        state.store(ui.ctx(), id);
+    }
+}
+
+fn common_prefix(candidates: &[String]) -> &str {
+    let Some((first, rest)) = candidates.split_first() else {
+        return "";
+    };
+
+    let mut len = first.len();
+    for candidate in rest {
+        len = first
+            .char_indices()
+            .zip(candidate.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((idx, a), _)| idx + a.len_utf8())
+            .min(len);
+    }
+
+    &first[..len]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reflect::reflect_resource;
+
+    use super::*;
+
+    #[test]
+    fn test_resource_command() {
+        let mut world = World::new();
+        init_commands(&mut world);
+        world.insert_resource(String::from("hello"));
+        reflect_resource::<String>(&mut world, "greeting");
+
+        assert_eq!(
+            execute(&mut world, "resource greeting").unwrap(),
+            r#"{"String":"hello"}"#
+        );
+
+        execute(&mut world, r#"resource greeting {"String":"bye"}"#).unwrap();
+        assert_eq!(*world.expect_resource::<String>(), "bye");
+
+        assert!(execute(&mut world, "resource missing").is_err());
+        assert_eq!(
+            complete(&world, "resource gr"),
+            vec!["resource greeting ".to_owned()]
+        );
+    }
+}
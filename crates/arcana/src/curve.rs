@@ -0,0 +1,257 @@
+//! Keyframed scalar curves.
+//!
+//! Curves map time to value and are used for easing,
+//! parameters that change over lifetime, falloffs and similar things.
+//! Ed edits them in place as model values.
+
+use std::future::Future;
+
+use arcana_names::{ident, Ident};
+use arcana_proc::WithStid;
+
+use crate::assets::{self, Asset, AssetBuilder, Assets};
+
+/// How value changes from a key to the next one.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum Interpolation {
+    /// Value stays the same until next key.
+    Constant,
+
+    /// Value changes linearly.
+    #[default]
+    Linear,
+
+    /// Value follows cubic Hermite spline
+    /// with tangents derived from neighbour keys.
+    Smooth,
+}
+
+impl Interpolation {
+    pub const ALL: [Interpolation; 3] = [
+        Interpolation::Constant,
+        Interpolation::Linear,
+        Interpolation::Smooth,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Interpolation::Constant => "Constant",
+            Interpolation::Linear => "Linear",
+            Interpolation::Smooth => "Smooth",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Keyframe {
+    pub time: f32,
+    pub value: f32,
+
+    /// Interpolation towards the next key.
+    #[serde(default)]
+    pub interp: Interpolation,
+}
+
+/// Scalar curve defined by keyframes sorted by time.
+///
+/// Before first key and after last key value is clamped.
+/// Curve without keys is zero everywhere.
+#[derive(Clone, Debug, PartialEq, WithStid, serde::Serialize, serde::Deserialize)]
+pub struct Curve {
+    keys: Vec<Keyframe>,
+}
+
+impl Default for Curve {
+    fn default() -> Self {
+        Curve::linear(0.0, 1.0)
+    }
+}
+
+impl Curve {
+    /// Returns curve with keys sorted by time.
+    pub fn new(mut keys: Vec<Keyframe>) -> Self {
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Curve { keys }
+    }
+
+    pub fn constant(value: f32) -> Self {
+        Curve {
+            keys: vec![Keyframe {
+                time: 0.0,
+                value,
+                interp: Interpolation::Constant,
+            }],
+        }
+    }
+
+    /// Returns curve that goes from `from` to `to` over unit time.
+    pub fn linear(from: f32, to: f32) -> Self {
+        Curve::new(vec![
+            Keyframe {
+                time: 0.0,
+                value: from,
+                interp: Interpolation::Linear,
+            },
+            Keyframe {
+                time: 1.0,
+                value: to,
+                interp: Interpolation::Linear,
+            },
+        ])
+    }
+
+    /// Returns smooth curve from 0 to 1 over unit time.
+    pub fn ease_in_out() -> Self {
+        Curve::new(vec![
+            Keyframe {
+                time: 0.0,
+                value: 0.0,
+                interp: Interpolation::Smooth,
+            },
+            Keyframe {
+                time: 1.0,
+                value: 1.0,
+                interp: Interpolation::Smooth,
+            },
+        ])
+    }
+
+    pub fn keys(&self) -> &[Keyframe] {
+        &self.keys
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns time of first and last keys.
+    pub fn time_range(&self) -> Option<(f32, f32)> {
+        let first = self.keys.first()?;
+        let last = self.keys.last()?;
+        Some((first.time, last.time))
+    }
+
+    /// Inserts new key keeping keys sorted.
+    /// Returns index of the key.
+    pub fn insert(&mut self, key: Keyframe) -> usize {
+        let idx = self.keys.partition_point(|k| k.time <= key.time);
+        self.keys.insert(idx, key);
+        idx
+    }
+
+    pub fn remove(&mut self, idx: usize) -> Keyframe {
+        self.keys.remove(idx)
+    }
+
+    /// Moves key to new time and value.
+    /// Time is clamped between neighbour keys so order is preserved.
+    pub fn move_key(&mut self, idx: usize, time: f32, value: f32) {
+        let min = match idx {
+            0 => f32::NEG_INFINITY,
+            _ => self.keys[idx - 1].time,
+        };
+        let max = match self.keys.get(idx + 1) {
+            None => f32::INFINITY,
+            Some(next) => next.time,
+        };
+
+        let key = &mut self.keys[idx];
+        key.time = time.clamp(min, max);
+        key.value = value;
+    }
+
+    pub fn set_interp(&mut self, idx: usize, interp: Interpolation) {
+        self.keys[idx].interp = interp;
+    }
+
+    /// Returns value of the curve at given time.
+    pub fn sample(&self, time: f32) -> f32 {
+        let idx = self.keys.partition_point(|k| k.time <= time);
+
+        if idx == 0 {
+            return self.keys.first().map_or(0.0, |k| k.value);
+        }
+        if idx == self.keys.len() {
+            return self.keys[idx - 1].value;
+        }
+
+        let a = &self.keys[idx - 1];
+        let b = &self.keys[idx];
+
+        let span = b.time - a.time;
+        if span <= 0.0 {
+            return b.value;
+        }
+
+        let t = (time - a.time) / span;
+
+        match a.interp {
+            Interpolation::Constant => a.value,
+            Interpolation::Linear => a.value + (b.value - a.value) * t,
+            Interpolation::Smooth => {
+                let m0 = self.tangent(idx - 1) * span;
+                let m1 = self.tangent(idx) * span;
+                hermite(a.value, m0, b.value, m1, t)
+            }
+        }
+    }
+
+    /// Catmull-Rom style tangent, flat at the ends.
+    fn tangent(&self, idx: usize) -> f32 {
+        if idx == 0 || idx + 1 >= self.keys.len() {
+            return 0.0;
+        }
+
+        let prev = &self.keys[idx - 1];
+        let next = &self.keys[idx + 1];
+
+        let span = next.time - prev.time;
+        if span <= 0.0 {
+            return 0.0;
+        }
+
+        (next.value - prev.value) / span
+    }
+
+    /// Encodes curve into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Curve serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, assets::Error> {
+        let curve: Curve = bincode::deserialize(data).map_err(assets::Error::new)?;
+        Ok(Curve::new(curve.keys))
+    }
+}
+
+fn hermite(p0: f32, m0: f32, p1: f32, m1: f32, t: f32) -> f32 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    (2.0 * t3 - 3.0 * t2 + 1.0) * p0
+        + (t3 - 2.0 * t2 + t) * m0
+        + (-2.0 * t3 + 3.0 * t2) * p1
+        + (t3 - t2) * m1
+}
+
+impl Asset for Curve {
+    type Loaded = Curve;
+
+    fn target() -> Ident {
+        ident!(curve)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<Curve, assets::Error>> + Send {
+        futures::future::ready(Curve::decode(&data))
+    }
+
+    fn build(loaded: Curve, _builder: &mut AssetBuilder) -> Result<Self, assets::Error> {
+        Ok(loaded)
+    }
+}
@@ -1,41 +1,66 @@
-use std::{borrow::Cow, hash::Hash, path::PathBuf};
+use std::{borrow::Cow, collections::BTreeMap, hash::Hash, path::PathBuf};
 
 use arboard::Clipboard;
 use blink_alloc::BlinkAlloc;
-use egui::{Id, TopBottomPanel, WidgetText};
+use egui::{Id, Key, KeyboardShortcut, Modifiers, TopBottomPanel, WidgetText};
 use egui_dock::{DockState, NodeIndex, TabIndex, TabViewer, Tree};
-use egui_tracing::EventCollector;
-use gametime::{Clock, ClockStep, FrequencyNumExt, FrequencyTicker};
+use gametime::{Clock, ClockStep, FrequencyNumExt, FrequencyTicker, TimeSpan};
 use miette::IntoDiagnostic;
 use winit::{
     dpi,
-    event::WindowEvent,
+    event::{DeviceEvent, DeviceId, WindowEvent},
     event_loop::{ActiveEventLoop, ControlFlow},
-    window::{Window, WindowId},
+    window::{CustomCursor, Window, WindowId},
 };
 
-use crate::{input::ViewInput, project::Project};
+use crate::{
+    input::{CursorAppearance, CursorShape, ViewInput},
+    pacing::FrameLimiter,
+    project::Project,
+    vfs::Vfs,
+    viewport::set_window_cursor_grab,
+};
 
 use super::{
     assets::Assets,
+    behavior::BehaviorTrees,
     code::CodeTool,
+    console::Console,
     container::Container,
     data::ProjectData,
     filters::Filters,
+    history::History,
     ide::{Ide, IdeType},
     init_mev,
+    input_map::InputMaps,
+    inspector::Inspector,
     instance::Instance,
+    logs::{LogCollector, Logs},
+    params::ParamSets,
     plugins::Plugins,
+    profiler::Profiler,
     render::Rendering,
     sample::ImageSample,
+    scene::SceneView,
+    schedule::ScheduleView,
     subprocess::{filter_subprocesses, kill_subprocesses},
     systems::Systems,
     ui::{Ui, UiViewport, UserTextures},
+    world::WorldBrowser,
 };
 
 #[derive(Clone, Default, egui_probe::EguiProbe, serde::Serialize, serde::Deserialize)]
 pub struct AppConfig {
     ide: Option<IdeType>,
+
+    /// Skip redrawing unfocused and minimized windows
+    /// while nothing changes.
+    low_power: bool,
+
+    /// Redraw focused windows too only when UI requests repaint,
+    /// input arrives or game world changes.
+    /// Game views are not re-rendered while world stays the same.
+    reactive: bool,
 }
 
 pub enum UserEvent {}
@@ -44,13 +69,22 @@ pub enum UserEvent {}
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 enum Tab {
     Plugins,
-    // Console,
+    Console,
     Systems,
     Filters,
     Rendering,
     // Main,
     Codes,
     Inspector,
+    Scene,
+    Assets,
+    Profiler,
+    World,
+    Schedule,
+    Logs,
+    InputMaps,
+    BehaviorTrees,
+    ParamSets,
     // Custom(ToolId),
 }
 
@@ -71,22 +105,41 @@ pub struct App {
 
     assets: Assets,
     plugins: Plugins,
-    // console: Console,
+    console: Console,
     code: CodeTool,
     systems: Systems,
     filters: Filters,
     rendering: Rendering,
+    inspector: Inspector,
+    scene: SceneView,
+    profiler: Profiler,
+    world: WorldBrowser,
+    schedule: ScheduleView,
+    logs: Logs,
+    input_maps: InputMaps,
+    behavior_trees: BehaviorTrees,
+    param_sets: ParamSets,
     main: Instance,
 
+    /// Undo history of project data.
+    history: History,
+
     image_sample: ImageSample,
     clipboard: Clipboard,
     should_quit: bool,
 
     clock: Clock,
     limiter: FrequencyTicker,
+
+    /// Caps frame rate to one requested by the game.
+    frame_limiter: FrameLimiter,
     cfg: AppConfig,
     show_preferences: bool,
 
+    /// Layouts saved by the user.
+    layouts: BTreeMap<String, DockState<Tab>>,
+    new_layout_name: String,
+
     ide: Option<Box<dyn Ide>>,
 }
 
@@ -95,20 +148,71 @@ struct AppView {
     surface: Option<mev::Surface>,
     dock_state: DockState<Tab>,
     viewport: UiViewport,
+
+    /// Window has input focus.
+    focused: bool,
+
+    /// Window is fully hidden from the user.
+    occluded: bool,
+
+    /// Cursor is grabbed by the game view in this window.
+    cursor_grabbed: bool,
+
+    /// Version of the game cursor appearance applied to the window.
+    /// `None` when cursor is controlled by the UI.
+    cursor_version: Option<u64>,
+}
+
+impl AppView {
+    fn new(window: Window, dock_state: DockState<Tab>, viewport: UiViewport) -> Self {
+        let focused = window.has_focus();
+
+        AppView {
+            window,
+            surface: None,
+            dock_state,
+            viewport,
+            focused,
+            occluded: false,
+            cursor_grabbed: false,
+            cursor_version: None,
+        }
+    }
+
+    /// Returns true if window is not seen or interacted with
+    /// and may skip frames while nothing changes.
+    fn is_idle(&self) -> bool {
+        !self.focused || self.occluded || self.window.is_minimized().unwrap_or(false)
+    }
+
+    /// Returns true if window is redrawn only when there is something new to show.
+    fn redraws_on_demand(&self, cfg: &AppConfig) -> bool {
+        cfg.reactive || (cfg.low_power && self.is_idle())
+    }
 }
 
 impl App {
-    pub fn new(_event_collector: EventCollector, project: Project, data: ProjectData) -> Self {
+    pub fn new(log_collector: LogCollector, project: Project, data: ProjectData) -> Self {
         let (device, queue) = init_mev();
 
         let plugins = Plugins::new();
-        // let console = Console::new(event_collector);
+        let console = Console::new();
         let systems = Systems::new();
         let filters = Filters::new();
         let rendering = Rendering::new();
         let image_sample = ImageSample::new(&device).unwrap();
         let code = CodeTool::new();
-        let main = Instance::new();
+        let inspector = Inspector::new();
+        let scene = SceneView::new();
+        let profiler = Profiler::new();
+        let world = WorldBrowser::new();
+        let schedule = ScheduleView::new();
+        let logs = Logs::new(log_collector);
+        let input_maps = InputMaps::new();
+        let behavior_trees = BehaviorTrees::new();
+        let param_sets = ParamSets::new();
+        let mut main = Instance::new();
+        main.set_vfs(Vfs::editor(project.root_path(), project.name().as_str()));
 
         let clock = Clock::new();
 
@@ -131,7 +235,16 @@ impl App {
             Some(ide) => Some(ide.get()),
         };
 
+        let layouts = match load_layouts(&project.name()) {
+            Ok(layouts) => layouts,
+            Err(err) => {
+                tracing::debug!("Failed to load layouts: {err:?}");
+                BTreeMap::new()
+            }
+        };
+
         let assets = Assets::new(&project.root_path().join("Assets"));
+        let history = History::new(&data);
 
         App {
             project,
@@ -147,11 +260,22 @@ impl App {
 
             assets,
             plugins,
+            console,
             code,
             systems,
             filters,
             rendering,
+            inspector,
+            scene,
+            profiler,
+            world,
+            schedule,
+            logs,
+            input_maps,
+            behavior_trees,
+            param_sets,
             main,
+            history,
 
             image_sample,
             clipboard,
@@ -160,14 +284,31 @@ impl App {
 
             clock,
             limiter,
+            frame_limiter: FrameLimiter::new(),
             cfg,
             show_preferences: false,
 
+            layouts,
+            new_layout_name: String::new(),
+
             ide,
         }
     }
 
     pub fn try_tick(&mut self, events: &ActiveEventLoop) {
+        let pacing = self.main.pacing();
+
+        // Event loop sleeps until limiter's deadline,
+        // so only the spin part is left to wait here.
+        // Events that arrive earlier wait for the frame like ticks do.
+        if self
+            .limiter
+            .next_tick()
+            .map_or(false, |next| next <= self.clock.now())
+        {
+            self.frame_limiter.wait(&pacing);
+        }
+
         let step = self.clock.step();
 
         let ticks = self.limiter.ticks(step.step);
@@ -178,7 +319,37 @@ impl App {
 
         filter_subprocesses();
 
-        let until = self.clock.stamp_instant(self.limiter.next_tick().unwrap());
+        self.update_cursors(events);
+
+        let mut next = self.limiter.next_tick().unwrap();
+
+        if self.cfg.low_power || self.cfg.reactive {
+            let mut all_idle = true;
+
+            for view in &self.views {
+                if !view.is_idle() {
+                    all_idle = false;
+                }
+
+                if !view.redraws_on_demand(&self.cfg) {
+                    continue;
+                }
+
+                if self.main.has_changes() || self.ui.needs_repaint(&view.viewport) {
+                    view.window.request_redraw();
+                }
+            }
+
+            // Wake up few times per second to check for changes.
+            if self.cfg.low_power && all_idle {
+                next = next.max(self.clock.now() + TimeSpan::MILLISECOND * 250);
+            }
+        }
+
+        let mut until = self.clock.stamp_instant(next);
+        if let Some(deadline) = self.frame_limiter.deadline(&pacing) {
+            until = until.max(deadline);
+        }
         events.set_control_flow(ControlFlow::WaitUntil(until));
     }
 
@@ -192,16 +363,62 @@ impl App {
             self.filters.update_plugins(&mut self.data, &c);
             self.code.update_plugins(&mut self.data, &c);
             self.rendering.update_plugins(&mut self.data, &c);
+            self.inspector.update_plugins(&c);
+            self.profiler.update_plugins(&c);
+            self.world.update_plugins(&c);
+            self.schedule.update_plugins(&c);
+            self.behavior_trees.update_plugins(&c);
+            self.param_sets.update_plugins(&c);
             self.main.update_plugins(&c);
 
             self.container = Some(c);
         }
 
         self.main.tick(&self.data, &self.systems, step);
+
+        let grab = self.main.cursor_grab();
+        for view in &mut self.views {
+            let grabbed = grab == Some(view.window.id());
+            if view.cursor_grabbed != grabbed {
+                set_window_cursor_grab(&view.window, grabbed);
+                view.cursor_grabbed = grabbed;
+            }
+        }
+    }
+
+    /// Applies cursor appearance requested by the game
+    /// to windows where cursor hovers game view.
+    fn update_cursors(&mut self, events: &ActiveEventLoop) {
+        let mut cursor_override = false;
+
+        for view in &mut self.views {
+            match self.main.hovered_cursor_appearance(view.window.id()) {
+                None => view.cursor_version = None,
+                Some(appearance) => {
+                    cursor_override = true;
+
+                    if view.cursor_version != Some(appearance.version()) {
+                        apply_cursor_appearance(&view.window, events, &appearance);
+                        view.cursor_version = Some(appearance.version());
+                    }
+                }
+            }
+        }
+
+        self.ui.set_cursor_override(cursor_override);
     }
 
     /// Runs rendering.
     pub fn handle_event(&mut self, window_id: WindowId, event: &WindowEvent) {
+        if self.input_maps.capture(event) {
+            for view in &self.views {
+                if view.window.id() == window_id {
+                    view.window.request_redraw();
+                }
+            }
+            return;
+        }
+
         if self.main.handle_event(&self.data, window_id, event) {
             return;
         }
@@ -214,6 +431,9 @@ impl App {
 
                 self.ui
                     .handle_event(&mut view.viewport, &mut self.clipboard, &event);
+
+                // Any input wakes up idle window.
+                view.window.request_redraw();
                 break;
             }
         }
@@ -231,6 +451,24 @@ impl App {
                     &view.window,
                     self.clock.now(),
                     |cx, textures| {
+                        let mut undo = false;
+                        let mut redo = false;
+
+                        cx.input_mut(|input| {
+                            // Check longer shortcut first, since plain Ctrl+Z matches it too.
+                            redo |= input.consume_shortcut(&KeyboardShortcut::new(
+                                Modifiers::COMMAND | Modifiers::SHIFT,
+                                Key::Z,
+                            ));
+                            undo |= input.consume_shortcut(&KeyboardShortcut::new(
+                                Modifiers::COMMAND,
+                                Key::Z,
+                            ));
+                        });
+
+                        let mut apply_layout = None;
+                        let mut save_layout = None;
+
                         let tabs = view.dock_state.main_surface_mut();
                         TopBottomPanel::top("Menu").show(cx, |ui| {
                             ui.horizontal(|ui| {
@@ -245,15 +483,34 @@ impl App {
                                         ui.close_menu();
                                     }
                                 });
+                                ui.menu_button("Edit", |ui| {
+                                    let r = ui.add_enabled(
+                                        self.history.can_undo(),
+                                        egui::Button::new("Undo").shortcut_text("Ctrl+Z"),
+                                    );
+                                    if r.clicked() {
+                                        undo = true;
+                                        ui.close_menu();
+                                    }
+
+                                    let r = ui.add_enabled(
+                                        self.history.can_redo(),
+                                        egui::Button::new("Redo").shortcut_text("Ctrl+Shift+Z"),
+                                    );
+                                    if r.clicked() {
+                                        redo = true;
+                                        ui.close_menu();
+                                    }
+                                });
                                 ui.menu_button("View", |ui| {
                                     if ui.button("Plugins").clicked() {
                                         focus_or_add_tab(tabs, Tab::Plugins);
                                         ui.close_menu();
                                     }
-                                    // if ui.button("Console").clicked() {
-                                    //     focus_or_add_tab(tabs, Tab::Console);
-                                    //     ui.close_menu();
-                                    // }
+                                    if ui.button("Console").clicked() {
+                                        focus_or_add_tab(tabs, Tab::Console);
+                                        ui.close_menu();
+                                    }
                                     if ui.button("Codes").clicked() {
                                         focus_or_add_tab(tabs, Tab::Codes);
                                         ui.close_menu();
@@ -270,25 +527,157 @@ impl App {
                                         focus_or_add_tab(tabs, Tab::Rendering);
                                         ui.close_menu();
                                     }
+                                    if ui.button("Inspector").clicked() {
+                                        focus_or_add_tab(tabs, Tab::Inspector);
+                                        ui.close_menu();
+                                    }
+                                    if ui.button("Scene").clicked() {
+                                        focus_or_add_tab(tabs, Tab::Scene);
+                                        ui.close_menu();
+                                    }
+                                    if ui.button("Assets").clicked() {
+                                        focus_or_add_tab(tabs, Tab::Assets);
+                                        ui.close_menu();
+                                    }
+                                    if ui.button("Profiler").clicked() {
+                                        focus_or_add_tab(tabs, Tab::Profiler);
+                                        ui.close_menu();
+                                    }
+                                    if ui.button("World").clicked() {
+                                        focus_or_add_tab(tabs, Tab::World);
+                                        ui.close_menu();
+                                    }
+                                    if ui.button("Schedule").clicked() {
+                                        focus_or_add_tab(tabs, Tab::Schedule);
+                                        ui.close_menu();
+                                    }
+                                    if ui.button("Logs").clicked() {
+                                        focus_or_add_tab(tabs, Tab::Logs);
+                                        ui.close_menu();
+                                    }
+                                    if ui.button("Input Maps").clicked() {
+                                        focus_or_add_tab(tabs, Tab::InputMaps);
+                                        ui.close_menu();
+                                    }
+                                    if ui.button("Behavior Trees").clicked() {
+                                        focus_or_add_tab(tabs, Tab::BehaviorTrees);
+                                        ui.close_menu();
+                                    }
+                                    if ui.button("Parameters").clicked() {
+                                        focus_or_add_tab(tabs, Tab::ParamSets);
+                                        ui.close_menu();
+                                    }
                                     // if ui.button("Main").clicked() {
                                     //     focus_or_add_tab(tabs, Tab::Main);
                                     //     ui.close_menu();
                                     // }
                                 });
+                                ui.menu_button("Layout", |ui| {
+                                    for preset in LayoutPreset::ALL {
+                                        if ui.button(preset.name()).clicked() {
+                                            apply_layout = Some(preset.dock_state());
+                                            ui.close_menu();
+                                        }
+                                    }
+
+                                    if !self.layouts.is_empty() {
+                                        ui.separator();
+                                    }
+
+                                    let mut remove = None;
+                                    for (name, layout) in &self.layouts {
+                                        ui.horizontal(|ui| {
+                                            if ui.button(name).clicked() {
+                                                apply_layout = Some(layout.clone());
+                                                ui.close_menu();
+                                            }
+                                            if ui
+                                                .small_button(egui_phosphor::regular::TRASH)
+                                                .on_hover_text("Remove layout")
+                                                .clicked()
+                                            {
+                                                remove = Some(name.clone());
+                                            }
+                                        });
+                                    }
+
+                                    if let Some(name) = remove {
+                                        self.layouts.remove(&name);
+                                        if let Err(err) =
+                                            save_layouts(&self.layouts, &self.project.name())
+                                        {
+                                            tracing::error!("Failed to save layouts: {err:?}");
+                                        }
+                                    }
+
+                                    ui.separator();
+
+                                    ui.horizontal(|ui| {
+                                        ui.add(
+                                            egui::TextEdit::singleline(&mut self.new_layout_name)
+                                                .hint_text("Layout name")
+                                                .desired_width(120.0),
+                                        );
+
+                                        let r = ui.add_enabled(
+                                            !self.new_layout_name.is_empty(),
+                                            egui::Button::new("Save"),
+                                        );
+                                        if r.clicked() {
+                                            save_layout =
+                                                Some(std::mem::take(&mut self.new_layout_name));
+                                            ui.close_menu();
+                                        }
+                                    });
+                                });
                             });
                         });
 
+                        if let Some(name) = save_layout {
+                            self.layouts.insert(name, view.dock_state.clone());
+                            if let Err(err) = save_layouts(&self.layouts, &self.project.name()) {
+                                tracing::error!("Failed to save layouts: {err:?}");
+                            }
+                        }
+
+                        if let Some(layout) = apply_layout {
+                            view.dock_state = layout;
+                        }
+
+                        let restored = if redo {
+                            self.history.redo(&mut self.data)
+                        } else if undo {
+                            self.history.undo(&mut self.data)
+                        } else {
+                            false
+                        };
+
+                        if restored {
+                            self.systems.invalidate();
+                            try_log_err!(self.data.sync(&self.project));
+                        }
+
                         let mut model = AppModel {
                             window: &view.window,
                             linked: self.container.as_ref(),
                             project: &mut self.project,
                             data: &mut self.data,
                             plugins: &mut self.plugins,
-                            // console: &mut self.console,
+                            console: &mut self.console,
                             systems: &mut self.systems,
                             filters: &mut self.filters,
                             code: &mut self.code,
                             rendering: &mut self.rendering,
+                            inspector: &mut self.inspector,
+                            scene: &mut self.scene,
+                            profiler: &mut self.profiler,
+                            world: &mut self.world,
+                            schedule: &mut self.schedule,
+                            logs: &mut self.logs,
+                            input_maps: &mut self.input_maps,
+                            behavior_trees: &mut self.behavior_trees,
+                            param_sets: &mut self.param_sets,
+                            assets: &mut self.assets,
                             main: &mut self.main,
                             sample: &self.image_sample,
                             device: &device,
@@ -298,6 +687,10 @@ impl App {
 
                         egui_dock::DockArea::new(&mut view.dock_state).show(cx, &mut model);
 
+                        // Keep slider and node drags in single undo step.
+                        let dragging = cx.input(|input| input.pointer.any_down());
+                        self.history.record(&self.data, dragging);
+
                         if self.show_preferences {
                             egui::Window::new("Preferences")
                                 .collapsible(false)
@@ -320,7 +713,10 @@ impl App {
                     },
                 );
 
-                view.window.request_redraw();
+                // Such windows are woken up by `try_tick` when needed.
+                if !view.redraws_on_demand(&self.cfg) {
+                    view.window.request_redraw();
+                }
 
                 break;
             }
@@ -329,6 +725,14 @@ impl App {
 
     /// Runs rendering.
     pub fn render(&mut self, window_id: WindowId) {
+        // Game views are re-rendered only when world changes.
+        // Skipping is done for whole view work graph, not per pass,
+        // so any change re-encodes all passes of the view.
+        let idle = self
+            .views
+            .iter()
+            .all(|view| view.redraws_on_demand(&self.cfg));
+
         for view in &mut self.views {
             if view.window.id() == window_id {
                 // let mut render_view = |view: &mut AppView| {
@@ -359,7 +763,7 @@ impl App {
         }
 
         self.main
-            .render(&mut self.queue, &self.data, &mut self.ui.textures())
+            .render(&mut self.queue, &self.data, &mut self.ui.textures(), idle)
             .unwrap();
     }
 
@@ -421,12 +825,7 @@ impl App {
                         window.scale_factor() as f32,
                     );
 
-                    let view = AppView {
-                        window,
-                        surface: None,
-                        dock_state: view.dock_state.into_owned(),
-                        viewport,
-                    };
+                    let view = AppView::new(window, view.dock_state.into_owned(), viewport);
 
                     self.views.push(view);
                 }
@@ -450,12 +849,34 @@ impl App {
                 window.scale_factor() as f32,
             );
 
-            self.views.push(AppView {
-                window,
-                surface: None,
-                dock_state: DockState::new(vec![]),
-                viewport,
-            });
+            self.views
+                .push(AppView::new(window, DockState::new(vec![]), viewport));
+        }
+    }
+}
+
+fn apply_cursor_appearance(
+    window: &Window,
+    events: &ActiveEventLoop,
+    appearance: &CursorAppearance,
+) {
+    window.set_cursor_visible(appearance.is_visible());
+
+    match appearance.shape() {
+        CursorShape::Icon(icon) => window.set_cursor(*icon),
+        CursorShape::Image(image) => {
+            let source = CustomCursor::from_rgba(
+                image.rgba.clone(),
+                image.width,
+                image.height,
+                image.hotspot_x,
+                image.hotspot_y,
+            );
+
+            match source {
+                Ok(source) => window.set_cursor(events.create_custom_cursor(source)),
+                Err(err) => tracing::error!("Failed to create custom cursor: {err}"),
+            }
         }
     }
 }
@@ -478,6 +899,55 @@ fn focus_or_add_tab(tabs: &mut Tree<Tab>, tab: Tab) {
     }
 }
 
+/// Built-in layouts of editor panels.
+#[derive(Clone, Copy)]
+enum LayoutPreset {
+    Code,
+    Scene,
+    Profiling,
+}
+
+impl LayoutPreset {
+    const ALL: [Self; 3] = [Self::Code, Self::Scene, Self::Profiling];
+
+    fn name(&self) -> &'static str {
+        match self {
+            LayoutPreset::Code => "Code",
+            LayoutPreset::Scene => "Scene",
+            LayoutPreset::Profiling => "Profiling",
+        }
+    }
+
+    fn dock_state(&self) -> DockState<Tab> {
+        match self {
+            LayoutPreset::Code => {
+                let mut state = DockState::new(vec![Tab::Codes, Tab::Systems]);
+                let tree = state.main_surface_mut();
+                let [main, _] =
+                    tree.split_right(NodeIndex::root(), 0.75, vec![Tab::Inspector, Tab::World]);
+                tree.split_below(main, 0.75, vec![Tab::Console, Tab::Logs]);
+                state
+            }
+            LayoutPreset::Scene => {
+                let mut state = DockState::new(vec![Tab::Scene]);
+                let tree = state.main_surface_mut();
+                let [main, _] =
+                    tree.split_right(NodeIndex::root(), 0.75, vec![Tab::Inspector, Tab::World]);
+                tree.split_below(main, 0.7, vec![Tab::Assets, Tab::Console]);
+                state
+            }
+            LayoutPreset::Profiling => {
+                let mut state = DockState::new(vec![Tab::Scene]);
+                let tree = state.main_surface_mut();
+                let [main, _] =
+                    tree.split_right(NodeIndex::root(), 0.75, vec![Tab::Schedule, Tab::Systems]);
+                tree.split_below(main, 0.5, vec![Tab::Profiler]);
+                state
+            }
+        }
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 struct AppViewState<'a> {
     pos: dpi::LogicalPosition<f64>,
@@ -497,11 +967,21 @@ struct AppModel<'a> {
     project: &'a mut Project,
     data: &'a mut ProjectData,
     plugins: &'a mut Plugins,
-    // console: &'a mut Console,
+    console: &'a mut Console,
     systems: &'a mut Systems,
     filters: &'a mut Filters,
     code: &'a mut CodeTool,
     rendering: &'a mut Rendering,
+    inspector: &'a mut Inspector,
+    scene: &'a mut SceneView,
+    profiler: &'a mut Profiler,
+    world: &'a mut WorldBrowser,
+    schedule: &'a mut ScheduleView,
+    logs: &'a mut Logs,
+    input_maps: &'a mut InputMaps,
+    behavior_trees: &'a mut BehaviorTrees,
+    param_sets: &'a mut ParamSets,
+    assets: &'a mut Assets,
     main: &'a mut Instance,
     sample: &'a ImageSample,
     device: &'a mev::Device,
@@ -518,11 +998,16 @@ impl TabViewer for AppModel<'_> {
 
     fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Tab) {
         match *tab {
-            Tab::Plugins => self.plugins.show(self.linked, self.project, self.data, ui),
-            // Tab::Console => self.console.show(ui),
+            Tab::Plugins => {
+                self.plugins
+                    .show(self.linked, self.project, self.data, self.systems, ui)
+            }
+            Tab::Console => self.console.show(self.main, ui),
             Tab::Systems => self.systems.show(self.project, self.data, self.ide, ui),
             Tab::Filters => self.filters.show(self.project, self.data, self.ide, ui),
-            Tab::Codes => self.code.show(self.project, self.data, ui),
+            Tab::Codes => self
+                .code
+                .show(self.project, self.data, self.main.code_trace_mut(), ui),
             Tab::Rendering => self.rendering.show(
                 self.project,
                 self.data,
@@ -534,29 +1019,72 @@ impl TabViewer for AppModel<'_> {
                 ui,
             ),
             // Tab::Main => self.main.show(self.window.id(), &mut self.textures, ui),
-            Tab::Inspector => {} //Inspector::show(self.world, ui),
+            Tab::Inspector => self.inspector.show(self.main, ui),
+            Tab::Scene => {
+                let mut selected = self.inspector.selected();
+                self.scene.show(
+                    self.main,
+                    &mut selected,
+                    self.window.id(),
+                    &mut self.textures,
+                    ui,
+                );
+                self.inspector.select(selected);
+            }
+            Tab::Assets => self.assets.show(ui),
+            Tab::Profiler => self.profiler.show(self.main, ui),
+            Tab::World => {
+                let mut selected = self.inspector.selected();
+                self.world.show(self.main, &mut selected, ui);
+                self.inspector.select(selected);
+            }
+            Tab::Schedule => {
+                self.schedule
+                    .show(self.project, self.data, self.systems, self.main, ui)
+            }
+            Tab::Logs => self.logs.show(self.project, self.ide, ui),
+            Tab::InputMaps => self.input_maps.show(self.project, ui),
+            Tab::BehaviorTrees => self.behavior_trees.show(self.project, ui),
+            Tab::ParamSets => self.param_sets.show(self.project, ui),
         }
     }
 
     fn title(&mut self, tab: &mut Tab) -> WidgetText {
         match *tab {
             Tab::Plugins => "Plugins".into(),
-            // Tab::Console => "Console".into(),
+            Tab::Console => "Console".into(),
             Tab::Systems => "Systems".into(),
             Tab::Filters => "Filters".into(),
             Tab::Codes => "Codes".into(),
             Tab::Rendering => "Rendering".into(),
             // Tab::Main => "Main".into(),
             Tab::Inspector => "Inspector".into(),
+            Tab::Scene => "Scene".into(),
+            Tab::Assets => "Assets".into(),
+            Tab::Profiler => "Profiler".into(),
+            Tab::World => "World".into(),
+            Tab::Schedule => "Schedule".into(),
+            Tab::Logs => "Logs".into(),
+            Tab::InputMaps => "Input Maps".into(),
+            Tab::BehaviorTrees => "Behavior Trees".into(),
+            Tab::ParamSets => "Parameters".into(),
         }
     }
 
     fn scroll_bars(&self, tab: &Tab) -> [bool; 2] {
         match tab {
-            // Tab::Console => [false, false],
+            Tab::Console => [false, false],
             Tab::Systems => [false, false],
             Tab::Codes => [false, false],
             Tab::Rendering => [false, false],
+            Tab::Scene => [false, false],
+            Tab::Assets => [false, false],
+            Tab::World => [false, false],
+            Tab::Schedule => [false, false],
+            Tab::Logs => [false, false],
+            Tab::InputMaps => [false, false],
+            Tab::BehaviorTrees => [false, false],
+            Tab::ParamSets => [false, false],
             _ => [true, true],
         }
     }
@@ -601,6 +1129,31 @@ fn save_app_state(state: &AppState, name: &str) -> miette::Result<()> {
     Ok(())
 }
 
+fn layouts_path(create: bool, name: &str) -> Option<PathBuf> {
+    let mut path = app_state_path(create, name)?;
+    path.set_file_name("layouts.bin");
+    Some(path)
+}
+
+fn load_layouts(name: &str) -> miette::Result<BTreeMap<String, DockState<Tab>>> {
+    let path =
+        layouts_path(true, name).ok_or_else(|| miette::miette!("Failed to get layouts path"))?;
+
+    let mut file = std::fs::File::open(path).into_diagnostic()?;
+
+    let layouts = bincode::deserialize_from(&mut file).into_diagnostic()?;
+
+    Ok(layouts)
+}
+
+fn save_layouts(layouts: &BTreeMap<String, DockState<Tab>>, name: &str) -> miette::Result<()> {
+    let path =
+        layouts_path(true, name).ok_or_else(|| miette::miette!("Failed to get layouts path"))?;
+    let mut file = std::fs::File::create(path).into_diagnostic()?;
+    bincode::serialize_into(&mut file, layouts).into_diagnostic()?;
+    Ok(())
+}
+
 fn app_cfg_path(create: bool) -> Option<PathBuf> {
     let mut path = match dirs::config_dir() {
         None => {
@@ -671,12 +1224,29 @@ impl winit::application::ApplicationHandler<UserEvent> for App {
                 self.update_ui(window_id);
                 self.render(window_id);
             }
+            WindowEvent::Focused(focused) => {
+                if let Some(view) = self.views.iter_mut().find(|v| v.window.id() == window_id) {
+                    view.focused = focused;
+                    view.window.request_redraw();
+                }
+            }
+            WindowEvent::Occluded(occluded) => {
+                if let Some(view) = self.views.iter_mut().find(|v| v.window.id() == window_id) {
+                    view.occluded = occluded;
+                    view.window.request_redraw();
+                }
+            }
             _ => {}
         }
 
         self.try_tick(events);
     }
 
+    fn device_event(&mut self, events: &ActiveEventLoop, device_id: DeviceId, event: DeviceEvent) {
+        self.main.handle_device_event(&self.data, device_id, &event);
+        self.try_tick(events);
+    }
+
     fn exiting(&mut self, _events: &ActiveEventLoop) {
         self.save_state();
         kill_subprocesses();
@@ -0,0 +1,478 @@
+//! Panel that shows asset sources of the project.
+//!
+//! Directories of the asset base are shown as a tree
+//! and files of selected directory as a grid of tiles.
+//! Image sources get thumbnails, other files get an icon.
+//!
+//! Imported assets can be dragged into inspector fields that reference assets.
+
+use std::path::{Path, PathBuf};
+
+use egui::{TextureHandle, Ui};
+use hashbrown::HashMap;
+
+use super::store::{AssetEntry, Store};
+
+/// Size of a tile in the grid.
+const TILE_SIZE: f32 = 80.0;
+
+/// Size of thumbnail images.
+const THUMBNAIL_SIZE: u32 = 64;
+
+/// Number of thumbnails decoded per frame to keep UI responsive.
+const THUMBNAILS_PER_FRAME: usize = 2;
+
+struct Dir {
+    path: PathBuf,
+    name: String,
+    children: Vec<Dir>,
+}
+
+impl Dir {
+    fn scan(path: &Path, skip: &[&Path]) -> Self {
+        let mut children = Vec::new();
+
+        if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.flatten() {
+                let child = entry.path();
+                if child.is_dir() && !skip.contains(&child.as_path()) {
+                    children.push(Dir::scan(&child, skip));
+                }
+            }
+        }
+
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Dir {
+            name: path
+                .file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().into_owned()),
+            path: path.to_owned(),
+            children,
+        }
+    }
+}
+
+/// Action requested from context menu.
+enum Action {
+    Reimport(PathBuf),
+    Reveal(PathBuf),
+    Delete(PathBuf),
+}
+
+pub struct Browser {
+    /// Directory tree of asset sources.
+    root: Option<Dir>,
+
+    /// Assets imported from each source file.
+    assets: HashMap<PathBuf, Vec<AssetEntry>>,
+
+    /// Assets from sources outside of base directory.
+    external: Vec<AssetEntry>,
+
+    current: Option<PathBuf>,
+    selected: Option<PathBuf>,
+
+    /// Decoded thumbnails.
+    /// `None` for files that are not images.
+    thumbnails: HashMap<PathBuf, Option<TextureHandle>>,
+
+    /// Source waiting for delete confirmation.
+    confirm_delete: Option<PathBuf>,
+}
+
+impl Browser {
+    pub fn new() -> Self {
+        Browser {
+            root: None,
+            assets: HashMap::new(),
+            external: Vec::new(),
+            current: None,
+            selected: None,
+            thumbnails: HashMap::new(),
+            confirm_delete: None,
+        }
+    }
+
+    /// Rescans sources and assets.
+    pub fn rescan(&mut self, store: &Store) {
+        let skip = [store.artifacts_base(), store.external()];
+        self.root = Some(Dir::scan(store.base(), &skip));
+
+        self.assets.clear();
+        self.external.clear();
+
+        for entry in store.entries() {
+            match entry.source.to_file_path() {
+                Ok(path) if path.starts_with(store.base()) => {
+                    self.assets.entry(path).or_default().push(entry);
+                }
+                _ => self.external.push(entry),
+            }
+        }
+
+        for entries in self.assets.values_mut() {
+            entries.sort_by_key(|e| e.target);
+        }
+
+        self.thumbnails.clear();
+    }
+
+    pub fn show(&mut self, store: &Store, ui: &mut Ui) {
+        if self.root.is_none() {
+            self.rescan(store);
+            self.current = Some(store.base().to_owned());
+        }
+
+        let mut action = None;
+
+        ui.horizontal(|ui| {
+            if ui
+                .button(egui_phosphor::regular::ARROWS_CLOCKWISE)
+                .on_hover_text("Rescan")
+                .clicked()
+            {
+                self.rescan(store);
+            }
+
+            if let Some(current) = &self.current {
+                let relative = current.strip_prefix(store.base()).unwrap_or(current);
+                ui.label(relative.display().to_string());
+            }
+        });
+
+        egui::SidePanel::left("asset-dirs")
+            .resizable(true)
+            .show_inside(ui, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if let Some(root) = &self.root {
+                        show_dir(root, &mut self.current, true, ui);
+                    }
+
+                    if !self.external.is_empty() {
+                        let r = ui.selectable_label(self.current.is_none(), "External");
+                        if r.clicked() {
+                            self.current = None;
+                        }
+                    }
+                });
+            });
+
+        if self.selected.is_some() {
+            egui::TopBottomPanel::bottom("asset-details")
+                .resizable(true)
+                .show_inside(ui, |ui| self.show_details(ui));
+        }
+
+        egui::ScrollArea::vertical().show(ui, |ui| match self.current.clone() {
+            None => show_external(&self.external, ui),
+            Some(dir) => self.show_grid(&dir, &mut action, ui),
+        });
+
+        self.show_confirm_delete(store, ui);
+
+        match action {
+            None => {}
+            Some(Action::Reimport(path)) => {
+                for entry in self.assets.get(&path).into_iter().flatten() {
+                    if let Err(err) = futures::executor::block_on(store.reimport(entry)) {
+                        tracing::error!(
+                            "Failed to reimport '{}' as '{}'. {:#}",
+                            entry.source,
+                            entry.target,
+                            err
+                        );
+                    }
+                }
+                self.thumbnails.remove(&path);
+            }
+            Some(Action::Reveal(path)) => reveal(&path),
+            Some(Action::Delete(path)) => self.confirm_delete = Some(path),
+        }
+    }
+
+    fn show_grid(&mut self, dir: &Path, action: &mut Option<Action>, ui: &mut Ui) {
+        let mut files = Vec::new();
+        if let Ok(entries) = std::fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_file() && !Store::is_meta_file(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+
+        let mut decoded = 0;
+
+        ui.horizontal_wrapped(|ui| {
+            for path in files {
+                if !self.thumbnails.contains_key(&path) && decoded < THUMBNAILS_PER_FRAME {
+                    let thumbnail = load_thumbnail(ui.ctx(), &path);
+                    self.thumbnails.insert(path.clone(), thumbnail);
+                    decoded += 1;
+                }
+
+                let assets = self.assets.get(&path).map_or(&[][..], |a| &a[..]);
+
+                let id = ui.id().with(&path);
+
+                let tile = |ui: &mut Ui| {
+                    show_tile(
+                        &path,
+                        self.thumbnails.get(&path).and_then(Option::as_ref),
+                        self.selected.as_ref() == Some(&path),
+                        ui,
+                    )
+                };
+
+                // Sources with single asset can be dragged as a whole.
+                let r = match assets {
+                    [single] => ui.dnd_drag_source(id, single.id, tile).inner,
+                    _ => tile(ui),
+                };
+
+                if r.clicked() {
+                    self.selected = Some(path.clone());
+                }
+
+                let r = r.on_hover_ui(|ui| {
+                    if assets.is_empty() {
+                        ui.weak("Not imported");
+                    }
+                    for entry in assets {
+                        ui.label(format!("{} ({})", entry.target, entry.id));
+                    }
+                });
+
+                r.context_menu(|ui| {
+                    if ui
+                        .add_enabled(!assets.is_empty(), egui::Button::new("Reimport"))
+                        .clicked()
+                    {
+                        *action = Some(Action::Reimport(path.clone()));
+                        ui.close_menu();
+                    }
+                    if ui.button("Reveal in file manager").clicked() {
+                        *action = Some(Action::Reveal(path.clone()));
+                        ui.close_menu();
+                    }
+                    if ui.button("Delete").clicked() {
+                        *action = Some(Action::Delete(path.clone()));
+                        ui.close_menu();
+                    }
+                });
+            }
+        });
+
+        if decoded == THUMBNAILS_PER_FRAME {
+            ui.ctx().request_repaint();
+        }
+    }
+
+    /// Shows assets of selected source.
+    fn show_details(&mut self, ui: &mut Ui) {
+        let Some(path) = &self.selected else {
+            return;
+        };
+
+        ui.strong(
+            path.file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().into_owned()),
+        );
+
+        match self.assets.get(path) {
+            None => {
+                ui.weak("Source is not imported");
+            }
+            Some(entries) => {
+                for entry in entries {
+                    show_entry(entry, ui);
+                }
+            }
+        }
+    }
+
+    fn show_confirm_delete(&mut self, store: &Store, ui: &mut Ui) {
+        let Some(path) = self.confirm_delete.clone() else {
+            return;
+        };
+
+        let mut close = false;
+
+        egui::Window::new("Delete asset source")
+            .collapsible(false)
+            .resizable(false)
+            .show(ui.ctx(), |ui| {
+                ui.label(format!("Delete '{}'?", path.display()));
+                ui.weak("Source file, its metadata and imported artifacts will be removed");
+
+                ui.horizontal(|ui| {
+                    if ui.button("Delete").clicked() {
+                        if let Err(err) = store.delete_source(&path) {
+                            tracing::error!("Failed to delete '{}'. {:#}", path.display(), err);
+                        }
+                        if self.selected.as_ref() == Some(&path) {
+                            self.selected = None;
+                        }
+                        self.rescan(store);
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if close {
+            self.confirm_delete = None;
+        }
+    }
+}
+
+fn show_dir(dir: &Dir, current: &mut Option<PathBuf>, root: bool, ui: &mut Ui) {
+    let selected = current.as_ref() == Some(&dir.path);
+    let name = if root { "Assets" } else { dir.name.as_str() };
+
+    if dir.children.is_empty() {
+        if ui.selectable_label(selected, name).clicked() {
+            *current = Some(dir.path.clone());
+        }
+        return;
+    }
+
+    let id = ui.make_persistent_id(&dir.path);
+    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, root)
+        .show_header(ui, |ui| {
+            if ui.selectable_label(selected, name).clicked() {
+                *current = Some(dir.path.clone());
+            }
+        })
+        .body(|ui| {
+            for child in &dir.children {
+                show_dir(child, current, false, ui);
+            }
+        });
+}
+
+fn show_tile(
+    path: &Path,
+    thumbnail: Option<&TextureHandle>,
+    selected: bool,
+    ui: &mut Ui,
+) -> egui::Response {
+    let name = path
+        .file_name()
+        .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+
+    let frame = egui::Frame::none()
+        .inner_margin(egui::Margin::same(4.0))
+        .rounding(egui::Rounding::same(4.0))
+        .fill(if selected {
+            ui.visuals().selection.bg_fill
+        } else {
+            egui::Color32::TRANSPARENT
+        });
+
+    let r = frame.show(ui, |ui| {
+        ui.set_width(TILE_SIZE);
+        ui.vertical_centered(|ui| {
+            let size = egui::Vec2::splat(THUMBNAIL_SIZE as f32);
+            match thumbnail {
+                Some(texture) => {
+                    ui.add(egui::Image::new(texture).fit_to_exact_size(size));
+                }
+                None => {
+                    ui.add_sized(
+                        size,
+                        egui::Label::new(
+                            egui::RichText::new(egui_phosphor::regular::FILE).size(40.0),
+                        ),
+                    );
+                }
+            }
+            ui.add(egui::Label::new(name).truncate());
+        });
+    });
+
+    r.response.interact(egui::Sense::click())
+}
+
+fn show_entry(entry: &AssetEntry, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        ui.dnd_drag_source(ui.id().with(entry.id), entry.id, |ui| {
+            ui.label(format!("{} {}", egui_phosphor::regular::CUBE, entry.target));
+        });
+
+        ui.weak(format!("{}", entry.id));
+
+        if let Some(format) = &entry.format {
+            ui.weak(format!("from {format}"));
+        }
+
+        if !entry.artifact.is_file() {
+            ui.colored_label(ui.visuals().warn_fg_color, "not imported");
+        }
+    });
+}
+
+fn show_external(entries: &[AssetEntry], ui: &mut Ui) {
+    if entries.is_empty() {
+        ui.weak("No external assets");
+    }
+
+    for entry in entries {
+        ui.horizontal(|ui| {
+            show_entry(entry, ui);
+            ui.weak(entry.source.as_str());
+        });
+    }
+}
+
+/// Decodes thumbnail of the image source.
+fn load_thumbnail(cx: &egui::Context, path: &Path) -> Option<TextureHandle> {
+    // Only formats `image` crate recognizes have previews.
+    image::ImageFormat::from_path(path).ok()?;
+
+    let image = match image::open(path) {
+        Ok(image) => image,
+        Err(err) => {
+            tracing::debug!("Failed to decode thumbnail of '{}'. {err}", path.display());
+            return None;
+        }
+    };
+
+    let thumbnail = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgba8();
+    let size = [thumbnail.width() as usize, thumbnail.height() as usize];
+    let image = egui::ColorImage::from_rgba_unmultiplied(size, thumbnail.as_raw());
+
+    Some(cx.load_texture(
+        path.display().to_string(),
+        image,
+        egui::TextureOptions::LINEAR,
+    ))
+}
+
+/// Shows file in system file manager.
+fn reveal(path: &Path) {
+    #[cfg(target_os = "windows")]
+    let result = std::process::Command::new("explorer")
+        .arg("/select,")
+        .arg(path)
+        .spawn();
+
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open")
+        .arg("-R")
+        .arg(path)
+        .spawn();
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let result = std::process::Command::new("xdg-open")
+        .arg(path.parent().unwrap_or(path))
+        .spawn();
+
+    if let Err(err) = result {
+        tracing::error!("Failed to reveal '{}'. {err}", path.display());
+    }
+}
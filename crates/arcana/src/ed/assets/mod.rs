@@ -1,24 +1,54 @@
 use std::path::Path;
 
 use egui::Ui;
+use miette::IntoDiagnostic;
 
+use crate::assets::{archive::ArchiveWriter, import::Importer};
+
+mod browser;
 mod store;
 
-use store::{Store, StoreInfo};
+use self::{
+    browser::Browser,
+    store::{Store, StoreInfo},
+};
 
 /// Assets viewer.
 pub struct Assets {
     store: Store,
+    browser: Browser,
 }
 
 impl Assets {
     pub fn new(base: &Path) -> Self {
+        let store = Store::new(base, StoreInfo::default()).expect("Failed to create asset store");
+
+        // Re-import assets changed since last run and drop stale artifacts.
+        futures::executor::block_on(store.refresh());
+
         Self {
-            store: Store::new(base, StoreInfo::default()).expect("Failed to create asset store"),
+            store,
+            browser: Browser::new(),
         }
     }
 
+    pub fn register_importer(&mut self, importer: Box<dyn Importer>) {
+        self.store.register_importer(importer);
+    }
+
+    /// Re-imports assets changed since last import.
+    /// Returns number of assets that failed to import.
+    pub fn refresh(&self) -> usize {
+        futures::executor::block_on(self.store.refresh())
+    }
+
+    /// Imports all assets and packs them into archive.
+    /// Returns number of packed assets.
+    pub fn pack(&self, archive: &mut ArchiveWriter) -> miette::Result<usize> {
+        futures::executor::block_on(self.store.pack(archive)).into_diagnostic()
+    }
+
     pub fn show(&mut self, ui: &mut Ui) {
-        ui.label("Assets");
+        self.browser.show(&self.store, ui);
     }
 }
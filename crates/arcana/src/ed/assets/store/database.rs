@@ -0,0 +1,120 @@
+//! Local database of imported assets.
+//!
+//! Source metadata files are authoritative and committed along with sources.
+//! Database lives in the artifacts directory and records which artifacts
+//! were produced for which assets on this machine.
+//! It can be removed at any time and will be rebuilt on next refresh.
+
+use std::path::{Path, PathBuf};
+
+use hashbrown::HashMap;
+
+use crate::assets::AssetId;
+
+use super::{meta::MetaError, AssetItem};
+
+const DATABASE_FILE: &'static str = ".database.toml";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Record {
+    id: AssetId,
+
+    #[serde(flatten)]
+    item: AssetItem,
+
+    /// Artifact file name in the artifacts directory.
+    artifact: String,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct DatabaseFile {
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    assets: Vec<Record>,
+}
+
+pub struct Database {
+    path: PathBuf,
+    records: HashMap<AssetId, Record>,
+}
+
+impl Database {
+    /// Opens database in the artifacts directory.
+    ///
+    /// Missing or corrupted database is treated as empty.
+    pub fn open(artifacts: &Path) -> Self {
+        let path = artifacts.join(DATABASE_FILE);
+
+        let file = match std::fs::read_to_string(&path) {
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => DatabaseFile::default(),
+            Err(err) => {
+                tracing::error!(
+                    "Failed to read asset database '{}'. {:#}",
+                    path.display(),
+                    err
+                );
+                DatabaseFile::default()
+            }
+            Ok(data) => match toml::from_str(&data) {
+                Err(err) => {
+                    tracing::error!(
+                        "Failed to parse asset database '{}'. {:#}",
+                        path.display(),
+                        err
+                    );
+                    DatabaseFile::default()
+                }
+                Ok(file) => file,
+            },
+        };
+
+        let records = file
+            .assets
+            .into_iter()
+            .map(|record| (record.id, record))
+            .collect();
+
+        Database { path, records }
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = (AssetId, &AssetItem)> + '_ {
+        self.records.iter().map(|(id, record)| (*id, &record.item))
+    }
+
+    /// Records artifact produced for the asset.
+    pub fn insert(&mut self, id: AssetId, item: AssetItem, artifact: &Path) {
+        let artifact = artifact
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+
+        self.records.insert(id, Record { id, item, artifact });
+    }
+
+    /// Keeps only records for which predicate returns `true`.
+    pub fn retain(&mut self, mut f: impl FnMut(AssetId) -> bool) {
+        self.records.retain(|id, _| f(*id));
+    }
+
+    /// Writes database to the artifacts directory.
+    /// Directory must exist.
+    pub fn save(&self) -> Result<(), MetaError> {
+        let mut assets: Vec<_> = self.records.values().collect();
+        assets.sort_by_key(|record| record.id);
+
+        #[derive(serde::Serialize)]
+        struct DatabaseFileRef<'a> {
+            assets: Vec<&'a Record>,
+        }
+
+        let data = toml::to_string_pretty(&DatabaseFileRef { assets }).map_err(|error| {
+            MetaError::SerializeError {
+                error,
+                path: self.path.clone(),
+            }
+        })?;
+
+        std::fs::write(&self.path, data.as_bytes()).map_err(|error| MetaError::WriteError {
+            error,
+            path: self.path.clone(),
+        })
+    }
+}
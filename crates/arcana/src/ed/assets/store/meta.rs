@@ -3,7 +3,7 @@ use std::{
     time::SystemTime,
 };
 
-use arcana_names::Ident;
+use arcana_names::{Ident, Name};
 use arcana_project::real_path;
 use hashbrown::HashMap;
 use url::Url;
@@ -15,6 +15,7 @@ use crate::{
 
 use super::{
     content_address::{move_file_with_content_address, with_path_candidates, PREFIX_STARTING_LEN},
+    importer::Importers,
     scheme::Scheme,
 };
 
@@ -47,6 +48,23 @@ pub struct AssetMeta {
     // Maps source URL to last modified time.
     #[serde(skip_serializing_if = "HashMap::is_empty", default)]
     sources: HashMap<String, SystemTime>,
+
+    // Maps source URL to content hash.
+    // Used to skip reimport when source is touched but not changed.
+    #[serde(skip_serializing_if = "HashMap::is_empty", default)]
+    hashes: HashMap<String, Hash256>,
+
+    /// Name of the importer that produced the asset.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    importer: Option<Name>,
+
+    /// Version of the importer that produced the asset.
+    #[serde(skip_serializing_if = "version_is_default", default)]
+    importer_version: u32,
+}
+
+fn version_is_default(version: &u32) -> bool {
+    *version == 0
 }
 
 fn prefix_is_default(prefix: &u64) -> bool {
@@ -143,7 +161,9 @@ impl AssetMeta {
     pub fn new(
         id: AssetId,
         format: Option<String>,
-        sources: Vec<(String, SystemTime)>,
+        importer: Name,
+        importer_version: u32,
+        sources: Vec<(String, SystemTime, Hash256)>,
         dependencies: Vec<AssetId>,
         output: &Path,
         artifacts: &Path,
@@ -163,13 +183,24 @@ impl AssetMeta {
                 }
             })?;
 
+        let hashes = sources
+            .iter()
+            .map(|(url, _, hash)| (url.clone(), *hash))
+            .collect();
+
         Ok(AssetMeta {
             id,
             format,
             sha256,
             path_len,
-            sources: sources.into_iter().collect(),
+            sources: sources
+                .into_iter()
+                .map(|(url, modified, _)| (url, modified))
+                .collect(),
+            hashes,
             dependencies,
+            importer: Some(importer),
+            importer_version,
         })
     }
 
@@ -181,14 +212,38 @@ impl AssetMeta {
         self.format.as_deref()
     }
 
-    pub fn needs_reimport(&self, base: &Url) -> bool {
-        for (url, last_modified) in &self.sources {
-            let url = match base.join(url) {
+    /// Checks if asset must be imported again.
+    ///
+    /// This happens when artifact is missing,
+    /// importer version changed or any source content changed.
+    pub fn needs_reimport(&self, base: &Url, artifacts: &Path, importers: &Importers) -> bool {
+        if !self.artifact_path(artifacts).is_file() {
+            tracing::debug!("Artifact is missing");
+            return true;
+        }
+
+        if let Some(name) = self.importer {
+            // If importer is not registered, keep what we have.
+            if let Some(importer) = importers.get(name) {
+                if importer.version() != self.importer_version {
+                    tracing::debug!(
+                        "Importer '{}' version changed {} -> {}",
+                        name,
+                        self.importer_version,
+                        importer.version()
+                    );
+                    return true;
+                }
+            }
+        }
+
+        for (source, last_modified) in &self.sources {
+            let url = match base.join(source) {
                 Err(err) => {
                     tracing::error!(
                         "Failed to figure out source URL from base: {} and source: {}. {:#}. Asset can be outdated",
                         base,
-                        url,
+                        source,
                         err,
                     );
                     continue;
@@ -217,15 +272,30 @@ impl AssetMeta {
                         Ok(modified) => modified,
                     };
 
+                    if modified == *last_modified {
+                        continue;
+                    }
+
+                    if let Some(hash) = self.hashes.get(source) {
+                        match sha256_file(&path) {
+                            Ok(actual) if actual == *hash => {
+                                tracing::debug!("Source file was touched but content is the same");
+                                continue;
+                            }
+                            Ok(_) => {}
+                            Err(err) => {
+                                tracing::error!("Failed to hash source file. {:#}", err);
+                            }
+                        }
+                    }
+
                     if modified < *last_modified {
                         tracing::warn!("Source file is older than when asset was imported. Could be clock change. Reimort just in case");
                         return true;
                     }
 
-                    if modified > *last_modified {
-                        tracing::debug!("Source file was updated");
-                        return true;
-                    }
+                    tracing::debug!("Source file was updated");
+                    return true;
                 }
                 Ok(Scheme::Data) => continue,
                 Err(_) => tracing::error!("Unsupported scheme: '{}'", url.scheme()),
@@ -283,6 +353,13 @@ impl SourceMeta {
         &self.url
     }
 
+    /// Returns path of the metadata file for source file inside base directory.
+    pub fn local_meta_path(source_path: &Path) -> PathBuf {
+        let mut filename = source_path.file_name().unwrap_or("".as_ref()).to_owned();
+        filename.push(DOT_EXTENSION);
+        source_path.with_file_name(filename)
+    }
+
     pub fn is_local_meta_path(meta_path: &Path) -> bool {
         meta_path.extension().map_or(false, |e| e == EXTENSION)
     }
@@ -425,11 +502,7 @@ fn get_meta_path(source: &Url, base: &Path, external: &Path) -> Result<(PathBuf,
                 if path.starts_with(base) {
                     // Files inside `base` directory has meta attached to them as sibling file with `.arc` extension added.
 
-                    let mut filename = path.file_name().unwrap_or("".as_ref()).to_owned();
-                    filename.push(DOT_EXTENSION);
-
-                    let path = path.with_file_name(filename);
-                    return Ok((path, false));
+                    return Ok((SourceMeta::local_meta_path(&path), false));
                 }
             }
             Err(()) => {}
@@ -10,15 +10,20 @@ use arcana_names::{Ident, Name};
 use arcana_project::real_path;
 use futures::future::BoxFuture;
 use hashbrown::{HashMap, HashSet};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use url::Url;
 
-use crate::assets::{
-    import::{AssetDependencies, AssetSources, ImportError, Importer},
-    AssetData, AssetId, Error, Loader, NotFound,
+use crate::{
+    assets::{
+        archive::{ArchiveError, ArchiveWriter},
+        import::{AssetDependencies, AssetSources, ImportError, Importer},
+        AssetData, AssetId, Error, Loader, NotFound,
+    },
+    hash::{sha256_file, Hash256},
 };
 
 mod content_address;
+mod database;
 mod generator;
 mod importer;
 mod meta;
@@ -27,6 +32,7 @@ mod sources;
 mod temp;
 
 use self::{
+    database::Database,
     generator::Generator,
     importer::Importers,
     meta::{AssetMeta, MetaError, SourceMeta},
@@ -132,6 +138,21 @@ pub enum StoreError {
         error: std::io::Error,
         path: PathBuf,
     },
+
+    #[error("Failed to hash source '{path}'. {error}")]
+    FailedToHashSource {
+        error: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[error("Failed to read artifact '{path}'. {error}")]
+    FailedToReadArtifact {
+        error: std::io::Error,
+        path: PathBuf,
+    },
+
+    #[error(transparent)]
+    ArchiveError(ArchiveError),
 }
 
 impl Default for StoreInfo {
@@ -154,13 +175,27 @@ impl StoreInfo {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 struct AssetItem {
     source: Url,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
     format: Option<String>,
     target: Ident,
 }
 
+/// Asset described by source metadata.
+#[derive(Clone, Debug)]
+pub struct AssetEntry {
+    pub id: AssetId,
+    pub source: Url,
+    pub target: Ident,
+    pub format: Option<String>,
+
+    /// Path to imported artifact.
+    /// File may be missing if asset was not imported yet.
+    pub artifact: PathBuf,
+}
+
 pub struct Store {
     base: PathBuf,
     base_url: Url,
@@ -171,6 +206,7 @@ pub struct Store {
 
     artifacts: RwLock<HashMap<AssetId, AssetItem>>,
     scanned: RwLock<bool>,
+    database: Mutex<Database>,
     id_gen: Generator,
 }
 
@@ -201,6 +237,12 @@ impl Store {
 
         let importers = Importers::new();
 
+        let database = Database::open(&artifacts);
+        let known = database
+            .items()
+            .map(|(id, item)| (id, item.clone()))
+            .collect();
+
         Ok(Store {
             base,
             base_url,
@@ -208,12 +250,89 @@ impl Store {
             external,
             temp,
             importers,
-            artifacts: RwLock::new(HashMap::new()),
+            artifacts: RwLock::new(known),
             scanned: RwLock::new(false),
+            database: Mutex::new(database),
             id_gen: Generator::new(),
         })
     }
 
+    /// Returns directory with asset sources.
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// Returns directory where artifacts are stored.
+    pub fn artifacts_base(&self) -> &Path {
+        &self.artifacts_base
+    }
+
+    /// Returns directory with metadata of external sources.
+    pub fn external(&self) -> &Path {
+        &self.external
+    }
+
+    /// Lists all assets described by source metadata.
+    pub fn entries(&self) -> Vec<AssetEntry> {
+        let mut entries = Vec::new();
+
+        let mut add = |meta: SourceMeta| {
+            for (target, asset) in meta.assets() {
+                entries.push(AssetEntry {
+                    id: asset.id(),
+                    source: meta.url().clone(),
+                    target,
+                    format: asset.format().map(ToOwned::to_owned),
+                    artifact: asset.artifact_path(&self.artifacts_base),
+                });
+            }
+        };
+
+        scan_local(&self.base, &mut add);
+        scan_external(&self.external, &mut add);
+
+        entries
+    }
+
+    /// Imports the asset again even if it is up to date.
+    #[tracing::instrument(skip(self))]
+    pub async fn reimport(&self, entry: &AssetEntry) -> Result<(), StoreError> {
+        // Missing artifact forces importer to run.
+        if let Err(err) = std::fs::remove_file(&entry.artifact) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(
+                    "Failed to remove artifact '{}'. {:#}",
+                    entry.artifact.display(),
+                    err
+                );
+            }
+        }
+
+        self.store_from_url(entry.source.clone(), entry.target, entry.format.as_deref())
+            .await?;
+        Ok(())
+    }
+
+    /// Deletes source file inside base directory together with its metadata.
+    /// Artifacts of its assets are removed as garbage.
+    #[tracing::instrument(skip(self))]
+    pub fn delete_source(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::remove_file(path)?;
+
+        match std::fs::remove_file(SourceMeta::local_meta_path(path)) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+
+        self.collect_garbage();
+        Ok(())
+    }
+
+    /// Returns true if file is metadata of an asset source.
+    pub fn is_meta_file(path: &Path) -> bool {
+        SourceMeta::is_local_meta_path(path)
+    }
+
     /// Register importer.
     #[tracing::instrument(skip(self), fields(importer = %importer.name()))]
     pub fn register_importer(&mut self, importer: Box<dyn Importer>) {
@@ -296,7 +415,7 @@ impl Store {
                 .map_err(StoreError::MetaError)?;
 
             if let Some(asset) = meta.get_asset(item.target) {
-                if asset.needs_reimport(&self.base_url) {
+                if asset.needs_reimport(&self.base_url, &self.artifacts_base, &self.importers) {
                     tracing::debug!("'{}' as '{}' reimporting", item.source, item.target);
                 } else {
                     tracing::debug!("Found '{}' as '{}'", item.source, item.target);
@@ -470,28 +589,48 @@ impl Store {
                 }
             }
 
-            let new_id = AssetId(self.id_gen.generate());
             let item = stack.pop().unwrap();
 
+            // Reimported asset keeps its ID.
+            let new_id = match meta.get_asset(item.target) {
+                Some(asset) => asset.id(),
+                None => AssetId(self.id_gen.generate()),
+            };
+
             let make_relative_source = |source| match self.base_url.make_relative(source) {
                 None => source.to_string(),
                 Some(source) => source,
             };
 
-            let mut sources = Vec::new();
+            let hash_source = |path: &Path| {
+                sha256_file(path).map_err(|error| StoreError::FailedToHashSource {
+                    error,
+                    path: path.to_owned(),
+                })
+            };
 
-            sources.push((make_relative_source(&item.source), source_modified));
+            let mut source_stamps: Vec<(String, SystemTime, Hash256)> = Vec::new();
 
-            sources.extend(
-                item.sources
-                    .iter()
-                    .map(|(url, modified)| (make_relative_source(url), (*modified))),
-            );
+            source_stamps.push((
+                make_relative_source(&item.source),
+                source_modified,
+                hash_source(&source_path)?,
+            ));
+
+            for (url, modified) in &item.sources {
+                let hash = match sources.get(url) {
+                    Some((path, _)) => hash_source(path)?,
+                    None => continue,
+                };
+                source_stamps.push((make_relative_source(url), *modified, hash));
+            }
 
             let asset = AssetMeta::new(
                 new_id,
                 item.format.clone(),
-                sources,
+                importer.name(),
+                importer.version(),
+                source_stamps,
                 item.dependencies.into_iter().collect(),
                 &output_path,
                 artifacts_base,
@@ -504,14 +643,20 @@ impl Store {
             meta.add_asset(item.target, asset, base, external)
                 .map_err(StoreError::MetaError)?;
 
-            self.artifacts.write().insert(
-                new_id,
-                AssetItem {
-                    source: item.source,
-                    format: item.format,
-                    target: item.target,
-                },
-            );
+            let asset_item = AssetItem {
+                source: item.source,
+                format: item.format,
+                target: item.target,
+            };
+
+            let mut database = self.database.lock();
+            database.insert(new_id, asset_item.clone(), &artifact_path);
+            if let Err(err) = database.save() {
+                tracing::error!("Failed to save asset database. {:#}", err);
+            }
+            drop(database);
+
+            self.artifacts.write().insert(new_id, asset_item);
 
             if stack.is_empty() {
                 return Ok((new_id, artifact_path, latest_modified));
@@ -530,8 +675,23 @@ impl Store {
             let mut scanned = self.scanned.write();
 
             if !*scanned {
-                scan_local(&self.base, &existing_artifacts, &mut new_artifacts);
-                scan_external(&self.external, &existing_artifacts, &mut new_artifacts);
+                let mut add = |meta: SourceMeta| {
+                    for (target, asset) in meta.assets() {
+                        if !existing_artifacts.contains(&asset.id()) {
+                            new_artifacts.push((
+                                asset.id(),
+                                AssetItem {
+                                    source: meta.url().clone(),
+                                    format: asset.format().map(ToOwned::to_owned),
+                                    target,
+                                },
+                            ));
+                        }
+                    }
+                };
+
+                scan_local(&self.base, &mut add);
+                scan_external(&self.external, &mut add);
 
                 let mut artifacts = self.artifacts.write();
                 for (id, item) in new_artifacts {
@@ -592,6 +752,154 @@ impl Store {
             Some(asset) => Ok(Some(asset.id())),
         }
     }
+
+    /// Brings all known assets up to date.
+    ///
+    /// Only assets whose sources or importers changed are re-imported.
+    /// Artifacts not referenced by any asset are removed afterwards.
+    /// Returns number of assets that failed to import.
+    #[tracing::instrument(skip(self))]
+    pub async fn refresh(&self) -> usize {
+        let mut items = Vec::new();
+
+        let mut add = |meta: SourceMeta| {
+            for (target, asset) in meta.assets() {
+                items.push(AssetItem {
+                    source: meta.url().clone(),
+                    format: asset.format().map(ToOwned::to_owned),
+                    target,
+                });
+            }
+        };
+
+        scan_local(&self.base, &mut add);
+        scan_external(&self.external, &mut add);
+
+        let mut failed = 0;
+        for item in items {
+            match self
+                .store_from_url(item.source.clone(), item.target, item.format.as_deref())
+                .await
+            {
+                Err(err) => {
+                    failed += 1;
+                    tracing::error!(
+                        "Failed to refresh '{}' as '{}'. {:#}",
+                        item.source,
+                        item.target,
+                        err
+                    );
+                }
+                Ok((id, _, _)) => {
+                    self.artifacts.write().insert(id, item);
+                }
+            }
+        }
+
+        *self.scanned.write() = true;
+
+        self.collect_garbage();
+        failed
+    }
+
+    /// Imports all known assets and packs them into archive.
+    /// Returns number of packed assets.
+    #[tracing::instrument(skip(self, archive))]
+    pub async fn pack(&self, archive: &mut ArchiveWriter) -> Result<usize, StoreError> {
+        self.refresh().await;
+
+        let items: Vec<_> = self.artifacts.read().values().cloned().collect();
+
+        for item in &items {
+            let (id, path, _) = self
+                .store_from_url(item.source.clone(), item.target, item.format.as_deref())
+                .await?;
+
+            let data = std::fs::read(&path)
+                .map_err(|error| StoreError::FailedToReadArtifact { error, path })?;
+
+            archive.add(id, &data).map_err(StoreError::ArchiveError)?;
+
+            let source = match self.base_url.make_relative(&item.source) {
+                None => item.source.to_string(),
+                Some(source) => source,
+            };
+            archive.add_source(&source, item.target, id);
+        }
+
+        Ok(items.len())
+    }
+
+    /// Removes artifacts that are not referenced by any asset
+    /// and forgets assets that are no longer listed in source metadata.
+    #[tracing::instrument(skip(self))]
+    pub fn collect_garbage(&self) {
+        if !self.artifacts_base.exists() {
+            return;
+        }
+
+        let mut live_artifacts = HashSet::new();
+        let mut live_ids = HashSet::new();
+
+        let mut add = |meta: SourceMeta| {
+            for (_, asset) in meta.assets() {
+                live_artifacts.insert(asset.artifact_path(&self.artifacts_base));
+                live_ids.insert(asset.id());
+            }
+        };
+
+        scan_local(&self.base, &mut add);
+        scan_external(&self.external, &mut add);
+
+        let dir = match std::fs::read_dir(&self.artifacts_base) {
+            Err(err) => {
+                tracing::error!(
+                    "Failed to scan artifacts directory '{}'. {:#}",
+                    self.artifacts_base.display(),
+                    err
+                );
+                return;
+            }
+            Ok(dir) => dir,
+        };
+
+        for e in dir {
+            let e = match e {
+                Err(err) => {
+                    tracing::error!("Failed to read entry in artifacts directory. {:#}", err);
+                    continue;
+                }
+                Ok(e) => e,
+            };
+
+            // Skip `.gitignore` and database file.
+            if e.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            let path = e.path();
+            if !path.is_file() || live_artifacts.contains(&path) {
+                continue;
+            }
+
+            match std::fs::remove_file(&path) {
+                Ok(()) => tracing::debug!("Removed stale artifact '{}'", path.display()),
+                Err(err) => tracing::error!(
+                    "Failed to remove stale artifact '{}'. {:#}",
+                    path.display(),
+                    err
+                ),
+            }
+        }
+
+        self.artifacts.write().retain(|id, _| live_ids.contains(id));
+
+        let mut database = self.database.lock();
+        database.retain(|id| live_ids.contains(&id));
+        if let Err(err) = database.save() {
+            tracing::error!("Failed to save asset database. {:#}", err);
+        }
+    }
 }
 
 fn url_ext(url: &Url) -> Option<&str> {
@@ -610,11 +918,8 @@ fn url_ext(url: &Url) -> Option<&str> {
     Some(&path[dot + 1..])
 }
 
-fn scan_external(
-    external: &Path,
-    existing_artifacts: &HashSet<AssetId>,
-    artifacts: &mut Vec<(AssetId, AssetItem)>,
-) {
+/// Calls `f` for each source meta in external directory.
+fn scan_external(external: &Path, f: &mut impl FnMut(SourceMeta)) {
     let dir = match std::fs::read_dir(&external) {
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
             tracing::info!("External directory does not exists");
@@ -660,29 +965,13 @@ fn scan_external(
                 Ok(meta) => meta,
             };
 
-            let source = meta.url();
-
-            for (target, asset) in meta.assets() {
-                if !existing_artifacts.contains(&asset.id()) {
-                    artifacts.push((
-                        asset.id(),
-                        AssetItem {
-                            source: source.clone(),
-                            format: asset.format().map(ToOwned::to_owned),
-                            target: target.to_owned(),
-                        },
-                    ));
-                }
-            }
+            f(meta);
         }
     }
 }
 
-fn scan_local(
-    base: &Path,
-    existing_artifacts: &HashSet<AssetId>,
-    artifacts: &mut Vec<(AssetId, AssetItem)>,
-) {
+/// Calls `f` for each source meta in local directory tree.
+fn scan_local(base: &Path, f: &mut impl FnMut(SourceMeta)) {
     debug_assert!(base.is_absolute());
 
     if !base.exists() {
@@ -738,19 +1027,7 @@ fn scan_local(
                     Ok(meta) => meta,
                 };
 
-                let source = meta.url();
-                for (target, asset) in meta.assets() {
-                    if !existing_artifacts.contains(&asset.id()) {
-                        artifacts.push((
-                            asset.id(),
-                            AssetItem {
-                                source: source.clone(),
-                                format: asset.format().map(ToOwned::to_owned),
-                                target: target.to_owned(),
-                            },
-                        ));
-                    }
-                }
+                f(meta);
             }
         }
     }
@@ -783,7 +1060,9 @@ impl Loader for Store {
             match self.fetch(id).await {
                 None => Ok(None),
                 Some((path, modified)) => {
-                    if modified_to_version(modified) <= version {
+                    // Source timestamps may go backwards, e.g. after checkout,
+                    // so any difference counts as a new version.
+                    if modified_to_version(modified) == version {
                         return Ok(None);
                     }
                     let bytes = std::fs::read(&path).map_err(Error::new)?;
@@ -795,12 +1074,23 @@ impl Loader for Store {
             }
         })
     }
+
+    #[inline]
+    fn find<'a>(
+        &'a self,
+        source: &'a str,
+        target: Ident,
+    ) -> BoxFuture<'a, Result<Option<AssetId>, Error>> {
+        Box::pin(async move { self.find_asset(source, target).await.map_err(Error::new) })
+    }
 }
 
+/// Uses nanoseconds so that edits within the same second
+/// produce distinct versions.
 #[inline]
 fn modified_to_version(modified: SystemTime) -> u64 {
     modified
         .duration_since(SystemTime::UNIX_EPOCH)
         .expect("SystemTime must be after UNIX_EPOCH")
-        .as_secs()
+        .as_nanos() as u64
 }
@@ -0,0 +1,694 @@
+//! Node editor for behavior trees.
+//!
+//! Lists `.behavior` sources in project assets
+//! and edits them as a graph where each node is wired to its children.
+//! Order of children follows order of output pins.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use egui::{Color32, Ui};
+use egui_snarl::{
+    ui::{PinInfo, SnarlStyle, SnarlViewer},
+    InPin, InPinId, NodeId, OutPin, OutPinId, Snarl,
+};
+use hashbrown::HashMap;
+
+use crate::{
+    behavior::{BehaviorKind, BehaviorNode, BehaviorTree},
+    plugin::EventInfo,
+    project::Project,
+    Ident,
+};
+
+use super::container::Container;
+
+/// Extension of behavior tree sources.
+const EXTENSION: &str = "behavior";
+
+const BEHAVIOR_VIEWER_ID: &str = "behavior-viewer";
+
+/// Offset of the root marker from the root node.
+const ROOT_OFFSET: egui::Vec2 = egui::vec2(-150.0, 0.0);
+
+#[derive(Clone, Debug)]
+enum EdNode {
+    /// Marks root of the tree.
+    Root,
+
+    Node {
+        kind: BehaviorKind,
+
+        /// Number of output pins.
+        /// Composite nodes have one free pin after connected ones.
+        slots: usize,
+    },
+}
+
+impl EdNode {
+    fn new(kind: BehaviorKind) -> Self {
+        let slots = kind.max_children().map_or(1, |max| max.min(1));
+        EdNode::Node { kind, slots }
+    }
+}
+
+pub struct BehaviorTrees {
+    /// Behavior tree sources relative to assets directory.
+    files: Vec<PathBuf>,
+    scanned: bool,
+
+    selected: Option<PathBuf>,
+    snarl: Snarl<EdNode>,
+
+    /// Tree as it was last loaded or saved.
+    saved: BehaviorTree,
+
+    new_file: String,
+    available_events: BTreeMap<Ident, Vec<EventInfo>>,
+}
+
+impl BehaviorTrees {
+    pub fn new() -> Self {
+        BehaviorTrees {
+            files: Vec::new(),
+            scanned: false,
+            selected: None,
+            snarl: Snarl::new(),
+            saved: BehaviorTree::default(),
+            new_file: String::new(),
+            available_events: BTreeMap::new(),
+        }
+    }
+
+    pub fn update_plugins(&mut self, container: &Container) {
+        self.available_events.clear();
+
+        for (name, plugin) in container.plugins() {
+            let events = self.available_events.entry(name).or_insert(plugin.events());
+
+            // Behavior trees emit events without payload.
+            events.retain(|event| event.values.is_empty());
+            events.sort_by_key(|event| event.name);
+        }
+    }
+
+    fn scan(&mut self, assets: &Path) {
+        self.files.clear();
+        scan_dir(assets, assets, &mut self.files);
+        self.files.sort();
+        self.scanned = true;
+    }
+
+    fn open(&mut self, assets: &Path, file: PathBuf) {
+        let path = assets.join(&file);
+        let tree = std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| BehaviorTree::from_json(&text).map_err(|err| err.to_string()));
+
+        let tree = match tree {
+            Ok(tree) => tree,
+            Err(err) => {
+                tracing::error!("Failed to read behavior tree '{}': {err}", path.display());
+                BehaviorTree::default()
+            }
+        };
+
+        self.snarl = tree_to_snarl(&tree);
+        self.saved = tree;
+        self.selected = Some(file);
+    }
+
+    fn save(&mut self, assets: &Path) {
+        let Some(file) = &self.selected else {
+            return;
+        };
+
+        let tree = snarl_to_tree(&self.snarl);
+
+        let path = assets.join(file);
+        match std::fs::write(&path, tree.to_json()) {
+            Ok(()) => self.saved = tree,
+            Err(err) => {
+                tracing::error!("Failed to write behavior tree '{}': {err}", path.display());
+            }
+        }
+    }
+
+    pub fn show(&mut self, project: &Project, ui: &mut Ui) {
+        let assets = project.root_path().join("Assets");
+
+        if !self.scanned {
+            self.scan(&assets);
+        }
+
+        let mut open = None;
+
+        ui.horizontal(|ui| {
+            let selected = match &self.selected {
+                None => "Select behavior tree".to_owned(),
+                Some(file) => file.display().to_string(),
+            };
+
+            egui::ComboBox::from_id_source("behavior-tree-file")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for file in &self.files {
+                        let r = ui.selectable_label(
+                            self.selected.as_ref() == Some(file),
+                            file.display().to_string(),
+                        );
+                        if r.clicked() {
+                            open = Some(file.clone());
+                        }
+                    }
+                });
+
+            if ui
+                .button(egui_phosphor::regular::ARROWS_CLOCKWISE)
+                .on_hover_text("Rescan assets")
+                .clicked()
+            {
+                self.scan(&assets);
+            }
+
+            ui.separator();
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_file)
+                    .hint_text("New behavior tree")
+                    .desired_width(150.0),
+            );
+
+            let r = ui.add_enabled(
+                !self.new_file.trim().is_empty(),
+                egui::Button::new(egui_phosphor::regular::FILE_PLUS),
+            );
+            if r.on_hover_text("Create behavior tree").clicked() {
+                let file = PathBuf::from(format!("{}.{EXTENSION}", self.new_file.trim()));
+                let path = assets.join(&file);
+
+                if path.exists() {
+                    tracing::error!("Behavior tree '{}' already exists", path.display());
+                } else {
+                    self.new_file.clear();
+                    self.selected = Some(file);
+                    self.snarl = tree_to_snarl(&BehaviorTree::default());
+                    self.save(&assets);
+                    self.scan(&assets);
+                }
+            }
+        });
+
+        // Switching away drops unsaved changes.
+        if let Some(file) = open {
+            self.open(&assets, file);
+        }
+
+        if self.selected.is_none() {
+            ui.separator();
+            ui.label("No behavior tree selected");
+            return;
+        }
+
+        let tree = snarl_to_tree(&self.snarl);
+        let modified = tree != self.saved;
+
+        ui.horizontal(|ui| {
+            let r = ui.add_enabled(
+                modified,
+                egui::Button::new(egui_phosphor::regular::FLOPPY_DISK),
+            );
+            if r.on_hover_text("Save").clicked() {
+                self.save(&assets);
+            }
+
+            let r = ui.add_enabled(
+                modified,
+                egui::Button::new(egui_phosphor::regular::ARROW_COUNTER_CLOCKWISE),
+            );
+            if r.on_hover_text("Revert").clicked() {
+                if let Some(file) = self.selected.clone() {
+                    self.open(&assets, file);
+                }
+            }
+
+            if let Err(err) = tree.validate() {
+                ui.colored_label(Color32::RED, err.to_string());
+            }
+        });
+
+        ui.separator();
+
+        self.snarl.show(
+            &mut BehaviorViewer {
+                available_events: &self.available_events,
+            },
+            &SnarlStyle::default(),
+            BEHAVIOR_VIEWER_ID,
+            ui,
+        );
+    }
+}
+
+/// Builds editor graph from the tree.
+fn tree_to_snarl(tree: &BehaviorTree) -> Snarl<EdNode> {
+    let mut snarl = Snarl::new();
+
+    let ids = tree
+        .nodes
+        .iter()
+        .map(|node| {
+            let mut ed = EdNode::new(node.kind.clone());
+            if let EdNode::Node { slots, .. } = &mut ed {
+                if node.kind.max_children().is_none() {
+                    *slots = node.children.len() + 1;
+                }
+            }
+            snarl.insert_node(egui::pos2(node.pos[0], node.pos[1]), ed)
+        })
+        .collect::<Vec<_>>();
+
+    for (idx, node) in tree.nodes.iter().enumerate() {
+        for (output, &child) in node.children.iter().enumerate() {
+            let Some(&child) = ids.get(child) else {
+                continue;
+            };
+
+            snarl.connect(
+                OutPinId {
+                    node: ids[idx],
+                    output,
+                },
+                InPinId {
+                    node: child,
+                    input: 0,
+                },
+            );
+        }
+    }
+
+    let root_pos = match tree.root.and_then(|root| tree.nodes.get(root)) {
+        Some(node) => egui::pos2(node.pos[0], node.pos[1]) + ROOT_OFFSET,
+        None => egui::Pos2::ZERO,
+    };
+
+    let root = snarl.insert_node(root_pos, EdNode::Root);
+
+    if let Some(&node) = tree.root.and_then(|root| ids.get(root)) {
+        snarl.connect(
+            OutPinId {
+                node: root,
+                output: 0,
+            },
+            InPinId { node, input: 0 },
+        );
+    }
+
+    snarl
+}
+
+/// Collects the tree from editor graph.
+fn snarl_to_tree(snarl: &Snarl<EdNode>) -> BehaviorTree {
+    let mut indices = HashMap::new();
+    let mut nodes = Vec::new();
+    let mut root_marker = None;
+
+    for (id, node) in snarl.node_ids() {
+        match node {
+            EdNode::Root => root_marker = Some(id),
+            EdNode::Node { kind, .. } => {
+                let pos = snarl
+                    .get_node_info(id)
+                    .map_or(egui::Pos2::ZERO, |info| info.pos);
+
+                indices.insert(id, nodes.len());
+                nodes.push(BehaviorNode {
+                    kind: kind.clone(),
+                    children: Vec::new(),
+                    pos: [pos.x, pos.y],
+                });
+            }
+        }
+    }
+
+    for (id, node) in snarl.node_ids() {
+        let EdNode::Node { slots, .. } = *node else {
+            continue;
+        };
+
+        let Some(&idx) = indices.get(&id) else {
+            continue;
+        };
+
+        // Empty slots are skipped.
+        nodes[idx].children = (0..slots)
+            .filter_map(|output| {
+                let pin = snarl.out_pin(OutPinId { node: id, output });
+                let child = pin.remotes.first()?;
+                indices.get(&child.node).copied()
+            })
+            .collect();
+    }
+
+    let root = root_marker.and_then(|marker| {
+        let pin = snarl.out_pin(OutPinId {
+            node: marker,
+            output: 0,
+        });
+        let node = pin.remotes.first()?;
+        indices.get(&node.node).copied()
+    });
+
+    BehaviorTree { root, nodes }
+}
+
+/// Checks if `node` is `ancestor` or lies below it.
+fn is_descendant(snarl: &Snarl<EdNode>, mut node: NodeId, ancestor: NodeId) -> bool {
+    loop {
+        if node == ancestor {
+            return true;
+        }
+
+        if matches!(snarl[node], EdNode::Root) {
+            return false;
+        }
+
+        let pin = snarl.in_pin(InPinId { node, input: 0 });
+        match pin.remotes.first() {
+            None => return false,
+            Some(parent) => node = parent.node,
+        }
+    }
+}
+
+/// Moves children of composite node to consecutive pins
+/// leaving single free pin at the end.
+fn compact_children(snarl: &mut Snarl<EdNode>, node: NodeId) {
+    let EdNode::Node { ref kind, slots } = snarl[node] else {
+        return;
+    };
+
+    if kind.max_children().is_some() {
+        return;
+    }
+
+    let children = (0..slots)
+        .filter_map(|output| {
+            let pin = snarl.out_pin(OutPinId { node, output });
+            pin.remotes.first().copied()
+        })
+        .collect::<Vec<_>>();
+
+    for output in 0..slots {
+        snarl.drop_outputs(OutPinId { node, output });
+    }
+
+    for (output, &child) in children.iter().enumerate() {
+        snarl.connect(OutPinId { node, output }, child);
+    }
+
+    if let EdNode::Node { slots, .. } = &mut snarl[node] {
+        *slots = children.len() + 1;
+    }
+}
+
+struct BehaviorViewer<'a> {
+    available_events: &'a BTreeMap<Ident, Vec<EventInfo>>,
+}
+
+impl SnarlViewer<EdNode> for BehaviorViewer<'_> {
+    fn title(&mut self, node: &EdNode) -> String {
+        match node {
+            EdNode::Root => "Root".to_owned(),
+            EdNode::Node { kind, .. } => kind.title().to_owned(),
+        }
+    }
+
+    fn show_header(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<EdNode>,
+    ) {
+        match &snarl[node] {
+            EdNode::Root => {
+                ui.label(format!("{} Root", egui_phosphor::regular::TREE_STRUCTURE));
+            }
+            EdNode::Node {
+                kind: BehaviorKind::Event { name, .. },
+                ..
+            } => {
+                ui.label(format!("{} {name}", egui_phosphor::regular::LIGHTNING));
+            }
+            EdNode::Node { kind, .. } => {
+                ui.label(kind.title());
+            }
+        }
+    }
+
+    fn inputs(&mut self, node: &EdNode) -> usize {
+        match node {
+            EdNode::Root => 0,
+            EdNode::Node { .. } => 1,
+        }
+    }
+
+    fn outputs(&mut self, node: &EdNode) -> usize {
+        match *node {
+            EdNode::Root => 1,
+            EdNode::Node { slots, .. } => slots,
+        }
+    }
+
+    fn has_body(&mut self, node: &EdNode) -> bool {
+        matches!(
+            node,
+            EdNode::Node {
+                kind: BehaviorKind::Repeat { .. }
+                    | BehaviorKind::Wait { .. }
+                    | BehaviorKind::Check { .. }
+                    | BehaviorKind::Task { .. },
+                ..
+            }
+        )
+    }
+
+    fn show_body(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<EdNode>,
+    ) {
+        let EdNode::Node { kind, .. } = &mut snarl[node] else {
+            return;
+        };
+
+        match kind {
+            BehaviorKind::Repeat { count } => {
+                ui.add(egui::DragValue::new(count).prefix("Count: "))
+                    .on_hover_text("Zero repeats forever");
+            }
+            BehaviorKind::Wait { seconds } => {
+                ui.add(
+                    egui::DragValue::new(seconds)
+                        .speed(0.05)
+                        .range(0.0..=f32::MAX)
+                        .suffix(" s"),
+                );
+            }
+            BehaviorKind::Check { key } => {
+                ui.add(
+                    egui::TextEdit::singleline(key)
+                        .hint_text("Blackboard key")
+                        .desired_width(120.0),
+                );
+            }
+            BehaviorKind::Task { name } => {
+                ui.add(
+                    egui::TextEdit::singleline(name)
+                        .hint_text("Task name")
+                        .desired_width(120.0),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn show_input(
+        &mut self,
+        _pin: &InPin,
+        _ui: &mut Ui,
+        _scale: f32,
+        _snarl: &mut Snarl<EdNode>,
+    ) -> PinInfo {
+        PinInfo::circle().with_fill(Color32::LIGHT_GRAY)
+    }
+
+    fn show_output(
+        &mut self,
+        pin: &OutPin,
+        ui: &mut Ui,
+        _scale: f32,
+        _snarl: &mut Snarl<EdNode>,
+    ) -> PinInfo {
+        if pin.remotes.is_empty() {
+            ui.weak(egui_phosphor::regular::PLUS);
+            PinInfo::circle().with_fill(Color32::GRAY)
+        } else {
+            ui.weak(pin.id.output.to_string());
+            PinInfo::circle().with_fill(Color32::LIGHT_GRAY)
+        }
+    }
+
+    fn connect(&mut self, from: &OutPin, to: &InPin, snarl: &mut Snarl<EdNode>) {
+        // Keep it a tree.
+        if is_descendant(snarl, from.id.node, to.id.node) {
+            return;
+        }
+
+        snarl.drop_inputs(to.id);
+        snarl.drop_outputs(from.id);
+        snarl.connect(from.id, to.id);
+
+        // Previous parent lost a child and new one may need a free pin.
+        for &parent in &to.remotes {
+            compact_children(snarl, parent.node);
+        }
+        compact_children(snarl, from.id.node);
+    }
+
+    fn disconnect(&mut self, from: &OutPin, to: &InPin, snarl: &mut Snarl<EdNode>) {
+        snarl.disconnect(from.id, to.id);
+        compact_children(snarl, from.id.node);
+    }
+
+    fn drop_outputs(&mut self, pin: &OutPin, snarl: &mut Snarl<EdNode>) {
+        snarl.drop_outputs(pin.id);
+        compact_children(snarl, pin.id.node);
+    }
+
+    fn drop_inputs(&mut self, pin: &InPin, snarl: &mut Snarl<EdNode>) {
+        snarl.drop_inputs(pin.id);
+        for &parent in &pin.remotes {
+            compact_children(snarl, parent.node);
+        }
+    }
+
+    fn has_node_menu(&mut self, _node: &EdNode) -> bool {
+        true
+    }
+
+    fn show_node_menu(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<EdNode>,
+    ) {
+        if matches!(snarl[node], EdNode::Root) {
+            ui.weak("Root cannot be removed");
+            return;
+        }
+
+        if ui.button("Remove").clicked() {
+            let parent = snarl
+                .in_pin(InPinId { node, input: 0 })
+                .remotes
+                .first()
+                .map(|pin| pin.node);
+
+            snarl.remove_node(node);
+
+            if let Some(parent) = parent {
+                compact_children(snarl, parent);
+            }
+            ui.close_menu();
+        }
+    }
+
+    fn has_graph_menu(&mut self, _pos: egui::Pos2, _snarl: &mut Snarl<EdNode>) -> bool {
+        true
+    }
+
+    fn show_graph_menu(
+        &mut self,
+        pos: egui::Pos2,
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<EdNode>,
+    ) {
+        let kinds = [
+            BehaviorKind::Sequence,
+            BehaviorKind::Selector,
+            BehaviorKind::Parallel,
+            BehaviorKind::Invert,
+            BehaviorKind::Succeed,
+            BehaviorKind::Repeat { count: 0 },
+            BehaviorKind::Wait { seconds: 1.0 },
+            BehaviorKind::Check { key: String::new() },
+            BehaviorKind::Task {
+                name: String::new(),
+            },
+        ];
+
+        ui.label("Add node");
+        for kind in kinds {
+            if ui.button(kind.title()).clicked() {
+                snarl.insert_node(pos, EdNode::new(kind));
+                ui.close_menu();
+                return;
+            }
+        }
+
+        if !self.available_events.is_empty() {
+            ui.label("Emit event");
+            for (&plugin, events) in self.available_events.iter() {
+                if events.is_empty() {
+                    continue;
+                }
+
+                ui.separator();
+                ui.weak(plugin.as_str());
+
+                for event in events {
+                    if ui.button(event.name.as_str()).clicked() {
+                        let kind = BehaviorKind::Event {
+                            id: event.id,
+                            name: event.name,
+                        };
+                        snarl.insert_node(pos, EdNode::new(kind));
+                        ui.close_menu();
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn scan_dir(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            scan_dir(root, &path, files);
+        } else if path.extension().map_or(false, |e| e == EXTENSION) {
+            if let Ok(file) = path.strip_prefix(root) {
+                files.push(file.to_owned());
+            }
+        }
+    }
+}
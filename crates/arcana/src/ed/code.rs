@@ -1,6 +1,6 @@
 //! This module UI to generate flows.
 
-use std::{collections::BTreeMap, hash::Hash, ops::Range};
+use std::{cell::RefCell, collections::BTreeMap, hash::Hash, ops::Range};
 
 use edict::{
     flow::{FlowEntity, Flows},
@@ -13,18 +13,19 @@ use egui_snarl::{
     InPin, InPinId, NodeId, OutPin, OutPinId, Snarl,
 };
 use hashbrown::{HashMap, HashSet};
+use rhai::{Dynamic, AST};
 use smallvec::SmallVec;
 
 use crate::{
     code::{
-        AsyncContinueQueue, CodeDesc, CodeGraphId, CodeNodeId, CodeValues, Continuation, FlowCode,
-        PureCode, ValueId,
+        AsyncContinue, AsyncContinueQueue, CodeDesc, CodeGraphId, CodeNodeId, CodeValues,
+        Continuation, FlowCode, PureCode, ValueId,
     },
     events::{EventId, Events},
-    hash_id,
+    hash_id, na,
     plugin::{CodeInfo, EventInfo, PluginsHub},
     project::Project,
-    Ident, Name, NameError, NoSuchEntity, Stid,
+    EntityId, Ident, Name, NameError, NoSuchEntity, Stid,
 };
 
 use super::{container::Container, data::ProjectData, hue_hash, ui::Selector};
@@ -81,6 +82,256 @@ pub enum CodeNode {
         inputs: Vec<Stid>,
         outputs: Vec<Stid>,
     },
+
+    /// Sticky note that is never executed.
+    Comment { text: String },
+
+    /// Entry of a function graph.
+    /// Outputs inflow of the call and its arguments.
+    Input { inputs: Vec<Stid> },
+
+    /// Exit of a function graph.
+    /// Takes outflow of the call and its results.
+    Output { outputs: Vec<Stid> },
+
+    /// Call of a function graph.
+    /// Expanded in place before execution.
+    Call {
+        graph: CodeGraphId,
+        name: Name,
+        inputs: Vec<Stid>,
+        outputs: Vec<Stid>,
+    },
+
+    /// Pure node that evaluates Rhai script.
+    /// Inputs are visible to the script as variables,
+    /// outputs are read from variables the script assigns.
+    Script {
+        source: String,
+        inputs: Vec<ScriptParam>,
+        outputs: Vec<ScriptParam>,
+    },
+}
+
+/// Named and typed pin of a script node.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct ScriptParam {
+    pub name: String,
+    pub ty: ScriptType,
+}
+
+/// Types of values script nodes can take and produce.
+///
+/// Rhai works with `i64` and `f64`,
+/// so narrower numbers are converted on the way in and out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum ScriptType {
+    Bool,
+    I32,
+    I64,
+    U32,
+    U64,
+    F32,
+    F64,
+    String,
+    Entity,
+}
+
+impl ScriptType {
+    const ALL: [ScriptType; 9] = [
+        ScriptType::Bool,
+        ScriptType::I32,
+        ScriptType::I64,
+        ScriptType::U32,
+        ScriptType::U64,
+        ScriptType::F32,
+        ScriptType::F64,
+        ScriptType::String,
+        ScriptType::Entity,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            ScriptType::Bool => "bool",
+            ScriptType::I32 => "i32",
+            ScriptType::I64 => "i64",
+            ScriptType::U32 => "u32",
+            ScriptType::U64 => "u64",
+            ScriptType::F32 => "f32",
+            ScriptType::F64 => "f64",
+            ScriptType::String => "String",
+            ScriptType::Entity => "Entity",
+        }
+    }
+
+    fn stid(self) -> Stid {
+        match self {
+            ScriptType::Bool => Stid::of::<bool>(),
+            ScriptType::I32 => Stid::of::<i32>(),
+            ScriptType::I64 => Stid::of::<i64>(),
+            ScriptType::U32 => Stid::of::<u32>(),
+            ScriptType::U64 => Stid::of::<u64>(),
+            ScriptType::F32 => Stid::of::<f32>(),
+            ScriptType::F64 => Stid::of::<f64>(),
+            ScriptType::String => Stid::of::<String>(),
+            ScriptType::Entity => Stid::of::<EntityId>(),
+        }
+    }
+
+    /// Value of unconnected input and of output script failed to produce.
+    fn default_value(self) -> Dynamic {
+        match self {
+            ScriptType::Bool => Dynamic::FALSE,
+            ScriptType::I32 | ScriptType::I64 | ScriptType::U32 | ScriptType::U64 => {
+                Dynamic::from_int(0)
+            }
+            ScriptType::F32 | ScriptType::F64 => Dynamic::from_float(0.0),
+            ScriptType::String => Dynamic::from(String::new()),
+            ScriptType::Entity => Dynamic::UNIT,
+        }
+    }
+
+    fn get(self, values: &CodeValues, id: ValueId) -> Option<Dynamic> {
+        match self {
+            ScriptType::Bool => values.get::<bool>(id).map(|&v| Dynamic::from_bool(v)),
+            ScriptType::I32 => values.get::<i32>(id).map(|&v| Dynamic::from_int(v.into())),
+            ScriptType::I64 => values.get::<i64>(id).map(|&v| Dynamic::from_int(v)),
+            ScriptType::U32 => values.get::<u32>(id).map(|&v| Dynamic::from_int(v.into())),
+            ScriptType::U64 => values.get::<u64>(id).map(|&v| Dynamic::from_int(v as i64)),
+            ScriptType::F32 => values
+                .get::<f32>(id)
+                .map(|&v| Dynamic::from_float(v.into())),
+            ScriptType::F64 => values.get::<f64>(id).map(|&v| Dynamic::from_float(v)),
+            ScriptType::String => values.get::<String>(id).map(|v| Dynamic::from(v.clone())),
+            ScriptType::Entity => values.get::<EntityId>(id).map(|&v| Dynamic::from(v)),
+        }
+    }
+
+    /// Stores value produced by script.
+    /// Returns name of actual type if value has wrong type.
+    fn set(self, values: &mut CodeValues, id: ValueId, value: Dynamic) -> Result<(), &'static str> {
+        // Integers are accepted where floats are expected.
+        let float = |value: &Dynamic| {
+            value
+                .as_float()
+                .or_else(|_| value.as_int().map(|v| v as f64))
+        };
+
+        match self {
+            ScriptType::Bool => values.set(id, value.as_bool()?),
+            ScriptType::I32 => values.set(id, value.as_int()? as i32),
+            ScriptType::I64 => values.set(id, value.as_int()?),
+            ScriptType::U32 => values.set(id, value.as_int()? as u32),
+            ScriptType::U64 => values.set(id, value.as_int()? as u64),
+            ScriptType::F32 => values.set(id, float(&value)? as f32),
+            ScriptType::F64 => values.set(id, float(&value)?),
+            ScriptType::String => values.set(id, value.into_string()?),
+            ScriptType::Entity => {
+                let type_name = value.type_name();
+                values.set(id, value.try_cast::<EntityId>().ok_or(type_name)?)
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Maximum number of operations script node may perform in one evaluation.
+/// Script that exceeds it is stopped, so endless loop can't hang the game.
+const SCRIPT_MAX_OPERATIONS: u64 = 100_000;
+
+/// Number of compiled scripts kept before cache is flushed.
+/// Sources change on every keystroke while edited, so cache must not grow unbounded.
+const SCRIPT_CACHE_SIZE: usize = 256;
+
+/// Rhai engine and scripts compiled with it.
+struct ScriptCache {
+    engine: rhai::Engine,
+
+    /// Compiled scripts by source.
+    /// `None` if compilation failed, so error is reported once.
+    asts: HashMap<String, Option<AST>>,
+}
+
+impl ScriptCache {
+    fn new() -> Self {
+        let mut engine = rhai::Engine::new();
+        engine.set_max_operations(SCRIPT_MAX_OPERATIONS);
+
+        ScriptCache {
+            engine,
+            asts: HashMap::new(),
+        }
+    }
+}
+
+thread_local! {
+    // Rhai engine is not `Send`, and code runs on the thread that owns the world anyway.
+    static SCRIPTS: RefCell<ScriptCache> = RefCell::new(ScriptCache::new());
+}
+
+/// What pin of a code node carries.
+enum PinKind {
+    Flow,
+    Value(Stid),
+
+    /// Extra pin of function input or output that adds new typed pin when connected.
+    New,
+}
+
+impl CodeNode {
+    fn input_kind(&self, input: usize) -> PinKind {
+        match *self {
+            CodeNode::Event { .. } | CodeNode::Comment { .. } | CodeNode::Input { .. } => {
+                unreachable!()
+            }
+            CodeNode::Pure { ref inputs, .. } => PinKind::Value(inputs[input]),
+            CodeNode::Script { ref inputs, .. } => PinKind::Value(inputs[input].ty.stid()),
+            CodeNode::Flow {
+                inflows,
+                ref inputs,
+                ..
+            } => match input.checked_sub(inflows) {
+                None => PinKind::Flow,
+                Some(idx) => PinKind::Value(inputs[idx]),
+            },
+            CodeNode::Output { ref outputs, .. } => match input {
+                0 => PinKind::Flow,
+                _ if input > outputs.len() => PinKind::New,
+                _ => PinKind::Value(outputs[input - 1]),
+            },
+            CodeNode::Call { ref inputs, .. } => match input {
+                0 => PinKind::Flow,
+                _ => PinKind::Value(inputs[input - 1]),
+            },
+        }
+    }
+
+    fn output_kind(&self, output: usize) -> PinKind {
+        match *self {
+            CodeNode::Comment { .. } | CodeNode::Output { .. } => unreachable!(),
+            CodeNode::Event { ref outputs, .. } | CodeNode::Call { ref outputs, .. } => {
+                match output {
+                    0 => PinKind::Flow,
+                    _ => PinKind::Value(outputs[output - 1]),
+                }
+            }
+            CodeNode::Pure { ref outputs, .. } => PinKind::Value(outputs[output]),
+            CodeNode::Script { ref outputs, .. } => PinKind::Value(outputs[output].ty.stid()),
+            CodeNode::Flow {
+                outflows,
+                ref outputs,
+                ..
+            } => match output.checked_sub(outflows) {
+                None => PinKind::Flow,
+                Some(idx) => PinKind::Value(outputs[idx]),
+            },
+            CodeNode::Input { ref inputs, .. } => match output {
+                0 => PinKind::Flow,
+                _ if output > inputs.len() => PinKind::New,
+                _ => PinKind::Value(inputs[output - 1]),
+            },
+        }
+    }
 }
 
 fn schedule_pure_inputs(
@@ -111,32 +362,33 @@ fn schedule_pure_inputs(
 
         let mut delay = false;
 
-        match snarl.get_node(node) {
-            Some(CodeNode::Pure { inputs, .. }) => {
-                for input in 0..inputs.len() {
-                    let inpin = snarl.in_pin(InPinId { node, input });
-                    assert!(inpin.remotes.len() <= 1);
-
-                    if !inpin.remotes.is_empty() {
-                        let producer = inpin.remotes[0];
-                        if !scheduled.contains(&producer.node) {
-                            match snarl.get_node(producer.node) {
-                                Some(CodeNode::Pure { .. }) => {
-                                    if !delay {
-                                        queue.push(node);
-                                    }
-                                    delay = true;
-                                    queue.push(producer.node);
-                                }
-                                _ => {
-                                    scheduled.insert(producer.node);
-                                }
+        let inputs = match snarl.get_node(node) {
+            Some(CodeNode::Pure { inputs, .. }) => inputs.len(),
+            Some(CodeNode::Script { inputs, .. }) => inputs.len(),
+            _ => continue,
+        };
+
+        for input in 0..inputs {
+            let inpin = snarl.in_pin(InPinId { node, input });
+            assert!(inpin.remotes.len() <= 1);
+
+            if !inpin.remotes.is_empty() {
+                let producer = inpin.remotes[0];
+                if !scheduled.contains(&producer.node) {
+                    match snarl.get_node(producer.node) {
+                        Some(CodeNode::Pure { .. } | CodeNode::Script { .. }) => {
+                            if !delay {
+                                queue.push(node);
                             }
+                            delay = true;
+                            queue.push(producer.node);
+                        }
+                        _ => {
+                            scheduled.insert(producer.node);
                         }
                     }
                 }
             }
-            _ => continue,
         }
 
         if !delay {
@@ -192,6 +444,15 @@ fn execute_pure(
 
             pure_code(entity, &inputs, &mut outputs, values);
         }
+        CodeNode::Script {
+            ref source,
+            ref inputs,
+            ref outputs,
+        } => {
+            SCRIPTS.with_borrow_mut(|scripts| {
+                execute_script(scripts, node, source, inputs, outputs, snarl, values)
+            });
+        }
         _ => {
             tracing::error!("Node {node:?} is not pure");
             return;
@@ -199,6 +460,99 @@ fn execute_pure(
     }
 }
 
+/// Evaluates script node.
+///
+/// Failed script still produces outputs, with default values,
+/// so that nodes consuming them can run.
+fn execute_script(
+    scripts: &mut ScriptCache,
+    node: NodeId,
+    source: &str,
+    inputs: &[ScriptParam],
+    outputs: &[ScriptParam],
+    snarl: &Snarl<CodeNode>,
+    values: &mut CodeValues,
+) {
+    let ScriptCache { engine, asts } = scripts;
+
+    if !asts.contains_key(source) {
+        if asts.len() >= SCRIPT_CACHE_SIZE {
+            asts.clear();
+        }
+
+        let ast = match engine.compile(source) {
+            Ok(ast) => Some(ast),
+            Err(err) => {
+                tracing::error!("Failed to compile script of node {node:?}: {err}");
+                None
+            }
+        };
+        asts.insert(source.to_owned(), ast);
+    }
+
+    let mut scope = rhai::Scope::new();
+
+    for (input, param) in inputs.iter().enumerate() {
+        let in_pin = snarl.in_pin(InPinId { node, input });
+        let value = in_pin.remotes.first().and_then(|producer| {
+            param.ty.get(
+                values,
+                ValueId {
+                    node: producer.node.0,
+                    output: producer.output,
+                },
+            )
+        });
+
+        scope.push_dynamic(
+            param.name.clone(),
+            value.unwrap_or_else(|| param.ty.default_value()),
+        );
+    }
+
+    // Output named after input is initialized with the input value.
+    for param in outputs {
+        if !inputs.iter().any(|input| input.name == param.name) {
+            scope.push_dynamic(param.name.clone(), param.ty.default_value());
+        }
+    }
+
+    let result = match asts[source] {
+        None => None,
+        Some(ref ast) => match engine.eval_ast_with_scope::<Dynamic>(&mut scope, ast) {
+            Ok(result) => Some(result),
+            Err(err) => {
+                tracing::error!("Script of node {node:?} failed: {err}");
+                None
+            }
+        },
+    };
+
+    for (output, param) in outputs.iter().enumerate() {
+        let id = ValueId {
+            node: node.0,
+            output,
+        };
+
+        // Value of the last expression is the result of single-output script.
+        let value = match result {
+            Some(ref result) if outputs.len() == 1 && !result.is_unit() => result.clone(),
+            _ => scope
+                .get_value::<Dynamic>(&param.name)
+                .unwrap_or_else(|| param.ty.default_value()),
+        };
+
+        if let Err(actual) = param.ty.set(values, id, value) {
+            tracing::error!(
+                "Script of node {node:?} produced {actual} for output '{}' of type {}",
+                param.name,
+                param.ty.name()
+            );
+            let _ = param.ty.set(values, id, param.ty.default_value());
+        }
+    }
+}
+
 /// Execute specific flow code.
 fn execute_flow(
     codes: CodeGraphId,
@@ -277,6 +631,10 @@ fn execute_flow(
 
             next
         }
+        CodeNode::Output { .. } => {
+            // Function graph executed on its own finishes here.
+            None
+        }
         _ => {
             tracing::error!("Node {:?} is not flow", pin.node);
             None
@@ -293,6 +651,7 @@ fn run_codes(
     entity: FlowEntity,
     mut outflow: OutPinId,
     mut values: Option<CodeValues>,
+    tracer: &mut Tracer,
 ) {
     loop {
         let Some(code_node) = snarl.get_node(outflow.node) else {
@@ -307,7 +666,12 @@ fn run_codes(
                     break;
                 }
             }
-            CodeNode::Pure { .. } => {
+            CodeNode::Pure { .. }
+            | CodeNode::Script { .. }
+            | CodeNode::Comment { .. }
+            | CodeNode::Input { .. }
+            | CodeNode::Output { .. }
+            | CodeNode::Call { .. } => {
                 tracing::error!("Node {:?} is not event or flow", outflow.node);
                 break;
             }
@@ -334,6 +698,20 @@ fn run_codes(
 
         let inflow = outpin.remotes[0];
 
+        if tracer.enter(codes, inflow.node) {
+            // Stop at breakpoint before the node is executed.
+            // Flow is resumed from the same outflow later.
+            let values = values.take().unwrap_or_else(|| cache.grab(codes));
+            tracer.trace.paused = Some(AsyncContinue {
+                entity: entity.id(),
+                codes,
+                node: outflow.node.0,
+                outflow: outflow.output,
+                values,
+            });
+            return;
+        }
+
         values.get_or_insert_with(|| cache.grab(codes));
 
         let next = execute_flow(
@@ -347,6 +725,10 @@ fn run_codes(
             &mut values,
         );
 
+        if let Some(values) = &values {
+            tracer.record(codes, snarl, inflow.node, values);
+        }
+
         match next {
             Some(output) => {
                 outflow = OutPinId {
@@ -364,29 +746,37 @@ fn run_codes(
 }
 
 /// Run scheduled [`CodeAfter`]
+///
+/// Flow resumed from breakpoint runs first.
 fn run_async_continuations(
     world: &mut World,
     queue: &mut AsyncContinueQueue,
     cache: &mut OutputCache,
-    codes: &HashMap<CodeGraphId, CodeGraph>,
+    codes: &HashMap<CodeGraphId, Executable>,
     pures: &HashMap<CodeNodeId, PureCode>,
     flows: &HashMap<CodeNodeId, FlowCode>,
+    trace: &mut CodeTrace,
+    resumed: Option<AsyncContinue>,
 ) {
     queue.extend(&mut world.expect_resource_mut::<AsyncContinueQueue>());
 
+    let pending = queue.drain().collect::<Vec<_>>();
+
     Flows::enter(world, |world| {
-        for c in queue.drain() {
+        let mut pending = resumed.into_iter().chain(pending);
+
+        while let Some(c) = pending.next() {
             let Ok(entity) = world.entity(c.entity) else {
                 continue;
             };
 
-            let Some(graph) = codes.get(&c.codes) else {
+            let Some(code) = codes.get(&c.codes) else {
                 continue;
             };
 
             run_codes(
                 c.codes,
-                &graph.snarl,
+                code.snarl,
                 cache,
                 pures,
                 flows,
@@ -396,7 +786,14 @@ fn run_async_continuations(
                     output: c.outflow,
                 },
                 Some(c.values),
-            )
+                &mut Tracer::new(trace, code.origin),
+            );
+
+            if trace.is_paused() {
+                // Keep the rest for when flow is resumed.
+                pending.for_each(|c| queue.push(c));
+                return;
+            }
         }
     });
 }
@@ -404,10 +801,11 @@ fn run_async_continuations(
 pub fn handle_code_events(
     world: &mut World,
     cache: &mut OutputCache,
-    codes: &HashMap<CodeGraphId, CodeGraph>,
+    codes: &HashMap<CodeGraphId, Executable>,
     pures: &HashMap<CodeNodeId, PureCode>,
     flows: &HashMap<CodeNodeId, FlowCode>,
     start: &mut u64,
+    trace: &mut CodeTrace,
 ) {
     let world = world.local();
 
@@ -430,22 +828,19 @@ pub fn handle_code_events(
                 Ok(Some(codes_id)) => codes_id,
             };
 
-            let Some(graph) = codes.get(&codes_id) else {
+            let Some(code) = codes.get(&codes_id) else {
                 tracing::debug!("Code {codes_id} is not found");
                 continue;
             };
 
-            let Some((node, outputs)) =
-                graph
-                    .snarl
-                    .node_ids()
-                    .find_map(|(node_id, node)| match *node {
-                        CodeNode::Event {
-                            id, ref outputs, ..
-                        } if id == event.id => Some((node_id, outputs)),
-                        _ => None,
-                    })
-            else {
+            let snarl = code.snarl;
+
+            let Some((node, outputs)) = snarl.node_ids().find_map(|(node_id, node)| match *node {
+                CodeNode::Event {
+                    id, ref outputs, ..
+                } if id == event.id => Some((node_id, outputs)),
+                _ => None,
+            }) else {
                 continue;
             };
 
@@ -487,16 +882,21 @@ pub fn handle_code_events(
 
                 run_codes(
                     codes_id,
-                    &graph.snarl,
+                    snarl,
                     cache,
                     &pures,
                     &flows,
                     entity,
                     outflow,
                     None,
+                    &mut Tracer::new(trace, code.origin),
                 );
             });
 
+            if trace.is_paused() {
+                return;
+            }
+
             continue 'outer;
         }
 
@@ -504,10 +904,312 @@ pub fn handle_code_events(
     }
 }
 
+/// Code graph with calls of function graphs expanded in place.
+struct CompiledCode {
+    /// Hash of the graph and all graphs it calls.
+    fingerprint: u64,
+    snarl: Snarl<CodeNode>,
+    origin: HashMap<NodeId, Origin>,
+}
+
+/// Where node of compiled graph comes from in the edited graph.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Origin {
+    /// Node of the graph itself.
+    Node(NodeId),
+
+    /// Node inlined from function called by this node.
+    Call(NodeId),
+}
+
+/// Execution trace of code graphs for the debugger in code editor.
+///
+/// Instance records nodes it executes and values they produce,
+/// editor sets breakpoints and resumes flow paused at them.
+/// While flow is paused no other code runs.
+pub struct CodeTrace {
+    /// Record values of pins.
+    enabled: bool,
+    graphs: HashMap<CodeGraphId, GraphTrace>,
+    breakpoints: HashSet<(CodeGraphId, NodeId)>,
+
+    /// Flow stopped at breakpoint.
+    paused: Option<AsyncContinue>,
+
+    /// Paused flow continues on next execution.
+    resume: bool,
+
+    /// Resumed flow stops again before next node.
+    step: bool,
+
+    /// Node at which flow was paused is not stopped at again when resumed.
+    skip: bool,
+}
+
+/// Last recorded state of one code graph.
+#[derive(Default)]
+struct GraphTrace {
+    /// Node that executed last or is about to execute when paused.
+    current: Option<NodeId>,
+    inputs: HashMap<InPinId, String>,
+    outputs: HashMap<OutPinId, String>,
+}
+
+impl CodeTrace {
+    pub fn new() -> Self {
+        CodeTrace {
+            enabled: false,
+            graphs: HashMap::new(),
+            breakpoints: HashSet::new(),
+            paused: None,
+            resume: false,
+            step: false,
+            skip: false,
+        }
+    }
+
+    /// Clears recorded state and drops paused flow.
+    /// Breakpoints are kept.
+    pub fn reset(&mut self) {
+        self.graphs.clear();
+        self.paused = None;
+        self.resume = false;
+        self.step = false;
+        self.skip = false;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            for graph in self.graphs.values_mut() {
+                graph.inputs.clear();
+                graph.outputs.clear();
+            }
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    /// Returns graph and entity of the paused flow.
+    pub fn paused_at(&self) -> Option<(CodeGraphId, EntityId)> {
+        let paused = self.paused.as_ref()?;
+        Some((paused.codes, paused.entity))
+    }
+
+    /// Continues paused flow until next breakpoint.
+    pub fn resume(&mut self) {
+        if self.paused.is_some() {
+            self.resume = true;
+            self.step = false;
+        }
+    }
+
+    /// Continues paused flow for one node.
+    pub fn step(&mut self) {
+        if self.paused.is_some() {
+            self.resume = true;
+            self.step = true;
+        }
+    }
+
+    pub fn has_breakpoint(&self, codes: CodeGraphId, node: NodeId) -> bool {
+        self.breakpoints.contains(&(codes, node))
+    }
+
+    pub fn toggle_breakpoint(&mut self, codes: CodeGraphId, node: NodeId) {
+        if !self.breakpoints.remove(&(codes, node)) {
+            self.breakpoints.insert((codes, node));
+        }
+    }
+
+    pub fn clear_breakpoints(&mut self, codes: CodeGraphId) {
+        self.breakpoints.retain(|&(c, _)| c != codes);
+    }
+
+    fn take_resumed(&mut self) -> Option<AsyncContinue> {
+        if !self.resume {
+            return None;
+        }
+
+        self.resume = false;
+        self.skip = true;
+        self.paused.take()
+    }
+}
+
+/// Records execution of one flow into [`CodeTrace`].
+struct Tracer<'a> {
+    trace: &'a mut CodeTrace,
+    origin: Option<&'a HashMap<NodeId, Origin>>,
+
+    /// Origin of the last entered node.
+    last: Option<Origin>,
+}
+
+impl<'a> Tracer<'a> {
+    fn new(trace: &'a mut CodeTrace, origin: Option<&'a HashMap<NodeId, Origin>>) -> Self {
+        Tracer {
+            trace,
+            origin,
+            last: None,
+        }
+    }
+
+    fn origin(&self, node: NodeId) -> Origin {
+        match self.origin {
+            None => Origin::Node(node),
+            Some(origin) => origin.get(&node).copied().unwrap_or(Origin::Node(node)),
+        }
+    }
+
+    /// Marks flow node as current.
+    /// Returns true if flow must pause before executing it.
+    fn enter(&mut self, codes: CodeGraphId, node: NodeId) -> bool {
+        let origin = self.origin(node);
+        let last = self.last.replace(origin);
+
+        let (Origin::Node(edited) | Origin::Call(edited)) = origin;
+        self.trace.graphs.entry(codes).or_default().current = Some(edited);
+
+        if std::mem::take(&mut self.trace.skip) {
+            return false;
+        }
+
+        if std::mem::take(&mut self.trace.step) {
+            return true;
+        }
+
+        match origin {
+            Origin::Node(node) => self.trace.has_breakpoint(codes, node),
+            // Breakpoint on call stops once when flow enters the function.
+            Origin::Call(node) => last != Some(origin) && self.trace.has_breakpoint(codes, node),
+        }
+    }
+
+    /// Records values of inputs and outputs of executed flow node.
+    /// Nodes inlined from functions are not recorded.
+    fn record(
+        &mut self,
+        codes: CodeGraphId,
+        snarl: &Snarl<CodeNode>,
+        node: NodeId,
+        values: &CodeValues,
+    ) {
+        if !self.trace.enabled {
+            return;
+        }
+
+        let Origin::Node(edited) = self.origin(node) else {
+            return;
+        };
+
+        let CodeNode::Flow {
+            inflows,
+            outflows,
+            ref inputs,
+            ref outputs,
+            ..
+        } = snarl[node]
+        else {
+            return;
+        };
+
+        let graph = self.trace.graphs.entry(codes).or_default();
+
+        for input in inflows..inflows + inputs.len() {
+            let in_pin = snarl.in_pin(InPinId { node, input });
+            let Some(&producer) = in_pin.remotes.first() else {
+                continue;
+            };
+
+            let value = ValueId {
+                node: producer.node.0,
+                output: producer.output,
+            };
+
+            if let Some(text) = format_value(values, value) {
+                graph.inputs.insert(
+                    InPinId {
+                        node: edited,
+                        input,
+                    },
+                    text,
+                );
+            }
+        }
+
+        for output in 0..outputs.len() {
+            let value = ValueId {
+                node: node.0,
+                output,
+            };
+
+            if let Some(text) = format_value(values, value) {
+                graph.outputs.insert(
+                    OutPinId {
+                        node: edited,
+                        output: outflows + output,
+                    },
+                    text,
+                );
+            }
+        }
+    }
+}
+
+/// Formats values of common types.
+/// Values are type-erased, so other types can't be shown.
+fn format_value(values: &CodeValues, id: ValueId) -> Option<String> {
+    macro_rules! try_types {
+        ($($ty:ty),* $(,)?) => {
+            $(
+                if let Some(value) = values.get::<$ty>(id) {
+                    return Some(format!("{value:?}"));
+                }
+            )*
+        };
+    }
+
+    try_types!(
+        bool,
+        i32,
+        i64,
+        u32,
+        u64,
+        usize,
+        f32,
+        f64,
+        String,
+        EntityId,
+        na::Vector2<f32>,
+        na::Point2<f32>,
+        na::Vector3<f32>,
+        na::Point3<f32>,
+    );
+
+    None
+}
+
+/// Code graph ready for execution.
+struct Executable<'a> {
+    snarl: &'a Snarl<CodeNode>,
+
+    /// Maps nodes back to edited graph when calls were expanded.
+    origin: Option<&'a HashMap<NodeId, Origin>>,
+}
+
 pub struct CodeContext {
     queue: AsyncContinueQueue,
     cache: OutputCache,
     next_event: u64,
+    compiled: HashMap<CodeGraphId, CompiledCode>,
+    trace: CodeTrace,
 }
 
 impl CodeContext {
@@ -516,6 +1218,8 @@ impl CodeContext {
             queue: AsyncContinueQueue::new(),
             cache: OutputCache::new(),
             next_event: 0,
+            compiled: HashMap::new(),
+            trace: CodeTrace::new(),
         }
     }
 
@@ -523,39 +1227,491 @@ impl CodeContext {
         self.queue.clear();
         self.cache.map.clear();
         self.next_event = 0;
+        self.compiled.clear();
+        self.trace.reset();
+    }
+
+    pub fn trace_mut(&mut self) -> &mut CodeTrace {
+        &mut self.trace
     }
 
     pub fn execute(&mut self, hub: &PluginsHub, data: &ProjectData, world: &mut World) {
+        self.compile(&data.codes);
+
+        let codes = data
+            .codes
+            .iter()
+            .map(|(&id, graph)| match self.compiled.get(&id) {
+                Some(compiled) => (
+                    id,
+                    Executable {
+                        snarl: &compiled.snarl,
+                        origin: Some(&compiled.origin),
+                    },
+                ),
+                None => (
+                    id,
+                    Executable {
+                        snarl: &graph.snarl,
+                        origin: None,
+                    },
+                ),
+            })
+            .collect::<HashMap<_, _>>();
+
+        let resumed = self.trace.take_resumed();
+
+        if resumed.is_none() && self.trace.is_paused() {
+            // Everything waits until flow stopped at breakpoint is resumed.
+            return;
+        }
+
         run_async_continuations(
             world,
             &mut self.queue,
             &mut self.cache,
-            &data.codes,
+            &codes,
             &hub.pure_fns,
             &hub.flow_fns,
+            &mut self.trace,
+            resumed,
         );
 
+        if self.trace.is_paused() {
+            return;
+        }
+
         handle_code_events(
             world,
             &mut self.cache,
-            &data.codes,
+            &codes,
             &hub.pure_fns,
             &hub.flow_fns,
             &mut self.next_event,
+            &mut self.trace,
         );
     }
-}
 
-#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
-pub struct CodeGraph {
+    /// Expands function calls in graphs that have them.
+    /// Graphs are recompiled only when they or called graphs change.
+    fn compile(&mut self, codes: &HashMap<CodeGraphId, CodeGraph>) {
+        self.compiled.retain(|id, _| codes.contains_key(id));
+
+        for (&id, graph) in codes {
+            if !graph.has_calls() {
+                self.compiled.remove(&id);
+                continue;
+            }
+
+            let fingerprint = fingerprint(id, codes, &mut Vec::new());
+
+            match self.compiled.get(&id) {
+                Some(compiled) if compiled.fingerprint == fingerprint => continue,
+                _ => {}
+            }
+
+            let (snarl, origin) = expand_calls(id, codes, &mut Vec::new());
+            self.compiled.insert(
+                id,
+                CompiledCode {
+                    fingerprint,
+                    snarl,
+                    origin,
+                },
+            );
+        }
+    }
+}
+
+fn fingerprint(
+    id: CodeGraphId,
+    codes: &HashMap<CodeGraphId, CodeGraph>,
+    stack: &mut Vec<CodeGraphId>,
+) -> u64 {
+    use std::hash::{DefaultHasher, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    id.hash(&mut hasher);
+
+    let Some(graph) = codes.get(&id) else {
+        return hasher.finish();
+    };
+
+    // Nodes are not hashable, serialized form is hashed instead.
+    match serde_json::to_vec(&graph.snarl) {
+        Ok(bytes) => bytes.hash(&mut hasher),
+        Err(err) => tracing::error!("Failed to serialize code {id}: {err}"),
+    }
+
+    if stack.contains(&id) {
+        return hasher.finish();
+    }
+
+    stack.push(id);
+    for node in graph.snarl.nodes() {
+        if let CodeNode::Call { graph, .. } = *node {
+            fingerprint(graph, codes, stack).hash(&mut hasher);
+        }
+    }
+    stack.pop();
+
+    hasher.finish()
+}
+
+/// Inner graph placed in place of a call node.
+struct Inlined {
+    input: NodeId,
+    output: Option<NodeId>,
+}
+
+/// Builds snarl of the graph with all calls of function graphs replaced by their nodes.
+///
+/// Wires into call node are rerouted to consumers of function input,
+/// wires from call node are rerouted from producers of function output.
+/// Recursive calls are left in place and never executed.
+fn expand_calls(
+    id: CodeGraphId,
+    codes: &HashMap<CodeGraphId, CodeGraph>,
+    stack: &mut Vec<CodeGraphId>,
+) -> (Snarl<CodeNode>, HashMap<NodeId, Origin>) {
+    let mut snarl = Snarl::new();
+    let mut origin = HashMap::new();
+
+    let Some(graph) = codes.get(&id) else {
+        return (snarl, origin);
+    };
+
+    stack.push(id);
+
+    let mut map = HashMap::new();
+    let mut inlined = HashMap::new();
+
+    for (node_id, node) in graph.snarl.node_ids() {
+        let callee = match *node {
+            CodeNode::Call { graph: callee, .. } => callee,
+            _ => {
+                map.insert(node_id, snarl.insert_node(egui::Pos2::ZERO, node.clone()));
+                continue;
+            }
+        };
+
+        if stack.contains(&callee) {
+            tracing::error!(
+                "Code {} calls {} recursively. The call is ignored",
+                graph.name,
+                callee
+            );
+            map.insert(node_id, snarl.insert_node(egui::Pos2::ZERO, node.clone()));
+            continue;
+        }
+
+        let (inner, _) = expand_calls(callee, codes, stack);
+
+        let mut inner_map = HashMap::new();
+        for (inner_id, inner_node) in inner.node_ids() {
+            let new_id = snarl.insert_node(egui::Pos2::ZERO, inner_node.clone());
+            inner_map.insert(inner_id, new_id);
+            origin.insert(new_id, Origin::Call(node_id));
+        }
+
+        for (from, to) in inner.wires() {
+            snarl.connect(
+                OutPinId {
+                    node: inner_map[&from.node],
+                    output: from.output,
+                },
+                InPinId {
+                    node: inner_map[&to.node],
+                    input: to.input,
+                },
+            );
+        }
+
+        let input = inner.node_ids().find_map(|(id, node)| match node {
+            CodeNode::Input { .. } => Some(inner_map[&id]),
+            _ => None,
+        });
+
+        let Some(input) = input else {
+            tracing::error!(
+                "Code {} called from {} is not a function",
+                callee,
+                graph.name
+            );
+            map.insert(node_id, snarl.insert_node(egui::Pos2::ZERO, node.clone()));
+            continue;
+        };
+
+        let output = inner.node_ids().find_map(|(id, node)| match node {
+            CodeNode::Output { .. } => Some(inner_map[&id]),
+            _ => None,
+        });
+
+        inlined.insert(node_id, Inlined { input, output });
+    }
+
+    stack.pop();
+
+    let mut wires = Vec::new();
+
+    for (from, to) in graph.snarl.wires() {
+        let sources = call_sources(&graph.snarl, &snarl, &map, &inlined, from);
+
+        let targets = match inlined.get(&to.node) {
+            None => vec![InPinId {
+                node: map[&to.node],
+                input: to.input,
+            }],
+            Some(inlined) => snarl
+                .out_pin(OutPinId {
+                    node: inlined.input,
+                    output: to.input,
+                })
+                .remotes
+                .into_iter()
+                // Pass-through to the output is resolved from the other side.
+                .filter(|pin| Some(pin.node) != inlined.output)
+                .collect(),
+        };
+
+        for &source in &sources {
+            for &target in &targets {
+                wires.push((source, target));
+            }
+        }
+    }
+
+    // Function input and output nodes are not executed, so wires to them are dropped.
+    for inlined in inlined.values() {
+        snarl.remove_node(inlined.input);
+        if let Some(output) = inlined.output {
+            snarl.remove_node(output);
+        }
+    }
+
+    for (from, to) in wires {
+        snarl.connect(from, to);
+    }
+
+    for (&old_id, &new_id) in &map {
+        origin.insert(new_id, Origin::Node(old_id));
+    }
+
+    (snarl, origin)
+}
+
+/// Finds pins in expanded snarl that produce value or flow for the output pin of original snarl.
+fn call_sources(
+    original: &Snarl<CodeNode>,
+    expanded: &Snarl<CodeNode>,
+    map: &HashMap<NodeId, NodeId>,
+    inlined: &HashMap<NodeId, Inlined>,
+    pin: OutPinId,
+) -> Vec<OutPinId> {
+    let Some(call) = inlined.get(&pin.node) else {
+        return vec![OutPinId {
+            node: map[&pin.node],
+            output: pin.output,
+        }];
+    };
+
+    let Some(output) = call.output else {
+        return Vec::new();
+    };
+
+    let mut sources = Vec::new();
+
+    let remotes = expanded
+        .in_pin(InPinId {
+            node: output,
+            input: pin.output,
+        })
+        .remotes;
+
+    for remote in remotes {
+        if remote.node == call.input {
+            // Function output is wired directly to its input.
+            let outer = original
+                .in_pin(InPinId {
+                    node: pin.node,
+                    input: remote.output,
+                })
+                .remotes;
+
+            for outer in outer {
+                sources.extend(call_sources(original, expanded, map, inlined, outer));
+            }
+        } else {
+            sources.push(remote);
+        }
+    }
+
+    sources
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CodeGraph {
     name: Name,
     snarl: Snarl<CodeNode>,
     events: HashMap<EventId, OutPinId>,
+
+    #[serde(default)]
+    groups: Vec<NodeGroup>,
+}
+
+/// Named set of nodes that are collapsed and expanded together.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct NodeGroup {
+    name: String,
+    nodes: Vec<NodeId>,
+    collapsed: bool,
+}
+
+/// Nodes copied to clipboard with wires between them.
+///
+/// Serialized as JSON so it can be pasted into another graph or another editor instance.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CodeClipboard {
+    nodes: Vec<ClipboardNode>,
+    wires: Vec<ClipboardWire>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ClipboardNode {
+    pos: egui::Pos2,
+    open: bool,
+    node: CodeNode,
+}
+
+/// Wire between copied nodes referenced by their index in the clipboard.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ClipboardWire {
+    from: usize,
+    output: usize,
+    to: usize,
+    input: usize,
+}
+
+/// Offset of pasted nodes from copied ones.
+const PASTE_OFFSET: egui::Vec2 = egui::vec2(20.0, 20.0);
+
+impl CodeClipboard {
+    fn copy(snarl: &Snarl<CodeNode>, selected: &[NodeId]) -> Self {
+        let mut nodes = Vec::new();
+        let mut indices = HashMap::new();
+
+        for &id in selected {
+            let Some(info) = snarl.get_node_info(id) else {
+                continue;
+            };
+
+            indices.insert(id, nodes.len());
+            nodes.push(ClipboardNode {
+                pos: info.pos,
+                open: info.open,
+                node: info.value.clone(),
+            });
+        }
+
+        let wires = snarl
+            .wires()
+            .filter_map(|(from, to)| {
+                Some(ClipboardWire {
+                    from: *indices.get(&from.node)?,
+                    output: from.output,
+                    to: *indices.get(&to.node)?,
+                    input: to.input,
+                })
+            })
+            .collect();
+
+        CodeClipboard { nodes, wires }
+    }
+
+    /// Inserts copied nodes into the graph.
+    /// Returns ids of inserted nodes.
+    fn paste(self, snarl: &mut Snarl<CodeNode>) -> Vec<NodeId> {
+        let ids = self
+            .nodes
+            .into_iter()
+            .map(|node| {
+                let pos = node.pos + PASTE_OFFSET;
+                if node.open {
+                    snarl.insert_node(pos, node.node)
+                } else {
+                    snarl.insert_node_collapsed(pos, node.node)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        for wire in self.wires {
+            let (Some(&from), Some(&to)) = (ids.get(wire.from), ids.get(wire.to)) else {
+                continue;
+            };
+
+            snarl.connect(
+                OutPinId {
+                    node: from,
+                    output: wire.output,
+                },
+                InPinId {
+                    node: to,
+                    input: wire.input,
+                },
+            );
+        }
+
+        ids
+    }
 }
 
 struct CodeViewer<'a> {
     available_events: &'a BTreeMap<Ident, Vec<EventInfo>>,
     available_codes: &'a BTreeMap<Ident, Vec<CodeInfo>>,
+    functions: &'a HashMap<CodeGraphId, Function>,
+    trace: &'a mut CodeTrace,
+
+    /// Graph being edited.
+    current: CodeGraphId,
+}
+
+/// Signature of a code graph that can be called from other graphs.
+#[derive(Clone, PartialEq)]
+struct Function {
+    name: Name,
+    inputs: Vec<Stid>,
+    outputs: Vec<Stid>,
+}
+
+impl CodeGraph {
+    fn has_calls(&self) -> bool {
+        self.snarl
+            .nodes()
+            .any(|node| matches!(node, CodeNode::Call { .. }))
+    }
+
+    /// Returns signature of the graph if it has function input.
+    fn function(&self) -> Option<Function> {
+        let inputs = self.snarl.nodes().find_map(|node| match node {
+            CodeNode::Input { inputs } => Some(inputs.clone()),
+            _ => None,
+        })?;
+
+        let outputs = self
+            .snarl
+            .nodes()
+            .find_map(|node| match node {
+                CodeNode::Output { outputs } => Some(outputs.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        Some(Function {
+            name: self.name,
+            inputs,
+            outputs,
+        })
+    }
 }
 
 impl SnarlViewer<CodeNode> for CodeViewer<'_> {
@@ -564,25 +1720,38 @@ impl SnarlViewer<CodeNode> for CodeViewer<'_> {
             CodeNode::Event { name, .. } => name.to_string(),
             CodeNode::Flow { name, .. } => name.to_string(),
             CodeNode::Pure { name, .. } => name.to_string(),
+            CodeNode::Script { .. } => "Script".to_owned(),
+            CodeNode::Comment { .. } => "Comment".to_owned(),
+            CodeNode::Input { .. } => "Input".to_owned(),
+            CodeNode::Output { .. } => "Output".to_owned(),
+            CodeNode::Call { name, .. } => name.to_string(),
         }
     }
 
     fn inputs(&mut self, node: &CodeNode) -> usize {
         match *node {
-            CodeNode::Event { .. } => 0,
+            CodeNode::Event { .. } | CodeNode::Comment { .. } | CodeNode::Input { .. } => 0,
             CodeNode::Pure { ref inputs, .. } => inputs.len(),
+            CodeNode::Script { ref inputs, .. } => inputs.len(),
             CodeNode::Flow {
                 inflows,
                 ref inputs,
                 ..
             } => inflows + inputs.len(),
+            CodeNode::Output { ref outputs } => 2 + outputs.len(),
+            CodeNode::Call { ref inputs, .. } => 1 + inputs.len(),
         }
     }
 
     fn outputs(&mut self, node: &CodeNode) -> usize {
         match *node {
-            CodeNode::Event { ref outputs, .. } => 1 + outputs.len(),
+            CodeNode::Comment { .. } | CodeNode::Output { .. } => 0,
+            CodeNode::Event { ref outputs, .. } | CodeNode::Call { ref outputs, .. } => {
+                1 + outputs.len()
+            }
+            CodeNode::Input { ref inputs } => 2 + inputs.len(),
             CodeNode::Pure { ref outputs, .. } => outputs.len(),
+            CodeNode::Script { ref outputs, .. } => outputs.len(),
             CodeNode::Flow {
                 outflows,
                 ref outputs,
@@ -600,6 +1769,20 @@ impl SnarlViewer<CodeNode> for CodeViewer<'_> {
         _scale: f32,
         snarl: &mut Snarl<CodeNode>,
     ) {
+        if self.trace.has_breakpoint(self.current, node) {
+            ui.colored_label(Color32::RED, egui_phosphor::regular::CIRCLE);
+        }
+
+        let trace = self.trace.graphs.get(&self.current);
+        if trace.map_or(false, |trace| trace.current == Some(node)) {
+            let color = if self.trace.is_paused() {
+                Color32::YELLOW
+            } else {
+                Color32::GREEN
+            };
+            ui.colored_label(color, egui_phosphor::regular::PLAY);
+        }
+
         let node = &snarl[node];
 
         match *node {
@@ -612,73 +1795,231 @@ impl SnarlViewer<CodeNode> for CodeViewer<'_> {
             CodeNode::Flow { name, .. } => {
                 ui.label(name.to_string());
             }
+            CodeNode::Script { .. } => {
+                ui.label(format!("{} Script", egui_phosphor::regular::CODE));
+            }
+            CodeNode::Comment { .. } => {
+                ui.weak(egui_phosphor::regular::NOTE);
+            }
+            CodeNode::Input { .. } => {
+                ui.label("Input");
+            }
+            CodeNode::Output { .. } => {
+                ui.label("Output");
+            }
+            CodeNode::Call { name, .. } => {
+                ui.label(format!("{} {name}", egui_phosphor::regular::FUNCTION));
+            }
+        }
+    }
+
+    fn has_body(&mut self, node: &CodeNode) -> bool {
+        matches!(node, CodeNode::Comment { .. } | CodeNode::Script { .. })
+    }
+
+    fn show_body(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<CodeNode>,
+    ) {
+        match &mut snarl[node] {
+            CodeNode::Comment { text } => {
+                ui.add(
+                    egui::TextEdit::multiline(text)
+                        .desired_width(200.0)
+                        .desired_rows(3)
+                        .hint_text("Comment"),
+                );
+            }
+            CodeNode::Script {
+                source,
+                inputs,
+                outputs,
+            } => {
+                ui.add(
+                    egui::TextEdit::multiline(source)
+                        .code_editor()
+                        .desired_width(200.0)
+                        .desired_rows(3)
+                        .hint_text("a + b"),
+                );
+
+                let retyped_inputs = show_script_params(node, "Inputs", inputs, ui);
+                let retyped_outputs = show_script_params(node, "Outputs", outputs, ui);
+
+                // Wires of retyped pins carry values of old type.
+                for input in retyped_inputs {
+                    snarl.drop_inputs(InPinId { node, input });
+                }
+                for output in retyped_outputs {
+                    snarl.drop_outputs(OutPinId { node, output });
+                }
+            }
+            _ => {}
         }
     }
 
     fn show_input(
         &mut self,
         pin: &InPin,
-        _ui: &mut Ui,
+        ui: &mut Ui,
         _scale: f32,
         snarl: &mut Snarl<CodeNode>,
     ) -> PinInfo {
-        let node = &snarl[pin.id.node];
+        let info = pin_info(snarl[pin.id.node].input_kind(pin.id.input), ui);
 
-        match *node {
-            CodeNode::Event { .. } => unreachable!(),
-            CodeNode::Pure { ref inputs, .. } => {
-                let input = inputs[pin.id.input];
-                PinInfo::square().with_fill(hue_hash(&input))
-            }
-            CodeNode::Flow {
-                inflows,
-                ref inputs,
-                ..
-            } => {
-                if pin.id.input < inflows {
-                    flow_pin()
-                } else {
-                    let input = inputs[pin.id.input - inflows];
-                    PinInfo::square().with_fill(hue_hash(&input))
-                }
+        if let CodeNode::Script { ref inputs, .. } = snarl[pin.id.node] {
+            ui.label(&inputs[pin.id.input].name);
+        }
+
+        if let Some(trace) = self.trace.graphs.get(&self.current) {
+            if let Some(value) = trace.inputs.get(&pin.id) {
+                ui.weak(value);
             }
         }
+
+        info
     }
 
     fn show_output(
         &mut self,
         pin: &OutPin,
-        _ui: &mut Ui,
+        ui: &mut Ui,
         _scale: f32,
         snarl: &mut Snarl<CodeNode>,
     ) -> PinInfo {
-        let node = &snarl[pin.id.node];
+        if let Some(trace) = self.trace.graphs.get(&self.current) {
+            if let Some(value) = trace.outputs.get(&pin.id) {
+                ui.weak(value);
+            }
+        }
 
-        match *node {
-            CodeNode::Event { ref outputs, .. } => {
-                if pin.id.output == 0 {
-                    flow_pin()
-                } else {
-                    let output = outputs[pin.id.output - 1];
-                    PinInfo::square().with_fill(hue_hash(&output))
+        if let CodeNode::Script { ref outputs, .. } = snarl[pin.id.node] {
+            ui.label(&outputs[pin.id.output].name);
+        }
+
+        pin_info(snarl[pin.id.node].output_kind(pin.id.output), ui)
+    }
+
+    fn connect(&mut self, from: &OutPin, to: &InPin, snarl: &mut Snarl<CodeNode>) {
+        let from_kind = snarl[from.id.node].output_kind(from.id.output);
+        let to_kind = snarl[to.id.node].input_kind(to.id.input);
+
+        match (from_kind, to_kind) {
+            (PinKind::New, PinKind::Value(stid)) => {
+                // Add argument to the function typed after connected input.
+                let CodeNode::Input { inputs } = &mut snarl[from.id.node] else {
+                    unreachable!()
+                };
+                inputs.push(stid);
+                snarl.connect(from.id, to.id);
+            }
+            (PinKind::Value(stid), PinKind::New) => {
+                // Add result to the function typed after connected output.
+                let CodeNode::Output { outputs } = &mut snarl[to.id.node] else {
+                    unreachable!()
+                };
+                outputs.push(stid);
+                snarl.connect(from.id, to.id);
+            }
+            (PinKind::Flow, PinKind::Flow) => {
+                snarl.drop_outputs(from.id);
+                snarl.connect(from.id, to.id);
+            }
+            (PinKind::Value(a), PinKind::Value(b)) if a == b => {
+                snarl.drop_inputs(to.id);
+                snarl.connect(from.id, to.id);
+            }
+            _ => {}
+        }
+    }
+
+    fn has_node_menu(&mut self, _node: &CodeNode) -> bool {
+        true
+    }
+
+    fn show_node_menu(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<CodeNode>,
+    ) {
+        if ui.button("Duplicate").clicked() {
+            CodeClipboard::copy(snarl, &[node]).paste(snarl);
+            ui.close_menu();
+        }
+
+        if matches!(snarl[node], CodeNode::Flow { .. } | CodeNode::Call { .. })
+            && ui.button("Toggle breakpoint").clicked()
+        {
+            self.trace.toggle_breakpoint(self.current, node);
+            ui.close_menu();
+        }
+
+        match snarl[node] {
+            CodeNode::Input { ref inputs } if !inputs.is_empty() => {
+                if ui.button("Remove last argument").clicked() {
+                    let output = inputs.len();
+                    snarl.drop_outputs(OutPinId { node, output });
+                    if let CodeNode::Input { inputs } = &mut snarl[node] {
+                        inputs.pop();
+                    }
+                    ui.close_menu();
                 }
             }
-            CodeNode::Pure { ref outputs, .. } => {
-                let output = outputs[pin.id.output];
-                PinInfo::square().with_fill(hue_hash(&output))
+            CodeNode::Output { ref outputs } if !outputs.is_empty() => {
+                if ui.button("Remove last result").clicked() {
+                    let input = outputs.len();
+                    snarl.drop_inputs(InPinId { node, input });
+                    if let CodeNode::Output { outputs } = &mut snarl[node] {
+                        outputs.pop();
+                    }
+                    ui.close_menu();
+                }
             }
-            CodeNode::Flow {
-                outflows,
+            CodeNode::Script {
+                ref inputs,
                 ref outputs,
                 ..
             } => {
-                if pin.id.output < outflows {
-                    flow_pin()
-                } else {
-                    let output = outputs[pin.id.output - outflows];
-                    PinInfo::square().with_fill(hue_hash(&output))
+                let inputs = inputs.len();
+                let outputs = outputs.len();
+
+                if inputs > 0 && ui.button("Remove last input").clicked() {
+                    snarl.drop_inputs(InPinId {
+                        node,
+                        input: inputs - 1,
+                    });
+                    if let CodeNode::Script { inputs, .. } = &mut snarl[node] {
+                        inputs.pop();
+                    }
+                    ui.close_menu();
+                }
+
+                if outputs > 0 && ui.button("Remove last output").clicked() {
+                    snarl.drop_outputs(OutPinId {
+                        node,
+                        output: outputs - 1,
+                    });
+                    if let CodeNode::Script { outputs, .. } = &mut snarl[node] {
+                        outputs.pop();
+                    }
+                    ui.close_menu();
                 }
             }
+            _ => {}
+        }
+
+        if ui.button("Remove").clicked() {
+            snarl.remove_node(node);
+            ui.close_menu();
         }
     }
 
@@ -693,6 +2034,79 @@ impl SnarlViewer<CodeNode> for CodeViewer<'_> {
         _scale: f32,
         snarl: &mut Snarl<CodeNode>,
     ) {
+        if ui.button("Add comment").clicked() {
+            snarl.insert_node(
+                pos,
+                CodeNode::Comment {
+                    text: String::new(),
+                },
+            );
+            ui.close_menu();
+            return;
+        }
+
+        if ui.button("Add script").clicked() {
+            snarl.insert_node(
+                pos,
+                CodeNode::Script {
+                    source: String::new(),
+                    inputs: Vec::new(),
+                    outputs: Vec::new(),
+                },
+            );
+            ui.close_menu();
+            return;
+        }
+
+        let has_input = snarl
+            .nodes()
+            .any(|node| matches!(node, CodeNode::Input { .. }));
+        let has_output = snarl
+            .nodes()
+            .any(|node| matches!(node, CodeNode::Output { .. }));
+
+        if !has_input && ui.button("Add function input").clicked() {
+            snarl.insert_node(pos, CodeNode::Input { inputs: Vec::new() });
+            ui.close_menu();
+            return;
+        }
+
+        if has_input && !has_output && ui.button("Add function output").clicked() {
+            snarl.insert_node(
+                pos,
+                CodeNode::Output {
+                    outputs: Vec::new(),
+                },
+            );
+            ui.close_menu();
+            return;
+        }
+
+        if self.functions.keys().any(|&id| id != self.current) {
+            ui.separator();
+            ui.label("Call function");
+
+            for (&graph, function) in self.functions.iter() {
+                if graph == self.current {
+                    continue;
+                }
+
+                if ui.button(function.name.as_str()).clicked() {
+                    snarl.insert_node(
+                        pos,
+                        CodeNode::Call {
+                            graph,
+                            name: function.name,
+                            inputs: function.inputs.clone(),
+                            outputs: function.outputs.clone(),
+                        },
+                    );
+                    ui.close_menu();
+                    return;
+                }
+            }
+        }
+
         if !self.available_events.is_empty() {
             ui.label("Add event");
             for (&plugin, events) in self.available_events.iter() {
@@ -769,6 +2183,56 @@ impl SnarlViewer<CodeNode> for CodeViewer<'_> {
     }
 }
 
+/// Shows editor of script node parameters.
+/// Returns indices of parameters which type was changed.
+fn show_script_params(
+    node: NodeId,
+    label: &str,
+    params: &mut Vec<ScriptParam>,
+    ui: &mut Ui,
+) -> Vec<usize> {
+    let mut retyped = Vec::new();
+
+    ui.horizontal(|ui| {
+        ui.weak(label);
+        if ui.small_button(egui_phosphor::regular::PLUS).clicked() {
+            params.push(ScriptParam {
+                name: format!("{}{}", label[..1].to_lowercase(), params.len()),
+                ty: ScriptType::F32,
+            });
+        }
+    });
+
+    for (idx, param) in params.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut param.name).desired_width(80.0));
+
+            egui::ComboBox::from_id_source((node, label, idx))
+                .selected_text(param.ty.name())
+                .show_ui(ui, |ui| {
+                    for ty in ScriptType::ALL {
+                        if ui.selectable_value(&mut param.ty, ty, ty.name()).changed() {
+                            retyped.push(idx);
+                        }
+                    }
+                });
+        });
+    }
+
+    retyped
+}
+
+fn pin_info(kind: PinKind, ui: &mut Ui) -> PinInfo {
+    match kind {
+        PinKind::Flow => flow_pin(),
+        PinKind::Value(stid) => PinInfo::square().with_fill(hue_hash(&stid)),
+        PinKind::New => {
+            ui.weak(egui_phosphor::regular::PLUS);
+            PinInfo::circle().with_fill(Color32::GRAY)
+        }
+    }
+}
+
 fn draw_flow_pin(painter: &Painter, rect: Rect) {
     painter.add(Shape::Path(PathShape {
         points: vec![rect.left_top(), rect.right_center(), rect.left_bottom()],
@@ -784,6 +2248,190 @@ fn flow_pin() -> PinInfo {
     )))
 }
 
+const CODE_VIEWER_ID: &str = "code-viwer";
+
+fn handle_clipboard(code: &mut CodeGraph, selected: &[NodeId], ui: &mut Ui) {
+    let events = ui.input(|i| i.events.clone());
+
+    for event in events {
+        match event {
+            egui::Event::Copy | egui::Event::Cut if !selected.is_empty() => {
+                let clipboard = CodeClipboard::copy(&code.snarl, selected);
+                match serde_json::to_string(&clipboard) {
+                    Ok(text) => ui.ctx().copy_text(text),
+                    Err(err) => tracing::error!("Failed to copy nodes: {err}"),
+                }
+
+                if matches!(event, egui::Event::Cut) {
+                    for &node in selected {
+                        code.snarl.remove_node(node);
+                    }
+                }
+            }
+            egui::Event::Paste(text) => {
+                // Clipboard may contain anything, ignore what is not nodes.
+                if let Ok(clipboard) = serde_json::from_str::<CodeClipboard>(&text) {
+                    clipboard.paste(&mut code.snarl);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn show_groups(code: &mut CodeGraph, selected: &[NodeId], ui: &mut Ui) {
+    let snarl = &mut code.snarl;
+
+    code.groups.retain_mut(|group| {
+        group.nodes.retain(|&node| snarl.get_node(node).is_some());
+        !group.nodes.is_empty()
+    });
+
+    ui.horizontal_wrapped(|ui| {
+        let r = ui.add_enabled(
+            !selected.is_empty(),
+            egui::Button::new(egui_phosphor::regular::SELECTION_PLUS),
+        );
+        if r.on_hover_text("Group selected nodes").clicked() {
+            code.groups.push(NodeGroup {
+                name: format!("Group {}", code.groups.len() + 1),
+                nodes: selected.to_vec(),
+                collapsed: false,
+            });
+        }
+
+        let mut remove = None;
+
+        for (idx, group) in code.groups.iter_mut().enumerate() {
+            ui.group(|ui| {
+                let icon = if group.collapsed {
+                    egui_phosphor::regular::CARET_RIGHT
+                } else {
+                    egui_phosphor::regular::CARET_DOWN
+                };
+
+                if ui.small_button(icon).clicked() {
+                    group.collapsed = !group.collapsed;
+                    for &node in &group.nodes {
+                        snarl.open_node(node, !group.collapsed);
+                    }
+                }
+
+                ui.add(egui::TextEdit::singleline(&mut group.name).desired_width(80.0));
+                ui.weak(format!("{}", group.nodes.len()));
+
+                if ui.small_button(egui_phosphor::regular::X).clicked() {
+                    remove = Some(idx);
+                }
+            });
+        }
+
+        if let Some(idx) = remove {
+            code.groups.remove(idx);
+        }
+    });
+}
+
+/// Updates call nodes after signatures of called functions change.
+/// Wires of the changed call nodes are dropped as they may not match new types.
+fn update_calls(snarl: &mut Snarl<CodeNode>, functions: &HashMap<CodeGraphId, Function>) {
+    let outdated = snarl
+        .node_ids()
+        .filter_map(|(id, node)| match *node {
+            CodeNode::Call {
+                graph,
+                name,
+                ref inputs,
+                ref outputs,
+            } => {
+                let function = functions.get(&graph)?;
+                if function.name == name
+                    && function.inputs == *inputs
+                    && function.outputs == *outputs
+                {
+                    return None;
+                }
+                Some((id, graph, function.clone()))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    for (node, graph, function) in outdated {
+        let CodeNode::Call {
+            inputs, outputs, ..
+        } = &snarl[node]
+        else {
+            unreachable!()
+        };
+
+        let inputs = inputs.len();
+        let outputs = outputs.len();
+
+        for input in 1..=inputs {
+            snarl.drop_inputs(InPinId { node, input });
+        }
+        for output in 1..=outputs {
+            snarl.drop_outputs(OutPinId { node, output });
+        }
+
+        snarl[node] = CodeNode::Call {
+            graph,
+            name: function.name,
+            inputs: function.inputs,
+            outputs: function.outputs,
+        };
+    }
+}
+
+fn show_debugger(id: CodeGraphId, trace: &mut CodeTrace, ui: &mut Ui) {
+    ui.horizontal(|ui| {
+        let mut enabled = trace.is_enabled();
+        if ui.checkbox(&mut enabled, "Trace values").changed() {
+            trace.set_enabled(enabled);
+        }
+
+        if ui
+            .button(egui_phosphor::regular::TRASH)
+            .on_hover_text("Clear breakpoints")
+            .clicked()
+        {
+            trace.clear_breakpoints(id);
+        }
+
+        let Some((codes, entity)) = trace.paused_at() else {
+            return;
+        };
+
+        ui.separator();
+
+        if codes == id {
+            ui.colored_label(Color32::YELLOW, format!("Paused for {entity}"));
+        } else {
+            ui.colored_label(
+                Color32::YELLOW,
+                format!("Paused in other code for {entity}"),
+            );
+        }
+
+        if ui
+            .button(egui_phosphor::regular::PLAY)
+            .on_hover_text("Continue")
+            .clicked()
+        {
+            trace.resume();
+        }
+
+        if ui
+            .button(egui_phosphor::regular::ARROW_LINE_RIGHT)
+            .on_hover_text("Step")
+            .clicked()
+        {
+            trace.step();
+        }
+    });
+}
+
 pub struct CodeTool {
     selected: Option<CodeGraphId>,
     new_code_name: String,
@@ -818,7 +2466,13 @@ impl CodeTool {
         }
     }
 
-    pub fn show(&mut self, project: &Project, data: &mut ProjectData, ui: &mut Ui) {
+    pub fn show(
+        &mut self,
+        project: &Project,
+        data: &mut ProjectData,
+        trace: &mut CodeTrace,
+        ui: &mut Ui,
+    ) {
         let selector = Selector::<_, CodeGraph>::new("selected-code", |_, code| code.name.as_str());
 
         ui.vertical(|ui| {
@@ -835,6 +2489,7 @@ impl CodeTool {
                                 name,
                                 snarl: Snarl::new(),
                                 events: HashMap::new(),
+                                groups: Vec::new(),
                             };
 
                             let id = hash_id!(name);
@@ -861,19 +2516,45 @@ impl CodeTool {
                 return;
             };
 
+            let functions = data
+                .codes
+                .iter()
+                .filter_map(|(&id, code)| Some((id, code.function()?)))
+                .collect::<HashMap<_, _>>();
+
             let Some(code) = data.codes.get_mut(&id) else {
                 return;
             };
 
-            code.snarl.show(
-                &mut CodeViewer {
-                    available_events: &self.available_events,
-                    available_codes: &self.available_codes,
-                },
-                &SnarlStyle::default(),
-                "code-viwer",
-                ui,
-            );
+            update_calls(&mut code.snarl, &functions);
+
+            show_debugger(id, trace, ui);
+
+            let selected = Snarl::<CodeNode>::get_selected_nodes(CODE_VIEWER_ID, ui);
+
+            show_groups(code, &selected, ui);
+
+            let r = ui
+                .scope(|ui| {
+                    code.snarl.show(
+                        &mut CodeViewer {
+                            available_events: &self.available_events,
+                            available_codes: &self.available_codes,
+                            functions: &functions,
+                            trace,
+                            current: id,
+                        },
+                        &SnarlStyle::default(),
+                        CODE_VIEWER_ID,
+                        ui,
+                    );
+                })
+                .response;
+
+            // Clipboard shortcuts apply to the graph only when no text field is edited.
+            if r.contains_pointer() && !ui.ctx().wants_keyboard_input() {
+                handle_clipboard(code, &selected, ui);
+            }
         });
 
         try_log_err!(data.sync(&project));
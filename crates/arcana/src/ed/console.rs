@@ -1,16 +1,368 @@
+//! Console panel of the running instance.
+//!
+//! Executes commands registered by plugins in the instance world
+//! and editor commands that manipulate the instance itself.
+
+use arcana::{
+    console::{self, CommandError},
+    EntityId,
+};
 use egui::Ui;
-use egui_tracing::{EventCollector, Logs};
 
-pub(super) struct Console {
-    collector: EventCollector,
+use super::instance::Instance;
+
+/// Command that operates on the instance rather than its world.
+struct EdCommand {
+    name: &'static str,
+    help: &'static str,
+    run: fn(&mut Instance, &[&str]) -> Result<String, CommandError>,
+    complete: Option<fn(&Instance, &[&str]) -> Vec<String>>,
+}
+
+const ED_COMMANDS: &[EdCommand] = &[
+    EdCommand {
+        name: "entities",
+        help: "entities - lists entities with their components",
+        run: entities,
+        complete: None,
+    },
+    EdCommand {
+        name: "spawn",
+        help: "spawn [component...] - spawns entity with default components",
+        run: spawn,
+        complete: Some(|instance, _| component_names(instance)),
+    },
+    EdCommand {
+        name: "despawn",
+        help: "despawn <entity> - despawns entity",
+        run: despawn,
+        complete: Some(|instance, args| match args {
+            [_] => entity_names(instance),
+            _ => Vec::new(),
+        }),
+    },
+    EdCommand {
+        name: "set",
+        help: "set <entity> <component> <value> - sets component value written in JSON",
+        run: set,
+        complete: Some(|instance, args| match args {
+            [_] => entity_names(instance),
+            [_, _] => component_names(instance),
+            _ => Vec::new(),
+        }),
+    },
+    EdCommand {
+        name: "plugins",
+        help: "plugins [plugin] - lists plugins with their components, systems, jobs and events",
+        run: plugins,
+        complete: Some(|instance, args| match args {
+            [_] => plugin_names(instance),
+            _ => Vec::new(),
+        }),
+    },
+    EdCommand {
+        name: "pause",
+        help: "pause - pauses simulation",
+        run: |instance, _| {
+            instance.set_paused(true);
+            Ok(String::new())
+        },
+        complete: None,
+    },
+    EdCommand {
+        name: "resume",
+        help: "resume - resumes simulation",
+        run: |instance, _| {
+            instance.set_paused(false);
+            Ok(String::new())
+        },
+        complete: None,
+    },
+    EdCommand {
+        name: "step",
+        help: "step [count] - advances paused simulation by fixed ticks",
+        run: step,
+        complete: None,
+    },
+    EdCommand {
+        name: "rate",
+        help: "rate <scale> - sets simulation time scale",
+        run: rate,
+        complete: None,
+    },
+];
+
+pub struct Console {
+    console: console::Console,
 }
 
 impl Console {
-    pub fn new(collector: EventCollector) -> Self {
-        Console { collector }
+    pub fn new() -> Self {
+        Console {
+            console: console::Console::new(),
+        }
+    }
+
+    pub fn show(&mut self, instance: &mut Instance, ui: &mut Ui) {
+        let instance = std::cell::RefCell::new(instance);
+
+        self.console.show(
+            ui,
+            |line| execute(&mut instance.borrow_mut(), line),
+            |line| complete(&instance.borrow(), line),
+        );
+    }
+}
+
+fn execute(instance: &mut Instance, line: &str) -> Result<String, CommandError> {
+    let args = console::split_args(line);
+
+    match args.split_first() {
+        Some((&"help", [])) => {
+            let mut help = ED_COMMANDS.iter().map(|c| c.help).collect::<Vec<_>>();
+            help.push("");
+            let mut help = help.join("\n");
+            help.push_str(&instance.execute_command(line)?);
+            Ok(help)
+        }
+        Some((&"help", [name])) => match ED_COMMANDS.iter().find(|c| c.name == *name) {
+            Some(command) => Ok(command.help.to_owned()),
+            None => instance.execute_command(line),
+        },
+        Some((name, args)) => match ED_COMMANDS.iter().find(|c| c.name == *name) {
+            Some(command) => (command.run)(instance, args),
+            None => instance.execute_command(line),
+        },
+        None => Ok(String::new()),
+    }
+}
+
+fn complete(instance: &Instance, line: &str) -> Vec<String> {
+    let args = console::split_args(line);
+    let ends_with_space = line.ends_with(char::is_whitespace);
+
+    match args.split_first() {
+        Some((prefix, [])) if !ends_with_space => {
+            let mut names = ED_COMMANDS
+                .iter()
+                .filter(|c| c.name.starts_with(prefix))
+                .map(|c| format!("{} ", c.name))
+                .collect::<Vec<_>>();
+            names.extend(instance.complete_command(line));
+            names.sort();
+            names
+        }
+        Some((name, args)) => {
+            let Some(command) = ED_COMMANDS.iter().find(|c| c.name == *name) else {
+                return instance.complete_command(line);
+            };
+
+            let Some(complete_args) = command.complete else {
+                return Vec::new();
+            };
+
+            let mut args = args.to_vec();
+            let head = if ends_with_space {
+                args.push("");
+                line
+            } else {
+                let last = args.last().unwrap();
+                let start = last.as_ptr() as usize - line.as_ptr() as usize;
+                let head = &line[..start];
+                head.strip_suffix('"').unwrap_or(head)
+            };
+
+            let last = *args.last().unwrap();
+
+            complete_args(instance, &args)
+                .into_iter()
+                .filter(|candidate| candidate.starts_with(last))
+                .map(|candidate| {
+                    if candidate.contains(char::is_whitespace) {
+                        format!("{head}\"{candidate}\" ")
+                    } else {
+                        format!("{head}{candidate} ")
+                    }
+                })
+                .collect()
+        }
+        None => instance.complete_command(line),
+    }
+}
+
+fn entity_names(instance: &Instance) -> Vec<String> {
+    instance.entities().iter().map(|e| e.to_string()).collect()
+}
+
+fn component_names(instance: &Instance) -> Vec<String> {
+    let mut names = instance
+        .component_infos()
+        .into_iter()
+        .map(|info| info.name.to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+fn plugin_names(instance: &Instance) -> Vec<String> {
+    instance
+        .plugin_infos()
+        .iter()
+        .map(|p| p.name.to_string())
+        .collect()
+}
+
+fn find_entity(instance: &Instance, arg: &str) -> Result<EntityId, CommandError> {
+    instance
+        .entities()
+        .into_iter()
+        .find(|e| e.to_string() == arg)
+        .ok_or_else(|| CommandError::Failed(format!("Entity '{arg}' not found")))
+}
+
+fn entities(instance: &mut Instance, _args: &[&str]) -> Result<String, CommandError> {
+    let infos = instance.component_infos();
+
+    let lines = instance
+        .entities()
+        .into_iter()
+        .map(|entity| {
+            let components = instance
+                .component_set(entity)
+                .into_iter()
+                .filter_map(|id| infos.iter().find(|info| info.id == id))
+                .map(|info| info.name.as_str())
+                .collect::<Vec<_>>();
+            format!("{entity}: {}", components.join(", "))
+        })
+        .collect::<Vec<_>>();
+
+    Ok(lines.join("\n"))
+}
+
+fn plugins(instance: &mut Instance, args: &[&str]) -> Result<String, CommandError> {
+    let filter = match args {
+        [] => None,
+        [name] => Some(*name),
+        _ => return Err(CommandError::Usage("plugins [plugin]")),
+    };
+
+    let mut lines = Vec::new();
+    for plugin in instance.plugin_infos() {
+        if filter.map_or(false, |name| plugin.name.as_str() != name) {
+            continue;
+        }
+
+        match &plugin.version {
+            Some(version) => lines.push(format!("{} {version}", plugin.name)),
+            None => lines.push(plugin.name.to_string()),
+        }
+
+        for info in &plugin.components {
+            lines.push(format!("  component {} {}", info.name, info.id));
+        }
+        for info in &plugin.systems {
+            lines.push(format!("  system {} {}", info.name, info.id));
+        }
+        for info in &plugin.jobs {
+            lines.push(format!("  job {} {}", info.name, info.id));
+        }
+        for info in &plugin.events {
+            lines.push(format!("  event {} {}", info.name, info.id));
+        }
+    }
+
+    if let (Some(name), []) = (filter, &lines[..]) {
+        return Err(CommandError::Failed(format!("Plugin '{name}' not found")));
+    }
+
+    Ok(lines.join("\n"))
+}
+
+fn spawn(instance: &mut Instance, args: &[&str]) -> Result<String, CommandError> {
+    let infos = instance.component_infos();
+
+    let components = args
+        .iter()
+        .map(|&arg| {
+            infos
+                .iter()
+                .find(|info| info.name.as_str() == arg)
+                .cloned()
+                .ok_or_else(|| CommandError::Failed(format!("Component '{arg}' not found")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match instance.spawn_entity(&components) {
+        Ok(entity) => Ok(format!("Spawned {entity}")),
+        Err(err) => Err(CommandError::Failed(err.to_string())),
+    }
+}
+
+fn despawn(instance: &mut Instance, args: &[&str]) -> Result<String, CommandError> {
+    let [arg] = args else {
+        return Err(CommandError::Usage("despawn <entity>"));
+    };
+
+    let entity = find_entity(instance, arg)?;
+    instance.despawn_entity(entity);
+    Ok(String::new())
+}
+
+fn set(instance: &mut Instance, args: &[&str]) -> Result<String, CommandError> {
+    let [entity, component, value] = args else {
+        return Err(CommandError::Usage("set <entity> <component> <value>"));
+    };
+
+    let entity = find_entity(instance, entity)?;
+
+    let Some(info) = instance
+        .component_infos()
+        .into_iter()
+        .find(|info| info.name.as_str() == *component)
+    else {
+        return Err(CommandError::Failed(format!(
+            "Component '{component}' not found"
+        )));
+    };
+
+    let value = serde_json::from_str(value)
+        .map_err(|err| CommandError::Failed(format!("Invalid value: {err}")))?;
+
+    match instance.set_component(entity, info.id, &value) {
+        Ok(()) => Ok(String::new()),
+        Err(err) => Err(CommandError::Failed(err.to_string())),
+    }
+}
+
+fn step(instance: &mut Instance, args: &[&str]) -> Result<String, CommandError> {
+    let count = match args {
+        [] => 1,
+        [count] => count
+            .parse::<u32>()
+            .map_err(|_| CommandError::Usage("step [count]"))?,
+        _ => return Err(CommandError::Usage("step [count]")),
+    };
+
+    if !instance.is_paused() {
+        return Err(CommandError::Failed("Simulation is not paused".to_owned()));
     }
 
-    pub fn show(&mut self, ui: &mut Ui) {
-        ui.add(Logs::new(self.collector.clone()));
+    for _ in 0..count {
+        instance.step_once();
     }
+    Ok(String::new())
+}
+
+fn rate(instance: &mut Instance, args: &[&str]) -> Result<String, CommandError> {
+    let [scale] = args else {
+        return Err(CommandError::Usage("rate <scale>"));
+    };
+
+    let scale = scale
+        .parse::<f32>()
+        .map_err(|_| CommandError::Usage("rate <scale>"))?;
+
+    instance.rate_mut().set_rate(scale);
+    Ok(String::new())
 }
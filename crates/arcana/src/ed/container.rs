@@ -28,7 +28,7 @@ use thiserror::Error;
 use crate::{
     plugin::{check_arcana_instance, ArcanaPlugin},
     project::Dependency,
-    Ident,
+    sandbox, Ident,
 };
 
 use super::error::{FileCopyError, FileOpenError, FileReadError};
@@ -115,6 +115,9 @@ struct Loaded {
     /// In dependency-first order.
     plugins: Arc<[(Ident, ArcanaPlugin)]>,
 
+    /// Secrets of sandbox keys issued to the plugins.
+    sandbox_keys: Vec<u64>,
+
     /// Linked library.
     /// It is only used to keep the library loaded.
     /// It must be last member of the struct to ensure it is dropped last.
@@ -127,6 +130,7 @@ struct Loaded {
 impl Drop for Loaded {
     fn drop(&mut self) {
         tracing::info!("Dropping loaded library");
+        sandbox::forget_keys(&self.sandbox_keys);
     }
 }
 
@@ -134,6 +138,10 @@ impl Drop for Loaded {
 pub struct Container {
     active_plugins: HashSet<Ident>,
 
+    /// Indices of loaded plugins in initialization order.
+    /// Follows project order where dependencies allow.
+    order: Arc<[usize]>,
+
     // Unload library last.
     loaded: Arc<Loaded>,
 }
@@ -171,12 +179,19 @@ impl fmt::Debug for Container {
 }
 
 impl Container {
-    /// Create a new container from same library with the given plugins enabled.
-    pub fn with_plugins(&self, enabled_plugins: &HashSet<Ident>) -> Self {
+    /// Create a new container from same library with the given plugins enabled
+    /// and initialized in the given order.
+    pub fn with_plugins(
+        &self,
+        enabled_plugins: &HashSet<Ident>,
+        order: impl IntoIterator<Item = Ident>,
+    ) -> Self {
         let active_plugins = get_active_plugins(&self.loaded, enabled_plugins);
+        let order = order_plugins(&self.loaded, order);
         Container {
             loaded: self.loaded.clone(),
             active_plugins,
+            order,
         }
     }
 
@@ -188,13 +203,23 @@ impl Container {
         self.active_plugins.contains(&name)
     }
 
-    // pub fn get(&self, name: Ident) -> Option<&ArcanaPlugin> {
-    //     let (_, p) = self.loaded.plugins.iter().find(|(n, _)| *n == name)?;
-    //     Some(*p)
-    // }
+    /// Returns dependencies of the plugin that are not active.
+    pub fn unmet_dependencies(&self, name: Ident) -> Vec<Ident> {
+        let Some((_, plugin)) = self.loaded.plugins.iter().find(|(n, _)| *n == name) else {
+            return Vec::new();
+        };
+
+        plugin
+            .dependencies()
+            .into_iter()
+            .map(|(dep_name, _)| dep_name)
+            .filter(|dep_name| !self.active_plugins.contains(dep_name))
+            .collect()
+    }
 
     pub fn plugins<'a>(&'a self) -> impl Iterator<Item = (Ident, &'a ArcanaPlugin)> + Clone + 'a {
-        self.loaded.plugins.iter().filter_map(|(name, plugin)| {
+        self.order.iter().filter_map(|&idx| {
+            let (name, plugin) = &self.loaded.plugins[idx];
             if self.active_plugins.contains(name) {
                 Some((*name, plugin))
             } else {
@@ -214,6 +239,10 @@ impl PartialEq for Container {
             return false;
         }
 
+        if self.order != other.order {
+            return false;
+        }
+
         true
     }
 }
@@ -418,6 +447,7 @@ impl Loader {
         &mut self,
         path: &Path,
         enabled_plugins: &HashSet<Ident>,
+        order: impl IntoIterator<Item = Ident>,
     ) -> miette::Result<Container> {
         let new_path = find_tmp_path(path).wrap_err("Failed to find temp path for dylib")?;
 
@@ -432,10 +462,12 @@ impl Loader {
         };
 
         let active_plugins = get_active_plugins(&loaded, enabled_plugins);
+        let order = order_plugins(&loaded, order);
 
         Ok(Container {
             loaded,
-            active_plugins: active_plugins.into(),
+            active_plugins,
+            order,
         })
     }
 }
@@ -463,6 +495,43 @@ fn get_active_plugins(loaded: &Loaded, enabled_plugins: &HashSet<Ident>) -> Hash
     active_set
 }
 
+/// Orders loaded plugins following preferred order,
+/// moving dependencies before plugins that depend on them.
+///
+/// Plugins missing in preferred order are placed last.
+fn order_plugins(loaded: &Loaded, preferred: impl IntoIterator<Item = Ident>) -> Arc<[usize]> {
+    fn visit(loaded: &Loaded, idx: usize, placed: &mut Vec<bool>, order: &mut Vec<usize>) {
+        if placed[idx] {
+            return;
+        }
+        placed[idx] = true;
+
+        // Circular dependencies are rejected on load.
+        for (dep_name, _) in loaded.plugins[idx].1.dependencies() {
+            if let Some(dep) = loaded.plugins.iter().position(|(n, _)| *n == dep_name) {
+                visit(loaded, dep, placed, order);
+            }
+        }
+
+        order.push(idx);
+    }
+
+    let mut placed = vec![false; loaded.plugins.len()];
+    let mut order = Vec::with_capacity(loaded.plugins.len());
+
+    for name in preferred {
+        if let Some(idx) = loaded.plugins.iter().position(|(n, _)| *n == name) {
+            visit(loaded, idx, &mut placed, &mut order);
+        }
+    }
+
+    for idx in 0..loaded.plugins.len() {
+        visit(loaded, idx, &mut placed, &mut order);
+    }
+
+    order.into()
+}
+
 fn load_lib(path: &Path, new_path: PathBuf) -> miette::Result<Loaded> {
     let tmp = copy_dylib(path, new_path).wrap_err("Failed to copy dylib")?;
 
@@ -525,8 +594,19 @@ fn load_lib(path: &Path, new_path: PathBuf) -> miette::Result<Loaded> {
     let mut plugins = arcana_plugins();
     sort_plugins(&mut plugins)?;
 
+    let sandbox_keys = plugins
+        .iter()
+        .map(|(name, plugin)| {
+            let key = sandbox::issue_key(*name);
+            let secret = key.secret();
+            plugin.bind_sandbox_key(key);
+            secret
+        })
+        .collect();
+
     Ok(Loaded {
         plugins: plugins.into(),
+        sandbox_keys,
         _lib: lib,
         _tmp: tmp,
     })
@@ -0,0 +1,57 @@
+//! Cook mode of the editor.
+//!
+//! Imports all project assets using importers from project plugins
+//! and packs them into archive shipped with the game.
+
+use std::{path::Path, time::Duration};
+
+use miette::{Context, IntoDiagnostic};
+
+use crate::{assets::archive::ArchiveWriter, plugin::PluginsHub};
+
+use super::{assets::Assets, container, get_profile, load_project};
+
+pub(super) fn cook(project_path: &Path, output: &Path) -> miette::Result<()> {
+    crate::plugin::set_running_arcana_instance();
+
+    if let Err(err) = tracing::subscriber::set_global_default(
+        tracing_subscriber::fmt()
+            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            .finish(),
+    ) {
+        panic!("Failed to install tracing subscriber: {}", err);
+    }
+
+    let (project, data) = load_project(project_path)?;
+
+    tracing::info!("Building plugins");
+    let mut build = project.build_plugins_library(get_profile())?;
+    while !build.finished()? {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    let container = container::Loader::new()
+        .load(
+            build.artifact(),
+            &data.enabled_plugins,
+            project.plugins().iter().map(|p| p.name),
+        )
+        .wrap_err("Failed to load plugins")?;
+
+    let mut hub = PluginsHub::new();
+    for (_, plugin) in container.plugins() {
+        plugin.fill_hub(&mut hub);
+    }
+
+    let mut assets = Assets::new(&project.root_path().join("Assets"));
+    for (_, importer) in hub.importers.drain() {
+        assets.register_importer(importer);
+    }
+
+    let mut archive = ArchiveWriter::create(output).into_diagnostic()?;
+    let count = assets.pack(&mut archive)?;
+    archive.finish().into_diagnostic()?;
+
+    tracing::info!("Packed {} assets into '{}'", count, output.display());
+    Ok(())
+}
@@ -0,0 +1,197 @@
+//! Editor widget for [`Curve`] values.
+
+use arcana::curve::{Curve, Interpolation, Keyframe};
+use egui::{
+    emath::RectTransform, pos2, vec2, Pos2, Rect, Response, Sense, Shape, Stroke, Ui, Widget,
+};
+
+/// Number of segments used to draw the curve.
+const SEGMENTS: usize = 96;
+
+const HEIGHT: f32 = 100.0;
+
+const KEY_RADIUS: f32 = 4.0;
+
+/// Shows curve plot with draggable keys.
+///
+/// - Drag key to move it.
+/// - Double-click to add key.
+/// - Right-click key to change interpolation or remove it.
+pub struct CurveEditor<'a> {
+    curve: &'a mut Curve,
+}
+
+impl<'a> CurveEditor<'a> {
+    pub fn new(curve: &'a mut Curve) -> Self {
+        CurveEditor { curve }
+    }
+}
+
+impl Widget for CurveEditor<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let width = ui.available_width().max(160.0);
+        let (rect, mut response) =
+            ui.allocate_exact_size(vec2(width, HEIGHT), Sense::click_and_drag());
+
+        let bounds = view_bounds(self.curve);
+        let to_screen = RectTransform::from_to(bounds, rect.shrink(KEY_RADIUS));
+        let from_screen = to_screen.inverse();
+
+        let mut changed = false;
+        let mut remove = None;
+
+        let visuals = ui.style().visuals.clone();
+        let painter = ui.painter_at(rect);
+
+        painter.rect_filled(
+            rect,
+            visuals.widgets.noninteractive.rounding,
+            visuals.extreme_bg_color,
+        );
+
+        // Unit lines help to see where 0 and 1 are.
+        let grid = Stroke::new(1.0, visuals.widgets.noninteractive.bg_stroke.color);
+        for v in [0.0, 1.0] {
+            let y = to_screen.transform_pos(pos2(0.0, v)).y;
+            if rect.y_range().contains(y) {
+                painter.hline(rect.x_range(), y, grid);
+            }
+        }
+        for t in [0.0, 1.0] {
+            let x = to_screen.transform_pos(pos2(t, 0.0)).x;
+            if rect.x_range().contains(x) {
+                painter.vline(x, rect.y_range(), grid);
+            }
+        }
+
+        // Keys are handled before drawing so the plot reflects this frame's edits.
+        let mut key_shapes = Vec::with_capacity(self.curve.keys().len());
+
+        for idx in 0..self.curve.keys().len() {
+            let key = self.curve.keys()[idx];
+            let center = to_screen.transform_pos(key_pos(&key));
+
+            let key_rect = Rect::from_center_size(center, vec2(KEY_RADIUS * 3.0, KEY_RADIUS * 3.0));
+            let key_response =
+                ui.interact(key_rect, response.id.with(idx), Sense::click_and_drag());
+
+            if key_response.dragged() {
+                if let Some(pointer) = key_response.interact_pointer_pos() {
+                    let pos = from_screen.transform_pos(pointer.clamp(rect.min, rect.max));
+                    self.curve.move_key(idx, pos.x, pos.y);
+                    changed = true;
+                }
+            }
+
+            key_response.context_menu(|ui| {
+                for interp in Interpolation::ALL {
+                    if ui.radio(key.interp == interp, interp.name()).clicked() {
+                        self.curve.set_interp(idx, interp);
+                        changed = true;
+                        ui.close_menu();
+                    }
+                }
+
+                ui.separator();
+
+                if ui.button("Remove key").clicked() {
+                    remove = Some(idx);
+                    ui.close_menu();
+                }
+            });
+
+            let key_response = key_response.on_hover_text(format!(
+                "t: {:.3}, v: {:.3}, {}",
+                key.time,
+                key.value,
+                key.interp.name()
+            ));
+
+            let key = self.curve.keys()[idx];
+            let center = to_screen.transform_pos(key_pos(&key));
+            let color = if key_response.hovered() || key_response.dragged() {
+                visuals.selection.stroke.color
+            } else {
+                visuals.widgets.inactive.fg_stroke.color
+            };
+            key_shapes.push(Shape::circle_filled(center, KEY_RADIUS, color));
+        }
+
+        if let Some(idx) = remove {
+            self.curve.remove(idx);
+            changed = true;
+        }
+
+        if response.double_clicked() {
+            if let Some(pointer) = response.interact_pointer_pos() {
+                let pos = from_screen.transform_pos(pointer);
+                self.curve.insert(Keyframe {
+                    time: pos.x,
+                    value: pos.y,
+                    interp: Interpolation::default(),
+                });
+                changed = true;
+            }
+        }
+
+        let points = (0..=SEGMENTS)
+            .map(|i| {
+                let t = bounds.min.x + bounds.width() * i as f32 / SEGMENTS as f32;
+                to_screen.transform_pos(pos2(t, self.curve.sample(t)))
+            })
+            .collect::<Vec<Pos2>>();
+
+        painter.add(Shape::line(
+            points,
+            Stroke::new(1.5, visuals.widgets.active.fg_stroke.color),
+        ));
+
+        if remove.is_none() {
+            painter.extend(key_shapes);
+        }
+
+        if self.curve.is_empty() {
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                "Double-click to add key",
+                egui::FontId::default(),
+                visuals.weak_text_color(),
+            );
+        }
+
+        if changed {
+            response.mark_changed();
+        }
+
+        response
+    }
+}
+
+/// Curve space position of the key.
+fn key_pos(key: &Keyframe) -> Pos2 {
+    pos2(key.time, key.value)
+}
+
+/// Returns curve space rect that contains all keys and unit square.
+/// Y axis is flipped to map greater values to the top of the screen.
+fn view_bounds(curve: &Curve) -> Rect {
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+    let (mut v0, mut v1) = (0.0f32, 1.0f32);
+
+    if let Some((first, last)) = curve.time_range() {
+        t0 = t0.min(first);
+        t1 = t1.max(last);
+    }
+
+    // Smooth segments may overshoot keys, so sample instead of checking keys only.
+    for i in 0..=SEGMENTS {
+        let t = t0 + (t1 - t0) * i as f32 / SEGMENTS as f32;
+        let v = curve.sample(t);
+        v0 = v0.min(v);
+        v1 = v1.max(v);
+    }
+
+    let pad = (v1 - v0) * 0.05;
+    Rect::from_min_max(pos2(t0, v1 + pad), pos2(t1, v0 - pad))
+}
@@ -3,10 +3,20 @@
 
 use std::io::Write;
 
-use arcana::{code::CodeGraphId, project::Project, render::RenderGraphId, Ident};
+use arcana::{
+    code::CodeGraphId,
+    project::{Permissions, Project, PROJECT_DATA_VERSION},
+    render::RenderGraphId,
+    Ident,
+};
 use hashbrown::{HashMap, HashSet};
 
-use super::{code::CodeGraph, filters::Funnel, render::RenderGraph, systems::SystemGraph};
+use super::{
+    code::CodeGraph,
+    filters::Funnel,
+    render::RenderGraph,
+    systems::{SystemGraph, DEFAULT_FIX_RATE},
+};
 
 /// In combination with `ProjectManifest` this defines the project completely.
 /// This includes enabled plugins, filter chain, system graph, asset collections, etc
@@ -14,12 +24,32 @@ use super::{code::CodeGraph, filters::Funnel, render::RenderGraph, systems::Syst
 /// Stored in the Ed's main `World`.
 #[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct ProjectData {
+    /// Version of the project data format.
+    /// Older data is migrated on load.
+    #[serde(default)]
+    pub version: u32,
+
     /// Set of enabled plugins.
     pub enabled_plugins: HashSet<Ident>,
 
+    /// Permissions the user granted to plugins.
+    #[serde(default)]
+    pub granted_permissions: HashMap<Ident, Permissions>,
+
     /// Systems graph.
     pub systems: SystemGraph,
 
+    /// Frequency of fixed updates in hertz.
+    /// Default is used if not set.
+    #[serde(default)]
+    pub fix_rate: Option<u64>,
+
+    /// Run systems one by one on the main thread.
+    /// Slower, but doesn't depend on thread timing,
+    /// which helps to debug systems with undeclared access.
+    #[serde(default)]
+    pub sequential_systems: bool,
+
     /// Event funnel.
     pub funnel: Funnel,
 
@@ -28,10 +58,21 @@ pub struct ProjectData {
 
     /// Code graphs.
     pub codes: HashMap<CodeGraphId, CodeGraph>,
+
+    /// Render graph of the renderer spawned by the editor
+    /// when plugins don't spawn one.
+    #[serde(default)]
+    pub main_render_graph: Option<RenderGraphId>,
 }
 
 impl ProjectData {
+    pub fn fix_rate(&self) -> u64 {
+        self.fix_rate.unwrap_or(DEFAULT_FIX_RATE)
+    }
+
     pub fn sync(&mut self, project: &Project) -> miette::Result<()> {
+        self.version = PROJECT_DATA_VERSION;
+
         let path = project.root_path().join("Arcana.bin");
         let bak = path.with_extension("bin.bak");
 
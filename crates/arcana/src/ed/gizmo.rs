@@ -0,0 +1,436 @@
+//! Gizmos to translate, rotate and scale entities in the scene view.
+//!
+//! Gizmo is drawn with egui painter over the view image.
+//! Transform of the entity is accessed through hooks plugins put into the world,
+//! see `arcana::gizmo`.
+
+use std::f32::consts::TAU;
+
+use arcana::{gizmo::Transform2, na, viewport::ViewId, EntityId};
+use egui::{Color32, Pos2, Rect, Sense, Stroke, Ui, Vec2};
+
+use super::instance::Instance;
+
+/// Length of axis handles in points.
+const AXIS_LENGTH: f32 = 64.0;
+
+/// Size of handle hit boxes in points.
+const HANDLE_SIZE: f32 = 12.0;
+
+/// Radius of rotation ring in points.
+const RING_RADIUS: f32 = 48.0;
+
+const X_COLOR: Color32 = Color32::from_rgb(230, 70, 70);
+const Y_COLOR: Color32 = Color32::from_rgb(70, 200, 70);
+const BOTH_COLOR: Color32 = Color32::from_rgb(80, 140, 240);
+const ACTIVE_COLOR: Color32 = Color32::from_rgb(250, 220, 60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GizmoMode {
+    Translate,
+    Rotate,
+    Scale,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Axis {
+    X,
+    Y,
+    Both,
+}
+
+/// Gizmo drag in progress.
+struct Drag {
+    axis: Axis,
+
+    /// Entity transform when drag started.
+    start: Transform2,
+
+    /// Pointer position in world space when drag started.
+    pointer: na::Point2<f32>,
+}
+
+/// Maps between world space and screen rect of the view.
+struct Projection {
+    rect: Rect,
+    world_to_view: na::Affine2<f32>,
+    view_to_world: na::Affine2<f32>,
+}
+
+impl Projection {
+    fn new(instance: &Instance, view: ViewId, rect: Rect) -> Option<Self> {
+        if rect.width() <= 0.0 || rect.height() <= 0.0 {
+            return None;
+        }
+
+        // View image is stretched over the rect.
+        let view_to_world = instance.view_to_world(view)?;
+        let world_to_view = view_to_world.try_inverse()?;
+
+        Some(Projection {
+            rect,
+            world_to_view,
+            view_to_world,
+        })
+    }
+
+    fn to_screen(&self, point: &na::Point2<f32>) -> Pos2 {
+        let ndc = self.world_to_view.transform_point(point);
+        let center = self.rect.center();
+        Pos2::new(
+            center.x + ndc.x * self.rect.width() * 0.5,
+            center.y - ndc.y * self.rect.height() * 0.5,
+        )
+    }
+
+    fn to_world(&self, pos: Pos2) -> na::Point2<f32> {
+        let center = self.rect.center();
+        let ndc = na::Point2::new(
+            (pos.x - center.x) / (self.rect.width() * 0.5),
+            (center.y - pos.y) / (self.rect.height() * 0.5),
+        );
+        self.view_to_world.transform_point(&ndc)
+    }
+}
+
+pub struct Gizmo {
+    mode: GizmoMode,
+
+    /// Snap transform to steps below while dragging.
+    snap: bool,
+    translate_step: f32,
+
+    /// Rotation step in degrees.
+    rotate_step: f32,
+    scale_step: f32,
+
+    drag: Option<Drag>,
+
+    /// Pointer is over a handle or drags it.
+    interacting: bool,
+}
+
+impl Gizmo {
+    pub fn new() -> Self {
+        Gizmo {
+            mode: GizmoMode::Translate,
+            snap: false,
+            translate_step: 0.5,
+            rotate_step: 15.0,
+            scale_step: 0.1,
+            drag: None,
+            interacting: false,
+        }
+    }
+
+    /// Returns true if gizmo handles pointer input,
+    /// so clicks should not pick entities.
+    pub fn interacting(&self) -> bool {
+        self.interacting
+    }
+
+    /// Picks entity at the screen position in the view rect.
+    pub fn pick(instance: &Instance, view: ViewId, rect: Rect, pos: Pos2) -> Option<EntityId> {
+        let projection = Projection::new(instance, view, rect)?;
+        let point = projection.to_world(pos);
+
+        // Accept entities within handle size from the cursor.
+        let edge = projection.to_world(pos + Vec2::splat(HANDLE_SIZE * 0.5));
+        let tolerance = (edge - point).norm();
+
+        instance.pick(point, tolerance)
+    }
+
+    pub fn show_toolbar(&mut self, ui: &mut Ui) {
+        ui.scope(|ui| {
+            ui.selectable_value(&mut self.mode, GizmoMode::Translate, "Move")
+                .on_hover_text("W");
+            ui.selectable_value(&mut self.mode, GizmoMode::Rotate, "Rotate")
+                .on_hover_text("E");
+            ui.selectable_value(&mut self.mode, GizmoMode::Scale, "Scale")
+                .on_hover_text("R");
+
+            ui.separator();
+
+            ui.checkbox(&mut self.snap, "Snap");
+            ui.add_enabled_ui(self.snap, |ui| match self.mode {
+                GizmoMode::Translate => {
+                    ui.add(
+                        egui::DragValue::new(&mut self.translate_step)
+                            .speed(0.05)
+                            .range(0.001..=f32::MAX),
+                    );
+                }
+                GizmoMode::Rotate => {
+                    ui.add(
+                        egui::DragValue::new(&mut self.rotate_step)
+                            .speed(1.0)
+                            .range(0.1..=180.0)
+                            .suffix("°"),
+                    );
+                }
+                GizmoMode::Scale => {
+                    ui.add(
+                        egui::DragValue::new(&mut self.scale_step)
+                            .speed(0.01)
+                            .range(0.001..=f32::MAX),
+                    );
+                }
+            });
+        });
+    }
+
+    /// Draws gizmo for the entity over view rect and applies dragging to its transform.
+    pub fn show(
+        &mut self,
+        instance: &mut Instance,
+        view: ViewId,
+        entity: EntityId,
+        rect: Rect,
+        ui: &mut Ui,
+    ) {
+        self.interacting = false;
+
+        if !ui.ctx().wants_keyboard_input() {
+            ui.input(|input| {
+                if input.key_pressed(egui::Key::W) {
+                    self.mode = GizmoMode::Translate;
+                }
+                if input.key_pressed(egui::Key::E) {
+                    self.mode = GizmoMode::Rotate;
+                }
+                if input.key_pressed(egui::Key::R) {
+                    self.mode = GizmoMode::Scale;
+                }
+            });
+        }
+
+        let Some(transform) = instance.entity_transform(entity) else {
+            self.drag = None;
+            return;
+        };
+
+        let Some(projection) = Projection::new(instance, view, rect) else {
+            self.drag = None;
+            return;
+        };
+
+        let origin = projection.to_screen(&na::Point2::from(transform.translation));
+
+        // Screen directions of entity axes.
+        // Translation uses world axes, scale uses local ones.
+        let (x_dir, y_dir) = match self.mode {
+            GizmoMode::Scale => {
+                let rotation = na::Rotation2::new(transform.rotation);
+                let x = rotation * na::Vector2::x();
+                let y = rotation * na::Vector2::y();
+                (
+                    screen_dir(&projection, &transform, x),
+                    screen_dir(&projection, &transform, y),
+                )
+            }
+            _ => (
+                screen_dir(&projection, &transform, na::Vector2::x()),
+                screen_dir(&projection, &transform, na::Vector2::y()),
+            ),
+        };
+
+        let id = ui.id().with("gizmo").with(entity);
+
+        let handles = match self.mode {
+            GizmoMode::Translate | GizmoMode::Scale => vec![
+                (Axis::X, handle_rect(origin + x_dir * AXIS_LENGTH)),
+                (Axis::Y, handle_rect(origin + y_dir * AXIS_LENGTH)),
+                (Axis::Both, handle_rect(origin)),
+            ],
+            GizmoMode::Rotate => vec![(
+                Axis::Both,
+                Rect::from_center_size(origin, Vec2::splat(RING_RADIUS * 2.0 + HANDLE_SIZE)),
+            )],
+        };
+
+        let mut hovered = None;
+
+        for (axis, handle) in handles {
+            let r = ui.interact(handle.intersect(rect), id.with(axis), Sense::drag());
+
+            if r.hovered() {
+                hovered = Some(axis);
+            }
+
+            if r.drag_started() {
+                if let Some(pos) = r.interact_pointer_pos() {
+                    self.drag = Some(Drag {
+                        axis,
+                        start: transform,
+                        pointer: projection.to_world(pos),
+                    });
+                }
+            }
+
+            if r.dragged() {
+                if let (Some(drag), Some(pos)) = (&self.drag, r.interact_pointer_pos()) {
+                    if drag.axis == axis {
+                        let updated = self.apply(drag, projection.to_world(pos));
+                        if updated != transform {
+                            instance.set_entity_transform(entity, updated);
+                        }
+                    }
+                }
+            }
+
+            if r.drag_stopped() {
+                self.drag = None;
+            }
+        }
+
+        self.interacting = hovered.is_some() || self.drag.is_some();
+
+        let active = self.drag.as_ref().map(|drag| drag.axis).or(hovered);
+        let color = |axis: Axis, base: Color32| {
+            if active == Some(axis) {
+                ACTIVE_COLOR
+            } else {
+                base
+            }
+        };
+
+        let painter = ui.painter_at(rect);
+
+        match self.mode {
+            GizmoMode::Translate => {
+                painter.arrow(
+                    origin,
+                    x_dir * AXIS_LENGTH,
+                    Stroke::new(2.0, color(Axis::X, X_COLOR)),
+                );
+                painter.arrow(
+                    origin,
+                    y_dir * AXIS_LENGTH,
+                    Stroke::new(2.0, color(Axis::Y, Y_COLOR)),
+                );
+                painter.rect_filled(handle_rect(origin), 2.0, color(Axis::Both, BOTH_COLOR));
+            }
+            GizmoMode::Rotate => {
+                let stroke = Stroke::new(2.0, color(Axis::Both, BOTH_COLOR));
+                painter.circle_stroke(origin, RING_RADIUS, stroke);
+
+                let angle = screen_angle(&projection, &transform);
+                let tip = origin + Vec2::angled(angle) * RING_RADIUS;
+                painter.line_segment([origin, tip], stroke);
+                painter.circle_filled(tip, 4.0, stroke.color);
+            }
+            GizmoMode::Scale => {
+                for (axis, dir, base) in [(Axis::X, x_dir, X_COLOR), (Axis::Y, y_dir, Y_COLOR)] {
+                    let end = origin + dir * AXIS_LENGTH;
+                    let color = color(axis, base);
+                    painter.line_segment([origin, end], Stroke::new(2.0, color));
+                    painter.rect_filled(handle_rect(end), 0.0, color);
+                }
+                painter.rect_filled(handle_rect(origin), 0.0, color(Axis::Both, BOTH_COLOR));
+            }
+        }
+    }
+
+    /// Returns new transform for the pointer dragged to the world position.
+    fn apply(&self, drag: &Drag, pointer: na::Point2<f32>) -> Transform2 {
+        let mut transform = drag.start;
+        let origin = na::Point2::from(drag.start.translation);
+
+        match self.mode {
+            GizmoMode::Translate => {
+                let delta = pointer - drag.pointer;
+                let mut translation = drag.start.translation;
+
+                if drag.axis != Axis::Y {
+                    translation.x += delta.x;
+                }
+                if drag.axis != Axis::X {
+                    translation.y += delta.y;
+                }
+
+                if self.snap {
+                    translation = translation.map(|v| snap(v, self.translate_step));
+                }
+
+                transform.translation = translation;
+            }
+            GizmoMode::Rotate => {
+                let from = drag.pointer - origin;
+                let to = pointer - origin;
+
+                if from.norm_squared() > 0.0 && to.norm_squared() > 0.0 {
+                    let delta = from.y.atan2(from.x) - to.y.atan2(to.x);
+                    let mut rotation = (drag.start.rotation - delta).rem_euclid(TAU);
+
+                    if self.snap {
+                        rotation = snap(rotation, self.rotate_step.to_radians());
+                    }
+
+                    transform.rotation = rotation;
+                }
+            }
+            GizmoMode::Scale => {
+                let rotation = na::Rotation2::new(drag.start.rotation);
+                let from = rotation.inverse() * (drag.pointer - origin);
+                let to = rotation.inverse() * (pointer - origin);
+
+                let ratio = |from: f32, to: f32| {
+                    if from.abs() > f32::EPSILON {
+                        to / from
+                    } else {
+                        1.0
+                    }
+                };
+
+                let mut scale = drag.start.scale;
+                match drag.axis {
+                    Axis::X => scale.x *= ratio(from.x, to.x),
+                    Axis::Y => scale.y *= ratio(from.y, to.y),
+                    Axis::Both => scale *= ratio(from.norm(), to.norm()),
+                }
+
+                if self.snap {
+                    scale = scale.map(|v| snap(v, self.scale_step));
+                }
+
+                transform.scale = scale;
+            }
+        }
+
+        transform
+    }
+}
+
+fn snap(value: f32, step: f32) -> f32 {
+    if step > 0.0 {
+        (value / step).round() * step
+    } else {
+        value
+    }
+}
+
+fn handle_rect(center: Pos2) -> Rect {
+    Rect::from_center_size(center, Vec2::splat(HANDLE_SIZE))
+}
+
+/// Returns normalized screen direction of the world space direction at entity position.
+fn screen_dir(projection: &Projection, transform: &Transform2, dir: na::Vector2<f32>) -> Vec2 {
+    let origin = na::Point2::from(transform.translation);
+    let a = projection.to_screen(&origin);
+    let b = projection.to_screen(&(origin + dir));
+    let v = b - a;
+
+    if v.length_sq() > 0.0 {
+        v.normalized()
+    } else {
+        Vec2::ZERO
+    }
+}
+
+/// Returns screen angle of the entity X axis.
+fn screen_angle(projection: &Projection, transform: &Transform2) -> f32 {
+    let rotation = na::Rotation2::new(transform.rotation);
+    let dir = screen_dir(projection, transform, rotation * na::Vector2::x());
+    dir.y.atan2(dir.x)
+}
@@ -0,0 +1,431 @@
+//! Headless mode of the editor.
+//!
+//! Editor runs without windows and executes commands from a script file,
+//! so projects can run automated checks in CI.
+//!
+//! Script contains one command per line.
+//! Empty lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! # Re-import all assets. Fails if any asset fails to import.
+//! import
+//!
+//! # Create 1280x720 view to render.
+//! view 1280 720
+//!
+//! # Run 600 frames with 16ms step.
+//! run 600 16
+//!
+//! # Render the view with first renderer in the world and save it.
+//! screenshot screenshots/level.png
+//!
+//! # Dump frame timings collected so far.
+//! metrics metrics.json
+//! ```
+//!
+//! Relative paths are resolved against directory of the script.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use arcana::{
+    gametime::{TimeSpan, TimeStamp},
+    mev,
+    viewport::ViewId,
+    ClockStep,
+};
+use miette::Context;
+
+use crate::{plugin::PluginsHub, project::Project};
+
+use super::{
+    assets::Assets, container, data::ProjectData, filters::Filters, get_profile, init_mev,
+    instance::Instance, load_project, render::Rendering, systems::Systems,
+};
+
+/// Step used by `run` command when none is specified.
+const DEFAULT_STEP_MS: u64 = 16;
+
+enum Command {
+    Import,
+    View { width: u32, height: u32 },
+    Run { frames: u64, step_ms: u64 },
+    Screenshot { path: PathBuf },
+    Metrics { path: PathBuf },
+}
+
+/// Parses whole script upfront, so typos are reported before anything runs.
+fn parse_script(script: &str, base: &Path) -> miette::Result<Vec<(usize, Command)>> {
+    let mut commands = Vec::new();
+
+    for (idx, line) in script.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap();
+        let args: Vec<&str> = words.collect();
+
+        let number = |idx: usize, what: &str| -> miette::Result<u64> {
+            match args.get(idx) {
+                None => miette::bail!("Line {line_no}: '{name}' requires {what}"),
+                Some(arg) => match arg.parse() {
+                    Ok(value) => Ok(value),
+                    Err(_) => miette::bail!("Line {line_no}: invalid {what} '{arg}'"),
+                },
+            }
+        };
+
+        let path = |what: &str| -> miette::Result<PathBuf> {
+            match args.first() {
+                None => miette::bail!("Line {line_no}: '{name}' requires {what}"),
+                Some(arg) => Ok(base.join(arg)),
+            }
+        };
+
+        let (command, expected_args) = match name {
+            "import" => (Command::Import, 0),
+            "view" => (
+                Command::View {
+                    width: number(0, "width")? as u32,
+                    height: number(1, "height")? as u32,
+                },
+                2,
+            ),
+            "run" => (
+                Command::Run {
+                    frames: number(0, "frame count")?,
+                    step_ms: match args.len() {
+                        1 => DEFAULT_STEP_MS,
+                        _ => number(1, "step in milliseconds")?,
+                    },
+                },
+                args.len().clamp(1, 2),
+            ),
+            "screenshot" => (
+                Command::Screenshot {
+                    path: path("output path")?,
+                },
+                1,
+            ),
+            "metrics" => (
+                Command::Metrics {
+                    path: path("output path")?,
+                },
+                1,
+            ),
+            _ => miette::bail!("Line {line_no}: unknown command '{name}'"),
+        };
+
+        if args.len() != expected_args {
+            miette::bail!(
+                "Line {line_no}: '{name}' expects {expected_args} arguments, got {}",
+                args.len()
+            );
+        }
+
+        commands.push((line_no, command));
+    }
+
+    Ok(commands)
+}
+
+/// Timings of frames executed in headless mode.
+#[derive(Default)]
+struct Metrics {
+    frames: u64,
+    simulated: Duration,
+    tick_total: Duration,
+    tick_max: Duration,
+    screenshots: u64,
+}
+
+impl Metrics {
+    fn to_json(&self) -> serde_json::Value {
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+
+        let tick_avg = match self.frames {
+            0 => Duration::ZERO,
+            frames => self.tick_total / frames as u32,
+        };
+
+        serde_json::json!({
+            "frames": self.frames,
+            "simulated_ms": ms(self.simulated),
+            "tick_total_ms": ms(self.tick_total),
+            "tick_avg_ms": ms(tick_avg),
+            "tick_max_ms": ms(self.tick_max),
+            "screenshots": self.screenshots,
+        })
+    }
+}
+
+struct Headless {
+    project: Project,
+    data: ProjectData,
+    queue: mev::Queue,
+
+    assets: Assets,
+    systems: Systems,
+    instance: Instance,
+    view: Option<ViewId>,
+
+    now: TimeStamp,
+    metrics: Metrics,
+}
+
+impl Headless {
+    fn execute(&mut self, command: &Command) -> miette::Result<()> {
+        match *command {
+            Command::Import => {
+                let failed = self.assets.refresh();
+                if failed > 0 {
+                    miette::bail!("{failed} assets failed to import");
+                }
+                Ok(())
+            }
+            Command::View { width, height } => {
+                if width == 0 || height == 0 {
+                    miette::bail!("View extent must not be zero");
+                }
+
+                let view = *self.view.get_or_insert_with(|| self.instance.new_view());
+                self.instance
+                    .set_view_extent(view, mev::Extent2::new(width, height));
+                Ok(())
+            }
+            Command::Run { frames, step_ms } => {
+                let step = TimeSpan::MILLISECOND * step_ms;
+
+                for _ in 0..frames {
+                    self.now = self.now + step;
+
+                    let start = Instant::now();
+                    self.instance.tick(
+                        &self.data,
+                        &self.systems,
+                        ClockStep {
+                            now: self.now,
+                            step,
+                        },
+                    );
+                    let elapsed = start.elapsed();
+
+                    self.metrics.frames += 1;
+                    self.metrics.simulated += Duration::from_millis(step_ms);
+                    self.metrics.tick_total += elapsed;
+                    self.metrics.tick_max = self.metrics.tick_max.max(elapsed);
+                }
+                Ok(())
+            }
+            Command::Screenshot { ref path } => {
+                self.screenshot(path)?;
+                self.metrics.screenshots += 1;
+                Ok(())
+            }
+            Command::Metrics { ref path } => {
+                let json = serde_json::to_string_pretty(&self.metrics.to_json()).unwrap();
+                write_output(path, json.as_bytes())
+            }
+        }
+    }
+
+    fn screenshot(&mut self, path: &Path) -> miette::Result<()> {
+        let Some(view) = self.view else {
+            miette::bail!("No view to take screenshot of. Use 'view' command first");
+        };
+
+        let Some(renderer) = self.instance.find_renderer() else {
+            miette::bail!("World has no renderer entity");
+        };
+        self.instance.set_view_renderer(view, Some(renderer));
+
+        self.instance
+            .render_headless(&mut self.queue, &self.data)
+            .map_err(|err| miette::miette!("Failed to render view: {err:?}"))?;
+
+        let Some(image) = self.instance.view_image(view) else {
+            miette::bail!(
+                "View was not rendered. Check that renderer has render graph with present node"
+            );
+        };
+
+        let extent = image.extent().expect_2d();
+        let pixels = read_image(&mut self.queue, &image)?;
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::write_buffer_with_format(
+            &mut png,
+            &pixels,
+            extent.width(),
+            extent.height(),
+            image::ColorType::Rgba8,
+            image::ImageFormat::Png,
+        )
+        .map_err(|err| miette::miette!("Failed to encode screenshot: {err}"))?;
+
+        write_output(path, png.get_ref())?;
+        tracing::info!("Screenshot saved to '{}'", path.display());
+        Ok(())
+    }
+}
+
+/// Copies image content into host memory.
+fn read_image(queue: &mut mev::Queue, image: &mev::Image) -> miette::Result<Vec<u8>> {
+    let extent = image.extent().expect_2d();
+    let bytes_per_line = 4 * extent.width() as usize;
+    let size = bytes_per_line * extent.height() as usize;
+
+    let buffer = queue
+        .new_buffer(mev::BufferDesc {
+            size,
+            usage: mev::BufferUsage::TRANSFER_DST,
+            memory: mev::Memory::Download,
+            name: "headless-readback",
+        })
+        .map_err(|err| miette::miette!("Failed to allocate readback buffer: {err:?}"))?;
+
+    let mut encoder = queue
+        .new_command_encoder()
+        .map_err(|err| miette::miette!("Failed to create command encoder: {err:?}"))?;
+
+    {
+        let mut copy = encoder.copy();
+        copy.barrier(mev::PipelineStages::all(), mev::PipelineStages::TRANSFER);
+        copy.copy_image_to_buffer(
+            image,
+            mev::Offset3::ZERO,
+            extent.to_3d(),
+            0..1,
+            0,
+            &buffer,
+            0,
+            bytes_per_line,
+            0,
+        );
+    }
+
+    let cbuf = encoder
+        .finish()
+        .map_err(|err| miette::miette!("Failed to finish readback commands: {err:?}"))?;
+    queue
+        .submit(std::iter::once(cbuf), true)
+        .map_err(|err| miette::miette!("Failed to submit readback commands: {err:?}"))?;
+    queue
+        .wait_idle()
+        .map_err(|err| miette::miette!("Failed to wait for readback: {err:?}"))?;
+
+    let mut pixels = vec![0; size];
+    unsafe {
+        buffer.read_unchecked(0, &mut pixels);
+    }
+    Ok(pixels)
+}
+
+fn write_output(path: &Path, data: &[u8]) -> miette::Result<()> {
+    if let Some(parent) = path.parent() {
+        if let Err(err) = std::fs::create_dir_all(parent) {
+            miette::bail!("Failed to create directory '{}': {err:?}", parent.display());
+        }
+    }
+
+    if let Err(err) = std::fs::write(path, data) {
+        miette::bail!("Failed to write '{}': {err:?}", path.display());
+    }
+
+    Ok(())
+}
+
+pub(super) fn headless(project_path: &Path, script_path: &Path) -> miette::Result<()> {
+    crate::plugin::set_running_arcana_instance();
+
+    if let Err(err) = tracing::subscriber::set_global_default(
+        tracing_subscriber::fmt()
+            .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+            .finish(),
+    ) {
+        panic!("Failed to install tracing subscriber: {}", err);
+    }
+
+    let script = match std::fs::read_to_string(script_path) {
+        Ok(script) => script,
+        Err(err) => {
+            miette::bail!("Failed to read script '{}': {err:?}", script_path.display());
+        }
+    };
+
+    let base = script_path.parent().unwrap_or(Path::new("."));
+    let commands = parse_script(&script, base)?;
+
+    let (project, mut data) = load_project(project_path)?;
+
+    basis_universal::transcoder_init();
+
+    tracing::info!("Building plugins");
+    let mut build = project.build_plugins_library(get_profile())?;
+    while !build.finished()? {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    let container = container::Loader::new()
+        .load(
+            build.artifact(),
+            &data.enabled_plugins,
+            project.plugins().iter().map(|p| p.name),
+        )
+        .wrap_err("Failed to load plugins")?;
+
+    let (_device, queue) = init_mev();
+
+    let mut assets = Assets::new(&project.root_path().join("Assets"));
+    let mut hub = PluginsHub::new();
+    for (_, plugin) in container.plugins() {
+        plugin.fill_hub(&mut hub);
+    }
+    for (_, importer) in hub.importers.drain() {
+        assets.register_importer(importer);
+    }
+
+    let mut systems = Systems::new();
+    systems.update_plugins(&mut data, &container);
+    Filters::new().update_plugins(&mut data, &container);
+    Rendering::new().update_plugins(&mut data, &container);
+
+    let mut instance = Instance::new();
+    instance.update_plugins(&container);
+
+    let mut headless = Headless {
+        project,
+        data,
+        queue,
+        assets,
+        systems,
+        instance,
+        view: None,
+        now: TimeStamp::start(),
+        metrics: Metrics::default(),
+    };
+
+    for (line_no, command) in &commands {
+        headless
+            .execute(command)
+            .wrap_err_with(|| format!("Script command at line {line_no} failed"))?;
+    }
+
+    tracing::info!(
+        "Script '{}' finished for project '{}'",
+        script_path.display(),
+        headless.project.name()
+    );
+
+    // Plugins must outlive instance that uses them.
+    drop(headless);
+    drop(container);
+
+    Ok(())
+}
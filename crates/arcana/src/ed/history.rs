@@ -0,0 +1,136 @@
+//! Undo and redo of project data changes.
+//!
+//! Tools mutate `ProjectData` in place, often deep inside node graph widgets,
+//! so history records transactions as snapshots of the data
+//! taken after each UI frame that changed it.
+//!
+//! Changes made while pointer is held down are coalesced into single transaction,
+//! so dragging a slider or a node is undone in one step.
+
+use super::data::ProjectData;
+
+/// Maximum number of transactions kept for undo.
+const HISTORY_LIMIT: usize = 128;
+
+pub struct History {
+    /// Snapshots of data before each transaction.
+    undo: Vec<String>,
+
+    /// Snapshots of data after each undone transaction.
+    redo: Vec<String>,
+
+    /// Snapshot of current data.
+    current: String,
+
+    /// Transaction that is still open and accepts more changes.
+    coalescing: bool,
+}
+
+impl History {
+    pub fn new(data: &ProjectData) -> Self {
+        History {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            current: snapshot(data).unwrap_or_default(),
+            coalescing: false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Records changes made to the data since last call.
+    ///
+    /// If `dragging` is true, transaction is kept open
+    /// and following changes are merged into it until dragging stops.
+    pub fn record(&mut self, data: &ProjectData, dragging: bool) {
+        let Some(snapshot) = snapshot(data) else {
+            return;
+        };
+
+        if snapshot == self.current {
+            self.coalescing &= dragging;
+            return;
+        }
+
+        let before = std::mem::replace(&mut self.current, snapshot);
+
+        if !self.coalescing {
+            if self.undo.len() == HISTORY_LIMIT {
+                self.undo.remove(0);
+            }
+            self.undo.push(before);
+        }
+
+        self.redo.clear();
+        self.coalescing = dragging;
+    }
+
+    /// Reverts last transaction.
+    /// Returns true if data was changed.
+    pub fn undo(&mut self, data: &mut ProjectData) -> bool {
+        let Some(before) = self.undo.pop() else {
+            return false;
+        };
+
+        let after = std::mem::replace(&mut self.current, before);
+        self.redo.push(after);
+        self.coalescing = false;
+
+        restore(&self.current, data)
+    }
+
+    /// Re-applies last undone transaction.
+    /// Returns true if data was changed.
+    pub fn redo(&mut self, data: &mut ProjectData) -> bool {
+        let Some(after) = self.redo.pop() else {
+            return false;
+        };
+
+        let before = std::mem::replace(&mut self.current, after);
+        self.undo.push(before);
+        self.coalescing = false;
+
+        restore(&self.current, data)
+    }
+}
+
+fn snapshot(data: &ProjectData) -> Option<String> {
+    match serde_json::to_string(data) {
+        Ok(snapshot) => Some(snapshot),
+        Err(err) => {
+            tracing::error!("Failed to snapshot project data: {err}");
+            None
+        }
+    }
+}
+
+fn restore(snapshot: &str, data: &mut ProjectData) -> bool {
+    match serde_json::from_str::<ProjectData>(snapshot) {
+        Ok(restored) => {
+            // Render graphs must be rebuilt from restored nodes.
+            let modification = data
+                .render_graphs
+                .values()
+                .map(|graph| graph.modification)
+                .max()
+                .unwrap_or(0);
+
+            *data = restored;
+
+            for graph in data.render_graphs.values_mut() {
+                graph.modification = modification + 1;
+            }
+            true
+        }
+        Err(err) => {
+            tracing::error!("Failed to restore project data: {err}");
+            false
+        }
+    }
+}
@@ -0,0 +1,440 @@
+//! Editor for input action maps.
+//!
+//! Lists `.actions` sources in project assets,
+//! shows actions of each context and rebinds them
+//! by capturing next key or mouse button press.
+
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, RichText, Ui};
+use hashbrown::HashMap;
+use winit::{event::WindowEvent, keyboard::KeyCode};
+
+use crate::{
+    input::{ActionBinding, ActionContext, ActionMap, Binding, ViewInput},
+    project::Project,
+};
+
+/// Extension of action map sources.
+const EXTENSION: &str = "actions";
+
+/// Action waiting for the input to bind.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Capture {
+    context: usize,
+    action: usize,
+}
+
+pub struct InputMaps {
+    /// Action map sources relative to assets directory.
+    files: Vec<PathBuf>,
+    scanned: bool,
+
+    selected: Option<PathBuf>,
+    map: ActionMap,
+    modified: bool,
+
+    capture: Option<Capture>,
+
+    new_file: String,
+    new_context: String,
+    new_actions: HashMap<usize, String>,
+}
+
+impl InputMaps {
+    pub fn new() -> Self {
+        InputMaps {
+            files: Vec::new(),
+            scanned: false,
+            selected: None,
+            map: ActionMap::default(),
+            modified: false,
+            capture: None,
+            new_file: String::new(),
+            new_context: String::new(),
+            new_actions: HashMap::new(),
+        }
+    }
+
+    /// Binds pressed key or mouse button to the action waiting for it.
+    ///
+    /// Returns `true` if the event is consumed.
+    pub fn capture(&mut self, event: &WindowEvent) -> bool {
+        let Some(capture) = self.capture else {
+            return false;
+        };
+
+        let Ok(input) = ViewInput::try_from(event) else {
+            return false;
+        };
+
+        match input {
+            ViewInput::KeyboardInput { .. } | ViewInput::MouseInput { .. } => {}
+            _ => return false,
+        }
+
+        let Some(binding) = Binding::pressed(&input) else {
+            // Swallow releases and repeats too.
+            return true;
+        };
+
+        self.capture = None;
+
+        if binding == Binding::Key(KeyCode::Escape) {
+            return true;
+        }
+
+        let Some(action) = self
+            .map
+            .contexts
+            .get_mut(capture.context)
+            .and_then(|c| c.actions.get_mut(capture.action))
+        else {
+            return true;
+        };
+
+        if !action.bindings.contains(&binding) {
+            action.bindings.push(binding);
+            self.modified = true;
+        }
+
+        true
+    }
+
+    fn scan(&mut self, assets: &Path) {
+        self.files.clear();
+        scan_dir(assets, assets, &mut self.files);
+        self.files.sort();
+        self.scanned = true;
+    }
+
+    fn open(&mut self, assets: &Path, file: PathBuf) {
+        self.capture = None;
+        self.modified = false;
+        self.new_actions.clear();
+
+        let path = assets.join(&file);
+        let map = std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| ActionMap::from_toml(&text).map_err(|err| err.to_string()));
+
+        match map {
+            Ok(map) => self.map = map,
+            Err(err) => {
+                tracing::error!("Failed to read action map '{}': {err}", path.display());
+                self.map = ActionMap::default();
+            }
+        }
+
+        self.selected = Some(file);
+    }
+
+    fn save(&mut self, assets: &Path) {
+        let Some(file) = &self.selected else {
+            return;
+        };
+
+        let path = assets.join(file);
+        match std::fs::write(&path, self.map.to_toml()) {
+            Ok(()) => self.modified = false,
+            Err(err) => {
+                tracing::error!("Failed to write action map '{}': {err}", path.display());
+            }
+        }
+    }
+
+    pub fn show(&mut self, project: &Project, ui: &mut Ui) {
+        let assets = project.root_path().join("Assets");
+
+        if !self.scanned {
+            self.scan(&assets);
+        }
+
+        let mut open = None;
+
+        ui.horizontal(|ui| {
+            let selected = match &self.selected {
+                None => "Select action map".to_owned(),
+                Some(file) => file.display().to_string(),
+            };
+
+            egui::ComboBox::from_id_source("action-map-file")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for file in &self.files {
+                        let r = ui.selectable_label(
+                            self.selected.as_ref() == Some(file),
+                            file.display().to_string(),
+                        );
+                        if r.clicked() {
+                            open = Some(file.clone());
+                        }
+                    }
+                });
+
+            if ui
+                .button(egui_phosphor::regular::ARROWS_CLOCKWISE)
+                .on_hover_text("Rescan assets")
+                .clicked()
+            {
+                self.scan(&assets);
+            }
+
+            ui.separator();
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_file)
+                    .hint_text("New action map")
+                    .desired_width(150.0),
+            );
+
+            let r = ui.add_enabled(
+                !self.new_file.trim().is_empty(),
+                egui::Button::new(egui_phosphor::regular::FILE_PLUS),
+            );
+            if r.on_hover_text("Create action map").clicked() {
+                let file = PathBuf::from(format!("{}.{EXTENSION}", self.new_file.trim()));
+                let path = assets.join(&file);
+
+                if path.exists() {
+                    tracing::error!("Action map '{}' already exists", path.display());
+                } else {
+                    self.new_file.clear();
+                    self.selected = Some(file);
+                    self.map = ActionMap::default();
+                    self.new_actions.clear();
+                    self.save(&assets);
+                    self.scan(&assets);
+                }
+            }
+        });
+
+        // Switching away drops unsaved changes.
+        if let Some(file) = open {
+            self.open(&assets, file);
+        }
+
+        if self.selected.is_none() {
+            ui.separator();
+            ui.label("No action map selected");
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            let r = ui.add_enabled(
+                self.modified,
+                egui::Button::new(egui_phosphor::regular::FLOPPY_DISK),
+            );
+            if r.on_hover_text("Save").clicked() {
+                self.save(&assets);
+            }
+
+            let r = ui.add_enabled(
+                self.modified,
+                egui::Button::new(egui_phosphor::regular::ARROW_COUNTER_CLOCKWISE),
+            );
+            if r.on_hover_text("Revert").clicked() {
+                if let Some(file) = self.selected.clone() {
+                    self.open(&assets, file);
+                }
+            }
+
+            if self.capture.is_some() {
+                ui.colored_label(
+                    Color32::YELLOW,
+                    "Press key or mouse button to bind, Escape to cancel",
+                );
+            }
+        });
+
+        ui.separator();
+
+        let mut remove_context = None;
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for (cidx, context) in self.map.contexts.iter_mut().enumerate() {
+                    let header = egui::CollapsingHeader::new(&context.name)
+                        .id_source(("action-context", cidx))
+                        .default_open(true);
+
+                    let r = header.show(ui, |ui| {
+                        let new_action = self.new_actions.entry(cidx).or_default();
+
+                        show_context(
+                            context,
+                            cidx,
+                            &mut self.capture,
+                            &mut self.modified,
+                            new_action,
+                            ui,
+                        );
+                    });
+
+                    r.header_response.context_menu(|ui| {
+                        if ui.button("Remove context").clicked() {
+                            remove_context = Some(cidx);
+                            ui.close_menu();
+                        }
+                    });
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.new_context)
+                            .hint_text("New context")
+                            .desired_width(150.0),
+                    );
+
+                    let name = self.new_context.trim();
+                    let valid =
+                        !name.is_empty() && self.map.contexts.iter().all(|c| c.name != name);
+
+                    let r = ui.add_enabled(valid, egui::Button::new(egui_phosphor::regular::PLUS));
+                    if r.on_hover_text("Add context").clicked() {
+                        self.map.contexts.push(ActionContext {
+                            name: name.to_owned(),
+                            actions: Vec::new(),
+                        });
+                        self.new_context.clear();
+                        self.modified = true;
+                    }
+                });
+            });
+
+        if let Some(cidx) = remove_context {
+            self.map.contexts.remove(cidx);
+            self.capture = None;
+            self.new_actions.clear();
+            self.modified = true;
+        }
+    }
+}
+
+fn show_context(
+    context: &mut ActionContext,
+    cidx: usize,
+    capture: &mut Option<Capture>,
+    modified: &mut bool,
+    new_action: &mut String,
+    ui: &mut Ui,
+) {
+    // Bindings used by more than one action are ambiguous.
+    let mut uses = HashMap::<Binding, usize>::new();
+    for action in &context.actions {
+        for &binding in &action.bindings {
+            *uses.entry(binding).or_default() += 1;
+        }
+    }
+
+    let mut remove_action = None;
+
+    egui::Grid::new(("action-grid", cidx))
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for (aidx, action) in context.actions.iter_mut().enumerate() {
+                ui.label(&action.name);
+
+                ui.horizontal_wrapped(|ui| {
+                    let mut remove_binding = None;
+
+                    for (bidx, &binding) in action.bindings.iter().enumerate() {
+                        let mut text = RichText::new(binding.to_string());
+                        let conflict = uses.get(&binding).copied().unwrap_or(0) > 1;
+                        if conflict {
+                            text = text.color(Color32::RED);
+                        }
+
+                        let mut r = ui.button(text);
+                        if conflict {
+                            r = r.on_hover_text("Bound to several actions in this context");
+                        }
+                        if r.on_hover_text("Click to unbind").clicked() {
+                            remove_binding = Some(bidx);
+                        }
+                    }
+
+                    if let Some(bidx) = remove_binding {
+                        action.bindings.remove(bidx);
+                        *modified = true;
+                    }
+
+                    let this = Capture {
+                        context: cidx,
+                        action: aidx,
+                    };
+
+                    if *capture == Some(this) {
+                        // Any click now binds the mouse button, Escape cancels.
+                        ui.colored_label(Color32::YELLOW, "…");
+                    } else if ui
+                        .button(egui_phosphor::regular::PLUS)
+                        .on_hover_text("Bind input")
+                        .clicked()
+                    {
+                        *capture = Some(this);
+                    }
+                });
+
+                if ui
+                    .button(egui_phosphor::regular::TRASH)
+                    .on_hover_text("Remove action")
+                    .clicked()
+                {
+                    remove_action = Some(aidx);
+                }
+
+                ui.end_row();
+            }
+        });
+
+    if let Some(aidx) = remove_action {
+        context.actions.remove(aidx);
+        if capture.map_or(false, |c| c.context == cidx) {
+            *capture = None;
+        }
+        *modified = true;
+    }
+
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(new_action)
+                .hint_text("New action")
+                .desired_width(150.0),
+        );
+
+        let name = new_action.trim();
+        let valid = !name.is_empty() && context.actions.iter().all(|a| a.name != name);
+
+        let r = ui.add_enabled(valid, egui::Button::new(egui_phosphor::regular::PLUS));
+        if r.on_hover_text("Add action").clicked() {
+            context.actions.push(ActionBinding {
+                name: name.to_owned(),
+                bindings: Vec::new(),
+            });
+            new_action.clear();
+            *modified = true;
+        }
+    });
+}
+
+fn scan_dir(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            scan_dir(root, &path, files);
+        } else if path.extension().map_or(false, |e| e == EXTENSION) {
+            if let Ok(file) = path.strip_prefix(root) {
+                files.push(file.to_owned());
+            }
+        }
+    }
+}
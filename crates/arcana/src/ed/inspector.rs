@@ -1,8 +1,169 @@
-use arcana::edict::world::WorldLocal;
+//! Inspector of entities in the running instance.
+//!
+//! Shows reflected components of the selected entity
+//! and writes edited values back into the world.
+//! Changes made to components by last plugins reload are listed on top.
+
+use arcana::{
+    mev,
+    reflect::{ComponentId, ComponentInfo},
+    EntityId,
+};
 use egui::Ui;
+use hashbrown::HashMap;
+
+use super::{
+    container::Container,
+    instance::{Instance, MigrationReport},
+    model::ValueProbe,
+};
 
-pub struct Inspector;
+pub struct Inspector {
+    /// Components registered by plugins.
+    components: HashMap<ComponentId, ComponentInfo>,
+
+    selected: Option<EntityId>,
+}
 
 impl Inspector {
-    pub fn show(world: &WorldLocal, ui: &mut Ui) {}
+    pub fn new() -> Self {
+        Inspector {
+            components: HashMap::new(),
+            selected: None,
+        }
+    }
+
+    pub fn selected(&self) -> Option<EntityId> {
+        self.selected
+    }
+
+    pub fn select(&mut self, entity: Option<EntityId>) {
+        self.selected = entity;
+    }
+
+    pub fn update_plugins(&mut self, container: &Container) {
+        self.components.clear();
+
+        for (_, plugin) in container.plugins() {
+            for info in plugin.components() {
+                self.components.insert(info.id, info);
+            }
+        }
+    }
+
+    pub fn show(&mut self, instance: &mut Instance, ui: &mut Ui) {
+        let entities = instance.entities();
+
+        if let Some(selected) = self.selected {
+            if !entities.contains(&selected) {
+                self.selected = None;
+            }
+        }
+
+        if let Some(report) = instance.migration_report() {
+            migration_report(ui, report);
+        }
+
+        egui::SidePanel::left("inspector-entities")
+            .resizable(true)
+            .show_inside(ui, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for &entity in &entities {
+                        let r =
+                            ui.selectable_label(self.selected == Some(entity), format!("{entity}"));
+                        if r.clicked() {
+                            self.selected = Some(entity);
+                        }
+                    }
+                });
+            });
+
+        let Some(entity) = self.selected else {
+            ui.weak("Select entity to inspect");
+            return;
+        };
+
+        if let Some((texture, extent)) = instance.viewport_texture(entity) {
+            viewport_preview(ui, texture, extent);
+        }
+
+        let mut components = instance.reflect_components(entity);
+        components.sort_by_key(|(id, _)| self.components.get(id).map(|info| info.name));
+
+        if components.is_empty() {
+            ui.weak("Entity has no reflected components");
+            return;
+        }
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (id, mut value) in components {
+                let Some(info) = self.components.get(&id) else {
+                    continue;
+                };
+
+                let original = value.clone();
+
+                let mut probe = ValueProbe::new(Some(&info.model), &mut value, (entity, id));
+                egui_probe::Probe::new(&mut probe)
+                    .with_header(info.name.as_str())
+                    .show(ui);
+
+                if value != original {
+                    if let Err(err) = instance.set_component(entity, id, &value) {
+                        tracing::error!("Failed to update component '{}': {err}", info.name);
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Shows live image of the game viewport fitted to panel width.
+fn viewport_preview(ui: &mut Ui, texture: egui::TextureId, extent: mev::Extent2) {
+    if extent.width() == 0 || extent.height() == 0 {
+        return;
+    }
+
+    egui::CollapsingHeader::new("Viewport")
+        .default_open(true)
+        .show(ui, |ui| {
+            let width = ui.available_width().min(extent.width() as f32);
+            let height = width * extent.height() as f32 / extent.width() as f32;
+            ui.image((texture, egui::vec2(width, height)));
+        });
+}
+
+fn migration_report(ui: &mut Ui, report: &MigrationReport) {
+    if report.migrated.is_empty() && report.missing.is_empty() && report.dropped == 0 {
+        return;
+    }
+
+    egui::TopBottomPanel::top("inspector-migration").show_inside(ui, |ui| {
+        egui::CollapsingHeader::new(format!(
+            "Plugins reload: {} components restored, {} dropped",
+            report.restored, report.dropped
+        ))
+        .id_source("inspector-migration")
+        .show(ui, |ui| {
+            for name in &report.missing {
+                ui.label(format!("{name} is no longer registered"));
+            }
+
+            for (name, migration) in &report.migrated {
+                ui.strong(name.as_str());
+                for (old, new) in &migration.renamed {
+                    ui.label(format!("  {old} renamed to {new}"));
+                }
+                for field in &migration.added {
+                    ui.label(format!("  {field} added"));
+                }
+                for field in &migration.removed {
+                    ui.label(format!("  {field} removed"));
+                }
+                for field in &migration.reset {
+                    ui.label(format!("  {field} reset to default"));
+                }
+            }
+        });
+    });
 }
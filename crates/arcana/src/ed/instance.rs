@@ -1,30 +1,59 @@
 //! Running instance of the project.
 
 use arcana::{
-    code::{builtin::emit_code_start, init_codes},
-    edict::{flow::Flows, query::Cpy},
+    assets::{
+        update_asset_server, update_reloaded_assets, AssetId, AssetWatcher, Assets, ParamSet,
+        ReloadedAssets,
+    },
+    begin_frame,
+    clocks::Clocks,
+    code::init_codes,
+    console::{self, init_commands, CommandError},
+    edict::{epoch::EpochId, flow::Flows, query::Cpy},
     events::init_events,
     flow::{init_flows, wake_flows},
     gametime::{ClockRate, FrequencyNumExt, TimeSpan, TimeStamp},
-    input::{DeviceId, Input, KeyCode, PhysicalKey, ViewInput},
+    gizmo::{self, GizmoCamera, GizmoTransform, Transform2},
+    input::{
+        CursorAppearance, CursorGrab, DeviceId, DeviceInput, Input, KeyCode, PhysicalKey, ViewInput,
+    },
     make_id, mev,
-    plugin::PluginsHub,
-    render::{CurrentRenderer, RenderGraphId, Renderer},
+    model::{Migration, Model, Value, ValueError},
+    na,
+    pacing::{init_pacing, FramePacing, DEFAULT_MAX_FPS},
+    plugin::{PluginInfo, PluginRegistry, PluginsHub, SystemId},
+    profile,
+    random::init_random,
+    reflect::{ComponentId, ComponentInfo},
+    render::{CurrentRenderer, RenderCamera, RenderGraphId, Renderer},
+    stats::{init_stats, FrameStats, RenderStats},
+    texture::{SamplerCache, Texture},
+    vfs::Vfs,
     viewport::{ViewId, Viewport},
-    work::{CommandStream, HookId, Image2D, Image2DInfo, PinId, Target, WorkGraph},
-    Blink, ClockStep, EntityId, FrequencyTicker, IdGen, Name, World,
+    window::{init_window_config, ExitRequest, WindowConfig},
+    work::{
+        CommandStream, GraphPreset, GraphPresets, HookId, Image2D, Image2DInfo, PinId, Target,
+        WorkGraph,
+    },
+    Blink, ClockStep, Entities, EntityId, FrequencyTicker, IdGen, Name, World,
 };
 use egui::Ui;
-use hashbrown::{HashMap, HashSet};
-use winit::{event::WindowEvent, window::WindowId};
+use hashbrown::{hash_map::Entry, HashMap, HashSet};
+use winit::{
+    event::{DeviceEvent, WindowEvent},
+    window::WindowId,
+};
 
 use crate::ed::ui::Sampler;
 
 use super::{
-    code::CodeContext,
+    code::{CodeContext, CodeTrace},
     container::Container,
     data::ProjectData,
-    systems::{self, Schedule, Systems},
+    profiler::Profile,
+    render::RenderGraph,
+    schedule::SystemAccess,
+    systems::{self, Schedule, Systems, DEFAULT_FIX_RATE},
     ui::{Selector, UserTextures},
 };
 
@@ -47,6 +76,13 @@ struct InstanceView {
     /// Modification id of the render graph.
     last_render_modification: u64,
 
+    /// Parameter sets bound to the render graph were not loaded yet
+    /// when work graph was built.
+    param_sets_pending: bool,
+
+    /// World epoch at the moment view was rendered last time.
+    last_render_epoch: Option<EpochId>,
+
     /// View work graph.
     work_graph: WorkGraph,
 
@@ -75,6 +111,30 @@ struct InstanceView {
     contains_cursors: HashSet<DeviceId>,
 }
 
+/// State of an entity with `Viewport` spawned by the game.
+struct WorldViewport {
+    /// Render graph work graph was built from.
+    graph: RenderGraphId,
+
+    /// Modification id of the render graph.
+    modification: u64,
+
+    /// Parameter sets bound to the render graph were not loaded yet
+    /// when work graph was built.
+    param_sets_pending: bool,
+
+    work_graph: WorkGraph,
+
+    /// Pin that presents to the viewport.
+    present: Option<PinId>,
+
+    /// UI texture that shows the viewport image in Ed.
+    texture_id: Option<egui::TextureId>,
+
+    /// Extent of the last rendered image.
+    extent: mev::Extent2,
+}
+
 /// Instance of the project.
 pub struct Instance {
     /// Own ECS world.
@@ -88,9 +148,15 @@ pub struct Instance {
     /// Specifies frequency of fixed updates.
     fix: FrequencyTicker,
 
+    /// Frequency of fixed updates in hertz.
+    fix_rate: u64,
+
     /// Limits variable updates.
     limiter: FrequencyTicker,
 
+    /// Frame rate `limiter` was created for.
+    max_fps: Option<u32>,
+
     /// Instance rate.
     rate: ClockRate,
 
@@ -113,6 +179,34 @@ pub struct Instance {
     views: HashMap<ViewId, InstanceView>,
 
     view_id_gen: IdGen,
+
+    /// World epoch at the moment instance was rendered last time.
+    last_render_epoch: EpochId,
+
+    /// Checks loaded assets for changes in sources.
+    asset_watcher: AssetWatcher,
+
+    /// Simulation does not advance unless stepped.
+    paused: bool,
+
+    /// Number of single steps requested while paused.
+    pending_steps: u32,
+
+    /// Timings of recent frames.
+    profile: Profile,
+
+    /// Renderer entity spawned for the main render graph.
+    main_renderer: Option<EntityId>,
+
+    /// Viewports spawned by the game.
+    world_viewports: HashMap<EntityId, WorldViewport>,
+
+    /// Report of components restored after last plugins reload.
+    migration_report: Option<MigrationReport>,
+
+    /// Filesystem inserted into the world.
+    /// Kept to re-insert when world is recreated.
+    vfs: Option<Vfs>,
 }
 
 impl Instance {
@@ -122,8 +216,8 @@ impl Instance {
         let blink = Blink::new();
 
         let rate = ClockRate::new();
-        let fix = FrequencyTicker::new(20.hz(), rate.now());
-        let limiter = FrequencyTicker::new(120.hz(), TimeStamp::start());
+        let fix = FrequencyTicker::new(DEFAULT_FIX_RATE.hz(), rate.now());
+        let limiter = FrequencyTicker::new(u64::from(DEFAULT_MAX_FPS).hz(), TimeStamp::start());
 
         let flows = Flows::new();
         let code: CodeContext = CodeContext::new();
@@ -131,13 +225,16 @@ impl Instance {
         let schedule = Schedule::new();
 
         init_world(&mut world);
+        let last_render_epoch = world.epoch();
 
         Instance {
             world,
             blink,
             hub,
             fix,
+            fix_rate: DEFAULT_FIX_RATE,
             limiter,
+            max_fps: Some(DEFAULT_MAX_FPS),
             rate,
             flows,
             code,
@@ -146,6 +243,15 @@ impl Instance {
             container: None,
             views: HashMap::new(),
             view_id_gen: IdGen::new(),
+            last_render_epoch,
+            asset_watcher: AssetWatcher::new(TimeSpan::SECOND),
+            paused: false,
+            pending_steps: 0,
+            profile: Profile::new(),
+            main_renderer: None,
+            world_viewports: HashMap::new(),
+            migration_report: None,
+            vfs: None,
         }
     }
 
@@ -155,39 +261,65 @@ impl Instance {
         match self.container.take() {
             None => {
                 self.container = Some(new.clone());
+                self.world
+                    .insert_resource(PluginRegistry::from_plugins(new.plugins()));
 
                 for (_, p) in new.plugins() {
                     p.init(&mut self.world, &mut self.hub);
                 }
             }
             Some(old) => {
+                // Components must be read with old plugins before they are unloaded.
+                let snapshot = WorldSnapshot::take(self, &old);
+
                 self.world = World::new();
                 init_world(&mut self.world);
 
+                if let Some(vfs) = &self.vfs {
+                    self.world.insert_resource(vfs.clone());
+                }
+
                 self.rate.reset();
                 self.code.reset();
+                self.main_renderer = None;
+                self.world_viewports.clear();
 
                 for view in self.views.values_mut() {
                     view.work_graph = WorkGraph::new(HashMap::new(), HashSet::new()).unwrap();
                     view.present = None;
                     view.last_render_modification = 0;
+                    view.last_render_epoch = None;
                 }
 
+                self.last_render_epoch = self.world.epoch();
+
                 self.hub = PluginsHub::new();
                 self.container = Some(new.clone());
                 self.blink.reset();
-                self.fix = FrequencyTicker::new(20.hz(), self.rate.now());
-                self.limiter = FrequencyTicker::new(120.hz(), TimeStamp::start());
+                self.fix = FrequencyTicker::new(self.fix_rate.hz(), self.rate.now());
+                self.limiter =
+                    FrequencyTicker::new(u64::from(DEFAULT_MAX_FPS).hz(), TimeStamp::start());
+                self.max_fps = Some(DEFAULT_MAX_FPS);
+                self.world
+                    .insert_resource(PluginRegistry::from_plugins(new.plugins()));
 
                 for (_, p) in new.plugins() {
                     p.init(&mut self.world, &mut self.hub);
                 }
 
+                self.migration_report = Some(snapshot.restore(self, new));
+
                 drop(old);
             }
         }
     }
 
+    /// Sets filesystem used by the game.
+    pub fn set_vfs(&mut self, vfs: Vfs) {
+        self.world.insert_resource(vfs.clone());
+        self.vfs = Some(vfs);
+    }
+
     pub fn new_view(&mut self) -> ViewId {
         let id = self.view_id_gen.next();
 
@@ -199,6 +331,8 @@ impl Instance {
                 renderer: None,
                 last_render_graph: None,
                 last_render_modification: 0,
+                param_sets_pending: false,
+                last_render_epoch: None,
                 work_graph: WorkGraph::new(HashMap::new(), HashSet::new()).unwrap(),
                 present: None,
                 window: None,
@@ -214,6 +348,243 @@ impl Instance {
         id
     }
 
+    /// Sets extent of the view that is not shown in UI.
+    pub fn set_view_extent(&mut self, view: ViewId, extent: mev::Extent2) {
+        if let Some(view) = self.views.get_mut(&view) {
+            view.extent = extent;
+        }
+    }
+
+    pub fn set_view_renderer(&mut self, view: ViewId, renderer: Option<EntityId>) {
+        if let Some(view) = self.views.get_mut(&view) {
+            view.renderer = renderer;
+        }
+    }
+
+    /// Returns entity at the world space point.
+    ///
+    /// Uses picker provided by plugins if any,
+    /// otherwise picks entity with origin closest to the point within `tolerance`.
+    pub fn pick(&self, point: na::Point2<f32>, tolerance: f32) -> Option<EntityId> {
+        gizmo::pick(&self.world, point, tolerance)
+    }
+
+    /// Returns true if view is focused and receives input.
+    pub fn is_view_focused(&self, view: ViewId) -> bool {
+        self.views.get(&view).map_or(false, |view| view.focused)
+    }
+
+    /// Returns image view was rendered to last time.
+    pub fn view_image(&self, view: ViewId) -> Option<mev::Image> {
+        self.views.get(&view)?.viewport.get_image().cloned()
+    }
+
+    /// Returns first entity with renderer component.
+    pub fn find_renderer(&self) -> Option<EntityId> {
+        self.world
+            .view::<Entities>()
+            .with::<Renderer>()
+            .into_iter()
+            .next()
+            .map(|e| e.id())
+    }
+
+    /// Spawns renderer with main render graph from project data
+    /// unless plugins spawned their own renderer.
+    fn update_main_renderer(&mut self, data: &ProjectData) {
+        let Some(graph) = data.main_render_graph else {
+            if let Some(entity) = self.main_renderer.take() {
+                let _ = self.world.despawn(entity);
+            }
+            return;
+        };
+
+        if let Some(entity) = self.main_renderer {
+            match self.world.get::<Cpy<Renderer>>(entity) {
+                Ok(renderer) if renderer.graph == graph => return,
+                Ok(_) => {
+                    let _ = self.world.insert(entity, Renderer { graph });
+                    return;
+                }
+                Err(_) => self.main_renderer = None,
+            }
+        }
+
+        if self.find_renderer().is_some() {
+            return;
+        }
+
+        let entity = self.world.spawn((Renderer { graph },)).id();
+        self.main_renderer = Some(entity);
+    }
+
+    /// Returns access of the system to reflected components and the world.
+    pub fn system_access(&self, id: SystemId) -> Option<SystemAccess> {
+        let system = self.hub.systems.get(&id)?;
+
+        let mut components = self
+            .hub
+            .components
+            .iter()
+            .filter_map(|(&id, reflect)| Some((id, system.access_component(reflect.type_id())?)))
+            .collect::<Vec<_>>();
+        components.sort_by_key(|&(id, _)| id);
+
+        Some(SystemAccess {
+            world: system.world_access(),
+            components,
+        })
+    }
+
+    /// Returns all entities in the world.
+    pub fn entities(&self) -> Vec<EntityId> {
+        self.world
+            .view::<Entities>()
+            .into_iter()
+            .map(|e| e.id())
+            .collect()
+    }
+
+    /// Returns values of reflected components the entity has.
+    pub fn reflect_components(&self, entity: EntityId) -> Vec<(ComponentId, Value)> {
+        self.hub
+            .components
+            .iter()
+            .filter_map(|(&id, reflect)| Some((id, reflect.get(&self.world, entity)?)))
+            .collect()
+    }
+
+    /// Returns sorted set of reflected components the entity has.
+    pub fn component_set(&self, entity: EntityId) -> Vec<ComponentId> {
+        let mut set = self
+            .hub
+            .components
+            .iter()
+            .filter(|(_, reflect)| reflect.has(&self.world, entity))
+            .map(|(&id, _)| id)
+            .collect::<Vec<_>>();
+        set.sort();
+        set
+    }
+
+    /// Returns report of components restored after last plugins reload.
+    pub fn migration_report(&self) -> Option<&MigrationReport> {
+        self.migration_report.as_ref()
+    }
+
+    /// Returns reflected components available in the instance.
+    pub fn component_infos(&self) -> Vec<ComponentInfo> {
+        let Some(container) = &self.container else {
+            return Vec::new();
+        };
+
+        container
+            .plugins()
+            .flat_map(|(_, plugin)| plugin.components())
+            .filter(|info| self.hub.components.contains_key(&info.id))
+            .collect()
+    }
+
+    /// Returns plugins registered in the world.
+    pub fn plugin_infos(&self) -> Vec<PluginInfo> {
+        self.world
+            .expect_resource::<PluginRegistry>()
+            .plugins()
+            .to_vec()
+    }
+
+    /// Spawns entity with reflected components set to default values.
+    pub fn spawn_entity(&mut self, components: &[ComponentInfo]) -> Result<EntityId, ValueError> {
+        let entity = self.world.spawn(()).id();
+
+        for info in components {
+            let result = match self.hub.components.get(&info.id) {
+                None => Err(ValueError::Custom(format!(
+                    "Component {} is not registered",
+                    info.name
+                ))),
+                Some(reflect) => {
+                    reflect.insert(&mut self.world, entity, &info.model.default_value())
+                }
+            };
+
+            if let Err(err) = result {
+                let _ = self.world.despawn(entity);
+                return Err(err);
+            }
+        }
+
+        Ok(entity)
+    }
+
+    /// Despawns entity. Returns false if entity does not exist.
+    pub fn despawn_entity(&mut self, entity: EntityId) -> bool {
+        self.world.despawn(entity).is_ok()
+    }
+
+    /// Executes console command registered in the world.
+    pub fn execute_command(&mut self, line: &str) -> Result<String, CommandError> {
+        console::execute(&mut self.world, line)
+    }
+
+    /// Completes console command registered in the world.
+    pub fn complete_command(&self, line: &str) -> Vec<String> {
+        console::complete(&self.world, line)
+    }
+
+    /// Writes value into reflected component of the entity.
+    pub fn set_component(
+        &mut self,
+        entity: EntityId,
+        id: ComponentId,
+        value: &Value,
+    ) -> Result<(), ValueError> {
+        match self.hub.components.get(&id) {
+            None => Err(ValueError::Custom(format!(
+                "Component {id} is not registered"
+            ))),
+            Some(reflect) => reflect.set(&mut self.world, entity, value),
+        }
+    }
+
+    /// Returns transform of the entity if plugins provide access to it.
+    pub fn entity_transform(&self, entity: EntityId) -> Option<Transform2> {
+        let gizmo = *self.world.get_resource::<GizmoTransform>()?;
+        (gizmo.get)(&self.world, entity)
+    }
+
+    pub fn set_entity_transform(&mut self, entity: EntityId, transform: Transform2) {
+        let Some(gizmo) = self.world.get_resource::<GizmoTransform>().map(|g| *g) else {
+            return;
+        };
+        (gizmo.set)(&mut self.world, entity, transform);
+    }
+
+    /// Returns transform from normalized view coordinates into world space
+    /// if plugins provide camera for gizmos.
+    ///
+    /// Uses camera selected by view's renderer and current extent of the view.
+    pub fn view_to_world(&self, view: ViewId) -> Option<na::Affine2<f32>> {
+        let camera = *self.world.get_resource::<GizmoCamera>()?;
+        let view = self.views.get(&view)?;
+
+        if view.extent.width() == 0 || view.extent.height() == 0 {
+            return None;
+        }
+
+        let entity = view.renderer.and_then(|renderer| {
+            let camera = self.world.get::<Cpy<RenderCamera>>(renderer).ok()?;
+            Some(camera.entity)
+        });
+
+        (camera.view_to_world)(
+            &self.world,
+            entity,
+            view.extent.width(),
+            view.extent.height(),
+        )
+    }
+
     pub fn rate(&self) -> &ClockRate {
         &self.rate
     }
@@ -222,26 +593,136 @@ impl Instance {
         &mut self.rate
     }
 
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pauses or resumes simulation.
+    /// Paused instance keeps its world intact and does not run systems.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.pending_steps = 0;
+    }
+
+    /// Requests paused instance to advance by exactly one fixed tick.
+    pub fn step_once(&mut self) {
+        if self.paused {
+            self.pending_steps += 1;
+        }
+    }
+
+    pub fn code_trace_mut(&mut self) -> &mut CodeTrace {
+        self.code.trace_mut()
+    }
+
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
     pub fn tick(&mut self, data: &ProjectData, systems: &Systems, step: ClockStep) {
+        let _span = profile::frame();
+        self.profile.begin_frame();
+
         if self.systems_modification < systems.modification() {
             self.schedule = data.systems.make_schedule();
             self.systems_modification = systems.modification();
         }
 
-        emit_code_start(&mut self.world);
+        if self.fix_rate != data.fix_rate() {
+            // Accumulated time of the old rate is dropped.
+            self.fix_rate = data.fix_rate();
+            self.fix = FrequencyTicker::new(self.fix_rate.hz(), self.rate.now());
+        }
+
+        let span = if self.paused {
+            if self.pending_steps == 0 {
+                return;
+            }
+            self.pending_steps -= 1;
+
+            // Single step is one fixed tick regardless of time scale.
+            TimeSpan::SECOND / self.fix_rate
+        } else {
+            step.step
+        };
+
+        // Paused instance returns above without touching the world,
+        // so that it does not count as a change for idle redraw skipping.
+        let frame_time = self
+            .profile
+            .frames()
+            .back()
+            .map_or(std::time::Duration::ZERO, |frame| frame.total);
+        self.world
+            .expect_resource_mut::<FrameStats>()
+            .begin_frame(frame_time);
+
+        begin_frame(&mut self.world);
+
+        let step = if self.paused {
+            let rate = self.rate.rate();
+            self.rate.set_rate(1.0);
+            let step = self.rate.step(span);
+            self.rate.set_rate(rate as f32);
+            step
+        } else {
+            self.rate.step(span)
+        };
+
+        if let Some(assets) = self.world.get_resource::<Assets>().map(|a| a.clone()) {
+            self.asset_watcher.tick(step.now, &assets);
+        }
+        update_reloaded_assets(&mut self.world);
+        update_asset_server(&mut self.world);
+
+        let pacing = *self.world.expect_resource::<FramePacing>();
+        if pacing.max_fps != self.max_fps {
+            self.max_fps = pacing.max_fps;
+            if let Some(fps) = pacing.max_fps.filter(|&fps| fps > 0) {
+                self.limiter = FrequencyTicker::new(u64::from(fps).hz(), self.rate.now());
+            }
+        }
+
+        // Variable updates run at real rate, so that UI keeps working while game is paused.
+        // Without frame rate cap they run on each tick of the host.
+        let run_var = match self.max_fps {
+            Some(fps) if fps > 0 => self.limiter.tick_count(step.step) > 0,
+            _ => true,
+        };
 
-        let step = self.rate.step(step.step);
+        let step = self
+            .world
+            .expect_resource_mut::<Clocks>()
+            .advance(step.step);
 
         self.fix.with_ticks(step.step, |fix| {
             self.world.insert_resource(fix);
-            self.schedule
-                .run(systems::Category::Fix, &mut self.world, &mut self.hub);
+
+            let start = std::time::Instant::now();
+            self.schedule.run(
+                systems::Category::Fix,
+                data.sequential_systems,
+                &mut self.world,
+                &mut self.hub,
+                &mut self.profile,
+            );
+
+            let mut stats = self.world.expect_resource_mut::<FrameStats>();
+            stats.fix_systems += start.elapsed();
+            stats.fix_ticks += 1;
         });
 
         self.world.insert_resource(step);
-        if self.limiter.tick_count(step.step) > 0 {
-            self.schedule
-                .run(systems::Category::Var, &mut self.world, &mut self.hub);
+        if run_var {
+            let start = std::time::Instant::now();
+            self.schedule.run(
+                systems::Category::Var,
+                data.sequential_systems,
+                &mut self.world,
+                &mut self.hub,
+                &mut self.profile,
+            );
+            self.world.expect_resource_mut::<FrameStats>().var_systems = start.elapsed();
         }
 
         self.code.execute(&self.hub, data, &mut self.world);
@@ -251,14 +732,60 @@ impl Instance {
 
         self.world.run_deferred();
         self.world.execute_received_actions();
+
+        // Ed keeps running when the game asks to exit.
+        if self.world.expect_resource_mut::<ExitRequest>().take() {
+            tracing::info!("Game requested exit. Instance is paused");
+            self.set_paused(true);
+        }
+    }
+
+    /// Returns frame pacing requested by the game.
+    pub fn pacing(&self) -> FramePacing {
+        self.world
+            .get_resource::<FramePacing>()
+            .map_or_else(FramePacing::new, |pacing| *pacing)
+    }
+
+    /// Returns true if world was modified since last render.
+    ///
+    /// Any mutable access to the world counts as a change.
+    /// While game runs every tick modifies the world.
+    /// Paused instance is changed only by input events delivered to it,
+    /// single steps and edits made from the editor.
+    /// Writes made by rendering itself, like render stats and job caches, are not counted.
+    pub fn has_changes(&self) -> bool {
+        self.world.epoch() != self.last_render_epoch
     }
 
     /// Render instance view to a texture.
+    ///
+    /// When `idle` is true views that have nothing new to show are skipped.
     pub fn render(
         &mut self,
         queue: &mut mev::Queue,
         data: &ProjectData,
         textures: &mut UserTextures,
+        idle: bool,
+    ) -> Result<(), mev::SurfaceError> {
+        self.render_views(queue, data, Some(textures), idle)
+    }
+
+    /// Render all views without registering their images as UI textures.
+    pub fn render_headless(
+        &mut self,
+        queue: &mut mev::Queue,
+        data: &ProjectData,
+    ) -> Result<(), mev::SurfaceError> {
+        self.render_views(queue, data, None, false)
+    }
+
+    fn render_views(
+        &mut self,
+        queue: &mut mev::Queue,
+        data: &ProjectData,
+        mut textures: Option<&mut UserTextures>,
+        idle: bool,
     ) -> Result<(), mev::SurfaceError> {
         #[cold]
         fn new_image(
@@ -270,7 +797,8 @@ impl Instance {
                 format: mev::PixelFormat::Rgba8Srgb,
                 usage: mev::ImageUsage::TARGET
                     | mev::ImageUsage::SAMPLED
-                    | mev::ImageUsage::STORAGE,
+                    | mev::ImageUsage::STORAGE
+                    | mev::ImageUsage::TRANSFER_SRC,
                 layers: 1,
                 levels: 1,
                 name: "Game Viewport",
@@ -278,46 +806,55 @@ impl Instance {
             Ok(image)
         }
 
+        self.update_main_renderer(data);
+
+        let epoch = self.world.epoch();
+
+        // Jobs add draw calls while running.
+        self.world.insert_resource(RenderStats::new());
+
+        let assets = self.world.get_resource::<Assets>().map(|a| a.clone());
+
+        // Views may show images of game viewports, so those are rendered first.
+        self.render_world_viewports(queue, data, assets.as_ref(), textures.as_deref_mut())?;
+
         for view in self.views.values_mut() {
             if view.extent.width() == 0 || view.extent.height() == 0 {
                 // View has ZERO extent.
-                return Ok(());
+                continue;
             }
 
             let Some(renderer_id) = view.renderer else {
                 // View does not have a renderer
-                return Ok(());
+                continue;
             };
 
             let Ok(renderer) = self.world.get::<Cpy<Renderer>>(renderer_id) else {
                 // View renderer is not found
-                return Ok(());
+                continue;
             };
 
-            let Some(render_graph) = data.render_graphs.get(&renderer.graph) else {
+            let Some(source) = GraphSource::find(data, &self.world, renderer.graph) else {
                 // View render graph is not found
-                return Ok(());
+                continue;
             };
 
             if view.last_render_graph != Some(renderer.graph)
-                || view.last_render_modification < render_graph.modification
+                || view.last_render_modification < source.modification()
+                || view.param_sets_pending
+                || source.reloaded(&self.world)
             {
-                let work_graph = match render_graph.make_work_graph() {
-                    Ok(work_graph) => work_graph,
-                    Err(err) => {
-                        tracing::error!("Failed to make work graph: {err:?}");
-                        return Ok(());
-                    }
-                };
+                (view.work_graph, view.present, view.param_sets_pending) =
+                    source.build(assets.as_ref());
 
-                view.work_graph = work_graph;
-                view.present = render_graph.get_present();
                 view.last_render_graph = Some(renderer.graph);
+                view.last_render_modification = source.modification();
+                view.last_render_epoch = None;
             }
 
             let Some(pin) = view.present else {
                 // View does not have a present pin
-                return Ok(());
+                continue;
             };
 
             if view
@@ -329,6 +866,10 @@ impl Instance {
 
                 tracing::debug!("Creating new image for viewport");
                 view.viewport.set_image(new_image);
+                view.last_render_epoch = None;
+            } else if idle && view.last_render_epoch == Some(epoch) {
+                // Nothing changed since last frame.
+                continue;
             }
 
             let image = match view
@@ -348,18 +889,173 @@ impl Instance {
                 entity: renderer_id,
             });
 
+            let _span = profile::render("view");
+            let start = std::time::Instant::now();
             view.work_graph
                 .run(queue, &mut self.world, &mut self.hub)
                 .unwrap();
+            self.profile.record_jobs(start, view.work_graph.timings());
+
+            let mut stats = self.world.expect_resource_mut::<RenderStats>();
+            for timing in view.work_graph.timings() {
+                stats.jobs += 1;
+                stats.plan += timing.plan;
+                stats.exec += timing.exec;
+            }
+            stats.target_memory += view.work_graph.target_memory();
 
-            if let Some(texture_id) = view.texture_id {
+            view.last_render_epoch = Some(epoch);
+
+            if let (Some(texture_id), Some(textures)) = (view.texture_id, textures.as_deref_mut()) {
                 textures.set(texture_id, image, Sampler::NearestNearest);
             }
         }
 
+        // Rendering writes stats and job state into the world.
+        // Those writes are not changes to show, so epoch is recorded after them.
+        let rendered = self.world.epoch();
+        self.last_render_epoch = rendered;
+        for view in self.views.values_mut() {
+            if view.last_render_epoch == Some(epoch) {
+                view.last_render_epoch = Some(rendered);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Renders viewports spawned by the game.
+    ///
+    /// Each entity with `Viewport` and `Renderer` components
+    /// gets own work graph built from renderer's render graph.
+    /// Images of texture viewports are set as `Texture` component of the entity
+    /// and registered as UI textures if `textures` are provided.
+    fn render_world_viewports(
+        &mut self,
+        queue: &mut mev::Queue,
+        data: &ProjectData,
+        assets: Option<&Assets>,
+        mut textures: Option<&mut UserTextures>,
+    ) -> Result<(), mev::SurfaceError> {
+        let viewports = self
+            .world
+            .view::<(Entities, Cpy<Renderer>)>()
+            .with::<Viewport>()
+            .into_iter()
+            .map(|(e, renderer)| (e.id(), renderer.graph))
+            .collect::<Vec<_>>();
+
+        self.world_viewports
+            .retain(|e, _| viewports.iter().any(|(v, _)| v == e));
+
+        for (entity, graph) in viewports {
+            let Some(source) = GraphSource::find(data, &self.world, graph) else {
+                continue;
+            };
+
+            let state = match self.world_viewports.entry(entity) {
+                Entry::Occupied(entry) => {
+                    let state = entry.into_mut();
+                    if state.graph != graph
+                        || state.modification < source.modification()
+                        || state.param_sets_pending
+                        || source.reloaded(&self.world)
+                    {
+                        (state.work_graph, state.present, state.param_sets_pending) =
+                            source.build(assets);
+                        state.graph = graph;
+                        state.modification = source.modification();
+                    }
+                    state
+                }
+                Entry::Vacant(entry) => {
+                    let (work_graph, present, param_sets_pending) = source.build(assets);
+                    entry.insert(WorldViewport {
+                        graph,
+                        modification: source.modification(),
+                        param_sets_pending,
+                        work_graph,
+                        present,
+                        texture_id: None,
+                        extent: mev::Extent2::ZERO,
+                    })
+                }
+            };
+
+            let Some(pin) = state.present else {
+                continue;
+            };
+
+            let next = match self.world.get::<&mut Viewport>(entity) {
+                Ok(mut viewport) => viewport.next_frame(queue, mev::PipelineStages::all())?,
+                Err(_) => continue,
+            };
+
+            let Some((image, frame)) = next else {
+                continue;
+            };
+
+            let info = Image2DInfo::from_image(&image);
+            state.work_graph.set_sink(pin, Image2D(image.clone()), info);
+
+            self.world.insert_resource(CurrentRenderer { entity });
+
+            let _span = profile::render("viewport");
+            let start = std::time::Instant::now();
+            state
+                .work_graph
+                .run(queue, &mut self.world, &mut self.hub)
+                .unwrap();
+            self.profile.record_jobs(start, state.work_graph.timings());
+
+            let mut stats = self.world.expect_resource_mut::<RenderStats>();
+            for timing in state.work_graph.timings() {
+                stats.jobs += 1;
+                stats.plan += timing.plan;
+                stats.exec += timing.exec;
+            }
+            stats.target_memory += state.work_graph.target_memory();
+            drop(stats);
+
+            match frame {
+                Some(frame) => {
+                    if let Err(err) = present_frame(queue, frame) {
+                        tracing::error!("Failed to present viewport {entity}: {err}");
+                    }
+                }
+                None => {
+                    state.extent = image.extent().expect_2d();
+
+                    if let Some(textures) = textures.as_deref_mut() {
+                        let id = *state.texture_id.get_or_insert_with(|| textures.new_id());
+                        textures.set(id, image.clone(), Sampler::LinearLinear);
+                    }
+
+                    // Image is replaced only when viewport is resized.
+                    let stale = self
+                        .world
+                        .get::<&Texture>(entity)
+                        .map_or(true, |texture| texture.image.extent() != image.extent());
+
+                    if stale {
+                        let _ = self.world.insert(entity, Texture { image });
+                    }
+                }
+            }
+        }
+
         Ok(())
     }
 
+    /// Returns UI texture that shows image of the game viewport
+    /// and extent of the image.
+    ///
+    /// Texture is updated each time instance is rendered.
+    pub fn viewport_texture(&self, entity: EntityId) -> Option<(egui::TextureId, mev::Extent2)> {
+        let state = self.world_viewports.get(&entity)?;
+        Some((state.texture_id?, state.extent))
+    }
+
     pub fn handle_event(
         &mut self,
         data: &ProjectData,
@@ -471,6 +1167,68 @@ impl Instance {
         false
     }
 
+    /// Feeds device events to the focused view.
+    pub fn handle_device_event(
+        &mut self,
+        data: &ProjectData,
+        device_id: winit::event::DeviceId,
+        event: &DeviceEvent,
+    ) -> bool {
+        if !self.views.values().any(|view| view.focused) {
+            return false;
+        }
+
+        let Ok(event) = DeviceInput::try_from(event) else {
+            return false;
+        };
+
+        data.funnel.filter(
+            &mut self.hub,
+            &self.blink,
+            &mut self.world,
+            &Input::DeviceInput {
+                device: DeviceId::from(device_id),
+                event,
+            },
+        )
+    }
+
+    /// Returns window that should have cursor grabbed.
+    ///
+    /// Cursor is grabbed when game requests it via `CursorGrab` resource
+    /// and one of the views is focused.
+    pub fn cursor_grab(&self) -> Option<WindowId> {
+        let grab = self
+            .world
+            .get_resource::<CursorGrab>()
+            .map_or(false, |grab| grab.is_grabbed());
+
+        if !grab {
+            return None;
+        }
+
+        self.views
+            .values()
+            .find(|view| view.focused)
+            .and_then(|view| view.window)
+    }
+
+    /// Returns cursor appearance requested by the game
+    /// if cursor is over one of the views shown in the window.
+    pub fn hovered_cursor_appearance(&self, window: WindowId) -> Option<CursorAppearance> {
+        let hovered = self
+            .views
+            .values()
+            .any(|view| view.window == Some(window) && !view.contains_cursors.is_empty());
+
+        if !hovered {
+            return None;
+        }
+
+        let appearance = self.world.get_resource::<CursorAppearance>()?;
+        Some(appearance.clone())
+    }
+
     pub fn add_work_graph_hook<T>(
         &mut self,
         view: ViewId,
@@ -500,22 +1258,154 @@ impl Instance {
     }
 }
 
+/// Outcome of restoring components after plugins reload.
+#[derive(Clone, Debug, Default)]
+pub struct MigrationReport {
+    pub restored: usize,
+    pub dropped: usize,
+
+    /// Components whose model changed, with changes made to their values.
+    pub migrated: Vec<(Name, Migration)>,
+
+    /// Components that are no longer registered.
+    pub missing: Vec<Name>,
+}
+
+/// Reflected components of the world kept across plugins reload.
+///
+/// Components are restored into fresh world after new plugins are initialized.
+/// Entities spawned by plugins initialization that carry reflected components
+/// are replaced by restored ones, so scene is not duplicated.
+/// Values of components with changed models are migrated to new models.
+/// Components not reflected or without `Default` are lost.
+struct WorldSnapshot {
+    entities: Vec<Vec<(ComponentId, Value)>>,
+
+    /// Names and models of the components at the moment snapshot was taken.
+    models: HashMap<ComponentId, (Name, Model)>,
+}
+
+impl WorldSnapshot {
+    fn take(instance: &Instance, container: &Container) -> Self {
+        let models = container
+            .plugins()
+            .flat_map(|(_, plugin)| plugin.components())
+            .map(|info| (info.id, (info.name, info.model)))
+            .collect();
+
+        let entities = instance
+            .entities()
+            .into_iter()
+            .map(|entity| instance.reflect_components(entity))
+            .filter(|components| !components.is_empty())
+            .collect();
+
+        WorldSnapshot { entities, models }
+    }
+
+    fn restore(self, instance: &mut Instance, container: &Container) -> MigrationReport {
+        let mut report = MigrationReport::default();
+
+        if self.entities.is_empty() {
+            return report;
+        }
+
+        let models: HashMap<ComponentId, Model> = container
+            .plugins()
+            .flat_map(|(_, plugin)| plugin.components())
+            .map(|info| (info.id, info.model))
+            .collect();
+
+        for entity in instance.entities() {
+            if !instance.reflect_components(entity).is_empty() {
+                let _ = instance.world.despawn(entity);
+            }
+        }
+
+        let mut migrated = HashMap::<ComponentId, Migration>::new();
+
+        for components in self.entities {
+            let entity = instance.world.spawn(()).id();
+
+            for (id, mut value) in components {
+                let old = self.models.get(&id);
+
+                let Some(reflect) = instance.hub.components.get(&id) else {
+                    if let Some(&(name, _)) = old {
+                        if !report.missing.contains(&name) {
+                            report.missing.push(name);
+                        }
+                    }
+                    report.dropped += 1;
+                    continue;
+                };
+
+                // Values of changed components are migrated field by field,
+                // failing that they are dropped.
+                if let (Some((_, old)), Some(new)) = (old, models.get(&id)) {
+                    if old != new {
+                        let mut migration = Migration::new();
+                        value = new.migrate(old, value, &mut migration);
+                        migrated.entry(id).or_insert(migration);
+                    }
+                }
+
+                match reflect.insert(&mut instance.world, entity, &value) {
+                    Ok(()) => report.restored += 1,
+                    Err(err) => {
+                        tracing::debug!("Component {id} is dropped: {err}");
+                        report.dropped += 1;
+                    }
+                }
+            }
+        }
+
+        report.migrated = migrated
+            .into_iter()
+            .filter_map(|(id, migration)| Some((self.models.get(&id)?.0, migration)))
+            .collect();
+        report.migrated.sort_by_key(|(name, _)| *name);
+
+        tracing::info!(
+            "Restored {} components after plugins reload, dropped {}",
+            report.restored,
+            report.dropped
+        );
+
+        report
+    }
+}
+
 pub struct Simulation {
     view: Option<ViewId>,
+
+    /// Require double click to focus the view,
+    /// leaving single clicks to the caller.
+    focus_on_double_click: bool,
 }
 
 impl Simulation {
     pub fn new() -> Self {
-        Simulation { view: None }
+        Simulation {
+            view: None,
+            focus_on_double_click: false,
+        }
+    }
+
+    pub fn with_focus_on_double_click(mut self) -> Self {
+        self.focus_on_double_click = true;
+        self
     }
 
+    /// Shows simulation view.
+    /// Returns id of the shown view and response of its image.
     pub fn show(
         &mut self,
         instance: &mut Instance,
         window: WindowId,
         textures: &mut UserTextures,
         ui: &mut Ui,
-    ) {
+    ) -> Option<(ViewId, egui::Response)> {
         ui.horizontal_top(|ui| {
             let selector =
                 Selector::<_, InstanceView>::new("Simulation view", |_, view| view.name.as_str())
@@ -531,16 +1421,20 @@ impl Simulation {
             }
         });
 
-        let view = match self.view {
-            Some(id) => match instance.views.get_mut(&id) {
-                Some(view) => view,
-                None => unreachable!(),
-            },
-            None => return,
+        let renderer = instance.find_renderer();
+
+        let view_id = self.view?;
+        let view = match instance.views.get_mut(&view_id) {
+            Some(view) => view,
+            None => unreachable!(),
         };
 
         view.window = Some(window);
 
+        if view.renderer.is_none() {
+            view.renderer = renderer;
+        }
+
         let game_frame = egui::Frame::none()
             .rounding(egui::Rounding::same(5.0))
             .stroke(egui::Stroke::new(
@@ -553,7 +1447,7 @@ impl Simulation {
             ))
             .inner_margin(egui::Margin::same(10.0));
 
-        game_frame.show(ui, |ui| {
+        let r = game_frame.show(ui, |ui| {
             let size = ui.available_size();
             view.extent = mev::Extent2::new(size.x as u32, size.y as u32);
 
@@ -578,8 +1472,14 @@ impl Simulation {
                     r.surrender_focus();
                 }
 
+                let focus_click = if self.focus_on_double_click {
+                    r.double_clicked()
+                } else {
+                    r.clicked()
+                };
+
                 let mut make_focused = false;
-                if r.clicked() {
+                if focus_click {
                     r.request_focus();
                     make_focused = !view.focused
                 }
@@ -589,14 +1489,139 @@ impl Simulation {
                     view.pixel_per_point = ui.ctx().pixels_per_point();
                 }
             }
+
+            r
         });
+
+        Some((view_id, r.inner))
+    }
+}
+
+/// Builds work graph for the render graph.
+///
+/// Returns work graph, present pin
+/// and whether bound parameter sets are still loading.
+fn build_work_graph(
+    render_graph: &RenderGraph,
+    assets: Option<&Assets>,
+) -> (WorkGraph, Option<PinId>, bool) {
+    let (work_graph, present) = match render_graph.make_work_graph(assets) {
+        Ok(work_graph) => (work_graph, render_graph.get_present()),
+        Err(errors) => {
+            for err in errors.iter().filter(|err| err.is_fatal()) {
+                tracing::error!("Render graph '{}' is invalid: {err}", render_graph.name);
+            }
+
+            // Nothing is rendered until the graph is fixed.
+            (
+                WorkGraph::new(HashMap::new(), HashSet::new()).unwrap(),
+                None,
+            )
+        }
+    };
+
+    // Rebuild again when pending parameter sets are loaded.
+    let pending = assets.map_or(false, |assets| {
+        render_graph
+            .param_sets()
+            .any(|id| assets.get::<ParamSet>(id).is_pending())
+    });
+
+    (work_graph, present, pending)
+}
+
+/// Render graph of a renderer.
+///
+/// Graphs edited in Ed take precedence
+/// over presets loaded by the game with `load_graph`.
+enum GraphSource<'a> {
+    Edited(&'a RenderGraph),
+    Preset(Option<AssetId>, GraphPreset),
+}
+
+impl<'a> GraphSource<'a> {
+    fn find(data: &'a ProjectData, world: &World, graph: RenderGraphId) -> Option<Self> {
+        if let Some(render_graph) = data.render_graphs.get(&graph) {
+            return Some(GraphSource::Edited(render_graph));
+        }
+
+        let presets = world.get_resource::<GraphPresets>()?;
+        let handle = presets.handle(graph)?;
+        Some(GraphSource::Preset(handle.id(), handle.get()?))
+    }
+
+    fn modification(&self) -> u64 {
+        match self {
+            GraphSource::Edited(render_graph) => render_graph.modification,
+            GraphSource::Preset(..) => 0,
+        }
+    }
+
+    /// Returns true if assets the graph is built from were reloaded.
+    fn reloaded(&self, world: &World) -> bool {
+        match self {
+            GraphSource::Edited(render_graph) => params_reloaded(world, render_graph),
+            GraphSource::Preset(id, _) => id.map_or(false, |id| {
+                world
+                    .get_resource::<ReloadedAssets>()
+                    .map_or(false, |reloaded| reloaded.contains(id))
+            }),
+        }
     }
+
+    fn build(&self, assets: Option<&Assets>) -> (WorkGraph, Option<PinId>, bool) {
+        match self {
+            GraphSource::Edited(render_graph) => build_work_graph(render_graph, assets),
+            GraphSource::Preset(_, preset) => match preset.make_work_graph() {
+                Ok(work_graph) => (work_graph, preset.present, false),
+                Err(errors) => {
+                    for err in errors.iter().filter(|err| err.is_fatal()) {
+                        tracing::error!("Render graph preset '{}' is invalid: {err}", preset.name);
+                    }
+                    (
+                        WorkGraph::new(HashMap::new(), HashSet::new()).unwrap(),
+                        None,
+                        false,
+                    )
+                }
+            },
+        }
+    }
+}
+
+/// Returns true if parameter sets bound to the render graph were reloaded.
+fn params_reloaded(world: &World, render_graph: &RenderGraph) -> bool {
+    world
+        .get_resource::<ReloadedAssets>()
+        .map_or(false, |reloaded| {
+            render_graph.param_sets().any(|id| reloaded.contains(id))
+        })
+}
+
+fn present_frame(queue: &mut mev::Queue, frame: mev::Frame) -> Result<(), mev::DeviceError> {
+    let mut encoder = queue.new_command_encoder()?;
+    encoder.present(frame, mev::PipelineStages::all());
+    let cbuf = encoder.finish()?;
+    queue.submit(std::iter::once(cbuf), true)
 }
 
 fn init_world(world: &mut World) {
     init_flows(world);
     init_events(world);
     init_codes(world);
+    init_commands(world);
+    init_random(world);
+    init_stats(world);
+    init_pacing(world);
+
+    // Ed shows game in viewports, so config is only kept for systems that read it.
+    init_window_config(world, &WindowConfig::default());
+    world.insert_resource(SamplerCache::new());
+    world.insert_resource(CursorGrab::new());
+    world.insert_resource(CursorAppearance::new());
+    world.insert_resource(ExitRequest::new());
+    world.insert_resource(PluginRegistry::new());
+    world.insert_resource(ReloadedAssets::new());
     world.insert_resource(ClockStep {
         now: TimeStamp::start(),
         step: TimeSpan::ZERO,
@@ -0,0 +1,380 @@
+//! Log panel.
+//!
+//! Collects tracing events of the editor and loaded plugins
+//! and shows them with level, target and text filters.
+//! Repeated messages are collapsed into one line with a counter.
+
+use std::{
+    collections::VecDeque,
+    fmt::{self, Write as _},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use egui::{Color32, Ui};
+use parking_lot::Mutex;
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, Layer};
+
+use crate::project::Project;
+
+use super::ide::Ide;
+
+/// Maximum number of log lines kept.
+const MAX_LINES: usize = 4096;
+
+/// Number of recent lines checked for repeated message.
+const REPEAT_LOOKBACK: usize = 16;
+
+/// Repeated message is collapsed if it comes within this time since last one.
+const REPEAT_WINDOW: Duration = Duration::from_secs(1);
+
+pub struct LogLine {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+
+    /// Time since collector was created when message was first logged.
+    pub first: Duration,
+
+    /// Time when message was last logged.
+    pub last: Duration,
+
+    /// Number of times the message was logged.
+    pub count: usize,
+}
+
+impl LogLine {
+    fn is_repeat_of(&self, other: &LogLine) -> bool {
+        self.level == other.level
+            && self.line == other.line
+            && self.file == other.file
+            && self.target == other.target
+            && self.message == other.message
+    }
+}
+
+struct LogBuffer {
+    start: Instant,
+    lines: VecDeque<LogLine>,
+}
+
+impl LogBuffer {
+    fn push(&mut self, mut line: LogLine) {
+        let now = self.start.elapsed();
+        line.first = now;
+        line.last = now;
+
+        let repeated = self
+            .lines
+            .iter_mut()
+            .rev()
+            .take(REPEAT_LOOKBACK)
+            .find(|l| l.is_repeat_of(&line) && now - l.last < REPEAT_WINDOW);
+
+        if let Some(repeated) = repeated {
+            repeated.count += 1;
+            repeated.last = now;
+            return;
+        }
+
+        if self.lines.len() >= MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+}
+
+/// Tracing layer that collects events for the log panel.
+#[derive(Clone)]
+pub struct LogCollector {
+    buffer: Arc<Mutex<LogBuffer>>,
+}
+
+impl LogCollector {
+    pub fn new() -> Self {
+        LogCollector {
+            buffer: Arc::new(Mutex::new(LogBuffer {
+                start: Instant::now(),
+                lines: VecDeque::new(),
+            })),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.buffer.lock().lines.clear();
+    }
+
+    /// Returns last `count` lines formatted as text.
+    ///
+    /// Used from panic hook, so it gives up instead of waiting
+    /// if the panic happened while the buffer was locked.
+    pub fn tail(&self, count: usize) -> Vec<String> {
+        let Some(buffer) = self.buffer.try_lock() else {
+            return Vec::new();
+        };
+
+        let skip = buffer.lines.len().saturating_sub(count);
+        buffer
+            .lines
+            .iter()
+            .skip(skip)
+            .map(|line| {
+                let mut text = format!("{} {}: {}", line.level, line.target, line.message);
+                if line.count > 1 {
+                    let _ = write!(text, " (×{})", line.count);
+                }
+                text
+            })
+            .collect()
+    }
+}
+
+impl<S> Layer<S> for LogCollector
+where
+    S: Subscriber,
+{
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let meta = event.metadata();
+
+        let mut visitor = MessageVisitor {
+            message: String::new(),
+            fields: String::new(),
+        };
+        event.record(&mut visitor);
+
+        let mut message = visitor.message;
+        if !visitor.fields.is_empty() {
+            if !message.is_empty() {
+                message.push(' ');
+            }
+            message.push_str(&visitor.fields);
+        }
+
+        self.buffer.lock().push(LogLine {
+            level: *meta.level(),
+            target: meta.target().to_owned(),
+            message,
+            file: meta.file().map(str::to_owned),
+            line: meta.line(),
+            first: Duration::ZERO,
+            last: Duration::ZERO,
+            count: 1,
+        });
+    }
+}
+
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            if !self.fields.is_empty() {
+                self.fields.push(' ');
+            }
+            let _ = write!(self.fields, "{}={value:?}", field.name());
+        }
+    }
+}
+
+pub struct Logs {
+    collector: LogCollector,
+
+    /// Lowest level shown.
+    level: Level,
+
+    /// Comma separated target prefixes.
+    /// Lines with any of the targets are shown.
+    targets: String,
+
+    /// Case-insensitive text searched in messages.
+    search: String,
+
+    /// Keep view scrolled to the latest line.
+    follow: bool,
+}
+
+impl Logs {
+    pub fn new(collector: LogCollector) -> Self {
+        Logs {
+            collector,
+            level: Level::INFO,
+            targets: String::new(),
+            search: String::new(),
+            follow: true,
+        }
+    }
+
+    fn matches(&self, line: &LogLine, targets: &[&str], search: &str) -> bool {
+        if line.level > self.level {
+            return false;
+        }
+
+        if !targets.is_empty() && !targets.iter().any(|t| line.target.starts_with(t)) {
+            return false;
+        }
+
+        search.is_empty() || line.message.to_lowercase().contains(search)
+    }
+
+    pub fn show(&mut self, project: &Project, ide: Option<&dyn Ide>, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source("log-level")
+                .selected_text(self.level.as_str())
+                .show_ui(ui, |ui| {
+                    for level in [
+                        Level::ERROR,
+                        Level::WARN,
+                        Level::INFO,
+                        Level::DEBUG,
+                        Level::TRACE,
+                    ] {
+                        ui.selectable_value(&mut self.level, level, level.as_str());
+                    }
+                });
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.targets)
+                    .hint_text("Targets")
+                    .desired_width(150.0),
+            )
+            .on_hover_text("Comma separated target prefixes");
+
+            ui.label(egui_phosphor::regular::MAGNIFYING_GLASS);
+            ui.add(egui::TextEdit::singleline(&mut self.search).desired_width(200.0));
+
+            ui.checkbox(&mut self.follow, "Follow");
+
+            if ui
+                .button(egui_phosphor::regular::TRASH)
+                .on_hover_text("Clear logs")
+                .clicked()
+            {
+                self.collector.clear();
+            }
+        });
+
+        ui.separator();
+
+        let targets = self
+            .targets
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>();
+        let search = self.search.to_lowercase();
+
+        let buffer = self.collector.buffer.lock();
+
+        let lines = buffer
+            .lines
+            .iter()
+            .filter(|line| self.matches(line, &targets, &search))
+            .collect::<Vec<_>>();
+
+        let mut open = None;
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .stick_to_bottom(self.follow)
+            .show_rows(
+                ui,
+                ui.spacing().interact_size.y,
+                lines.len(),
+                |ui, range| {
+                    for line in &lines[range] {
+                        ui.horizontal(|ui| {
+                            let secs = line.first.as_secs_f32();
+                            ui.weak(format!("{secs:>9.3}"));
+
+                            ui.colored_label(level_color(line.level), line.level.as_str());
+                            ui.weak(&line.target);
+
+                            if line.count > 1 {
+                                ui.strong(format!("×{}", line.count));
+                            }
+
+                            ui.label(&line.message);
+
+                            if let Some(file) = &line.file {
+                                let location = match line.line {
+                                    Some(n) => format!("{file}:{n}"),
+                                    None => file.clone(),
+                                };
+
+                                let r = ui.add_enabled(
+                                    ide.is_some(),
+                                    egui::Button::new(egui_phosphor::regular::CODE).small(),
+                                );
+                                let r = r
+                                    .on_hover_text(&location)
+                                    .on_disabled_hover_text("No IDE configured");
+
+                                if r.clicked() {
+                                    open = Some((file.clone(), line.line));
+                                }
+                            }
+                        });
+                    }
+                },
+            );
+
+        drop(buffer);
+
+        if let (Some((file, line)), Some(ide)) = (open, ide) {
+            let path = source_path(project, Path::new(&file));
+            if !ide.open(&path, line) {
+                tracing::error!("Failed to open {} in IDE", path.display());
+            }
+        }
+    }
+}
+
+/// Source paths in log metadata are relative to the crate or workspace being built.
+/// Try project root first, as plugins are built there.
+fn source_path(project: &Project, file: &Path) -> PathBuf {
+    if file.is_absolute() {
+        return file.to_owned();
+    }
+
+    let in_project = project.root_path().join(file);
+    if in_project.exists() {
+        return in_project;
+    }
+
+    let in_engine = Path::new(env!("CARGO_MANIFEST_DIR")).join(file);
+    if in_engine.exists() {
+        return in_engine;
+    }
+
+    file.to_owned()
+}
+
+fn level_color(level: Level) -> Color32 {
+    match level {
+        Level::ERROR => Color32::RED,
+        Level::WARN => Color32::YELLOW,
+        Level::INFO => Color32::LIGHT_GREEN,
+        Level::DEBUG => Color32::LIGHT_BLUE,
+        _ => Color32::GRAY,
+    }
+}
@@ -1,4 +1,8 @@
-use std::{hash::Hash, io::ErrorKind, path::Path};
+use std::{
+    hash::Hash,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
 
 use data::ProjectData;
 use winit::event_loop::EventLoop;
@@ -6,7 +10,10 @@ use winit::event_loop::EventLoop;
 #[cfg(windows)]
 use winit::platform::windows::EventLoopBuilderExtWindows;
 
-use crate::project::{Profile, Project};
+use crate::project::{
+    backup_before_migration, migrate_project_data, CrashReport, Profile, Project,
+    PROJECT_DATA_VERSION,
+};
 
 /// Result::ok, but logs Err case.
 macro_rules! ok_log_err {
@@ -37,22 +44,36 @@ macro_rules! try_log_err {
 
 mod app;
 mod assets;
+mod behavior;
 mod code;
+mod console;
 mod container;
+mod cook;
+mod curve;
 mod data;
 mod error;
 mod filters;
+mod gizmo;
+mod headless;
+mod history;
 mod ide;
+mod input_map;
 mod inspector;
 mod instance;
+mod logs;
 mod model;
+mod params;
 mod plugins;
+mod profiler;
 mod render;
 mod sample;
+mod scene;
+mod schedule;
 mod subprocess;
 mod systems;
 mod tool;
 mod ui;
+mod world;
 
 /// Runs the editor application
 pub fn run(project_path: impl AsRef<Path>) {
@@ -61,6 +82,27 @@ pub fn run(project_path: impl AsRef<Path>) {
     }
 }
 
+/// Imports project assets and packs them into archive at `output`.
+///
+/// Used by `arcn cook`.
+pub fn cook(project_path: impl AsRef<Path>, output: impl AsRef<Path>) {
+    if let Err(err) = cook::cook(project_path.as_ref(), output.as_ref()) {
+        eprintln!("Error: {:?}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Runs the editor without windows, executing commands from the script.
+/// Exits with non-zero code if any command fails.
+///
+/// Used by `arcn ed --headless`.
+pub fn headless(project_path: impl AsRef<Path>, script: impl AsRef<Path>) {
+    if let Err(err) = headless::headless(project_path.as_ref(), script.as_ref()) {
+        eprintln!("Error: {:?}", err);
+        std::process::exit(1);
+    }
+}
+
 fn _run(project_path: &Path) -> miette::Result<()> {
     // Marks the running instance of Arcana library.
     // This flag is checked in plugins to ensure they are linked to this arcana.
@@ -68,7 +110,8 @@ fn _run(project_path: &Path) -> miette::Result<()> {
 
     let (project, data) = load_project(project_path)?;
 
-    let event_collector = egui_tracing::EventCollector::default();
+    let log_collector = logs::LogCollector::new();
+    install_crash_hook(project.root_path().to_owned(), log_collector.clone());
 
     use tracing_subscriber::layer::SubscriberExt as _;
 
@@ -77,7 +120,8 @@ fn _run(project_path: &Path) -> miette::Result<()> {
             // .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
             .finish()
             .with(tracing_error::ErrorLayer::default())
-            .with(event_collector.clone()),
+            .with(log_collector.clone())
+            .with(crate::profile::layer()),
     ) {
         panic!("Failed to install tracing subscriber: {}", err);
     }
@@ -90,13 +134,49 @@ fn _run(project_path: &Path) -> miette::Result<()> {
     builder.with_any_thread(true);
 
     let events = builder.build().expect("Failed to create event loop");
-    let mut app = app::App::new(event_collector, project, data);
+    let mut app = app::App::new(log_collector, project, data);
 
     events.run_app(&mut app).unwrap();
 
+    crate::profile::flush();
+
     Ok(())
 }
 
+/// Number of log lines included in crash report.
+const CRASH_LOG_LINES: usize = 100;
+
+/// Writes crash report for the launcher when the editor panics.
+/// Plugins run in the editor process, so this covers game code too.
+fn install_crash_hook(root: PathBuf, log_collector: logs::LogCollector) {
+    let default_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = match payload.downcast_ref::<&str>() {
+            Some(s) => s.to_string(),
+            None => match payload.downcast_ref::<String>() {
+                Some(s) => s.clone(),
+                None => "Box<dyn Any>".to_owned(),
+            },
+        };
+
+        let report = CrashReport {
+            message,
+            location: info.location().map(|l| l.to_string()),
+            thread: std::thread::current().name().map(str::to_owned),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+            log: log_collector.tail(CRASH_LOG_LINES),
+        };
+
+        if let Err(err) = report.write(&root) {
+            eprintln!("Failed to write crash report: {err}");
+        }
+
+        default_hook(info);
+    }));
+}
+
 // fn move_element<T>(slice: &mut [T], from_index: usize, to_index: usize) {
 //     if from_index == to_index {
 //         return;
@@ -115,12 +195,20 @@ fn load_project(path: &Path) -> miette::Result<(Project, ProjectData)> {
 
     let path = project.root_path().join("Arcana.bin");
 
-    let data = match std::fs::File::open(path) {
-        Err(err) if err.kind() == ErrorKind::NotFound => ProjectData::default(),
-        Ok(file) => match serde_json::from_reader(file) {
-            Ok(data) => data,
+    let mut data = match std::fs::File::open(&path) {
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            return Ok((
+                project,
+                ProjectData {
+                    version: PROJECT_DATA_VERSION,
+                    ..ProjectData::default()
+                },
+            ));
+        }
+        Ok(file) => match serde_json::from_reader::<_, serde_json::Value>(file) {
+            Ok(document) => document,
             Err(err) => {
-                miette::bail!("Failed to deserialize project data: {}", err);
+                miette::bail!("Failed to parse project data: {}", err);
             }
         },
         Err(err) => {
@@ -128,6 +216,24 @@ fn load_project(path: &Path) -> miette::Result<(Project, ProjectData)> {
         }
     };
 
+    let version = migrate_project_data(&mut data)?;
+
+    let mut data: ProjectData = match serde_json::from_value(data) {
+        Ok(data) => data,
+        Err(err) => {
+            miette::bail!("Failed to deserialize project data: {}", err);
+        }
+    };
+
+    if version != PROJECT_DATA_VERSION {
+        let backup = backup_before_migration(&path, version)?;
+        tracing::info!(
+            "Project data migrated. Original saved to '{}'",
+            backup.display()
+        );
+        data.sync(&project)?;
+    }
+
     Ok((project, data))
 }
 
@@ -165,6 +271,11 @@ fn get_profile() -> Profile {
     }
 }
 
+/// Checks if editor was started in watch mode.
+fn get_watch() -> bool {
+    std::env::var("ARCANA_WATCH").map_or(false, |s| !s.is_empty() && s != "0")
+}
+
 fn init_mev() -> (mev::Device, mev::Queue) {
     let instance = mev::Instance::load().expect("Failed to init graphics");
 
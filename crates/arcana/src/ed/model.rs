@@ -1,93 +1,153 @@
 use std::hash::Hash;
 
 pub use ::arcana::model::{Model, Value};
-use arcana::model::{default_value, ColorModel, ColorValue};
+use arcana::{
+    assets::AssetId,
+    curve::Curve,
+    model::{default_value, ColorModel, ColorValue},
+    name, Name,
+};
 use egui::{Id, Response, Ui, Widget};
 use egui_probe::{DeleteMe, EguiProbe, Style};
 use hashbrown::HashMap;
 
+use super::curve::CurveEditor;
+
+/// Models that can be picked in model editor.
+fn model_templates() -> [Model; 16] {
+    [
+        Model::Bool,
+        Model::Int,
+        Model::Float,
+        Model::String,
+        Model::Color(ColorModel::Srgb),
+        Model::Vec2,
+        Model::Vec3,
+        Model::Vec4,
+        Model::Option(None),
+        Model::Array {
+            elem: None,
+            len: None,
+        },
+        Model::Map(None),
+        Model::Tuple(Vec::new()),
+        Model::Record(Vec::new()),
+        Model::Struct {
+            name: name!(Struct),
+            fields: Vec::new(),
+        },
+        Model::Asset,
+        Model::Curve,
+    ]
+}
+
+/// Label of the model kind in model editor.
+/// Structs are shown by their names elsewhere.
+fn template_label(model: &Model) -> &str {
+    match model {
+        Model::Struct { .. } => "Struct",
+        model => model.kind(),
+    }
+}
+
+fn same_kind(a: &Model, b: &Model) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+/// Shows controls that add fields to composite models.
+/// Returns true if model is changed.
+fn composite_model_ui(ui: &mut Ui, model: &mut Model, local_id: Id, style: &Style) -> bool {
+    #[derive(Clone)]
+    struct NewField(String);
+
+    let mut changed = false;
+
+    if let Model::Struct { name, .. } = model {
+        let mut text = name.to_string();
+        if ui.text_edit_singleline(&mut text).changed() {
+            if let Ok(new_name) = Name::from_str(&text) {
+                *name = new_name;
+                changed = true;
+            }
+        }
+    }
+
+    match model {
+        Model::Tuple(fields) => {
+            if ui.small_button(style.add_button_text()).clicked() {
+                fields.push(None);
+                changed = true;
+            }
+        }
+        Model::Record(fields) | Model::Struct { fields, .. } => {
+            let mut new_field = ui
+                .ctx()
+                .data(|d| d.get_temp::<NewField>(local_id))
+                .unwrap_or(NewField(String::new()));
+
+            ui.text_edit_singleline(&mut new_field.0);
+
+            if ui.small_button(style.add_button_text()).clicked() {
+                match Name::from_str(&new_field.0) {
+                    Ok(name) if !fields.iter().any(|(n, _)| *n == name) => {
+                        fields.push((name, None));
+                        new_field.0.clear();
+                        changed = true;
+                    }
+                    Ok(name) => {
+                        tracing::error!("Field '{name}' already exists");
+                    }
+                    Err(err) => {
+                        tracing::error!("Invalid field name '{}': {err}", new_field.0);
+                    }
+                }
+            }
+
+            ui.ctx().data_mut(|d| d.insert_temp(local_id, new_field));
+        }
+        _ => {}
+    }
+
+    changed
+}
+
 pub struct ModelProbe<'a> {
     model: &'a mut Model,
     id_source: Id,
     local_id: Id,
 }
 
+impl<'a> ModelProbe<'a> {
+    pub fn new(model: &'a mut Model, id_source: impl Hash) -> Self {
+        ModelProbe {
+            model,
+            id_source: Id::new(id_source),
+            local_id: Id::NULL,
+        }
+    }
+}
+
 impl EguiProbe for ModelProbe<'_> {
-    fn probe(&mut self, ui: &mut Ui, _style: &Style) -> Response {
+    fn probe(&mut self, ui: &mut Ui, style: &Style) -> Response {
         self.local_id = ui.make_persistent_id(self.id_source);
         let mut changed = false;
 
-        let var_name = |m: &Model| match *m {
-            Model::Unit => "Unit",
-            Model::Bool => "Bool",
-            Model::Int => "Int",
-            Model::Float => "Float",
-            Model::String => "String",
-            Model::Color(ColorModel::Srgb) => "Color",
-            Model::Option(_) => "Option",
-            Model::Array { .. } => "Array",
-            Model::Map(_) => "Map",
-            Model::Tuple { .. } => "Tuple",
-            Model::Record { .. } => "Record",
-            _ => todo!(),
-        };
-
-        let mut r = egui::ComboBox::from_id_source(self.local_id)
-            .selected_text(var_name(self.model))
-            .show_ui(ui, |ui| {
-                let r = ui.selectable_label(matches!(self.model, Model::Bool), "Bool");
-                if r.clicked() && !matches!(self.model, Model::Bool) {
-                    *self.model = Model::Bool;
-                    changed = true;
-                }
-                let r = ui.selectable_label(matches!(self.model, Model::Int), "Int");
-                if r.clicked() && !matches!(self.model, Model::Int) {
-                    *self.model = Model::Int;
-                    changed = true;
-                }
-                let r = ui.selectable_label(matches!(self.model, Model::Float), "Float");
-                if r.clicked() && !matches!(self.model, Model::Float) {
-                    *self.model = Model::Float;
-                    changed = true;
-                }
-                let r = ui.selectable_label(matches!(self.model, Model::String), "String");
-                if r.clicked() && !matches!(self.model, Model::String) {
-                    *self.model = Model::String;
-                    changed = true;
-                }
-                let r = ui.selectable_label(matches!(self.model, Model::Color(_)), "Color");
-                if r.clicked() && !matches!(self.model, Model::Color(_)) {
-                    *self.model = Model::Color(ColorModel::Srgb);
-                    changed = true;
-                }
-                let r = ui.selectable_label(matches!(self.model, Model::Option(_)), "Option");
-                if r.clicked() && !matches!(self.model, Model::Option(_)) {
-                    *self.model = Model::Option(None);
-                    changed = true;
-                }
-                let r = ui.selectable_label(matches!(self.model, Model::Array { .. }), "Array");
-                if r.clicked() && !matches!(self.model, Model::Array { .. }) {
-                    *self.model = Model::Array {
-                        elem: None,
-                        len: None,
-                    };
-                    changed = true;
-                }
-                let r = ui.selectable_label(matches!(self.model, Model::Map(_)), "Map");
-                if r.clicked() && !matches!(self.model, Model::Map(_)) {
-                    *self.model = Model::Map(None);
-                    changed = true;
-                }
-                let r = ui.selectable_label(matches!(self.model, Model::Tuple { .. }), "Tuple");
-                if r.clicked() && !matches!(self.model, Model::Tuple { .. }) {
-                    *self.model = Model::Tuple(Vec::new());
-                    changed = true;
-                }
-                let r = ui.selectable_label(matches!(self.model, Model::Record { .. }), "Record");
-                if r.clicked() && !matches!(self.model, Model::Record { .. }) {
-                    *self.model = Model::Record(Vec::new());
-                    changed = true;
-                }
+        let mut r = ui
+            .horizontal(|ui| {
+                egui::ComboBox::from_id_source(self.local_id)
+                    .selected_text(template_label(self.model))
+                    .show_ui(ui, |ui| {
+                        for template in model_templates() {
+                            let selected = same_kind(self.model, &template);
+                            let r = ui.selectable_label(selected, template_label(&template));
+                            if r.clicked() && !selected {
+                                *self.model = template;
+                                changed = true;
+                            }
+                        }
+                    });
+
+                changed |= composite_model_ui(ui, self.model, self.local_id.with("new"), style);
             })
             .response;
 
@@ -128,11 +188,57 @@ impl EguiProbe for ModelProbe<'_> {
                 };
                 f("value", ui, &mut probe);
             }
+            Model::Tuple(ref mut fields) => {
+                let mut idx = 0;
+                fields.retain_mut(|field| {
+                    let delete =
+                        field_model_item(field, self.local_id.with(idx), &format!("{idx}"), ui, f);
+                    idx += 1;
+                    !delete
+                });
+            }
+            Model::Record(ref mut fields) | Model::Struct { ref mut fields, .. } => {
+                fields.retain_mut(|(name, field)| {
+                    !field_model_item(
+                        field,
+                        self.local_id.with(name.as_str()),
+                        name.as_str(),
+                        ui,
+                        f,
+                    )
+                });
+            }
             _ => {}
         }
     }
 }
 
+/// Shows model of a field that can be deleted.
+/// Returns true if field should be deleted.
+fn field_model_item(
+    field: &mut Option<Model>,
+    id_source: Id,
+    label: &str,
+    ui: &mut Ui,
+    f: &mut dyn FnMut(&str, &mut Ui, &mut dyn EguiProbe),
+) -> bool {
+    let mut model = field.take().map(Box::new);
+
+    let mut probe = MaybeModelProbe {
+        model: &mut model,
+        id_source,
+        local_id: Id::NULL,
+    };
+    let mut item = DeleteMe {
+        value: &mut probe,
+        delete: false,
+    };
+    f(label, ui, &mut item);
+
+    *field = model.map(|model| *model);
+    item.delete
+}
+
 pub struct MaybeModelProbe<'a> {
     model: &'a mut Option<Box<Model>>,
     id_source: Id,
@@ -140,99 +246,45 @@ pub struct MaybeModelProbe<'a> {
 }
 
 impl EguiProbe for MaybeModelProbe<'_> {
-    fn probe(&mut self, ui: &mut Ui, _style: &Style) -> Response {
+    fn probe(&mut self, ui: &mut Ui, style: &Style) -> Response {
         self.local_id = ui.make_persistent_id(self.id_source);
+        let mut changed = false;
 
-        let var_name = |m: Option<&Model>| match m {
-            None => "None",
-            Some(Model::Unit) => "Unit",
-            Some(Model::Bool) => "Bool",
-            Some(Model::Int) => "Int",
-            Some(Model::Float) => "Float",
-            Some(Model::String) => "String",
-            Some(Model::Color(ColorModel::Srgb)) => "Color",
-            Some(Model::Option(_)) => "Option",
-            Some(Model::Array { .. }) => "Array",
-            Some(Model::Map(_)) => "Map",
-            Some(Model::Tuple { .. }) => "Tuple",
-            Some(Model::Record { .. }) => "Record",
-            _ => todo!(),
-        };
-
-        egui::ComboBox::from_id_source(self.local_id)
-            .selected_text(var_name(self.model.as_deref()))
-            .show_ui(ui, |ui| {
-                let r = ui.selectable_label(matches!(self.model, None), "None");
-                if r.clicked() {
-                    *self.model = None;
-                }
-                let r =
-                    ui.selectable_label(matches!(self.model.as_deref(), Some(Model::Bool)), "Bool");
-                if r.clicked() {
-                    *self.model = Some(Box::new(Model::Bool));
-                }
-                let r =
-                    ui.selectable_label(matches!(self.model.as_deref(), Some(Model::Int)), "Int");
-                if r.clicked() {
-                    *self.model = Some(Box::new(Model::Int));
-                }
-                let r = ui
-                    .selectable_label(matches!(self.model.as_deref(), Some(Model::Float)), "Float");
-                if r.clicked() {
-                    *self.model = Some(Box::new(Model::Float));
-                }
-                let r = ui.selectable_label(
-                    matches!(self.model.as_deref(), Some(Model::String)),
-                    "String",
-                );
-                if r.clicked() {
-                    *self.model = Some(Box::new(Model::String));
-                }
-                let r = ui.selectable_label(
-                    matches!(self.model.as_deref(), Some(Model::Color(_))),
-                    "Color",
-                );
-                if r.clicked() && !matches!(self.model.as_deref(), Some(Model::Color(_))) {
-                    *self.model = Some(Box::new(Model::Color(ColorModel::Srgb)));
-                }
-                let r = ui.selectable_label(
-                    matches!(self.model.as_deref(), Some(Model::Option(_))),
-                    "Option",
-                );
-                if r.clicked() && !matches!(self.model.as_deref(), Some(Model::Option(_))) {
-                    *self.model = Some(Box::new(Model::Option(None)));
-                }
-                let r = ui.selectable_label(
-                    matches!(self.model.as_deref(), Some(Model::Array { .. })),
-                    "Array",
-                );
-                if r.clicked() && !matches!(self.model.as_deref(), Some(Model::Array { .. })) {
-                    *self.model = Some(Box::new(Model::Array {
-                        elem: None,
-                        len: None,
-                    }));
-                }
-                let r = ui
-                    .selectable_label(matches!(self.model.as_deref(), Some(Model::Map(_))), "Map");
-                if r.clicked() && !matches!(self.model.as_deref(), Some(Model::Map(_))) {
-                    *self.model = Some(Box::new(Model::Map(None)));
-                }
-                let r = ui.selectable_label(
-                    matches!(self.model.as_deref(), Some(Model::Tuple { .. })),
-                    "Tuple",
-                );
-                if r.clicked() && !matches!(self.model.as_deref(), Some(Model::Tuple { .. })) {
-                    *self.model = Some(Box::new(Model::Tuple(Vec::new())));
-                }
-                let r = ui.selectable_label(
-                    matches!(self.model.as_deref(), Some(Model::Record { .. })),
-                    "Record",
-                );
-                if r.clicked() && !matches!(self.model.as_deref(), Some(Model::Record { .. })) {
-                    *self.model = Some(Box::new(Model::Record(Vec::new())));
+        let mut r = ui
+            .horizontal(|ui| {
+                egui::ComboBox::from_id_source(self.local_id)
+                    .selected_text(self.model.as_deref().map_or("None", template_label))
+                    .show_ui(ui, |ui| {
+                        let r = ui.selectable_label(self.model.is_none(), "None");
+                        if r.clicked() && self.model.is_some() {
+                            *self.model = None;
+                            changed = true;
+                        }
+
+                        for template in model_templates() {
+                            let selected = self
+                                .model
+                                .as_deref()
+                                .map_or(false, |model| same_kind(model, &template));
+                            let r = ui.selectable_label(selected, template_label(&template));
+                            if r.clicked() && !selected {
+                                *self.model = Some(Box::new(template));
+                                changed = true;
+                            }
+                        }
+                    });
+
+                if let Some(model) = self.model.as_deref_mut() {
+                    changed |= composite_model_ui(ui, model, self.local_id.with("new"), style);
                 }
             })
-            .response
+            .response;
+
+        if changed {
+            r.mark_changed();
+        }
+
+        r
     }
 
     fn iterate_inner(
@@ -240,32 +292,13 @@ impl EguiProbe for MaybeModelProbe<'_> {
         ui: &mut egui::Ui,
         f: &mut dyn FnMut(&str, &mut egui::Ui, &mut dyn EguiProbe),
     ) {
-        match self.model.as_deref_mut() {
-            Some(Model::Option(model)) => {
-                let mut probe = MaybeModelProbe {
-                    model,
-                    id_source: self.local_id.with("Model"),
-                    local_id: Id::NULL,
-                };
-                f("model", ui, &mut probe);
-            }
-            Some(Model::Array { elem, .. }) => {
-                let mut probe = MaybeModelProbe {
-                    model: elem,
-                    id_source: self.local_id.with("Model"),
-                    local_id: Id::NULL,
-                };
-                f("elem", ui, &mut probe);
+        if let Some(model) = self.model.as_deref_mut() {
+            ModelProbe {
+                model,
+                id_source: self.id_source,
+                local_id: self.local_id,
             }
-            Some(Model::Map(elem)) => {
-                let mut probe = MaybeModelProbe {
-                    model: elem,
-                    id_source: self.local_id.with("Model"),
-                    local_id: Id::NULL,
-                };
-                f("value", ui, &mut probe);
-            }
-            _ => {}
+            .iterate_inner(ui, f);
         }
     }
 }
@@ -847,16 +880,19 @@ impl EguiProbe for ValueProbe<'_> {
                 },
             },
             Some(&Model::Option(ref model)) => match self.value {
-                Value::Option(value) => egui_probe::option_probe_with(
-                    value,
-                    ui,
-                    style,
-                    || Box::new(default_value(model.as_deref())),
-                    |value, ui, style| {
-                        ValueProbe::new(model.as_deref(), value, self.local_id.with("some"))
-                            .probe(ui, style)
-                    },
-                ),
+                Value::Option(value) => {
+                    self.local_id = ui.make_persistent_id(self.id_source);
+                    egui_probe::option_probe_with(
+                        value,
+                        ui,
+                        style,
+                        || Box::new(default_value(model.as_deref())),
+                        |value, ui, style| {
+                            ValueProbe::new(model.as_deref(), value, self.local_id.with("some"))
+                                .probe(ui, style)
+                        },
+                    )
+                }
                 _ => {
                     let mut changed = false;
                     let mut r = ui
@@ -1130,8 +1166,57 @@ impl EguiProbe for ValueProbe<'_> {
                     r
                 }
             },
-
-            _ => todo!(),
+            Some(&Model::Vec2) => match self.value {
+                Value::Vec2(v) => vector_probe(ui, v.as_mut_slice()),
+                _ => reset_probe(ui, self.value, "vector", &Model::Vec2),
+            },
+            Some(&Model::Vec3) => match self.value {
+                Value::Vec3(v) => vector_probe(ui, v.as_mut_slice()),
+                _ => reset_probe(ui, self.value, "vector", &Model::Vec3),
+            },
+            Some(&Model::Vec4) => match self.value {
+                Value::Vec4(v) => vector_probe(ui, v.as_mut_slice()),
+                _ => reset_probe(ui, self.value, "vector", &Model::Vec4),
+            },
+            Some(&Model::Mat2) => match self.value {
+                Value::Mat2(m) => matrix_probe(ui, m.as_mut_slice(), 2),
+                _ => reset_probe(ui, self.value, "matrix", &Model::Mat2),
+            },
+            Some(&Model::Mat3) => match self.value {
+                Value::Mat3(m) => matrix_probe(ui, m.as_mut_slice(), 3),
+                _ => reset_probe(ui, self.value, "matrix", &Model::Mat3),
+            },
+            Some(&Model::Mat4) => match self.value {
+                Value::Mat4(m) => matrix_probe(ui, m.as_mut_slice(), 4),
+                _ => reset_probe(ui, self.value, "matrix", &Model::Mat4),
+            },
+            Some(model @ &Model::Record(_)) => match self.value {
+                Value::Map(_) => {
+                    self.local_id = ui.make_persistent_id(self.id_source);
+                    ui.weak("Record")
+                }
+                _ => reset_probe(ui, self.value, "record", model),
+            },
+            Some(model @ &Model::Struct { name, .. }) => match self.value {
+                Value::Map(_) => {
+                    self.local_id = ui.make_persistent_id(self.id_source);
+                    ui.weak(name.as_str())
+                }
+                _ => reset_probe(ui, self.value, name.as_str(), model),
+            },
+            Some(model @ &Model::Tuple(ref fields)) => match self.value {
+                Value::Array(values) if values.len() == fields.len() => {
+                    self.local_id = ui.make_persistent_id(self.id_source);
+                    ui.weak("Tuple")
+                }
+                _ => reset_probe(ui, self.value, "tuple", model),
+            },
+            Some(&Model::Asset) => asset_probe(ui, self.value),
+            Some(&Model::Curve) => match self.value {
+                Value::Curve(curve) => ui.add(CurveEditor::new(curve)),
+                _ => reset_probe(ui, self.value, "curve", &Model::Curve),
+            },
+            Some(&Model::Opaque(_)) => ui.weak("Opaque"),
         }
     }
 
@@ -1148,6 +1233,19 @@ impl EguiProbe for ValueProbe<'_> {
             Some(Model::Float { .. }) => {}
             Some(Model::String { .. }) => {}
             Some(Model::Color(_)) => {}
+            Some(Model::Option(model)) => match self.value {
+                Value::Option(Some(value)) => {
+                    let id = self.local_id.with("some");
+                    let mut probe = ValueProbe {
+                        model: model.as_deref(),
+                        local_id: id,
+                        value,
+                        id_source: id,
+                    };
+                    probe.iterate_inner(ui, f);
+                }
+                _ => {}
+            },
             Some(Model::Array { elem, len }) => {
                 let local_elem;
                 let elem = match elem {
@@ -1230,11 +1328,136 @@ impl EguiProbe for ValueProbe<'_> {
                 }
                 _ => {}
             },
-            _ => todo!(),
+            Some(
+                Model::Vec2
+                | Model::Vec3
+                | Model::Vec4
+                | Model::Mat2
+                | Model::Mat3
+                | Model::Mat4
+                | Model::Asset
+                | Model::Curve
+                | Model::Opaque(_),
+            ) => {}
+            Some(Model::Record(fields) | Model::Struct { fields, .. }) => match self.value {
+                Value::Map(values) => {
+                    for (name, model) in fields {
+                        let value = values
+                            .entry(name.to_string())
+                            .or_insert_with(|| default_value(model.as_ref()));
+
+                        let mut probe = ValueProbe::new(
+                            model.as_ref(),
+                            value,
+                            self.local_id.with(name.as_str()),
+                        );
+                        f(name.as_str(), ui, &mut probe);
+                    }
+                }
+                _ => {}
+            },
+            Some(Model::Tuple(fields)) => match self.value {
+                Value::Array(values) => {
+                    for (idx, (model, value)) in fields.iter().zip(values.iter_mut()).enumerate() {
+                        let mut probe =
+                            ValueProbe::new(model.as_ref(), value, self.local_id.with(idx));
+                        f(&format!("{idx}"), ui, &mut probe);
+                    }
+                }
+                _ => {}
+            },
         }
     }
 }
 
+fn vector_probe(ui: &mut Ui, components: &mut [f64]) -> Response {
+    let mut changed = false;
+    let mut r = ui
+        .horizontal(|ui| {
+            for c in components {
+                changed |= ui.add(egui::DragValue::new(c).speed(0.01)).changed();
+            }
+        })
+        .response;
+
+    if changed {
+        r.mark_changed();
+    }
+
+    r
+}
+
+/// Shows matrix as grid of rows.
+/// Components are stored column-major.
+fn matrix_probe(ui: &mut Ui, components: &mut [f64], n: usize) -> Response {
+    let mut changed = false;
+    let mut r = ui
+        .vertical(|ui| {
+            for row in 0..n {
+                ui.horizontal(|ui| {
+                    for col in 0..n {
+                        let c = &mut components[col * n + row];
+                        changed |= ui.add(egui::DragValue::new(c).speed(0.01)).changed();
+                    }
+                });
+            }
+        })
+        .response;
+
+    if changed {
+        r.mark_changed();
+    }
+
+    r
+}
+
+/// Shows asset reference that accepts assets dragged from asset browser.
+fn asset_probe(ui: &mut Ui, value: &mut Value) -> Response {
+    let frame = egui::Frame::group(ui.style()).inner_margin(egui::Margin::symmetric(4.0, 1.0));
+
+    let (inner, payload) = ui.dnd_drop_zone::<AssetId, _>(frame, |ui| match *value {
+        Value::Asset(id) => {
+            ui.label(format!("{id}"));
+        }
+        _ => {
+            ui.weak("Drop asset here");
+        }
+    });
+
+    let mut r = inner.response;
+
+    if let Some(id) = payload {
+        *value = Value::Asset(*id);
+        r.mark_changed();
+    }
+
+    r
+}
+
+/// Shows value that does not match the model with button to reset it to default.
+fn reset_probe(ui: &mut Ui, value: &mut Value, expected: &str, model: &Model) -> Response {
+    let mut changed = false;
+    let mut r = ui
+        .horizontal(|ui| {
+            ui.strong(format!(
+                "Expected {expected}, but is {} instead",
+                value.kind()
+            ));
+            if ui.small_button("Reset to default").clicked() {
+                *value = model.default_value();
+                changed = true;
+            }
+            ui.strong("?");
+        })
+        .response;
+
+    if changed {
+        r.mark_changed();
+    }
+
+    r
+}
+
 fn convert_to_string<T: ToString>(
     ui: &mut Ui,
     value: &T,
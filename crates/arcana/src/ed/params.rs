@@ -0,0 +1,345 @@
+//! Editor for parameter sets.
+//!
+//! Lists `.params` sources in project assets
+//! and edits models and values of their parameters.
+//! Parameters of the job the set is meant for can be added in one click.
+
+use std::path::{Path, PathBuf};
+
+use egui::Ui;
+use hashbrown::HashMap;
+
+use crate::{
+    assets::params::{Param, ParamSet},
+    model::Model,
+    project::Project,
+    Name,
+};
+
+use super::{
+    container::Container,
+    model::{ModelProbe, ValueProbe},
+};
+
+/// Extension of parameter set sources.
+const EXTENSION: &str = "params";
+
+pub struct ParamSets {
+    /// Parameter set sources relative to assets directory.
+    files: Vec<PathBuf>,
+    scanned: bool,
+
+    selected: Option<PathBuf>,
+    set: ParamSet,
+    modified: bool,
+
+    /// Parameters of jobs available in plugins.
+    jobs: HashMap<Name, Vec<(Name, Model)>>,
+
+    new_file: String,
+    new_param: String,
+}
+
+impl ParamSets {
+    pub fn new() -> Self {
+        ParamSets {
+            files: Vec::new(),
+            scanned: false,
+            selected: None,
+            set: ParamSet::default(),
+            modified: false,
+            jobs: HashMap::new(),
+            new_file: String::new(),
+            new_param: String::new(),
+        }
+    }
+
+    pub fn update_plugins(&mut self, container: &Container) {
+        self.jobs.clear();
+
+        for (_, plugin) in container.plugins() {
+            for job in plugin.jobs() {
+                self.jobs.insert(job.name, job.desc.params);
+            }
+        }
+    }
+
+    fn scan(&mut self, assets: &Path) {
+        self.files.clear();
+        scan_dir(assets, assets, &mut self.files);
+        self.files.sort();
+        self.scanned = true;
+    }
+
+    fn open(&mut self, assets: &Path, file: PathBuf) {
+        self.modified = false;
+
+        let path = assets.join(&file);
+        let set = std::fs::read_to_string(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|text| ParamSet::from_json(&text).map_err(|err| err.to_string()));
+
+        match set {
+            Ok(set) => self.set = set,
+            Err(err) => {
+                tracing::error!("Failed to read parameter set '{}': {err}", path.display());
+                self.set = ParamSet::default();
+            }
+        }
+
+        self.selected = Some(file);
+    }
+
+    fn save(&mut self, assets: &Path) {
+        let Some(file) = &self.selected else {
+            return;
+        };
+
+        let path = assets.join(file);
+        match std::fs::write(&path, self.set.to_json()) {
+            Ok(()) => self.modified = false,
+            Err(err) => {
+                tracing::error!("Failed to write parameter set '{}': {err}", path.display());
+            }
+        }
+    }
+
+    pub fn show(&mut self, project: &Project, ui: &mut Ui) {
+        let assets = project.root_path().join("Assets");
+
+        if !self.scanned {
+            self.scan(&assets);
+        }
+
+        let mut open = None;
+
+        ui.horizontal(|ui| {
+            let selected = match &self.selected {
+                None => "Select parameter set".to_owned(),
+                Some(file) => file.display().to_string(),
+            };
+
+            egui::ComboBox::from_id_source("param-set-file")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    for file in &self.files {
+                        let r = ui.selectable_label(
+                            self.selected.as_ref() == Some(file),
+                            file.display().to_string(),
+                        );
+                        if r.clicked() {
+                            open = Some(file.clone());
+                        }
+                    }
+                });
+
+            if ui
+                .button(egui_phosphor::regular::ARROWS_CLOCKWISE)
+                .on_hover_text("Rescan assets")
+                .clicked()
+            {
+                self.scan(&assets);
+            }
+
+            ui.separator();
+
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_file)
+                    .hint_text("New parameter set")
+                    .desired_width(150.0),
+            );
+
+            let r = ui.add_enabled(
+                !self.new_file.trim().is_empty(),
+                egui::Button::new(egui_phosphor::regular::FILE_PLUS),
+            );
+            if r.on_hover_text("Create parameter set").clicked() {
+                let file = PathBuf::from(format!("{}.{EXTENSION}", self.new_file.trim()));
+                let path = assets.join(&file);
+
+                if path.exists() {
+                    tracing::error!("Parameter set '{}' already exists", path.display());
+                } else {
+                    self.new_file.clear();
+                    self.selected = Some(file);
+                    self.set = ParamSet::default();
+                    self.save(&assets);
+                    self.scan(&assets);
+                }
+            }
+        });
+
+        // Switching away drops unsaved changes.
+        if let Some(file) = open {
+            self.open(&assets, file);
+        }
+
+        if self.selected.is_none() {
+            ui.separator();
+            ui.label("No parameter set selected");
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            let r = ui.add_enabled(
+                self.modified,
+                egui::Button::new(egui_phosphor::regular::FLOPPY_DISK),
+            );
+            if r.on_hover_text("Save").clicked() {
+                self.save(&assets);
+            }
+
+            let r = ui.add_enabled(
+                self.modified,
+                egui::Button::new(egui_phosphor::regular::ARROW_COUNTER_CLOCKWISE),
+            );
+            if r.on_hover_text("Revert").clicked() {
+                if let Some(file) = self.selected.clone() {
+                    self.open(&assets, file);
+                }
+            }
+
+            ui.separator();
+
+            ui.label("Job");
+            self.show_job(ui);
+        });
+
+        ui.separator();
+
+        let mut remove = None;
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for (idx, param) in self.set.params.iter_mut().enumerate() {
+                    let original = param.clone();
+
+                    ui.horizontal(|ui| {
+                        ui.strong(param.name.as_str());
+                        if ui
+                            .small_button(egui_phosphor::regular::TRASH)
+                            .on_hover_text("Remove parameter")
+                            .clicked()
+                        {
+                            remove = Some(idx);
+                        }
+                    });
+
+                    let mut probe = ModelProbe::new(&mut param.model, ("param-model", idx));
+                    egui_probe::Probe::new(&mut probe)
+                        .with_header("model")
+                        .show(ui);
+
+                    let mut probe =
+                        ValueProbe::new(Some(&param.model), &mut param.value, ("param-value", idx));
+                    egui_probe::Probe::new(&mut probe)
+                        .with_header("value")
+                        .show(ui);
+
+                    if *param != original {
+                        self.modified = true;
+                    }
+
+                    ui.separator();
+                }
+
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.new_param)
+                            .hint_text("New parameter")
+                            .desired_width(150.0),
+                    );
+
+                    let name = Name::from_str(self.new_param.trim()).ok();
+                    let valid = name.map_or(false, |name| self.set.get(name).is_none());
+
+                    let r = ui.add_enabled(valid, egui::Button::new(egui_phosphor::regular::PLUS));
+                    if r.on_hover_text("Add parameter").clicked() {
+                        if let Some(name) = name {
+                            self.set.params.push(Param {
+                                name,
+                                model: Model::Float,
+                                value: Model::Float.default_value(),
+                            });
+                            self.new_param.clear();
+                            self.modified = true;
+                        }
+                    }
+                });
+            });
+
+        if let Some(idx) = remove {
+            self.set.params.remove(idx);
+            self.modified = true;
+        }
+    }
+
+    fn show_job(&mut self, ui: &mut Ui) {
+        let selected = match self.set.job {
+            None => "None".to_owned(),
+            Some(job) => job.to_string(),
+        };
+
+        let mut names = self.jobs.keys().copied().collect::<Vec<_>>();
+        names.sort();
+
+        egui::ComboBox::from_id_source("param-set-job")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(self.set.job.is_none(), "None")
+                    .clicked()
+                {
+                    self.set.job = None;
+                    self.modified = true;
+                }
+                for name in names {
+                    let r = ui.selectable_label(self.set.job == Some(name), name.as_str());
+                    if r.clicked() && self.set.job != Some(name) {
+                        self.set.job = Some(name);
+                        self.modified = true;
+                    }
+                }
+            });
+
+        let Some(params) = self.set.job.and_then(|job| self.jobs.get(&job)) else {
+            return;
+        };
+
+        let missing = params
+            .iter()
+            .filter(|(name, _)| self.set.get(*name).is_none())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let r = ui.add_enabled(
+            !missing.is_empty(),
+            egui::Button::new(egui_phosphor::regular::LIST_PLUS),
+        );
+        if r.on_hover_text("Add parameters of the job").clicked() {
+            for (name, model) in missing {
+                let value = model.default_value();
+                self.set.params.push(Param { name, model, value });
+            }
+            self.modified = true;
+        }
+    }
+}
+
+fn scan_dir(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            scan_dir(root, &path, files);
+        } else if path.extension().map_or(false, |e| e == EXTENSION) {
+            if let Ok(file) = path.strip_prefix(root) {
+                files.push(file.to_owned());
+            }
+        }
+    }
+}
@@ -3,8 +3,13 @@ use arcana::{
         new_plugin_crate, process_path_ident, BuildProcess, Dependency, Plugin, Profile, Project,
         ProjectManifest,
     },
-    Ident,
+    sandbox, Ident,
 };
+use std::{
+    path::Path,
+    time::{Duration, Instant, SystemTime},
+};
+
 use camino::{Utf8Path, Utf8PathBuf};
 use egui::{Color32, RichText, Ui};
 use egui_file::FileDialog;
@@ -12,9 +17,13 @@ use egui_file::FileDialog;
 use super::{
     container::{Container, Loader, PluginsError},
     data::ProjectData,
-    get_profile,
+    get_profile, get_watch,
+    systems::Systems,
 };
 
+/// Interval between checks of plugin sources in watch mode.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Tool to manage plugins libraries
 /// and enable/disable self.
 pub(super) struct Plugins {
@@ -36,6 +45,14 @@ pub(super) struct Plugins {
     dialog: Option<PluginsDialog>,
 
     profile: Profile,
+
+    /// Watches plugin sources and triggers rebuild when they change.
+    /// Unset when watch mode is off.
+    watcher: Option<SourceWatcher>,
+
+    /// Plugin waiting for the user to grant requested permissions
+    /// before it is enabled.
+    approval: Option<Ident>,
 }
 
 enum PluginsDialog {
@@ -52,6 +69,8 @@ impl Plugins {
             build: None,
             dialog: None,
             profile: get_profile(),
+            watcher: get_watch().then(SourceWatcher::new),
+            approval: None,
         }
     }
 
@@ -98,6 +117,25 @@ impl Plugins {
         data: &ProjectData,
         need_build: bool,
     ) -> Option<Container> {
+        if let Some(watcher) = &mut self.watcher {
+            if watcher.check(project) {
+                tracing::info!("Plugin sources changed. Rebuilding plugins library");
+
+                // Restart build if one is running, it may have missed the change.
+                self.build = None;
+                self.failure = None;
+
+                match project.build_plugins_library(self.profile) {
+                    Ok(build) => {
+                        self.build = Some(build);
+                    }
+                    Err(err) => {
+                        self.failure = Some(err);
+                    }
+                }
+            }
+        }
+
         if let Some(mut build) = self.build.take() {
             match build.finished() {
                 Ok(false) => self.build = Some(build),
@@ -106,8 +144,14 @@ impl Plugins {
                         "Finished building plugins library {}",
                         build.artifact().display()
                     );
+                    // Plugins resolved by the build may request new permissions.
+                    ok_log_err!(project.refresh_permissions());
+
                     let path = build.artifact();
-                    match self.loader.load(&path, &data.enabled_plugins) {
+                    match self
+                        .loader
+                        .load(&path, &data.enabled_plugins, plugin_order(project))
+                    {
                         Ok(container) => {
                             if !Self::check_plugins(project.manifest(), &container) {
                                 tracing::warn!("Not all plugins are linked. Rebuilding");
@@ -188,6 +232,7 @@ impl Plugins {
             }
             Some(c) => {
                 tracing::info!("New plugins container version linked. {c:#?}");
+                apply_grants(project, data);
                 Some(c)
             }
         }
@@ -198,10 +243,12 @@ impl Plugins {
         linked: Option<&Container>,
         project: &mut Project,
         data: &mut ProjectData,
+        systems: &mut Systems,
         ui: &mut Ui,
     ) {
         let mut sync = false;
         let mut rebuild = false;
+        let mut reorder = false;
 
         // Building status
 
@@ -239,6 +286,18 @@ impl Plugins {
                     let build = try_log_err!(project.build_plugins_library(self.profile));
                     self.build = Some(build);
                 }
+
+                let r = ui.selectable_label(self.watcher.is_some(), egui_phosphor::regular::EYE);
+                if r.clicked() {
+                    self.watcher = match self.watcher {
+                        None => Some(SourceWatcher::new()),
+                        Some(_) => None,
+                    };
+                } else {
+                    r.on_hover_ui(|ui| {
+                        ui.label("Rebuild plugins when sources change");
+                    });
+                }
                 let r = ui.button(egui_phosphor::regular::PLUS);
 
                 if r.clicked() {
@@ -267,6 +326,8 @@ impl Plugins {
 
             // Plugins list
             let mut remove_plugin = None;
+            let mut move_plugin = None;
+            let plugins_count = project.plugins().len();
 
             egui::Grid::new("plugins-list")
                 .striped(true)
@@ -274,20 +335,26 @@ impl Plugins {
                     for (idx, plugin) in project.plugins().iter().enumerate() {
                         let mut heading = RichText::from(plugin.name.as_str());
 
-                        let mut tooltip = "";
+                        let mut tooltip = String::new();
+                        let mut unmet = Vec::new();
                         if !linked.map_or(false, |c| c.has(plugin.name)) {
                             // Not linked plugin may not be active.
                             if self.pending.is_some() || self.build.is_some() {
-                                tooltip = "Pending";
+                                tooltip = "Pending".to_owned();
                                 heading = heading.color(ui.visuals().warn_fg_color);
                             } else {
-                                tooltip = "Plugin is missing in library";
+                                tooltip = "Plugin is missing in library".to_owned();
                                 heading = heading.color(ui.visuals().error_fg_color);
                             }
                         } else if !data.enabled_plugins.contains(&plugin.name) {
                             heading = heading.color(ui.visuals().warn_fg_color);
-                        } else if !linked.map_or(false, |c| c.is_active(plugin.name)) {
-                            tooltip = "Dependencies are not enabled";
+                        } else if let Some(c) = linked.filter(|c| !c.is_active(plugin.name)) {
+                            unmet = c.unmet_dependencies(plugin.name);
+                            tooltip = "Dependencies are not enabled:".to_owned();
+                            for dep in &unmet {
+                                tooltip.push_str("\n  ");
+                                tooltip.push_str(dep.as_str());
+                            }
                             heading = heading.color(ui.visuals().warn_fg_color);
                         } else {
                             heading = heading.color(Color32::LIGHT_GREEN);
@@ -301,9 +368,51 @@ impl Plugins {
                             r.on_hover_text(tooltip);
                         }
 
+                        if !plugin.permissions.is_empty() {
+                            ui.label(egui_phosphor::regular::SHIELD_WARNING)
+                                .on_hover_ui(|ui| show_permissions(plugin, ui));
+                        } else {
+                            ui.label("");
+                        }
+
+                        if !unmet.is_empty() {
+                            let r = ui
+                                .button(egui_phosphor::regular::LINK)
+                                .on_hover_text("Enable dependencies");
+                            if r.clicked() {
+                                for dep in unmet {
+                                    if data.enabled_plugins.contains(&dep) {
+                                        continue;
+                                    }
+                                    match project.manifest().get_plugin(dep) {
+                                        Some(dep_plugin) if needs_approval(dep_plugin, data) => {
+                                            self.approval = Some(dep);
+                                        }
+                                        Some(dep_plugin) => {
+                                            data.granted_permissions
+                                                .insert(dep, dep_plugin.permissions);
+                                            data.enabled_plugins.insert(dep);
+                                            sync = true;
+                                        }
+                                        None => {
+                                            tracing::warn!("Dependency '{dep}' is not in project");
+                                        }
+                                    }
+                                }
+                            }
+                        } else {
+                            ui.label("");
+                        }
+
                         if !was_enabled && enabled {
-                            data.enabled_plugins.insert(plugin.name.clone());
-                            sync = true;
+                            if needs_approval(plugin, data) {
+                                self.approval = Some(plugin.name);
+                            } else {
+                                data.granted_permissions
+                                    .insert(plugin.name, plugin.permissions);
+                                data.enabled_plugins.insert(plugin.name.clone());
+                                sync = true;
+                            }
                         } else if was_enabled && !enabled {
                             data.enabled_plugins.remove(&plugin.name);
                             sync = true;
@@ -313,10 +422,31 @@ impl Plugins {
                             let r = ui.button(egui_phosphor::regular::TRASH);
                             if r.clicked() {
                                 data.enabled_plugins.remove(&plugin.name);
+                                data.granted_permissions.remove(&plugin.name);
                                 remove_plugin = Some(idx);
                                 sync = true;
                                 rebuild = true;
                             }
+
+                            let r = ui
+                                .add_enabled(
+                                    idx + 1 < plugins_count,
+                                    egui::Button::new(egui_phosphor::regular::ARROW_DOWN),
+                                )
+                                .on_hover_text("Initialize later");
+                            if r.clicked() {
+                                move_plugin = Some((idx, idx + 1));
+                            }
+
+                            let r = ui
+                                .add_enabled(
+                                    idx > 0,
+                                    egui::Button::new(egui_phosphor::regular::ARROW_UP),
+                                )
+                                .on_hover_text("Initialize earlier");
+                            if r.clicked() {
+                                move_plugin = Some((idx, idx - 1));
+                            }
                         });
 
                         ui.end_row();
@@ -325,9 +455,80 @@ impl Plugins {
 
             if let Some(idx) = remove_plugin {
                 project.manifest_mut().remove_plugin_idx(idx);
+            } else if let Some((from, to)) = move_plugin {
+                // Dependencies are still initialized first.
+                project.manifest_mut().move_plugin_idx(from, to);
+                sync = true;
+                reorder = true;
+            }
+
+            // Systems of active plugins
+            if let Some(linked) = linked {
+                let mut modified = false;
+
+                for (name, _) in linked.plugins() {
+                    let mut plugin_systems = data.systems.plugin_systems_mut(name).peekable();
+                    if plugin_systems.peek().is_none() {
+                        continue;
+                    }
+
+                    egui::CollapsingHeader::new(format!("{name} systems"))
+                        .id_source(("plugin-systems", name))
+                        .show(ui, |ui| {
+                            for (system, enabled) in plugin_systems {
+                                modified |= ui.checkbox(enabled, system.as_str()).changed();
+                            }
+                        });
+                }
+
+                if modified {
+                    // Schedule is rebuilt by instances on next tick.
+                    systems.invalidate();
+                    try_log_err!(data.sync(&project));
+                }
             }
         });
 
+        if let Some(name) = self.approval {
+            match project.manifest().get_plugin(name) {
+                None => self.approval = None,
+                Some(plugin) => {
+                    let mut open = true;
+                    let mut decided = false;
+
+                    egui::Window::new("Plugin permissions")
+                        .collapsible(false)
+                        .resizable(false)
+                        .open(&mut open)
+                        .show(ui.ctx(), |ui| {
+                            ui.label(format!(
+                                "Plugin '{name}' from {} requests permissions",
+                                source(&plugin.dependency)
+                            ));
+                            ui.separator();
+                            show_permissions(plugin, ui);
+                            ui.separator();
+
+                            ui.horizontal(|ui| {
+                                if ui.button("Allow and enable").clicked() {
+                                    data.granted_permissions.insert(name, plugin.permissions);
+                                    data.enabled_plugins.insert(name);
+                                    sync = true;
+                                    decided = true;
+                                }
+                                if ui.button("Cancel").clicked() {
+                                    decided = true;
+                                }
+                            });
+                        });
+
+                    if !open || decided {
+                        self.approval = None;
+                    }
+                }
+            }
+        }
+
         match &mut self.dialog {
             None => {}
             Some(PluginsDialog::FindPlugin(dialog)) => match dialog.show(ui.ctx()).state() {
@@ -428,6 +629,10 @@ impl Plugins {
         if sync {
             try_log_err!(data.sync(&project));
 
+            if reorder && !rebuild {
+                try_log_err!(project.sync());
+            }
+
             if rebuild {
                 try_log_err!(project.sync());
 
@@ -438,17 +643,139 @@ impl Plugins {
             }
 
             if let Some(c) = &self.pending {
-                self.pending = Some(c.with_plugins(&data.enabled_plugins));
+                self.pending = Some(c.with_plugins(&data.enabled_plugins, plugin_order(project)));
             } else if let Some(c) = &linked {
-                self.pending = Some(c.with_plugins(&data.enabled_plugins));
+                self.pending = Some(c.with_plugins(&data.enabled_plugins, plugin_order(project)));
             }
         }
     }
 }
 
+/// Returns plugin names in project order.
+fn plugin_order(project: &Project) -> impl Iterator<Item = Ident> + '_ {
+    project.plugins().iter().map(|p| p.name)
+}
+
+/// Returns true if the plugin requests permissions the user didn't grant yet.
+///
+/// Local plugins are the user's own code, their permissions are granted on enable.
+fn needs_approval(plugin: &Plugin, data: &ProjectData) -> bool {
+    if let Dependency::Path { .. } = plugin.dependency {
+        return false;
+    }
+
+    let granted = data
+        .granted_permissions
+        .get(&plugin.name)
+        .copied()
+        .unwrap_or_default();
+
+    !granted.includes(&plugin.permissions)
+}
+
+/// Passes permissions granted to enabled plugins to engine wrappers.
+fn apply_grants(project: &Project, data: &ProjectData) {
+    sandbox::revoke_all();
+
+    for plugin in project.plugins() {
+        if !data.enabled_plugins.contains(&plugin.name) {
+            continue;
+        }
+        let granted = match plugin.dependency {
+            Dependency::Path { .. } => plugin.permissions,
+            _ => data
+                .granted_permissions
+                .get(&plugin.name)
+                .copied()
+                .unwrap_or_default(),
+        };
+        sandbox::grant(plugin.name, granted);
+    }
+}
+
+fn show_permissions(plugin: &Plugin, ui: &mut Ui) {
+    for permission in plugin.permissions.iter() {
+        ui.horizontal(|ui| {
+            ui.strong(permission.name());
+            ui.label(permission.description());
+        });
+    }
+}
+
+fn source(dependency: &Dependency) -> String {
+    match dependency {
+        Dependency::Crates(version) => format!("crates.io {version}"),
+        Dependency::Git { git, .. } => format!("git {git}"),
+        Dependency::Path { path } => format!("path {path}"),
+    }
+}
+
 /// Adds new plugins library
 fn add_plugin_with_path(path: Utf8PathBuf, project: &mut Project) -> miette::Result<bool> {
     let plugin = Plugin::open_local(path)?;
 
     project.add_plugin(plugin)
 }
+
+/// Polls sources of local plugins for modifications.
+///
+/// Only plugins with path dependency are watched,
+/// released and git plugins do not change under the editor.
+struct SourceWatcher {
+    next_check: Instant,
+
+    /// Latest modification time seen so far.
+    /// Unset before first check.
+    latest: Option<SystemTime>,
+}
+
+impl SourceWatcher {
+    fn new() -> Self {
+        SourceWatcher {
+            next_check: Instant::now(),
+            latest: None,
+        }
+    }
+
+    /// Returns true if any plugin source was modified since last check.
+    fn check(&mut self, project: &Project) -> bool {
+        let now = Instant::now();
+        if now < self.next_check {
+            return false;
+        }
+        self.next_check = now + WATCH_INTERVAL;
+
+        let mut latest = SystemTime::UNIX_EPOCH;
+        for plugin in project.plugins() {
+            if let Dependency::Path { path } = &plugin.dependency {
+                let root = project.root_path().join(path);
+                latest = latest.max(latest_modification(&root.join("Cargo.toml")));
+                latest = latest.max(latest_modification(&root.join("src")));
+            }
+        }
+
+        match self.latest.replace(latest) {
+            None => false,
+            Some(old) => latest > old,
+        }
+    }
+}
+
+/// Returns latest modification time of the file or any file in the directory tree.
+fn latest_modification(path: &Path) -> SystemTime {
+    let Ok(meta) = path.metadata() else {
+        return SystemTime::UNIX_EPOCH;
+    };
+
+    let mut latest = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+
+    if meta.is_dir() {
+        if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.flatten() {
+                latest = latest.max(latest_modification(&entry.path()));
+            }
+        }
+    }
+
+    latest
+}
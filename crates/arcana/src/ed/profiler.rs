@@ -0,0 +1,417 @@
+//! Frame profiler of the running instance.
+//!
+//! Instance measures every system it runs and every render job it executes
+//! and keeps breakdown of recent frames.
+//! Profiler panel shows history of frame times and breakdown of the selected frame,
+//! so plugin that got slower stands out immediately.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use arcana::{
+    plugin::SystemId,
+    work::{JobId, JobTiming},
+    Name,
+};
+use egui::{Color32, Ui};
+use hashbrown::HashMap;
+
+use super::{container::Container, hue_hash, instance::Instance, systems::Category};
+
+/// Number of frames kept in history.
+const HISTORY_LEN: usize = 240;
+
+/// Height of the frame history graph.
+const GRAPH_HEIGHT: f32 = 80.0;
+
+/// Height of a row in the frame breakdown.
+const ROW_HEIGHT: f32 = 18.0;
+
+/// What was measured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Span {
+    /// System in fixed or variable schedule.
+    System(Category, SystemId),
+
+    /// Render job, time spent planning and encoding commands.
+    Job(JobId),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub span: Span,
+
+    /// Offset from the start of the frame.
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+/// Breakdown of a single frame.
+#[derive(Clone, Debug, Default)]
+pub struct FrameProfile {
+    pub samples: Vec<Sample>,
+
+    /// Time from the start of this frame to the start of the next one.
+    pub total: Duration,
+}
+
+impl FrameProfile {
+    /// Returns time spent in fixed systems, variable systems and render jobs.
+    fn split(&self) -> [Duration; 3] {
+        let mut split = [Duration::ZERO; 3];
+        for sample in &self.samples {
+            let idx = match sample.span {
+                Span::System(Category::Fix, _) => 0,
+                Span::System(Category::Var, _) => 1,
+                Span::Job(_) => 2,
+            };
+            split[idx] += sample.duration;
+        }
+        split
+    }
+}
+
+/// Frames recorded by the instance.
+pub struct Profile {
+    frames: VecDeque<FrameProfile>,
+    current: FrameProfile,
+    start: Instant,
+}
+
+impl Profile {
+    pub fn new() -> Self {
+        Profile {
+            frames: VecDeque::with_capacity(HISTORY_LEN),
+            current: FrameProfile::default(),
+            start: Instant::now(),
+        }
+    }
+
+    /// Finishes current frame and starts new one.
+    pub fn begin_frame(&mut self) {
+        let now = Instant::now();
+        let mut frame = std::mem::take(&mut self.current);
+        frame.total = now - self.start;
+        self.start = now;
+
+        if self.frames.len() == HISTORY_LEN {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+
+    /// Records sample that started at `start`.
+    pub fn record(&mut self, span: Span, start: Instant, duration: Duration) {
+        self.current.samples.push(Sample {
+            span,
+            start: start.saturating_duration_since(self.start),
+            duration,
+        });
+    }
+
+    /// Records jobs executed by a work graph that started running at `start`.
+    pub fn record_jobs(&mut self, start: Instant, timings: &[JobTiming]) {
+        let mut offset = start.saturating_duration_since(self.start);
+        for timing in timings {
+            let duration = timing.plan + timing.exec;
+            self.current.samples.push(Sample {
+                span: Span::Job(timing.id),
+                start: offset,
+                duration,
+            });
+            offset += duration;
+        }
+    }
+
+    pub fn frames(&self) -> &VecDeque<FrameProfile> {
+        &self.frames
+    }
+}
+
+pub struct Profiler {
+    systems: HashMap<SystemId, Name>,
+    jobs: HashMap<JobId, Name>,
+
+    /// Frames captured when history was frozen.
+    frozen: Option<VecDeque<FrameProfile>>,
+
+    /// Selected frame counted from the latest.
+    selected: Option<usize>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler {
+            systems: HashMap::new(),
+            jobs: HashMap::new(),
+            frozen: None,
+            selected: None,
+        }
+    }
+
+    pub fn update_plugins(&mut self, container: &Container) {
+        self.systems.clear();
+        self.jobs.clear();
+
+        for (_, plugin) in container.plugins() {
+            for info in plugin.systems() {
+                self.systems.insert(info.id, info.name);
+            }
+            for info in plugin.jobs() {
+                self.jobs.insert(info.id, info.name);
+            }
+        }
+    }
+
+    fn span_name(&self, span: Span) -> String {
+        match span {
+            Span::System(_, id) => match self.systems.get(&id) {
+                Some(name) => name.to_string(),
+                None => format!("System {id}"),
+            },
+            Span::Job(id) => match self.jobs.get(&id) {
+                Some(name) => name.to_string(),
+                None => format!("Job {id}"),
+            },
+        }
+    }
+
+    pub fn show(&mut self, instance: &Instance, ui: &mut Ui) {
+        let mut frozen = self.frozen.is_some();
+
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut frozen, egui_phosphor::regular::PAUSE)
+                .on_hover_text("Freeze history");
+
+            if ui
+                .add_enabled(self.selected.is_some(), egui::Button::new("Latest"))
+                .clicked()
+            {
+                self.selected = None;
+            }
+        });
+
+        match (frozen, self.frozen.is_some()) {
+            (true, false) => self.frozen = Some(instance.profile().frames().clone()),
+            (false, true) => self.frozen = None,
+            _ => {}
+        }
+
+        let frames = match &self.frozen {
+            Some(frames) => frames,
+            None => instance.profile().frames(),
+        };
+
+        if frames.is_empty() {
+            ui.label("No frames recorded");
+            return;
+        }
+
+        if let Some(selected) = self.selected {
+            if selected >= frames.len() {
+                self.selected = None;
+            }
+        }
+
+        let avg = frames.iter().map(|f| f.total).sum::<Duration>() / frames.len() as u32;
+        let max = frames.iter().map(|f| f.total).max().unwrap_or_default();
+
+        ui.label(format!(
+            "Average {:.2} ms, worst {:.2} ms",
+            ms(avg),
+            ms(max)
+        ));
+
+        if let Some(selected) = show_history(frames, self.selected, max, ui) {
+            self.selected = Some(selected);
+        }
+
+        ui.separator();
+
+        let frame = &frames[frames.len() - 1 - self.selected.unwrap_or(0)];
+        self.show_frame(frame, ui);
+    }
+
+    fn show_frame(&self, frame: &FrameProfile, ui: &mut Ui) {
+        let [fix, var, jobs] = frame.split();
+        ui.label(format!(
+            "Frame {:.2} ms: fixed systems {:.2} ms, variable systems {:.2} ms, render jobs {:.2} ms",
+            ms(frame.total),
+            ms(fix),
+            ms(var),
+            ms(jobs)
+        ));
+
+        // Flame of the frame. Each category gets its own row.
+        let width = ui.available_width();
+        let (rect, _) =
+            ui.allocate_exact_size(egui::vec2(width, ROW_HEIGHT * 3.0), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+        let total = frame.total.max(Duration::from_micros(1)).as_secs_f32();
+        let hover = ui.ctx().pointer_hover_pos().filter(|p| rect.contains(*p));
+
+        for sample in &frame.samples {
+            let row = match sample.span {
+                Span::System(Category::Fix, _) => 0,
+                Span::System(Category::Var, _) => 1,
+                Span::Job(_) => 2,
+            };
+
+            let x0 = rect.left() + rect.width() * sample.start.as_secs_f32() / total;
+            let x1 = x0 + (rect.width() * sample.duration.as_secs_f32() / total).max(1.0);
+            let y0 = rect.top() + ROW_HEIGHT * row as f32;
+
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(x0, y0 + 1.0),
+                egui::pos2(x1.min(rect.right()), y0 + ROW_HEIGHT - 1.0),
+            );
+
+            painter.rect_filled(bar, 2.0, hue_hash(&sample.span));
+
+            if bar.width() > 40.0 {
+                painter.text(
+                    bar.left_center() + egui::vec2(2.0, 0.0),
+                    egui::Align2::LEFT_CENTER,
+                    self.span_name(sample.span),
+                    egui::FontId::proportional(ROW_HEIGHT - 6.0),
+                    Color32::BLACK,
+                );
+            }
+
+            if hover.map_or(false, |p| bar.contains(p)) {
+                egui::show_tooltip_at_pointer(ui.ctx(), ui.layer_id(), ui.id(), |ui| {
+                    ui.label(format!(
+                        "{} {:.3} ms",
+                        self.span_name(sample.span),
+                        ms(sample.duration)
+                    ));
+                });
+            }
+        }
+
+        // Table of spans sorted by time spent.
+        let mut spans = HashMap::<Span, Duration>::new();
+        for sample in &frame.samples {
+            *spans.entry(sample.span).or_default() += sample.duration;
+        }
+
+        let mut spans = spans.into_iter().collect::<Vec<_>>();
+        spans.sort_by(|a, b| b.1.cmp(&a.1));
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("profiler-spans")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    ui.strong("Name");
+                    ui.strong("Kind");
+                    ui.strong("ms");
+                    ui.strong("%");
+                    ui.end_row();
+
+                    for (span, duration) in spans {
+                        ui.colored_label(hue_hash(&span), self.span_name(span));
+                        ui.label(match span {
+                            Span::System(Category::Fix, _) => "Fix system",
+                            Span::System(Category::Var, _) => "Var system",
+                            Span::Job(_) => "Render job",
+                        });
+                        ui.label(format!("{:.3}", ms(duration)));
+                        ui.label(format!("{:.1}", 100.0 * duration.as_secs_f32() / total));
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+}
+
+/// Draws bar per frame with time split into categories.
+/// Returns frame clicked by user counted from the latest.
+fn show_history(
+    frames: &VecDeque<FrameProfile>,
+    selected: Option<usize>,
+    max: Duration,
+    ui: &mut Ui,
+) -> Option<usize> {
+    const COLORS: [Color32; 3] = [
+        Color32::from_rgb(90, 160, 230),
+        Color32::from_rgb(110, 200, 120),
+        Color32::from_rgb(230, 150, 70),
+    ];
+
+    let width = ui.available_width();
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(width, GRAPH_HEIGHT), egui::Sense::click());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+    let bar_width = rect.width() / HISTORY_LEN as f32;
+    let max = max.max(Duration::from_micros(1)).as_secs_f32();
+    let selected = selected.unwrap_or(0);
+
+    let bar_at = |x: f32| {
+        let from_right = ((rect.right() - x) / bar_width) as usize;
+        (from_right < frames.len()).then_some(from_right)
+    };
+
+    for (idx, frame) in frames.iter().rev().enumerate() {
+        let x1 = rect.right() - bar_width * idx as f32;
+        let x0 = x1 - bar_width;
+
+        if idx == selected {
+            painter.rect_filled(
+                egui::Rect::from_x_y_ranges(x0..=x1, rect.y_range()),
+                0.0,
+                ui.visuals().selection.bg_fill,
+            );
+        }
+
+        // Time outside measured spans is editor and presentation overhead.
+        let total = rect.height() * frame.total.as_secs_f32() / max;
+        painter.rect_filled(
+            egui::Rect::from_x_y_ranges(x0..=x1, (rect.bottom() - total)..=rect.bottom()),
+            0.0,
+            ui.visuals().weak_text_color(),
+        );
+
+        let mut y = rect.bottom();
+        for (part, color) in frame.split().into_iter().zip(COLORS) {
+            let h = rect.height() * part.as_secs_f32() / max;
+            painter.rect_filled(
+                egui::Rect::from_x_y_ranges(x0..=x1, (y - h)..=y),
+                0.0,
+                color,
+            );
+            y -= h;
+        }
+    }
+
+    let response = response.on_hover_ui_at_pointer(|ui| {
+        let Some(idx) = ui.ctx().pointer_hover_pos().and_then(|pos| bar_at(pos.x)) else {
+            return;
+        };
+
+        let frame = &frames[frames.len() - 1 - idx];
+        let [fix, var, jobs] = frame.split();
+        ui.label(format!("{:.2} ms", ms(frame.total)));
+        ui.colored_label(COLORS[0], format!("Fixed systems {:.2} ms", ms(fix)));
+        ui.colored_label(COLORS[1], format!("Variable systems {:.2} ms", ms(var)));
+        ui.colored_label(COLORS[2], format!("Render jobs {:.2} ms", ms(jobs)));
+    });
+
+    if response.clicked() {
+        return response
+            .interact_pointer_pos()
+            .and_then(|pos| bar_at(pos.x));
+    }
+
+    None
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
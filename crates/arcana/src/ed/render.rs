@@ -1,20 +1,25 @@
-use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc, task::Poll};
 
-use arcana_names::{Ident, Name};
+use arcana_names::{Ident, Name, NameError};
 use edict::entity::EntityId;
-use egui::Ui;
+use egui::{PointerButton, Ui};
 use egui_snarl::{
     ui::{AnyPins, PinInfo, SnarlStyle, SnarlViewer},
     InPin, InPinId, NodeId, OutPin, OutPinId, Snarl,
 };
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 
 use crate::{
+    assets::{AssetId, Assets, ParamSet},
+    hash_id,
     model::Value,
     plugin::{JobInfo, Location},
     project::Project,
     render::RenderGraphId,
-    work::{Edge, HookId, Image2D, JobDesc, JobId, JobIdx, PinId},
+    work::{
+        validate, Cycle, Edge, GraphError, GraphPreset, HookId, Image2D, JobDesc, JobId, JobIdx,
+        PinId, PresetJob,
+    },
     Stid,
 };
 
@@ -39,7 +44,65 @@ pub struct RenderGraph {
 }
 
 impl RenderGraph {
-    pub fn make_work_graph(&self) -> Result<arcana::work::WorkGraph, arcana::work::Cycle> {
+    /// Builds work graph from the render graph.
+    /// Parameter sets bound to jobs override their params if loaded from `assets`.
+    /// Fails if validation finds fatal errors.
+    pub fn make_work_graph(
+        &self,
+        assets: Option<&Assets>,
+    ) -> Result<arcana::work::WorkGraph, Vec<GraphError>> {
+        let (mut jobs, edges) = self.jobs_and_edges();
+
+        if let Some(assets) = assets {
+            for (id, node) in self.snarl.node_ids() {
+                let RenderGraphNode::Job {
+                    param_set: Some(set),
+                    ..
+                } = *node
+                else {
+                    continue;
+                };
+
+                let Some((_, _, params)) = jobs.get_mut(&JobIdx(id.0)) else {
+                    continue;
+                };
+
+                match assets.get::<ParamSet>(set) {
+                    Poll::Ready(Ok(set)) => set.apply(params),
+                    Poll::Ready(Err(err)) => {
+                        tracing::warn!("Failed to load parameter set {set}: {err}");
+                    }
+                    Poll::Pending => {}
+                }
+            }
+        }
+
+        let errors = validate(&jobs, &edges, &self.sinks());
+        if errors.iter().any(GraphError::is_fatal) {
+            return Err(errors);
+        }
+
+        arcana::work::WorkGraph::new(jobs, edges).map_err(|Cycle| errors)
+    }
+
+    /// Validates the graph.
+    /// Errors are grouped by job node.
+    pub fn validate(&self) -> HashMap<JobIdx, Vec<GraphError>> {
+        let (jobs, edges) = self.jobs_and_edges();
+
+        let mut by_job = HashMap::<_, Vec<_>>::new();
+        for err in validate(&jobs, &edges, &self.sinks()) {
+            by_job.entry(err.job()).or_default().push(err);
+        }
+        by_job
+    }
+
+    fn jobs_and_edges(
+        &self,
+    ) -> (
+        HashMap<JobIdx, (JobId, JobDesc, HashMap<Name, Value>)>,
+        HashSet<Edge>,
+    ) {
         let jobs = self
             .snarl
             .node_ids()
@@ -73,7 +136,47 @@ impl RenderGraph {
             })
             .collect();
 
-        arcana::work::WorkGraph::new(jobs, edges)
+        (jobs, edges)
+    }
+
+    /// Returns preset with jobs, connections and parameter values of the graph.
+    /// Bound parameter sets are not applied.
+    pub fn to_preset(&self) -> GraphPreset {
+        let (jobs, edges) = self.jobs_and_edges();
+
+        let mut jobs = jobs
+            .into_iter()
+            .map(|(idx, (job, desc, params))| PresetJob {
+                idx,
+                job,
+                desc,
+                params,
+            })
+            .collect::<Vec<_>>();
+        jobs.sort_by_key(|job| job.idx.0);
+
+        let mut edges = edges.into_iter().collect::<Vec<_>>();
+        edges.sort_by_key(|edge| (edge.from.job.0, edge.from.pin, edge.to.job.0, edge.to.pin));
+
+        GraphPreset {
+            name: self.name,
+            jobs,
+            edges,
+            present: self.get_present(),
+        }
+    }
+
+    /// Returns parameter sets bound to jobs of the graph.
+    pub fn param_sets(&self) -> impl Iterator<Item = AssetId> + '_ {
+        self.snarl.nodes().filter_map(|node| match *node {
+            RenderGraphNode::Job { param_set, .. } => param_set,
+            RenderGraphNode::MainPresent => None,
+        })
+    }
+
+    /// Output pins connected to present node.
+    fn sinks(&self) -> HashSet<PinId> {
+        self.get_present().into_iter().collect()
     }
 
     pub fn get_present(&self) -> Option<PinId> {
@@ -103,6 +206,7 @@ pub struct Rendering {
     preview: Option<Rc<RefCell<Preview>>>,
     render_graph: Option<RenderGraphId>,
     renderer: Option<EntityId>,
+    new_graph_name: String,
 }
 
 impl Rendering {
@@ -112,6 +216,7 @@ impl Rendering {
             preview: None,
             render_graph: None,
             renderer: None,
+            new_graph_name: String::new(),
         }
     }
 
@@ -179,12 +284,85 @@ impl Rendering {
     ) {
         ui.vertical(|ui| {
             ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut self.new_graph_name);
+
+                let r = ui.small_button(egui_phosphor::regular::PLUS);
+                if r.clicked_by(PointerButton::Primary) {
+                    match Name::from_str(&self.new_graph_name) {
+                        Ok(name) => {
+                            self.new_graph_name.clear();
+
+                            let mut snarl = Snarl::new();
+                            snarl.insert_node(
+                                egui::Pos2::new(0.0, 0.0),
+                                RenderGraphNode::MainPresent,
+                            );
+
+                            let id = hash_id!(name);
+                            data.render_graphs.insert(
+                                id,
+                                RenderGraph {
+                                    name,
+                                    snarl,
+                                    modification: 0,
+                                },
+                            );
+                            self.render_graph = Some(id);
+                            try_log_err!(data.sync(&project));
+                        }
+                        Err(NameError::Empty) => {
+                            tracing::error!("Failed to create render graph with empty name");
+                        }
+                        Err(NameError::Bad(c)) => {
+                            tracing::error!(
+                                "Failed to create render graph with name \"{}\". Bad character '{}'",
+                                self.new_graph_name,
+                                c,
+                            );
+                        }
+                    }
+                }
+
                 let selector =
                     Selector::<_, RenderGraph>::new("selected-render-graph", |_, graph| {
                         graph.name.as_str()
                     });
 
                 selector.show(&mut self.render_graph, data.render_graphs.iter(), ui);
+
+                let Some(id) = self.render_graph else {
+                    return;
+                };
+
+                let mut main = data.main_render_graph == Some(id);
+                let r = ui
+                    .toggle_value(&mut main, egui_phosphor::regular::MONITOR)
+                    .on_hover_text("Use for renderer spawned by the editor");
+                if r.changed() {
+                    data.main_render_graph = if main { Some(id) } else { None };
+                    try_log_err!(data.sync(&project));
+                }
+
+                let r = ui
+                    .small_button(egui_phosphor::regular::FLOPPY_DISK)
+                    .on_hover_text("Save as preset asset");
+                if r.clicked() {
+                    if let Some(render_graph) = data.render_graphs.get(&id) {
+                        save_preset(project, render_graph);
+                    }
+                }
+
+                let r = ui
+                    .small_button(egui_phosphor::regular::TRASH)
+                    .on_hover_text("Remove render graph");
+                if r.clicked() {
+                    data.render_graphs.remove(&id);
+                    if data.main_render_graph == Some(id) {
+                        data.main_render_graph = None;
+                    }
+                    self.render_graph = None;
+                    try_log_err!(data.sync(&project));
+                }
             });
 
             let Some(render_graph_id) = self.render_graph else {
@@ -232,8 +410,22 @@ impl Rendering {
                 }
             }
 
+            let Some(render_graph) = data.render_graphs.get_mut(&render_graph_id) else {
+                return;
+            };
+
+            let errors = render_graph.validate();
+            if !errors.is_empty() {
+                let count = errors.values().map(Vec::len).sum::<usize>();
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    format!("{} {count} problem(s) in render graph", egui_phosphor::regular::WARNING),
+                );
+            }
+
             let mut viewer = RenderGraphViewer {
                 modified: false,
+                errors,
                 available: &mut self.available,
                 main,
                 sample: &sample,
@@ -246,8 +438,6 @@ impl Rendering {
                 ..SnarlStyle::new()
             };
 
-            let render_graph = data.render_graphs.get_mut(&render_graph_id).unwrap();
-
             render_graph
                 .snarl
                 .show(&mut viewer, &style, "work-graph", ui);
@@ -260,6 +450,28 @@ impl Rendering {
     }
 }
 
+/// Extension of job graph preset files.
+const PRESET_EXTENSION: &str = "jobgraph";
+
+/// Writes render graph as preset file into project assets,
+/// where it is picked by preset importer.
+fn save_preset(project: &Project, render_graph: &RenderGraph) {
+    let path = project
+        .root_path()
+        .join("Assets")
+        .join(format!("{}.{PRESET_EXTENSION}", render_graph.name));
+
+    match std::fs::write(&path, render_graph.to_preset().to_json()) {
+        Ok(()) => tracing::info!("Saved render graph preset to '{}'", path.display()),
+        Err(err) => {
+            tracing::error!(
+                "Failed to save render graph preset to '{}': {err}",
+                path.display()
+            );
+        }
+    }
+}
+
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub enum RenderGraphNode {
     Job {
@@ -269,6 +481,10 @@ pub enum RenderGraphNode {
         desc: JobDesc,
         params: HashMap<Name, Value>,
 
+        /// Parameter set that overrides params.
+        #[serde(default)]
+        param_set: Option<AssetId>,
+
         #[serde(skip)]
         location: Option<Location>,
 
@@ -280,6 +496,7 @@ pub enum RenderGraphNode {
 
 pub struct RenderGraphViewer<'a> {
     modified: bool,
+    errors: HashMap<JobIdx, Vec<GraphError>>,
     available: &'a mut BTreeMap<Ident, Vec<JobInfo>>,
     main: &'a mut Instance,
     sample: &'a ImageSample,
@@ -311,6 +528,7 @@ impl SnarlViewer<RenderGraphNode> for RenderGraphViewer<'_> {
                 ref name,
                 ref plugin,
                 ref location,
+                ref mut param_set,
                 ..
             } => {
                 ui.vertical(|ui| {
@@ -361,6 +579,19 @@ impl SnarlViewer<RenderGraphNode> for RenderGraphViewer<'_> {
                             self.ide.unwrap().open(loc.file.as_ref(), Some(loc.line));
                         }
                     });
+
+                    if param_set_probe(ui, param_set) {
+                        self.modified = true;
+                    }
+
+                    if let Some(errors) = self.errors.get(&JobIdx(id.0)) {
+                        for err in errors {
+                            ui.colored_label(
+                                ui.visuals().error_fg_color,
+                                format!("{} {err}", egui_phosphor::regular::WARNING),
+                            );
+                        }
+                    }
                 });
             }
             RenderGraphNode::MainPresent => {
@@ -580,6 +811,7 @@ impl SnarlViewer<RenderGraphNode> for RenderGraphViewer<'_> {
                             plugin,
                             desc: job.desc.clone(),
                             params: job.desc.default_params(),
+                            param_set: None,
                             location: job.location.clone(),
                             active: true,
                         },
@@ -656,6 +888,7 @@ impl SnarlViewer<RenderGraphNode> for RenderGraphViewer<'_> {
                             plugin,
                             desc: job.desc.clone(),
                             params: job.desc.default_params(),
+                            param_set: None,
                             location: job.location.clone(),
                             active: true,
                         },
@@ -669,6 +902,38 @@ impl SnarlViewer<RenderGraphNode> for RenderGraphViewer<'_> {
     }
 }
 
+/// Shows parameter set of a job that accepts assets dragged from asset browser.
+/// Returns true if parameter set is changed.
+fn param_set_probe(ui: &mut Ui, param_set: &mut Option<AssetId>) -> bool {
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.weak("Parameters");
+
+        let frame = egui::Frame::group(ui.style()).inner_margin(egui::Margin::symmetric(4.0, 1.0));
+        let (_, payload) = ui.dnd_drop_zone::<AssetId, _>(frame, |ui| match *param_set {
+            Some(id) => {
+                ui.label(format!("{id}"));
+            }
+            None => {
+                ui.weak("Drop parameter set here");
+            }
+        });
+
+        if let Some(id) = payload {
+            *param_set = Some(*id);
+            changed = true;
+        }
+
+        if param_set.is_some() && ui.small_button(egui_phosphor::regular::X).clicked() {
+            *param_set = None;
+            changed = true;
+        }
+    });
+
+    changed
+}
+
 #[inline(always)]
 fn present_kind() -> Stid {
     Stid::of::<Image2D>()
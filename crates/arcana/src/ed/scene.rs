@@ -0,0 +1,101 @@
+//! Scene view of the running instance.
+//!
+//! Shows simulation view with transport controls and gizmos for the selected entity.
+//! Clicking the view picks entities, double click focuses it to pass input to the game.
+
+use arcana::EntityId;
+use egui::Ui;
+use winit::window::WindowId;
+
+use super::{
+    gizmo::Gizmo,
+    instance::{Instance, Simulation},
+    ui::UserTextures,
+};
+
+pub struct SceneView {
+    simulation: Simulation,
+    gizmo: Gizmo,
+}
+
+impl SceneView {
+    pub fn new() -> Self {
+        SceneView {
+            simulation: Simulation::new().with_focus_on_double_click(),
+            gizmo: Gizmo::new(),
+        }
+    }
+
+    pub fn show(
+        &mut self,
+        instance: &mut Instance,
+        selected: &mut Option<EntityId>,
+        window: WindowId,
+        textures: &mut UserTextures,
+        ui: &mut Ui,
+    ) {
+        ui.horizontal(|ui| {
+            show_transport(instance, ui);
+            ui.separator();
+            self.gizmo.show_toolbar(ui);
+        });
+
+        let Some((view, r)) = self.simulation.show(instance, window, textures, ui) else {
+            return;
+        };
+
+        // Focused view receives input as the game does.
+        if instance.is_view_focused(view) {
+            return;
+        }
+
+        if let Some(entity) = *selected {
+            self.gizmo.show(instance, view, entity, r.rect, ui);
+        }
+
+        if r.clicked() && !self.gizmo.interacting() {
+            if let Some(pos) = r.interact_pointer_pos() {
+                *selected = Gizmo::pick(instance, view, r.rect, pos);
+            }
+        }
+    }
+}
+
+/// Shows controls to pause, step and scale time of the instance.
+fn show_transport(instance: &mut Instance, ui: &mut Ui) {
+    let paused = instance.is_paused();
+
+    let r = ui
+        .add_enabled(paused, egui::Button::new(egui_phosphor::regular::PLAY))
+        .on_hover_text("Resume");
+    if r.clicked() {
+        instance.set_paused(false);
+    }
+
+    let r = ui
+        .add_enabled(!paused, egui::Button::new(egui_phosphor::regular::PAUSE))
+        .on_hover_text("Pause");
+    if r.clicked() {
+        instance.set_paused(true);
+    }
+
+    let r = ui
+        .add_enabled(
+            paused,
+            egui::Button::new(egui_phosphor::regular::SKIP_FORWARD),
+        )
+        .on_hover_text("Advance one fixed tick");
+    if r.clicked() {
+        instance.step_once();
+    }
+
+    let mut rate = instance.rate().rate();
+
+    let value = egui::Slider::new(&mut rate, 0.0..=10.0)
+        .clamp_to_range(false)
+        .text("Time scale");
+    let r = ui.add(value);
+    if r.changed() {
+        instance.rate_mut().set_rate(rate as f32);
+    }
+}
@@ -0,0 +1,219 @@
+//! Schedule of systems.
+//!
+//! Shows systems of the system graph in execution order
+//! with their access to reflected components and the world,
+//! and finds systems that conflict without explicit order between them.
+
+use arcana::{
+    edict::query::Access,
+    plugin::SystemId,
+    reflect::{ComponentId, ComponentInfo},
+};
+use egui::{Color32, Ui};
+use hashbrown::HashMap;
+
+use crate::project::Project;
+
+use super::{
+    container::Container,
+    data::ProjectData,
+    hue_hash,
+    instance::Instance,
+    systems::{Category, Systems},
+};
+
+/// Access of a system as reported by the system itself.
+///
+/// Only access to reflected components is known.
+pub struct SystemAccess {
+    /// Access to the whole world.
+    pub world: Option<Access>,
+
+    pub components: Vec<(ComponentId, Access)>,
+}
+
+impl SystemAccess {
+    /// Checks if two systems may not run in arbitrary order.
+    pub fn conflicts(&self, other: &SystemAccess) -> bool {
+        match (self.world, other.world) {
+            (Some(Access::Write), _) | (_, Some(Access::Write)) => return true,
+            (Some(Access::Read), _) if other.writes() => return true,
+            (_, Some(Access::Read)) if self.writes() => return true,
+            _ => {}
+        }
+
+        self.components.iter().any(|&(id, access)| {
+            other.components.iter().any(|&(other_id, other_access)| {
+                id == other_id && (access == Access::Write || other_access == Access::Write)
+            })
+        })
+    }
+
+    fn writes(&self) -> bool {
+        self.components
+            .iter()
+            .any(|&(_, access)| access == Access::Write)
+    }
+}
+
+pub struct ScheduleView {
+    category: Category,
+    components: HashMap<ComponentId, ComponentInfo>,
+}
+
+impl ScheduleView {
+    pub fn new() -> Self {
+        ScheduleView {
+            category: Category::Fix,
+            components: HashMap::new(),
+        }
+    }
+
+    pub fn update_plugins(&mut self, container: &Container) {
+        self.components.clear();
+
+        for (_, plugin) in container.plugins() {
+            for info in plugin.components() {
+                self.components.insert(info.id, info);
+            }
+        }
+    }
+
+    fn component_name(&self, id: ComponentId) -> String {
+        match self.components.get(&id) {
+            Some(info) => info.name.to_string(),
+            None => id.to_string(),
+        }
+    }
+
+    pub fn show(
+        &mut self,
+        project: &Project,
+        data: &mut ProjectData,
+        systems: &mut Systems,
+        instance: &Instance,
+        ui: &mut Ui,
+    ) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.category, Category::Fix, "Fixed rate");
+            ui.selectable_value(&mut self.category, Category::Var, "Variable rate");
+        });
+
+        ui.separator();
+
+        let order = data.systems.order(self.category);
+
+        let access = order
+            .iter()
+            .filter_map(|s| Some((s.system, instance.system_access(s.system)?)))
+            .collect::<HashMap<SystemId, SystemAccess>>();
+
+        let mut pin = None;
+
+        egui::ScrollArea::both().show(ui, |ui| {
+            egui::Grid::new("schedule")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    ui.strong("#");
+                    ui.strong("System");
+                    ui.strong("Access");
+                    ui.strong("Conflicts");
+                    ui.end_row();
+
+                    for (idx, system) in order.iter().enumerate() {
+                        ui.label(idx.to_string());
+
+                        let name = format!("{}@{}", system.name, system.plugin);
+                        if system.runs {
+                            ui.label(name);
+                        } else {
+                            ui.weak(name)
+                                .on_hover_text("System is disabled or not loaded");
+                        }
+
+                        let Some(system_access) = access.get(&system.system) else {
+                            ui.weak("Unknown");
+                            ui.end_row();
+                            continue;
+                        };
+
+                        ui.horizontal_wrapped(|ui| {
+                            match system_access.world {
+                                Some(Access::Write) => {
+                                    ui.colored_label(Color32::RED, "World (write)");
+                                }
+                                Some(Access::Read) => {
+                                    ui.label("World (read)");
+                                }
+                                None => {}
+                            }
+
+                            for &(id, access) in &system_access.components {
+                                let mark = match access {
+                                    Access::Read => "R",
+                                    Access::Write => "W",
+                                };
+                                ui.colored_label(
+                                    hue_hash(&id),
+                                    format!("{mark} {}", self.component_name(id)),
+                                );
+                            }
+                        });
+
+                        ui.vertical(|ui| {
+                            for other in &order {
+                                if other.node == system.node {
+                                    continue;
+                                }
+
+                                let Some(other_access) = access.get(&other.system) else {
+                                    continue;
+                                };
+
+                                if !system_access.conflicts(other_access) {
+                                    continue;
+                                }
+
+                                let before = data.systems.runs_before(system.node, other.node);
+                                let after = data.systems.runs_before(other.node, system.node);
+
+                                ui.horizontal(|ui| {
+                                    if before || after {
+                                        let relation = if before { "before" } else { "after" };
+                                        ui.weak(format!("{relation} {}", other.name));
+                                        return;
+                                    }
+
+                                    ui.colored_label(
+                                        Color32::YELLOW,
+                                        format!(
+                                            "{} {}",
+                                            egui_phosphor::regular::WARNING,
+                                            other.name
+                                        ),
+                                    )
+                                    .on_hover_text("Order between these systems is not defined");
+
+                                    if ui.small_button("Run before").clicked() {
+                                        pin = Some((system.node, other.node));
+                                    }
+                                    if ui.small_button("Run after").clicked() {
+                                        pin = Some((other.node, system.node));
+                                    }
+                                });
+                            }
+                        });
+
+                        ui.end_row();
+                    }
+                });
+        });
+
+        if let Some((before, after)) = pin {
+            data.systems.add_order(before, after);
+            systems.invalidate();
+            try_log_err!(data.sync(&project));
+        }
+    }
+}
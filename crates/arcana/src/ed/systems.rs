@@ -1,6 +1,20 @@
-use std::collections::VecDeque;
+use std::{
+    any::TypeId,
+    collections::VecDeque,
+    ops::Range,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    ptr::NonNull,
+    sync::{Mutex, OnceLock},
+    thread,
+    time::{Duration, Instant},
+};
 
-use edict::{action::ActionBufferSliceExt, world::World};
+use edict::{
+    action::{ActionBuffer, ActionBufferSliceExt},
+    query::Access,
+    system::System,
+    world::World,
+};
 use egui::{Color32, Ui};
 use egui_snarl::{
     ui::{AnyPins, PinInfo, PinShape, SnarlStyle, SnarlViewer},
@@ -10,11 +24,21 @@ use hashbrown::{HashMap, HashSet};
 
 use crate::{
     plugin::{Location, PluginsHub, SystemId},
+    profile,
     project::Project,
     Ident, Name,
 };
 
-use super::{container::Container, data::ProjectData, ide::Ide, toggle_ui};
+use super::{
+    container::Container,
+    data::ProjectData,
+    ide::Ide,
+    profiler::{Profile, Span},
+    toggle_ui,
+};
+
+/// Frequency of fixed updates in hertz unless project specifies other.
+pub const DEFAULT_FIX_RATE: u64 = 20;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub enum Category {
@@ -31,51 +55,423 @@ impl Category {
     }
 }
 
+/// Systems of one category in execution order.
+#[derive(Clone)]
+struct CategorySchedule {
+    systems: Vec<SystemId>,
+
+    /// Indices of earlier systems each system is explicitly ordered after.
+    after: Vec<Vec<usize>>,
+
+    /// Stages of systems that may run in parallel,
+    /// cached for the world layout they were built for.
+    stages: Option<(WorldLayout, Vec<Range<usize>>)>,
+}
+
+impl CategorySchedule {
+    fn new() -> Self {
+        CategorySchedule {
+            systems: Vec::new(),
+            after: Vec::new(),
+            stages: None,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Schedule {
-    fix_schedule: Vec<SystemId>,
-    var_schedule: Vec<SystemId>,
+    fix: CategorySchedule,
+    var: CategorySchedule,
+
+    /// Number of threads that run systems, including the caller.
+    workers: usize,
 }
 
 impl Schedule {
     pub fn new() -> Self {
         Schedule {
-            fix_schedule: Vec::new(),
-            var_schedule: Vec::new(),
+            fix: CategorySchedule::new(),
+            var: CategorySchedule::new(),
+            workers: workers(),
         }
     }
 
     /// Run systems in dependency order.
-    /// Reschedules systems if graph is modified.
-    pub fn run(&self, category: Category, world: &mut World, hub: &mut PluginsHub) {
+    /// Records time each system takes into the profile.
+    ///
+    /// Unless `sequential` is set, consecutive systems that don't conflict
+    /// and aren't ordered between each other run in parallel on worker threads.
+    /// Actions systems defer are executed in schedule order either way.
+    pub fn run(
+        &mut self,
+        category: Category,
+        sequential: bool,
+        world: &mut World,
+        hub: &mut PluginsHub,
+        profile: &mut Profile,
+    ) {
         let schedule = match category {
-            Category::Fix => &*self.fix_schedule,
-            Category::Var => &*self.var_schedule,
+            Category::Fix => &mut self.fix,
+            Category::Var => &mut self.var,
         };
 
-        let mut buffers = Vec::new();
+        if sequential || self.workers < 2 {
+            let mut buffers = Vec::new();
+
+            for id in &schedule.systems {
+                let system = hub.systems.get_mut(id).unwrap();
+                let _span = profile::system(*id);
+                let start = Instant::now();
+                system.run(world, &mut buffers);
+                profile.record(Span::System(category, *id), start, start.elapsed());
+            }
 
-        for id in schedule {
-            let system = hub.systems.get_mut(id).unwrap();
-            system.run(world, &mut buffers);
+            buffers.execute_all(world);
+            return;
         }
 
+        let mut buffers = schedule
+            .systems
+            .iter()
+            .map(|_| Vec::new())
+            .collect::<Vec<_>>();
+
+        let mut next = 0;
+        while next < schedule.systems.len() {
+            // Systems of earlier stages may add types to the world.
+            // Access to types unknown when stages were made is not checked,
+            // so stages are rebuilt before running systems that may access them.
+            let layout = WorldLayout::of(world);
+            if !matches!(&schedule.stages, Some((cached, _)) if *cached == layout) {
+                schedule.stages = Some((layout, make_stages(schedule, world, hub)));
+            }
+            let (_, stages) = schedule.stages.as_ref().unwrap();
+
+            // Rebuilt stage may start before the next system.
+            // Rest of it doesn't conflict either.
+            let end = stages
+                .iter()
+                .find(|stage| stage.contains(&next))
+                .unwrap()
+                .end;
+            let stage = next..end;
+            next = end;
+
+            if stage.len() == 1 {
+                let id = schedule.systems[stage.start];
+                let system = hub.systems.get_mut(&id).unwrap();
+                let _span = profile::system(id);
+                let start = Instant::now();
+                system.run(world, &mut buffers[stage.start]);
+                profile.record(Span::System(category, id), start, start.elapsed());
+                continue;
+            }
+
+            let samples = run_stage(
+                &schedule.systems[stage.clone()],
+                &mut buffers[stage.clone()],
+                self.workers,
+                world,
+                hub,
+            );
+
+            for (id, start, duration) in samples {
+                profile.record(Span::System(category, id), start, duration);
+            }
+        }
+
+        let mut buffers = buffers.into_iter().flatten().collect::<Vec<_>>();
         buffers.execute_all(world);
     }
 }
 
-fn order_systems(snarl: &Snarl<SystemNode>, category: Category) -> Vec<SystemId> {
-    let mut order = Vec::new();
+fn workers() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
 
-    let mut queue = VecDeque::new();
-    let mut scheduled = HashSet::new();
+/// Set of component and resource types known to the world.
+///
+/// Archetypes are never removed, so their count is enough
+/// to notice new component types.
+/// Resources may be removed and others inserted, so their types are compared.
+#[derive(Clone, PartialEq, Eq)]
+struct WorldLayout {
+    archetypes: usize,
+    resources: Vec<TypeId>,
+}
+
+impl WorldLayout {
+    fn of(world: &World) -> Self {
+        let mut resources = world.resource_types().collect::<Vec<_>>();
+        resources.sort_unstable();
 
-    for (idx, node) in snarl.node_ids() {
-        if node.category != category {
-            continue;
+        WorldLayout {
+            archetypes: world.archetypes().len(),
+            resources,
         }
-        queue.push_back(idx);
     }
+}
+
+/// Access of a system to types in the world.
+///
+/// Only types present in the world are checked.
+/// Systems can't access data of types that are not there,
+/// and stages are rebuilt when new types appear.
+struct TypeAccess {
+    /// System must run alone.
+    exclusive: bool,
+
+    /// Access to components and resources.
+    /// Type that is both component and resource is merged,
+    /// which may only add false conflicts.
+    types: Vec<(TypeId, Access)>,
+}
+
+impl TypeAccess {
+    fn of(system: &(dyn System + Send), components: &[TypeId], resources: &[TypeId]) -> Self {
+        let components = components
+            .iter()
+            .filter_map(|&ty| Some((ty, system.access_component(ty)?)));
+
+        let resources = resources
+            .iter()
+            .filter_map(|&ty| Some((ty, system.access_resource(ty)?)));
+
+        TypeAccess {
+            // Local systems must run on the main thread.
+            exclusive: system.is_local() || system.world_access().is_some(),
+            types: components.chain(resources).collect(),
+        }
+    }
+
+    fn conflicts(&self, other: &TypeAccess) -> bool {
+        if self.exclusive || other.exclusive {
+            return true;
+        }
+
+        self.types.iter().any(|&(ty, access)| {
+            other.types.iter().any(|&(other_ty, other_access)| {
+                ty == other_ty && (access == Access::Write || other_access == Access::Write)
+            })
+        })
+    }
+}
+
+/// Splits schedule into stages of consecutive systems
+/// that may run in parallel.
+///
+/// Stages keep the schedule order, so a system is never moved
+/// before a system it runs after sequentially.
+fn make_stages(schedule: &CategorySchedule, world: &World, hub: &PluginsHub) -> Vec<Range<usize>> {
+    let components = world
+        .archetypes()
+        .iter()
+        .flat_map(|archetype| archetype.infos().map(|info| info.id()))
+        .collect::<HashSet<TypeId>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    let resources = world.resource_types().collect::<Vec<TypeId>>();
+
+    let access = schedule
+        .systems
+        .iter()
+        .map(|id| TypeAccess::of(&**hub.systems.get(id).unwrap(), &components, &resources))
+        .collect::<Vec<_>>();
+
+    let mut stages = Vec::new();
+    let mut start = 0;
+
+    for idx in 0..schedule.systems.len() {
+        let joins = (start..idx).all(|other| {
+            schedule.systems[other] != schedule.systems[idx]
+                && !access[other].conflicts(&access[idx])
+        }) && schedule.after[idx].iter().all(|&other| other < start);
+
+        if !joins {
+            stages.push(start..idx);
+            start = idx;
+        }
+    }
+
+    if start < schedule.systems.len() {
+        stages.push(start..schedule.systems.len());
+    }
+
+    stages
+}
+
+/// Pointer to the world shared by systems of a stage.
+#[derive(Clone, Copy)]
+struct WorldPtr(NonNull<World>);
+
+// Systems of one stage only access the world through their declared access,
+// which doesn't conflict.
+unsafe impl Send for WorldPtr {}
+unsafe impl Sync for WorldPtr {}
+
+/// Runs systems of one stage on worker threads.
+/// Calling thread takes systems too.
+///
+/// Returns samples of the systems in schedule order.
+fn run_stage(
+    ids: &[SystemId],
+    buffers: &mut [Vec<ActionBuffer>],
+    workers: usize,
+    world: &mut World,
+    hub: &mut PluginsHub,
+) -> Vec<(SystemId, Instant, Duration)> {
+    assert_eq!(ids.len(), buffers.len());
+
+    // Systems are placed by their position in the stage.
+    // Stage never contains same system twice.
+    let mut systems = ids.iter().map(|_| None).collect::<Vec<_>>();
+    for (id, system) in hub.systems.iter_mut() {
+        if let Some(idx) = ids.iter().position(|s| s == id) {
+            systems[idx] = Some(system);
+        }
+    }
+
+    let tasks = systems
+        .into_iter()
+        .zip(buffers.iter_mut())
+        .enumerate()
+        .map(|(idx, (system, buffers))| {
+            let system = system
+                .unwrap_or_else(|| panic!("Scheduled system {:?} is not registered", ids[idx]));
+            (idx, system, buffers)
+        })
+        .collect::<Vec<_>>();
+
+    let world = WorldPtr(NonNull::from(world));
+    let queue = Mutex::new(tasks.into_iter());
+    let samples = Mutex::new(Vec::with_capacity(ids.len()));
+
+    let work = || loop {
+        let Some((idx, system, buffers)) = queue.lock().unwrap().next() else {
+            break;
+        };
+
+        let _span = profile::system(ids[idx]);
+        let start = Instant::now();
+
+        // Systems in a stage don't conflict
+        // and local systems never share a stage.
+        unsafe {
+            system.run_unchecked(world.0, buffers);
+        }
+
+        samples.lock().unwrap().push((idx, start, start.elapsed()));
+    };
+
+    Workers::get().run(workers.min(ids.len()) - 1, &work);
+
+    let mut samples = samples.into_inner().unwrap();
+    samples.sort_by_key(|&(idx, _, _)| idx);
+    samples
+        .into_iter()
+        .map(|(idx, start, duration)| (ids[idx], start, duration))
+        .collect()
+}
+
+/// Threads that run systems of parallel stages.
+///
+/// Started on first parallel stage and kept for the lifetime of the process,
+/// so stages don't spawn threads every frame.
+struct Workers {
+    senders: Vec<flume::Sender<Task>>,
+}
+
+/// Work of one stage sent to a worker.
+struct Task {
+    work: &'static (dyn Fn() + Sync),
+    done: flume::Sender<thread::Result<()>>,
+}
+
+impl Workers {
+    fn get() -> &'static Workers {
+        static WORKERS: OnceLock<Workers> = OnceLock::new();
+
+        WORKERS.get_or_init(|| {
+            let senders = (1..workers())
+                .map(|idx| {
+                    let (tx, rx) = flume::unbounded::<Task>();
+
+                    thread::Builder::new()
+                        .name(format!("arcana-systems-{idx}"))
+                        .spawn(move || {
+                            for task in rx.iter() {
+                                let result = catch_unwind(AssertUnwindSafe(task.work));
+                                let _ = task.done.send(result);
+                            }
+                        })
+                        .expect("Failed to spawn systems worker thread");
+
+                    tx
+                })
+                .collect();
+
+            Workers { senders }
+        })
+    }
+
+    /// Runs `work` on up to `extra` workers and the calling thread.
+    /// Returns when it returned on all of them.
+    ///
+    /// Panic in any of them is resumed on the calling thread.
+    fn run(&self, extra: usize, work: &(dyn Fn() + Sync)) {
+        let extra = extra.min(self.senders.len());
+        let (done_tx, done_rx) = flume::bounded(extra);
+
+        // Workers don't use `work` after reporting completion
+        // and this function doesn't return before all of them report.
+        let work: &'static (dyn Fn() + Sync) = unsafe { std::mem::transmute(work) };
+
+        for sender in &self.senders[..extra] {
+            sender
+                .send(Task {
+                    work,
+                    done: done_tx.clone(),
+                })
+                .unwrap();
+        }
+
+        let mut panic = catch_unwind(AssertUnwindSafe(work)).err();
+
+        for _ in 0..extra {
+            if let Err(payload) = done_rx.recv().unwrap() {
+                panic.get_or_insert(payload);
+            }
+        }
+
+        if let Some(payload) = panic {
+            resume_unwind(payload);
+        }
+    }
+}
+
+/// Orders all nodes of the category, including disabled ones.
+///
+/// Systems without order constraints between them are ordered by their ids,
+/// so the order depends only on the graph and not on how nodes were added to it.
+/// Same graph yields the same order on every machine,
+/// which deterministic simulations rely on.
+fn order_nodes(snarl: &Snarl<SystemNode>, category: Category) -> Vec<NodeId> {
+    let mut order = Vec::new();
+
+    let mut scheduled = HashSet::new();
+
+    let mut nodes = snarl
+        .node_ids()
+        .filter(|(_, node)| node.category == category)
+        .map(|(idx, node)| (node.system, idx))
+        .collect::<Vec<_>>();
+    nodes.sort_by_key(|&(system, _)| system);
+
+    let mut queue = nodes
+        .into_iter()
+        .map(|(_, idx)| idx)
+        .collect::<VecDeque<_>>();
 
     'outer: while let Some(idx) = queue.pop_front() {
         let in_pin = snarl.in_pin(InPinId {
@@ -90,11 +486,7 @@ fn order_systems(snarl: &Snarl<SystemNode>, category: Category) -> Vec<SystemId>
             }
         }
 
-        let node = &snarl[idx];
-
-        if node.active && node.enabled {
-            order.push(node.system);
-        }
+        order.push(idx);
         scheduled.insert(idx);
     }
 
@@ -118,6 +510,12 @@ impl Systems {
         self.modification
     }
 
+    /// Forces instances to rebuild schedule from the system graph.
+    /// Called when graph is replaced outside of the editor view.
+    pub fn invalidate(&mut self) {
+        self.modification += 1;
+    }
+
     pub fn show(
         &mut self,
         project: &Project,
@@ -127,6 +525,38 @@ impl Systems {
     ) {
         const STYLE: SnarlStyle = SnarlStyle::new();
 
+        ui.horizontal(|ui| {
+            ui.label("Fixed rate");
+
+            let mut rate = data.fix_rate();
+            let r = ui.add(
+                egui::DragValue::new(&mut rate)
+                    .clamp_range(1..=1000)
+                    .suffix(" Hz"),
+            );
+
+            if r.changed() {
+                data.fix_rate = Some(rate);
+            }
+
+            // Saved once editing is finished.
+            if r.drag_stopped() || r.lost_focus() {
+                try_log_err!(data.sync(&project));
+            }
+
+            ui.separator();
+
+            let r = ui
+                .checkbox(&mut data.sequential_systems, "Sequential")
+                .on_hover_text(
+                    "Run systems one by one instead of running non-conflicting systems in parallel",
+                );
+
+            if r.changed() {
+                try_log_err!(data.sync(&project));
+            }
+        });
+
         let mut viewer = SystemViewer {
             modified: false,
             available: &mut self.available,
@@ -167,7 +597,11 @@ impl Systems {
                 name: info.name,
                 plugin,
                 active: true,
-                category: Category::Fix,
+                category: if info.fixed {
+                    Category::Fix
+                } else {
+                    Category::Var
+                },
                 location: info.location,
                 enabled: false,
             })
@@ -195,9 +629,123 @@ impl SystemGraph {
 
     pub fn make_schedule(&self) -> Schedule {
         Schedule {
-            fix_schedule: order_systems(&self.snarl, Category::Fix),
-            var_schedule: order_systems(&self.snarl, Category::Var),
+            fix: self.category_schedule(Category::Fix),
+            var: self.category_schedule(Category::Var),
+            workers: workers(),
+        }
+    }
+
+    fn category_schedule(&self, category: Category) -> CategorySchedule {
+        let nodes = order_nodes(&self.snarl, category)
+            .into_iter()
+            .filter(|&idx| {
+                let node = &self.snarl[idx];
+                node.active && node.enabled
+            })
+            .collect::<Vec<_>>();
+
+        let after = nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, &node)| {
+                (0..idx)
+                    .filter(|&other| self.runs_before(nodes[other], node))
+                    .collect()
+            })
+            .collect();
+
+        CategorySchedule {
+            systems: nodes.iter().map(|&idx| self.snarl[idx].system).collect(),
+            after,
+            stages: None,
+        }
+    }
+}
+
+/// System in the execution order of its category.
+pub struct ScheduledSystem {
+    pub node: NodeId,
+    pub system: SystemId,
+    pub name: Name,
+    pub plugin: Ident,
+
+    /// System is enabled and provided by loaded plugin.
+    pub runs: bool,
+}
+
+impl SystemGraph {
+    /// Returns systems of the category in execution order.
+    pub fn order(&self, category: Category) -> Vec<ScheduledSystem> {
+        order_nodes(&self.snarl, category)
+            .into_iter()
+            .map(|idx| {
+                let node = &self.snarl[idx];
+                ScheduledSystem {
+                    node: idx,
+                    system: node.system,
+                    name: node.name,
+                    plugin: node.plugin,
+                    runs: node.active && node.enabled,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns systems of the plugin added to the graph
+    /// with flags that enable them.
+    pub fn plugin_systems_mut(
+        &mut self,
+        plugin: Ident,
+    ) -> impl Iterator<Item = (Name, &mut bool)> + '_ {
+        self.snarl
+            .nodes_mut()
+            .filter(move |node| node.plugin == plugin)
+            .map(|node| (node.name, &mut node.enabled))
+    }
+
+    /// Checks if system `a` is constrained to run before system `b`.
+    pub fn runs_before(&self, a: NodeId, b: NodeId) -> bool {
+        let mut stack = vec![a];
+        let mut visited = HashSet::new();
+
+        while let Some(idx) = stack.pop() {
+            if !visited.insert(idx) {
+                continue;
+            }
+
+            let out_pin = self.snarl.out_pin(OutPinId {
+                node: idx,
+                output: 0,
+            });
+
+            for remote in out_pin.remotes {
+                if remote.node == b {
+                    return true;
+                }
+                stack.push(remote.node);
+            }
         }
+
+        false
+    }
+
+    /// Adds explicit constraint that system `before` runs before system `after`.
+    pub fn add_order(&mut self, before: NodeId, after: NodeId) {
+        if self.runs_before(after, before) {
+            tracing::error!("Ordering {before:?} before {after:?} would create a cycle");
+            return;
+        }
+
+        self.snarl.connect(
+            OutPinId {
+                node: before,
+                output: 0,
+            },
+            InPinId {
+                node: after,
+                input: 0,
+            },
+        );
     }
 }
 
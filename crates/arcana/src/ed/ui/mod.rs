@@ -70,7 +70,12 @@ pub struct Ui {
     next_id: egui::Id,
     textures: HashMap<egui::TextureId, (mev::Image, Sampler)>,
     textures_delta: egui::TexturesDelta,
-    cursor: egui::CursorIcon,
+    /// Last cursor icon set by the UI.
+    /// `None` if cursor was overridden and needs to be set again.
+    cursor: Option<egui::CursorIcon>,
+
+    /// Cursor is controlled by something else.
+    cursor_override: bool,
     render: Render,
     next_user_texture_id: u64,
 }
@@ -90,7 +95,8 @@ impl Ui {
             textures_delta: egui::TexturesDelta::default(),
             textures: HashMap::new(),
             next_id: egui::Id::new("arcana-0"),
-            cursor: egui::CursorIcon::Default,
+            cursor: None,
+            cursor_override: false,
             render: Render::new(),
             next_user_texture_id: 0,
         }
@@ -165,12 +171,31 @@ impl Ui {
 
         assert_eq!(output.pixels_per_point, viewport.scale_factor);
 
-        handle_platform_output(output.platform_output, window, &mut self.cursor, clipboard);
+        let cursor = match self.cursor_override {
+            true => None,
+            false => Some(&mut self.cursor),
+        };
+
+        handle_platform_output(output.platform_output, window, cursor, clipboard);
 
         self.textures_delta.append(output.textures_delta);
         viewport.shapes = output.shapes;
     }
 
+    /// Stops UI from changing the cursor while override is active.
+    /// Once override is removed UI sets the cursor again.
+    pub fn set_cursor_override(&mut self, cursor_override: bool) {
+        if self.cursor_override && !cursor_override {
+            self.cursor = None;
+        }
+        self.cursor_override = cursor_override;
+    }
+
+    /// Returns true if UI of the viewport should be repainted.
+    pub fn needs_repaint(&self, viewport: &UiViewport) -> bool {
+        self.cx.has_requested_repaint_for(&viewport.id)
+    }
+
     pub fn render(&mut self, viewport: &mut UiViewport, frame: mev::Frame, queue: &mut mev::Queue) {
         let r = self.render.render(
             &self.cx,
@@ -317,17 +342,19 @@ fn map_cursor(icon: egui::CursorIcon) -> Option<cursor_icon::CursorIcon> {
 fn handle_platform_output(
     output: egui::PlatformOutput,
     window: &Window,
-    cursor: &mut egui::CursorIcon,
+    cursor: Option<&mut Option<egui::CursorIcon>>,
     clipboard: &mut Clipboard,
 ) {
-    if *cursor != output.cursor_icon {
-        *cursor = output.cursor_icon;
-
-        match map_cursor(output.cursor_icon) {
-            None => window.set_cursor_visible(false),
-            Some(cursor) => {
-                window.set_cursor_visible(true);
-                window.set_cursor(cursor);
+    if let Some(cursor) = cursor {
+        if *cursor != Some(output.cursor_icon) {
+            *cursor = Some(output.cursor_icon);
+
+            match map_cursor(output.cursor_icon) {
+                None => window.set_cursor_visible(false),
+                Some(cursor) => {
+                    window.set_cursor_visible(true);
+                    window.set_cursor(cursor);
+                }
             }
         }
     }
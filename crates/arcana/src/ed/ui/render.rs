@@ -1,8 +1,8 @@
 use std::mem::{offset_of, size_of_val};
 
 use arcana::{
-    bytemuck,
     mev::{self, Arguments, DeviceRepr},
+    render::StreamBuffer,
 };
 use egui::epaint::Vertex;
 use hashbrown::{hash_map::Entry, HashMap};
@@ -30,8 +30,11 @@ pub struct Render {
     linear_pipeline: Option<mev::RenderPipeline>,
     srgb_pipeline: Option<mev::RenderPipeline>,
 
-    vertex_buffer: Option<mev::Buffer>,
-    index_buffer: Option<mev::Buffer>,
+    /// Vertices and indices of the meshes.
+    stream: StreamBuffer,
+
+    /// Pixels of texture updates.
+    uploads: StreamBuffer,
 }
 
 impl Render {
@@ -41,8 +44,11 @@ impl Render {
             library: None,
             linear_pipeline: None,
             srgb_pipeline: None,
-            vertex_buffer: None,
-            index_buffer: None,
+            stream: StreamBuffer::new(
+                "egui-stream",
+                mev::BufferUsage::VERTEX.union(mev::BufferUsage::INDEX),
+            ),
+            uploads: StreamBuffer::new("egui-uploads", mev::BufferUsage::TRANSFER_SRC),
         }
     }
 
@@ -87,6 +93,9 @@ impl Render {
             }
         };
 
+        self.stream.reset();
+        self.uploads.reset();
+
         let mut encoder = queue.new_command_encoder()?;
 
         encoder.init_image(
@@ -99,43 +108,55 @@ impl Render {
             let mut copy_encoder = encoder.copy();
 
             copy_encoder.barrier(
-                mev::PipelineStages::VERTEX_INPUT | mev::PipelineStages::FRAGMENT_SHADER,
+                mev::PipelineStages::VERTEX_INPUT
+                    | mev::PipelineStages::FRAGMENT_SHADER
+                    | mev::PipelineStages::TRANSFER,
                 mev::PipelineStages::TRANSFER,
             );
 
             if !textures_delta.set.is_empty() {
                 let delta_size = textures_delta.set.iter().fold(0, |acc, (_, delta)| {
-                    acc + match &delta.image {
-                        egui::ImageData::Color(color) => std::mem::size_of_val(&color.pixels[..]),
-                        egui::ImageData::Font(font) => std::mem::size_of_val(&font.pixels[..]),
-                    }
+                    acc + StreamBuffer::ALIGNMENT
+                        + match &delta.image {
+                            egui::ImageData::Color(color) => {
+                                std::mem::size_of_val(&color.pixels[..])
+                            }
+                            egui::ImageData::Font(font) => std::mem::size_of_val(&font.pixels[..]),
+                        }
                 });
 
-                let mut upload_buffer = queue.new_buffer(mev::BufferDesc {
-                    size: delta_size,
-                    usage: mev::BufferUsage::TRANSFER_SRC,
-                    memory: mev::Memory::Upload,
-                    name: "texture-delta-upload",
-                })?;
+                self.uploads.reserve(queue.device(), delta_size)?;
 
-                let mut offset = 0usize;
+                let mut uploads = Vec::with_capacity(textures_delta.set.len());
                 for (_, delta) in textures_delta.set.iter() {
-                    match &delta.image {
-                        egui::ImageData::Color(color) => unsafe {
-                            upload_buffer
-                                .write_unchecked(offset, bytemuck::cast_slice(&color.pixels[..]));
-                            offset += std::mem::size_of_val(&color.pixels[..]);
-                        },
-                        egui::ImageData::Font(font) => unsafe {
-                            upload_buffer
-                                .write_unchecked(offset, bytemuck::cast_slice(&font.pixels[..]));
-                            offset += std::mem::size_of_val(&font.pixels[..]);
-                        },
-                    }
+                    let upload = match &delta.image {
+                        egui::ImageData::Color(color) => {
+                            let upload = self
+                                .uploads
+                                .alloc(queue.device(), size_of_val(&color.pixels[..]))?;
+                            copy_encoder.write_buffer_slice(
+                                upload.buffer.slice(upload.offset..),
+                                &color.pixels[..],
+                            );
+                            upload
+                        }
+                        egui::ImageData::Font(font) => {
+                            let upload = self
+                                .uploads
+                                .alloc(queue.device(), size_of_val(&font.pixels[..]))?;
+                            copy_encoder.write_buffer_slice(
+                                upload.buffer.slice(upload.offset..),
+                                &font.pixels[..],
+                            );
+                            upload
+                        }
+                    };
+                    uploads.push(upload);
                 }
 
-                let mut offset = 0usize;
-                for &(id, ref delta) in &textures_delta.set {
+                copy_encoder.barrier(mev::PipelineStages::TRANSFER, mev::PipelineStages::TRANSFER);
+
+                for (&(id, ref delta), upload) in textures_delta.set.iter().zip(&uploads) {
                     let region = delta.image.size();
                     let pos = delta.pos.unwrap_or([0; 2]);
                     let size = [pos[0] + region[0], pos[1] + region[1]];
@@ -230,8 +251,8 @@ impl Render {
                     }
 
                     copy_encoder.copy_buffer_to_image(
-                        &upload_buffer,
-                        offset,
+                        &upload.buffer,
+                        upload.offset,
                         4 * region[0],
                         0,
                         &image,
@@ -240,15 +261,6 @@ impl Render {
                         0..1,
                         0,
                     );
-
-                    match &delta.image {
-                        egui::ImageData::Color(color) => {
-                            offset += std::mem::size_of_val(&color.pixels[..]);
-                        }
-                        egui::ImageData::Font(font) => {
-                            offset += std::mem::size_of_val(&font.pixels[..]);
-                        }
-                    }
                 }
 
                 textures_delta.set.clear();
@@ -265,65 +277,40 @@ impl Render {
                 let primitives = cx.tessellate(shapes, pixels_per_point);
 
                 if !primitives.is_empty() {
-                    let mut total_vertex_size = 0;
-                    let mut total_index_size = 0;
+                    let mut total_size = 0;
 
                     for primitive in &primitives {
                         match &primitive.primitive {
                             egui::epaint::Primitive::Mesh(mesh) => {
-                                total_vertex_size += size_of_val(&mesh.vertices[..]);
-                                total_vertex_size = (total_vertex_size + 31) & !31;
-                                total_index_size += size_of_val(&mesh.indices[..]);
-                                total_index_size = (total_index_size + 31) & !31;
+                                total_size += size_of_val(&mesh.vertices[..])
+                                    + size_of_val(&mesh.indices[..])
+                                    + 2 * StreamBuffer::ALIGNMENT;
                             }
                             egui::epaint::Primitive::Callback(_) => todo!(),
                         }
                     }
 
-                    let vertex_buffer = match &mut self.vertex_buffer {
-                        Some(buffer) if buffer.size() >= total_vertex_size => buffer,
-                        slot => {
-                            *slot = None;
-                            slot.get_or_insert(queue.new_buffer(mev::BufferDesc {
-                                size: total_vertex_size,
-                                usage: mev::BufferUsage::VERTEX | mev::BufferUsage::TRANSFER_DST,
-                                memory: mev::Memory::Device,
-                                name: "egui-vertex-buffer",
-                            })?)
-                        }
-                    };
-
-                    let index_buffer = match &mut self.index_buffer {
-                        Some(buffer) if buffer.size() >= total_index_size => buffer,
-                        slot => {
-                            *slot = None;
-                            slot.get_or_insert(queue.new_buffer(mev::BufferDesc {
-                                size: total_index_size,
-                                usage: mev::BufferUsage::INDEX | mev::BufferUsage::TRANSFER_DST,
-                                memory: mev::Memory::Device,
-                                name: "egui-index-buffer",
-                            })?)
-                        }
-                    };
-
-                    let mut vertex_buffer_offset = 0;
-                    let mut index_buffer_offset = 0;
+                    self.stream.reserve(queue.device(), total_size)?;
 
+                    let mut meshes = Vec::with_capacity(primitives.len());
                     for primitive in &primitives {
                         match &primitive.primitive {
                             egui::epaint::Primitive::Mesh(mesh) => {
+                                let vertices = self
+                                    .stream
+                                    .alloc(queue.device(), size_of_val(&mesh.vertices[..]))?;
                                 copy_encoder.write_buffer_slice(
-                                    vertex_buffer.slice(vertex_buffer_offset..),
+                                    vertices.buffer.slice(vertices.offset..),
                                     &mesh.vertices[..],
                                 );
+                                let indices = self
+                                    .stream
+                                    .alloc(queue.device(), size_of_val(&mesh.indices[..]))?;
                                 copy_encoder.write_buffer_slice(
-                                    index_buffer.slice(index_buffer_offset..),
+                                    indices.buffer.slice(indices.offset..),
                                     &mesh.indices[..],
                                 );
-                                vertex_buffer_offset += size_of_val(&mesh.vertices[..]);
-                                vertex_buffer_offset = (vertex_buffer_offset + 31) & !31;
-                                index_buffer_offset += size_of_val(&mesh.indices[..]);
-                                index_buffer_offset = (index_buffer_offset + 31) & !31;
+                                meshes.push((vertices, indices));
                             }
                             egui::epaint::Primitive::Callback(_) => todo!(),
                         }
@@ -465,21 +452,9 @@ impl Render {
                         scale: pixels_per_point,
                     });
 
-                    let mut vertex_buffer_offset = 0;
-                    let mut index_buffer_offset = 0;
-
-                    for primitive in primitives {
+                    for (primitive, (vertices, indices)) in primitives.into_iter().zip(&meshes) {
                         match primitive.primitive {
                             egui::epaint::Primitive::Mesh(mesh) => {
-                                macro_rules! next_mesh {
-                                    () => {
-                                        vertex_buffer_offset += size_of_val(&mesh.vertices[..]);
-                                        vertex_buffer_offset = (vertex_buffer_offset + 31) & !31;
-                                        index_buffer_offset += size_of_val(&mesh.indices[..]);
-                                        index_buffer_offset = (index_buffer_offset + 31) & !31;
-                                    };
-                                }
-
                                 let offset = mev::Offset2::new(
                                     ((primitive.clip_rect.left() * pixels_per_point) as i32)
                                         .min(dims.width() as i32)
@@ -508,15 +483,12 @@ impl Render {
 
                                     render.bind_vertex_buffers(
                                         0,
-                                        &[vertex_buffer.slice(vertex_buffer_offset..)],
-                                    );
-                                    render.bind_index_buffer(
-                                        index_buffer.slice(index_buffer_offset..),
+                                        &[vertices.buffer.slice(vertices.offset..)],
                                     );
+                                    render
+                                        .bind_index_buffer(indices.buffer.slice(indices.offset..));
                                     render.draw_indexed(0, 0..mesh.indices.len() as u32, 0..1);
                                 }
-
-                                next_mesh!();
                             }
                             egui::epaint::Primitive::Callback(_) => todo!(),
                         }
@@ -0,0 +1,212 @@
+//! Browser of entities in the running instance.
+//!
+//! Lists entities with sets of reflected components they have,
+//! groups them by component set with live counts
+//! and filters them by components and text.
+
+use arcana::{
+    reflect::{ComponentId, ComponentInfo},
+    EntityId,
+};
+use egui::Ui;
+use hashbrown::{HashMap, HashSet};
+
+use super::{container::Container, hue_hash, instance::Instance};
+
+pub struct WorldBrowser {
+    /// Components registered by plugins.
+    components: HashMap<ComponentId, ComponentInfo>,
+
+    /// Entities must have all of these components.
+    with: HashSet<ComponentId>,
+
+    /// Entities must not have any of these components.
+    without: HashSet<ComponentId>,
+
+    /// Matches entity id or component names.
+    text: String,
+}
+
+impl WorldBrowser {
+    pub fn new() -> Self {
+        WorldBrowser {
+            components: HashMap::new(),
+            with: HashSet::new(),
+            without: HashSet::new(),
+            text: String::new(),
+        }
+    }
+
+    pub fn update_plugins(&mut self, container: &Container) {
+        self.components.clear();
+
+        for (_, plugin) in container.plugins() {
+            for info in plugin.components() {
+                self.components.insert(info.id, info);
+            }
+        }
+
+        self.with.retain(|id| self.components.contains_key(id));
+        self.without.retain(|id| self.components.contains_key(id));
+    }
+
+    fn component_name(&self, id: ComponentId) -> String {
+        match self.components.get(&id) {
+            Some(info) => info.name.to_string(),
+            None => id.to_string(),
+        }
+    }
+
+    fn matches(&self, entity: EntityId, set: &[ComponentId]) -> bool {
+        if !self.with.iter().all(|id| set.contains(id)) {
+            return false;
+        }
+
+        if self.without.iter().any(|id| set.contains(id)) {
+            return false;
+        }
+
+        if self.text.is_empty() {
+            return true;
+        }
+
+        let text = self.text.to_lowercase();
+        entity.to_string().to_lowercase().contains(&text)
+            || set
+                .iter()
+                .any(|&id| self.component_name(id).to_lowercase().contains(&text))
+    }
+
+    pub fn show(&mut self, instance: &Instance, selected: &mut Option<EntityId>, ui: &mut Ui) {
+        let entities = instance
+            .entities()
+            .into_iter()
+            .map(|e| (e, instance.component_set(e)))
+            .collect::<Vec<_>>();
+
+        // Count entities per component set.
+        let mut sets = HashMap::<&[ComponentId], usize>::new();
+        for (_, set) in &entities {
+            *sets.entry(&set[..]).or_default() += 1;
+        }
+
+        let mut sets = sets
+            .into_iter()
+            .map(|(set, count)| {
+                let names = if set.is_empty() {
+                    "No reflected components".to_owned()
+                } else {
+                    set.iter()
+                        .map(|&id| self.component_name(id))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                (set, count, names)
+            })
+            .collect::<Vec<_>>();
+        sets.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.2.cmp(&b.2)));
+
+        egui::TopBottomPanel::top("world-filter").show_inside(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui_phosphor::regular::MAGNIFYING_GLASS);
+                ui.text_edit_singleline(&mut self.text);
+
+                if ui.button("Clear").clicked() {
+                    self.text.clear();
+                    self.with.clear();
+                    self.without.clear();
+                }
+            });
+
+            let mut components = self.components.values().collect::<Vec<_>>();
+            components.sort_by_key(|info| info.name);
+
+            ui.horizontal_wrapped(|ui| {
+                for info in components {
+                    let mut state = if self.with.contains(&info.id) {
+                        Some(true)
+                    } else if self.without.contains(&info.id) {
+                        Some(false)
+                    } else {
+                        None
+                    };
+
+                    let text = match state {
+                        None => egui::RichText::new(info.name.as_str()),
+                        Some(true) => egui::RichText::new(info.name.as_str()).strong(),
+                        Some(false) => egui::RichText::new(info.name.as_str()).strikethrough(),
+                    };
+
+                    let r = ui
+                        .selectable_label(state.is_some(), text)
+                        .on_hover_text("Click to require, click again to exclude");
+
+                    if r.clicked() {
+                        state = match state {
+                            None => Some(true),
+                            Some(true) => Some(false),
+                            Some(false) => None,
+                        };
+
+                        self.with.remove(&info.id);
+                        self.without.remove(&info.id);
+                        match state {
+                            Some(true) => self.with.insert(info.id),
+                            Some(false) => self.without.insert(info.id),
+                            None => false,
+                        };
+                    }
+                }
+            });
+        });
+
+        egui::SidePanel::left("world-sets")
+            .resizable(true)
+            .show_inside(ui, |ui| {
+                ui.label(format!("{} entities", entities.len()));
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (set, count, names) in sets {
+                        let active = !set.is_empty()
+                            && self.without.is_empty()
+                            && self.with.len() == set.len()
+                            && set.iter().all(|id| self.with.contains(id));
+
+                        let r = ui.selectable_label(active, format!("{count} × {names}"));
+                        if r.clicked() {
+                            self.with = set.iter().copied().collect();
+                            self.without.clear();
+                        }
+                    }
+                });
+            });
+
+        let filtered = entities
+            .iter()
+            .filter(|(e, set)| self.matches(*e, set))
+            .collect::<Vec<_>>();
+
+        ui.label(format!("{} of {} entities", filtered.len(), entities.len()));
+
+        egui::ScrollArea::vertical().show_rows(
+            ui,
+            ui.spacing().interact_size.y,
+            filtered.len(),
+            |ui, range| {
+                for &&(entity, ref set) in &filtered[range] {
+                    ui.horizontal(|ui| {
+                        let r = ui.selectable_label(*selected == Some(entity), entity.to_string());
+                        if r.clicked() {
+                            *selected = Some(entity);
+                        }
+
+                        for &id in set {
+                            ui.colored_label(hue_hash(&id), self.component_name(id));
+                        }
+                    });
+                }
+            },
+        );
+    }
+}
@@ -4,10 +4,16 @@
 use std::{
     any::{Any, TypeId},
     collections::VecDeque,
+    marker::PhantomData,
+    ptr::NonNull,
 };
 
 use edict::{
+    action::ActionBufferQueue,
+    archetype::Archetype,
     entity::{Entity, EntityId},
+    query::Access,
+    system::{FnArg, FnArgState, Res, ResMut},
     world::World,
 };
 
@@ -382,10 +388,11 @@ where
         debug_assert!(idx >= self.offset);
         let pos = idx - self.offset;
 
-        debug_assert!(usize::try_from(pos).is_ok());
+        debug_assert!(pos < self.events.len() as u64);
         let pos = pos as usize;
 
-        &self.events[pos]
+        // Recent payloads are in the front of the queue.
+        &self.events[self.events.len() - pos - 1]
     }
 }
 
@@ -437,10 +444,12 @@ impl Events {
         // Keeping only `keep` most recent events.
         // Recent events are in the front of the queue.
 
-        for event in self.events.drain(..keep) {
+        let removed = self.events.len() - keep;
+        for event in self.events.drain(keep..) {
             let storage = self.storages.get_mut(&event.payload_id).unwrap();
-            storage.evict_before(event.payload_idx);
+            storage.evict_before(event.payload_idx + 1);
         }
+        self.offset += removed as u64;
     }
 
     pub fn get(&self, idx: u64) -> Option<Event<&dyn AnyPayload>> {
@@ -534,8 +543,307 @@ impl<'a> Iterator for EventIter<'a> {
     }
 }
 
+/// Typed event channel.
+///
+/// Unlike [`Events`], channel carries structured events of one type
+/// from systems that send them to systems that read them.
+/// Systems send events with [`EventWriter`] and read them with [`EventReader`] parameters.
+/// Code that has the world reads the channel resource with own [`EventCursor`].
+///
+/// Storage is double-buffered.
+/// Events sent during a frame are kept until the end of the next frame,
+/// so every reader that runs at least once per two frames sees every event exactly once,
+/// regardless of order in which writers and readers run.
+///
+/// Channel is created with [`add_event_channel`].
+pub struct EventChannel<T> {
+    /// Events sent during previous frame.
+    old: Vec<T>,
+
+    /// Events sent during current frame.
+    new: Vec<T>,
+
+    /// Sequence number of the first event in `old`.
+    old_start: u64,
+}
+
+impl<T> EventChannel<T> {
+    pub fn new() -> Self {
+        EventChannel {
+            old: Vec::new(),
+            new: Vec::new(),
+            old_start: 0,
+        }
+    }
+
+    pub fn send(&mut self, event: T) {
+        self.new.push(event);
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        self.new.extend(events);
+    }
+
+    /// Sequence number of the next event to be sent.
+    fn end(&self) -> u64 {
+        self.old_start + self.old.len() as u64 + self.new.len() as u64
+    }
+
+    /// Drops events of previous frame.
+    ///
+    /// Called by [`update_event_channels`] at the start of each frame.
+    pub fn update(&mut self) {
+        self.old_start += self.old.len() as u64;
+        std::mem::swap(&mut self.old, &mut self.new);
+        self.new.clear();
+    }
+}
+
+/// Cursor of the reader in [`EventChannel`].
+///
+/// Each reader keeps its own cursor,
+/// so readers don't steal events from each other.
+pub struct EventCursor<T> {
+    next: u64,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for EventCursor<T> {
+    fn default() -> Self {
+        EventCursor::new()
+    }
+}
+
+impl<T> EventCursor<T> {
+    pub const fn new() -> Self {
+        EventCursor {
+            next: 0,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns events sent since last read.
+    pub fn read<'a>(&mut self, channel: &'a EventChannel<T>) -> impl Iterator<Item = &'a T> {
+        if self.next < channel.old_start {
+            tracing::warn!(
+                "Reader of {} missed {} events",
+                std::any::type_name::<T>(),
+                channel.old_start - self.next
+            );
+            self.next = channel.old_start;
+        }
+
+        let skip = (self.next - channel.old_start) as usize;
+        self.next = channel.end();
+
+        channel.old.iter().chain(channel.new.iter()).skip(skip)
+    }
+
+    /// Returns true if there are events reader haven't read yet.
+    pub fn has_events(&self, channel: &EventChannel<T>) -> bool {
+        self.next < channel.end()
+    }
+
+    /// Skips all unread events.
+    pub fn clear(&mut self, channel: &EventChannel<T>) {
+        self.next = channel.end();
+    }
+}
+
+/// System parameter that sends events to [`EventChannel<T>`].
+///
+/// Channel must be created with [`add_event_channel`].
+pub struct EventWriter<'a, T: 'static> {
+    channel: ResMut<'a, EventChannel<T>>,
+}
+
+impl<T> EventWriter<'_, T> {
+    pub fn send(&mut self, event: T) {
+        self.channel.send(event);
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = T>) {
+        self.channel.send_batch(events);
+    }
+}
+
+/// System parameter that reads events from [`EventChannel<T>`].
+///
+/// Keeps cursor in the system, so each system reads every event once.
+/// Channel must be created with [`add_event_channel`].
+pub struct EventReader<'a, T: 'static> {
+    channel: Res<'a, EventChannel<T>>,
+    cursor: &'a mut EventCursor<T>,
+}
+
+impl<T> EventReader<'_, T> {
+    /// Returns events sent since last read.
+    pub fn read(&mut self) -> impl Iterator<Item = &T> {
+        self.cursor.read(&self.channel)
+    }
+
+    /// Returns true if there are events the system haven't read yet.
+    pub fn has_events(&self) -> bool {
+        self.cursor.has_events(&self.channel)
+    }
+
+    /// Skips all unread events.
+    pub fn clear(&mut self) {
+        self.cursor.clear(&self.channel);
+    }
+}
+
+impl<T> FnArg for EventWriter<'_, T>
+where
+    T: Send + 'static,
+{
+    type State = EventWriterState<T>;
+}
+
+/// State of [`EventWriter`] parameter.
+pub struct EventWriterState<T: Send + 'static> {
+    channel: <ResMut<'static, EventChannel<T>> as FnArg>::State,
+}
+
+unsafe impl<T> FnArgState for EventWriterState<T>
+where
+    T: Send + 'static,
+{
+    type Arg<'a> = EventWriter<'a, T>;
+
+    fn new() -> Self {
+        EventWriterState {
+            channel: FnArgState::new(),
+        }
+    }
+
+    fn is_local(&self) -> bool {
+        self.channel.is_local()
+    }
+
+    fn world_access(&self) -> Option<Access> {
+        self.channel.world_access()
+    }
+
+    fn visit_archetype(&self, archetype: &Archetype) -> bool {
+        self.channel.visit_archetype(archetype)
+    }
+
+    fn access_component(&self, id: TypeId) -> Option<Access> {
+        self.channel.access_component(id)
+    }
+
+    fn access_resource(&self, id: TypeId) -> Option<Access> {
+        self.channel.access_resource(id)
+    }
+
+    unsafe fn get_unchecked<'a>(
+        &'a mut self,
+        world: NonNull<World>,
+        queue: &mut dyn ActionBufferQueue,
+    ) -> EventWriter<'a, T> {
+        EventWriter {
+            channel: unsafe { self.channel.get_unchecked(world, queue) },
+        }
+    }
+}
+
+impl<T> FnArg for EventReader<'_, T>
+where
+    T: Sync + 'static,
+{
+    type State = EventReaderState<T>;
+}
+
+/// State of [`EventReader`] parameter.
+/// Keeps the cursor between runs of the system.
+pub struct EventReaderState<T: Sync + 'static> {
+    channel: <Res<'static, EventChannel<T>> as FnArg>::State,
+    cursor: EventCursor<T>,
+}
+
+unsafe impl<T> FnArgState for EventReaderState<T>
+where
+    T: Sync + 'static,
+{
+    type Arg<'a> = EventReader<'a, T>;
+
+    fn new() -> Self {
+        EventReaderState {
+            channel: FnArgState::new(),
+            cursor: EventCursor::new(),
+        }
+    }
+
+    fn is_local(&self) -> bool {
+        self.channel.is_local()
+    }
+
+    fn world_access(&self) -> Option<Access> {
+        self.channel.world_access()
+    }
+
+    fn visit_archetype(&self, archetype: &Archetype) -> bool {
+        self.channel.visit_archetype(archetype)
+    }
+
+    fn access_component(&self, id: TypeId) -> Option<Access> {
+        self.channel.access_component(id)
+    }
+
+    fn access_resource(&self, id: TypeId) -> Option<Access> {
+        self.channel.access_resource(id)
+    }
+
+    unsafe fn get_unchecked<'a>(
+        &'a mut self,
+        world: NonNull<World>,
+        queue: &mut dyn ActionBufferQueue,
+    ) -> EventReader<'a, T> {
+        EventReader {
+            channel: unsafe { self.channel.get_unchecked(world, queue) },
+            cursor: &mut self.cursor,
+        }
+    }
+}
+
+/// Updates of registered event channels.
+struct EventChannels {
+    registered: NoHashMap<TypeId, fn(&World)>,
+}
+
+/// Creates event channel for events of type `T` unless it already exists.
+/// Channel is updated every frame by [`update_event_channels`].
+pub fn add_event_channel<T>(world: &mut World)
+where
+    T: Send + Sync + 'static,
+{
+    if world.get_resource::<EventChannel<T>>().is_none() {
+        world.insert_resource(EventChannel::<T>::new());
+    }
+
+    world
+        .expect_resource_mut::<EventChannels>()
+        .registered
+        .insert(type_id::<T>(), |world| {
+            world.expect_resource_mut::<EventChannel<T>>().update();
+        });
+}
+
+/// Swaps buffers of all event channels.
+/// Called once at the start of each frame by [`begin_frame`](crate::begin_frame).
+pub fn update_event_channels(world: &mut World) {
+    let channels = world.expect_resource::<EventChannels>();
+    for update in channels.registered.values() {
+        update(world);
+    }
+}
+
 pub fn init_events(world: &mut World) {
     world.insert_resource(Events::new());
+    world.insert_resource(EventChannels {
+        registered: no_hash_map(),
+    });
 }
 
 pub fn emit_event<T>(world: &World, event: Event<T>)
@@ -545,3 +853,104 @@ where
     let mut events = world.get_resource_mut::<Events>().unwrap();
     events.emit(event);
 }
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use super::*;
+
+    fn event_id(id: u64) -> EventId {
+        EventId::new(NonZeroU64::new(id).unwrap())
+    }
+
+    #[test]
+    fn test_channel_keeps_events_for_two_frames() {
+        let mut channel = EventChannel::new();
+        let mut early = EventCursor::new();
+        let mut late = EventCursor::new();
+
+        channel.send(1);
+        channel.send(2);
+        assert_eq!(early.read(&channel).copied().collect::<Vec<_>>(), [1, 2]);
+
+        channel.update();
+        channel.send(3);
+        assert_eq!(early.read(&channel).copied().collect::<Vec<_>>(), [3]);
+        assert_eq!(late.read(&channel).copied().collect::<Vec<_>>(), [1, 2, 3]);
+        assert!(!early.has_events(&channel));
+        assert!(!late.has_events(&channel));
+    }
+
+    #[test]
+    fn test_channel_reader_misses_old_events() {
+        let mut channel = EventChannel::new();
+        let mut cursor = EventCursor::new();
+
+        channel.send(1);
+        channel.update();
+        channel.send(2);
+        channel.update();
+        channel.send(3);
+
+        assert!(cursor.has_events(&channel));
+        assert_eq!(cursor.read(&channel).copied().collect::<Vec<_>>(), [2, 3]);
+
+        channel.send(4);
+        cursor.clear(&channel);
+        assert!(!cursor.has_events(&channel));
+        assert_eq!(cursor.read(&channel).count(), 0);
+    }
+
+    #[test]
+    fn test_events_keep_order_and_payloads() {
+        let mut world = World::new();
+        let entity = world.spawn(()).id();
+
+        let mut events = Events::new();
+        events.emit(Event::new(event_id(1), entity).with_payload((10u32,)));
+        events.emit(Event::new(event_id(2), entity).with_payload(("a",)));
+        events.emit(Event::new(event_id(3), entity).with_payload((30u32,)));
+
+        assert_eq!(events.start(), 0);
+        assert_eq!(events.end(), 3);
+
+        let first = events.get(0).unwrap();
+        assert_eq!(first.id, event_id(1));
+        assert_eq!(first.payload.get(0).downcast_ref::<u32>(), Some(&10));
+
+        let ids = events
+            .iter_events(1)
+            .map(|event| event.id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [event_id(2), event_id(3)]);
+
+        let mut next = 2;
+        let last = events.next(&mut next).unwrap();
+        assert_eq!(last.payload.get(0).downcast_ref::<u32>(), Some(&30));
+        assert_eq!(next, 3);
+        assert!(events.next(&mut next).is_none());
+    }
+
+    #[test]
+    fn test_events_evict_oldest() {
+        let mut world = World::new();
+        let entity = world.spawn(()).id();
+
+        let mut events = Events::new();
+        for idx in 0..4u32 {
+            events.emit(Event::new(event_id(1), entity).with_payload((idx,)));
+        }
+
+        events.evict(2);
+        assert_eq!(events.start(), 2);
+        assert_eq!(events.end(), 4);
+        assert!(events.get(1).is_none());
+
+        let payloads = events
+            .iter_events(0)
+            .map(|event| *event.payload.get(0).downcast_ref::<u32>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(payloads, [2, 3]);
+    }
+}
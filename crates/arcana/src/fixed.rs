@@ -0,0 +1,418 @@
+//! Fixed-point numbers for deterministic simulation.
+//!
+//! Floating-point results may differ between platforms and compilers,
+//! which makes simulations diverge when they must stay in lockstep.
+//! [`Fixed`] arithmetic is pure integer math and produces identical
+//! results everywhere.
+
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign},
+};
+
+const FRAC_BITS: u32 = 32;
+
+/// Signed fixed-point number with 32 integer and 32 fractional bits.
+///
+/// Overflow wraps in release and panics in debug, same as integers.
+/// This includes operators, [`Fixed::round`], [`Fixed::ceil`] and [`Fixed::abs`]
+/// when the result does not fit.
+/// Division by zero always panics.
+/// Use `checked_*` and `saturating_*` methods where overflow is expected.
+#[derive(
+    Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(transparent)]
+#[repr(transparent)]
+pub struct Fixed(i64);
+
+impl Fixed {
+    pub const ZERO: Self = Fixed(0);
+    pub const ONE: Self = Fixed(1 << FRAC_BITS);
+    pub const HALF: Self = Fixed(1 << (FRAC_BITS - 1));
+    pub const MIN: Self = Fixed(i64::MIN);
+    pub const MAX: Self = Fixed(i64::MAX);
+
+    /// Smallest positive value.
+    pub const EPSILON: Self = Fixed(1);
+
+    pub const PI: Self = Fixed(13493037705);
+
+    /// Creates number from raw bits.
+    pub const fn from_bits(bits: i64) -> Self {
+        Fixed(bits)
+    }
+
+    /// Returns raw bits of the number.
+    pub const fn to_bits(self) -> i64 {
+        self.0
+    }
+
+    /// Narrows wide intermediate result back to fixed-point,
+    /// panicking on overflow in debug and wrapping in release.
+    const fn from_wide(bits: i128) -> Self {
+        debug_assert!(
+            bits >= i64::MIN as i128 && bits <= i64::MAX as i128,
+            "Fixed arithmetic overflow"
+        );
+        Fixed(bits as i64)
+    }
+
+    pub const fn from_int(value: i32) -> Self {
+        Fixed((value as i64) << FRAC_BITS)
+    }
+
+    /// Converts float to fixed-point.
+    /// Out of range values saturate, NaN becomes zero.
+    ///
+    /// Conversion itself is deterministic, so it is safe to use for constants
+    /// and values loaded from assets.
+    pub fn from_f32(value: f32) -> Self {
+        Self::from_f64(value as f64)
+    }
+
+    /// Converts float to fixed-point.
+    /// Out of range values saturate, NaN becomes zero.
+    pub fn from_f64(value: f64) -> Self {
+        Fixed((value * (1u64 << FRAC_BITS) as f64) as i64)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.to_f64() as f32
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / (1u64 << FRAC_BITS) as f64
+    }
+
+    /// Returns integer part rounded towards negative infinity.
+    pub const fn to_int(self) -> i32 {
+        (self.0 >> FRAC_BITS) as i32
+    }
+
+    pub const fn floor(self) -> Self {
+        Fixed(self.0 & !((1 << FRAC_BITS) - 1))
+    }
+
+    pub const fn ceil(self) -> Self {
+        let bits = self.0 as i128 + ((1 << FRAC_BITS) - 1);
+        Self::from_wide((bits >> FRAC_BITS) << FRAC_BITS)
+    }
+
+    /// Rounds half away from zero.
+    pub const fn round(self) -> Self {
+        if self.0 < 0 {
+            // ceil(x - 0.5) never goes below `Fixed::MIN`.
+            let bits = self.0 as i128 - Self::HALF.0 as i128 + ((1 << FRAC_BITS) - 1);
+            Self::from_wide((bits >> FRAC_BITS) << FRAC_BITS)
+        } else {
+            let bits = self.0 as i128 + Self::HALF.0 as i128;
+            Self::from_wide((bits >> FRAC_BITS) << FRAC_BITS)
+        }
+    }
+
+    /// Returns fractional part, always non-negative.
+    pub const fn fract(self) -> Self {
+        Fixed(self.0 & ((1 << FRAC_BITS) - 1))
+    }
+
+    /// Returns absolute value.
+    /// Overflows for [`Fixed::MIN`], use [`Fixed::checked_abs`] if it may occur.
+    pub const fn abs(self) -> Self {
+        Fixed(self.0.abs())
+    }
+
+    pub const fn checked_abs(self) -> Option<Self> {
+        match self.0.checked_abs() {
+            Some(bits) => Some(Fixed(bits)),
+            None => None,
+        }
+    }
+
+    pub const fn signum(self) -> Self {
+        Fixed::from_int(self.0.signum() as i32)
+    }
+
+    pub const fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    /// Returns square root.
+    /// Negative numbers yield zero.
+    pub const fn sqrt(self) -> Self {
+        if self.0 <= 0 {
+            return Fixed::ZERO;
+        }
+
+        // sqrt(x * 2^32) * 2^16 = sqrt(x) * 2^32
+        let mut value = (self.0 as u128) << FRAC_BITS;
+        let mut result = 0u128;
+        let mut bit = 1u128 << 126;
+
+        while bit > value {
+            bit >>= 2;
+        }
+
+        while bit != 0 {
+            if value >= result + bit {
+                value -= result + bit;
+                result = (result >> 1) + bit;
+            } else {
+                result >>= 1;
+            }
+            bit >>= 2;
+        }
+
+        Fixed(result as i64)
+    }
+
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_add(rhs.0) {
+            Some(bits) => Some(Fixed(bits)),
+            None => None,
+        }
+    }
+
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(bits) => Some(Fixed(bits)),
+            None => None,
+        }
+    }
+
+    pub const fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let bits = (self.0 as i128 * rhs.0 as i128) >> FRAC_BITS;
+        if bits > i64::MAX as i128 || bits < i64::MIN as i128 {
+            None
+        } else {
+            Some(Fixed(bits as i64))
+        }
+    }
+
+    pub const fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.0 == 0 {
+            return None;
+        }
+        let bits = ((self.0 as i128) << FRAC_BITS) / rhs.0 as i128;
+        if bits > i64::MAX as i128 || bits < i64::MIN as i128 {
+            None
+        } else {
+            Some(Fixed(bits as i64))
+        }
+    }
+
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Fixed(self.0.saturating_add(rhs.0))
+    }
+
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Fixed(self.0.saturating_sub(rhs.0))
+    }
+
+    pub const fn saturating_mul(self, rhs: Self) -> Self {
+        match self.checked_mul(rhs) {
+            Some(value) => value,
+            None if (self.0 < 0) != (rhs.0 < 0) => Fixed::MIN,
+            None => Fixed::MAX,
+        }
+    }
+}
+
+impl fmt::Debug for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_f64(), f)
+    }
+}
+
+impl fmt::Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_f64(), f)
+    }
+}
+
+impl From<i32> for Fixed {
+    fn from(value: i32) -> Self {
+        Fixed::from_int(value)
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Fixed(-self.0)
+    }
+}
+
+impl Add for Fixed {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Fixed(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Fixed(self.0 - rhs.0)
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Fixed::from_wide((self.0 as i128 * rhs.0 as i128) >> FRAC_BITS)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self {
+        if rhs.0 == 0 {
+            panic!("attempt to divide Fixed by zero");
+        }
+        Fixed::from_wide(((self.0 as i128) << FRAC_BITS) / rhs.0 as i128)
+    }
+}
+
+impl Rem for Fixed {
+    type Output = Self;
+
+    fn rem(self, rhs: Self) -> Self {
+        Fixed(self.0 % rhs.0)
+    }
+}
+
+impl Mul<i32> for Fixed {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self {
+        Fixed(self.0 * rhs as i64)
+    }
+}
+
+impl Div<i32> for Fixed {
+    type Output = Self;
+
+    fn div(self, rhs: i32) -> Self {
+        Fixed(self.0 / rhs as i64)
+    }
+}
+
+macro_rules! assign_ops {
+    ($($trait:ident::$method:ident => $op:ident::$op_method:ident [$rhs:ty];)*) => {$(
+        impl $trait<$rhs> for Fixed {
+            fn $method(&mut self, rhs: $rhs) {
+                *self = $op::$op_method(*self, rhs);
+            }
+        }
+    )*};
+}
+
+assign_ops! {
+    AddAssign::add_assign => Add::add [Fixed];
+    SubAssign::sub_assign => Sub::sub [Fixed];
+    MulAssign::mul_assign => Mul::mul [Fixed];
+    DivAssign::div_assign => Div::div [Fixed];
+    RemAssign::rem_assign => Rem::rem [Fixed];
+    MulAssign::mul_assign => Mul::mul [i32];
+    DivAssign::div_assign => Div::div [i32];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed(value: f64) -> Fixed {
+        Fixed::from_f64(value)
+    }
+
+    #[test]
+    fn test_mul() {
+        assert_eq!(fixed(1.5) * fixed(2.0), fixed(3.0));
+        assert_eq!(fixed(-1.5) * fixed(2.0), fixed(-3.0));
+        assert_eq!(fixed(-1.5) * fixed(-0.5), fixed(0.75));
+        assert_eq!(Fixed::MAX * Fixed::ONE, Fixed::MAX);
+        assert_eq!(Fixed::MIN * Fixed::ONE, Fixed::MIN);
+        assert_eq!(Fixed::MAX.checked_mul(fixed(2.0)), None);
+        assert_eq!(Fixed::MIN.checked_mul(-Fixed::ONE), None);
+        assert_eq!(Fixed::MAX.saturating_mul(fixed(-2.0)), Fixed::MIN);
+    }
+
+    #[test]
+    fn test_div() {
+        assert_eq!(fixed(3.0) / fixed(2.0), fixed(1.5));
+        assert_eq!(fixed(-3.0) / fixed(2.0), fixed(-1.5));
+        assert_eq!(fixed(-3.0) / fixed(-0.5), fixed(6.0));
+        assert_eq!(Fixed::MIN / Fixed::ONE, Fixed::MIN);
+        assert_eq!(Fixed::ONE.checked_div(Fixed::ZERO), None);
+        assert_eq!(Fixed::MAX.checked_div(Fixed::HALF), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "divide Fixed by zero")]
+    fn test_div_by_zero() {
+        let _ = Fixed::ONE / Fixed::ZERO;
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Fixed arithmetic overflow")]
+    fn test_mul_overflow() {
+        let _ = Fixed::MAX * fixed(2.0);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        assert_eq!(fixed(4.0).sqrt(), fixed(2.0));
+        assert_eq!(fixed(0.25).sqrt(), fixed(0.5));
+        assert_eq!(Fixed::ZERO.sqrt(), Fixed::ZERO);
+        assert_eq!(fixed(-4.0).sqrt(), Fixed::ZERO);
+        assert_eq!(Fixed::MIN.sqrt(), Fixed::ZERO);
+
+        let root = Fixed::MAX.sqrt();
+        assert!(root * root <= Fixed::MAX);
+        assert_eq!(root.to_int(), 46340);
+    }
+
+    #[test]
+    fn test_round() {
+        assert_eq!(fixed(1.4).round(), fixed(1.0));
+        assert_eq!(fixed(1.5).round(), fixed(2.0));
+        assert_eq!(fixed(-1.4).round(), fixed(-1.0));
+        assert_eq!(fixed(-1.5).round(), fixed(-2.0));
+        assert_eq!(fixed(-0.5).round(), fixed(-1.0));
+        assert_eq!(Fixed::MIN.round(), Fixed::MIN);
+        assert_eq!((Fixed::MIN + Fixed::EPSILON).round(), Fixed::MIN);
+        assert_eq!((Fixed::MIN + Fixed::HALF).round(), Fixed::MIN);
+        assert_eq!(
+            (Fixed::MIN + Fixed::HALF + Fixed::EPSILON).round(),
+            Fixed::MIN + Fixed::ONE
+        );
+    }
+
+    #[test]
+    fn test_floor_ceil() {
+        assert_eq!(fixed(1.5).floor(), fixed(1.0));
+        assert_eq!(fixed(-1.5).floor(), fixed(-2.0));
+        assert_eq!(fixed(1.5).ceil(), fixed(2.0));
+        assert_eq!(fixed(-1.5).ceil(), fixed(-1.0));
+        assert_eq!(fixed(-2.0).ceil(), fixed(-2.0));
+        assert_eq!(Fixed::MIN.floor(), Fixed::MIN);
+        assert_eq!(Fixed::MIN.ceil(), Fixed::MIN);
+        assert_eq!(Fixed::MAX.floor(), Fixed::from_int(i32::MAX));
+        assert_eq!(Fixed::from_int(i32::MAX).ceil(), Fixed::from_int(i32::MAX));
+    }
+
+    #[test]
+    fn test_abs() {
+        assert_eq!(fixed(-1.5).abs(), fixed(1.5));
+        assert_eq!(Fixed::MAX.abs(), Fixed::MAX);
+        assert_eq!((Fixed::MIN + Fixed::EPSILON).abs(), Fixed::MAX);
+        assert_eq!(Fixed::MIN.checked_abs(), None);
+    }
+}
@@ -3,43 +3,272 @@
 use std::{
     cmp::Ordering,
     collections::BinaryHeap,
-    task::{Poll, Waker},
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
 };
 
+use arcana_names::Ident;
 pub use edict::flow::{FlowEntity, FlowWorld};
-use gametime::{ClockStep, TimeSpan, TimeStamp};
+use edict::{component::Component, entity::EntityId, world::World};
+pub use futures::{future::FutureExt, select, select_biased};
+use futures::{
+    future::{select as select_fut, Either},
+    pin_mut, Stream,
+};
+use gametime::{TimeSpan, TimeStamp};
+use hashbrown::HashMap;
+use parking_lot::Mutex;
+
+use crate::{
+    clocks::{game_clock, Clocks},
+    NoSuchEntity,
+};
 
-/// Causes flow to sleep for the specified duration.
+/// Causes flow to sleep for the specified duration of gameplay time.
 pub async fn sleep(duration: TimeSpan, world: FlowWorld) {
+    sleep_on(game_clock(), duration, world).await;
+}
+
+/// Causes flow to sleep untile specified gameplay time.
+pub async fn sleep_until(deadline: TimeStamp, world: FlowWorld) {
+    sleep_until_on(game_clock(), deadline, world).await;
+}
+
+/// Causes flow to sleep for the specified duration of the named clock.
+///
+/// Flow that sleeps on the clock unaffected by pause
+/// keeps running while the game is paused.
+pub async fn sleep_on(clock: Ident, duration: TimeSpan, world: FlowWorld) {
     if duration == TimeSpan::ZERO {
         return;
     }
 
-    let now = world.map(|world| world.expect_resource::<ClockStep>().now);
+    let Some(now) = world.map(|world| world.expect_resource::<Clocks>().now(clock)) else {
+        tracing::error!("Flow sleeps on clock {clock} that does not exist");
+        return;
+    };
 
     let deadline = now + duration;
 
-    sleep_until(deadline, world).await;
+    sleep_until_on(clock, deadline, world).await;
 }
 
-/// Causes flow to sleep untile specified time.
-pub async fn sleep_until(deadline: TimeStamp, world: FlowWorld) {
+/// Causes flow to sleep until specified time of the named clock.
+pub async fn sleep_until_on(clock: Ident, deadline: TimeStamp, world: FlowWorld) {
     world
         .poll(|world, cx| {
-            let now = world.expect_resource::<ClockStep>().now;
+            let Some(now) = world.expect_resource::<Clocks>().now(clock) else {
+                tracing::error!("Flow sleeps on clock {clock} that does not exist");
+                return Poll::Ready(());
+            };
 
             if now >= deadline {
                 Poll::Ready(())
             } else {
-                world
-                    .expect_resource_mut::<Timers>()
-                    .add_timer(cx.waker().clone(), deadline);
+                world.expect_resource_mut::<Timers>().add_timer(
+                    clock,
+                    cx.waker().clone(),
+                    deadline,
+                );
                 Poll::Pending
             }
         })
         .await
 }
 
+/// Error returned by [`timeout`] when future did not complete in time.
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+#[error("Flow timed out")]
+pub struct Elapsed;
+
+/// Awaits the future for at most the specified duration of gameplay time.
+pub async fn timeout<F>(duration: TimeSpan, fut: F, world: FlowWorld) -> Result<F::Output, Elapsed>
+where
+    F: Future,
+{
+    let sleep = sleep(duration, world);
+    pin_mut!(fut, sleep);
+
+    match select_fut(fut, sleep).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(((), _)) => Err(Elapsed),
+    }
+}
+
+/// Ticks periodically on a clock.
+///
+/// Ticks are scheduled at fixed period from the creation,
+/// so interval does not drift when flow resumes late.
+/// If flow falls behind, missed ticks are returned immediately one after another.
+pub struct Interval {
+    world: FlowWorld,
+    clock: Ident,
+    period: TimeSpan,
+    next: TimeStamp,
+}
+
+impl Interval {
+    /// Waits for the next tick and returns its scheduled time.
+    pub async fn tick(&mut self) -> TimeStamp {
+        let deadline = self.next;
+        sleep_until_on(self.clock, deadline, self.world).await;
+        self.next = deadline + self.period;
+        deadline
+    }
+
+    /// Converts interval into stream of tick times.
+    pub fn into_stream(self) -> impl Stream<Item = TimeStamp> {
+        futures::stream::unfold(self, |mut interval| async move {
+            let now = interval.tick().await;
+            Some((now, interval))
+        })
+    }
+}
+
+/// Returns interval that ticks every `period` of gameplay time.
+/// First tick happens one period from now.
+pub fn interval(period: TimeSpan, world: FlowWorld) -> Interval {
+    interval_on(game_clock(), period, world)
+}
+
+/// Returns interval that ticks every `period` of the named clock.
+pub fn interval_on(clock: Ident, period: TimeSpan, world: FlowWorld) -> Interval {
+    assert!(period > TimeSpan::ZERO, "Interval period must be positive");
+
+    let now = world.map(|world| world.expect_resource::<Clocks>().now(clock));
+    let Some(now) = now else {
+        panic!("Interval on clock {clock} that does not exist");
+    };
+
+    Interval {
+        world,
+        clock,
+        period,
+        next: now + period,
+    }
+}
+
+struct TokenState {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+/// Token that flows check or await to stop early.
+///
+/// Clones share the state, cancelling one cancels all.
+#[derive(Clone)]
+pub struct CancellationToken {
+    state: Arc<TokenState>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken {
+            state: Arc::new(TokenState {
+                cancelled: AtomicBool::new(false),
+                wakers: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Cancels the token and wakes all flows awaiting it.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, AtomicOrdering::Release);
+
+        for waker in self.state.wakers.lock().drain(..) {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(AtomicOrdering::Acquire)
+    }
+
+    /// Returns future that completes when the token is cancelled.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            token: self.clone(),
+        }
+    }
+
+    /// Awaits the future unless the token is cancelled first.
+    /// Returns `None` if cancelled.
+    pub async fn run<F>(&self, fut: F) -> Option<F::Output>
+    where
+        F: Future,
+    {
+        let cancelled = self.cancelled();
+        pin_mut!(fut, cancelled);
+
+        match select_fut(fut, cancelled).await {
+            Either::Left((output, _)) => Some(output),
+            Either::Right(((), _)) => None,
+        }
+    }
+}
+
+/// Future returned by [`CancellationToken::cancelled`].
+pub struct Cancelled {
+    token: CancellationToken,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        {
+            let mut wakers = self.token.state.wakers.lock();
+            if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+        }
+
+        // Token could be cancelled before waker was registered.
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        Poll::Pending
+    }
+}
+
+/// Component that cancels its token when dropped,
+/// which happens when entity is despawned or the component is removed.
+#[derive(Component)]
+struct DespawnToken(CancellationToken);
+
+impl Drop for DespawnToken {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Returns token that is cancelled when the entity is despawned.
+///
+/// Flows that wait on an entity should run their waits with the token,
+/// so they stop instead of waiting on the dead entity forever.
+pub fn despawn_token(
+    world: &mut World,
+    entity: EntityId,
+) -> Result<CancellationToken, NoSuchEntity> {
+    if let Ok(token) = world.get::<&DespawnToken>(entity) {
+        return Ok(token.0.clone());
+    }
+
+    let token = CancellationToken::new();
+    world.insert(entity, DespawnToken(token.clone()))?;
+    Ok(token)
+}
+
 struct Timer {
     when: TimeStamp,
     waker: Waker,
@@ -82,38 +311,51 @@ impl Ord for Timer {
 }
 
 /// Resource that contains wakers with timers when to wake them.
+/// Each clock has its own timers.
 struct Timers {
-    timers_heap: BinaryHeap<Timer>,
+    timers_heaps: HashMap<Ident, BinaryHeap<Timer>>,
 }
 
 impl Timers {
     fn new() -> Self {
         Timers {
-            timers_heap: BinaryHeap::new(),
+            timers_heaps: HashMap::new(),
         }
     }
 
-    fn add_timer(&mut self, waker: Waker, when: TimeStamp) {
-        self.timers_heap.push(Timer { when, waker });
+    fn add_timer(&mut self, clock: Ident, waker: Waker, when: TimeStamp) {
+        self.timers_heaps
+            .entry(clock)
+            .or_default()
+            .push(Timer { when, waker });
     }
 
-    fn wake_until(&mut self, now: TimeStamp) {
-        while let Some(top) = self.timers_heap.peek() {
-            if top.when > now {
-                break;
+    fn wake_until(&mut self, clocks: &Clocks) {
+        self.timers_heaps.retain(|&clock, timers_heap| {
+            // Flows sleeping on removed clock are woken up
+            // and find out that clock no longer exists.
+            let now = clocks.now(clock);
+
+            while let Some(top) = timers_heap.peek() {
+                if now.map_or(false, |now| top.when > now) {
+                    break;
+                }
+                timers_heap.pop().unwrap().waker.wake();
             }
-            self.timers_heap.pop().unwrap().waker.wake();
-        }
+
+            !timers_heap.is_empty()
+        });
     }
 }
 
 pub fn init_flows(world: &mut edict::world::World) {
     world.insert_resource(Timers::new());
+    world.insert_resource(Clocks::new());
 }
 
 pub fn wake_flows(world: &mut edict::world::World) {
     let mut times = world.expect_resource_mut::<Timers>();
-    let clocks = world.expect_resource::<ClockStep>();
+    let clocks = world.expect_resource::<Clocks>();
 
-    times.wake_until(clocks.now);
+    times.wake_until(&clocks);
 }
@@ -0,0 +1,231 @@
+//! Hooks for editor gizmos.
+//!
+//! Transform and camera components are defined by plugins,
+//! so editor can't move entities or project them into views on its own.
+//! Plugins that define them insert resources from this module into the world
+//! to let editor gizmos manipulate and pick entities.
+//! `scene` plugin provides [`GizmoTransform`] for `Global` and `Local`,
+//! `camera` plugin provides [`GizmoCamera`] for `Camera2`.
+//!
+//! ```ignore
+//! #[arcana::init]
+//! fn init_gizmos(world: &mut World) {
+//!     world.insert_resource(GizmoTransform {
+//!         get: |world, entity| {
+//!             let global = world.get::<Cpy<Global>>(entity).ok()?;
+//!             Some(Transform2::from_isometry(&global.iso))
+//!         },
+//!         set: |world, entity, transform| {
+//!             if let Ok(mut global) = world.get::<&mut Global>(entity) {
+//!                 global.iso = transform.to_isometry();
+//!             }
+//!         },
+//!     });
+//! }
+//! ```
+
+use edict::{entity::EntityId, query::Entities, world::World};
+
+/// Decomposed 2D transform of an entity.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform2 {
+    pub translation: na::Vector2<f32>,
+
+    /// Rotation angle in radians.
+    pub rotation: f32,
+
+    pub scale: na::Vector2<f32>,
+}
+
+impl Transform2 {
+    pub fn identity() -> Self {
+        Transform2 {
+            translation: na::Vector2::zeros(),
+            rotation: 0.0,
+            scale: na::Vector2::new(1.0, 1.0),
+        }
+    }
+
+    pub fn from_isometry(iso: &na::Isometry2<f32>) -> Self {
+        Transform2 {
+            translation: iso.translation.vector,
+            rotation: iso.rotation.angle(),
+            scale: na::Vector2::new(1.0, 1.0),
+        }
+    }
+
+    /// Returns isometry part of the transform, scale is dropped.
+    pub fn to_isometry(&self) -> na::Isometry2<f32> {
+        na::Isometry2::new(self.translation, self.rotation)
+    }
+
+    pub fn to_affine(&self) -> na::Affine2<f32> {
+        let scale = na::Matrix3::new_nonuniform_scaling(&self.scale);
+        na::Affine2::from_matrix_unchecked(self.to_isometry().to_homogeneous() * scale)
+    }
+}
+
+/// Resource that gives gizmos access to entity transforms.
+#[derive(Clone, Copy)]
+pub struct GizmoTransform {
+    /// Returns world space transform of the entity.
+    /// `None` if entity has no transform.
+    pub get: fn(&World, EntityId) -> Option<Transform2>,
+
+    /// Sets world space transform of the entity.
+    pub set: fn(&mut World, EntityId, Transform2),
+}
+
+/// Resource that gives gizmos camera that renders views.
+#[derive(Clone, Copy)]
+pub struct GizmoCamera {
+    /// Returns transform from normalized view coordinates into world space
+    /// for view of `width` × `height` pixels.
+    ///
+    /// Camera entity is the one selected by view's renderer.
+    /// When it is `None` hook should pick camera the same way render jobs do.
+    ///
+    /// Normalized view coordinates span from -1 to 1 with Y axis pointing up.
+    pub view_to_world: fn(&World, Option<EntityId>, u32, u32) -> Option<na::Affine2<f32>>,
+}
+
+/// Resource that lets editor pick entities by clicking in the view.
+///
+/// Plugins with shapes or colliders may provide it for precise picking.
+/// Without it editor picks entity with origin closest to the cursor.
+#[derive(Clone, Copy)]
+pub struct GizmoPicker {
+    /// Returns entity at the world space point.
+    pub pick: fn(&World, na::Point2<f32>) -> Option<EntityId>,
+}
+
+/// Returns entity at the world space point.
+///
+/// Uses [`GizmoPicker`] if plugins provide one,
+/// otherwise picks entity with origin closest to the point within `tolerance`
+/// using [`GizmoTransform`].
+pub fn pick(world: &World, point: na::Point2<f32>, tolerance: f32) -> Option<EntityId> {
+    if let Some(picker) = world.get_resource::<GizmoPicker>().map(|p| *p) {
+        if let Some(entity) = (picker.pick)(world, point) {
+            return Some(entity);
+        }
+    }
+
+    let gizmo = *world.get_resource::<GizmoTransform>()?;
+
+    world
+        .view::<Entities>()
+        .into_iter()
+        .filter_map(|e| {
+            let transform = (gizmo.get)(world, e.id())?;
+            let distance = (transform.translation - point.coords).norm();
+            (distance <= tolerance).then_some((e.id(), distance))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(e, _)| e)
+}
+
+#[cfg(test)]
+mod tests {
+    use edict::{component::Component, query::Cpy};
+
+    use super::*;
+
+    #[derive(Clone, Copy, Component)]
+    struct Position(na::Vector2<f32>);
+
+    fn init_hooks(world: &mut World) {
+        world.insert_resource(GizmoTransform {
+            get: |world, entity| {
+                let position = world.get::<Cpy<Position>>(entity).ok()?;
+                Some(Transform2 {
+                    translation: position.0,
+                    ..Transform2::identity()
+                })
+            },
+            set: |world, entity, transform| {
+                if let Ok(position) = world.get::<&mut Position>(entity) {
+                    position.0 = transform.translation;
+                }
+            },
+        });
+
+        // Camera at (10, 0) with one unit per 10 pixels.
+        world.insert_resource(GizmoCamera {
+            view_to_world: |_, _, width, height| {
+                let scale = na::Matrix3::new_nonuniform_scaling(&na::Vector2::new(
+                    width as f32 / 20.0,
+                    height as f32 / 20.0,
+                ));
+                let translation = na::Translation2::new(10.0, 0.0).to_homogeneous();
+                Some(na::Affine2::from_matrix_unchecked(translation * scale))
+            },
+        });
+    }
+
+    /// Returns normalized view position at which entity is drawn.
+    fn drawn_at(world: &World, entity: EntityId) -> na::Point2<f32> {
+        let camera = *world.get_resource::<GizmoCamera>().unwrap();
+        let view_to_world = (camera.view_to_world)(world, None, 200, 100).unwrap();
+        let transform = (world.get_resource::<GizmoTransform>().unwrap().get)(world, entity);
+        view_to_world
+            .inverse()
+            .transform_point(&na::Point2::from(transform.unwrap().translation))
+    }
+
+    #[test]
+    fn test_click_picks_drawn_entity() {
+        let mut world = World::new();
+        init_hooks(&mut world);
+
+        let a = world.spawn((Position(na::Vector2::new(8.0, 1.0)),)).id();
+        let b = world.spawn((Position(na::Vector2::new(12.0, -2.0)),)).id();
+
+        let camera = *world.get_resource::<GizmoCamera>().unwrap();
+        let view_to_world = (camera.view_to_world)(&world, None, 200, 100).unwrap();
+
+        for entity in [a, b] {
+            let click = view_to_world.transform_point(&drawn_at(&world, entity));
+            assert_eq!(pick(&world, click, 0.5), Some(entity));
+        }
+
+        // Click slightly off the entity is still within tolerance.
+        let click =
+            view_to_world.transform_point(&drawn_at(&world, a)) + na::Vector2::new(0.3, 0.0);
+        assert_eq!(pick(&world, click, 0.5), Some(a));
+
+        // Empty space selects nothing.
+        let click = view_to_world.transform_point(&na::Point2::origin());
+        assert_eq!(pick(&world, click, 0.5), None);
+    }
+
+    #[test]
+    fn test_picker_takes_precedence() {
+        let mut world = World::new();
+        init_hooks(&mut world);
+
+        let a = world.spawn((Position(na::Vector2::new(0.0, 0.0)),)).id();
+        let b = world.spawn((Position(na::Vector2::new(5.0, 0.0)),)).id();
+
+        // Picker with large shapes around entities.
+        world.insert_resource(GizmoPicker {
+            pick: |world, point| {
+                let gizmo = *world.get_resource::<GizmoTransform>()?;
+                world
+                    .view::<Entities>()
+                    .into_iter()
+                    .filter_map(|e| {
+                        let transform = (gizmo.get)(world, e.id())?;
+                        let distance = (transform.translation - point.coords).norm();
+                        (distance <= 4.0).then_some((e.id(), distance))
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(e, _)| e)
+            },
+        });
+
+        assert_eq!(pick(&world, na::Point2::new(3.0, 0.0), 0.5), Some(b));
+        assert_eq!(pick(&world, na::Point2::new(-1.0, 0.0), 0.5), Some(a));
+        assert_eq!(pick(&world, na::Point2::new(100.0, 0.0), 0.5), None);
+    }
+}
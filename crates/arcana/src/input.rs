@@ -1,7 +1,8 @@
 //! OS events handling.
 
-use std::fmt;
+use std::{fmt, future::Future};
 
+use arcana_names::{ident, Ident};
 use blink_alloc::Blink;
 use edict::world::World;
 use winit::event::WindowEvent;
@@ -12,7 +13,11 @@ pub use winit::{
     window::CursorIcon,
 };
 
-use crate::{make_id, viewport::ViewId};
+use crate::{
+    assets::{self, Asset, AssetBuilder, Assets},
+    make_id,
+    viewport::ViewId,
+};
 
 make_id! {
     /// ID of the input filter
@@ -182,14 +187,183 @@ impl TryFrom<&WindowEvent> for ViewInput {
 }
 
 #[derive(Clone)]
-pub enum DeviceInput {}
+pub enum DeviceInput {
+    /// Raw mouse movement.
+    ///
+    /// Unlike `ViewInput::CursorMoved` it is not bound to the viewport
+    /// and continues to be reported when cursor is grabbed.
+    MouseMotion { delta_x: f64, delta_y: f64 },
+}
 
 impl TryFrom<&winit::event::DeviceEvent> for DeviceInput {
     type Error = UnsupportedEvent;
 
     #[inline(always)]
-    fn try_from(_value: &winit::event::DeviceEvent) -> Result<Self, UnsupportedEvent> {
-        Err(UnsupportedEvent)
+    fn try_from(event: &winit::event::DeviceEvent) -> Result<Self, UnsupportedEvent> {
+        match *event {
+            winit::event::DeviceEvent::MouseMotion {
+                delta: (delta_x, delta_y),
+            } => Ok(DeviceInput::MouseMotion { delta_x, delta_y }),
+            _ => Err(UnsupportedEvent),
+        }
+    }
+}
+
+/// Resource that controls whether cursor is grabbed by the game.
+///
+/// Grabbed cursor is hidden and locked in place.
+/// Mouse movement is then reported with `DeviceInput::MouseMotion` events.
+///
+/// Cursor is grabbed only while the viewport is focused.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CursorGrab {
+    grabbed: bool,
+}
+
+impl CursorGrab {
+    pub const fn new() -> Self {
+        CursorGrab { grabbed: false }
+    }
+
+    /// Requests cursor to be grabbed.
+    pub fn grab(&mut self) {
+        self.grabbed = true;
+    }
+
+    /// Releases grabbed cursor.
+    pub fn release(&mut self) {
+        self.grabbed = false;
+    }
+
+    pub fn set(&mut self, grabbed: bool) {
+        self.grabbed = grabbed;
+    }
+
+    pub fn is_grabbed(&self) -> bool {
+        self.grabbed
+    }
+}
+
+/// Image for custom cursor.
+///
+/// Stored as an asset in the form of serialized RGBA8 pixels with hotspot.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct CursorImage {
+    pub width: u16,
+    pub height: u16,
+    pub hotspot_x: u16,
+    pub hotspot_y: u16,
+
+    /// RGBA8 pixels, row by row.
+    pub rgba: Vec<u8>,
+}
+
+impl CursorImage {
+    /// Encodes cursor image into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Cursor image serialization cannot fail")
+    }
+}
+
+impl Asset for CursorImage {
+    type Loaded = CursorImage;
+
+    fn target() -> Ident {
+        ident!(cursor)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<CursorImage, assets::Error>> + Send {
+        futures::future::ready(load_cursor_image(&data))
+    }
+
+    fn build(loaded: CursorImage, _builder: &mut AssetBuilder) -> Result<Self, assets::Error> {
+        Ok(loaded)
+    }
+}
+
+fn load_cursor_image(data: &[u8]) -> Result<CursorImage, assets::Error> {
+    let image: CursorImage = bincode::deserialize(data).map_err(assets::Error::new)?;
+
+    if image.rgba.len() != image.width as usize * image.height as usize * 4 {
+        return Err(assets::Error::msg("Cursor image size mismatch"));
+    }
+
+    if image.hotspot_x >= image.width || image.hotspot_y >= image.height {
+        return Err(assets::Error::msg("Cursor hotspot is out of image bounds"));
+    }
+
+    Ok(image)
+}
+
+/// Shape of the cursor.
+#[derive(Clone)]
+pub enum CursorShape {
+    /// One of the system cursor icons.
+    Icon(CursorIcon),
+
+    /// Custom cursor image.
+    Image(CursorImage),
+}
+
+/// Resource that controls cursor appearance over the game viewport.
+///
+/// Host applies changes to the window when cursor is over the viewport.
+#[derive(Clone)]
+pub struct CursorAppearance {
+    shape: CursorShape,
+    visible: bool,
+    version: u64,
+}
+
+impl CursorAppearance {
+    pub const fn new() -> Self {
+        CursorAppearance {
+            shape: CursorShape::Icon(CursorIcon::Default),
+            visible: true,
+            version: 0,
+        }
+    }
+
+    pub fn set_icon(&mut self, icon: CursorIcon) {
+        self.shape = CursorShape::Icon(icon);
+        self.version += 1;
+    }
+
+    pub fn set_image(&mut self, image: CursorImage) {
+        self.shape = CursorShape::Image(image);
+        self.version += 1;
+    }
+
+    pub fn hide(&mut self) {
+        if self.visible {
+            self.visible = false;
+            self.version += 1;
+        }
+    }
+
+    pub fn show(&mut self) {
+        if !self.visible {
+            self.visible = true;
+            self.version += 1;
+        }
+    }
+
+    pub fn shape(&self) -> &CursorShape {
+        &self.shape
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Version is incremented on each change.
+    /// Host may use it to skip applying unchanged appearance.
+    pub fn version(&self) -> u64 {
+        self.version
     }
 }
 
@@ -357,6 +531,144 @@ impl InputFilter for InputFunnel {
     }
 }
 
+/// Raw input that triggers an action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    /// Returns binding pressed by the input event.
+    pub fn pressed(input: &ViewInput) -> Option<Self> {
+        match *input {
+            ViewInput::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(code),
+                        state: ElementState::Pressed,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => Some(Binding::Key(code)),
+            ViewInput::MouseInput {
+                state: ElementState::Pressed,
+                button,
+                ..
+            } => Some(Binding::Mouse(button)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Binding::Key(code) => write!(f, "{code:?}"),
+            Binding::Mouse(MouseButton::Other(n)) => write!(f, "Mouse {n}"),
+            Binding::Mouse(button) => write!(f, "Mouse {button:?}"),
+        }
+    }
+}
+
+/// Named action with raw inputs bound to it.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ActionBinding {
+    pub name: String,
+
+    #[serde(default)]
+    pub bindings: Vec<Binding>,
+}
+
+/// Set of actions active together.
+/// For example "gameplay" and "menu".
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ActionContext {
+    pub name: String,
+
+    #[serde(default)]
+    pub actions: Vec<ActionBinding>,
+}
+
+impl ActionContext {
+    /// Returns name of the action bound to the input.
+    pub fn action(&self, binding: Binding) -> Option<&str> {
+        self.actions
+            .iter()
+            .find(|a| a.bindings.contains(&binding))
+            .map(|a| a.name.as_str())
+    }
+}
+
+/// Data-driven mapping from raw input to named actions.
+///
+/// Source format is TOML with list of contexts,
+/// each having list of actions with bindings.
+///
+/// ```toml
+/// [[contexts]]
+/// name = "gameplay"
+///
+/// [[contexts.actions]]
+/// name = "jump"
+/// bindings = [{ key = "Space" }, { mouse = "Right" }]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ActionMap {
+    #[serde(default)]
+    pub contexts: Vec<ActionContext>,
+}
+
+impl ActionMap {
+    pub fn from_toml(text: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(text)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).expect("ActionMap serialization cannot fail")
+    }
+
+    /// Encodes action map into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("ActionMap serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, assets::Error> {
+        bincode::deserialize(data).map_err(assets::Error::new)
+    }
+
+    pub fn context(&self, name: &str) -> Option<&ActionContext> {
+        self.contexts.iter().find(|c| c.name == name)
+    }
+
+    /// Returns name of the action bound to the input in the context.
+    pub fn action(&self, context: &str, binding: Binding) -> Option<&str> {
+        self.context(context)?.action(binding)
+    }
+}
+
+impl Asset for ActionMap {
+    type Loaded = ActionMap;
+
+    fn target() -> Ident {
+        ident!(action_map)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<ActionMap, assets::Error>> + Send {
+        futures::future::ready(ActionMap::decode(&data))
+    }
+
+    fn build(loaded: ActionMap, _builder: &mut AssetBuilder) -> Result<Self, assets::Error> {
+        Ok(loaded)
+    }
+}
+
 // fn is_printable_char(chr: char) -> bool {
 //     let is_in_private_use_area = '\u{e000}' <= chr && chr <= '\u{f8ff}'
 //         || '\u{f0000}' <= chr && chr <= '\u{ffffd}'
@@ -79,7 +79,7 @@ extern crate self as arcana;
 // Re-exports
 pub use {
     arcana_names::{ident, name, Ident, IdentError, Name, NameError},
-    arcana_proc::{filter, init, job, stable_hash_tokens, system, with_stid, WithStid},
+    arcana_proc::{filter, importer, init, job, stable_hash_tokens, system, with_stid, WithStid},
     arcana_project as project,
     blink_alloc::{self, Blink, BlinkAlloc},
     bytemuck,
@@ -95,25 +95,42 @@ pub use mev;
 pub mod arena;
 pub mod assets;
 pub mod base58;
+pub mod behavior;
+pub mod clocks;
 pub mod code;
+pub mod color;
+pub mod console;
+pub mod curve;
 pub mod ed;
 pub mod events;
+pub mod fixed;
 pub mod flow;
+pub mod gizmo;
 pub mod hash;
 pub mod id;
 pub mod input;
 pub mod io;
 pub mod model;
 mod num2name;
+pub mod pacing;
 pub mod plugin;
+pub mod profile;
+pub mod random;
+pub mod reflect;
+pub mod relation;
 pub mod render;
+pub mod sandbox;
 pub mod serde_with;
+pub mod settings;
+pub mod stats;
 pub mod stid;
 pub mod tany;
 pub mod task;
 pub mod texture;
 pub mod unfold;
+pub mod vfs;
 pub mod viewport;
+pub mod window;
 pub mod work;
 
 pub use self::{
@@ -128,6 +145,15 @@ pub fn version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
 
+/// Prepares the world for the next frame.
+///
+/// Every host calls it at the start of each tick before running systems,
+/// so that codes get start events and event channels drop events of older frames.
+pub fn begin_frame(world: &mut World) {
+    code::builtin::emit_code_start(world);
+    events::update_event_channels(world);
+}
+
 /// Triggers panic.
 /// Use when too large capacity is requested.
 #[inline(never)]
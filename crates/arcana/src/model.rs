@@ -10,7 +10,12 @@ use edict::entity::EntityId;
 use hashbrown::HashMap;
 use palette::IntoColor;
 
-use crate::{base58, Stid};
+use crate::{
+    assets::AssetId,
+    base58,
+    curve::{Curve, Interpolation},
+    name, Stid,
+};
 
 #[derive(
     Copy, Clone, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
@@ -93,11 +98,24 @@ pub enum Model {
     /// Record with named fields.
     Record(Vec<(Name, Option<Model>)>),
 
+    /// Named structure with named fields.
+    /// Values are maps, same as for records.
+    Struct {
+        name: Name,
+        fields: Vec<(Name, Option<Model>)>,
+    },
+
     /// Enum with named variants.
     Enum(Vec<(Name, Option<Model>)>),
 
     /// Opaque type not representable in model.
     Opaque(Stid),
+
+    /// Reference to an asset.
+    Asset,
+
+    /// Keyframed scalar curve.
+    Curve,
 }
 
 /// Returns default value that corresponds to the model or `Unit` if model is not specified.
@@ -109,6 +127,33 @@ pub fn default_value(model: Option<&Model>) -> Value {
 }
 
 impl Model {
+    pub fn kind(&self) -> &str {
+        match self {
+            Model::Unit => "Unit",
+            Model::Bool => "Bool",
+            Model::Int => "Int",
+            Model::Float => "Float",
+            Model::String => "String",
+            Model::Color(_) => "Color",
+            Model::Vec2 => "Vec2",
+            Model::Vec3 => "Vec3",
+            Model::Vec4 => "Vec4",
+            Model::Mat2 => "Mat2",
+            Model::Mat3 => "Mat3",
+            Model::Mat4 => "Mat4",
+            Model::Option(_) => "Option",
+            Model::Array { .. } => "Array",
+            Model::Map(_) => "Map",
+            Model::Tuple(_) => "Tuple",
+            Model::Record(_) => "Record",
+            Model::Struct { name, .. } => name.as_str(),
+            Model::Enum(_) => "Enum",
+            Model::Opaque(_) => "Opaque",
+            Model::Asset => "Asset",
+            Model::Curve => "Curve",
+        }
+    }
+
     /// Returns default value that corresponds to the model.
     pub fn default_value(&self) -> Value {
         match *self {
@@ -150,7 +195,7 @@ impl Model {
             Model::Tuple(ref fields) => {
                 Value::Array(fields.iter().map(|f| default_value(f.as_ref())).collect())
             }
-            Model::Record(ref fields) => Value::Map(
+            Model::Record(ref fields) | Model::Struct { ref fields, .. } => Value::Map(
                 fields
                     .iter()
                     .map(|(k, v)| (k.to_string(), default_value(v.as_ref())))
@@ -162,8 +207,229 @@ impl Model {
                 Value::Enum(v.0, Box::new(default_value(v.1.as_ref())))
             }
             Model::Opaque(_) => Value::Unit,
+            // There is no meaningful default asset.
+            Model::Asset => Value::Unit,
+            Model::Curve => Value::Curve(Curve::default()),
         }
     }
+
+    /// Converts value of `old` model to this model.
+    ///
+    /// Fields are matched by name, a single removed field with the same model
+    /// as a single added one is considered renamed.
+    /// Added fields and values that can't be converted get defaults.
+    /// Changes are recorded into `migration`.
+    pub fn migrate(&self, old: &Model, value: Value, migration: &mut Migration) -> Value {
+        self.migrate_at("", old, value, migration)
+    }
+
+    fn migrate_at(
+        &self,
+        path: &str,
+        old: &Model,
+        value: Value,
+        migration: &mut Migration,
+    ) -> Value {
+        if self == old {
+            return value;
+        }
+
+        match (old, self, value) {
+            (
+                Model::Record(old_fields)
+                | Model::Struct {
+                    fields: old_fields, ..
+                },
+                Model::Record(new_fields)
+                | Model::Struct {
+                    fields: new_fields, ..
+                },
+                Value::Map(mut values),
+            ) => {
+                let removed = old_fields
+                    .iter()
+                    .filter(|(name, _)| new_fields.iter().all(|(n, _)| n != name))
+                    .collect::<Vec<_>>();
+                let added = new_fields
+                    .iter()
+                    .filter(|(name, _)| old_fields.iter().all(|(n, _)| n != name))
+                    .collect::<Vec<_>>();
+
+                let mut migrated = HashMap::new();
+                let mut renamed_from = Vec::new();
+
+                for (name, model) in new_fields {
+                    let at = field_path(path, name.as_str());
+
+                    let old_field = match old_fields.iter().find(|(n, _)| n == name) {
+                        Some((_, old_model)) => Some((*name, old_model)),
+                        None => {
+                            let same = |(_, m): &&&(Name, Option<Model>)| m == model;
+                            let candidates = removed.iter().filter(same).count();
+                            let targets = added.iter().filter(same).count();
+
+                            match removed.iter().find(same) {
+                                Some((old_name, old_model)) if candidates == 1 && targets == 1 => {
+                                    migration
+                                        .renamed
+                                        .push((field_path(path, old_name.as_str()), at.clone()));
+                                    renamed_from.push(*old_name);
+                                    Some((*old_name, old_model))
+                                }
+                                _ => None,
+                            }
+                        }
+                    };
+
+                    let value = match old_field.and_then(|(old_name, old_model)| {
+                        Some((values.remove(old_name.as_str())?, old_model))
+                    }) {
+                        None => {
+                            migration.added.push(at);
+                            default_value(model.as_ref())
+                        }
+                        Some((value, old_model)) => match (model, old_model) {
+                            (Some(model), Some(old_model)) => {
+                                model.migrate_at(&at, old_model, value, migration)
+                            }
+                            _ => value,
+                        },
+                    };
+
+                    migrated.insert(name.to_string(), value);
+                }
+
+                for (name, _) in removed {
+                    if !renamed_from.contains(name) {
+                        migration.removed.push(field_path(path, name.as_str()));
+                    }
+                }
+
+                Value::Map(migrated)
+            }
+            (Model::Tuple(old_fields), Model::Tuple(new_fields), Value::Array(values)) => {
+                let mut values = values.into_iter();
+                let migrated = new_fields
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, model)| {
+                        let at = field_path(path, &idx.to_string());
+                        match (values.next(), old_fields.get(idx)) {
+                            (Some(value), Some(old_model)) => match (model, old_model) {
+                                (Some(model), Some(old_model)) => {
+                                    model.migrate_at(&at, old_model, value, migration)
+                                }
+                                _ => value,
+                            },
+                            _ => {
+                                migration.added.push(at);
+                                default_value(model.as_ref())
+                            }
+                        }
+                    })
+                    .collect();
+
+                for idx in new_fields.len()..old_fields.len() {
+                    migration.removed.push(field_path(path, &idx.to_string()));
+                }
+
+                Value::Array(migrated)
+            }
+            (Model::Option(Some(old_model)), Model::Option(Some(model)), Value::Option(value)) => {
+                Value::Option(
+                    value.map(|value| {
+                        Box::new(model.migrate_at(path, old_model, *value, migration))
+                    }),
+                )
+            }
+            (
+                Model::Array {
+                    elem: Some(old_elem),
+                    ..
+                },
+                Model::Array { elem, len },
+                Value::Array(values),
+            ) => {
+                let mut values = match elem {
+                    Some(elem) => values
+                        .into_iter()
+                        .map(|value| elem.migrate_at(path, old_elem, value, migration))
+                        .collect(),
+                    None => values,
+                };
+                if let Some(len) = *len {
+                    values.resize_with(len, || default_value(elem.as_deref()));
+                }
+                Value::Array(values)
+            }
+            (Model::Map(Some(old_model)), Model::Map(Some(model)), Value::Map(values)) => {
+                Value::Map(
+                    values
+                        .into_iter()
+                        .map(|(key, value)| {
+                            let value = model.migrate_at(
+                                &field_path(path, &key),
+                                old_model,
+                                value,
+                                migration,
+                            );
+                            (key, value)
+                        })
+                        .collect(),
+                )
+            }
+            (Model::Int, Model::Float, Value::Int(value)) => Value::Float(value as f64),
+            (Model::Float, Model::Int, Value::Float(value)) => Value::Int(value.round() as i64),
+            (old, Model::Option(Some(model)), value) if !matches!(old, Model::Option(_)) => {
+                Value::Option(Some(Box::new(
+                    model.migrate_at(path, old, value, migration),
+                )))
+            }
+            _ => {
+                migration.reset.push(path.to_owned());
+                self.default_value()
+            }
+        }
+    }
+}
+
+/// Changes made to a value by [`Model::migrate`].
+///
+/// Fields are identified by paths of names joined with dots.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Migration {
+    /// Fields matched by model under a new name, as `(old, new)`.
+    pub renamed: Vec<(String, String)>,
+
+    /// Fields set to defaults.
+    pub added: Vec<String>,
+
+    /// Fields that no longer exist.
+    pub removed: Vec<String>,
+
+    /// Fields that changed model incompatibly and were reset to defaults.
+    pub reset: Vec<String>,
+}
+
+impl Migration {
+    pub fn new() -> Self {
+        Migration::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.renamed.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.reset.is_empty()
+    }
+}
+
+fn field_path(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_owned()
+    } else {
+        format!("{path}.{field}")
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -276,6 +542,8 @@ pub enum Value {
     Array(Vec<Value>),
     Map(HashMap<String, Value>),
     Enum(Name, Box<Value>),
+    Asset(AssetId),
+    Curve(Curve),
 }
 
 impl Default for Value {
@@ -310,6 +578,8 @@ impl Value {
             Value::Array(_) => "Array",
             Value::Map(_) => "Map",
             Value::Enum(_, _) => "Enum",
+            Value::Asset(_) => "Asset",
+            Value::Curve(_) => "Curve",
         }
     }
 }
@@ -598,6 +868,30 @@ impl<'de> serde::de::Deserializer<'de> for Value {
                 visitor.visit_map(serde::de::value::MapDeserializer::new(map.into_iter()))
             }
             Value::Enum(name, value) => visitor.visit_enum(Variant { name, value: value }),
+            Value::Asset(id) => visitor.visit_u64(id.0.get()),
+            Value::Curve(curve) => {
+                let keys = curve
+                    .keys()
+                    .iter()
+                    .map(|key| {
+                        let interp = match key.interp {
+                            Interpolation::Constant => name!(Constant),
+                            Interpolation::Linear => name!(Linear),
+                            Interpolation::Smooth => name!(Smooth),
+                        };
+
+                        Value::Array(vec![
+                            Value::Float(key.time as f64),
+                            Value::Float(key.value as f64),
+                            Value::Enum(interp, Box::new(Value::Unit)),
+                        ])
+                    })
+                    .collect();
+
+                visitor.visit_map(serde::de::value::MapDeserializer::new(
+                    [("keys", Value::Array(keys))].into_iter(),
+                ))
+            }
         }
     }
 
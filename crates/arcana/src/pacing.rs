@@ -0,0 +1,106 @@
+//! Frame pacing.
+//!
+//! Games insert or change [`FramePacing`] resource to cap frame rate.
+//! Host loops use [`FrameLimiter`] to wait precisely for the next frame.
+//!
+//! Pacing only controls when CPU starts frames.
+//! Waiting for presentation (e.g. `VK_KHR_present_wait`) to limit
+//! frames queued on GPU is not supported, as renderer doesn't expose it.
+
+use std::time::{Duration, Instant};
+
+use edict::world::World;
+
+/// Default frame rate cap of variable updates.
+pub const DEFAULT_MAX_FPS: u32 = 120;
+
+/// Resource that controls frame pacing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FramePacing {
+    /// Maximum frames per second.
+    /// `None` leaves rate to the host, e.g. to vsync.
+    pub max_fps: Option<u32>,
+
+    /// Part of the wait before frame that is spent spinning instead of sleeping.
+    /// Sleep is imprecise on most platforms,
+    /// spinning for last millisecond or two makes frame times stable.
+    pub spin: Duration,
+}
+
+impl Default for FramePacing {
+    fn default() -> Self {
+        FramePacing::new()
+    }
+}
+
+impl FramePacing {
+    pub const fn new() -> Self {
+        FramePacing {
+            max_fps: Some(DEFAULT_MAX_FPS),
+            spin: Duration::ZERO,
+        }
+    }
+
+    /// Returns minimal duration of a frame.
+    pub fn frame_time(&self) -> Option<Duration> {
+        match self.max_fps {
+            Some(0) | None => None,
+            Some(fps) => Some(Duration::from_secs(1) / fps),
+        }
+    }
+}
+
+/// Waits for the next frame according to [`FramePacing`].
+pub struct FrameLimiter {
+    next: Instant,
+}
+
+impl FrameLimiter {
+    pub fn new() -> Self {
+        FrameLimiter {
+            next: Instant::now(),
+        }
+    }
+
+    /// Returns instant until which host may sleep before calling [`FrameLimiter::wait`].
+    ///
+    /// Event loop hosts wait for it instead of blocking in `wait`,
+    /// so they keep handling events.
+    /// Returns `None` if frame rate is not capped.
+    pub fn deadline(&self, pacing: &FramePacing) -> Option<Instant> {
+        pacing.frame_time()?;
+        Some(self.next.checked_sub(pacing.spin).unwrap_or(self.next))
+    }
+
+    /// Blocks until next frame should start.
+    ///
+    /// Sleeps until `spin` before the deadline and spins the rest.
+    /// If frame took longer than its budget, deadline is reset
+    /// instead of running several frames back to back.
+    pub fn wait(&mut self, pacing: &FramePacing) {
+        let Some(frame_time) = pacing.frame_time() else {
+            self.next = Instant::now();
+            return;
+        };
+
+        let now = Instant::now();
+        if self.next > now {
+            let sleep = (self.next - now).saturating_sub(pacing.spin);
+            if !sleep.is_zero() {
+                std::thread::sleep(sleep);
+            }
+
+            while Instant::now() < self.next {
+                std::hint::spin_loop();
+            }
+
+            self.next += frame_time;
+        } else {
+            self.next = now + frame_time;
+        }
+    }
+}
+
+pub fn init_pacing(world: &mut World) {
+    world.insert_resource(FramePacing::new());
+}
@@ -14,6 +14,9 @@ use crate::{
     code::{CodeDesc, CodeNodeId, FlowCode, PureCode},
     events::EventId,
     input::{FilterId, InputFilter, IntoInputFilter},
+    reflect::{ComponentId, ComponentInfo, ComponentReflect},
+    sandbox::PluginKey,
+    stid::{TypeInfo, TypeRegistry},
     work::{Job, JobDesc, JobId},
     {make_id, Stid},
 };
@@ -45,6 +48,10 @@ pub struct SystemInfo {
     /// Name of the system.
     pub name: Name,
 
+    /// System is added to fixed-rate schedule by default.
+    /// Declared with `#[arcana::system(fixed)]` or `#[arcana::system(var)]`.
+    pub fixed: bool,
+
     /// Location of the system in the source code.
     pub location: Option<Location>,
 }
@@ -133,6 +140,8 @@ pub struct PluginsHub {
     pub pure_fns: HashMap<CodeNodeId, PureCode>,
     pub flow_fns: HashMap<CodeNodeId, FlowCode>,
     pub importers: HashMap<ImporterId, Box<dyn Importer>>,
+    pub components: HashMap<ComponentId, ComponentReflect>,
+    pub types: TypeRegistry,
 }
 
 impl PluginsHub {
@@ -144,6 +153,8 @@ impl PluginsHub {
             pure_fns: HashMap::new(),
             flow_fns: HashMap::new(),
             importers: HashMap::new(),
+            components: HashMap::new(),
+            types: TypeRegistry::new(),
         }
     }
 
@@ -178,6 +189,129 @@ impl PluginsHub {
     pub fn add_flow_fn(&mut self, id: CodeNodeId, code: FlowCode) {
         self.flow_fns.insert(id, code);
     }
+
+    /// Adds an importer from a plugin to the hub.
+    pub fn add_importer(&mut self, id: ImporterId, importer: impl Importer + 'static) {
+        self.importers.insert(id, Box::new(importer));
+    }
+
+    /// Adds reflection of a component from a plugin to the hub.
+    pub fn add_component(&mut self, id: ComponentId, reflect: ComponentReflect) {
+        self.components.insert(id, reflect);
+    }
+
+    /// Adds runtime information of a type from a plugin to the hub.
+    pub fn add_type(&mut self, info: TypeInfo) {
+        self.types.add(info);
+    }
+}
+
+/// Information about a plugin loaded into the world.
+#[derive(Clone)]
+pub struct PluginInfo {
+    /// Name of the plugin.
+    pub name: Ident,
+
+    /// Version of the plugin crate.
+    pub version: Option<String>,
+
+    /// Names of plugins this one depends on.
+    pub dependencies: Vec<Ident>,
+
+    pub systems: Vec<SystemInfo>,
+    pub filters: Vec<FilterInfo>,
+    pub jobs: Vec<JobInfo>,
+    pub events: Vec<EventInfo>,
+    pub codes: Vec<CodeInfo>,
+    pub importers: Vec<ImporterInfo>,
+    pub components: Vec<ComponentInfo>,
+}
+
+impl PluginInfo {
+    pub fn new(name: Ident, plugin: &ArcanaPlugin) -> Self {
+        PluginInfo {
+            name,
+            version: plugin.version(),
+            dependencies: plugin.dependencies.iter().map(|(name, _)| *name).collect(),
+            systems: plugin.systems(),
+            filters: plugin.filters(),
+            jobs: plugin.jobs(),
+            events: plugin.events(),
+            codes: plugin.codes(),
+            importers: plugin.importers(),
+            components: plugin.components(),
+        }
+    }
+}
+
+/// Resource that lists plugins loaded into the world.
+///
+/// Game code may use it to check what plugins are present
+/// and look up systems, jobs and events they registered.
+#[derive(Clone, Default)]
+pub struct PluginRegistry {
+    plugins: Vec<PluginInfo>,
+}
+
+impl PluginRegistry {
+    pub const fn new() -> Self {
+        PluginRegistry {
+            plugins: Vec::new(),
+        }
+    }
+
+    /// Builds registry from list of active plugins.
+    pub fn from_plugins<'a>(plugins: impl IntoIterator<Item = (Ident, &'a ArcanaPlugin)>) -> Self {
+        PluginRegistry {
+            plugins: plugins
+                .into_iter()
+                .map(|(name, plugin)| PluginInfo::new(name, plugin))
+                .collect(),
+        }
+    }
+
+    /// Returns all loaded plugins.
+    pub fn plugins(&self) -> &[PluginInfo] {
+        &self.plugins
+    }
+
+    /// Returns plugin by name.
+    pub fn get(&self, name: Ident) -> Option<&PluginInfo> {
+        self.plugins.iter().find(|p| p.name == name)
+    }
+
+    /// Checks if plugin with given name is loaded.
+    pub fn has(&self, name: Ident) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Finds system and plugin that registered it.
+    pub fn find_system(&self, id: SystemId) -> Option<(&PluginInfo, &SystemInfo)> {
+        self.plugins
+            .iter()
+            .find_map(|p| Some((p, p.systems.iter().find(|s| s.id == id)?)))
+    }
+
+    /// Finds filter and plugin that registered it.
+    pub fn find_filter(&self, id: FilterId) -> Option<(&PluginInfo, &FilterInfo)> {
+        self.plugins
+            .iter()
+            .find_map(|p| Some((p, p.filters.iter().find(|f| f.id == id)?)))
+    }
+
+    /// Finds job and plugin that registered it.
+    pub fn find_job(&self, id: JobId) -> Option<(&PluginInfo, &JobInfo)> {
+        self.plugins
+            .iter()
+            .find_map(|p| Some((p, p.jobs.iter().find(|j| j.id == id)?)))
+    }
+
+    /// Finds event and plugin that registered it.
+    pub fn find_event(&self, id: EventId) -> Option<(&PluginInfo, &EventInfo)> {
+        self.plugins
+            .iter()
+            .find_map(|p| Some((p, p.events.iter().find(|e| e.id == id)?)))
+    }
 }
 
 #[doc(hidden)]
@@ -220,6 +354,7 @@ pub fn unknown_dependency() -> ! {
 #[derive(Default)]
 pub struct ArcanaPlugin {
     location: Option<PathBuf>,
+    version: Option<String>,
     dependencies: Vec<(Ident, Dependency)>,
     filters: Vec<FilterInfo>,
     systems: Vec<SystemInfo>,
@@ -227,8 +362,10 @@ pub struct ArcanaPlugin {
     events: Vec<EventInfo>,
     codes: Vec<CodeInfo>,
     importers: Vec<ImporterInfo>,
+    components: Vec<ComponentInfo>,
     fill_hub: Vec<fn(&mut PluginsHub)>,
     init: Vec<fn(&mut World)>,
+    bind_sandbox_key: Option<fn(PluginKey)>,
 }
 
 impl ArcanaPlugin {
@@ -269,6 +406,15 @@ impl ArcanaPlugin {
         self.fill_hub.push(add);
     }
 
+    pub fn add_component(&mut self, info: ComponentInfo, add: fn(&mut PluginsHub)) {
+        self.components.push(info);
+        self.fill_hub.push(add);
+    }
+
+    pub fn add_type(&mut self, add: fn(&mut PluginsHub)) {
+        self.fill_hub.push(add);
+    }
+
     pub fn add_init(&mut self, add: fn(&mut World)) {
         self.init.push(add);
     }
@@ -279,6 +425,10 @@ impl ArcanaPlugin {
         self.location.clone()
     }
 
+    pub fn version(&self) -> Option<String> {
+        self.version.clone()
+    }
+
     pub fn dependencies(&self) -> Vec<(Ident, Dependency)> {
         self.dependencies.clone()
     }
@@ -303,10 +453,30 @@ impl ArcanaPlugin {
         self.codes.clone()
     }
 
-    pub fn init(&self, world: &mut World, hub: &mut PluginsHub) {
+    pub fn importers(&self) -> Vec<ImporterInfo> {
+        self.importers.clone()
+    }
+
+    pub fn components(&self) -> Vec<ComponentInfo> {
+        self.components.clone()
+    }
+
+    /// Adds plugin's systems, filters, jobs, codes, importers, components and types to the hub.
+    pub fn fill_hub(&self, hub: &mut PluginsHub) {
         for fill in &self.fill_hub {
             fill(hub);
         }
+    }
+
+    /// Hands sandbox key issued by the engine to the plugin.
+    pub(crate) fn bind_sandbox_key(&self, key: PluginKey) {
+        if let Some(bind) = self.bind_sandbox_key {
+            bind(key);
+        }
+    }
+
+    pub fn init(&self, world: &mut World, hub: &mut PluginsHub) {
+        self.fill_hub(hub);
 
         for init in &self.init {
             init(world);
@@ -347,6 +517,11 @@ macro_rules! declare_plugin {
     ($([$($dependency:ident $($kind:tt)*)+])?) => {
         #[doc(hidden)]
         pub mod arcana_plugin {
+            /// Returns name of the plugin.
+            pub fn name() -> $crate::Ident {
+                $crate::Ident::from_str(env!("CARGO_PKG_NAME")).unwrap()
+            }
+
             pub fn dependency() -> $crate::project::Dependency {
                 $crate::project::Dependency::Crates(env!("CARGO_PKG_VERSION").to_owned())
             }
@@ -355,6 +530,22 @@ macro_rules! declare_plugin {
                 $crate::project::Dependency::from_path(env!("CARGO_MANIFEST_DIR")).unwrap()
             }
 
+            static SANDBOX_KEY: ::std::sync::OnceLock<$crate::sandbox::PluginKey> =
+                ::std::sync::OnceLock::new();
+
+            /// Returns key the plugin passes to `arcana::sandbox` wrappers.
+            /// It is bound by the engine when plugins library is loaded.
+            #[allow(dead_code)]
+            pub(crate) fn sandbox_key() -> &'static $crate::sandbox::PluginKey {
+                SANDBOX_KEY.get_or_init(|| $crate::sandbox::PluginKey::unbound(name()))
+            }
+
+            fn bind_sandbox_key(key: $crate::sandbox::PluginKey) {
+                if SANDBOX_KEY.set(key).is_err() {
+                    $crate::tracing::error!("Sandbox key of plugin '{}' is already bound", name());
+                }
+            }
+
             pub fn get() -> $crate::plugin::ArcanaPlugin {
                 // Safety: This value is accessed mutably at cdylib load time.
                 // Afterwards it can only be accessed immutably here.
@@ -362,7 +553,7 @@ macro_rules! declare_plugin {
             }
 
             pub static mut ARCANA_PLUGIN_REGISTRY: $crate::plugin::init::Registry =
-                ::arcana::plugin::init::Registry::new();
+                ::arcana::plugin::init::Registry::new(env!("CARGO_PKG_VERSION"), bind_sandbox_key);
         }
 
         $(
@@ -397,16 +588,20 @@ pub mod init {
 
     pub struct Registry {
         manifest_dir: &'static str,
+        version: &'static str,
         list: Option<&'static CtorNode>,
         dependencies: BTreeMap<Ident, Dependency>,
+        bind_sandbox_key: fn(PluginKey),
     }
 
     impl Registry {
-        pub const fn new() -> Self {
+        pub const fn new(version: &'static str, bind_sandbox_key: fn(PluginKey)) -> Self {
             Registry {
                 manifest_dir: env!("CARGO_MANIFEST_DIR"),
+                version,
                 list: None,
                 dependencies: BTreeMap::new(),
+                bind_sandbox_key,
             }
         }
 
@@ -424,6 +619,8 @@ pub mod init {
                 .collect();
 
             plugin.location = Some(PathBuf::from(self.manifest_dir));
+            plugin.version = Some(self.version.to_owned());
+            plugin.bind_sandbox_key = Some(self.bind_sandbox_key);
 
             let mut node = self.list;
             while let Some(n) = node {
@@ -0,0 +1,153 @@
+//! Profiling spans.
+//!
+//! Engine opens a span around each frame, system and render job,
+//! so external profilers see the same breakdown as the editor profiler.
+//! Spans are `tracing` spans with target [`TARGET`]
+//! and are compiled in only with `profile` feature.
+//! Without it all functions here are no-ops.
+//!
+//! Spans are collected by the layer returned from [`layer`].
+//! Which backend it uses is chosen with `ARCANA_PROFILE` environment variable:
+//!
+//! - `tracy` - streams spans to Tracy, requires `profile-tracy` feature.
+//! - `chrome` - writes `trace-<timestamp>.json` for `chrome://tracing`,
+//!   requires `profile-chrome` feature.
+//!
+//! Spans can be switched off at runtime with [`set_enabled`].
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tracing::Subscriber;
+use tracing_subscriber::{registry::LookupSpan, Layer};
+
+use crate::{plugin::SystemId, work::JobId};
+
+/// Target of all profiling spans.
+pub const TARGET: &str = "arcana::profile";
+
+/// Environment variable that selects profiling backend.
+pub const ENV: &str = "ARCANA_PROFILE";
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turns span creation on or off.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns true if spans are compiled in and enabled.
+#[inline(always)]
+pub fn enabled() -> bool {
+    cfg!(feature = "profile") && ENABLED.load(Ordering::Relaxed)
+}
+
+/// Guard of entered profiling span.
+/// Span is exited when guard is dropped.
+#[must_use = "Span is exited when guard is dropped"]
+pub struct ProfileSpan {
+    #[cfg(feature = "profile")]
+    _span: Option<tracing::span::EnteredSpan>,
+}
+
+impl ProfileSpan {
+    #[inline(always)]
+    fn new(make: impl FnOnce() -> tracing::Span) -> Self {
+        #[cfg(feature = "profile")]
+        {
+            let span = match enabled() {
+                true => Some(make().entered()),
+                false => None,
+            };
+            ProfileSpan { _span: span }
+        }
+
+        #[cfg(not(feature = "profile"))]
+        {
+            let _ = make;
+            ProfileSpan {}
+        }
+    }
+}
+
+/// Enters span that covers one frame of the instance.
+#[inline(always)]
+pub fn frame() -> ProfileSpan {
+    ProfileSpan::new(|| tracing::info_span!(target: TARGET, "frame"))
+}
+
+/// Enters span of a system run.
+#[inline(always)]
+pub fn system(id: SystemId) -> ProfileSpan {
+    ProfileSpan::new(|| tracing::info_span!(target: TARGET, "system", %id))
+}
+
+/// Enters span of a render job planning.
+#[inline(always)]
+pub fn job_plan(id: JobId) -> ProfileSpan {
+    ProfileSpan::new(|| tracing::info_span!(target: TARGET, "job-plan", %id))
+}
+
+/// Enters span of a render job command encoding.
+#[inline(always)]
+pub fn job_exec(id: JobId) -> ProfileSpan {
+    ProfileSpan::new(|| tracing::info_span!(target: TARGET, "job-exec", %id))
+}
+
+/// Enters span of a work graph run that renders one view.
+#[inline(always)]
+pub fn render(name: &str) -> ProfileSpan {
+    ProfileSpan::new(|| tracing::info_span!(target: TARGET, "render", name))
+}
+
+/// Enters span with custom name for user code.
+#[inline(always)]
+pub fn scope(name: &str) -> ProfileSpan {
+    ProfileSpan::new(|| tracing::info_span!(target: TARGET, "scope", name))
+}
+
+/// Returns layer that sends profiling spans to backend selected by [`ENV`].
+///
+/// Returns `None` if no backend is selected or it is not compiled in.
+pub fn layer<S>() -> Option<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    if !cfg!(feature = "profile") {
+        return None;
+    }
+
+    let backend = std::env::var(ENV).ok()?;
+
+    match &*backend {
+        #[cfg(feature = "profile-tracy")]
+        "tracy" => Some(Box::new(tracing_tracy::TracyLayer::default())),
+
+        #[cfg(feature = "profile-chrome")]
+        "chrome" => {
+            let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new()
+                .include_args(true)
+                .build();
+            *chrome::GUARD.lock() = Some(guard);
+            Some(Box::new(layer))
+        }
+
+        _ => {
+            eprintln!("Unknown or disabled profiling backend '{backend}'");
+            None
+        }
+    }
+}
+
+/// Finishes writing profile to file.
+/// Should be called before exit, otherwise trace file may be truncated.
+pub fn flush() {
+    #[cfg(feature = "profile-chrome")]
+    drop(chrome::GUARD.lock().take());
+}
+
+#[cfg(feature = "profile-chrome")]
+mod chrome {
+    use parking_lot::Mutex;
+
+    pub static GUARD: Mutex<Option<tracing_chrome::FlushGuard>> = Mutex::new(None);
+}
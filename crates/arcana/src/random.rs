@@ -0,0 +1,271 @@
+//! Deterministic random numbers.
+//!
+//! Game code should take random numbers from [`Rng`] resource
+//! or [`EntityRng`] component instead of `rand::random()`.
+//! Sequence depends only on the seed, so replays, tests and rollback
+//! reproduce exact behavior when started with the same seed.
+//!
+//! Generator is xoshiro256++ seeded with splitmix64,
+//! which gives the same numbers on all platforms.
+//!
+//! Engine tooling, like asset ID generation and temporary file names in the editor,
+//! keeps using `rand` on purpose, since those must not repeat between runs.
+
+use edict::{component::Component, world::World};
+use rand::{
+    distributions::{
+        uniform::{SampleRange, SampleUniform},
+        Distribution, Standard,
+    },
+    RngCore, SeedableRng,
+};
+
+/// Seedable random number generator.
+///
+/// Implements `rand::RngCore`, so it can be used with anything from `rand`.
+/// State is serializable, so it can be saved with the game or into a snapshot.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Rng {
+    seed: u64,
+    state: [u64; 4],
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut sm = seed;
+        Rng {
+            seed,
+            state: [
+                splitmix64(&mut sm),
+                splitmix64(&mut sm),
+                splitmix64(&mut sm),
+                splitmix64(&mut sm),
+            ],
+        }
+    }
+
+    /// Returns seed generator was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restarts the sequence with new seed.
+    pub fn reseed(&mut self, seed: u64) {
+        *self = Rng::new(seed);
+    }
+
+    /// Returns independent generator for the key.
+    ///
+    /// Stream depends only on the seed and the key,
+    /// not on how many numbers were taken from this generator,
+    /// so the same entity gets the same numbers regardless of what other entities do.
+    pub fn stream(&self, key: u64) -> Rng {
+        let mut sm = key;
+        Rng::new(self.seed ^ splitmix64(&mut sm))
+    }
+
+    /// Returns random value of the type.
+    pub fn gen<T>(&mut self) -> T
+    where
+        Standard: Distribution<T>,
+    {
+        rand::Rng::gen(self)
+    }
+
+    /// Returns random value in the range.
+    pub fn gen_range<T, R>(&mut self, range: R) -> T
+    where
+        T: SampleUniform,
+        R: SampleRange<T>,
+    {
+        rand::Rng::gen_range(self, range)
+    }
+
+    /// Returns true with probability `p`.
+    pub fn gen_bool(&mut self, p: f64) -> bool {
+        rand::Rng::gen_bool(self, p)
+    }
+}
+
+impl RngCore for Rng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[0].wrapping_add(s[3]).rotate_left(23).wrapping_add(s[0]);
+
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+
+        result
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for Rng {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: [u8; 8]) -> Self {
+        Rng::new(u64::from_le_bytes(seed))
+    }
+
+    fn seed_from_u64(seed: u64) -> Self {
+        Rng::new(seed)
+    }
+}
+
+/// Random stream of an entity.
+///
+/// Created from [`Rng`] resource with a key that is stable for the entity,
+/// e.g. rollback or network id.
+#[derive(Clone, Debug, Component)]
+pub struct EntityRng(pub Rng);
+
+impl EntityRng {
+    /// Returns stream of the world's [`Rng`] for the key.
+    pub fn new(world: &World, key: u64) -> Self {
+        EntityRng(world.expect_resource::<Rng>().stream(key))
+    }
+}
+
+impl std::ops::Deref for EntityRng {
+    type Target = Rng;
+
+    fn deref(&self) -> &Rng {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for EntityRng {
+    fn deref_mut(&mut self) -> &mut Rng {
+        &mut self.0
+    }
+}
+
+/// Inserts [`Rng`] resource with random seed.
+///
+/// Seed is logged, so that run can be reproduced by reseeding with it.
+pub fn init_random(world: &mut World) {
+    let seed = rand::random();
+    tracing::info!("Random seed is {seed}");
+    world.insert_resource(Rng::new(seed));
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_splitmix64_known_answer() {
+        let mut state = 1477776061723855037;
+        let expected = [
+            1985237415132408290,
+            2979275885539914483,
+            13511426838097143398,
+            8488337342461049707,
+            15141737807933549159,
+            17093170987380407015,
+            16389528042912955399,
+            13177319091862933652,
+            10841969400225389492,
+            17094824097954834098,
+        ];
+        for value in expected {
+            assert_eq!(splitmix64(&mut state), value);
+        }
+    }
+
+    #[test]
+    fn test_xoshiro256plusplus_known_answer() {
+        let mut rng = Rng {
+            seed: 0,
+            state: [1, 2, 3, 4],
+        };
+        let expected = [
+            41943041,
+            58720359,
+            3588806011781223,
+            3591011842654386,
+            9228616714210784205,
+            9973669472204895162,
+            14011001112246962877,
+            12406186145184390807,
+            15849039046786891736,
+            10450023813501588000,
+        ];
+        for value in expected {
+            assert_eq!(rng.next_u64(), value);
+        }
+    }
+
+    #[test]
+    fn test_seeded_sequence() {
+        let rng = Rng::new(0);
+        assert_eq!(
+            rng.state,
+            [
+                16294208416658607535,
+                7960286522194355700,
+                487617019471545679,
+                17909611376780542444
+            ]
+        );
+
+        let mut rng = Rng::new(42);
+        let expected = [
+            15021278609987233951,
+            5881210131331364753,
+            18149643915985481100,
+            12933668939759105464,
+        ];
+        for value in expected {
+            assert_eq!(rng.next_u64(), value);
+        }
+    }
+
+    #[test]
+    fn test_stream() {
+        let mut rng = Rng::new(42);
+        let mut stream = rng.stream(7);
+        let expected = [
+            979695631230394729,
+            10097116656222583117,
+            12830481692229142472,
+            660658770889294027,
+        ];
+        for value in expected {
+            assert_eq!(stream.next_u64(), value);
+        }
+
+        // Stream does not depend on numbers taken from the parent.
+        rng.next_u64();
+        assert_eq!(rng.stream(7), Rng::new(42).stream(7));
+        assert_ne!(rng.stream(7), rng.stream(8));
+    }
+}
@@ -0,0 +1,849 @@
+//! Reflection of component types.
+//!
+//! Plugins describe their components with [`Model`] and register them
+//! with [`reflect_component!`].
+//! Ed uses registered schemas to show and edit components
+//! of entities in the running instance without knowing their types.
+//!
+//! ```ignore
+//! #[derive(Component)]
+//! struct Speed {
+//!     value: f32,
+//!     direction: na::Vector2<f32>,
+//! }
+//!
+//! arcana::reflect_struct!(Speed { value, direction });
+//! arcana::reflect_component!(Speed);
+//! ```
+//!
+//! Resources are registered by name with [`reflect_resource`],
+//! console `resource` command reads and writes them.
+
+use std::{any::TypeId, collections::BTreeMap};
+
+use edict::{component::Component, entity::EntityId, world::World};
+use hashbrown::HashMap;
+
+use crate::{
+    assets::AssetId,
+    color::Color,
+    curve::Curve,
+    fixed::Fixed,
+    make_id,
+    model::{ColorModel, ColorValue, Model, TypeModel, Value, ValueError},
+    plugin::Location,
+    Name,
+};
+
+make_id! {
+    /// ID of the reflected component type.
+    pub ComponentId;
+}
+
+/// Type that can be converted to and from model value.
+///
+/// Use [`reflect_struct!`] and [`reflect_enum!`] to implement it
+/// for structs with reflected fields and for fieldless enums.
+pub trait Reflect: TypeModel + 'static {
+    /// Returns value that represents current state.
+    fn to_value(&self) -> Value;
+
+    /// Updates state from the value.
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError>;
+}
+
+/// Component information declared by a plugin.
+#[derive(Clone, Debug)]
+pub struct ComponentInfo {
+    /// Unique identified of the component.
+    pub id: ComponentId,
+
+    /// Name of the component.
+    pub name: Name,
+
+    /// Data model of the component.
+    pub model: Model,
+
+    /// Location of the component registration in the source code.
+    pub location: Option<Location>,
+}
+
+/// Type-erased access to reflected component.
+#[derive(Clone, Copy)]
+pub struct ComponentReflect {
+    type_id: TypeId,
+    has: fn(&World, EntityId) -> bool,
+    get: fn(&World, EntityId) -> Option<Value>,
+    set: fn(&mut World, EntityId, &Value) -> Result<(), ValueError>,
+    insert: Option<fn(&mut World, EntityId, &Value) -> Result<(), ValueError>>,
+}
+
+impl ComponentReflect {
+    pub fn new<T>() -> Self
+    where
+        T: Reflect + Component + Send + Sync,
+    {
+        ComponentReflect {
+            type_id: TypeId::of::<T>(),
+            has: |world, entity| match world.try_view_one::<&T>(entity) {
+                Ok(mut view) => view.get().is_some(),
+                Err(_) => false,
+            },
+            get: |world, entity| {
+                let mut view = world.try_view_one::<&T>(entity).ok()?;
+                view.get().map(T::to_value)
+            },
+            set: |world, entity, value| {
+                let Ok(mut view) = world.try_view_one::<&mut T>(entity) else {
+                    return Err(ValueError::Custom(format!("Entity {entity} is missing")));
+                };
+                match view.get_mut() {
+                    None => Err(ValueError::Custom(format!(
+                        "Entity {entity} does not have the component"
+                    ))),
+                    Some(component) => component.set_value(value),
+                }
+            },
+            insert: None,
+        }
+    }
+
+    /// Same as [`ComponentReflect::new`], but also allows inserting components
+    /// built from values, which lets ed restore them after plugins reload.
+    pub fn with_default<T>() -> Self
+    where
+        T: Reflect + Component + Send + Sync + Default,
+    {
+        ComponentReflect {
+            insert: Some(|world, entity, value| {
+                let mut component = T::default();
+                component.set_value(value)?;
+                match world.insert(entity, component) {
+                    Ok(()) => Ok(()),
+                    Err(_) => Err(ValueError::Custom(format!("Entity {entity} is missing"))),
+                }
+            }),
+            ..ComponentReflect::new::<T>()
+        }
+    }
+
+    /// Returns type id of the component.
+    /// Systems report their access to components by type id.
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// Returns true if entity has the component.
+    pub fn has(&self, world: &World, entity: EntityId) -> bool {
+        (self.has)(world, entity)
+    }
+
+    /// Returns value of the component if entity has one.
+    pub fn get(&self, world: &World, entity: EntityId) -> Option<Value> {
+        (self.get)(world, entity)
+    }
+
+    /// Updates component of the entity from the value.
+    pub fn set(
+        &self,
+        world: &mut World,
+        entity: EntityId,
+        value: &Value,
+    ) -> Result<(), ValueError> {
+        (self.set)(world, entity, value)
+    }
+
+    /// Returns true if component can be inserted from a value.
+    pub fn can_insert(&self) -> bool {
+        self.insert.is_some()
+    }
+
+    /// Inserts component built from the value into the entity.
+    pub fn insert(
+        &self,
+        world: &mut World,
+        entity: EntityId,
+        value: &Value,
+    ) -> Result<(), ValueError> {
+        match self.insert {
+            None => Err(ValueError::Custom(
+                "Component can't be constructed from value".to_owned(),
+            )),
+            Some(insert) => insert(world, entity, value),
+        }
+    }
+}
+
+/// Accessors of a resource registered for reflection.
+#[derive(Clone, Copy)]
+pub struct ResourceReflect {
+    get: fn(&World) -> Option<Value>,
+    set: fn(&World, &Value) -> Result<(), ValueError>,
+}
+
+impl ResourceReflect {
+    pub fn new<T>() -> Self
+    where
+        T: Reflect + Send + Sync,
+    {
+        ResourceReflect {
+            get: |world| world.get_resource::<T>().map(|r| r.to_value()),
+            set: |world, value| match world.get_resource_mut::<T>() {
+                None => Err(ValueError::Custom("Resource is missing".to_owned())),
+                Some(mut resource) => resource.set_value(value),
+            },
+        }
+    }
+
+    /// Returns value of the resource if it is present.
+    pub fn get(&self, world: &World) -> Option<Value> {
+        (self.get)(world)
+    }
+
+    /// Updates the resource from the value.
+    pub fn set(&self, world: &World, value: &Value) -> Result<(), ValueError> {
+        (self.set)(world, value)
+    }
+}
+
+/// Registry of reflected resources by name.
+#[derive(Default)]
+pub struct ReflectedResources {
+    resources: BTreeMap<&'static str, ResourceReflect>,
+}
+
+impl ReflectedResources {
+    pub fn new() -> Self {
+        ReflectedResources::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ResourceReflect> {
+        self.resources.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.resources.keys().copied()
+    }
+}
+
+/// Registers resource for reflection under the name.
+pub fn reflect_resource<T>(world: &mut World, name: &'static str)
+where
+    T: Reflect + Send + Sync,
+{
+    let reflect = ResourceReflect::new::<T>();
+    match world.get_resource_mut::<ReflectedResources>() {
+        Some(mut resources) => {
+            resources.resources.insert(name, reflect);
+        }
+        None => {
+            let mut resources = ReflectedResources::new();
+            resources.resources.insert(name, reflect);
+            world.insert_resource(resources);
+        }
+    }
+}
+
+fn mismatch(expected: &str, value: &Value) -> ValueError {
+    ValueError::Custom(format!("Expected {expected}, but is {}", value.kind()))
+}
+
+macro_rules! reflect_int {
+    ($($ty:ty),*) => {$(
+        impl TypeModel for $ty {
+            fn model() -> Model {
+                Model::Int
+            }
+
+            fn model_dyn(&self) -> Model {
+                Model::Int
+            }
+        }
+
+        impl Reflect for $ty {
+            fn to_value(&self) -> Value {
+                Value::Int(*self as i64)
+            }
+
+            fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+                let converted = match *value {
+                    Value::Int(v) => <$ty>::try_from(v).ok(),
+                    Value::Uint(v) => <$ty>::try_from(v).ok(),
+                    _ => return Err(mismatch("integer", value)),
+                };
+
+                match converted {
+                    Some(v) => {
+                        *self = v;
+                        Ok(())
+                    }
+                    None => Err(ValueError::Custom(format!(
+                        "Value is out of range of {}",
+                        stringify!($ty)
+                    ))),
+                }
+            }
+        }
+    )*};
+}
+
+reflect_int!(i8, i16, i32, i64, u8, u16, u32, u64);
+
+macro_rules! reflect_float {
+    ($($ty:ty),*) => {$(
+        impl TypeModel for $ty {
+            fn model() -> Model {
+                Model::Float
+            }
+
+            fn model_dyn(&self) -> Model {
+                Model::Float
+            }
+        }
+
+        impl Reflect for $ty {
+            fn to_value(&self) -> Value {
+                Value::Float(*self as f64)
+            }
+
+            fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+                match *value {
+                    Value::Float(v) => *self = v as $ty,
+                    Value::Int(v) => *self = v as $ty,
+                    _ => return Err(mismatch("float", value)),
+                }
+                Ok(())
+            }
+        }
+    )*};
+}
+
+reflect_float!(f32, f64);
+
+impl TypeModel for bool {
+    fn model() -> Model {
+        Model::Bool
+    }
+
+    fn model_dyn(&self) -> Model {
+        Model::Bool
+    }
+}
+
+impl Reflect for bool {
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+        match *value {
+            Value::Bool(v) => *self = v,
+            _ => return Err(mismatch("boolean", value)),
+        }
+        Ok(())
+    }
+}
+
+impl TypeModel for String {
+    fn model() -> Model {
+        Model::String
+    }
+
+    fn model_dyn(&self) -> Model {
+        Model::String
+    }
+}
+
+impl Reflect for String {
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+        match value {
+            Value::String(v) => self.clone_from(v),
+            _ => return Err(mismatch("string", value)),
+        }
+        Ok(())
+    }
+}
+
+impl TypeModel for AssetId {
+    fn model() -> Model {
+        Model::Asset
+    }
+
+    fn model_dyn(&self) -> Model {
+        Model::Asset
+    }
+}
+
+impl Reflect for AssetId {
+    fn to_value(&self) -> Value {
+        Value::Asset(*self)
+    }
+
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+        match *value {
+            Value::Asset(id) => *self = id,
+            _ => return Err(mismatch("asset", value)),
+        }
+        Ok(())
+    }
+}
+
+impl TypeModel for Curve {
+    fn model() -> Model {
+        Model::Curve
+    }
+
+    fn model_dyn(&self) -> Model {
+        Model::Curve
+    }
+}
+
+impl Reflect for Curve {
+    fn to_value(&self) -> Value {
+        Value::Curve(self.clone())
+    }
+
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+        match value {
+            Value::Curve(curve) => self.clone_from(curve),
+            _ => return Err(mismatch("curve", value)),
+        }
+        Ok(())
+    }
+}
+
+impl TypeModel for Fixed {
+    fn model() -> Model {
+        Model::Float
+    }
+
+    fn model_dyn(&self) -> Model {
+        Model::Float
+    }
+}
+
+impl Reflect for Fixed {
+    fn to_value(&self) -> Value {
+        Value::Float(self.to_f64())
+    }
+
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+        match *value {
+            Value::Float(v) => *self = Fixed::from_f64(v),
+            Value::Int(v) => *self = Fixed::from_f64(v as f64),
+            _ => return Err(mismatch("float", value)),
+        }
+        Ok(())
+    }
+}
+
+macro_rules! reflect_vector {
+    ($($model:ident, $vector:ident [$($c:ident),+];)*) => {$(
+        impl TypeModel for na::$vector<f32> {
+            fn model() -> Model {
+                Model::$model
+            }
+
+            fn model_dyn(&self) -> Model {
+                Model::$model
+            }
+        }
+
+        impl Reflect for na::$vector<f32> {
+            fn to_value(&self) -> Value {
+                Value::$model(na::$vector::new($(self.$c as f64),+))
+            }
+
+            fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+                match value {
+                    Value::$model(v) => *self = na::$vector::new($(v.$c as f32),+),
+                    _ => return Err(mismatch(stringify!($model), value)),
+                }
+                Ok(())
+            }
+        }
+
+        impl TypeModel for na::$vector<f64> {
+            fn model() -> Model {
+                Model::$model
+            }
+
+            fn model_dyn(&self) -> Model {
+                Model::$model
+            }
+        }
+
+        impl Reflect for na::$vector<f64> {
+            fn to_value(&self) -> Value {
+                Value::$model(*self)
+            }
+
+            fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+                match value {
+                    Value::$model(v) => *self = *v,
+                    _ => return Err(mismatch(stringify!($model), value)),
+                }
+                Ok(())
+            }
+        }
+    )*};
+}
+
+reflect_vector! {
+    Vec2, Vector2 [x, y];
+    Vec3, Vector3 [x, y, z];
+    Vec4, Vector4 [x, y, z, w];
+}
+
+impl TypeModel for palette::Srgb {
+    fn model() -> Model {
+        Model::Color(ColorModel::Srgb)
+    }
+
+    fn model_dyn(&self) -> Model {
+        Model::Color(ColorModel::Srgb)
+    }
+}
+
+impl Reflect for palette::Srgb {
+    fn to_value(&self) -> Value {
+        Value::Color(ColorValue::Srgb(*self))
+    }
+
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+        match *value {
+            Value::Color(color) => *self = color.into_srgb(),
+            _ => return Err(mismatch("color", value)),
+        }
+        Ok(())
+    }
+}
+
+impl TypeModel for palette::Srgba {
+    fn model() -> Model {
+        Model::Color(ColorModel::Srgba)
+    }
+
+    fn model_dyn(&self) -> Model {
+        Model::Color(ColorModel::Srgba)
+    }
+}
+
+impl Reflect for palette::Srgba {
+    fn to_value(&self) -> Value {
+        Value::Color(ColorValue::Srgba(*self))
+    }
+
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+        match *value {
+            Value::Color(color) => *self = color.into_srgba(),
+            _ => return Err(mismatch("color", value)),
+        }
+        Ok(())
+    }
+}
+
+impl TypeModel for Color {
+    fn model() -> Model {
+        Model::Color(ColorModel::Srgba)
+    }
+
+    fn model_dyn(&self) -> Model {
+        Model::Color(ColorModel::Srgba)
+    }
+}
+
+impl Reflect for Color {
+    fn to_value(&self) -> Value {
+        Value::Color((*self).into())
+    }
+
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+        match *value {
+            Value::Color(color) => *self = color.into(),
+            _ => return Err(mismatch("color", value)),
+        }
+        Ok(())
+    }
+}
+
+impl<T> TypeModel for Option<T>
+where
+    T: TypeModel,
+{
+    fn model() -> Model {
+        Model::Option(Some(Box::new(T::model())))
+    }
+
+    fn model_dyn(&self) -> Model {
+        Self::model()
+    }
+}
+
+impl<T> Reflect for Option<T>
+where
+    T: Reflect + Default,
+{
+    fn to_value(&self) -> Value {
+        Value::Option(self.as_ref().map(|v| Box::new(v.to_value())))
+    }
+
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+        match value {
+            Value::Option(None) => *self = None,
+            Value::Option(Some(v)) => self.get_or_insert_with(T::default).set_value(v)?,
+            _ => return Err(mismatch("option", value)),
+        }
+        Ok(())
+    }
+}
+
+impl<T> TypeModel for Vec<T>
+where
+    T: TypeModel,
+{
+    fn model() -> Model {
+        Model::Array {
+            elem: Some(Box::new(T::model())),
+            len: None,
+        }
+    }
+
+    fn model_dyn(&self) -> Model {
+        Self::model()
+    }
+}
+
+impl<T> Reflect for Vec<T>
+where
+    T: Reflect + Default,
+{
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(|v| v.to_value()).collect())
+    }
+
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+        let Value::Array(values) = value else {
+            return Err(mismatch("array", value));
+        };
+
+        self.resize_with(values.len(), T::default);
+        for (idx, (elem, value)) in self.iter_mut().zip(values).enumerate() {
+            elem.set_value(value)
+                .map_err(|err| ValueError::Custom(format!("Element [{idx}]: {err}")))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> TypeModel for HashMap<String, T>
+where
+    T: TypeModel,
+{
+    fn model() -> Model {
+        Model::Map(Some(Box::new(T::model())))
+    }
+
+    fn model_dyn(&self) -> Model {
+        Self::model()
+    }
+}
+
+impl<T> Reflect for HashMap<String, T>
+where
+    T: Reflect + Default,
+{
+    fn to_value(&self) -> Value {
+        Value::Map(
+            self.iter()
+                .map(|(k, v)| (k.clone(), v.to_value()))
+                .collect(),
+        )
+    }
+
+    fn set_value(&mut self, value: &Value) -> Result<(), ValueError> {
+        let Value::Map(values) = value else {
+            return Err(mismatch("map", value));
+        };
+
+        self.retain(|key, _| values.contains_key(key));
+        for (key, value) in values {
+            self.entry(key.clone())
+                .or_default()
+                .set_value(value)
+                .map_err(|err| ValueError::Custom(format!("Entry '{key}': {err}")))?;
+        }
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub fn field_model<S, T>(_field: fn(&S) -> &T) -> Model
+where
+    T: TypeModel,
+{
+    T::model()
+}
+
+#[doc(hidden)]
+pub fn set_field(
+    map: &HashMap<String, Value>,
+    name: &str,
+    field: &mut dyn Reflect,
+) -> Result<(), ValueError> {
+    match map.get(name) {
+        // Missing fields are left unchanged.
+        None => Ok(()),
+        Some(value) => field
+            .set_value(value)
+            .map_err(|err| ValueError::Custom(format!("Field '{name}': {err}"))),
+    }
+}
+
+/// Implements [`Reflect`] for a struct with listed fields.
+/// Types of the fields must implement [`Reflect`].
+#[macro_export]
+macro_rules! reflect_struct {
+    ($ty:ident { $($field:ident),* $(,)? }) => {
+        impl $crate::model::TypeModel for $ty {
+            fn model() -> $crate::model::Model {
+                $crate::model::Model::Struct {
+                    name: $crate::name!($ty),
+                    fields: ::std::vec![$(
+                        (
+                            $crate::name!($field),
+                            ::std::option::Option::Some($crate::reflect::field_model(|v: &$ty| &v.$field)),
+                        ),
+                    )*],
+                }
+            }
+
+            fn model_dyn(&self) -> $crate::model::Model {
+                <Self as $crate::model::TypeModel>::model()
+            }
+        }
+
+        impl $crate::reflect::Reflect for $ty {
+            fn to_value(&self) -> $crate::model::Value {
+                let mut map = $crate::hashbrown::HashMap::new();
+                $(
+                    map.insert(
+                        ::std::string::String::from(::std::stringify!($field)),
+                        $crate::reflect::Reflect::to_value(&self.$field),
+                    );
+                )*
+                $crate::model::Value::Map(map)
+            }
+
+            fn set_value(&mut self, value: &$crate::model::Value) -> ::std::result::Result<(), $crate::model::ValueError> {
+                match value {
+                    $crate::model::Value::Map(map) => {
+                        $(
+                            $crate::reflect::set_field(map, ::std::stringify!($field), &mut self.$field)?;
+                        )*
+                        ::std::result::Result::Ok(())
+                    }
+                    _ => ::std::result::Result::Err($crate::model::ValueError::Custom(
+                        ::std::format!("Expected record, but is {}", value.kind()),
+                    )),
+                }
+            }
+        }
+    };
+}
+
+/// Implements [`Reflect`] for an enum with listed fieldless variants.
+#[macro_export]
+macro_rules! reflect_enum {
+    ($ty:ident { $($variant:ident),+ $(,)? }) => {
+        impl $crate::model::TypeModel for $ty {
+            fn model() -> $crate::model::Model {
+                $crate::model::Model::Enum(::std::vec![$(
+                    (
+                        $crate::name!($variant),
+                        ::std::option::Option::Some($crate::model::Model::Unit),
+                    ),
+                )+])
+            }
+
+            fn model_dyn(&self) -> $crate::model::Model {
+                <Self as $crate::model::TypeModel>::model()
+            }
+        }
+
+        impl $crate::reflect::Reflect for $ty {
+            fn to_value(&self) -> $crate::model::Value {
+                match *self {
+                    $(
+                        Self::$variant => $crate::model::Value::Enum(
+                            $crate::name!($variant),
+                            ::std::boxed::Box::new($crate::model::Value::Unit),
+                        ),
+                    )+
+                }
+            }
+
+            fn set_value(&mut self, value: &$crate::model::Value) -> ::std::result::Result<(), $crate::model::ValueError> {
+                match value {
+                    $crate::model::Value::Enum(name, _) => match name.as_str() {
+                        $(
+                            ::std::stringify!($variant) => {
+                                *self = Self::$variant;
+                                ::std::result::Result::Ok(())
+                            }
+                        )+
+                        _ => ::std::result::Result::Err($crate::model::ValueError::Custom(
+                            ::std::format!("Unknown variant '{}'", name),
+                        )),
+                    },
+                    _ => ::std::result::Result::Err($crate::model::ValueError::Custom(
+                        ::std::format!("Expected enum, but is {}", value.kind()),
+                    )),
+                }
+            }
+        }
+    };
+}
+
+/// Registers component schema with the plugin,
+/// so ed can inspect and edit the component.
+/// Component type must implement [`Reflect`].
+///
+/// Use `reflect_component!(Type, Default)` for components that implement [`Default`]
+/// to keep them in the world when plugins are reloaded.
+#[macro_export]
+macro_rules! reflect_component {
+    ($ty:ident) => {
+        $crate::reflect_component!(@register $ty, new);
+    };
+    ($ty:ident, Default) => {
+        $crate::reflect_component!(@register $ty, with_default);
+    };
+    (@register $ty:ident, $ctor:ident) => {
+        $crate::plugin_ctor_add!(plugin => {
+            let id: $crate::reflect::ComponentId = $crate::local_name_hash_id!($ty);
+
+            let add = |hub: &mut $crate::plugin::PluginsHub| {
+                let id: $crate::reflect::ComponentId = $crate::local_name_hash_id!($ty);
+                hub.add_component(id, $crate::reflect::ComponentReflect::$ctor::<$ty>());
+            };
+
+            let info = $crate::reflect::ComponentInfo {
+                id,
+                name: $crate::name!($ty),
+                model: <$ty as $crate::model::TypeModel>::model(),
+                location: ::std::option::Option::Some($crate::plugin::Location {
+                    file: ::std::string::String::from(::std::file!()),
+                    line: ::std::line!(),
+                    column: ::std::column!(),
+                }),
+            };
+
+            plugin.add_component(info, add);
+        });
+    };
+}
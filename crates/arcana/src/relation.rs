@@ -0,0 +1,85 @@
+//! Relations between entities.
+//!
+//! Relations are edict relations, so they are queried in views
+//! with `Related`, `RelatesExclusive` and `FilterRelates`,
+//! and removed automatically when either side is despawned.
+//!
+//! This module provides relations for common patterns,
+//! so that plugins agree on them instead of keeping `EntityId`s in components.
+//! Stored ids break silently when target is despawned,
+//! while relation is dropped together with it.
+//!
+//! ```ignore
+//! fn follow(
+//!     followers: View<(&mut Global, RelatesExclusive<&Targets>)>,
+//!     targets: View<&Global>,
+//! ) {
+//!     for (global, (_, target)) in followers {
+//!         ...
+//!     }
+//! }
+//! ```
+
+pub use edict::relation::{FilterRelates, Related, RelatesExclusive, Relation};
+use edict::{entity::EntityId, world::World};
+
+use crate::NoSuchEntity;
+
+/// Origin is a child of the target.
+///
+/// Entity has at most one parent and is despawned with it.
+#[derive(Clone, Copy, Debug, Relation)]
+#[edict(owned, exclusive)]
+pub struct ChildOf;
+
+/// Origin is owned by the target,
+/// e.g. an item in inventory of a character.
+///
+/// Entity has at most one owner and is despawned with it.
+/// Unlike [`ChildOf`] it does not imply hierarchy of transforms.
+#[derive(Clone, Copy, Debug, Relation)]
+#[edict(owned, exclusive)]
+pub struct OwnedBy;
+
+/// Origin targets the entity, e.g. follows or attacks it.
+///
+/// Entity has at most one target.
+/// Relation is dropped when target is despawned, origin stays alive.
+#[derive(Clone, Copy, Debug, Relation)]
+#[edict(exclusive)]
+pub struct Targets;
+
+/// Makes `child` a child of `parent`, replacing previous parent.
+pub fn set_parent(
+    world: &mut World,
+    child: EntityId,
+    parent: EntityId,
+) -> Result<(), NoSuchEntity> {
+    world.add_relation(child, ChildOf, parent)
+}
+
+/// Detaches `child` from `parent`.
+/// Returns false if `child` is not a child of `parent`.
+pub fn remove_parent(world: &mut World, child: EntityId, parent: EntityId) -> bool {
+    world.remove_relation::<ChildOf>(child, parent).is_ok()
+}
+
+/// Makes `owner` the owner of `entity`, replacing previous owner.
+pub fn set_owner(world: &mut World, entity: EntityId, owner: EntityId) -> Result<(), NoSuchEntity> {
+    world.add_relation(entity, OwnedBy, owner)
+}
+
+/// Makes `entity` target `target`, replacing previous target.
+pub fn set_target(
+    world: &mut World,
+    entity: EntityId,
+    target: EntityId,
+) -> Result<(), NoSuchEntity> {
+    world.add_relation(entity, Targets, target)
+}
+
+/// Makes `entity` stop targeting `target`.
+/// Returns false if `entity` does not target `target`.
+pub fn clear_target(world: &mut World, entity: EntityId, target: EntityId) -> bool {
+    world.remove_relation::<Targets>(entity, target).is_ok()
+}
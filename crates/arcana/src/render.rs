@@ -1,4 +1,4 @@
-use edict::{component::Component, entity::EntityId};
+use edict::{component::Component, entity::EntityId, query::Cpy, world::World};
 
 use crate::make_id;
 
@@ -26,3 +26,216 @@ pub struct Renderer {
 pub struct CurrentRenderer {
     pub entity: EntityId,
 }
+
+/// Component for the renderer entity that selects camera to render with.
+///
+/// Lets several viewports render the same world from different points of view,
+/// e.g. rear-view mirrors and minimaps.
+/// Jobs use first camera they find if renderer has no `RenderCamera`.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct RenderCamera {
+    pub entity: EntityId,
+}
+
+/// Returns camera entity selected by the current renderer.
+pub fn current_camera(world: &World) -> Option<EntityId> {
+    let renderer = world.get_resource::<CurrentRenderer>()?;
+    let camera = world.get::<Cpy<RenderCamera>>(renderer.entity).ok()?;
+    Some(camera.entity)
+}
+
+/// Number of frames render jobs keep per-frame resources for.
+///
+/// Jobs that keep resources per frame alternate between this many copies,
+/// so a frame doesn't overwrite data the previous one was built from.
+pub const FRAMES_IN_FLIGHT: usize = 2;
+
+/// Persistent buffer for data render passes stream every frame -
+/// vertices, indices and texture uploads.
+///
+/// Passes allocate regions in the buffer instead of allocating new buffers
+/// every frame. Buffer grows when it can't fit the data
+/// and is kept for following frames.
+///
+/// CPU never writes the memory directly. Passes fill regions with
+/// `write_buffer_slice` of the frame's copy encoder, so GPU executes writes
+/// after commands of previous frames, as long as the pass puts a barrier
+/// from stages that read the buffer to `TRANSFER` before the copies.
+pub struct StreamBuffer {
+    name: &'static str,
+    usage: mev::BufferUsage,
+    buffer: Option<mev::Buffer>,
+    offset: usize,
+}
+
+/// Region of the [`StreamBuffer`].
+#[derive(Clone)]
+pub struct StreamSlice {
+    pub buffer: mev::Buffer,
+    pub offset: usize,
+    pub size: usize,
+}
+
+impl StreamBuffer {
+    /// Alignment of the regions.
+    /// Enough for vertex, index and image copy offsets.
+    pub const ALIGNMENT: usize = 256;
+
+    /// Size of the smallest buffer allocated.
+    const MIN_SIZE: usize = 65536;
+
+    /// Returns new stream which buffer is used as `usage`
+    /// and as transfer destination.
+    pub const fn new(name: &'static str, usage: mev::BufferUsage) -> Self {
+        StreamBuffer {
+            name,
+            usage: usage.union(mev::BufferUsage::TRANSFER_DST),
+            buffer: None,
+            offset: 0,
+        }
+    }
+
+    /// Starts new frame, regions of the previous frame are reused.
+    ///
+    /// Called once per frame before allocating regions.
+    pub fn reset(&mut self) {
+        self.offset = 0;
+    }
+
+    /// Makes sure `size` bytes can be allocated in current frame
+    /// without allocating new buffer.
+    ///
+    /// Passes that know how much they write call it first,
+    /// so that all the data ends up in one buffer.
+    /// Each region may be padded by up to [`StreamBuffer::ALIGNMENT`] bytes.
+    pub fn reserve(&mut self, device: &mev::Device, size: usize) -> Result<(), mev::OutOfMemory> {
+        let offset = align(self.offset);
+
+        if let Some(buffer) = &self.buffer {
+            if buffer.size() >= offset + size {
+                return Ok(());
+            }
+        }
+
+        // Regions allocated before stay in the old buffer,
+        // slices keep it alive until they are dropped.
+        let buffer = device.new_buffer(mev::BufferDesc {
+            size: size.next_power_of_two().max(Self::MIN_SIZE),
+            usage: self.usage,
+            memory: mev::Memory::Device,
+            name: self.name,
+        })?;
+
+        self.buffer = Some(buffer);
+        self.offset = 0;
+        Ok(())
+    }
+
+    /// Allocates region of `size` bytes in current frame.
+    pub fn alloc(
+        &mut self,
+        device: &mev::Device,
+        size: usize,
+    ) -> Result<StreamSlice, mev::OutOfMemory> {
+        self.reserve(device, size)?;
+
+        let offset = align(self.offset);
+        self.offset = offset + size;
+
+        Ok(StreamSlice {
+            buffer: self.buffer.clone().unwrap(),
+            offset,
+            size,
+        })
+    }
+}
+
+/// Remembers pipeline and argument sets bound in a render pass,
+/// so draws that use the same resources don't bind them again.
+///
+/// Arguments are written into the command buffer on each bind,
+/// so skipping repeated binds saves CPU time and command memory
+/// when consecutive draws share material.
+/// Keys identify argument sets, e.g. images of the material.
+///
+/// Bindings live in one render pass, so cache is reset at its start.
+pub struct ArgumentCache<P, K> {
+    pipeline: Option<P>,
+    bound: Vec<Option<K>>,
+}
+
+impl<P, K> ArgumentCache<P, K>
+where
+    P: PartialEq,
+    K: PartialEq,
+{
+    pub const fn new() -> Self {
+        ArgumentCache {
+            pipeline: None,
+            bound: Vec::new(),
+        }
+    }
+
+    /// Forgets everything bound.
+    pub fn reset(&mut self) {
+        self.pipeline = None;
+        self.bound.clear();
+    }
+
+    /// Returns true if pipeline with `key` must be bound.
+    ///
+    /// Arguments bound for previous pipeline are forgotten when pipeline changes.
+    pub fn bind_pipeline(&mut self, key: P) -> bool {
+        if self.pipeline.as_ref() == Some(&key) {
+            return false;
+        }
+
+        self.pipeline = Some(key);
+        self.bound.clear();
+        true
+    }
+
+    /// Returns true if arguments with `key` must be bound at `group`.
+    pub fn bind_arguments(&mut self, group: u32, key: K) -> bool {
+        let group = group as usize;
+        if self.bound.len() <= group {
+            self.bound.resize_with(group + 1, || None);
+        }
+
+        if self.bound[group].as_ref() == Some(&key) {
+            return false;
+        }
+
+        self.bound[group] = Some(key);
+        true
+    }
+}
+
+fn align(offset: usize) -> usize {
+    (offset + StreamBuffer::ALIGNMENT - 1) & !(StreamBuffer::ALIGNMENT - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_argument_cache_skips_repeated_binds() {
+        let mut cache = ArgumentCache::<u32, u32>::new();
+
+        assert!(cache.bind_pipeline(0));
+        assert!(cache.bind_arguments(0, 1));
+        assert!(!cache.bind_arguments(0, 1));
+        assert!(cache.bind_arguments(1, 1));
+        assert!(cache.bind_arguments(0, 2));
+
+        assert!(!cache.bind_pipeline(0));
+        assert!(!cache.bind_arguments(0, 2));
+
+        assert!(cache.bind_pipeline(1));
+        assert!(cache.bind_arguments(0, 2));
+
+        cache.reset();
+        assert!(cache.bind_pipeline(1));
+    }
+}
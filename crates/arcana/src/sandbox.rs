@@ -0,0 +1,223 @@
+//! Engine wrappers for capabilities guarded by plugin permissions.
+//!
+//! Plugins declare permissions they need in their manifest
+//! and editor asks the user to grant them before enabling the plugin.
+//! Plugins access files, network and processes through functions of this module,
+//! which fail with [`PermissionDenied`] unless the permission is granted.
+//!
+//! Plugin identifies itself with [`PluginKey`] issued by the engine when plugins library is loaded,
+//! available as `crate::arcana_plugin::sandbox_key()`.
+//! Key of another plugin can't be obtained through the engine API.
+//!
+//! These checks are advisory, not a security boundary.
+//! Plugins are native code running in the engine process,
+//! nothing stops them from calling `std` directly or reading another plugin's key from memory.
+//! Wrappers make well-behaved plugins fail loudly and consistently
+//! when they go beyond what the user allowed.
+
+use std::{collections::BTreeMap, io, path::Path};
+
+use arcana_names::Ident;
+use parking_lot::RwLock;
+
+pub use arcana_project::{Permission, Permissions};
+
+static GRANTED: RwLock<BTreeMap<Ident, Permissions>> = RwLock::new(BTreeMap::new());
+
+/// Secrets of issued keys mapped to plugins they were issued to.
+static KEYS: RwLock<BTreeMap<u64, Ident>> = RwLock::new(BTreeMap::new());
+
+/// Identity of a plugin for permission checks.
+///
+/// Issued by the engine for each plugin of loaded library.
+/// Key that was not issued, or was issued for previously loaded library,
+/// has no permissions.
+#[derive(Debug)]
+pub struct PluginKey {
+    plugin: Ident,
+    secret: u64,
+}
+
+impl PluginKey {
+    /// Returns key that has no permissions.
+    /// Used by plugin until the engine issues the real key.
+    pub fn unbound(plugin: Ident) -> Self {
+        PluginKey { plugin, secret: 0 }
+    }
+
+    pub fn plugin(&self) -> Ident {
+        self.plugin
+    }
+
+    pub(crate) fn secret(&self) -> u64 {
+        self.secret
+    }
+
+    fn is_valid(&self) -> bool {
+        self.secret != 0 && KEYS.read().get(&self.secret) == Some(&self.plugin)
+    }
+}
+
+/// Issues new key for the plugin.
+pub(crate) fn issue_key(plugin: Ident) -> PluginKey {
+    let mut keys = KEYS.write();
+    loop {
+        let secret = rand::random::<u64>();
+        if secret != 0 && !keys.contains_key(&secret) {
+            keys.insert(secret, plugin);
+            return PluginKey { plugin, secret };
+        }
+    }
+}
+
+/// Invalidates keys issued for unloaded library.
+pub(crate) fn forget_keys(secrets: &[u64]) {
+    let mut keys = KEYS.write();
+    for secret in secrets {
+        keys.remove(secret);
+    }
+}
+
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+#[error("Plugin '{plugin}' is not granted '{}' permission", permission.name())]
+pub struct PermissionDenied {
+    pub plugin: Ident,
+    pub permission: Permission,
+}
+
+impl From<PermissionDenied> for io::Error {
+    fn from(err: PermissionDenied) -> Self {
+        io::Error::new(io::ErrorKind::PermissionDenied, err)
+    }
+}
+
+/// Replaces permissions granted to the plugin.
+pub(crate) fn grant(plugin: Ident, permissions: Permissions) {
+    GRANTED.write().insert(plugin, permissions);
+}
+
+/// Revokes all permissions of all plugins.
+pub(crate) fn revoke_all() {
+    GRANTED.write().clear();
+}
+
+/// Returns permissions granted to the plugin.
+pub fn granted(plugin: Ident) -> Permissions {
+    GRANTED.read().get(&plugin).copied().unwrap_or_default()
+}
+
+/// Checks that the plugin holding the key is granted the permission.
+pub fn check(key: &PluginKey, permission: Permission) -> Result<(), PermissionDenied> {
+    if key.is_valid() && granted(key.plugin).contains(permission) {
+        Ok(())
+    } else {
+        Err(PermissionDenied {
+            plugin: key.plugin,
+            permission,
+        })
+    }
+}
+
+/// File access guarded by [`Permission::Filesystem`].
+pub mod fs {
+    use super::*;
+
+    pub fn read(key: &PluginKey, path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+        check(key, Permission::Filesystem)?;
+        std::fs::read(path)
+    }
+
+    pub fn read_to_string(key: &PluginKey, path: impl AsRef<Path>) -> io::Result<String> {
+        check(key, Permission::Filesystem)?;
+        std::fs::read_to_string(path)
+    }
+
+    pub fn write(
+        key: &PluginKey,
+        path: impl AsRef<Path>,
+        data: impl AsRef<[u8]>,
+    ) -> io::Result<()> {
+        check(key, Permission::Filesystem)?;
+        std::fs::write(path, data)
+    }
+
+    pub fn open(key: &PluginKey, path: impl AsRef<Path>) -> io::Result<std::fs::File> {
+        check(key, Permission::Filesystem)?;
+        std::fs::File::open(path)
+    }
+
+    pub fn create(key: &PluginKey, path: impl AsRef<Path>) -> io::Result<std::fs::File> {
+        check(key, Permission::Filesystem)?;
+        std::fs::File::create(path)
+    }
+
+    pub fn create_dir_all(key: &PluginKey, path: impl AsRef<Path>) -> io::Result<()> {
+        check(key, Permission::Filesystem)?;
+        std::fs::create_dir_all(path)
+    }
+}
+
+/// Network access guarded by [`Permission::Network`].
+pub mod net {
+    use std::net::{TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+
+    use super::*;
+
+    pub fn connect(key: &PluginKey, addr: impl ToSocketAddrs) -> io::Result<TcpStream> {
+        check(key, Permission::Network)?;
+        TcpStream::connect(addr)
+    }
+
+    pub fn listen(key: &PluginKey, addr: impl ToSocketAddrs) -> io::Result<TcpListener> {
+        check(key, Permission::Network)?;
+        TcpListener::bind(addr)
+    }
+
+    pub fn bind_udp(key: &PluginKey, addr: impl ToSocketAddrs) -> io::Result<UdpSocket> {
+        check(key, Permission::Network)?;
+        UdpSocket::bind(addr)
+    }
+}
+
+/// Process spawning guarded by [`Permission::Subprocess`].
+pub mod process {
+    use std::{ffi::OsStr, process::Command};
+
+    use super::*;
+
+    /// Returns command builder for the program.
+    pub fn command(
+        key: &PluginKey,
+        program: impl AsRef<OsStr>,
+    ) -> Result<Command, PermissionDenied> {
+        check(key, Permission::Subprocess)?;
+        Ok(Command::new(program))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arcana_names::ident;
+
+    use super::*;
+
+    #[test]
+    fn test_check_key() {
+        let key = issue_key(ident!(sandbox_test_plugin));
+        assert!(check(&key, Permission::Network).is_err());
+
+        grant(
+            ident!(sandbox_test_plugin),
+            Permissions::from(vec![Permission::Network]),
+        );
+        assert!(check(&key, Permission::Network).is_ok());
+        assert!(check(&key, Permission::Subprocess).is_err());
+
+        // Plugin can't make a key with the name of granted plugin.
+        let spoofed = PluginKey::unbound(ident!(sandbox_test_plugin));
+        assert!(check(&spoofed, Permission::Network).is_err());
+
+        forget_keys(&[key.secret()]);
+        assert!(check(&key, Permission::Network).is_err());
+    }
+}
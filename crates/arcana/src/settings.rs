@@ -0,0 +1,463 @@
+//! Game settings persisted between runs.
+//!
+//! Game defines settings as a serde struct implementing [`GameSettings`]
+//! and inserts [`Settings`] resource with [`init_settings`].
+//! Settings are read from `settings.toml` in the platform config directory,
+//! e.g. `~/.config/<game>/settings.toml` on Linux.
+//!
+//! Systems edit settings through [`Settings::get_mut`].
+//! [`commit_settings`] saves edited settings to the file
+//! and sends [`SettingsChanged`] to the event channel.
+//!
+//! [`SettingsScreen`] draws egui widgets for settings fields
+//! and lets player rebind actions.
+
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+use edict::world::World;
+use egui_probe::EguiProbe;
+use serde::{de::DeserializeOwned, Serialize};
+use winit::keyboard::KeyCode;
+
+use crate::{
+    events::{add_event_channel, EventChannel},
+    input::{ActionMap, Binding, ViewInput},
+};
+
+/// Name of the settings file in the game config directory.
+pub const FILE_NAME: &str = "settings.toml";
+
+/// Trait for game settings types.
+pub trait GameSettings:
+    Clone + Default + PartialEq + Serialize + DeserializeOwned + EguiProbe + Send + Sync + 'static
+{
+    /// Returns action bindings the player may change.
+    /// They are shown in a separate section of [`SettingsScreen`].
+    fn bindings(&mut self) -> Option<&mut ActionMap> {
+        None
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SettingsError {
+    #[error("Failed to access settings file")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse settings")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Failed to serialize settings")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// Window resolution in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EguiProbe, serde::Serialize, serde::Deserialize)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Resolution {
+            width: 1280,
+            height: 720,
+        }
+    }
+}
+
+/// Settings most games have.
+/// Games may use it as is or embed it into their own settings.
+#[derive(Clone, Debug, PartialEq, EguiProbe, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct CommonSettings {
+    pub resolution: Resolution,
+    pub fullscreen: bool,
+    pub vsync: bool,
+
+    /// Master volume from 0 to 1.
+    #[egui_probe(range = 0.0..=1.0)]
+    pub volume: f32,
+
+    #[egui_probe(skip)]
+    pub bindings: ActionMap,
+}
+
+impl Default for CommonSettings {
+    fn default() -> Self {
+        CommonSettings {
+            resolution: Resolution::default(),
+            fullscreen: false,
+            vsync: true,
+            volume: 1.0,
+            bindings: ActionMap::default(),
+        }
+    }
+}
+
+impl GameSettings for CommonSettings {
+    fn bindings(&mut self) -> Option<&mut ActionMap> {
+        Some(&mut self.bindings)
+    }
+}
+
+/// Event sent when settings are committed.
+#[derive(Clone, Debug)]
+pub struct SettingsChanged<T> {
+    pub old: T,
+    pub new: T,
+}
+
+/// Resource with current game settings.
+pub struct Settings<T> {
+    value: T,
+
+    /// Last committed value.
+    committed: T,
+
+    /// File to save settings to.
+    /// Unset if config directory is unknown.
+    path: Option<PathBuf>,
+}
+
+impl<T> Deref for Settings<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> Settings<T>
+where
+    T: GameSettings,
+{
+    /// Loads settings of the game from the config directory.
+    ///
+    /// Missing or broken file results in default settings.
+    pub fn load(game: &str) -> Self {
+        let path = settings_path(game);
+
+        let value = match &path {
+            None => T::default(),
+            Some(path) => match read_settings(path) {
+                Ok(value) => value,
+                Err(SettingsError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                    T::default()
+                }
+                Err(err) => {
+                    tracing::warn!("Failed to load settings from {}: {err}", path.display());
+                    T::default()
+                }
+            },
+        };
+
+        Settings {
+            committed: value.clone(),
+            value,
+            path,
+        }
+    }
+
+    /// Returns settings that are never saved.
+    pub fn in_memory(value: T) -> Self {
+        Settings {
+            committed: value.clone(),
+            value,
+            path: None,
+        }
+    }
+
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns settings for editing.
+    /// Changes are saved and announced by [`commit_settings`].
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    /// Returns path of the settings file.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Returns true if settings were changed since last commit.
+    pub fn is_changed(&self) -> bool {
+        self.value != self.committed
+    }
+
+    /// Resets settings to defaults.
+    pub fn reset(&mut self) {
+        self.value = T::default();
+    }
+
+    /// Discards changes since last commit.
+    pub fn revert(&mut self) {
+        self.value = self.committed.clone();
+    }
+
+    /// Writes current settings to the file.
+    pub fn save(&self) -> Result<(), SettingsError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let text = toml::to_string_pretty(&self.value)?;
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+fn read_settings<T>(path: &Path) -> Result<T, SettingsError>
+where
+    T: DeserializeOwned,
+{
+    let text = std::fs::read_to_string(path)?;
+    Ok(toml::from_str(&text)?)
+}
+
+/// Returns path to the settings file of the game.
+///
+/// Falls back to directory of the executable
+/// when platform has no config directory.
+pub fn settings_path(game: &str) -> Option<PathBuf> {
+    let mut path = match dirs::config_dir() {
+        None => {
+            let mut path = std::env::current_exe().ok()?;
+            path.pop();
+            path
+        }
+        Some(mut path) => {
+            path.push(game);
+            path
+        }
+    };
+    path.push(FILE_NAME);
+    Some(path)
+}
+
+/// Loads settings of the game and inserts them as resource
+/// along with channel for [`SettingsChanged`] events.
+pub fn init_settings<T>(world: &mut World, game: &str)
+where
+    T: GameSettings,
+{
+    world.insert_resource(Settings::<T>::load(game));
+    add_event_channel::<SettingsChanged<T>>(world);
+}
+
+/// Saves changed settings and sends [`SettingsChanged`] event.
+///
+/// Games call it after the player confirms changes,
+/// or from a system once per frame to commit changes as they are made.
+pub fn commit_settings<T>(world: &World)
+where
+    T: GameSettings,
+{
+    let mut settings = world.expect_resource_mut::<Settings<T>>();
+    if !settings.is_changed() {
+        return;
+    }
+
+    if let Err(err) = settings.save() {
+        tracing::error!("Failed to save settings: {err}");
+    }
+
+    let new = settings.value.clone();
+    let old = std::mem::replace(&mut settings.committed, new.clone());
+    drop(settings);
+
+    if let Some(mut channel) = world.get_resource_mut::<EventChannel<SettingsChanged<T>>>() {
+        channel.send(SettingsChanged { old, new });
+    }
+}
+
+/// State of the settings screen.
+#[derive(Default)]
+pub struct SettingsScreen {
+    /// Action waiting for the player to press new binding.
+    /// Context and action indices.
+    capture: Option<(usize, usize)>,
+}
+
+impl SettingsScreen {
+    pub fn new() -> Self {
+        SettingsScreen::default()
+    }
+
+    /// Returns true if screen waits for the player to press new binding.
+    pub fn is_capturing(&self) -> bool {
+        self.capture.is_some()
+    }
+
+    /// Draws settings widgets.
+    ///
+    /// Edits are applied to `settings` immediately, "Revert" discards them.
+    /// Returns true if "Apply" was clicked,
+    /// game should call [`commit_settings`] then.
+    pub fn show<T>(&mut self, settings: &mut Settings<T>, ui: &mut egui::Ui) -> bool
+    where
+        T: GameSettings,
+    {
+        egui_probe::Probe::new(&mut settings.value).show(ui);
+
+        if let Some(bindings) = settings.value.bindings() {
+            ui.separator();
+            self.show_bindings(bindings, ui);
+        }
+
+        ui.separator();
+        self.show_buttons(settings, ui)
+    }
+
+    /// Draws settings widgets without action bindings.
+    /// For games that show bindings on a separate page with [`SettingsScreen::show_controls`].
+    pub fn show_values<T>(&mut self, settings: &mut Settings<T>, ui: &mut egui::Ui) -> bool
+    where
+        T: GameSettings,
+    {
+        egui_probe::Probe::new(&mut settings.value).show(ui);
+
+        ui.separator();
+        self.show_buttons(settings, ui)
+    }
+
+    /// Draws action bindings only.
+    pub fn show_controls<T>(&mut self, settings: &mut Settings<T>, ui: &mut egui::Ui) -> bool
+    where
+        T: GameSettings,
+    {
+        match settings.value.bindings() {
+            None => {
+                ui.label("Game has no controls to rebind");
+                return false;
+            }
+            Some(bindings) => self.show_bindings(bindings, ui),
+        }
+
+        ui.separator();
+        self.show_buttons(settings, ui)
+    }
+
+    /// Draws "Apply", "Revert" and "Defaults" buttons.
+    fn show_buttons<T>(&mut self, settings: &mut Settings<T>, ui: &mut egui::Ui) -> bool
+    where
+        T: GameSettings,
+    {
+        let mut apply = false;
+        ui.horizontal(|ui| {
+            let changed = settings.is_changed();
+
+            apply = ui
+                .add_enabled(changed, egui::Button::new("Apply"))
+                .clicked();
+
+            if ui
+                .add_enabled(changed, egui::Button::new("Revert"))
+                .clicked()
+            {
+                settings.revert();
+                self.capture = None;
+            }
+
+            if ui.button("Defaults").clicked() {
+                settings.reset();
+                self.capture = None;
+            }
+        });
+
+        apply
+    }
+
+    fn show_bindings(&mut self, map: &mut ActionMap, ui: &mut egui::Ui) {
+        for (cidx, context) in map.contexts.iter_mut().enumerate() {
+            egui::CollapsingHeader::new(&context.name)
+                .id_source(("settings-bindings", cidx))
+                .default_open(true)
+                .show(ui, |ui| {
+                    egui::Grid::new(("settings-bindings-grid", cidx))
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (aidx, action) in context.actions.iter_mut().enumerate() {
+                                ui.label(&action.name);
+
+                                ui.horizontal(|ui| {
+                                    let mut remove = None;
+                                    for (bidx, binding) in action.bindings.iter().enumerate() {
+                                        let r = ui
+                                            .button(binding.to_string())
+                                            .on_hover_text("Click to remove");
+                                        if r.clicked() {
+                                            remove = Some(bidx);
+                                        }
+                                    }
+                                    if let Some(bidx) = remove {
+                                        action.bindings.remove(bidx);
+                                    }
+
+                                    if self.capture == Some((cidx, aidx)) {
+                                        if ui.button("Press a key...").clicked() {
+                                            self.capture = None;
+                                        }
+                                    } else if ui.button("+").clicked() {
+                                        self.capture = Some((cidx, aidx));
+                                    }
+                                });
+
+                                ui.end_row();
+                            }
+                        });
+                });
+        }
+    }
+
+    /// Feeds input to the screen.
+    ///
+    /// While screen captures a binding, pressed key or mouse button
+    /// is bound to the action and `true` is returned,
+    /// so the game should not handle this input.
+    /// Escape cancels capture.
+    pub fn capture<T>(&mut self, settings: &mut Settings<T>, input: &ViewInput) -> bool
+    where
+        T: GameSettings,
+    {
+        let Some((cidx, aidx)) = self.capture else {
+            return false;
+        };
+
+        let Some(binding) = Binding::pressed(input) else {
+            return false;
+        };
+
+        self.capture = None;
+
+        if binding == Binding::Key(KeyCode::Escape) {
+            return true;
+        }
+
+        let Some(map) = settings.value.bindings() else {
+            return true;
+        };
+
+        if let Some(action) = map
+            .contexts
+            .get_mut(cidx)
+            .and_then(|context| context.actions.get_mut(aidx))
+        {
+            if !action.bindings.contains(&binding) {
+                action.bindings.push(binding);
+            }
+        }
+
+        true
+    }
+}
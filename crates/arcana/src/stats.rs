@@ -0,0 +1,129 @@
+//! Frame statistics for diagnostics.
+//!
+//! Instance fills [`FrameStats`] while ticking the world
+//! and [`RenderStats`] while running render graphs,
+//! so that in-game overlays can show them without access to the editor profiler.
+//!
+//! Render jobs report draw calls they record with [`count_draws`].
+
+use std::{collections::VecDeque, time::Duration};
+
+use edict::world::World;
+
+/// Number of frames kept in frame time history.
+pub const FRAME_HISTORY: usize = 120;
+
+/// Resource with timings of the scheduler.
+#[derive(Clone, Debug, Default)]
+pub struct FrameStats {
+    /// Real time between starts of recent frames, oldest first.
+    frame_times: VecDeque<Duration>,
+
+    /// Time spent in fixed systems during last frame, all ticks combined.
+    pub fix_systems: Duration,
+
+    /// Number of fixed ticks during last frame.
+    pub fix_ticks: u32,
+
+    /// Time spent in variable systems during last frame.
+    pub var_systems: Duration,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        FrameStats {
+            frame_times: VecDeque::with_capacity(FRAME_HISTORY),
+            fix_systems: Duration::ZERO,
+            fix_ticks: 0,
+            var_systems: Duration::ZERO,
+        }
+    }
+
+    /// Records duration of finished frame and resets per-frame timings.
+    pub fn begin_frame(&mut self, frame_time: Duration) {
+        if self.frame_times.len() == FRAME_HISTORY {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+
+        self.fix_systems = Duration::ZERO;
+        self.fix_ticks = 0;
+        self.var_systems = Duration::ZERO;
+    }
+
+    /// Returns recent frame times, oldest first.
+    pub fn frame_times(&self) -> impl ExactSizeIterator<Item = Duration> + '_ {
+        self.frame_times.iter().copied()
+    }
+
+    /// Returns average frame time over the history.
+    pub fn average_frame_time(&self) -> Duration {
+        if self.frame_times.is_empty() {
+            return Duration::ZERO;
+        }
+        self.frame_times.iter().sum::<Duration>() / self.frame_times.len() as u32
+    }
+
+    /// Returns frames per second averaged over the history.
+    pub fn fps(&self) -> f32 {
+        let average = self.average_frame_time().as_secs_f32();
+        if average > 0.0 {
+            1.0 / average
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Resource with statistics of the last rendered frame.
+/// Values are summed over all views rendered in the frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RenderStats {
+    /// Number of jobs executed.
+    pub jobs: u32,
+
+    /// Time spent planning jobs.
+    pub plan: Duration,
+
+    /// Time spent recording commands.
+    pub exec: Duration,
+
+    /// Draw calls reported by jobs.
+    pub draw_calls: u32,
+
+    /// Instances skipped by jobs because they are out of view.
+    pub culled: u32,
+
+    /// Estimated memory of render targets allocated by work graphs.
+    /// Resources jobs allocate on their own are not included.
+    pub target_memory: u64,
+}
+
+impl RenderStats {
+    pub fn new() -> Self {
+        RenderStats::default()
+    }
+}
+
+/// Adds draw calls to the current frame's [`RenderStats`].
+///
+/// Called by render jobs from `exec`.
+pub fn count_draws(world: &World, count: u32) {
+    if let Some(mut stats) = world.get_resource_mut::<RenderStats>() {
+        stats.draw_calls += count;
+    }
+}
+
+/// Adds instances culled by a job to the current frame's [`RenderStats`].
+///
+/// Called by render jobs from `plan` or `exec`.
+pub fn count_culled(world: &World, count: u32) {
+    if let Some(mut stats) = world.get_resource_mut::<RenderStats>() {
+        stats.culled += count;
+    }
+}
+
+pub fn init_stats(world: &mut World) {
+    world.insert_resource(FrameStats::new());
+    world.insert_resource(RenderStats::new());
+}
@@ -15,9 +15,21 @@
 //! Generics use the identifier number and hash them with the type parameters identifiers
 //! to produce identifier for the concrete generic type instance.
 //!
+//! Plugins may register their types in [`TypeRegistry`] with [`register_type!`],
+//! so that tools can inspect and (de)serialize values of types they never saw statically.
+//! Values cross the registry as [`Value`]s described by the type's [`Model`].
+//!
+
+use std::any::Any;
 
 use arcana_proc::with_stid;
 use gametime::TimeSpan;
+use hashbrown::HashMap;
+
+use crate::{
+    model::{Model, Value, ValueError},
+    reflect::Reflect,
+};
 
 crate::make_id! {
     /// Stable Type Identifier.
@@ -72,6 +84,161 @@ with_stid!(i32 = 0x0000_0000_0000_0007);
 with_stid!(i64 = 0x0000_0000_0000_0008);
 with_stid!(f32 = 0x0000_0000_0000_0009);
 with_stid!(f64 = 0x0000_0000_0000_000A);
+with_stid!(bool = 0x0000_0000_0000_000B);
+with_stid!(String = 0x0000_0000_0000_000C);
 
 with_stid!(TimeSpan = 0x0000_0000_0000_00041);
 with_stid!(::edict::entity::EntityId = 0x0000_0000_0000_0042);
+
+/// Runtime information about a type registered by a plugin.
+#[derive(Clone)]
+pub struct TypeInfo {
+    pub stid: Stid,
+
+    /// Rust name of the type.
+    pub name: &'static str,
+
+    pub size: usize,
+    pub align: usize,
+
+    /// Data model of the type.
+    /// Ed builds editor widgets for values from it.
+    pub model: Model,
+
+    to_value: fn(&dyn Any) -> Option<Value>,
+    set_value: fn(&mut dyn Any, &Value) -> Result<(), ValueError>,
+    from_value: Option<fn(&Value) -> Result<Box<dyn Any + Send + Sync>, ValueError>>,
+}
+
+impl TypeInfo {
+    pub fn new<T>() -> Self
+    where
+        T: WithStid + Reflect,
+    {
+        TypeInfo {
+            stid: T::stid(),
+            name: std::any::type_name::<T>(),
+            size: std::mem::size_of::<T>(),
+            align: std::mem::align_of::<T>(),
+            model: T::model(),
+            to_value: |value| value.downcast_ref::<T>().map(T::to_value),
+            set_value: |value, new| match value.downcast_mut::<T>() {
+                Some(value) => value.set_value(new),
+                None => Err(ValueError::Custom(format!(
+                    "Value is not of type {}",
+                    std::any::type_name::<T>()
+                ))),
+            },
+            from_value: None,
+        }
+    }
+
+    /// Same as [`TypeInfo::new`], but also allows building values of the type.
+    pub fn with_default<T>() -> Self
+    where
+        T: WithStid + Reflect + Default + Send + Sync,
+    {
+        TypeInfo {
+            from_value: Some(|value| {
+                let mut new = T::default();
+                new.set_value(value)?;
+                Ok(Box::new(new))
+            }),
+            ..TypeInfo::new::<T>()
+        }
+    }
+
+    /// Converts value of the type to model value.
+    /// Returns `None` if value is of another type.
+    pub fn to_value(&self, value: &dyn Any) -> Option<Value> {
+        (self.to_value)(value)
+    }
+
+    /// Updates value of the type from model value.
+    pub fn set_value(&self, value: &mut dyn Any, new: &Value) -> Result<(), ValueError> {
+        (self.set_value)(value, new)
+    }
+
+    /// Returns true if values of the type can be built with [`TypeInfo::from_value`].
+    pub fn can_build(&self) -> bool {
+        self.from_value.is_some()
+    }
+
+    /// Builds value of the type from model value.
+    /// Returns `None` if type was registered without default.
+    pub fn from_value(
+        &self,
+        value: &Value,
+    ) -> Option<Result<Box<dyn Any + Send + Sync>, ValueError>> {
+        self.from_value.map(|from_value| from_value(value))
+    }
+}
+
+/// Registry of types by their stable identifiers.
+///
+/// Filled from plugins when they are loaded into the hub.
+#[derive(Clone, Default)]
+pub struct TypeRegistry {
+    types: HashMap<Stid, TypeInfo>,
+}
+
+impl TypeRegistry {
+    pub fn new() -> Self {
+        TypeRegistry {
+            types: HashMap::new(),
+        }
+    }
+
+    /// Adds type to the registry.
+    /// Type registered with the same stid is replaced.
+    pub fn add(&mut self, info: TypeInfo) {
+        if let Some(old) = self.types.get(&info.stid) {
+            if old.name != info.name {
+                tracing::warn!(
+                    "Stid {} of type {} is reused by type {}",
+                    info.stid,
+                    old.name,
+                    info.name
+                );
+            }
+        }
+        self.types.insert(info.stid, info);
+    }
+
+    pub fn get(&self, stid: Stid) -> Option<&TypeInfo> {
+        self.types.get(&stid)
+    }
+
+    /// Returns name of the type, if registered.
+    pub fn name(&self, stid: Stid) -> Option<&'static str> {
+        self.types.get(&stid).map(|info| info.name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TypeInfo> + '_ {
+        self.types.values()
+    }
+}
+
+/// Registers type in the [`TypeRegistry`] of the hub.
+/// Type must implement [`WithStid`] and [`Reflect`].
+///
+/// Use `register_type!(Type, Default)` for types that implement [`Default`]
+/// to allow tools to build values of the type.
+///
+/// [`Reflect`]: crate::reflect::Reflect
+#[macro_export]
+macro_rules! register_type {
+    ($ty:ty) => {
+        $crate::register_type!(@register $ty, new);
+    };
+    ($ty:ty, Default) => {
+        $crate::register_type!(@register $ty, with_default);
+    };
+    (@register $ty:ty, $ctor:ident) => {
+        $crate::plugin_ctor_add!(plugin => {
+            plugin.add_type(|hub: &mut $crate::plugin::PluginsHub| {
+                hub.add_type($crate::stid::TypeInfo::$ctor::<$ty>());
+            });
+        });
+    };
+}
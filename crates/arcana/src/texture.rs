@@ -1,7 +1,10 @@
 use std::future::Future;
 
-use basis_universal::{self, TranscodeError, TranscodeParameters, TranscoderTextureFormat};
-use edict::component::Component;
+use arcana_names::{ident, Ident};
+use basis_universal::{
+    self, TranscodeError, TranscodeParameters, Transcoder, TranscoderTextureFormat,
+};
+use edict::{component::Component, world::World};
 use mev::Extent2;
 use smallvec::SmallVec;
 
@@ -18,11 +21,14 @@ impl Component for Texture {
     }
 }
 
+/// Basis Universal texture data validated on load.
+///
+/// Transcoding is deferred to build step
+/// where device is available to choose target format.
 pub struct LoadedTexture {
+    data: Box<[u8]>,
     extent: Extent2,
-
-    level_offsets: SmallVec<[usize; 8]>,
-    transcoded_bytes: Vec<u8>,
+    levels: u32,
 }
 
 #[derive(Clone, Copy, Debug, thiserror::Error)]
@@ -41,9 +47,57 @@ enum TextureError {
     DecodeFailed,
 }
 
+/// Transcoding target along with matching pixel format.
+struct TranscodeTarget {
+    transcode: TranscoderTextureFormat,
+    format: mev::PixelFormat,
+
+    /// Block size in pixels. 1 for uncompressed formats.
+    block: u32,
+
+    /// Bytes per block or pixel.
+    block_bytes: u32,
+}
+
+/// Transcoding targets in order of preference.
+///
+/// BC7 is available on desktop GPUs,
+/// ASTC and ETC2 on mobile and Apple silicon.
+/// Uncompressed RGBA is the last resort and is supported everywhere.
+const TRANSCODE_TARGETS: [TranscodeTarget; 4] = [
+    TranscodeTarget {
+        transcode: TranscoderTextureFormat::BC7_RGBA,
+        format: mev::PixelFormat::Bc7Unorm,
+        block: 4,
+        block_bytes: 16,
+    },
+    TranscodeTarget {
+        transcode: TranscoderTextureFormat::ASTC_4x4_RGBA,
+        format: mev::PixelFormat::Astc4x4Unorm,
+        block: 4,
+        block_bytes: 16,
+    },
+    TranscodeTarget {
+        transcode: TranscoderTextureFormat::ETC2_RGBA,
+        format: mev::PixelFormat::Etc2Rgba8Unorm,
+        block: 4,
+        block_bytes: 16,
+    },
+    TranscodeTarget {
+        transcode: TranscoderTextureFormat::RGBA32,
+        format: mev::PixelFormat::Rgba8Unorm,
+        block: 1,
+        block_bytes: 4,
+    },
+];
+
 impl Asset for Texture {
     type Loaded = LoadedTexture;
 
+    fn target() -> Ident {
+        ident!(texture)
+    }
+
     fn load(
         data: Box<[u8]>,
         assets: &Assets,
@@ -55,56 +109,147 @@ impl Asset for Texture {
         loaded: LoadedTexture,
         builder: &mut AssetBuilder,
     ) -> Result<Self, crate::assets::Error> {
-        let image = builder
-            .device()
-            .new_image(mev::ImageDesc {
+        let basis_format = Transcoder::new().basis_tex_format(&loaded.data);
+
+        for target in &TRANSCODE_TARGETS {
+            if !target.transcode.can_transcode_from(basis_format) {
+                continue;
+            }
+
+            // Device rejects formats it does not support.
+            let Ok(image) = builder.device().new_image(mev::ImageDesc {
                 extent: loaded.extent.into(),
-                format: mev::PixelFormat::Rgba8Unorm,
+                format: target.format,
                 usage: mev::ImageUsage::SAMPLED | mev::ImageUsage::TRANSFER_DST,
                 layers: 1,
-                levels: loaded.level_offsets.len() as u32,
+                levels: loaded.levels,
                 name: "texture",
-            })
-            .map_err(crate::assets::Error::new)?;
-
-        let scratch = builder
-            .device()
-            .new_buffer_init(mev::BufferInitDesc {
-                data: &loaded.transcoded_bytes,
-                usage: mev::BufferUsage::TRANSFER_SRC,
-                memory: mev::Memory::Upload,
-                name: "scratch",
-            })
-            .map_err(crate::assets::Error::new)?;
+            }) else {
+                continue;
+            };
+
+            let levels = transcode_levels(&loaded, target)?;
+            upload_levels(builder, &image, &levels)?;
+
+            return Ok(Texture { image });
+        }
+
+        Err(crate::assets::Error::new(TextureError::FormatNotSupported))
+    }
+}
 
-        let mut encoder = builder.encoder().copy();
+/// Transcoded mip level.
+struct Level {
+    extent: Extent2,
+    bytes: Vec<u8>,
+    bytes_per_line: usize,
+}
 
-        encoder.init_image(
-            mev::PipelineStages::empty(),
-            mev::PipelineStages::all(),
-            &image,
+fn transcode_levels(
+    loaded: &LoadedTexture,
+    target: &TranscodeTarget,
+) -> Result<Vec<Level>, crate::assets::Error> {
+    let mut transcoder = Transcoder::new();
+
+    if let Err(()) = transcoder.prepare_transcoding(&loaded.data) {
+        return Err(crate::assets::Error::new(TextureError::DecodeFailed));
+    }
+
+    let mut levels = Vec::with_capacity(loaded.levels as usize);
+
+    for l in 0..loaded.levels {
+        let extent = Extent2::new(
+            (loaded.extent.width() >> l).max(1),
+            (loaded.extent.height() >> l).max(1),
+        );
+
+        let result = transcoder.transcode_image_level(
+            &loaded.data,
+            target.transcode,
+            TranscodeParameters {
+                image_index: 0,
+                level_index: l,
+                decode_flags: None,
+                output_row_pitch_in_blocks_or_pixels: None,
+                output_rows_in_pixels: None,
+            },
         );
 
-        for (level, offset) in std::iter::once(0).chain(loaded.level_offsets).enumerate() {
-            encoder.copy_buffer_to_image(
-                &scratch,
-                offset,
-                4 * loaded.extent.width() as usize,
-                4 * loaded.extent.width() as usize * loaded.extent.height() as usize,
-                &image,
-                mev::Offset3::ZERO,
-                loaded.extent.to_3d(),
-                0..1,
-                level as u32,
-            );
+        match result {
+            Err(TranscodeError::TranscodeFormatNotSupported) => {
+                return Err(crate::assets::Error::new(TextureError::FormatNotSupported));
+            }
+            Err(TranscodeError::ImageLevelNotFound) => {
+                return Err(crate::assets::Error::new(TextureError::NoImageLevels));
+            }
+            Err(TranscodeError::TranscodeFailed) => {
+                return Err(crate::assets::Error::new(TextureError::DecodeFailed));
+            }
+            Ok(bytes) => {
+                let blocks_per_line = extent.width().div_ceil(target.block);
+                levels.push(Level {
+                    extent,
+                    bytes,
+                    bytes_per_line: (blocks_per_line * target.block_bytes) as usize,
+                });
+            }
         }
+    }
+
+    transcoder.end_transcoding();
+    Ok(levels)
+}
 
-        Ok(Texture { image })
+fn upload_levels(
+    builder: &mut AssetBuilder,
+    image: &mev::Image,
+    levels: &[Level],
+) -> Result<(), crate::assets::Error> {
+    let mut offsets = SmallVec::<[usize; 16]>::new();
+    let mut bytes = Vec::new();
+
+    for level in levels {
+        offsets.push(bytes.len());
+        bytes.extend_from_slice(&level.bytes);
     }
+
+    let scratch = builder
+        .device()
+        .new_buffer_init(mev::BufferInitDesc {
+            data: &bytes,
+            usage: mev::BufferUsage::TRANSFER_SRC,
+            memory: mev::Memory::Upload,
+            name: "scratch",
+        })
+        .map_err(crate::assets::Error::new)?;
+
+    let mut encoder = builder.encoder().copy();
+
+    encoder.init_image(
+        mev::PipelineStages::empty(),
+        mev::PipelineStages::all(),
+        image,
+    );
+
+    for (index, (level, offset)) in levels.iter().zip(offsets).enumerate() {
+        encoder.copy_buffer_to_image(
+            &scratch,
+            offset,
+            level.bytes_per_line,
+            level.bytes.len(),
+            image,
+            mev::Offset3::ZERO,
+            level.extent.to_3d(),
+            0..1,
+            index as u32,
+        );
+    }
+
+    Ok(())
 }
 
 fn load_texture(data: Box<[u8]>, _assets: &Assets) -> Result<LoadedTexture, crate::assets::Error> {
-    let mut transcoder = basis_universal::Transcoder::new();
+    let transcoder = Transcoder::new();
 
     if !transcoder.validate_header(&data) {
         return Err(crate::assets::Error::new(TextureError::InvalidData));
@@ -119,54 +264,15 @@ fn load_texture(data: Box<[u8]>, _assets: &Assets) -> Result<LoadedTexture, crat
 
             let info = transcoder.image_info(&data, 0).unwrap();
 
-            let image_level_count = transcoder.image_level_count(&data, 0);
-            if image_level_count == 0 {
-                return Err(crate::assets::Error::msg("No image levels found"));
-            }
-
-            let mut level_offsets = SmallVec::new();
-            let mut transcoded_bytes = Vec::new();
-
-            for l in 0..image_level_count {
-                if let Err(()) = transcoder.prepare_transcoding(&data) {
-                    return Err(crate::assets::Error::new(TextureError::NoImageLevels));
-                }
-
-                let result = transcoder.transcode_image_level(
-                    &data,
-                    TranscoderTextureFormat::RGBA32,
-                    TranscodeParameters {
-                        image_index: 0,
-                        level_index: l,
-                        decode_flags: None,
-                        output_row_pitch_in_blocks_or_pixels: None,
-                        output_rows_in_pixels: None,
-                    },
-                );
-
-                match result {
-                    Err(TranscodeError::TranscodeFormatNotSupported) => {
-                        return Err(crate::assets::Error::new(TextureError::FormatNotSupported));
-                    }
-                    Err(TranscodeError::ImageLevelNotFound) => {
-                        unreachable!();
-                    }
-                    Err(TranscodeError::TranscodeFailed) => {
-                        return Err(crate::assets::Error::new(TextureError::DecodeFailed))
-                    }
-                    Ok(bytes) => {
-                        if l != 0 {
-                            level_offsets.push(transcoded_bytes.len());
-                        }
-                        transcoded_bytes.extend_from_slice(&bytes);
-                    }
-                }
+            let levels = transcoder.image_level_count(&data, 0);
+            if levels == 0 {
+                return Err(crate::assets::Error::new(TextureError::NoImageLevels));
             }
 
             Ok(LoadedTexture {
+                data,
                 extent: Extent2::new(info.m_width, info.m_height),
-                level_offsets,
-                transcoded_bytes,
+                levels,
             })
         }
         _ => {
@@ -176,3 +282,55 @@ fn load_texture(data: Box<[u8]>, _assets: &Assets) -> Result<LoadedTexture, crat
         }
     }
 }
+
+/// Resource with samplers shared by all jobs and passes.
+///
+/// Samplers are created once per descriptor,
+/// so passes that sample the same way use the same sampler.
+pub struct SamplerCache {
+    samplers: Vec<(mev::SamplerDesc, mev::Sampler)>,
+}
+
+impl SamplerCache {
+    pub const fn new() -> Self {
+        SamplerCache {
+            samplers: Vec::new(),
+        }
+    }
+
+    /// Returns sampler for the descriptor, creating it on first use.
+    pub fn get(
+        &mut self,
+        device: &mev::Device,
+        desc: mev::SamplerDesc,
+    ) -> Result<mev::Sampler, mev::DeviceError> {
+        // There are only few distinct samplers, linear search is fine.
+        if let Some((_, sampler)) = self.samplers.iter().find(|(d, _)| *d == desc) {
+            return Ok(sampler.clone());
+        }
+
+        let sampler = device.new_sampler(desc.clone())?;
+        self.samplers.push((desc, sampler.clone()));
+        Ok(sampler)
+    }
+
+    /// Drops all cached samplers.
+    /// Must be called if device is recreated.
+    pub fn clear(&mut self) {
+        self.samplers.clear();
+    }
+}
+
+/// Returns sampler from [`SamplerCache`] resource.
+///
+/// Creates uncached sampler if the world has no cache.
+pub fn cached_sampler(
+    world: &World,
+    device: &mev::Device,
+    desc: mev::SamplerDesc,
+) -> Result<mev::Sampler, mev::DeviceError> {
+    match world.get_resource_mut::<SamplerCache>() {
+        Some(mut cache) => cache.get(device, desc),
+        None => Ok(device.new_sampler(desc)?),
+    }
+}
@@ -0,0 +1,495 @@
+//! Virtual filesystem for game IO.
+//!
+//! Game reads and writes files by virtual paths like `assets/1f2e3d` or `user/saves/slot1.bin`.
+//! First path component selects mount point, the rest is passed to the [`Mount`].
+//! Same code works with loose files in the editor, archives in cooked builds
+//! and HTTP in web builds, only mounts differ.
+//!
+//! Several mounts may share mount point.
+//! Later ones shadow earlier ones, reads fall through to earlier mounts
+//! when file is not found.
+//!
+//! [`Vfs`] implements asset [`Loader`] reading assets from [`ASSETS`] mount point.
+
+use std::{
+    collections::HashMap,
+    fmt, io,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use futures::future::BoxFuture;
+use parking_lot::RwLock;
+use url::Url;
+
+use crate::assets::{self, Archive, AssetData, AssetId, Loader, NotFound};
+
+/// Mount point for project directory.
+pub const PROJECT: &str = "project";
+
+/// Mount point for assets.
+/// Paths under it are asset IDs.
+pub const ASSETS: &str = "assets";
+
+/// Mount point for user data, like save games.
+pub const USER: &str = "user";
+
+/// Mount point for temporary in-memory files.
+pub const MEMORY: &str = "mem";
+
+#[derive(Debug, thiserror::Error)]
+pub enum VfsError {
+    #[error("File '{0}' not found")]
+    NotFound(String),
+
+    #[error("File '{0}' is on read-only mount")]
+    ReadOnly(String),
+
+    #[error("Invalid path '{0}'")]
+    InvalidPath(String),
+
+    #[error("Failed to access file '{path}'")]
+    Io {
+        path: String,
+
+        #[source]
+        error: io::Error,
+    },
+
+    #[error("Failed to fetch '{url}'")]
+    Http {
+        url: Url,
+
+        #[source]
+        error: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl VfsError {
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, VfsError::NotFound(_))
+    }
+}
+
+/// Source of files attached to the [`Vfs`].
+///
+/// Paths passed to mount are relative to mount point
+/// and use `/` as separator.
+pub trait Mount: Send + Sync + 'static {
+    fn read<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Vec<u8>, VfsError>>;
+
+    /// Writes file, replacing existing one.
+    fn write<'a>(&'a self, path: &'a str, data: &'a [u8]) -> BoxFuture<'a, Result<(), VfsError>> {
+        let _ = data;
+        Box::pin(futures::future::ready(Err(VfsError::ReadOnly(
+            path.to_owned(),
+        ))))
+    }
+
+    fn remove<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<(), VfsError>> {
+        Box::pin(futures::future::ready(Err(VfsError::ReadOnly(
+            path.to_owned(),
+        ))))
+    }
+
+    fn is_writable(&self) -> bool {
+        false
+    }
+}
+
+/// Virtual filesystem.
+#[derive(Clone, Default)]
+pub struct Vfs {
+    /// Mounts in order they were added.
+    mounts: Vec<(String, Arc<dyn Mount>)>,
+}
+
+impl fmt::Debug for Vfs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.mounts.iter().map(|(point, _)| point))
+            .finish()
+    }
+}
+
+impl Vfs {
+    pub fn new() -> Self {
+        Vfs::default()
+    }
+
+    /// Returns filesystem for the game running in the editor.
+    ///
+    /// Project files are read-only,
+    /// assets are loaded by the editor asset store.
+    pub fn editor(project_dir: &Path, game: &str) -> Self {
+        let mut vfs = Vfs::new();
+        vfs.mount(PROJECT, DirMount::read_only(project_dir));
+        if let Some(dir) = user_data_dir(game) {
+            vfs.mount(USER, DirMount::new(dir));
+        }
+        vfs.mount(MEMORY, MemoryMount::new());
+        vfs
+    }
+
+    /// Returns filesystem for desktop builds.
+    ///
+    /// Assets are read from cooked archive,
+    /// user data is stored in platform data directory.
+    pub fn desktop(game: &str, archive: Archive) -> Self {
+        let mut vfs = Vfs::new();
+        vfs.mount(ASSETS, ArchiveMount::new(archive));
+        if let Some(dir) = user_data_dir(game) {
+            vfs.mount(USER, DirMount::new(dir));
+        }
+        vfs.mount(MEMORY, MemoryMount::new());
+        vfs
+    }
+
+    /// Attaches mount at mount point.
+    /// Mount point is single path component.
+    pub fn mount(&mut self, point: &str, mount: impl Mount) {
+        assert!(
+            !point.is_empty() && !point.contains('/'),
+            "Mount point must be single path component"
+        );
+        self.mounts.push((point.to_owned(), Arc::new(mount)));
+    }
+
+    /// Detaches all mounts at mount point.
+    /// Returns true if any were detached.
+    pub fn unmount(&mut self, point: &str) -> bool {
+        let len = self.mounts.len();
+        self.mounts.retain(|(p, _)| p != point);
+        self.mounts.len() != len
+    }
+
+    pub fn is_mounted(&self, point: &str) -> bool {
+        self.mounts.iter().any(|(p, _)| p == point)
+    }
+
+    /// Reads file.
+    /// Tries mounts from latest to earliest until file is found.
+    pub async fn read(&self, path: &str) -> Result<Vec<u8>, VfsError> {
+        let (point, rest) = split_path(path)?;
+
+        for (_, mount) in self.mounts.iter().rev().filter(|(p, _)| p == point) {
+            match mount.read(rest).await {
+                Err(err) if err.is_not_found() => continue,
+                result => return result,
+            }
+        }
+
+        Err(VfsError::NotFound(path.to_owned()))
+    }
+
+    pub async fn read_to_string(&self, path: &str) -> Result<String, VfsError> {
+        let data = self.read(path).await?;
+        String::from_utf8(data).map_err(|err| VfsError::Io {
+            path: path.to_owned(),
+            error: io::Error::new(io::ErrorKind::InvalidData, err),
+        })
+    }
+
+    /// Writes file to the latest writable mount.
+    pub async fn write(&self, path: &str, data: &[u8]) -> Result<(), VfsError> {
+        let (point, rest) = split_path(path)?;
+
+        match self.writable(point) {
+            None => Err(VfsError::ReadOnly(path.to_owned())),
+            Some(mount) => mount.write(rest, data).await,
+        }
+    }
+
+    /// Removes file from the latest writable mount.
+    pub async fn remove(&self, path: &str) -> Result<(), VfsError> {
+        let (point, rest) = split_path(path)?;
+
+        match self.writable(point) {
+            None => Err(VfsError::ReadOnly(path.to_owned())),
+            Some(mount) => mount.remove(rest).await,
+        }
+    }
+
+    pub async fn exists(&self, path: &str) -> bool {
+        self.read(path).await.is_ok()
+    }
+
+    fn writable(&self, point: &str) -> Option<&Arc<dyn Mount>> {
+        self.mounts
+            .iter()
+            .rev()
+            .find(|(p, mount)| p == point && mount.is_writable())
+            .map(|(_, mount)| mount)
+    }
+}
+
+/// Splits virtual path into mount point and relative path.
+fn split_path(path: &str) -> Result<(&str, &str), VfsError> {
+    let path = path.trim_start_matches('/');
+
+    let Some((point, rest)) = path.split_once('/') else {
+        return Err(VfsError::InvalidPath(path.to_owned()));
+    };
+
+    let valid = !rest.is_empty()
+        && rest
+            .split('/')
+            .all(|c| !c.is_empty() && c != "." && c != ".." && !c.contains(['\\', ':']));
+
+    if !valid {
+        return Err(VfsError::InvalidPath(path.to_owned()));
+    }
+
+    Ok((point, rest))
+}
+
+impl Loader for Vfs {
+    fn load<'a>(&'a self, id: AssetId) -> BoxFuture<'a, Result<AssetData, assets::Error>> {
+        Box::pin(async move {
+            match self.read(&format!("{ASSETS}/{id}")).await {
+                Ok(bytes) => Ok(AssetData {
+                    bytes: bytes.into(),
+                    version: 0,
+                }),
+                Err(err) if err.is_not_found() => Err(assets::Error::new(NotFound)),
+                Err(err) => Err(assets::Error::new(err)),
+            }
+        })
+    }
+
+    fn update<'a>(
+        &'a self,
+        _id: AssetId,
+        _version: u64,
+    ) -> BoxFuture<'a, Result<Option<AssetData>, assets::Error>> {
+        // Editor reloads assets through its own store.
+        Box::pin(futures::future::ready(Ok(None)))
+    }
+}
+
+/// Returns directory for user data of the game.
+pub fn user_data_dir(game: &str) -> Option<PathBuf> {
+    let mut path = dirs::data_dir()?;
+    path.push(game);
+    Some(path)
+}
+
+/// Mount of loose files in a directory.
+pub struct DirMount {
+    root: PathBuf,
+    writable: bool,
+}
+
+impl DirMount {
+    /// Returns writable mount of the directory.
+    /// Directory is created on first write.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        DirMount {
+            root: root.into(),
+            writable: true,
+        }
+    }
+
+    pub fn read_only(root: impl Into<PathBuf>) -> Self {
+        DirMount {
+            root: root.into(),
+            writable: false,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Joins relative path to the root one component at a time.
+    /// Rejects anything that could escape the root,
+    /// like `..`, absolute paths or Windows prefixes.
+    fn resolve(&self, path: &str) -> Result<PathBuf, VfsError> {
+        let mut full = self.root.clone();
+        for c in path.split('/') {
+            let mut components = Path::new(c).components();
+            match (components.next(), components.next()) {
+                (Some(Component::Normal(c)), None) => full.push(c),
+                _ => return Err(VfsError::InvalidPath(path.to_owned())),
+            }
+        }
+        Ok(full)
+    }
+
+    fn io_error(path: &str, error: io::Error) -> VfsError {
+        if error.kind() == io::ErrorKind::NotFound {
+            VfsError::NotFound(path.to_owned())
+        } else {
+            VfsError::Io {
+                path: path.to_owned(),
+                error,
+            }
+        }
+    }
+}
+
+impl Mount for DirMount {
+    fn read<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Vec<u8>, VfsError>> {
+        let result = self
+            .resolve(path)
+            .and_then(|full| std::fs::read(full).map_err(|err| Self::io_error(path, err)));
+        Box::pin(futures::future::ready(result))
+    }
+
+    fn write<'a>(&'a self, path: &'a str, data: &'a [u8]) -> BoxFuture<'a, Result<(), VfsError>> {
+        if !self.writable {
+            return Box::pin(futures::future::ready(Err(VfsError::ReadOnly(
+                path.to_owned(),
+            ))));
+        }
+
+        let result = self.resolve(path).and_then(|full| {
+            full.parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|()| std::fs::write(&full, data))
+                .map_err(|err| Self::io_error(path, err))
+        });
+
+        Box::pin(futures::future::ready(result))
+    }
+
+    fn remove<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<(), VfsError>> {
+        if !self.writable {
+            return Box::pin(futures::future::ready(Err(VfsError::ReadOnly(
+                path.to_owned(),
+            ))));
+        }
+
+        let result = self
+            .resolve(path)
+            .and_then(|full| std::fs::remove_file(full).map_err(|err| Self::io_error(path, err)));
+        Box::pin(futures::future::ready(result))
+    }
+
+    fn is_writable(&self) -> bool {
+        self.writable
+    }
+}
+
+/// Mount of files kept in memory.
+#[derive(Default)]
+pub struct MemoryMount {
+    files: RwLock<HashMap<String, Arc<[u8]>>>,
+}
+
+impl MemoryMount {
+    pub fn new() -> Self {
+        MemoryMount::default()
+    }
+
+    /// Adds file to the mount.
+    pub fn with_file(self, path: &str, data: impl Into<Arc<[u8]>>) -> Self {
+        self.files.write().insert(path.to_owned(), data.into());
+        self
+    }
+}
+
+impl Mount for MemoryMount {
+    fn read<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Vec<u8>, VfsError>> {
+        let result = match self.files.read().get(path) {
+            None => Err(VfsError::NotFound(path.to_owned())),
+            Some(data) => Ok(data.to_vec()),
+        };
+        Box::pin(futures::future::ready(result))
+    }
+
+    fn write<'a>(&'a self, path: &'a str, data: &'a [u8]) -> BoxFuture<'a, Result<(), VfsError>> {
+        self.files.write().insert(path.to_owned(), data.into());
+        Box::pin(futures::future::ready(Ok(())))
+    }
+
+    fn remove<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<(), VfsError>> {
+        let result = match self.files.write().remove(path) {
+            None => Err(VfsError::NotFound(path.to_owned())),
+            Some(_) => Ok(()),
+        };
+        Box::pin(futures::future::ready(result))
+    }
+
+    fn is_writable(&self) -> bool {
+        true
+    }
+}
+
+/// Read-only mount of cooked asset archive.
+/// Paths are asset IDs.
+pub struct ArchiveMount {
+    archive: Archive,
+}
+
+impl ArchiveMount {
+    pub fn new(archive: Archive) -> Self {
+        ArchiveMount { archive }
+    }
+
+    pub fn archive(&self) -> &Archive {
+        &self.archive
+    }
+}
+
+impl Mount for ArchiveMount {
+    fn read<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Vec<u8>, VfsError>> {
+        let result = match path.parse::<AssetId>() {
+            Err(_) => Err(VfsError::NotFound(path.to_owned())),
+            Ok(id) => match self.archive.read(id) {
+                None => Err(VfsError::NotFound(path.to_owned())),
+                Some(Ok(data)) => Ok(data.into_vec()),
+                Some(Err(err)) => Err(VfsError::Io {
+                    path: path.to_owned(),
+                    error: io::Error::new(io::ErrorKind::InvalidData, err),
+                }),
+            },
+        };
+        Box::pin(futures::future::ready(result))
+    }
+}
+
+/// Function that fetches content of the URL.
+/// Returns `Ok(None)` if server has no such file.
+pub type Fetch = Box<
+    dyn Fn(
+            Url,
+        )
+            -> BoxFuture<'static, Result<Option<Vec<u8>>, Box<dyn std::error::Error + Send + Sync>>>
+        + Send
+        + Sync,
+>;
+
+/// Read-only mount of files served over HTTP.
+///
+/// Engine does not ship HTTP client,
+/// platform layer provides fetch function, e.g. browser `fetch` in web builds.
+pub struct HttpMount {
+    base: Url,
+    fetch: Fetch,
+}
+
+impl HttpMount {
+    /// Returns mount of files under base URL.
+    /// Base URL should end with `/`, otherwise its last segment is replaced.
+    pub fn new(base: Url, fetch: Fetch) -> Self {
+        HttpMount { base, fetch }
+    }
+}
+
+impl Mount for HttpMount {
+    fn read<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Vec<u8>, VfsError>> {
+        Box::pin(async move {
+            let url = self
+                .base
+                .join(path)
+                .map_err(|_| VfsError::InvalidPath(path.to_owned()))?;
+
+            match (self.fetch)(url.clone()).await {
+                Ok(Some(data)) => Ok(data),
+                Ok(None) => Err(VfsError::NotFound(path.to_owned())),
+                Err(error) => Err(VfsError::Http { url, error }),
+            }
+        })
+    }
+}
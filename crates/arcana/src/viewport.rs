@@ -1,7 +1,7 @@
 //! Contains logic for the viewports.
 
 use edict::component::Component;
-use winit::window::Window;
+use winit::window::{CursorGrabMode, Window};
 
 use crate::make_id;
 
@@ -17,8 +17,15 @@ make_id! {
 ///
 /// `RenderGraph::present` will present to main viewport which is resource in the `World`.
 /// `RenderGraph::present_to` takes `EntityId` where it will look for `Viewport` component.
+///
+/// Game may spawn any number of entities with `Viewport` and `Renderer` components.
+/// Each of them is rendered with own render graph,
+/// and with own camera if `RenderCamera` is attached.
+/// Texture viewports are rendered offscreen and their images
+/// can be sampled by other jobs, e.g. to show a minimap.
 pub struct Viewport {
     kind: ViewportKind,
+    cursor_grabbed: bool,
 }
 
 const SURFACE_RECREATE_TRIES: usize = 2;
@@ -32,6 +39,10 @@ enum ViewportKind {
     Image {
         image: Option<mev::Image>,
     },
+    Texture {
+        image: Option<mev::Image>,
+        extent: mev::Extent2,
+    },
 }
 
 impl Component for Viewport {
@@ -47,12 +58,25 @@ impl Viewport {
                 surface: None,
                 window,
             },
+            cursor_grabbed: false,
         }
     }
 
     pub fn new_image() -> Self {
         Viewport {
             kind: ViewportKind::Image { image: None },
+            cursor_grabbed: false,
+        }
+    }
+
+    /// Returns viewport that renders into own image of the given extent.
+    pub fn new_texture(extent: mev::Extent2) -> Self {
+        Viewport {
+            kind: ViewportKind::Texture {
+                image: None,
+                extent,
+            },
+            cursor_grabbed: false,
         }
     }
 
@@ -64,6 +88,10 @@ impl Viewport {
         matches!(self.kind, ViewportKind::Image { .. })
     }
 
+    pub fn is_texture(&self) -> bool {
+        matches!(self.kind, ViewportKind::Texture { .. })
+    }
+
     pub fn extent(&self) -> mev::Extent2 {
         match &self.kind {
             ViewportKind::Window { window, .. } => {
@@ -72,6 +100,16 @@ impl Viewport {
             }
             ViewportKind::Image { image: Some(image) } => image.extent().expect_2d(),
             ViewportKind::Image { .. } => mev::Extent2::ZERO,
+            ViewportKind::Texture { extent, .. } => *extent,
+        }
+    }
+
+    /// Changes extent of texture viewport.
+    /// Image is reallocated on next frame.
+    pub fn set_extent(&mut self, extent: mev::Extent2) {
+        match &mut self.kind {
+            ViewportKind::Texture { extent: e, .. } => *e = extent,
+            _ => panic!("Cannot set extent to non-texture viewport"),
         }
     }
 
@@ -90,7 +128,9 @@ impl Viewport {
 
     pub fn get_image(&self) -> Option<&mev::Image> {
         match &self.kind {
-            ViewportKind::Image { image, .. } => image.as_ref(),
+            ViewportKind::Image { image, .. } | ViewportKind::Texture { image, .. } => {
+                image.as_ref()
+            }
             _ => panic!("Cannot get image from window viewport"),
         }
     }
@@ -134,9 +174,53 @@ impl Viewport {
                 Some(image) => Ok(Some((image, None))),
                 None => Ok(None),
             },
+            ViewportKind::Texture { image, extent } => {
+                if extent.width() == 0 || extent.height() == 0 {
+                    image.take();
+                    return Ok(None);
+                }
+
+                if image.as_ref().map_or(true, |i| i.extent() != *extent) {
+                    let new_image = queue.device().new_image(mev::ImageDesc {
+                        extent: (*extent).into(),
+                        format: mev::PixelFormat::Rgba8Srgb,
+                        usage: mev::ImageUsage::TARGET
+                            | mev::ImageUsage::SAMPLED
+                            | mev::ImageUsage::STORAGE
+                            | mev::ImageUsage::TRANSFER_SRC,
+                        layers: 1,
+                        levels: 1,
+                        name: "Viewport Texture",
+                    })?;
+                    *image = Some(new_image);
+                }
+
+                Ok(image.clone().map(|image| (image, None)))
+            }
         }
     }
 
+    /// Grabs or releases the cursor.
+    ///
+    /// For window viewport cursor is locked and hidden immediately.
+    /// Image viewport only records the state, it is up to the host
+    /// to grab the cursor when image is focused.
+    pub fn set_cursor_grab(&mut self, grab: bool) {
+        if self.cursor_grabbed == grab {
+            return;
+        }
+
+        if let ViewportKind::Window { window, .. } = &self.kind {
+            set_window_cursor_grab(window, grab);
+        }
+
+        self.cursor_grabbed = grab;
+    }
+
+    pub fn is_cursor_grabbed(&self) -> bool {
+        self.cursor_grabbed
+    }
+
     #[doc(hidden)]
     pub fn get_window(&self) -> &Window {
         match &self.kind {
@@ -153,3 +237,26 @@ impl Viewport {
         }
     }
 }
+
+/// Locks and hides cursor in the window or restores it.
+///
+/// Not all platforms support locking cursor,
+/// so it falls back to confining cursor to the window.
+#[doc(hidden)]
+pub fn set_window_cursor_grab(window: &Window, grab: bool) {
+    if grab {
+        let result = window
+            .set_cursor_grab(CursorGrabMode::Locked)
+            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined));
+
+        if let Err(err) = result {
+            tracing::warn!("Failed to grab cursor: {err}");
+        }
+        window.set_cursor_visible(false);
+    } else {
+        if let Err(err) = window.set_cursor_grab(CursorGrabMode::None) {
+            tracing::warn!("Failed to release cursor: {err}");
+        }
+        window.set_cursor_visible(true);
+    }
+}
@@ -0,0 +1,267 @@
+//! Game window configuration.
+//!
+//! [`WindowConfig`] from project manifest is inserted into the world as a resource.
+//! Host creates game window with [`window_attributes`]
+//! and calls [`WindowSync::update`] each frame,
+//! so changes systems make to the resource are applied to the window.
+//!
+//! Game asks the host to exit through [`ExitRequest`] resource.
+
+use std::{future::Future, task::Poll};
+
+use arcana_names::{ident, Ident};
+use edict::world::World;
+use winit::{
+    dpi::LogicalSize,
+    window::{Fullscreen, Icon, Window, WindowAttributes},
+};
+
+pub use arcana_project::{WindowConfig, WindowMode};
+
+use crate::assets::{self, Asset, AssetBuilder, AssetId, Assets};
+
+/// Image for window icon.
+///
+/// Stored as an asset in the form of serialized RGBA8 pixels.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct WindowIcon {
+    pub width: u32,
+    pub height: u32,
+
+    /// RGBA8 pixels, row by row.
+    pub rgba: Vec<u8>,
+}
+
+impl WindowIcon {
+    /// Encodes icon into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Window icon serialization cannot fail")
+    }
+
+    fn to_icon(&self) -> Option<Icon> {
+        match Icon::from_rgba(self.rgba.clone(), self.width, self.height) {
+            Ok(icon) => Some(icon),
+            Err(err) => {
+                tracing::error!("Invalid window icon: {err}");
+                None
+            }
+        }
+    }
+}
+
+impl Asset for WindowIcon {
+    type Loaded = WindowIcon;
+
+    fn target() -> Ident {
+        ident!(window_icon)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<WindowIcon, assets::Error>> + Send {
+        futures::future::ready(load_window_icon(&data))
+    }
+
+    fn build(loaded: WindowIcon, _builder: &mut AssetBuilder) -> Result<Self, assets::Error> {
+        Ok(loaded)
+    }
+}
+
+fn load_window_icon(data: &[u8]) -> Result<WindowIcon, assets::Error> {
+    let icon: WindowIcon = bincode::deserialize(data).map_err(assets::Error::new)?;
+
+    if icon.rgba.len() != icon.width as usize * icon.height as usize * 4 {
+        return Err(assets::Error::msg("Window icon size mismatch"));
+    }
+
+    Ok(icon)
+}
+
+/// Returns id of the icon asset set in the config.
+pub fn icon_asset(config: &WindowConfig) -> Option<AssetId> {
+    let icon = config.icon.as_ref()?;
+    match icon.parse() {
+        Ok(id) => Some(id),
+        Err(err) => {
+            tracing::error!("Invalid window icon id '{icon}': {err}");
+            None
+        }
+    }
+}
+
+/// Returns attributes to create game window with.
+///
+/// `name` is used as title if config does not set one.
+/// Exclusive fullscreen starts as borderless since video mode
+/// can be chosen only when window's monitor is known.
+/// Icon is set by [`WindowSync`] once the asset is loaded.
+pub fn window_attributes(config: &WindowConfig, name: &str) -> WindowAttributes {
+    let fullscreen = match config.mode {
+        WindowMode::Windowed => None,
+        WindowMode::Fullscreen | WindowMode::Borderless => Some(Fullscreen::Borderless(None)),
+    };
+
+    let mut attributes = Window::default_attributes()
+        .with_title(config.title.as_deref().unwrap_or(name))
+        .with_inner_size(LogicalSize::new(config.size[0], config.size[1]))
+        .with_resizable(config.resizable)
+        .with_fullscreen(fullscreen);
+
+    if let Some([width, height]) = config.min_size {
+        attributes = attributes.with_min_inner_size(LogicalSize::new(width, height));
+    }
+
+    attributes
+}
+
+/// Keeps game window in sync with [`WindowConfig`] resource.
+pub struct WindowSync {
+    name: String,
+
+    /// Config last applied to the window.
+    applied: WindowConfig,
+
+    /// Icon asset that is being loaded.
+    loading_icon: Option<AssetId>,
+}
+
+impl WindowSync {
+    /// Returns sync state for window created with [`window_attributes`].
+    pub fn new(config: &WindowConfig, name: &str) -> Self {
+        let mut applied = config.clone();
+
+        if applied.mode == WindowMode::Fullscreen {
+            // Switched to exclusive on first update.
+            applied.mode = WindowMode::Borderless;
+        }
+
+        WindowSync {
+            name: name.to_owned(),
+            applied,
+            loading_icon: icon_asset(config),
+        }
+    }
+
+    /// Applies changes of [`WindowConfig`] resource to the window.
+    pub fn update(&mut self, window: &Window, world: &World) {
+        if let Some(config) = world.get_resource::<WindowConfig>() {
+            if *config != self.applied {
+                self.apply(window, &config);
+            }
+        }
+
+        let Some(id) = self.loading_icon else {
+            return;
+        };
+
+        let Some(assets) = world.get_resource::<Assets>() else {
+            return;
+        };
+
+        match assets.get::<WindowIcon>(id) {
+            Poll::Pending => {}
+            Poll::Ready(Ok(icon)) => {
+                self.loading_icon = None;
+                window.set_window_icon(icon.to_icon());
+            }
+            Poll::Ready(Err(err)) => {
+                self.loading_icon = None;
+                tracing::error!("Failed to load window icon {id}: {err}");
+            }
+        }
+    }
+
+    fn apply(&mut self, window: &Window, config: &WindowConfig) {
+        if config.title != self.applied.title {
+            window.set_title(config.title.as_deref().unwrap_or(&self.name));
+        }
+
+        if config.size != self.applied.size {
+            let _ = window.request_inner_size(LogicalSize::new(config.size[0], config.size[1]));
+        }
+
+        if config.mode != self.applied.mode {
+            window.set_fullscreen(fullscreen(window, config.mode));
+        }
+
+        if config.resizable != self.applied.resizable {
+            window.set_resizable(config.resizable);
+        }
+
+        if config.min_size != self.applied.min_size {
+            window.set_min_inner_size(
+                config
+                    .min_size
+                    .map(|[width, height]| LogicalSize::new(width, height)),
+            );
+        }
+
+        if config.icon != self.applied.icon {
+            self.loading_icon = icon_asset(config);
+            if self.loading_icon.is_none() {
+                window.set_window_icon(None);
+            }
+        }
+
+        self.applied = config.clone();
+    }
+}
+
+/// Picks fullscreen mode for the window.
+/// Exclusive fullscreen uses largest video mode of the current monitor.
+fn fullscreen(window: &Window, mode: WindowMode) -> Option<Fullscreen> {
+    match mode {
+        WindowMode::Windowed => None,
+        WindowMode::Borderless => Some(Fullscreen::Borderless(None)),
+        WindowMode::Fullscreen => {
+            let video_mode = window.current_monitor().and_then(|monitor| {
+                monitor.video_modes().max_by_key(|mode| {
+                    let size = mode.size();
+                    (size.width * size.height, mode.refresh_rate_millihertz())
+                })
+            });
+
+            match video_mode {
+                Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                None => Some(Fullscreen::Borderless(None)),
+            }
+        }
+    }
+}
+
+/// Inserts window config resource.
+pub fn init_window_config(world: &mut World, config: &WindowConfig) {
+    world.insert_resource(config.clone());
+}
+
+/// Resource through which game asks the host to exit.
+///
+/// Host checks it after each tick.
+/// Game host closes the window and exits,
+/// Ed pauses the instance instead.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExitRequest {
+    requested: bool,
+}
+
+impl ExitRequest {
+    pub const fn new() -> Self {
+        ExitRequest { requested: false }
+    }
+
+    /// Asks the host to exit.
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+
+    /// Returns true if exit was requested and clears the request.
+    pub fn take(&mut self) -> bool {
+        std::mem::take(&mut self.requested)
+    }
+}
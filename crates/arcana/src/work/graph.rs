@@ -1,7 +1,9 @@
 use std::{
     borrow::Borrow,
     cell::{Cell, RefCell},
-    hash::Hash,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    time::{Duration, Instant},
 };
 
 use arcana_names::Name;
@@ -10,16 +12,18 @@ use hashbrown::{hash_map::Entry, HashMap, HashSet};
 use slab::Slab;
 
 use crate::{
-    arena::Arena, id::IdGen, model::Value, plugin::PluginsHub, work::job::invalid_output_pin, Stid,
+    arena::Arena, id::IdGen, model::Value, plugin::PluginsHub, profile,
+    work::job::invalid_output_pin, Stid,
 };
 
 use super::{
     job::{JobDesc, JobId},
     target::{Target, TargetHub, TargetId},
+    Image2D, SampledImage2D,
 };
 
 /// Index of a job in work graph.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 #[repr(transparent)]
 pub struct JobIdx(pub usize);
 
@@ -43,15 +47,52 @@ pub struct WorkGraph {
     // Cleared after each run.
     selected_jobs: HashSet<JobIdx>,
     cbufs: Arena<mev::CommandEncoder>,
+
+    /// Timings of jobs executed in last run.
+    timings: Vec<JobTiming>,
+
+    /// Estimated memory of targets allocated for last run.
+    target_memory: u64,
+
+    /// Runs submitted and completed by GPU.
+    frames: Frames,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// Counts runs of the work graph.
+///
+/// There is no way to poll completion of a single submission,
+/// so GPU is known to complete a run only after waiting for the queue to go idle.
+#[derive(Default)]
+struct Frames {
+    /// Index of the current run.
+    current: u64,
+
+    /// All runs before this one are completed.
+    completed: Cell<u64>,
+}
+
+/// Time spent by a job in last run of the work graph.
+///
+/// Measured on CPU around planning and command encoding,
+/// time GPU spends executing recorded commands is not included.
+#[derive(Clone, Copy, Debug)]
+pub struct JobTiming {
+    pub idx: JobIdx,
+    pub id: JobId,
+    pub plan: Duration,
+    pub exec: Duration,
+
+    /// Job declared inputs that were unchanged, so `exec` was skipped.
+    pub cached: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct PinId {
     pub job: JobIdx,
     pub pin: usize,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct Edge {
     pub from: PinId,
     pub to: PinId,
@@ -228,6 +269,9 @@ impl WorkGraph {
             sinks: HashMap::new(),
             selected_jobs: HashSet::new(),
             cbufs: Arena::new(),
+            timings: Vec::new(),
+            target_memory: 0,
+            frames: Frames::default(),
         })
     }
 
@@ -346,6 +390,16 @@ impl WorkGraph {
         let _ = job.hooks.try_remove(id.hook);
     }
 
+    /// Returns timings of jobs executed in last run in execution order.
+    pub fn timings(&self) -> &[JobTiming] {
+        &self.timings
+    }
+
+    /// Returns estimated memory of image targets allocated for last run.
+    pub fn target_memory(&self) -> u64 {
+        self.target_memory
+    }
+
     pub fn run(
         &mut self,
         queue: &mut mev::Queue,
@@ -353,6 +407,7 @@ impl WorkGraph {
         hub: &mut PluginsHub,
     ) -> Result<(), mev::DeviceError> {
         self.selected_jobs.clear();
+        self.timings.clear();
 
         for (&PinId { job, .. }, _) in &self.sinks {
             self.selected_jobs.insert(job);
@@ -365,6 +420,8 @@ impl WorkGraph {
             if !self.selected_jobs.contains(&job.idx) {
                 continue;
             }
+            let _span = profile::job_plan(job.id);
+            let start = Instant::now();
             job.plan(
                 &mut self.hub,
                 &mut self.selected_jobs,
@@ -372,16 +429,48 @@ impl WorkGraph {
                 world,
                 hub,
             );
+            self.timings.push(JobTiming {
+                idx: job.idx,
+                id: job.id,
+                plan: start.elapsed(),
+                exec: Duration::ZERO,
+                cached: false,
+            });
         }
 
+        // Planning went in reverse order.
+        self.timings.reverse();
+
+        // All targets are allocated by now.
+        self.target_memory = self
+            .hub
+            .allocated::<Image2D>()
+            .map(|info| info.estimated_size())
+            .chain(
+                self.hub
+                    .allocated::<SampledImage2D>()
+                    .map(|info| info.estimated_size()),
+            )
+            .sum();
+
         for job in self.plan.iter_mut() {
             if !self.selected_jobs.contains(&job.idx) {
                 continue;
             }
-            job.exec(&mut self.hub, queue, &self.cbufs, world, hub);
+            let _span = profile::job_exec(job.id);
+            let start = Instant::now();
+            let cached = job.exec(&mut self.hub, queue, &self.cbufs, &self.frames, world, hub);
+            let elapsed = start.elapsed();
+
+            if let Some(timing) = self.timings.iter_mut().find(|t| t.idx == job.idx) {
+                timing.exec = elapsed;
+                timing.cached = cached;
+            }
         }
 
-        queue.submit(self.cbufs.drain().filter_map(|e| e.finish().ok()), true)
+        let result = queue.submit(self.cbufs.drain().filter_map(|e| e.finish().ok()), true);
+        self.frames.current += 1;
+        result
     }
 }
 
@@ -406,6 +495,9 @@ pub struct Planner<'a> {
     idx: JobIdx,
 
     params: &'a HashMap<Name, Value>,
+
+    /// Inputs declared by the job.
+    inputs: &'a mut Option<DefaultHasher>,
 }
 
 impl Planner<'_> {
@@ -466,11 +558,25 @@ impl Planner<'_> {
     {
         &self.params[name]
     }
+
+    /// Declares inputs the job reads from the world or its own state.
+    ///
+    /// Jobs that declare inputs are cached.
+    /// Their `exec` is skipped when declared inputs are equal to ones of last `exec`
+    /// and targets the job reads and updates were not changed since,
+    /// leaving outputs as they were.
+    /// Params can't change without rebuilding the work graph, so they don't need to be declared.
+    ///
+    /// Multiple calls declare all inputs passed.
+    pub fn cache_inputs(&mut self, inputs: &impl Hash) {
+        inputs.hash(self.inputs.get_or_insert_with(DefaultHasher::new));
+    }
 }
 
 pub struct CommandStream<'a> {
     queue: RefCell<&'a mut mev::Queue>,
     cbufs: &'a Arena<mev::CommandEncoder>,
+    frames: &'a Frames,
 }
 
 impl CommandStream<'_> {
@@ -483,6 +589,28 @@ impl CommandStream<'_> {
         let encoder = self.queue.borrow_mut().new_command_encoder().unwrap();
         self.cbufs.put(encoder)
     }
+
+    /// Returns index of the work graph run commands are recorded for.
+    ///
+    /// Commands are submitted at the end of the run.
+    pub fn frame(&self) -> u64 {
+        self.frames.current
+    }
+
+    /// Blocks until GPU completes commands submitted in `frame` run of the work graph.
+    ///
+    /// Waits for the whole queue, which also completes all runs before the current one.
+    /// Following calls for those runs return immediately.
+    /// Jobs that read results back keep a few frames of buffers,
+    /// so waiting happens once in a few frames and commands are likely completed by then.
+    pub fn wait_frame(&self, frame: u64) -> Result<(), mev::DeviceError> {
+        // Runs of the graph this one replaced are waited for as well.
+        if frame >= self.frames.completed.get() {
+            self.queue.borrow_mut().wait_idle()?;
+            self.frames.completed.set(self.frames.current);
+        }
+        Ok(())
+    }
 }
 
 pub struct Exec<'a> {
@@ -562,6 +690,22 @@ impl Exec<'_> {
         self.commands.new_encoder()
     }
 
+    /// Returns index of the work graph run.
+    ///
+    /// See [`CommandStream::frame`].
+    pub fn frame(&self) -> u64 {
+        self.commands.frame()
+    }
+
+    /// Blocks until GPU completes commands submitted in `frame` run.
+    ///
+    /// Jobs must call this before reading buffers written by GPU in earlier runs
+    /// or writing buffers GPU may still read.
+    /// See [`CommandStream::wait_frame`].
+    pub fn wait_frame(&self, frame: u64) -> Result<(), mev::DeviceError> {
+        self.commands.wait_frame(frame)
+    }
+
     /// Returns reference to device.
     pub fn device(&self) -> &mev::Device {
         &self.device
@@ -616,6 +760,12 @@ struct TargetRead {
     dep_idx: Option<JobIdx>,
 }
 
+/// State of inputs and targets after last `exec` of a cached job.
+struct JobCache {
+    inputs: u64,
+    versions: Vec<u64>,
+}
+
 // #[derive(Debug)]
 struct JobNode {
     idx: JobIdx,
@@ -625,6 +775,10 @@ struct JobNode {
     creates: Vec<TargetCreate>,
     reads: Vec<TargetRead>,
     hooks: Slab<Box<dyn FnMut(&TargetHub, &mev::Device, &CommandStream)>>,
+
+    /// Inputs declared during last planning.
+    inputs: Option<DefaultHasher>,
+    cache: Option<JobCache>,
 }
 
 impl JobNode {
@@ -662,6 +816,8 @@ impl JobNode {
                 })
                 .collect(),
             hooks: Slab::new(),
+            inputs: None,
+            cache: None,
         }
     }
 
@@ -673,6 +829,8 @@ impl JobNode {
         world: &mut World,
         plugins: &mut PluginsHub,
     ) {
+        self.inputs = None;
+
         let planner = Planner {
             updates: self.updates.iter(),
             creates: self.creates.iter(),
@@ -682,6 +840,7 @@ impl JobNode {
             device,
             idx: self.idx,
             params: &self.params,
+            inputs: &mut self.inputs,
         };
 
         if let Some(job) = plugins.jobs.get_mut(&self.id) {
@@ -689,11 +848,61 @@ impl JobNode {
         }
     }
 
+    /// Executes the job unless it is cached.
+    /// Returns true if `exec` was skipped.
     fn exec(
         &mut self,
         hub: &mut TargetHub,
         queue: &mut mev::Queue,
         cbufs: &Arena<mev::CommandEncoder>,
+        frames: &Frames,
+        world: &mut World,
+        plugins: &mut PluginsHub,
+    ) -> bool {
+        let device = queue.device().clone();
+
+        let inputs = self.inputs.take().map(|hasher| hasher.finish());
+        let cached = match (&self.cache, inputs) {
+            (Some(cache), Some(inputs)) => {
+                cache.inputs == inputs && cache.versions == self.versions(hub)
+            }
+            _ => false,
+        };
+
+        if !cached {
+            self.exec_job(hub, queue, cbufs, frames, world, plugins);
+
+            for id in self.output_ids() {
+                hub.touch(id);
+            }
+
+            // Versions are taken after outputs are written,
+            // so that changes by other jobs are noticed.
+            self.cache = inputs.map(|inputs| JobCache {
+                inputs,
+                versions: self.versions(hub),
+            });
+        }
+
+        let commands = CommandStream {
+            queue: RefCell::new(queue),
+            cbufs,
+            frames,
+        };
+
+        for (_, hook) in self.hooks.iter_mut() {
+            hook(hub, &device, &commands);
+        }
+
+        cached
+    }
+
+    fn exec_job(
+        &mut self,
+        hub: &mut TargetHub,
+        queue: &mut mev::Queue,
+        cbufs: &Arena<mev::CommandEncoder>,
+        frames: &Frames,
         world: &mut World,
         plugins: &mut PluginsHub,
     ) {
@@ -702,6 +911,7 @@ impl JobNode {
         let commands = CommandStream {
             queue: RefCell::new(queue),
             cbufs,
+            frames,
         };
 
         let exec = Exec {
@@ -712,7 +922,7 @@ impl JobNode {
             reads: &self.reads,
             next_read: Cell::new(0),
             hub,
-            device: device.clone(),
+            device,
             commands,
             idx: self.idx,
             params: &self.params,
@@ -721,15 +931,22 @@ impl JobNode {
         if let Some(job) = plugins.jobs.get_mut(&self.id) {
             job.exec(exec, world);
         }
+    }
 
-        let commands = CommandStream {
-            queue: RefCell::new(queue),
-            cbufs,
-        };
+    /// Targets the job writes.
+    fn output_ids(&self) -> impl Iterator<Item = TargetId> + '_ {
+        let updates = self.updates.iter().filter_map(|u| u.id);
+        let creates = self.creates.iter().filter_map(|c| c.id);
+        updates.chain(creates)
+    }
 
-        for (_, hook) in self.hooks.iter_mut() {
-            hook(hub, &device, &commands);
-        }
+    /// Versions of all targets the job works on.
+    fn versions(&self, hub: &TargetHub) -> Vec<u64> {
+        let reads = self.reads.iter().filter_map(|r| r.id);
+        self.output_ids()
+            .chain(reads)
+            .map(|id| hub.version(id))
+            .collect()
     }
 
     // fn update_idx(&self, pin: usize) -> Option<usize> {
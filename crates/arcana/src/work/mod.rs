@@ -1,19 +1,37 @@
 //! This module contains GPU work-graph implementation.
 //! Work graph consists of jobs that declare resources they work on and set of edges between them.
 //! Jobs work in isolation except for shared resoruces they declared.
+//! Jobs that declare their inputs with `Planner::cache_inputs` are skipped
+//! while inputs and targets they work on stay unchanged.
+//!
+//! Besides GPU resources jobs may pass CPU values to each other with [`CpuValue`] target.
+//! Jobs that only work with values don't record commands,
+//! but run in the same dependency order, so procedural generation
+//! and data preparation can feed rendering jobs directly.
 
 mod graph;
 mod job;
+mod preset;
 mod target;
+mod validate;
 
-use std::ops::Deref;
+use std::{
+    cell::{Ref, RefCell},
+    ops::Deref,
+};
 
 use arcana_proc::WithStid;
 
+use crate::model::{Model, Value};
+
 pub use self::{
-    graph::{CommandStream, Cycle, Edge, Exec, HookId, JobIdx, PinId, Planner, WorkGraph},
+    graph::{
+        CommandStream, Cycle, Edge, Exec, HookId, JobIdx, JobTiming, PinId, Planner, WorkGraph,
+    },
     job::{Job, JobDesc, JobId, TargetCreateDesc, TargetReadDesc, TargetUpdateDesc},
+    preset::{load_graph, GraphPreset, GraphPresets, PresetJob},
     target::{Target, TargetHub, TargetId},
+    validate::{validate, GraphError},
 };
 
 /// Generic 2d image target.
@@ -45,6 +63,25 @@ impl Image2DInfo {
             usage: image.usage(),
         }
     }
+
+    /// Returns estimated size of the image in bytes.
+    pub fn estimated_size(&self) -> u64 {
+        estimated_image_size(self.extent, self.format)
+    }
+}
+
+/// Estimates memory of single-level 2d image.
+/// Unknown formats are assumed to use 4 bytes per texel.
+fn estimated_image_size(extent: mev::Extent2, format: mev::PixelFormat) -> u64 {
+    let texels = extent.width() as u64 * extent.height() as u64;
+
+    match format {
+        // Block-compressed formats use 16 bytes per 4x4 block.
+        mev::PixelFormat::Bc7Unorm
+        | mev::PixelFormat::Etc2Rgba8Unorm
+        | mev::PixelFormat::Astc4x4Unorm => texels,
+        _ => texels * 4,
+    }
 }
 
 impl target::Target for Image2D {
@@ -91,6 +128,13 @@ pub struct SampledImage2DInfo {
     pub usage: mev::ImageUsage,
 }
 
+impl SampledImage2DInfo {
+    /// Returns estimated size of the image in bytes.
+    pub fn estimated_size(&self) -> u64 {
+        estimated_image_size(self.extent, self.format)
+    }
+}
+
 impl target::Target for SampledImage2D {
     type Info = SampledImage2DInfo;
 
@@ -124,3 +168,43 @@ impl target::Target for SampledImage2D {
         true
     }
 }
+
+/// CPU value passed between jobs.
+///
+/// Job that creates the value sets it in `exec`,
+/// jobs that read it are executed later and see the new value.
+#[derive(Debug, WithStid)]
+pub struct CpuValue {
+    value: RefCell<Value>,
+}
+
+impl CpuValue {
+    pub fn get(&self) -> Ref<'_, Value> {
+        self.value.borrow()
+    }
+
+    pub fn set(&self, value: Value) {
+        *self.value.borrow_mut() = value;
+    }
+}
+
+/// Model of the value readers expect.
+/// Readers must agree on the model.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CpuValueInfo {
+    pub model: Model,
+}
+
+impl target::Target for CpuValue {
+    type Info = CpuValueInfo;
+
+    fn allocate(_device: &mev::Device, _name: &str, info: &CpuValueInfo) -> Self {
+        CpuValue {
+            value: RefCell::new(info.model.default_value()),
+        }
+    }
+
+    fn merge_info(info: &mut CpuValueInfo, other: &CpuValueInfo) -> bool {
+        info.model == other.model
+    }
+}
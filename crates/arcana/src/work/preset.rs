@@ -0,0 +1,182 @@
+use std::future::Future;
+
+use arcana_names::{ident, Ident, Name};
+use edict::world::World;
+use hashbrown::{HashMap, HashSet};
+
+use crate::{
+    assets::{self, Asset, AssetBuilder, AssetServer, Assets, Handle},
+    hash_id,
+    model::Value,
+    render::RenderGraphId,
+};
+
+use super::{
+    graph::{Cycle, Edge, JobIdx, PinId, WorkGraph},
+    job::{JobDesc, JobId},
+    validate::{validate, GraphError},
+};
+
+/// Job of the [`GraphPreset`] with its parameter values.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct PresetJob {
+    pub idx: JobIdx,
+    pub job: JobId,
+    pub desc: JobDesc,
+
+    #[serde(default)]
+    pub params: HashMap<Name, Value>,
+}
+
+/// Configured job graph saved as an asset.
+///
+/// Editor saves render graphs as presets,
+/// so they can be shared between projects
+/// and instantiated from game code with [`load_graph`].
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GraphPreset {
+    pub name: Name,
+
+    #[serde(default)]
+    pub jobs: Vec<PresetJob>,
+
+    #[serde(default)]
+    pub edges: Vec<Edge>,
+
+    /// Output presented to the viewport.
+    #[serde(default)]
+    pub present: Option<PinId>,
+}
+
+impl GraphPreset {
+    pub fn from_json(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Graph preset serialization cannot fail")
+    }
+
+    /// Encodes preset into format loaded by the asset.
+    /// Intended for importers.
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Graph preset serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, assets::Error> {
+        bincode::deserialize(data).map_err(assets::Error::new)
+    }
+
+    /// Validates the preset.
+    pub fn validate(&self) -> Vec<GraphError> {
+        let (jobs, edges, sinks) = self.parts();
+        validate(&jobs, &edges, &sinks)
+    }
+
+    /// Builds work graph from the preset.
+    /// Fails if validation finds fatal errors.
+    pub fn make_work_graph(&self) -> Result<WorkGraph, Vec<GraphError>> {
+        let (jobs, edges, sinks) = self.parts();
+
+        let errors = validate(&jobs, &edges, &sinks);
+        if errors.iter().any(GraphError::is_fatal) {
+            return Err(errors);
+        }
+
+        WorkGraph::new(jobs, edges).map_err(|Cycle| errors)
+    }
+
+    fn parts(
+        &self,
+    ) -> (
+        HashMap<JobIdx, (JobId, JobDesc, HashMap<Name, Value>)>,
+        HashSet<Edge>,
+        HashSet<PinId>,
+    ) {
+        let jobs = self
+            .jobs
+            .iter()
+            .map(|job| (job.idx, (job.job, job.desc.clone(), job.params.clone())))
+            .collect();
+
+        let edges = self.edges.iter().copied().collect();
+        let sinks = self.present.into_iter().collect();
+
+        (jobs, edges, sinks)
+    }
+}
+
+impl Asset for GraphPreset {
+    type Loaded = GraphPreset;
+
+    fn target() -> Ident {
+        ident!(job_graph)
+    }
+
+    fn load(
+        data: Box<[u8]>,
+        _assets: &Assets,
+    ) -> impl Future<Output = Result<GraphPreset, assets::Error>> + Send {
+        futures::future::ready(GraphPreset::decode(&data))
+    }
+
+    fn build(loaded: GraphPreset, _builder: &mut AssetBuilder) -> Result<Self, assets::Error> {
+        Ok(loaded)
+    }
+}
+
+/// Resource with presets loaded by game code.
+/// Renderers refer to them by IDs returned from [`load_graph`].
+#[derive(Default)]
+pub struct GraphPresets {
+    handles: HashMap<RenderGraphId, Handle<GraphPreset>>,
+}
+
+impl GraphPresets {
+    pub fn new() -> Self {
+        GraphPresets::default()
+    }
+
+    /// Returns handle of the preset loaded for the graph ID.
+    pub fn handle(&self, id: RenderGraphId) -> Option<&Handle<GraphPreset>> {
+        self.handles.get(&id)
+    }
+
+    /// Returns preset if it is loaded.
+    pub fn get(&self, id: RenderGraphId) -> Option<GraphPreset> {
+        self.handles.get(&id)?.get()
+    }
+
+    /// Forgets preset loaded for the graph ID.
+    pub fn unload(&mut self, id: RenderGraphId) {
+        self.handles.remove(&id);
+    }
+}
+
+/// Starts loading job graph preset from the source
+/// and returns ID for the [`Renderer`](crate::render::Renderer) that renders with it.
+///
+/// Same source always maps to the same ID,
+/// so switching pipelines is a matter of changing renderer's graph.
+///
+/// Requires [`AssetServer`] resource.
+pub fn load_graph(world: &mut World, source: &str) -> RenderGraphId {
+    let id = hash_id!(source => RenderGraphId);
+
+    let handle = world
+        .expect_resource_mut::<AssetServer>()
+        .load::<GraphPreset>(source);
+
+    match world.get_resource_mut::<GraphPresets>() {
+        Some(mut presets) => {
+            presets.handles.insert(id, handle);
+        }
+        None => {
+            let mut presets = GraphPresets::new();
+            presets.handles.insert(id, handle);
+            world.insert_resource(presets);
+        }
+    }
+
+    id
+}
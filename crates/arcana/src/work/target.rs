@@ -103,27 +103,38 @@ where
     }
 
     pub fn plan_create<'a>(&'a mut self, name: &str, device: &mev::Device) -> Option<&'a T::Info> {
-        if let Some((_, info)) = &self.external {
-            return Some(info);
+        self.allocate(name, device)?;
+        self.info()
+    }
+
+    /// Allocates target for the info planned by readers.
+    ///
+    /// Returns `None` if there are no readers,
+    /// otherwise returns true if new instance was allocated.
+    fn allocate(&mut self, name: &str, device: &mev::Device) -> Option<bool> {
+        if self.external.is_some() {
+            return Some(false);
         }
 
         let new_info = self.new_info.take()?;
 
         if let Some((_, info)) = &self.target {
-            if *info != new_info {
-                self.target = None;
+            if *info == new_info {
+                return Some(false);
             }
         }
 
-        if self.target.is_none() {
-            let instance = T::allocate(device, name, &new_info);
-            self.target = Some((instance, new_info));
-        }
+        let instance = T::allocate(device, name, &new_info);
+        self.target = Some((instance, new_info));
+        Some(true)
+    }
 
-        match &self.target {
-            Some((_, info)) => Some(info),
-            None => unreachable!(),
+    fn info(&self) -> Option<&T::Info> {
+        if let Some((_, info)) = &self.external {
+            return Some(info);
         }
+
+        self.target.as_ref().map(|(_, info)| info)
     }
 
     pub fn plan_update(&mut self) -> Option<&T::Info> {
@@ -160,12 +171,28 @@ where
 
 pub struct TargetHub {
     types: NoHashMap<TypeId, AnyHashMap<TargetId>>,
+
+    /// Versions of target contents.
+    /// Changed when target is replaced or written by a job.
+    versions: HashMap<TargetId, u64>,
+    next_version: u64,
+}
+
+fn typed_data_mut<T: Target>(
+    types: &mut NoHashMap<TypeId, AnyHashMap<TargetId>>,
+    id: TargetId,
+) -> Option<&mut TargetData<T>> {
+    let any_hub = types.get_mut(&type_id::<T>())?;
+    let typed_hub = unsafe { any_hub.downcast_mut::<TargetData<T>>() };
+    typed_hub.get_mut(&id)
 }
 
 impl TargetHub {
-    pub const fn new() -> Self {
+    pub fn new() -> Self {
         TargetHub {
             types: no_hash_map(),
+            versions: HashMap::new(),
+            next_version: 1,
         }
     }
 
@@ -176,9 +203,7 @@ impl TargetHub {
     }
 
     pub fn data_mut<T: Target>(&mut self, id: TargetId) -> Option<&mut TargetData<T>> {
-        let any_hub = self.types.get_mut(&type_id::<T>())?;
-        let typed_hub = unsafe { any_hub.downcast_mut::<TargetData<T>>() };
-        typed_hub.get_mut(&id)
+        typed_data_mut(&mut self.types, id)
     }
 
     pub fn make_data_mut<T: Target>(&mut self, id: TargetId) -> &mut TargetData<T> {
@@ -196,8 +221,12 @@ impl TargetHub {
         name: &str,
         device: &mev::Device,
     ) -> Option<&T::Info> {
-        let data = self.data_mut::<T>(id)?;
-        data.plan_create(name, device)
+        let data = typed_data_mut::<T>(&mut self.types, id)?;
+        if data.allocate(name, device)? {
+            self.versions.insert(id, self.next_version);
+            self.next_version += 1;
+        }
+        data.info()
     }
 
     pub fn plan_update<T: Target>(&mut self, id: TargetId) -> Option<&T::Info> {
@@ -214,9 +243,39 @@ impl TargetHub {
         self.data::<T>(id)?.instance()
     }
 
+    /// Returns info of targets of the type allocated by the hub.
+    /// External targets are not included.
+    pub fn allocated<T: Target>(&self) -> impl Iterator<Item = &T::Info> + '_ {
+        self.types
+            .get(&type_id::<T>())
+            .into_iter()
+            .flat_map(|any_hub| {
+                let typed_hub = unsafe { any_hub.downcast_ref::<TargetData<T>>() };
+                typed_hub.values()
+            })
+            .filter_map(|data| data.target.as_ref().map(|(_, info)| info))
+    }
+
     pub fn external<T: Target>(&mut self, id: TargetId, instance: T, info: T::Info) {
         let data: &mut TargetData<T> = self.make_data_mut(id);
         data.external(instance, info);
+
+        // External target may be a different image every time, e.g. swapchain image.
+        self.touch(id);
+    }
+
+    /// Returns version of the target content.
+    pub fn version(&self, id: TargetId) -> u64 {
+        self.versions.get(&id).copied().unwrap_or(0)
+    }
+
+    /// Marks that target content is changed.
+    ///
+    /// Versions are never reused, so a version seen once
+    /// always refers to the same content.
+    pub fn touch(&mut self, id: TargetId) {
+        self.versions.insert(id, self.next_version);
+        self.next_version += 1;
     }
 
     pub fn clear_external<T: Target>(&mut self, id: TargetId) {
@@ -224,9 +283,11 @@ impl TargetHub {
             return;
         };
         data.clear_external();
+        self.touch(id);
     }
 
     pub fn clear(&mut self) {
         self.types.clear();
+        self.versions.clear();
     }
 }
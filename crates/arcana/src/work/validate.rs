@@ -0,0 +1,204 @@
+use arcana_names::Name;
+use hashbrown::{HashMap, HashSet};
+
+use crate::{model::Value, Stid};
+
+use super::{
+    graph::{Edge, JobIdx, PinId},
+    job::{JobDesc, JobId},
+};
+
+/// Problem found in work graph before it is built.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum GraphError {
+    #[error("Job is part of a dependency cycle")]
+    Cycle { job: JobIdx },
+
+    #[error("Edge connects pin {} that doesn't exist", .pin.pin)]
+    InvalidPin { job: JobIdx, pin: PinId },
+
+    #[error("Input '{name}' expects {expected} but {found} is connected")]
+    TypeMismatch {
+        job: JobIdx,
+        edge: Edge,
+        name: Name,
+        expected: Stid,
+        found: Stid,
+    },
+
+    #[error("Input '{name}' is not connected")]
+    MissingInput { job: JobIdx, name: Name },
+
+    #[error("Output '{name}' is created but never used")]
+    UnboundCreate { job: JobIdx, name: Name },
+}
+
+impl GraphError {
+    /// Returns job the error is reported for.
+    pub fn job(&self) -> JobIdx {
+        match *self {
+            GraphError::Cycle { job }
+            | GraphError::InvalidPin { job, .. }
+            | GraphError::TypeMismatch { job, .. }
+            | GraphError::MissingInput { job, .. }
+            | GraphError::UnboundCreate { job, .. } => job,
+        }
+    }
+
+    /// Returns true if work graph can't be built with this error.
+    ///
+    /// Other errors make jobs skip their work.
+    pub fn is_fatal(&self) -> bool {
+        matches!(
+            self,
+            GraphError::Cycle { .. }
+                | GraphError::InvalidPin { .. }
+                | GraphError::TypeMismatch { .. }
+        )
+    }
+}
+
+/// Checks jobs and edges before building a work graph.
+///
+/// `sinks` are output pins that get external targets, e.g. presented image.
+/// Errors are sorted by job.
+pub fn validate(
+    jobs: &HashMap<JobIdx, (JobId, JobDesc, HashMap<Name, Value>)>,
+    edges: &HashSet<Edge>,
+    sinks: &HashSet<PinId>,
+) -> Vec<GraphError> {
+    let mut errors = Vec::new();
+    let mut valid_edges = Vec::new();
+
+    for &edge in edges {
+        let Some((_, from, _)) = jobs.get(&edge.from.job) else {
+            errors.push(GraphError::InvalidPin {
+                job: edge.to.job,
+                pin: edge.from,
+            });
+            continue;
+        };
+
+        let Some((_, to, _)) = jobs.get(&edge.to.job) else {
+            errors.push(GraphError::InvalidPin {
+                job: edge.from.job,
+                pin: edge.to,
+            });
+            continue;
+        };
+
+        if edge.from.pin >= from.output_count() {
+            errors.push(GraphError::InvalidPin {
+                job: edge.from.job,
+                pin: edge.from,
+            });
+            continue;
+        }
+
+        // Params are not connected with edges.
+        let (expected, name) = match (to.update_idx(edge.to.pin), to.read_idx(edge.to.pin)) {
+            (Some(update), _) => (to.updates[update].ty, to.updates[update].name),
+            (_, Some(read)) => (to.reads[read].ty, to.reads[read].name),
+            _ => {
+                errors.push(GraphError::InvalidPin {
+                    job: edge.to.job,
+                    pin: edge.to,
+                });
+                continue;
+            }
+        };
+
+        let found = from.output_type(edge.from.pin);
+        if found != expected {
+            errors.push(GraphError::TypeMismatch {
+                job: edge.to.job,
+                edge,
+                name,
+                expected,
+                found,
+            });
+            continue;
+        }
+
+        valid_edges.push(edge);
+    }
+
+    for (&job, (_, desc, _)) in jobs {
+        let connected_in = |pin| valid_edges.iter().any(|e| e.to == PinId { job, pin });
+        let connected_out = |pin| {
+            let pin = PinId { job, pin };
+            sinks.contains(&pin) || valid_edges.iter().any(|e| e.from == pin)
+        };
+
+        // Update is fed either by an edge or by an external target.
+        for (idx, update) in desc.updates.iter().enumerate() {
+            if !connected_in(idx) && !sinks.contains(&PinId { job, pin: idx }) {
+                errors.push(GraphError::MissingInput {
+                    job,
+                    name: update.name,
+                });
+            }
+        }
+
+        for (idx, read) in desc.reads.iter().enumerate() {
+            if !connected_in(desc.updates.len() + idx) {
+                errors.push(GraphError::MissingInput {
+                    job,
+                    name: read.name,
+                });
+            }
+        }
+
+        for (idx, create) in desc.creates.iter().enumerate() {
+            if !connected_out(desc.updates.len() + idx) {
+                errors.push(GraphError::UnboundCreate {
+                    job,
+                    name: create.name,
+                });
+            }
+        }
+    }
+
+    for job in cycles(jobs.keys().copied(), &valid_edges) {
+        errors.push(GraphError::Cycle { job });
+    }
+
+    errors.sort_by_key(|err| err.job().0);
+    errors
+}
+
+/// Finds jobs that are on dependency cycles.
+///
+/// Jobs are peeled from both ends of the graph until nothing changes,
+/// whatever remains lies on a cycle or between cycles.
+fn cycles(jobs: impl Iterator<Item = JobIdx>, edges: &[Edge]) -> Vec<JobIdx> {
+    let mut remaining = jobs.collect::<HashSet<_>>();
+
+    loop {
+        let peeled = remaining
+            .iter()
+            .copied()
+            .filter(|&job| {
+                let has_dep = edges
+                    .iter()
+                    .any(|e| e.to.job == job && remaining.contains(&e.from.job));
+                let has_user = edges
+                    .iter()
+                    .any(|e| e.from.job == job && remaining.contains(&e.to.job));
+                !has_dep || !has_user
+            })
+            .collect::<Vec<_>>();
+
+        if peeled.is_empty() {
+            break;
+        }
+
+        for job in peeled {
+            remaining.remove(&job);
+        }
+    }
+
+    let mut remaining = remaining.into_iter().collect::<Vec<_>>();
+    remaining.sort_by_key(|job| job.0);
+    remaining
+}
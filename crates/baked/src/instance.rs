@@ -3,7 +3,8 @@
 use std::sync::Arc;
 
 use arcana::{
-    code::{builtin::emit_code_start, init_codes},
+    begin_frame,
+    code::init_codes,
     edict::world::WorldLocal,
     events::init_events,
     flow::{init_flows, wake_flows, Flows},
@@ -101,7 +102,7 @@ impl Instance {
     }
 
     pub fn tick(&mut self, span: TimeSpan, schedule: &Schedule, data: &ProjectData) {
-        emit_code_start(&mut self.world);
+        begin_frame(&mut self.world);
 
         let last_now = self.rate.now();
         let step = self.rate.step(span);
@@ -1,9 +1,15 @@
 use std::{fmt::Display, fs::File, io::Write, path::Path};
 
+use arcana::assets::sprite_sheet::{
+    AnimationDirection, SliceKey, SpriteFrame, SpriteRect, SpriteSheetData, SpriteSlice, SpriteTag,
+};
 use argosy_import::{Dependencies, ImportError, Importer, Sources};
 use asefile::AsepriteFile;
 
-/// Imports sprites from Aseprite files.
+/// Imports sprite sheets from Aseprite files.
+///
+/// All frames are packed into atlas grid.
+/// Tags, frame durations and slices are preserved.
 struct AsepriteSpriteImporter;
 
 impl Importer for AsepriteSpriteImporter {
@@ -20,7 +26,7 @@ impl Importer for AsepriteSpriteImporter {
     }
 
     fn target(&self) -> &str {
-        "qoi"
+        "sprite_sheet"
     }
 
     fn import(
@@ -31,20 +37,107 @@ impl Importer for AsepriteSpriteImporter {
         _dependencies: &mut dyn Dependencies,
     ) -> Result<(), ImportError> {
         let ase = AsepriteFile::read_file(source).map_err(error_to_reason)?;
-        let frame = ase.frame(0);
+        let sheet = sprite_sheet(&ase);
+
+        let mut outfile = File::create(output).map_err(error_to_reason)?;
+        outfile
+            .write_all(&sheet.encode())
+            .map_err(error_to_reason)?;
+        Ok(())
+    }
+}
+
+fn sprite_sheet(ase: &AsepriteFile) -> SpriteSheetData {
+    let frame_width = ase.width() as u32;
+    let frame_height = ase.height() as u32;
+    let frame_count = ase.num_frames();
+
+    // Square-ish grid keeps atlas within texture size limits for longer.
+    let columns = (frame_count as f64).sqrt().ceil().max(1.0) as u32;
+    let rows = frame_count.div_ceil(columns).max(1);
+
+    let width = columns * frame_width;
+    let height = rows * frame_height;
+    let mut pixels = vec![0; 4 * width as usize * height as usize];
+
+    let mut frames = Vec::with_capacity(frame_count as usize);
+
+    for index in 0..frame_count {
+        let frame = ase.frame(index);
         let image = frame.image();
 
-        let qoi = rapid_qoi::Qoi {
-            width: image.width(),
-            height: image.height(),
-            colors: rapid_qoi::Colors::SrgbLinA,
-        };
+        let x = (index % columns) * frame_width;
+        let y = (index / columns) * frame_height;
 
-        let encoded = qoi.encode_alloc(image.as_raw()).map_err(error_to_reason)?;
+        let row_bytes = 4 * frame_width as usize;
+        for row in 0..frame_height as usize {
+            let src = row * row_bytes;
+            let dst = 4 * ((y as usize + row) * width as usize + x as usize);
+            pixels[dst..dst + row_bytes].copy_from_slice(&image.as_raw()[src..src + row_bytes]);
+        }
 
-        let mut outfile = File::create(output).map_err(error_to_reason)?;
-        outfile.write_all(&encoded).map_err(error_to_reason)?;
-        Ok(())
+        frames.push(SpriteFrame {
+            rect: SpriteRect {
+                x: x as i32,
+                y: y as i32,
+                width: frame_width,
+                height: frame_height,
+            },
+            duration: frame.duration(),
+        });
+    }
+
+    let tags = (0..ase.num_tags())
+        .map(|index| {
+            let tag = ase.tag(index);
+            SpriteTag {
+                name: tag.name().to_owned(),
+                from: tag.from_frame(),
+                to: tag.to_frame(),
+                direction: match tag.animation_direction() {
+                    asefile::AnimationDirection::Reverse => AnimationDirection::Reverse,
+                    asefile::AnimationDirection::PingPong => AnimationDirection::PingPong,
+                    _ => AnimationDirection::Forward,
+                },
+            }
+        })
+        .collect();
+
+    let slices = ase
+        .slices()
+        .iter()
+        .map(|slice| SpriteSlice {
+            name: slice.name.clone(),
+            keys: slice
+                .keys
+                .iter()
+                .map(|key| SliceKey {
+                    frame: key.from_frame,
+                    bounds: SpriteRect {
+                        x: key.origin.0,
+                        y: key.origin.1,
+                        width: key.size.0,
+                        height: key.size.1,
+                    },
+                    center: key.slice9.as_ref().map(|slice9| SpriteRect {
+                        x: slice9.center_x,
+                        y: slice9.center_y,
+                        width: slice9.center_width,
+                        height: slice9.center_height,
+                    }),
+                    pivot: key.pivot.map(|(x, y)| [x, y]),
+                })
+                .collect(),
+        })
+        .collect();
+
+    SpriteSheetData {
+        width,
+        height,
+        pixels,
+        frames,
+        tags,
+        slices,
     }
 }
 
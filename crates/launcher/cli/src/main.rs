@@ -19,6 +19,20 @@ impl FromStr for ArcanaArg {
     }
 }
 
+#[derive(Debug, Clone, serde::Deserialize)]
+struct DependencyArg {
+    dependency: Dependency,
+}
+
+impl FromStr for DependencyArg {
+    type Err = toml::de::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let arg: DependencyArg = toml::from_str(&format!("dependency = {s}"))?;
+        Ok(arg)
+    }
+}
+
 #[derive(Clone, Copy)]
 struct IdentValueParser;
 
@@ -107,6 +121,19 @@ enum Command {
 
         #[arg(value_name = "release")]
         release: bool,
+
+        /// Rebuild and reload plugins when their sources change.
+        #[arg(long = "watch")]
+        watch: bool,
+
+        /// Run without windows, executing commands from the script.
+        /// Exits with error if any command fails.
+        #[arg(long = "headless", requires = "script", conflicts_with = "watch")]
+        headless: bool,
+
+        /// Script for headless mode.
+        #[arg(long = "script", value_name = "file", requires = "headless")]
+        script: Option<PathBuf>,
     },
     /// Creates new plugin.
     NewPlugin {
@@ -135,11 +162,58 @@ enum Command {
         release: bool,
     },
     /// Cooks game together with assets and all binaries.
+    /// Result is placed into `target/cook/<profile>/<platform>` in the project directory.
     Cook {
         /// Path to the project directory.
         #[arg(value_name = "path", default_value = ".")]
         path: PathBuf,
+
+        /// Cook with debug profile instead of release.
+        #[arg(long = "debug")]
+        debug: bool,
+
+        /// Cook for the web.
+        /// Game is built for `wasm32-unknown-unknown` and placed into `target/cook/<profile>/web`
+        /// with JS bindings and `index.html`.
+        /// Requires `wasm-bindgen-cli` to be installed.
+        #[arg(long = "web")]
+        web: bool,
+    },
+    /// Manages plugins of the project.
+    Plugin {
+        /// Path to the project directory.
+        #[arg(long = "project", value_name = "path", default_value = ".")]
+        path: PathBuf,
+
+        #[command(subcommand)]
+        command: PluginCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+#[command(rename_all = "kebab-case")]
+enum PluginCommand {
+    /// Adds plugin to the project.
+    Add {
+        /// Name of the plugin.
+        #[arg(value_name = "name", value_parser = IdentValueParser)]
+        name: Ident,
+
+        /// Plugin dependency.
+        /// This must be a string with valid toml syntax for a dependency.
+        /// Either version from crates.io, git repository or path to the plugin crate.
+        /// Relative paths are resolved from the current directory.
+        #[arg(value_name = "dependency")]
+        dependency: DependencyArg,
+    },
+    /// Removes plugin from the project.
+    Remove {
+        /// Name of the plugin.
+        #[arg(value_name = "name", value_parser = IdentValueParser)]
+        name: Ident,
     },
+    /// Lists plugins of the project.
+    List,
 }
 
 #[derive(Debug, Parser)]
@@ -160,6 +234,9 @@ fn main() -> miette::Result<()> {
     match cli.command.unwrap_or_else(|| Command::Ed {
         path: PathBuf::from("."),
         release: false,
+        watch: false,
+        headless: false,
+        script: None,
     }) {
         Command::Init { path, name, arcana } => {
             start.init(&path, name, pick_engine_version(&start, arcana), false)?;
@@ -170,15 +247,23 @@ fn main() -> miette::Result<()> {
         Command::InitWorkspace { path } => {
             start.init_workspace(&path)?;
         }
-        Command::Ed { path, release } => {
-            start.run_ed(
-                &path,
-                if release {
-                    Profile::Release
-                } else {
-                    Profile::Debug
-                },
-            )?;
+        Command::Ed {
+            path,
+            release,
+            watch,
+            headless,
+            script,
+        } => {
+            let profile = if release {
+                Profile::Release
+            } else {
+                Profile::Debug
+            };
+
+            match script {
+                Some(script) if headless => start.run_ed_headless(&path, profile, &script)?,
+                _ => start.run_ed(&path, profile, watch)?,
+            }
         }
         Command::NewPlugin { path, name, arcana } => {
             start.new_plugin(&path, name, pick_engine_version(&start, arcana))?;
@@ -193,28 +278,42 @@ fn main() -> miette::Result<()> {
                 },
             )?;
         }
-        Command::Cook { .. } => {
-            unimplemented!()
-            //     let path = start.build_game(&path)?;
-
-            //     if run {
-            //         tracing::info!("Game binary: {}", path.display());
-            //         match std::process::Command::new(path).status() {
-            //             Ok(status) => {
-            //                 if !status.success() {
-            //                     std::process::exit(status.code().unwrap_or(1));
-            //                 }
-            //             }
-            //             Err(err) => {
-            //                 eprintln!("Failed to run game: {}", err);
-            //                 std::process::exit(1);
-            //             }
-            //         }
-            //     } else {
-            //         println!("Game binary");
-            //         println!("{}", path.display());
-            //     }
+        Command::Cook { path, debug, web } => {
+            let path = start.cook(
+                &path,
+                if debug {
+                    Profile::Debug
+                } else {
+                    Profile::Release
+                },
+                web,
+            )?;
+
+            println!("Cooked game");
+            println!("{}", path.display());
         }
+        Command::Plugin { path, command } => match command {
+            PluginCommand::Add { name, dependency } => {
+                let plugin = start.add_plugin(&path, name, dependency.dependency)?;
+                println!("Added plugin {} = {}", plugin.name, plugin.dependency);
+                for permission in plugin.permissions.iter() {
+                    println!(
+                        "  requests {}: {}",
+                        permission.name(),
+                        permission.description()
+                    );
+                }
+            }
+            PluginCommand::Remove { name } => {
+                start.remove_plugin(&path, name)?;
+                println!("Removed plugin {name}");
+            }
+            PluginCommand::List => {
+                for plugin in start.list_plugins(&path)? {
+                    println!("{} = {}", plugin.name, plugin.dependency);
+                }
+            }
+        },
     }
 
     Ok(())
@@ -1,9 +1,14 @@
-use std::{path::PathBuf, process::Child};
+use std::{path::PathBuf, process::Child, time::Duration};
 
-use arcana_launcher::{validate_engine_path, Dependency, Ident, Profile, Project, Start};
+use arcana_launcher::{
+    validate_engine_path, CrashReport, Dependency, EngineSource, Ident, Profile, Project, Start,
+};
 use egui_file::FileDialog;
 use hashbrown::HashMap;
 
+/// How often status of the editor process is checked.
+const CHILD_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
 fn main() -> eframe::Result<()> {
     use tracing_subscriber::layer::SubscriberExt as _;
 
@@ -40,10 +45,11 @@ impl eframe::App for App {
                     if status.success() {
                         match self.child {
                             AppChild::EditorBuilding(_, ref path) => {
-                                let project = self.recent.get(path).unwrap().as_ref().unwrap();
+                                let path = path.clone();
+                                let project = self.recent.get(&path).unwrap().as_ref().unwrap();
                                 self.child = AppChild::None;
 
-                                match project.run_editor_non_blocking(self.profile) {
+                                match project.run_editor_non_blocking(self.profile, self.watch) {
                                     Err(err) => {
                                         self.dialog = Some(AppDialog::Error(ErrorDialog {
                                             title: "Failed to run Arcana Ed".to_owned(),
@@ -51,7 +57,7 @@ impl eframe::App for App {
                                         }));
                                     }
                                     Ok(child) => {
-                                        self.child = AppChild::EditorRunning(child);
+                                        self.child = AppChild::EditorRunning(child, path);
                                         return;
                                     }
                                 }
@@ -68,7 +74,7 @@ impl eframe::App for App {
                 }
                 Ok(None) => {}
             },
-            AppChild::EditorRunning(ref mut child) => {
+            AppChild::EditorRunning(ref mut child, ref path) => {
                 match child.try_wait() {
                     Err(err) => {
                         self.dialog = Some(AppDialog::Error(ErrorDialog {
@@ -78,11 +84,23 @@ impl eframe::App for App {
                         self.child = AppChild::None;
                     }
                     Ok(Some(status)) => {
+                        // Take the report even on success so a stale one is not shown later.
+                        let report = match self.recent.get(path) {
+                            Some(Ok(project)) => CrashReport::take(project.root_path()),
+                            _ => None,
+                        };
+
                         if !status.success() {
-                            self.dialog = Some(AppDialog::Error(ErrorDialog {
-                                title: "Arcana Ed exited with error".to_owned(),
-                                message: format!("{}", status),
-                            }));
+                            self.dialog = Some(match report {
+                                Some(report) => AppDialog::Crash(CrashDialog {
+                                    status: status.to_string(),
+                                    report,
+                                }),
+                                None => AppDialog::Error(ErrorDialog {
+                                    title: "Arcana Ed exited with error".to_owned(),
+                                    message: format!("{}", status),
+                                }),
+                            });
                         }
                         self.child = AppChild::None;
                     }
@@ -152,6 +170,16 @@ impl eframe::App for App {
                         ui.label("Add new engine to Arcana Launcher");
                     });
                 }
+
+                let r = ui.button("Manage Engines");
+                if r.clicked() {
+                    self.dialog = Some(AppDialog::Engines(EngineManager::new()));
+                    ui.close_menu();
+                } else {
+                    r.on_hover_ui(|ui| {
+                        ui.label("Install engine versions from crates.io or git");
+                    });
+                }
             });
         });
 
@@ -173,6 +201,11 @@ impl eframe::App for App {
                 {
                     self.profile = Profile::Release;
                 }
+
+                ui.separator();
+
+                ui.checkbox(&mut self.watch, "Watch")
+                    .on_hover_text("Rebuild plugins when their sources change");
             });
         });
 
@@ -296,6 +329,12 @@ impl eframe::App for App {
                         cx.request_repaint();
                     }
                 }
+                Some(AppDialog::Crash(ref crash)) => {
+                    if crash.show(cx) {
+                        self.dialog = None;
+                        cx.request_repaint();
+                    }
+                }
                 Some(AppDialog::OpenProject(ref mut file_dialog)) => {
                     match file_dialog.show(cx).state() {
                         egui_file::State::Open => {}
@@ -355,6 +394,11 @@ impl eframe::App for App {
                         },
                     }
                 }
+                Some(AppDialog::Engines(ref mut engines)) => {
+                    if engines.show(&mut self.start, cx) {
+                        self.dialog = None;
+                    }
+                }
                 Some(AppDialog::NewProject(ref mut new_project)) => {
                     match new_project.show(&mut self.start, cx) {
                         None => {}
@@ -383,13 +427,15 @@ impl eframe::App for App {
                         ui.spinner();
                     });
             }
-            AppChild::EditorRunning(_) => {
+            AppChild::EditorRunning(_, _) => {
                 unreachable!()
             }
         }
 
-        if cx.requested_repaint_last_frame() {
-            cx.request_repaint();
+        // UI is redrawn only on input and repaint requests,
+        // child process is polled at fixed interval.
+        if self.child.is_some() {
+            cx.request_repaint_after(CHILD_POLL_INTERVAL);
         }
 
         match run_editor {
@@ -437,17 +483,236 @@ impl ErrorDialog {
     }
 }
 
+/// Shows crash report written by the editor that panicked.
+struct CrashDialog {
+    status: String,
+    report: CrashReport,
+}
+
+impl CrashDialog {
+    fn show(&self, cx: &egui::Context) -> bool {
+        let mut close = false;
+        egui::Window::new("Arcana Ed crashed")
+            .collapsible(false)
+            .default_width(600.0)
+            .show(cx, |ui| {
+                ui.label(
+                    egui::RichText::new(&self.report.message).color(ui.visuals().error_fg_color),
+                );
+
+                if let Some(location) = &self.report.location {
+                    ui.label(format!("at {location}"));
+                }
+
+                if let Some(thread) = &self.report.thread {
+                    ui.weak(format!("in thread '{thread}'"));
+                }
+
+                ui.weak(&self.status);
+
+                egui::CollapsingHeader::new("Backtrace").show(ui, |ui| {
+                    egui::ScrollArea::both()
+                        .id_source("crash-backtrace")
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            ui.monospace(&self.report.backtrace);
+                        });
+                });
+
+                egui::CollapsingHeader::new("Log")
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        egui::ScrollArea::both()
+                            .id_source("crash-log")
+                            .max_height(300.0)
+                            .stick_to_bottom(true)
+                            .show(ui, |ui| {
+                                for line in &self.report.log {
+                                    ui.monospace(line);
+                                }
+                            });
+                    });
+
+                ui.horizontal(|ui| {
+                    if ui.button("Copy report").clicked() {
+                        ui.ctx().copy_text(self.text());
+                    }
+                    if ui.button("Ok").clicked() {
+                        close = true;
+                    }
+                });
+            });
+        close
+    }
+
+    fn text(&self) -> String {
+        let mut text = self.report.message.clone();
+        if let Some(location) = &self.report.location {
+            text.push_str(&format!("\nat {location}"));
+        }
+        if let Some(thread) = &self.report.thread {
+            text.push_str(&format!("\nin thread '{thread}'"));
+        }
+        text.push_str(&format!("\n{}\n\nBacktrace:\n", self.status));
+        text.push_str(&self.report.backtrace);
+        text.push_str("\n\nLog:\n");
+        text.push_str(&self.report.log.join("\n"));
+        text
+    }
+}
+
 enum AppDialog {
     NewProject(NewProject),
     OpenProject(FileDialog),
     AddEngine(FileDialog),
+    Engines(EngineManager),
     Error(ErrorDialog),
+    Crash(CrashDialog),
+}
+
+/// This widget installs engine versions and checks them for updates.
+struct EngineManager {
+    /// Install from git instead of crates.io.
+    git: bool,
+    version: String,
+    git_url: String,
+    tag: String,
+
+    /// Result of last operation.
+    status: Option<Result<String, String>>,
+}
+
+impl EngineManager {
+    fn new() -> Self {
+        EngineManager {
+            git: false,
+            version: String::new(),
+            git_url: String::new(),
+            tag: String::new(),
+            status: None,
+        }
+    }
+
+    /// Returns true when dialog is closed.
+    fn show(&mut self, start: &mut Start, cx: &egui::Context) -> bool {
+        let mut close = false;
+
+        egui::Window::new("Engines")
+            .auto_sized()
+            .collapsible(false)
+            .show(cx, |ui| {
+                let mut uninstall = None;
+
+                egui::Grid::new("installed-engines")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for engine in start.installed_engines() {
+                            ui.label(display_dependency(&engine.dependency));
+
+                            if ui
+                                .small_button(egui_phosphor::regular::ARROWS_CLOCKWISE)
+                                .on_hover_text("Check for updates")
+                                .clicked()
+                            {
+                                self.status = Some(match start.check_engine_update(engine) {
+                                    Ok(Some(latest)) => {
+                                        Ok(format!("Version {latest} is available"))
+                                    }
+                                    Ok(None) => {
+                                        Ok(format!("Engine {} is up to date", engine.version()))
+                                    }
+                                    Err(err) => Err(err.to_string()),
+                                });
+                            }
+
+                            if ui
+                                .small_button(egui_phosphor::regular::TRASH)
+                                .on_hover_text("Uninstall")
+                                .clicked()
+                            {
+                                uninstall = Some(engine.source.clone());
+                            }
+
+                            ui.end_row();
+                        }
+                    });
+
+                if let Some(source) = uninstall {
+                    if let Err(err) = start.uninstall_engine(&source) {
+                        self.status = Some(Err(err.to_string()));
+                    }
+                }
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut self.git, false, "crates.io");
+                    ui.selectable_value(&mut self.git, true, "git");
+                });
+
+                egui::Grid::new("install-engine")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        if self.git {
+                            ui.label("Repository");
+                            ui.text_edit_singleline(&mut self.git_url);
+                            ui.end_row();
+
+                            ui.label("Tag");
+                            ui.text_edit_singleline(&mut self.tag);
+                            ui.end_row();
+                        } else {
+                            ui.label("Version");
+                            ui.text_edit_singleline(&mut self.version);
+                            ui.end_row();
+                        }
+                    });
+
+                let source = if self.git {
+                    (!self.git_url.is_empty() && !self.tag.is_empty()).then(|| EngineSource::Git {
+                        git: self.git_url.clone(),
+                        tag: self.tag.clone(),
+                    })
+                } else {
+                    (!self.version.is_empty()).then(|| EngineSource::Crates {
+                        version: self.version.clone(),
+                    })
+                };
+
+                ui.horizontal(|ui| {
+                    let r = ui.add_enabled(source.is_some(), egui::Button::new("Install"));
+                    if r.clicked() {
+                        self.status = Some(match start.install_engine(source.unwrap()) {
+                            Ok(dep) => Ok(format!("Installed {}", display_dependency(&dep))),
+                            Err(err) => Err(err.to_string()),
+                        });
+                    }
+
+                    if ui.button("Close").clicked() {
+                        close = true;
+                    }
+                });
+
+                match &self.status {
+                    None => {}
+                    Some(Ok(message)) => {
+                        ui.label(message);
+                    }
+                    Some(Err(message)) => {
+                        ui.label(egui::RichText::new(message).color(ui.visuals().error_fg_color));
+                    }
+                }
+            });
+
+        close
+    }
 }
 
 enum AppChild {
     None,
     EditorBuilding(Child, PathBuf),
-    EditorRunning(Child),
+    EditorRunning(Child, PathBuf),
 }
 
 impl AppChild {
@@ -463,7 +728,7 @@ impl Drop for AppChild {
             AppChild::EditorBuilding(child, _) => {
                 let _ = child.kill();
             }
-            AppChild::EditorRunning(child) => {
+            AppChild::EditorRunning(child, _) => {
                 let _ = child.kill();
             }
         }
@@ -475,6 +740,10 @@ impl Drop for AppChild {
 pub struct App {
     start: Start,
     profile: Profile,
+
+    /// Run editor in watch mode.
+    watch: bool,
+
     recent: HashMap<PathBuf, Result<Project, miette::Report>>,
 
     /// Open dialog.
@@ -498,6 +767,7 @@ impl App {
         App {
             start: Start::new(),
             profile: Profile::Debug,
+            watch: false,
             recent: HashMap::new(),
 
             dialog: None,
@@ -0,0 +1,326 @@
+//! Engine versions installed by the launcher.
+//!
+//! Engines are fetched with `cargo` and `git` tools into managed directory
+//! `<local data dir>/Arcana/engines`.
+//! Released engines are fetched into cargo cache, so projects can be built offline.
+//! Git engines are cloned at specified tag and used as path dependency,
+//! so projects do not follow moving branch.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use arcana_project::{validate_engine_path, Dependency};
+
+const ENGINE_CRATE: &'static str = "arcana";
+
+/// Where engine version comes from.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum EngineSource {
+    /// Released version from crates.io.
+    Crates { version: String },
+
+    /// Tag in git repository.
+    Git { git: String, tag: String },
+}
+
+/// Engine version installed by the launcher.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct InstalledEngine {
+    pub source: EngineSource,
+
+    /// Dependency that projects use to reference this engine.
+    pub dependency: Dependency,
+}
+
+impl InstalledEngine {
+    pub fn version(&self) -> &str {
+        match &self.source {
+            EngineSource::Crates { version } => version,
+            EngineSource::Git { tag, .. } => tag,
+        }
+    }
+}
+
+/// Returns directory where engines are installed.
+pub fn engines_dir() -> miette::Result<PathBuf> {
+    match dirs::data_local_dir() {
+        Some(dir) => Ok(dir.join("Arcana").join("engines")),
+        None => miette::bail!("Failed to find local data directory"),
+    }
+}
+
+/// Fetches engine from the source into managed directory.
+pub fn install_engine(source: &EngineSource) -> miette::Result<InstalledEngine> {
+    let engines_dir = engines_dir()?;
+
+    let dependency = match source {
+        EngineSource::Crates { version } => {
+            let version = parse_version(version)?;
+            let path = engines_dir.join("crates").join(version.to_string());
+            fetch_released(&path, &version)?;
+            Dependency::Crates(format!("={version}"))
+        }
+        EngineSource::Git { git, tag } => {
+            let path = engines_dir.join("git").join(git_dir_name(git, tag));
+            clone_tag(&path, git, tag)?;
+
+            // Engine crate is either repository root or lives in the workspace.
+            match validate_engine_path(&path) {
+                Ok(dependency) => dependency,
+                Err(err) => {
+                    let crate_path = path.join("crates").join(ENGINE_CRATE);
+                    if !crate_path.exists() {
+                        return Err(err);
+                    }
+                    validate_engine_path(&crate_path)?
+                }
+            }
+        }
+    };
+
+    tracing::info!("Installed engine {dependency}");
+
+    Ok(InstalledEngine {
+        source: source.clone(),
+        dependency,
+    })
+}
+
+/// Removes engine files from managed directory.
+pub fn uninstall_engine(engine: &InstalledEngine) -> miette::Result<()> {
+    let engines_dir = engines_dir()?;
+
+    let path = match &engine.source {
+        EngineSource::Crates { version } => {
+            let version = parse_version(version)?;
+            engines_dir.join("crates").join(version.to_string())
+        }
+        EngineSource::Git { git, tag } => engines_dir.join("git").join(git_dir_name(git, tag)),
+    };
+
+    match std::fs::remove_dir_all(&path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => {
+            miette::bail!("Failed to remove engine '{}': {err:?}", path.display());
+        }
+    }
+}
+
+/// Checks if newer version than installed one is available.
+/// Returns newest version if it differs from installed.
+pub fn check_engine_update(engine: &InstalledEngine) -> miette::Result<Option<String>> {
+    let latest = match &engine.source {
+        EngineSource::Crates { .. } => latest_released()?,
+        EngineSource::Git { git, .. } => latest_tag(git)?,
+    };
+
+    match latest {
+        Some(latest) if compare_versions(&latest, engine.version()).is_gt() => Ok(Some(latest)),
+        _ => Ok(None),
+    }
+}
+
+/// Parses released engine version.
+///
+/// Version becomes directory name in managed directory,
+/// so anything but valid semver is rejected before touching the filesystem.
+fn parse_version(version: &str) -> miette::Result<semver::Version> {
+    semver::Version::parse(version.trim())
+        .map_err(|err| miette::miette!("Invalid engine version '{version}': {err}"))
+}
+
+/// Makes cargo download released engine crate
+/// by fetching dependencies of a stub crate.
+fn fetch_released(path: &Path, version: &semver::Version) -> miette::Result<()> {
+    std::fs::create_dir_all(path.join("src")).map_err(|err| {
+        miette::miette!(
+            "Failed to create engine directory '{}': {err:?}",
+            path.display()
+        )
+    })?;
+
+    let cargo_toml = format!(
+        r#"[package]
+name = "arcana-engine-fetch"
+version = "0.0.0"
+publish = false
+edition = "2021"
+
+[dependencies]
+{ENGINE_CRATE} = "={version}"
+"#
+    );
+
+    std::fs::write(path.join("Cargo.toml"), cargo_toml)
+        .and_then(|()| std::fs::write(path.join("src").join("lib.rs"), ""))
+        .map_err(|err| {
+            miette::miette!(
+                "Failed to write engine fetch crate '{}': {err:?}",
+                path.display()
+            )
+        })?;
+
+    let mut cmd = Command::new("cargo");
+    cmd.arg("fetch").current_dir(path);
+    run(cmd, "cargo fetch")
+}
+
+fn clone_tag(path: &Path, git: &str, tag: &str) -> miette::Result<()> {
+    if path.exists() {
+        tracing::info!("Engine '{git}' at '{tag}' is already cloned");
+        return Ok(());
+    }
+
+    let mut cmd = Command::new("git");
+    cmd.arg("clone")
+        .arg("--depth=1")
+        .arg("--branch")
+        .arg(tag)
+        .arg("--")
+        .arg(git)
+        .arg(path.as_os_str());
+    run(cmd, "git clone")
+}
+
+fn latest_released() -> miette::Result<Option<String>> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("search").arg(ENGINE_CRATE).arg("--limit=1");
+    let output = output(cmd, "cargo search")?;
+
+    // Output looks like `arcana = "0.1.0"    # Description`.
+    let prefix = format!("{ENGINE_CRATE} = \"");
+    let version = output.lines().find_map(|line| {
+        let rest = line.strip_prefix(&prefix)?;
+        let (version, _) = rest.split_once('"')?;
+        Some(version.to_owned())
+    });
+
+    Ok(version)
+}
+
+fn latest_tag(git: &str) -> miette::Result<Option<String>> {
+    let mut cmd = Command::new("git");
+    cmd.arg("ls-remote")
+        .arg("--tags")
+        .arg("--refs")
+        .arg("--")
+        .arg(git);
+    let output = output(cmd, "git ls-remote")?;
+
+    let latest = output
+        .lines()
+        .filter_map(|line| line.split_once("refs/tags/"))
+        .map(|(_, tag)| tag.trim())
+        .max_by(|a, b| compare_versions(a, b));
+
+    Ok(latest.map(str::to_owned))
+}
+
+/// Compares versions with semver ordering, so pre-releases precede releases.
+/// Leading `v` is ignored.
+/// Versions that are not semver are older than any semver version
+/// and are compared as strings between themselves.
+fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    fn parse(v: &str) -> Option<semver::Version> {
+        semver::Version::parse(v.trim().trim_start_matches('v')).ok()
+    }
+
+    match (parse(a), parse(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Greater,
+        (None, Some(_)) => std::cmp::Ordering::Less,
+        (None, None) => a.cmp(b),
+    }
+}
+
+/// Directory name for git engine clone.
+fn git_dir_name(git: &str, tag: &str) -> String {
+    let repo = git
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit('/')
+        .next()
+        .unwrap_or(ENGINE_CRATE);
+
+    let sanitize = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect()
+    };
+
+    format!("{}-{}", sanitize(repo), sanitize(tag))
+}
+
+fn run(mut cmd: Command, what: &str) -> miette::Result<()> {
+    let status = cmd
+        .status()
+        .map_err(|err| miette::miette!("Failed to run {what}: {err:?}"))?;
+
+    if !status.success() {
+        miette::bail!("{what} failed: {status}");
+    }
+
+    Ok(())
+}
+
+fn output(mut cmd: Command, what: &str) -> miette::Result<String> {
+    let output = cmd
+        .output()
+        .map_err(|err| miette::miette!("Failed to run {what}: {err:?}"))?;
+
+    if !output.status.success() {
+        miette::bail!(
+            "{what} failed: {}\n{}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_version_rejects_paths() {
+        for version in ["..", "../..", "/usr", "1.0.0/..", "1.0.0/../..", "1.0", ""] {
+            assert!(parse_version(version).is_err(), "{version:?} accepted");
+        }
+
+        assert_eq!(parse_version("1.2.3").unwrap().to_string(), "1.2.3");
+        assert_eq!(parse_version("1.0.0-rc1").unwrap().to_string(), "1.0.0-rc1");
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.0.0-rc1", "1.0.0"), Ordering::Less);
+        assert_eq!(
+            compare_versions("1.0.0-rc2", "1.0.0-rc1"),
+            Ordering::Greater
+        );
+        assert_eq!(compare_versions("v0.10.0", "0.9.1"), Ordering::Greater);
+        assert_eq!(compare_versions("v1.0.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("nightly", "0.1.0"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_git_dir_name_stays_inside() {
+        let name = git_dir_name("https://example.com/../../arcana.git", "../..");
+        assert!(!name.contains('/') && !name.contains('\\'));
+        assert_ne!(name, "..");
+    }
+}
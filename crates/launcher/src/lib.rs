@@ -9,8 +9,12 @@ use arcana_project::{new_plugin_crate, process_path_ident, Plugin};
 use camino::Utf8PathBuf;
 use figa::Figa;
 
+mod engine;
+
 pub use arcana_names::Ident;
-pub use arcana_project::{validate_engine_path, Dependency, Profile, Project};
+pub use arcana_project::{validate_engine_path, CrashReport, Dependency, Profile, Project};
+
+pub use self::engine::{engines_dir, EngineSource, InstalledEngine};
 
 #[derive(Default, serde::Serialize, serde::Deserialize, figa::Figa)]
 struct Config {
@@ -25,6 +29,10 @@ struct Config {
     // Known plugins.
     #[figa(append)]
     plugins: Vec<Dependency>,
+
+    // Engines installed into managed directory.
+    #[figa(append)]
+    installed: Vec<InstalledEngine>,
 }
 
 pub struct Start {
@@ -165,9 +173,23 @@ impl Start {
         Project::open(path)?.init_workspace()
     }
 
-    pub fn run_ed(&self, path: &Path, profile: Profile) -> miette::Result<()> {
+    /// Runs editor for the project.
+    ///
+    /// With `watch` editor rebuilds and reloads plugins when their sources change.
+    pub fn run_ed(&self, path: &Path, profile: Profile, watch: bool) -> miette::Result<()> {
         let p = Project::open(path)?;
-        p.run_editor(profile)
+        p.run_editor(profile, watch)
+    }
+
+    /// Runs editor without windows, executing commands from the script.
+    pub fn run_ed_headless(
+        &self,
+        path: &Path,
+        profile: Profile,
+        script: &Path,
+    ) -> miette::Result<()> {
+        let p = Project::open(path)?;
+        p.run_editor_headless(profile, script)
     }
 
     pub fn new_plugin(
@@ -193,12 +215,66 @@ impl Start {
         p.build_game(profile)
     }
 
+    /// Cooks the game and returns path to the directory with distributable files.
+    ///
+    /// With `web` the game is built for browsers instead of the host platform.
+    pub fn cook(&self, path: &Path, profile: Profile, web: bool) -> miette::Result<PathBuf> {
+        let p = Project::open(path)?;
+        p.init_workspace()?;
+        if web {
+            p.cook_web(profile)
+        } else {
+            p.cook(profile)
+        }
+    }
+
     pub fn run_game(&self, path: &Path, profile: Profile) -> miette::Result<()> {
         let p = Project::open(path)?;
         p.init_workspace()?;
         p.run_game(profile)
     }
 
+    /// Adds plugin to the project manifest and regenerates workspace.
+    ///
+    /// Path dependencies are resolved relative to current directory.
+    pub fn add_plugin(
+        &self,
+        path: &Path,
+        name: Ident,
+        dependency: Dependency,
+    ) -> miette::Result<Plugin> {
+        let mut p = Project::open(path)?;
+        let plugin = Plugin::from_dependency(name, dependency)?;
+
+        if !p.add_plugin(plugin)? {
+            miette::bail!("Plugin '{name}' is already added to the project");
+        }
+
+        p.sync()?;
+        p.init_workspace()?;
+        p.refresh_permissions()?;
+
+        Ok(p.manifest().get_plugin(name).unwrap().clone())
+    }
+
+    /// Removes plugin from the project manifest and regenerates workspace.
+    pub fn remove_plugin(&self, path: &Path, name: Ident) -> miette::Result<()> {
+        let mut p = Project::open(path)?;
+
+        if !p.remove_plugin(name) {
+            miette::bail!("Project has no plugin '{name}'");
+        }
+
+        p.sync()?;
+        p.init_workspace()
+    }
+
+    /// Returns plugins listed in the project manifest.
+    pub fn list_plugins(&self, path: &Path) -> miette::Result<Vec<Plugin>> {
+        let p = Project::open(path)?;
+        Ok(p.plugins().to_vec())
+    }
+
     pub fn recent<'a>(&'a self) -> impl ExactSizeIterator<Item = &'a Path> + 'a {
         self.config.recent.iter().rev().map(AsRef::as_ref)
     }
@@ -215,6 +291,53 @@ impl Start {
         }
     }
 
+    /// Returns engines installed by the launcher.
+    pub fn installed_engines(&self) -> &[InstalledEngine] {
+        &self.config.installed
+    }
+
+    /// Fetches engine from crates.io or git into managed directory
+    /// and adds it to the list of engine versions.
+    pub fn install_engine(&mut self, source: EngineSource) -> miette::Result<Dependency> {
+        let engine = engine::install_engine(&source)?;
+        let dependency = engine.dependency.clone();
+
+        self.config.installed.retain(|e| e.source != source);
+        self.config.installed.push(engine);
+
+        // Saves config.
+        self.add_engine(dependency.clone());
+
+        Ok(dependency)
+    }
+
+    /// Removes installed engine files and forgets the engine.
+    pub fn uninstall_engine(&mut self, source: &EngineSource) -> miette::Result<()> {
+        let Some(idx) = self
+            .config
+            .installed
+            .iter()
+            .position(|e| e.source == *source)
+        else {
+            miette::bail!("Engine {source:?} is not installed");
+        };
+
+        let engine = self.config.installed.remove(idx);
+        self.config.engines.retain(|e| *e != engine.dependency);
+
+        if let Some(dir) = dirs::config_local_dir() {
+            save_config_to_path(&self.config, &dir.join("Arcana/config.toml"));
+        }
+
+        engine::uninstall_engine(&engine)
+    }
+
+    /// Checks if newer version of installed engine is available.
+    /// Returns the newer version if there is one.
+    pub fn check_engine_update(&self, engine: &InstalledEngine) -> miette::Result<Option<String>> {
+        engine::check_engine_update(engine)
+    }
+
     pub fn add_recent(&mut self, project_path: PathBuf) {
         if let Some(idx) = self.config.recent.iter().position(|p| **p == project_path) {
             self.config.recent.remove(idx);
@@ -4,12 +4,17 @@ use std::{
 };
 
 use arcana::{
-    bytemuck,
+    assets::Font,
+    console::Console,
     gametime::TimeStamp,
     input::InputFilter,
     mev::{self, Arguments, DeviceRepr},
-    render::{Render, RenderBuilderContext, RenderContext, RenderError, RenderGraph, TargetId},
-    texture::Texture,
+    render::{
+        Render, RenderBuilderContext, RenderContext, RenderError, RenderGraph, StreamBuffer,
+        TargetId,
+    },
+    texture::{cached_sampler, Texture},
+    viewport::Viewport,
     Blink, Component, EntityId, World,
 };
 use egui::epaint::{ClippedShape, Primitive, Vertex};
@@ -45,6 +50,7 @@ pub struct Egui {
     textures_delta: TexturesDelta,
     shapes: Vec<ClippedShape>,
     textures: HashMap<u64, (mev::Image, Sampler)>,
+    fonts: FontDefinitions,
     raw_input: egui::RawInput,
     mouse_pos: Pos2,
     scale_factor: f32,
@@ -73,7 +79,7 @@ impl Egui {
     pub fn new(size: Vec2, scale_factor: f32) -> Self {
         let fonts = fonts();
         let cx: Context = Context::default();
-        cx.set_fonts(fonts);
+        cx.set_fonts(fonts.clone());
 
         let mut raw_input = egui::RawInput::default();
         let rect = Rect::from_min_size(Default::default(), size / scale_factor);
@@ -89,6 +95,7 @@ impl Egui {
             textures_delta: TexturesDelta::default(),
             shapes: Vec::new(),
             textures: HashMap::new(),
+            fonts,
             mouse_pos: Pos2::ZERO,
             raw_input,
             scale_factor,
@@ -105,6 +112,24 @@ impl Egui {
         self.cx.set_style(style);
     }
 
+    /// Adds font asset to this UI.
+    ///
+    /// Font takes priority over built-in fonts in the given family.
+    /// Adding font with the same name again replaces it.
+    pub fn add_font(&mut self, font: &Font, family: FontFamily) {
+        let name = font.name().to_owned();
+
+        self.fonts
+            .font_data
+            .insert(name.clone(), font.egui_font_data());
+
+        let fonts = self.fonts.families.entry(family).or_default();
+        fonts.retain(|n| *n != name);
+        fonts.insert(0, name);
+
+        self.cx.set_fonts(self.fonts.clone());
+    }
+
     pub fn run<R>(&mut self, time: TimeStamp, run_ui: impl FnOnce(&Context) -> R) -> R {
         self.raw_input.time = Some(time.elapsed_since_start().as_secs_f64());
 
@@ -115,10 +140,40 @@ impl Egui {
         // TODO: Handle platform output
         let _ = output.platform_output;
 
+        // Shapes of the previous frame are replaced before they were rendered,
+        // so textures freed after that frame can go right away.
+        self.evict_freed();
+
         self.textures_delta.append(output.textures_delta);
         self.shapes = output.shapes;
         ret
     }
+
+    /// Returns memory used by textures of this UI in bytes.
+    ///
+    /// Used by the diagnostics overlay.
+    pub fn texture_memory(&self) -> u64 {
+        self.textures
+            .values()
+            .map(|(image, _)| {
+                let extent = image.extent().expect_2d();
+
+                // Both color and font textures use 4 bytes per pixel.
+                u64::from(extent.width()) * u64::from(extent.height()) * 4
+            })
+            .sum()
+    }
+
+    /// Drops textures egui freed along with their pending uploads.
+    fn evict_freed(&mut self) {
+        for id in std::mem::take(&mut self.textures_delta.free) {
+            self.textures_delta.set.retain(|(set_id, _)| *set_id != id);
+
+            if let TextureId::Managed(id) = id {
+                self.textures.remove(&id);
+            }
+        }
+    }
 }
 
 #[derive(mev::Arguments)]
@@ -139,13 +194,15 @@ struct EguiConstants {
 pub struct EguiRender {
     id: Option<EntityId>,
     target: TargetId<mev::Image>,
-    samplers: Option<[mev::Sampler; 4]>,
     library: Option<mev::Library>,
     linear_pipeline: Option<mev::RenderPipeline>,
     srgb_pipeline: Option<mev::RenderPipeline>,
 
-    vertex_buffer: Option<mev::Buffer>,
-    index_buffer: Option<mev::Buffer>,
+    /// Vertices and indices of the meshes.
+    stream: StreamBuffer,
+
+    /// Pixels of texture updates.
+    uploads: StreamBuffer,
     load_op: mev::LoadOp<mev::ClearColor>,
 }
 
@@ -158,12 +215,14 @@ impl EguiRender {
         EguiRender {
             id,
             target,
-            samplers: None,
             library: None,
             linear_pipeline: None,
             srgb_pipeline: None,
-            vertex_buffer: None,
-            index_buffer: None,
+            stream: StreamBuffer::new(
+                "egui-stream",
+                mev::BufferUsage::VERTEX.union(mev::BufferUsage::INDEX),
+            ),
+            uploads: StreamBuffer::new("egui-uploads", mev::BufferUsage::TRANSFER_SRC),
             load_op,
         }
     }
@@ -211,82 +270,84 @@ impl Render for EguiRender {
             }
         };
 
-        let samplers = match &mut self.samplers {
-            Some(samplers) => &*samplers,
-            none => {
-                let sampler_nn = cx.device().new_sampler(mev::SamplerDesc {
-                    min_filter: mev::Filter::Nearest,
-                    mag_filter: mev::Filter::Nearest,
-                    address_mode: [mev::AddressMode::ClampToEdge; 3],
-                    ..mev::SamplerDesc::new()
-                })?;
-                let sampler_nl = cx.device().new_sampler(mev::SamplerDesc {
-                    min_filter: mev::Filter::Nearest,
-                    mag_filter: mev::Filter::Linear,
-                    address_mode: [mev::AddressMode::ClampToEdge; 3],
-                    ..mev::SamplerDesc::new()
-                })?;
-                let sampler_ln = cx.device().new_sampler(mev::SamplerDesc {
-                    min_filter: mev::Filter::Linear,
-                    mag_filter: mev::Filter::Nearest,
-                    address_mode: [mev::AddressMode::ClampToEdge; 3],
-                    ..mev::SamplerDesc::new()
-                })?;
-                let sampler_ll = cx.device().new_sampler(mev::SamplerDesc {
-                    min_filter: mev::Filter::Linear,
-                    mag_filter: mev::Filter::Linear,
+        // Indexed by `Sampler` as `min_filter` and `mag_filter` pairs.
+        let sampler = |min_filter, mag_filter| {
+            cached_sampler(
+                world,
+                cx.device(),
+                mev::SamplerDesc {
+                    min_filter,
+                    mag_filter,
                     address_mode: [mev::AddressMode::ClampToEdge; 3],
                     ..mev::SamplerDesc::new()
-                })?;
-                none.get_or_insert([sampler_nn, sampler_nl, sampler_ln, sampler_ll])
-            }
+                },
+            )
         };
+        let samplers = [
+            sampler(mev::Filter::Nearest, mev::Filter::Nearest)?,
+            sampler(mev::Filter::Nearest, mev::Filter::Linear)?,
+            sampler(mev::Filter::Linear, mev::Filter::Nearest)?,
+            sampler(mev::Filter::Linear, mev::Filter::Linear)?,
+        ];
 
         let mut encoder = cx.new_command_encoder()?;
 
         let target = cx.write_target(self.target, &mut encoder).clone();
 
+        self.stream.reset();
+        self.uploads.reset();
+
         {
             let mut copy_encoder = encoder.copy();
 
             copy_encoder.barrier(
-                mev::PipelineStages::VERTEX_INPUT | mev::PipelineStages::FRAGMENT_SHADER,
+                mev::PipelineStages::VERTEX_INPUT
+                    | mev::PipelineStages::FRAGMENT_SHADER
+                    | mev::PipelineStages::TRANSFER,
                 mev::PipelineStages::TRANSFER,
             );
 
             if !egui.textures_delta.set.is_empty() {
                 let delta_size = egui.textures_delta.set.iter().fold(0, |acc, (_, delta)| {
-                    acc + match &delta.image {
-                        ImageData::Color(color) => std::mem::size_of_val(&color.pixels[..]),
-                        ImageData::Font(font) => std::mem::size_of_val(&font.pixels[..]),
-                    }
+                    acc + StreamBuffer::ALIGNMENT
+                        + match &delta.image {
+                            ImageData::Color(color) => std::mem::size_of_val(&color.pixels[..]),
+                            ImageData::Font(font) => std::mem::size_of_val(&font.pixels[..]),
+                        }
                 });
 
-                let mut upload_buffer = cx.device().new_buffer(mev::BufferDesc {
-                    size: delta_size,
-                    usage: mev::BufferUsage::TRANSFER_SRC,
-                    memory: mev::Memory::Upload,
-                    name: "texture-delta-upload",
-                })?;
+                self.uploads.reserve(cx.device(), delta_size)?;
 
-                let mut offset = 0usize;
+                let mut uploads = Vec::with_capacity(egui.textures_delta.set.len());
                 for (_, delta) in egui.textures_delta.set.iter() {
-                    match &delta.image {
-                        ImageData::Color(color) => unsafe {
-                            upload_buffer
-                                .write_unchecked(offset, bytemuck::cast_slice(&color.pixels[..]));
-                            offset += std::mem::size_of_val(&color.pixels[..]);
-                        },
-                        ImageData::Font(font) => unsafe {
-                            upload_buffer
-                                .write_unchecked(offset, bytemuck::cast_slice(&font.pixels[..]));
-                            offset += std::mem::size_of_val(&font.pixels[..]);
-                        },
-                    }
+                    let upload = match &delta.image {
+                        ImageData::Color(color) => {
+                            let upload = self
+                                .uploads
+                                .alloc(cx.device(), size_of_val(&color.pixels[..]))?;
+                            copy_encoder.write_buffer_slice(
+                                upload.buffer.slice(upload.offset..),
+                                &color.pixels[..],
+                            );
+                            upload
+                        }
+                        ImageData::Font(font) => {
+                            let upload = self
+                                .uploads
+                                .alloc(cx.device(), size_of_val(&font.pixels[..]))?;
+                            copy_encoder.write_buffer_slice(
+                                upload.buffer.slice(upload.offset..),
+                                &font.pixels[..],
+                            );
+                            upload
+                        }
+                    };
+                    uploads.push(upload);
                 }
 
-                let mut offset = 0usize;
-                for (id, delta) in egui.textures_delta.set.iter() {
+                copy_encoder.barrier(mev::PipelineStages::TRANSFER, mev::PipelineStages::TRANSFER);
+
+                for ((id, delta), upload) in egui.textures_delta.set.iter().zip(&uploads) {
                     let region = delta.image.size();
                     let pos = delta.pos.unwrap_or([0; 2]);
                     let size = [pos[0] + region[0], pos[1] + region[1]];
@@ -338,9 +399,19 @@ impl Render for EguiRender {
                             entry.get_mut().1 = Sampler::from_options(delta.options);
                             image = entry.get().0.clone();
                             let extent = image.extent().expect_2d();
-                            if (extent.width() as usize) < size[0]
-                                || (extent.height() as usize) < size[1]
-                            {
+                            let width = extent.width() as usize;
+                            let height = extent.height() as usize;
+
+                            // Whole image replaces the texture, so it gets exact size.
+                            // This shrinks fonts atlas when scale goes down.
+                            let whole = delta.pos.is_none();
+                            let resize = if whole {
+                                width != size[0] || height != size[1]
+                            } else {
+                                width < size[0] || height < size[1]
+                            };
+
+                            if resize {
                                 let mut new_image = cx.device().new_image(mev::ImageDesc {
                                     extent: mev::Extent2::new(size[0] as u32, size[1] as u32)
                                         .into(),
@@ -366,18 +437,20 @@ impl Render for EguiRender {
                                     )?;
                                 }
 
-                                copy_encoder.copy_image_region(
-                                    &image,
-                                    0,
-                                    0,
-                                    mev::Offset3::ZERO,
-                                    &new_image,
-                                    0,
-                                    0,
-                                    mev::Offset3::ZERO,
-                                    image.extent().into_3d(),
-                                    1,
-                                );
+                                if !whole {
+                                    copy_encoder.copy_image_region(
+                                        &image,
+                                        0,
+                                        0,
+                                        mev::Offset3::ZERO,
+                                        &new_image,
+                                        0,
+                                        0,
+                                        mev::Offset3::ZERO,
+                                        image.extent().into_3d(),
+                                        1,
+                                    );
+                                }
 
                                 entry.get_mut().0 = new_image.clone();
                                 image = new_image;
@@ -386,8 +459,8 @@ impl Render for EguiRender {
                     }
 
                     copy_encoder.copy_buffer_to_image(
-                        &upload_buffer,
-                        offset,
+                        &upload.buffer,
+                        upload.offset,
                         4 * region[0],
                         0,
                         &image,
@@ -396,15 +469,6 @@ impl Render for EguiRender {
                         0..1,
                         0,
                     );
-
-                    match &delta.image {
-                        ImageData::Color(color) => {
-                            offset += std::mem::size_of_val(&color.pixels[..]);
-                        }
-                        ImageData::Font(font) => {
-                            offset += std::mem::size_of_val(&font.pixels[..]);
-                        }
-                    }
                 }
 
                 egui.textures_delta.set.clear();
@@ -416,65 +480,40 @@ impl Render for EguiRender {
                     .tessellate(std::mem::take(&mut egui.shapes), egui.scale_factor);
 
                 if !primitives.is_empty() {
-                    let mut total_vertex_size = 0;
-                    let mut total_index_size = 0;
+                    let mut total_size = 0;
 
                     for primitive in &primitives {
                         match &primitive.primitive {
                             Primitive::Mesh(mesh) => {
-                                total_vertex_size += size_of_val(&mesh.vertices[..]);
-                                total_vertex_size = (total_vertex_size + 31) & !31;
-                                total_index_size += size_of_val(&mesh.indices[..]);
-                                total_index_size = (total_index_size + 31) & !31;
+                                total_size += size_of_val(&mesh.vertices[..])
+                                    + size_of_val(&mesh.indices[..])
+                                    + 2 * StreamBuffer::ALIGNMENT;
                             }
                             Primitive::Callback(_) => todo!(),
                         }
                     }
 
-                    let vertex_buffer = match &mut self.vertex_buffer {
-                        Some(buffer) if buffer.size() >= total_vertex_size => buffer,
-                        slot => {
-                            *slot = None;
-                            slot.get_or_insert(cx.device().new_buffer(mev::BufferDesc {
-                                size: total_vertex_size,
-                                usage: mev::BufferUsage::VERTEX | mev::BufferUsage::TRANSFER_DST,
-                                memory: mev::Memory::Device,
-                                name: "egui-vertex-buffer",
-                            })?)
-                        }
-                    };
-
-                    let index_buffer = match &mut self.index_buffer {
-                        Some(buffer) if buffer.size() >= total_index_size => buffer,
-                        slot => {
-                            *slot = None;
-                            slot.get_or_insert(cx.device().new_buffer(mev::BufferDesc {
-                                size: total_index_size,
-                                usage: mev::BufferUsage::INDEX | mev::BufferUsage::TRANSFER_DST,
-                                memory: mev::Memory::Device,
-                                name: "egui-index-buffer",
-                            })?)
-                        }
-                    };
-
-                    let mut vertex_buffer_offset = 0;
-                    let mut index_buffer_offset = 0;
+                    self.stream.reserve(cx.device(), total_size)?;
 
+                    let mut meshes = Vec::with_capacity(primitives.len());
                     for primitive in &primitives {
                         match &primitive.primitive {
                             Primitive::Mesh(mesh) => {
+                                let vertices = self
+                                    .stream
+                                    .alloc(cx.device(), size_of_val(&mesh.vertices[..]))?;
                                 copy_encoder.write_buffer_slice(
-                                    vertex_buffer.slice(vertex_buffer_offset..),
+                                    vertices.buffer.slice(vertices.offset..),
                                     &mesh.vertices[..],
                                 );
+                                let indices = self
+                                    .stream
+                                    .alloc(cx.device(), size_of_val(&mesh.indices[..]))?;
                                 copy_encoder.write_buffer_slice(
-                                    index_buffer.slice(index_buffer_offset..),
+                                    indices.buffer.slice(indices.offset..),
                                     &mesh.indices[..],
                                 );
-                                vertex_buffer_offset += size_of_val(&mesh.vertices[..]);
-                                vertex_buffer_offset = (vertex_buffer_offset + 31) & !31;
-                                index_buffer_offset += size_of_val(&mesh.indices[..]);
-                                index_buffer_offset = (index_buffer_offset + 31) & !31;
+                                meshes.push((vertices, indices));
                             }
                             Primitive::Callback(_) => todo!(),
                         }
@@ -618,21 +657,9 @@ impl Render for EguiRender {
                         scale: egui.cx.pixels_per_point(),
                     });
 
-                    let mut vertex_buffer_offset = 0;
-                    let mut index_buffer_offset = 0;
-
-                    for primitive in primitives {
+                    for (primitive, (vertices, indices)) in primitives.into_iter().zip(&meshes) {
                         match primitive.primitive {
                             Primitive::Mesh(mesh) => {
-                                macro_rules! next_mesh {
-                                    () => {
-                                        vertex_buffer_offset += size_of_val(&mesh.vertices[..]);
-                                        vertex_buffer_offset = (vertex_buffer_offset + 31) & !31;
-                                        index_buffer_offset += size_of_val(&mesh.indices[..]);
-                                        index_buffer_offset = (index_buffer_offset + 31) & !31;
-                                    };
-                                }
-
                                 let offset = mev::Offset2::new(
                                     ((primitive.clip_rect.left() * egui.cx.pixels_per_point())
                                         as i32)
@@ -657,17 +684,14 @@ impl Render for EguiRender {
                                     TextureId::Managed(id) => egui.textures[&id].clone(),
                                     TextureId::User(id) => {
                                         let Some(id) = EntityId::from_bits(id) else {
-                                            next_mesh!();
                                             continue;
                                         };
 
                                         let Ok(mut texture) = world.try_view_one::<&Texture>(id)
                                         else {
-                                            next_mesh!();
                                             continue;
                                         };
                                         let Some(texture) = texture.get_mut() else {
-                                            next_mesh!();
                                             continue;
                                         };
 
@@ -685,12 +709,10 @@ impl Render for EguiRender {
 
                                 render.bind_vertex_buffers(
                                     0,
-                                    &[vertex_buffer.slice(vertex_buffer_offset..)],
+                                    &[vertices.buffer.slice(vertices.offset..)],
                                 );
-                                render.bind_index_buffer(index_buffer.slice(index_buffer_offset..));
+                                render.bind_index_buffer(indices.buffer.slice(indices.offset..));
                                 render.draw_indexed(0, 0..mesh.indices.len() as u32, 0..1);
-
-                                next_mesh!();
                             }
                             Primitive::Callback(_) => todo!(),
                         }
@@ -699,15 +721,7 @@ impl Render for EguiRender {
             }
         }
 
-        for id in egui.textures_delta.free.iter() {
-            match id {
-                TextureId::Managed(id) => {
-                    egui.textures.remove(id);
-                }
-                TextureId::User(_) => {}
-            }
-        }
-        egui.textures_delta.free.clear();
+        egui.evict_freed();
 
         cx.commit(encoder.finish()?);
 
@@ -715,6 +729,42 @@ impl Render for EguiRender {
     }
 }
 
+/// Returns texture id that shows `Texture` component of the entity.
+///
+/// Texture is looked up each time UI is rendered,
+/// so replacing the component switches image on the next frame
+/// and despawned entities are skipped.
+pub fn entity_texture(entity: EntityId) -> TextureId {
+    TextureId::User(entity.bits())
+}
+
+/// Returns image widget that shows live content of the texture viewport.
+///
+/// Host sets viewport image as `Texture` component each frame it renders the viewport.
+/// Returns `None` if entity has no texture viewport.
+pub fn viewport_image(world: &World, entity: EntityId) -> Option<Image<'static>> {
+    let viewport = world.try_view_one::<&Viewport>(entity).ok()?;
+    let viewport = viewport.get()?;
+
+    if !viewport.is_texture() {
+        return None;
+    }
+
+    let extent = viewport.extent();
+    let size = vec2(extent.width() as f32, extent.height() as f32);
+    Some(Image::new((entity_texture(entity), size)))
+}
+
+/// Shows console window that executes commands registered in the world.
+///
+/// Games keep `Console` state and call this from their UI code.
+pub fn console_window(ctx: &Context, open: &mut bool, console: &mut Console, world: &mut World) {
+    egui::Window::new("Console")
+        .open(open)
+        .default_size([480.0, 320.0])
+        .show(ctx, |ui| console.show_world(world, ui));
+}
+
 pub struct EguiFilter;
 
 impl InputFilter for EguiFilter {
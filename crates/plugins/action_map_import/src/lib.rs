@@ -0,0 +1,93 @@
+//! This plugin provides importer for input action maps.
+//!
+//! Action maps are written in TOML and are edited in the editor's input mapping tool.
+//! Importer validates them and encodes into the format [`ActionMap`] asset loads.
+//!
+//! [`ActionMap`]: arcana::input::ActionMap
+
+use std::{fmt::Display, path::Path};
+
+use arcana::{
+    assets::import::{AssetDependencies, AssetSources, ImportError, Importer},
+    hashbrown::HashSet,
+    ident,
+    input::ActionMap,
+    name, Ident, Name,
+};
+
+arcana::declare_plugin!();
+
+/// Imports TOML action maps.
+#[arcana::importer]
+#[derive(Default)]
+pub struct ActionMapImporter;
+
+impl ActionMapImporter {
+    pub fn new() -> Self {
+        ActionMapImporter
+    }
+}
+
+impl Importer for ActionMapImporter {
+    fn name(&self) -> Name {
+        name!(action_map)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["actions"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["actions"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(action_map)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        _sources: &mut dyn AssetSources,
+        _dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let text = std::fs::read_to_string(source).map_err(error_to_reason)?;
+        let map = ActionMap::from_toml(&text).map_err(error_to_reason)?;
+        validate(&map)?;
+        std::fs::write(output, map.encode()).map_err(error_to_reason)
+    }
+}
+
+/// Names must be unique, otherwise lookups by name would silently pick the first one.
+fn validate(map: &ActionMap) -> Result<(), ImportError> {
+    let mut contexts = HashSet::new();
+
+    for context in &map.contexts {
+        if !contexts.insert(&context.name) {
+            return Err(ImportError::Other {
+                reason: format!("Duplicate context '{}'", context.name),
+            });
+        }
+
+        let mut actions = HashSet::new();
+        for action in &context.actions {
+            if !actions.insert(&action.name) {
+                return Err(ImportError::Other {
+                    reason: format!(
+                        "Duplicate action '{}' in context '{}'",
+                        action.name, context.name
+                    ),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn error_to_reason<E: Display>(error: E) -> ImportError {
+    ImportError::Other {
+        reason: error.to_string(),
+    }
+}
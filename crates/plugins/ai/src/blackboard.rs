@@ -0,0 +1,45 @@
+use arcana::{
+    edict::{self, Component},
+    hashbrown::HashMap,
+    model::Value,
+};
+
+/// Per-entity storage of values shared by behavior tree tasks.
+#[derive(Clone, Debug, Default, Component)]
+pub struct Blackboard {
+    values: HashMap<String, Value>,
+}
+
+impl Blackboard {
+    pub fn new() -> Self {
+        Blackboard::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.values.get_mut(key)
+    }
+
+    pub fn set(&mut self, key: impl Into<String>, value: Value) {
+        self.values.insert(key.into(), value);
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.values.remove(key)
+    }
+
+    /// Returns `true` if value is set and is not `false`, unit or empty option.
+    pub fn check(&self, key: &str) -> bool {
+        match self.values.get(key) {
+            None | Some(Value::Unit | Value::Bool(false) | Value::Option(None)) => false,
+            Some(_) => true,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+}
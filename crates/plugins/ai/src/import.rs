@@ -0,0 +1,55 @@
+use std::{fmt::Display, path::Path};
+
+use arcana::{
+    assets::import::{AssetDependencies, AssetSources, ImportError, Importer},
+    behavior::BehaviorTree,
+    ident, name, Ident, Name,
+};
+
+/// Imports behavior trees saved by the editor.
+#[arcana::importer]
+#[derive(Default)]
+pub struct BehaviorTreeImporter;
+
+impl BehaviorTreeImporter {
+    pub fn new() -> Self {
+        BehaviorTreeImporter
+    }
+}
+
+impl Importer for BehaviorTreeImporter {
+    fn name(&self) -> Name {
+        name!(behavior_tree)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["behavior"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["behavior"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(behavior_tree)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        _sources: &mut dyn AssetSources,
+        _dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let text = std::fs::read_to_string(source).map_err(error_to_reason)?;
+        let tree = BehaviorTree::from_json(&text).map_err(error_to_reason)?;
+        tree.validate().map_err(error_to_reason)?;
+        std::fs::write(output, tree.encode()).map_err(error_to_reason)
+    }
+}
+
+fn error_to_reason<E: Display>(error: E) -> ImportError {
+    ImportError::Other {
+        reason: error.to_string(),
+    }
+}
@@ -0,0 +1,33 @@
+//! This plugin runs behavior trees.
+//!
+//! Trees are [`BehaviorTree`] assets authored in the editor's behavior tree tool.
+//! Entity with `Behavior` component ticks its tree every frame
+//! and restarts it after the root finishes.
+//!
+//! Leaf tasks are Rust functions registered in `BehaviorTasks` resource
+//! or events that trigger code graphs of the entity.
+//! `Blackboard` component keeps per-entity values that tasks read and write
+//! and `Check` nodes test.
+//!
+//! [`BehaviorTree`]: arcana::behavior::BehaviorTree
+
+use arcana::World;
+
+arcana::declare_plugin!();
+
+mod blackboard;
+mod import;
+mod task;
+mod tree;
+
+pub use self::{
+    blackboard::Blackboard,
+    import::BehaviorTreeImporter,
+    task::{BehaviorStatus, BehaviorTask, BehaviorTasks},
+    tree::Behavior,
+};
+
+#[arcana::init]
+fn init_ai(world: &mut World) {
+    world.insert_resource(BehaviorTasks::new());
+}
@@ -0,0 +1,42 @@
+use arcana::{edict::world::World, hashbrown::HashMap, EntityId};
+
+/// Result of ticking behavior tree node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BehaviorStatus {
+    /// Node is not finished and will be ticked again next frame.
+    Running,
+    Success,
+    Failure,
+}
+
+/// Leaf task of behavior tree.
+///
+/// Called every frame while the task node is running.
+pub type BehaviorTask = fn(entity: EntityId, world: &mut World) -> BehaviorStatus;
+
+/// Tasks that `Task` nodes of behavior trees call by name.
+pub struct BehaviorTasks {
+    tasks: HashMap<String, BehaviorTask>,
+}
+
+impl BehaviorTasks {
+    pub fn new() -> Self {
+        BehaviorTasks {
+            tasks: HashMap::new(),
+        }
+    }
+
+    /// Registers task under the name.
+    /// Replaces task previously registered under the same name.
+    pub fn add(&mut self, name: impl Into<String>, task: BehaviorTask) {
+        self.tasks.insert(name.into(), task);
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.tasks.remove(name);
+    }
+
+    pub fn get(&self, name: &str) -> Option<BehaviorTask> {
+        self.tasks.get(name).copied()
+    }
+}
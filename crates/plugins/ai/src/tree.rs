@@ -0,0 +1,286 @@
+use arcana::{
+    assets::Handle,
+    behavior::{BehaviorKind, BehaviorTree},
+    edict::{self, world::World, Component, Entities},
+    events::{Event, Events},
+    gametime::ClockStep,
+    EntityId,
+};
+
+use crate::{
+    blackboard::Blackboard,
+    task::{BehaviorStatus, BehaviorTasks},
+};
+
+/// Memory of a node between ticks.
+#[derive(Clone, Default)]
+struct NodeState {
+    running: bool,
+
+    /// Child to tick next in sequence and selector.
+    cursor: usize,
+
+    /// Seconds spent in wait node.
+    elapsed: f32,
+
+    /// Finished iterations of repeat node.
+    iteration: u32,
+
+    /// Children of parallel node that already succeeded.
+    succeeded: Vec<bool>,
+}
+
+/// Runs behavior tree for the entity.
+///
+/// Tree is ticked every frame once the asset is loaded.
+/// When root node finishes, tree starts over on the next tick.
+#[derive(Component)]
+pub struct Behavior {
+    handle: Handle<BehaviorTree>,
+    tree: Option<BehaviorTree>,
+    nodes: Vec<NodeState>,
+    status: Option<BehaviorStatus>,
+}
+
+impl Behavior {
+    pub fn new(tree: Handle<BehaviorTree>) -> Self {
+        Behavior {
+            handle: tree,
+            tree: None,
+            nodes: Vec::new(),
+            status: None,
+        }
+    }
+
+    /// Returns status of the root after last tick.
+    /// `None` if tree was not ticked yet.
+    pub fn status(&self) -> Option<BehaviorStatus> {
+        self.status
+    }
+
+    /// Aborts running nodes so that tree starts over on the next tick.
+    pub fn restart(&mut self) {
+        self.nodes.iter_mut().for_each(|node| node.running = false);
+        self.status = None;
+    }
+}
+
+struct Ticker<'a> {
+    tree: &'a BehaviorTree,
+    nodes: &'a mut [NodeState],
+    entity: EntityId,
+    world: &'a mut World,
+    delta_time: f32,
+}
+
+impl Ticker<'_> {
+    fn tick(&mut self, idx: usize) -> BehaviorStatus {
+        let tree = self.tree;
+        let node = &tree.nodes[idx];
+
+        if !self.nodes[idx].running {
+            self.nodes[idx] = NodeState {
+                running: true,
+                succeeded: vec![false; node.children.len()],
+                ..NodeState::default()
+            };
+        }
+
+        let status = match node.kind {
+            BehaviorKind::Sequence => loop {
+                let Some(&child) = node.children.get(self.nodes[idx].cursor) else {
+                    break BehaviorStatus::Success;
+                };
+                match self.tick(child) {
+                    BehaviorStatus::Success => self.nodes[idx].cursor += 1,
+                    status => break status,
+                }
+            },
+            BehaviorKind::Selector => loop {
+                let Some(&child) = node.children.get(self.nodes[idx].cursor) else {
+                    break BehaviorStatus::Failure;
+                };
+                match self.tick(child) {
+                    BehaviorStatus::Failure => self.nodes[idx].cursor += 1,
+                    status => break status,
+                }
+            },
+            BehaviorKind::Parallel => {
+                let mut status = BehaviorStatus::Success;
+                for (i, &child) in node.children.iter().enumerate() {
+                    if self.nodes[idx].succeeded[i] {
+                        continue;
+                    }
+                    match self.tick(child) {
+                        BehaviorStatus::Success => self.nodes[idx].succeeded[i] = true,
+                        BehaviorStatus::Running => status = BehaviorStatus::Running,
+                        BehaviorStatus::Failure => {
+                            status = BehaviorStatus::Failure;
+                            break;
+                        }
+                    }
+                }
+
+                if status == BehaviorStatus::Failure {
+                    for &child in &node.children {
+                        self.abort(child);
+                    }
+                }
+                status
+            }
+            BehaviorKind::Invert => match self.tick_child(idx) {
+                BehaviorStatus::Success => BehaviorStatus::Failure,
+                BehaviorStatus::Failure => BehaviorStatus::Success,
+                BehaviorStatus::Running => BehaviorStatus::Running,
+            },
+            BehaviorKind::Succeed => match self.tick_child(idx) {
+                BehaviorStatus::Running => BehaviorStatus::Running,
+                _ => BehaviorStatus::Success,
+            },
+            BehaviorKind::Repeat { count } => match self.tick_child(idx) {
+                BehaviorStatus::Success => {
+                    // Next iteration starts on the next tick,
+                    // so that instantly succeeding child does not hang the frame.
+                    self.nodes[idx].iteration += 1;
+                    if count > 0 && self.nodes[idx].iteration >= count {
+                        BehaviorStatus::Success
+                    } else {
+                        BehaviorStatus::Running
+                    }
+                }
+                status => status,
+            },
+            BehaviorKind::Wait { seconds } => {
+                self.nodes[idx].elapsed += self.delta_time;
+                if self.nodes[idx].elapsed >= seconds {
+                    BehaviorStatus::Success
+                } else {
+                    BehaviorStatus::Running
+                }
+            }
+            BehaviorKind::Check { ref key } => {
+                let set = self
+                    .world
+                    .get::<&Blackboard>(self.entity)
+                    .map_or(false, |blackboard| blackboard.check(key));
+
+                if set {
+                    BehaviorStatus::Success
+                } else {
+                    BehaviorStatus::Failure
+                }
+            }
+            BehaviorKind::Task { ref name } => {
+                let task = self
+                    .world
+                    .get_resource::<BehaviorTasks>()
+                    .and_then(|tasks| tasks.get(name));
+
+                match task {
+                    Some(task) => task(self.entity, self.world),
+                    None => {
+                        tracing::warn!("Behavior task '{name}' is not registered");
+                        BehaviorStatus::Failure
+                    }
+                }
+            }
+            BehaviorKind::Event { id, .. } => {
+                if let Some(mut events) = self.world.get_resource_mut::<Events>() {
+                    events.emit(Event::new(id, self.entity));
+                }
+                BehaviorStatus::Success
+            }
+        };
+
+        if status != BehaviorStatus::Running {
+            self.nodes[idx].running = false;
+        }
+
+        status
+    }
+
+    /// Ticks the only child of decorator node.
+    /// Decorator without child fails.
+    fn tick_child(&mut self, idx: usize) -> BehaviorStatus {
+        let tree = self.tree;
+        match tree.nodes[idx].children.first() {
+            Some(&child) => self.tick(child),
+            None => BehaviorStatus::Failure,
+        }
+    }
+
+    /// Stops running node and its descendants.
+    fn abort(&mut self, idx: usize) {
+        if !std::mem::replace(&mut self.nodes[idx].running, false) {
+            return;
+        }
+
+        let tree = self.tree;
+        for &child in &tree.nodes[idx].children {
+            self.abort(child);
+        }
+    }
+}
+
+#[arcana::system]
+fn tick_behaviors(world: &mut World) {
+    let delta_time = world.expect_resource::<ClockStep>().step.as_secs_f32();
+
+    let entities = world
+        .view::<Entities>()
+        .with::<Behavior>()
+        .iter()
+        .map(|e| e.id())
+        .collect::<Vec<_>>();
+
+    for entity in entities {
+        // Tree and its state are taken out of the component
+        // so that tasks can access the world freely.
+        let (tree, mut nodes) = {
+            let Ok(behavior) = world.get::<&mut Behavior>(entity) else {
+                continue;
+            };
+
+            if behavior.tree.is_none() {
+                let Some(tree) = behavior.handle.get() else {
+                    continue;
+                };
+
+                // Invalid tree is replaced with empty one that does nothing.
+                let tree = match tree.validate() {
+                    Ok(()) => tree,
+                    Err(err) => {
+                        tracing::error!("Invalid behavior tree of entity {entity}: {err}");
+                        BehaviorTree::default()
+                    }
+                };
+
+                behavior.nodes = vec![NodeState::default(); tree.nodes.len()];
+                behavior.tree = Some(tree);
+            }
+
+            (
+                behavior.tree.take().unwrap(),
+                std::mem::take(&mut behavior.nodes),
+            )
+        };
+
+        let status = tree.root.map(|root| {
+            Ticker {
+                tree: &tree,
+                nodes: &mut nodes,
+                entity,
+                world: &mut *world,
+                delta_time,
+            }
+            .tick(root)
+        });
+
+        // Tasks may have removed the component.
+        if let Ok(behavior) = world.get::<&mut Behavior>(entity) {
+            behavior.tree = Some(tree);
+            behavior.nodes = nodes;
+            behavior.status = status;
+        }
+    }
+}
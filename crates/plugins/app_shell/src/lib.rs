@@ -0,0 +1,69 @@
+//! This plugin implements application shell that almost every game needs.
+//!
+//! Shell goes through boot → menu → gameplay ⇄ pause states,
+//! asks for confirmation before quitting and shows settings screen.
+//!
+//! Game code checks `AppShell::state` to know whether gameplay is running
+//! and calls `AppShell::finish_boot` once initial loading is done.
+//!
+//! Game settings and action bindings from `arcana::settings` are shown
+//! on settings screen after `AppShell::add_game_settings` is called.
+//! Other plugins may extend settings screen with their own pages
+//! using `AppShell::add_settings_page`.
+//!
+//! Confirmed quit asks the host to exit with `arcana::window::ExitRequest`.
+//!
+//! UI is drawn with `Egui` resource if one is present in the world.
+
+use arcana::{
+    input::{ElementState, Input, KeyCode, PhysicalKey, ViewInput},
+    World,
+};
+
+arcana::declare_plugin!();
+
+mod state;
+mod ui;
+
+pub use self::state::{AppShell, SettingsPage, ShellState};
+
+#[arcana::init]
+fn init_shell(world: &mut World) {
+    world.insert_resource(AppShell::new());
+}
+
+/// Escape key navigates back through shell states.
+/// Settings screen consumes input while it waits for new binding.
+#[arcana::filter]
+fn shell_filter(world: &mut World, input: &Input) -> bool {
+    let Input::ViewInput { ref input, .. } = *input else {
+        return false;
+    };
+
+    let capture = world.expect_resource::<AppShell>().capture();
+    if let Some(capture) = capture {
+        if capture(world, input) {
+            return true;
+        }
+    }
+
+    let ViewInput::KeyboardInput { ref event, .. } = *input else {
+        return false;
+    };
+
+    if event.state != ElementState::Pressed || event.repeat {
+        return false;
+    }
+
+    if event.physical_key != PhysicalKey::Code(KeyCode::Escape) {
+        return false;
+    }
+
+    let mut shell = world.expect_resource_mut::<AppShell>();
+    shell.back()
+}
+
+#[arcana::system]
+fn shell_ui(world: &mut World) {
+    ui::show(world);
+}
@@ -0,0 +1,217 @@
+use arcana::{
+    input::ViewInput,
+    settings::{GameSettings, SettingsScreen},
+    World,
+};
+use arcana_egui::Ui;
+
+/// States of the application shell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShellState {
+    /// Game is loading initial resources.
+    Boot,
+
+    /// Main menu is shown.
+    Menu,
+
+    /// Game is running.
+    Gameplay,
+
+    /// Game is paused and pause menu is shown.
+    Paused,
+
+    /// Settings screen is shown.
+    Settings,
+
+    /// Quit confirmation is shown.
+    ConfirmQuit,
+}
+
+/// Page of the settings screen.
+pub struct SettingsPage {
+    pub title: String,
+    pub show: fn(&World, &mut Ui),
+}
+
+/// Resource that holds state of the application shell.
+pub struct AppShell {
+    title: String,
+    state: ShellState,
+
+    /// State to return to from settings and quit confirmation.
+    back: ShellState,
+
+    /// Index of the settings page shown.
+    page: usize,
+
+    pages: Vec<SettingsPage>,
+
+    /// State of game settings pages.
+    screen: SettingsScreen,
+
+    /// Feeds input to the screen while it captures a binding.
+    /// Set with game settings pages.
+    capture: Option<fn(&World, &ViewInput) -> bool>,
+
+    quit: bool,
+}
+
+impl AppShell {
+    pub fn new() -> Self {
+        AppShell {
+            title: String::new(),
+            state: ShellState::Boot,
+            back: ShellState::Menu,
+            page: 0,
+            pages: Vec::new(),
+            screen: SettingsScreen::new(),
+            capture: None,
+            quit: false,
+        }
+    }
+
+    /// Title shown in the main menu.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = title.into();
+    }
+
+    pub fn state(&self) -> ShellState {
+        self.state
+    }
+
+    /// Returns true if gameplay is running.
+    /// Gameplay systems should do nothing otherwise.
+    pub fn is_running(&self) -> bool {
+        self.state == ShellState::Gameplay
+    }
+
+    /// Returns true if user confirmed quitting.
+    /// Host is asked to exit with `ExitRequest` resource at the same time.
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Switches from boot to main menu.
+    pub fn finish_boot(&mut self) {
+        if self.state == ShellState::Boot {
+            self.state = ShellState::Menu;
+        }
+    }
+
+    /// Starts gameplay from the main menu.
+    pub fn start(&mut self) {
+        if self.state == ShellState::Menu {
+            self.state = ShellState::Gameplay;
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if self.state == ShellState::Gameplay {
+            self.state = ShellState::Paused;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.state == ShellState::Paused {
+            self.state = ShellState::Gameplay;
+        }
+    }
+
+    /// Leaves gameplay and returns to the main menu.
+    pub fn exit_to_menu(&mut self) {
+        if self.state == ShellState::Paused {
+            self.state = ShellState::Menu;
+        }
+    }
+
+    pub fn open_settings(&mut self) {
+        if matches!(self.state, ShellState::Menu | ShellState::Paused) {
+            self.back = self.state;
+            self.state = ShellState::Settings;
+        }
+    }
+
+    /// Asks user to confirm quitting.
+    pub fn request_quit(&mut self) {
+        if matches!(self.state, ShellState::Menu | ShellState::Paused) {
+            self.back = self.state;
+            self.state = ShellState::ConfirmQuit;
+        }
+    }
+
+    pub fn confirm_quit(&mut self) {
+        if self.state == ShellState::ConfirmQuit {
+            self.quit = true;
+        }
+    }
+
+    /// Navigates one step back.
+    /// Returns false if there is nowhere to go back.
+    pub fn back(&mut self) -> bool {
+        match self.state {
+            ShellState::Boot | ShellState::Menu => false,
+            ShellState::Gameplay => {
+                self.state = ShellState::Paused;
+                true
+            }
+            ShellState::Paused => {
+                self.state = ShellState::Gameplay;
+                true
+            }
+            ShellState::Settings | ShellState::ConfirmQuit => {
+                self.state = self.back;
+                true
+            }
+        }
+    }
+
+    /// Adds a page to the settings screen.
+    pub fn add_settings_page(&mut self, title: impl Into<String>, show: fn(&World, &mut Ui)) {
+        self.pages.push(SettingsPage {
+            title: title.into(),
+            show,
+        });
+    }
+
+    /// Adds "Game" and "Controls" pages that edit `Settings<T>` resource.
+    ///
+    /// Game inserts the resource with `arcana::settings::init_settings`.
+    /// Applied settings are committed with `arcana::settings::commit_settings`.
+    pub fn add_game_settings<T>(&mut self)
+    where
+        T: GameSettings,
+    {
+        self.add_settings_page("Game", crate::ui::game_settings_page::<T>);
+        self.add_settings_page("Controls", crate::ui::controls_page::<T>);
+        self.capture = Some(crate::ui::capture_binding::<T>);
+    }
+
+    pub fn settings_pages(&self) -> &[SettingsPage] {
+        &self.pages
+    }
+
+    pub(crate) fn screen_mut(&mut self) -> &mut SettingsScreen {
+        &mut self.screen
+    }
+
+    /// Returns function that feeds input to settings screen
+    /// if it is shown.
+    pub(crate) fn capture(&self) -> Option<fn(&World, &ViewInput) -> bool> {
+        match self.state {
+            ShellState::Settings => self.capture,
+            _ => None,
+        }
+    }
+
+    pub(crate) fn page(&self) -> usize {
+        self.page
+    }
+
+    pub(crate) fn set_page(&mut self, page: usize) {
+        self.page = page;
+    }
+}
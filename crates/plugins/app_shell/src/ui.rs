@@ -0,0 +1,214 @@
+use arcana::{
+    input::ViewInput,
+    settings::{commit_settings, GameSettings, Settings},
+    window::ExitRequest,
+    ClockStep, World,
+};
+use arcana_egui::{Align2, Area, CentralPanel, Context, Egui, Id, SidePanel, Ui, Window};
+
+use crate::state::{AppShell, ShellState};
+
+pub(crate) fn show(world: &World) {
+    let Some(mut egui) = world.get_resource_mut::<Egui>() else {
+        return;
+    };
+
+    let now = world.expect_resource::<ClockStep>().now;
+
+    egui.run(now, |cx| {
+        let mut shell = world.expect_resource_mut::<AppShell>();
+
+        match shell.state() {
+            ShellState::Boot => boot(cx),
+            ShellState::Menu => menu(cx, &mut shell),
+            ShellState::Gameplay => {}
+            ShellState::Paused => pause(cx, &mut shell),
+            ShellState::Settings => {
+                // Pages may access `AppShell` too.
+                let page = shell.page();
+                let pages = shell
+                    .settings_pages()
+                    .iter()
+                    .map(|p| (p.title.clone(), p.show))
+                    .collect::<Vec<_>>();
+                drop(shell);
+
+                let mut selected = page.min(pages.len().saturating_sub(1));
+                let close = settings(cx, world, &pages, &mut selected);
+
+                let mut shell = world.expect_resource_mut::<AppShell>();
+                shell.set_page(selected);
+                if close {
+                    shell.back();
+                }
+            }
+            ShellState::ConfirmQuit => {
+                if confirm_quit(cx, &mut shell) {
+                    shell.confirm_quit();
+                    if let Some(mut exit) = world.get_resource_mut::<ExitRequest>() {
+                        exit.request();
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Settings page with game settings values.
+pub(crate) fn game_settings_page<T>(world: &World, ui: &mut Ui)
+where
+    T: GameSettings,
+{
+    let Some(mut settings) = world.get_resource_mut::<Settings<T>>() else {
+        ui.label("Game settings are not loaded");
+        return;
+    };
+
+    let mut shell = world.expect_resource_mut::<AppShell>();
+    let apply = shell.screen_mut().show_values(&mut settings, ui);
+    drop(shell);
+    drop(settings);
+
+    if apply {
+        commit_settings::<T>(world);
+    }
+}
+
+/// Settings page that rebinds actions of the game settings.
+pub(crate) fn controls_page<T>(world: &World, ui: &mut Ui)
+where
+    T: GameSettings,
+{
+    let Some(mut settings) = world.get_resource_mut::<Settings<T>>() else {
+        ui.label("Game settings are not loaded");
+        return;
+    };
+
+    let mut shell = world.expect_resource_mut::<AppShell>();
+    let apply = shell.screen_mut().show_controls(&mut settings, ui);
+    drop(shell);
+    drop(settings);
+
+    if apply {
+        commit_settings::<T>(world);
+    }
+}
+
+/// Binds pressed input to the action when controls page waits for it.
+pub(crate) fn capture_binding<T>(world: &World, input: &ViewInput) -> bool
+where
+    T: GameSettings,
+{
+    let Some(mut settings) = world.get_resource_mut::<Settings<T>>() else {
+        return false;
+    };
+
+    let mut shell = world.expect_resource_mut::<AppShell>();
+    shell.screen_mut().capture(&mut settings, input)
+}
+
+fn boot(cx: &Context) {
+    Area::new(Id::new("app-shell-boot"))
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(cx, |ui| {
+            ui.spinner();
+            ui.label("Loading...");
+        });
+}
+
+fn menu(cx: &Context, shell: &mut AppShell) {
+    CentralPanel::default().show(cx, |ui| {
+        ui.vertical_centered(|ui| {
+            ui.heading(shell.title().to_owned());
+            ui.add_space(20.0);
+
+            if ui.button("Play").clicked() {
+                shell.start();
+            }
+            if ui.button("Settings").clicked() {
+                shell.open_settings();
+            }
+            if ui.button("Quit").clicked() {
+                shell.request_quit();
+            }
+        });
+    });
+}
+
+fn pause(cx: &Context, shell: &mut AppShell) {
+    Window::new("Paused")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(cx, |ui| {
+            ui.vertical_centered(|ui| {
+                if ui.button("Resume").clicked() {
+                    shell.resume();
+                }
+                if ui.button("Settings").clicked() {
+                    shell.open_settings();
+                }
+                if ui.button("Main menu").clicked() {
+                    shell.exit_to_menu();
+                }
+                if ui.button("Quit").clicked() {
+                    shell.request_quit();
+                }
+            });
+        });
+}
+
+/// Returns true if settings should be closed.
+fn settings(
+    cx: &Context,
+    world: &World,
+    pages: &[(String, fn(&World, &mut arcana_egui::Ui))],
+    selected: &mut usize,
+) -> bool {
+    let mut close = false;
+
+    SidePanel::left("app-shell-settings-pages").show(cx, |ui| {
+        ui.heading("Settings");
+
+        for (idx, (title, _)) in pages.iter().enumerate() {
+            ui.selectable_value(selected, idx, title);
+        }
+
+        ui.separator();
+        if ui.button("Back").clicked() {
+            close = true;
+        }
+    });
+
+    CentralPanel::default().show(cx, |ui| match pages.get(*selected) {
+        None => {
+            ui.label("No settings available");
+        }
+        Some((_, show)) => show(world, ui),
+    });
+
+    close
+}
+
+/// Returns true if user confirmed quitting.
+fn confirm_quit(cx: &Context, shell: &mut AppShell) -> bool {
+    let mut quit = false;
+
+    Window::new("Quit")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(cx, |ui| {
+            ui.label("Are you sure you want to quit?");
+            ui.horizontal(|ui| {
+                if ui.button("Quit").clicked() {
+                    quit = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    shell.back();
+                }
+            });
+        });
+
+    quit
+}
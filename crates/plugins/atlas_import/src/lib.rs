@@ -0,0 +1,167 @@
+//! This plugin provides importer that packs images into [`Atlas`].
+//!
+//! Source is a JSON manifest with `.atlas` extension that lists images to pack.
+//!
+//! ```json
+//! {
+//!     "images": ["hero.png", "tiles/grass.png"],
+//!     "max_size": 2048,
+//!     "padding": 1
+//! }
+//! ```
+//!
+//! Image paths are relative to the manifest.
+//! Images that do not fit into one page spill into additional pages.
+//!
+//! [`Atlas`]: arcana::assets::Atlas
+
+use std::{fmt::Display, path::Path};
+
+use arcana::{
+    assets::{
+        atlas::{AtlasData, AtlasPageData, AtlasRegion},
+        import::{ensure, AssetDependencies, AssetSources, ImportError, Importer},
+        sprite_sheet::SpriteRect,
+    },
+    ident, name, Ident, Name,
+};
+
+arcana::declare_plugin!();
+
+mod pack;
+
+fn default_max_size() -> u32 {
+    2048
+}
+
+fn default_padding() -> u32 {
+    1
+}
+
+#[derive(serde::Deserialize)]
+struct AtlasManifest {
+    images: Vec<String>,
+
+    #[serde(default = "default_max_size")]
+    max_size: u32,
+
+    #[serde(default = "default_padding")]
+    padding: u32,
+}
+
+/// Packs images listed in manifest into atlas pages.
+#[arcana::importer]
+#[derive(Default)]
+pub struct AtlasImporter;
+
+impl AtlasImporter {
+    pub fn new() -> Self {
+        AtlasImporter
+    }
+}
+
+impl Importer for AtlasImporter {
+    fn name(&self) -> Name {
+        name!(atlas)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["atlas"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["atlas"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(atlas)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        sources: &mut dyn AssetSources,
+        _dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let manifest = std::fs::read(source).map_err(error_to_reason)?;
+        let manifest: AtlasManifest = serde_json::from_slice(&manifest).map_err(error_to_reason)?;
+
+        let mut missing = Vec::new();
+        let paths = manifest
+            .images
+            .iter()
+            .map(|image| sources.get_or_append(image, &mut missing))
+            .collect::<Vec<_>>();
+        ensure(missing, Vec::new())?;
+
+        let images = paths
+            .into_iter()
+            .flatten()
+            .map(|path| Ok(image::open(path).map_err(error_to_reason)?.to_rgba8()))
+            .collect::<Result<Vec<_>, ImportError>>()?;
+
+        let sizes = images
+            .iter()
+            .map(|image| (image.width(), image.height()))
+            .collect::<Vec<_>>();
+
+        let packed =
+            pack::pack(&sizes, manifest.max_size, manifest.padding).map_err(error_to_reason)?;
+
+        let mut pages = packed
+            .pages
+            .iter()
+            .map(|&(width, height)| AtlasPageData {
+                width,
+                height,
+                pixels: vec![0; 4 * width as usize * height as usize],
+            })
+            .collect::<Vec<_>>();
+
+        let mut regions = Vec::with_capacity(images.len());
+
+        for ((name, image), placement) in
+            manifest.images.iter().zip(&images).zip(&packed.placements)
+        {
+            let page = &mut pages[placement.page as usize];
+
+            let row_bytes = 4 * image.width() as usize;
+            for row in 0..image.height() as usize {
+                let src = row * row_bytes;
+                let dst =
+                    4 * ((placement.y as usize + row) * page.width as usize + placement.x as usize);
+                page.pixels[dst..dst + row_bytes]
+                    .copy_from_slice(&image.as_raw()[src..src + row_bytes]);
+            }
+
+            let (page_width, page_height) = (page.width as f32, page.height as f32);
+
+            regions.push(AtlasRegion {
+                name: name.clone(),
+                page: placement.page,
+                rect: SpriteRect {
+                    x: placement.x as i32,
+                    y: placement.y as i32,
+                    width: image.width(),
+                    height: image.height(),
+                },
+                uv: [
+                    placement.x as f32 / page_width,
+                    placement.y as f32 / page_height,
+                    (placement.x + image.width()) as f32 / page_width,
+                    (placement.y + image.height()) as f32 / page_height,
+                ],
+            });
+        }
+
+        let atlas = AtlasData { pages, regions };
+        std::fs::write(output, atlas.encode()).map_err(error_to_reason)
+    }
+}
+
+fn error_to_reason<E: Display>(error: E) -> ImportError {
+    ImportError::Other {
+        reason: error.to_string(),
+    }
+}
@@ -0,0 +1,139 @@
+//! Shelf packing of rectangles into pages.
+
+#[derive(Debug, thiserror::Error)]
+#[error("Image {index} of size {width}x{height} does not fit into {max_size}x{max_size} page")]
+pub struct TooLarge {
+    index: usize,
+    width: u32,
+    height: u32,
+    max_size: u32,
+}
+
+pub struct Placement {
+    pub page: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+pub struct Packed {
+    /// Size of each page.
+    pub pages: Vec<(u32, u32)>,
+
+    /// Placement of each rectangle, in input order.
+    pub placements: Vec<Placement>,
+}
+
+struct Shelf {
+    y: u32,
+    height: u32,
+    x: u32,
+}
+
+struct Page {
+    shelves: Vec<Shelf>,
+    width: u32,
+    height: u32,
+}
+
+/// Packs rectangles into as few pages as this simple algorithm can.
+///
+/// Rectangles are sorted by height and placed on horizontal shelves.
+/// Padding is kept between rectangles, but not at page edges.
+pub fn pack(sizes: &[(u32, u32)], max_size: u32, padding: u32) -> Result<Packed, TooLarge> {
+    let mut order = (0..sizes.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&index| {
+        let (width, height) = sizes[index];
+        (std::cmp::Reverse(height), std::cmp::Reverse(width))
+    });
+
+    let mut pages: Vec<Page> = Vec::new();
+    let mut placements = Vec::with_capacity(sizes.len());
+    placements.resize_with(sizes.len(), || None);
+
+    for index in order {
+        let (width, height) = sizes[index];
+
+        if width > max_size || height > max_size {
+            return Err(TooLarge {
+                index,
+                width,
+                height,
+                max_size,
+            });
+        }
+
+        let placement = pages
+            .iter_mut()
+            .enumerate()
+            .find_map(|(page_index, page)| {
+                let (x, y) = page.place(width, height, max_size, padding)?;
+                Some(Placement {
+                    page: page_index as u32,
+                    x,
+                    y,
+                })
+            })
+            .unwrap_or_else(|| {
+                let mut page = Page {
+                    shelves: Vec::new(),
+                    width: 0,
+                    height: 0,
+                };
+                let (x, y) = page
+                    .place(width, height, max_size, padding)
+                    .expect("Rectangle must fit into empty page");
+                pages.push(page);
+
+                Placement {
+                    page: pages.len() as u32 - 1,
+                    x,
+                    y,
+                }
+            });
+
+        placements[index] = Some(placement);
+    }
+
+    Ok(Packed {
+        pages: pages.iter().map(|page| (page.width, page.height)).collect(),
+        placements: placements.into_iter().map(Option::unwrap).collect(),
+    })
+}
+
+impl Page {
+    fn place(
+        &mut self,
+        width: u32,
+        height: u32,
+        max_size: u32,
+        padding: u32,
+    ) -> Option<(u32, u32)> {
+        for shelf in &mut self.shelves {
+            if height <= shelf.height && shelf.x + width <= max_size {
+                let x = shelf.x;
+                shelf.x += width + padding;
+                self.width = self.width.max(x + width);
+                return Some((x, shelf.y));
+            }
+        }
+
+        let y = match self.shelves.last() {
+            None => 0,
+            Some(last) => last.y + last.height + padding,
+        };
+
+        if y + height > max_size {
+            return None;
+        }
+
+        self.shelves.push(Shelf {
+            y,
+            height,
+            x: width + padding,
+        });
+
+        self.width = self.width.max(width);
+        self.height = y + height;
+        Some((0, y))
+    }
+}
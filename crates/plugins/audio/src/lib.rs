@@ -0,0 +1,42 @@
+//! This plugin plays sounds.
+//!
+//! Sounds are played by `AudioSource` components that reference `AudioClip` assets.
+//! Each source is routed into a bus of the `Mixer` resource
+//! where volume of a group of sounds can be changed at once.
+//!
+//! Sources may be positioned in 2D space.
+//! Such sources are attenuated and panned based on their `Global` transform
+//! relative to the entity with `AudioListener` component.
+//!
+//! Output goes to the default device of the system.
+//! If there is no device, sources finish immediately.
+//!
+//! Flow code can play sounds with `FlowEntityExt::play_sound`.
+
+use arcana::World;
+
+arcana::declare_plugin!([scene ...]);
+
+mod mixer;
+mod output;
+mod source;
+
+pub use self::{
+    mixer::{BusId, Mixer},
+    output::{AudioOutput, OutputError},
+    source::{AudioListener, AudioSource, FlowEntityExt, Spatial},
+};
+
+#[arcana::init]
+fn init_audio(world: &mut World) {
+    let output = match AudioOutput::start() {
+        Ok(output) => output,
+        Err(err) => {
+            tracing::error!("Failed to start audio output: {err}");
+            AudioOutput::silent()
+        }
+    };
+
+    world.insert_resource(output);
+    world.insert_resource(Mixer::new());
+}
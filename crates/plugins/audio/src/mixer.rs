@@ -0,0 +1,89 @@
+/// Identifies bus in the [`Mixer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BusId(usize);
+
+impl BusId {
+    /// Bus all other buses are mixed into.
+    pub const MASTER: BusId = BusId(0);
+}
+
+struct Bus {
+    name: String,
+    volume: f32,
+    muted: bool,
+}
+
+/// Groups sources into named buses with their own volume.
+///
+/// Every bus is mixed into [`BusId::MASTER`],
+/// so master volume affects all sounds.
+pub struct Mixer {
+    buses: Vec<Bus>,
+}
+
+impl Mixer {
+    pub fn new() -> Self {
+        Mixer {
+            buses: vec![Bus {
+                name: "master".to_owned(),
+                volume: 1.0,
+                muted: false,
+            }],
+        }
+    }
+
+    /// Adds new bus with unit volume.
+    /// Returns existing bus if one with the same name exists.
+    pub fn add_bus(&mut self, name: &str) -> BusId {
+        if let Some(id) = self.find_bus(name) {
+            return id;
+        }
+
+        self.buses.push(Bus {
+            name: name.to_owned(),
+            volume: 1.0,
+            muted: false,
+        });
+        BusId(self.buses.len() - 1)
+    }
+
+    pub fn find_bus(&self, name: &str) -> Option<BusId> {
+        self.buses.iter().position(|b| b.name == name).map(BusId)
+    }
+
+    pub fn buses(&self) -> impl Iterator<Item = (BusId, &str)> + '_ {
+        self.buses
+            .iter()
+            .enumerate()
+            .map(|(idx, bus)| (BusId(idx), bus.name.as_str()))
+    }
+
+    pub fn volume(&self, bus: BusId) -> f32 {
+        self.buses[bus.0].volume
+    }
+
+    pub fn set_volume(&mut self, bus: BusId, volume: f32) {
+        self.buses[bus.0].volume = volume.max(0.0);
+    }
+
+    pub fn is_muted(&self, bus: BusId) -> bool {
+        self.buses[bus.0].muted
+    }
+
+    pub fn set_muted(&mut self, bus: BusId, muted: bool) {
+        self.buses[bus.0].muted = muted;
+    }
+
+    /// Returns gain applied to sources routed into the bus,
+    /// including master volume.
+    pub fn gain(&self, bus: BusId) -> f32 {
+        let gain = |bus: &Bus| if bus.muted { 0.0 } else { bus.volume };
+
+        let master = gain(&self.buses[BusId::MASTER.0]);
+        if bus == BusId::MASTER {
+            return master;
+        }
+
+        master * gain(&self.buses[bus.0])
+    }
+}
@@ -0,0 +1,269 @@
+use std::sync::Arc;
+
+use arcana::{assets::AudioClip, hashbrown::HashMap};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use parking_lot::Mutex;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OutputError {
+    #[error("No audio output device")]
+    NoDevice,
+
+    #[error("Audio output device does not support 32-bit float samples")]
+    NoFloatFormat,
+
+    #[error(transparent)]
+    DefaultConfig(#[from] cpal::DefaultStreamConfigError),
+
+    #[error(transparent)]
+    SupportedConfigs(#[from] cpal::SupportedStreamConfigsError),
+
+    #[error(transparent)]
+    BuildStream(#[from] cpal::BuildStreamError),
+
+    #[error(transparent)]
+    PlayStream(#[from] cpal::PlayStreamError),
+
+    #[error("Failed to spawn audio thread: {0}")]
+    Thread(#[from] std::io::Error),
+
+    #[error("Audio thread exited unexpectedly")]
+    ThreadExited,
+}
+
+/// Sound being played by the output.
+pub(crate) struct Voice {
+    pub clip: AudioClip,
+
+    /// Position in clip frames.
+    pub cursor: f64,
+
+    /// Gain for left and right channels.
+    pub gain: [f32; 2],
+
+    pub looping: bool,
+    pub paused: bool,
+}
+
+impl Voice {
+    /// Adds voice samples to the interleaved output buffer.
+    /// Returns `false` when clip has ended.
+    fn mix(&mut self, data: &mut [f32], channels: usize, sample_rate: u32) -> bool {
+        let clip_channels = self.clip.channels() as usize;
+        let frames = self.clip.frames();
+        let samples = self.clip.samples();
+
+        if frames == 0 {
+            return false;
+        }
+
+        // Clip is resampled to the device rate on the fly.
+        let step = self.clip.sample_rate() as f64 / sample_rate as f64;
+
+        // Mono clips are played on both channels.
+        let sample = |frame: usize, channel: usize| {
+            samples[frame * clip_channels + channel.min(clip_channels - 1)]
+        };
+
+        for out in data.chunks_exact_mut(channels) {
+            if self.cursor >= frames as f64 {
+                if !self.looping {
+                    return false;
+                }
+                self.cursor %= frames as f64;
+            }
+
+            let idx = self.cursor as usize;
+            let next = match idx + 1 {
+                next if next < frames => next,
+                _ if self.looping => 0,
+                _ => idx,
+            };
+            let t = self.cursor.fract() as f32;
+
+            let left = lerp(sample(idx, 0), sample(next, 0), t) * self.gain[0];
+            let right = lerp(sample(idx, 1), sample(next, 1), t) * self.gain[1];
+
+            match out {
+                [mono] => *mono += (left + right) * 0.5,
+                [l, r, ..] => {
+                    *l += left;
+                    *r += right;
+                }
+                [] => unreachable!(),
+            }
+
+            self.cursor += step;
+        }
+
+        true
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// State shared between the world and audio thread.
+pub(crate) struct Playback {
+    next_id: u64,
+    voices: HashMap<u64, Voice>,
+
+    /// Voices that reached the end since last check.
+    finished: Vec<u64>,
+}
+
+impl Playback {
+    fn new() -> Self {
+        Playback {
+            next_id: 0,
+            voices: HashMap::new(),
+            finished: Vec::new(),
+        }
+    }
+
+    pub fn add(&mut self, voice: Voice) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.voices.insert(id, voice);
+        id
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Voice> {
+        self.voices.get_mut(&id)
+    }
+
+    /// Stops voices that are not kept.
+    pub fn retain(&mut self, mut keep: impl FnMut(u64) -> bool) {
+        self.voices.retain(|&id, _| keep(id));
+    }
+
+    pub fn take_finished(&mut self) -> Vec<u64> {
+        std::mem::take(&mut self.finished)
+    }
+
+    fn mix(&mut self, data: &mut [f32], channels: usize, sample_rate: u32) {
+        data.fill(0.0);
+
+        let finished = &mut self.finished;
+        self.voices.retain(|&id, voice| {
+            if voice.paused {
+                return true;
+            }
+
+            let playing = voice.mix(data, channels, sample_rate);
+            if !playing {
+                finished.push(id);
+            }
+            playing
+        });
+
+        for sample in data {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+    }
+}
+
+/// Connection to the audio output device.
+///
+/// Device stream lives on a separate thread
+/// which is stopped when this resource is dropped.
+pub struct AudioOutput {
+    playback: Arc<Mutex<Playback>>,
+    sample_rate: u32,
+
+    /// Dropping the sender wakes up audio thread to stop the stream.
+    stop: Option<flume::Sender<()>>,
+}
+
+impl AudioOutput {
+    /// Starts playing to the default output device.
+    pub fn start() -> Result<Self, OutputError> {
+        let playback = Arc::new(Mutex::new(Playback::new()));
+
+        let (ready_tx, ready_rx) = flume::bounded(1);
+        let (stop_tx, stop_rx) = flume::bounded::<()>(0);
+
+        let thread_playback = playback.clone();
+        std::thread::Builder::new()
+            .name("audio-output".to_owned())
+            .spawn(move || {
+                // Stream may not be `Send`, so it is created and dropped on this thread.
+                let stream = match open_stream(thread_playback) {
+                    Ok((stream, sample_rate)) => {
+                        let _ = ready_tx.send(Ok(sample_rate));
+                        stream
+                    }
+                    Err(err) => {
+                        let _ = ready_tx.send(Err(err));
+                        return;
+                    }
+                };
+
+                let _ = stop_rx.recv();
+                drop(stream);
+            })?;
+
+        let sample_rate = ready_rx.recv().map_err(|_| OutputError::ThreadExited)??;
+
+        Ok(AudioOutput {
+            playback,
+            sample_rate,
+            stop: Some(stop_tx),
+        })
+    }
+
+    /// Returns output that plays nothing.
+    pub fn silent() -> Self {
+        AudioOutput {
+            playback: Arc::new(Mutex::new(Playback::new())),
+            sample_rate: 0,
+            stop: None,
+        }
+    }
+
+    /// Returns `true` if output is connected to a device.
+    pub fn is_active(&self) -> bool {
+        self.stop.is_some()
+    }
+
+    /// Sample rate of the output device.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub(crate) fn playback(&self) -> parking_lot::MutexGuard<'_, Playback> {
+        self.playback.lock()
+    }
+}
+
+fn open_stream(playback: Arc<Mutex<Playback>>) -> Result<(cpal::Stream, u32), OutputError> {
+    let host = cpal::default_host();
+    let device = host.default_output_device().ok_or(OutputError::NoDevice)?;
+
+    let mut supported = device.default_output_config()?;
+    if supported.sample_format() != cpal::SampleFormat::F32 {
+        supported = device
+            .supported_output_configs()?
+            .find(|c| c.sample_format() == cpal::SampleFormat::F32)
+            .ok_or(OutputError::NoFloatFormat)?
+            .with_max_sample_rate();
+    }
+
+    let config: cpal::StreamConfig = supported.config();
+    let channels = config.channels as usize;
+    let sample_rate = config.sample_rate.0;
+
+    let stream = device.build_output_stream(
+        &config,
+        move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+            playback.lock().mix(data, channels, sample_rate);
+        },
+        |err| tracing::error!("Audio output error: {err}"),
+        None,
+    )?;
+
+    stream.play()?;
+
+    Ok((stream, sample_rate))
+}
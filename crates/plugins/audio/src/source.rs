@@ -0,0 +1,274 @@
+use std::{
+    f32::consts::{FRAC_PI_4, SQRT_2},
+    task::{Poll, Waker},
+};
+
+use arcana::{
+    assets::{AudioClip, Handle, LoadState},
+    edict::{self, Component, Res, View},
+    flow::FlowEntity,
+    hashbrown::HashSet,
+    With,
+};
+use na::Point2;
+use scene::dim2::Global;
+
+use crate::{
+    mixer::{BusId, Mixer},
+    output::{AudioOutput, Voice},
+};
+
+/// Marks entity that hears spatial sources.
+///
+/// Only first listener found is used.
+#[derive(Clone, Copy, Debug, Default, Component)]
+pub struct AudioListener;
+
+/// Distance attenuation of spatial source.
+#[derive(Clone, Copy, Debug)]
+pub struct Spatial {
+    /// Source is heard at full volume closer than this.
+    pub min_distance: f32,
+
+    /// Source is not heard farther than this.
+    pub max_distance: f32,
+}
+
+impl Default for Spatial {
+    fn default() -> Self {
+        Spatial {
+            min_distance: 1.0,
+            max_distance: 50.0,
+        }
+    }
+}
+
+impl Spatial {
+    /// Returns gain for left and right channels
+    /// for source at given position relative to listener.
+    fn gains(&self, relative: Point2<f32>) -> [f32; 2] {
+        let distance = relative.coords.norm();
+
+        let range = (self.max_distance - self.min_distance).max(f32::EPSILON);
+        let attenuation = 1.0 - ((distance - self.min_distance) / range).clamp(0.0, 1.0);
+
+        // Sources within minimum distance are panned less.
+        let pan = (relative.x / distance.max(self.min_distance)).clamp(-1.0, 1.0);
+
+        // Constant power panning, scaled to unit gain at the center.
+        let angle = (pan + 1.0) * FRAC_PI_4;
+        [
+            angle.cos() * SQRT_2 * attenuation,
+            angle.sin() * SQRT_2 * attenuation,
+        ]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SourceState {
+    /// Waiting for clip to load.
+    Pending,
+    Playing {
+        voice: u64,
+    },
+    Finished,
+}
+
+/// Plays audio clip.
+///
+/// Playback starts as soon as clip is loaded.
+/// Non-looping source stays on entity after it finishes
+/// and can be restarted with [`AudioSource::play`].
+#[derive(Component)]
+pub struct AudioSource {
+    pub clip: Handle<AudioClip>,
+    pub bus: BusId,
+    pub volume: f32,
+    pub looping: bool,
+    pub paused: bool,
+
+    /// Makes source positioned by its `Global` transform.
+    /// Sources without transform are heard as if at listener position.
+    pub spatial: Option<Spatial>,
+
+    state: SourceState,
+    waker: Option<Waker>,
+}
+
+impl Drop for AudioSource {
+    fn drop(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl AudioSource {
+    pub fn new(clip: Handle<AudioClip>) -> Self {
+        AudioSource {
+            clip,
+            bus: BusId::MASTER,
+            volume: 1.0,
+            looping: false,
+            paused: false,
+            spatial: None,
+            state: SourceState::Pending,
+            waker: None,
+        }
+    }
+
+    pub fn with_bus(mut self, bus: BusId) -> Self {
+        self.bus = bus;
+        self
+    }
+
+    pub fn with_volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    pub fn looping(mut self) -> Self {
+        self.looping = true;
+        self
+    }
+
+    pub fn spatial(mut self, spatial: Spatial) -> Self {
+        self.spatial = Some(spatial);
+        self
+    }
+
+    /// Restarts playback from the beginning.
+    pub fn play(&mut self) {
+        self.state = SourceState::Pending;
+    }
+
+    /// Stops playback.
+    pub fn stop(&mut self) {
+        self.finish();
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self.state, SourceState::Playing { .. })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state == SourceState::Finished
+    }
+
+    fn finish(&mut self) {
+        self.state = SourceState::Finished;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn gains(&self, global: Option<&Global>, listener: Option<&Global>) -> [f32; 2] {
+        match (self.spatial, global, listener) {
+            (Some(spatial), Some(global), Some(listener)) => {
+                let position = Point2::from(global.iso.translation.vector);
+                spatial.gains(listener.iso.inverse_transform_point(&position))
+            }
+            _ => [1.0, 1.0],
+        }
+    }
+}
+
+/// Starts, updates and stops voices of audio sources.
+#[arcana::system]
+fn audio_system(
+    mut sources: View<(&mut AudioSource, Option<&Global>)>,
+    listeners: View<&Global, With<AudioListener>>,
+    mixer: Res<Mixer>,
+    output: Res<AudioOutput>,
+) {
+    let listener = listeners.iter().next();
+
+    let mut playback = output.playback();
+    let finished = playback.take_finished().into_iter().collect::<HashSet<_>>();
+    let mut alive = HashSet::new();
+
+    for (source, global) in sources.iter_mut() {
+        let gain = source
+            .gains(global, listener)
+            .map(|g| g * source.volume * mixer.gain(source.bus));
+
+        match source.state {
+            SourceState::Pending => match source.clip.state() {
+                LoadState::Queued | LoadState::Loading => {}
+                LoadState::Failed => source.finish(),
+                LoadState::Loaded if !output.is_active() => source.finish(),
+                LoadState::Loaded => {
+                    let Some(clip) = source.clip.get() else {
+                        continue;
+                    };
+
+                    let voice = playback.add(Voice {
+                        clip,
+                        cursor: 0.0,
+                        gain,
+                        looping: source.looping,
+                        paused: source.paused,
+                    });
+
+                    alive.insert(voice);
+                    source.state = SourceState::Playing { voice };
+                }
+            },
+            SourceState::Playing { voice } => {
+                if finished.contains(&voice) {
+                    source.finish();
+                    continue;
+                }
+
+                match playback.get_mut(voice) {
+                    None => source.finish(),
+                    Some(v) => {
+                        v.gain = gain;
+                        v.looping = source.looping;
+                        v.paused = source.paused;
+                        alive.insert(voice);
+                    }
+                }
+            }
+            SourceState::Finished => {}
+        }
+    }
+
+    // Voices of removed, stopped or restarted sources.
+    playback.retain(|voice| alive.contains(&voice));
+}
+
+/// Extension trait for `FlowEntity` to play sounds.
+#[allow(async_fn_in_trait)]
+pub trait FlowEntityExt {
+    /// Plays clip at the entity and waits until it finishes.
+    ///
+    /// Replaces audio source of the entity if there is one.
+    async fn play_sound(&mut self, clip: Handle<AudioClip>);
+
+    /// Waits until audio source of the entity finishes.
+    /// Returns immediately if the entity has no audio source.
+    async fn sound_finished(&mut self);
+}
+
+impl FlowEntityExt for FlowEntity<'_> {
+    async fn play_sound(&mut self, clip: Handle<AudioClip>) {
+        let source = AudioSource::new(clip).spatial(Spatial::default());
+        if self.set(source).is_err() {
+            return;
+        }
+
+        self.sound_finished().await;
+    }
+
+    async fn sound_finished(&mut self) {
+        self.try_poll_view_mut::<&mut AudioSource, _, _>(|source, cx| {
+            if source.is_finished() {
+                return Poll::Ready(());
+            }
+            source.waker = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await;
+    }
+}
@@ -0,0 +1,161 @@
+//! This plugin provides importer for audio files.
+//!
+//! WAV, Ogg Vorbis and FLAC files are decoded into interleaved
+//! 32-bit float samples that [`AudioClip`] asset loads as is.
+//!
+//! [`AudioClip`]: arcana::assets::AudioClip
+
+use std::{fmt::Display, fs::File, path::Path};
+
+use arcana::{
+    assets::{
+        audio::AudioData,
+        import::{AssetDependencies, AssetSources, ImportError, Importer},
+    },
+    ident, name, tracing, Ident, Name,
+};
+use symphonia::core::{
+    audio::SampleBuffer,
+    codecs::{DecoderOptions, CODEC_TYPE_NULL},
+    errors::Error as DecodeError,
+    formats::FormatOptions,
+    io::MediaSourceStream,
+    meta::MetadataOptions,
+    probe::Hint,
+};
+
+arcana::declare_plugin!();
+
+/// Decodes audio files into PCM samples.
+#[arcana::importer]
+#[derive(Default)]
+pub struct AudioImporter;
+
+impl AudioImporter {
+    pub fn new() -> Self {
+        AudioImporter
+    }
+}
+
+impl Importer for AudioImporter {
+    fn name(&self) -> Name {
+        name!(audio)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["wav", "ogg", "flac"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["wav", "wave", "ogg", "oga", "flac"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(audio)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        _sources: &mut dyn AssetSources,
+        _dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let audio = decode(source)?;
+        std::fs::write(output, audio.encode()).map_err(error_to_reason)
+    }
+}
+
+fn decode(source: &Path) -> Result<AudioData, ImportError> {
+    let file = File::open(source).map_err(error_to_reason)?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(extension) = source.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(extension);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(error_to_reason)?;
+
+    let mut format = probed.format;
+
+    let Some(track) = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+    else {
+        return Err(ImportError::Other {
+            reason: "No audio track found".to_owned(),
+        });
+    };
+
+    let track_id = track.id;
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(error_to_reason)?;
+
+    let mut audio = AudioData {
+        sample_rate: track.codec_params.sample_rate.unwrap_or(0),
+        channels: track
+            .codec_params
+            .channels
+            .map_or(0, |channels| channels.count() as u16),
+        samples: Vec::new(),
+    };
+
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(error))
+                if error.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                // End of stream.
+                break;
+            }
+            Err(error) => return Err(error_to_reason(error)),
+        };
+
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        match decoder.decode(&packet) {
+            Ok(decoded) => {
+                let spec = *decoded.spec();
+                audio.sample_rate = spec.rate;
+                audio.channels = spec.channels.count() as u16;
+
+                let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+                buffer.copy_interleaved_ref(decoded);
+                audio.samples.extend_from_slice(buffer.samples());
+            }
+            Err(DecodeError::DecodeError(error)) => {
+                // Corrupted packet, skip it.
+                tracing::warn!("Skipping audio packet in '{}': {error}", source.display());
+            }
+            Err(error) => return Err(error_to_reason(error)),
+        }
+    }
+
+    if audio.channels == 0 || audio.sample_rate == 0 {
+        return Err(ImportError::Other {
+            reason: "Audio stream has unknown channel layout or sample rate".to_owned(),
+        });
+    }
+
+    Ok(audio)
+}
+
+fn error_to_reason<E: Display>(error: E) -> ImportError {
+    ImportError::Other {
+        reason: error.to_string(),
+    }
+}
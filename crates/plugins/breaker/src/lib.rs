@@ -1,14 +1,15 @@
 use arcana::{
+    color::Color,
     edict::{self, spawn_block, ActionEncoder, Component, Entities, Res, View, World},
-    flow::sleep,
+    flow::{despawn_token, sleep},
     gametime::{timespan, TimeSpan},
     na,
+    random::Rng,
     render::RenderGraph,
-    viewport::Viewport,
     ClockStep,
 };
 use camera::Camera2;
-use cursor::MainCursor;
+use cursor::WorldCursor;
 use motion::dim2::{Motion, Motor, MoveAfter, MoveTo};
 use physics::dim2::{Collider, ContactForceEvents, FlowEntityExt, PhysicsResource, RigidBody};
 use scene::dim2::Global;
@@ -28,31 +29,11 @@ arcana::export_arcana_plugin! {
             cursor ...,
         ],
         systems: [
-            target_cursor: move |cursor: Res<MainCursor>,
-                viewport: Res<Viewport>,
-                mut motion: View<&mut Motion>,
-                cameras: View<(&Camera2, &Global)>| {
-                    let extent = viewport.extent();
-
-                    // Ignore when viewport is zero-sized.
-                    if extent.width() == 0 || extent.height() == 0 {
-                        return;
-                    }
-
-                    let point = na::Point2::new(cursor.x / extent.width() as f32 * 2.0 - 1.0, 1.0 - cursor.y / extent.height() as f32 * 2.0);
-
-                    let ratio = extent.width() as f32 / extent.height() as f32;
-
-                    let (camera, camera_global) = cameras.try_get(camera).unwrap();
-
-                    let position = camera
-                        .viewport
-                        .transform(1.0, ratio)
-                        .transform_point(&point);
-
-                    let position = camera_global.iso.transform_point(&position);
+            target_cursor: move |cursor: Res<WorldCursor>, mut motion: View<&mut Motion>| {
+                if let Some(position) = cursor.position() {
                     *motion.try_get_mut(target).unwrap() = MoveTo::new(position).into();
-                },
+                }
+            },
             burst_system,
         ],
 
@@ -61,6 +42,8 @@ arcana::export_arcana_plugin! {
                 .spawn((Global::identity(), Camera2::new().with_fovy(15.0)))
                 .id();
 
+            world.expect_resource_mut::<WorldCursor>().set_camera(camera);
+
             {
                 let world = world.local();
                 let mut graph = world.expect_resource_mut::<RenderGraph>();
@@ -80,15 +63,15 @@ arcana::export_arcana_plugin! {
             let target = world.allocate().id();
             let mut last_ball = target;
 
+            let color = {
+                let mut rng = world.expect_resource_mut::<Rng>();
+                Color::srgb(rng.gen(), rng.gen(), rng.gen())
+            };
+
             world.insert_bundle(
                 target,
                 (
-                    sdf::Shape::circle(1.0).with_color([
-                        rand::random(),
-                        rand::random(),
-                        rand::random(),
-                        1.0,
-                    ]),
+                    sdf::Shape::circle(1.0).with_color(color),
                     Global::identity(),
                     RigidBody::dynamic(),
                     Collider::ball(1.0),
@@ -114,19 +97,18 @@ arcana::export_arcana_plugin! {
             let mut new_node = move |world: &mut World| {
                 let id = world.allocate().id();
 
-                let global = Global::from_position(na::Point2::new(
-                    rand::random::<f32>() * 26.0 - 13.0,
-                    rand::random::<f32>() * 26.0 - 13.0,
-                ));
+                let (global, color) = {
+                    let mut rng = world.expect_resource_mut::<Rng>();
+                    let global = Global::from_position(na::Point2::new(
+                        rng.gen_range(-13.0..13.0),
+                        rng.gen_range(-13.0..13.0),
+                    ));
+                    (global, Color::srgb(rng.gen(), rng.gen(), rng.gen()))
+                };
 
                 world
                     .insert_bundle(id, (
-                        sdf::Shape::circle(1.0).with_color([
-                            rand::random(),
-                            rand::random(),
-                            rand::random(),
-                            1.0,
-                        ]),
+                        sdf::Shape::circle(1.0).with_color(color),
                         RigidBody::dynamic().position(global.iso),
                         global,
                         Collider::ball(1.0).enable_contact_force_events().contact_force_event_threshold(2000.0),
@@ -138,9 +120,15 @@ arcana::export_arcana_plugin! {
 
                 last_ball = id;
 
+                let Ok(despawned) = despawn_token(world, last_ball) else {
+                    return;
+                };
+
                 spawn_block!(in world for last_ball -> {
-                    last_ball.next_contact_force_event().await;
-                    let _ = last_ball.insert(Burst { span: TimeSpan::ZERO, scale: 1.0, color: [0.0, 0.0, 0.0] });
+                    if despawned.run(last_ball.next_contact_force_event()).await.is_none() {
+                        return;
+                    }
+                    let _ = last_ball.insert(Burst { span: TimeSpan::ZERO, scale: 1.0, color: Color::BLACK });
                 });
             };
 
@@ -159,7 +147,7 @@ arcana::export_arcana_plugin! {
 struct Burst {
     span: TimeSpan,
     scale: f32,
-    color: [f32; 3],
+    color: Color,
 }
 
 fn burst_system(
@@ -171,8 +159,7 @@ fn burst_system(
 ) {
     for (e, burst, shape, global) in burst {
         if burst.span == TimeSpan::ZERO {
-            let [r, g, b, _] = shape.color;
-            burst.color = [r, g, b];
+            burst.color = shape.color;
         }
 
         burst.span += clock.step;
@@ -199,10 +186,9 @@ fn burst_system(
         } else {
             let x = (1.0 / (3.001 - burst.span.as_secs_f32())).sin();
             if x.fract() < 0.1 {
-                shape.color = [1.0, 1.0, 1.0, 1.0];
+                shape.color = Color::WHITE;
             } else {
-                let [r, g, b] = burst.color;
-                shape.color = [r, g, b, 1.0];
+                shape.color = burst.color;
             }
 
             let new_scale = 2f32.powf(burst.span.as_secs_f32() / 3.0);
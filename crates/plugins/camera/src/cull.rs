@@ -0,0 +1,72 @@
+//! Visibility tests against camera view.
+//!
+//! Render jobs build [`ViewBounds`] or [`Frustum`] once per frame
+//! and skip instances whose bounding circle or sphere is outside.
+//! Tests are conservative, some invisible instances may pass.
+
+use arcana::na;
+
+/// World-space rectangle covered by 2D camera.
+#[derive(Clone, Copy, Debug)]
+pub struct ViewBounds {
+    pub min: na::Point2<f32>,
+    pub max: na::Point2<f32>,
+}
+
+impl ViewBounds {
+    /// Returns bounds of the view with given view to world transform.
+    pub fn new(view_to_world: &na::Affine2<f32>) -> Self {
+        let mut min = na::Point2::new(f32::INFINITY, f32::INFINITY);
+        let mut max = na::Point2::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+
+        for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+            let p = view_to_world.transform_point(&na::Point2::new(x, y));
+            min = min.inf(&p);
+            max = max.sup(&p);
+        }
+
+        ViewBounds { min, max }
+    }
+
+    /// Returns true if circle may be visible.
+    pub fn intersects_circle(&self, center: &na::Point2<f32>, radius: f32) -> bool {
+        center.x + radius >= self.min.x
+            && center.x - radius <= self.max.x
+            && center.y + radius >= self.min.y
+            && center.y - radius <= self.max.y
+    }
+}
+
+/// Volume visible by 3D camera.
+#[derive(Clone, Copy, Debug)]
+pub struct Frustum {
+    /// Planes with normals pointing inside.
+    planes: [na::Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /// Returns frustum of OpenGL-style projection-view matrix,
+    /// with clip space depth in `[-1, 1]`.
+    pub fn new(view_proj: &na::Matrix4<f32>) -> Self {
+        let row = |i: usize| view_proj.row(i).transpose();
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r3 + r2, r3 - r2].map(|plane| {
+            let len = plane.xyz().norm();
+            if len > 0.0 {
+                plane / len
+            } else {
+                plane
+            }
+        });
+
+        Frustum { planes }
+    }
+
+    /// Returns true if sphere may be visible.
+    pub fn intersects_sphere(&self, center: &na::Point3<f32>, radius: f32) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.xyz().dot(&center.coords) + plane.w >= -radius)
+    }
+}
@@ -0,0 +1,59 @@
+//! Routing of entities to cameras.
+//!
+//! Entity with [`RenderLayers`] component is rendered only by cameras
+//! whose mask shares at least one layer with it.
+//! Entities without the component are on [`RenderLayers::DEFAULT`] layer.
+
+use arcana::edict::{self, Component};
+
+/// Bitmask of layers entity belongs to,
+/// or layers camera renders when used as camera mask.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Component)]
+#[repr(transparent)]
+pub struct RenderLayers(pub u32);
+
+impl Default for RenderLayers {
+    fn default() -> Self {
+        RenderLayers::DEFAULT
+    }
+}
+
+impl RenderLayers {
+    /// Number of available layers.
+    pub const COUNT: u8 = 32;
+
+    /// Layer of entities without the component.
+    pub const DEFAULT: Self = RenderLayers(1);
+
+    pub const NONE: Self = RenderLayers(0);
+    pub const ALL: Self = RenderLayers(u32::MAX);
+
+    /// Returns mask with single layer.
+    pub const fn layer(layer: u8) -> Self {
+        assert!(layer < Self::COUNT, "Render layer is out of range");
+        RenderLayers(1 << layer)
+    }
+
+    pub const fn with(self, layer: u8) -> Self {
+        RenderLayers(self.0 | Self::layer(layer).0)
+    }
+
+    pub const fn without(self, layer: u8) -> Self {
+        RenderLayers(self.0 & !Self::layer(layer).0)
+    }
+
+    pub const fn contains(&self, layer: u8) -> bool {
+        self.0 & Self::layer(layer).0 != 0
+    }
+
+    /// Returns true if masks share at least one layer.
+    pub const fn intersects(&self, other: &Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Returns true if camera with this mask renders entity
+    /// with optional layers component.
+    pub fn renders(&self, layers: Option<&RenderLayers>) -> bool {
+        self.intersects(layers.unwrap_or(&RenderLayers::DEFAULT))
+    }
+}
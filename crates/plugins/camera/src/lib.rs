@@ -1,13 +1,20 @@
 use arcana::{
-    edict::{self, Component},
-    export_arcana_plugin, na,
+    edict::{self, query::Cpy, Component, EntityId, World},
+    gizmo::GizmoCamera,
+    na,
+    render::current_camera,
 };
+use scene::dim2::Global;
 
-export_arcana_plugin! {
-    CameraPlugin {
-        components: [Camera2],
-    }
-}
+arcana::declare_plugin!([scene ...]);
+
+pub mod cull;
+pub mod layers;
+
+pub use self::{
+    cull::{Frustum, ViewBounds},
+    layers::RenderLayers,
+};
 
 #[derive(Clone, Copy, Component)]
 pub struct Camera2 {
@@ -16,6 +23,9 @@ pub struct Camera2 {
 
     /// Parallax applied to the layers this camera renders.
     pub parallax: f32,
+
+    /// Render layers this camera renders.
+    pub layers: RenderLayers,
 }
 
 #[derive(Clone, Copy)]
@@ -25,19 +35,59 @@ pub enum ViewRect {
 
     /// ViewRect with fixed aspect ratio.
     FovXY(f32, f32),
+
+    /// ViewRect where each world unit covers whole number of device pixels.
+    /// View grows with the target instead of stretching the image,
+    /// so pixel art doesn't shimmer.
+    PixelPerfect { pixels_per_unit: f32 },
 }
 
 impl ViewRect {
-    pub fn transform(&self, scale: f32, ratio: f32) -> na::Affine2<f32> {
+    /// Returns transform from view space to camera space
+    /// for target of `width` × `height` device pixels.
+    pub fn transform(&self, scale: f32, width: u32, height: u32) -> na::Affine2<f32> {
+        let ratio = width as f32 / height as f32;
+
         let (x, y) = match *self {
             ViewRect::FovY(y) => (y * ratio, y),
             ViewRect::FovXY(x, y) => (x, y),
+            ViewRect::PixelPerfect { pixels_per_unit } => {
+                let ppu = snap_pixels_per_unit(pixels_per_unit);
+                (width as f32 * 0.5 / ppu, height as f32 * 0.5 / ppu)
+            }
         };
 
         let scaling = na::Vector3::new(x * scale, y * scale, 1.0);
         let scaling = na::Matrix3::from_diagonal(&scaling);
         na::Affine2::from_matrix_unchecked(scaling)
     }
+
+    /// Returns device pixels per world unit
+    /// for target of `width` × `height` device pixels.
+    pub fn pixels_per_unit(&self, width: u32, height: u32) -> f32 {
+        match *self {
+            ViewRect::FovY(y) => height as f32 * 0.5 / y,
+            ViewRect::FovXY(_, y) => height as f32 * 0.5 / y,
+            ViewRect::PixelPerfect { pixels_per_unit } => snap_pixels_per_unit(pixels_per_unit),
+        }
+    }
+}
+
+/// Rounds scale to whole device pixels, never below one.
+fn snap_pixels_per_unit(pixels_per_unit: f32) -> f32 {
+    pixels_per_unit.round().max(1.0)
+}
+
+/// Snaps coordinate to device pixel grid.
+///
+/// With odd number of pixels view center lies in the middle of a pixel,
+/// otherwise on its edge.
+fn snap_to_pixels(value: f32, ppu: f32, pixels: u32) -> f32 {
+    if pixels % 2 == 0 {
+        (value * ppu).round() / ppu
+    } else {
+        ((value * ppu - 0.5).round() + 0.5) / ppu
+    }
 }
 
 impl Camera2 {
@@ -45,6 +95,7 @@ impl Camera2 {
         Self {
             viewport: ViewRect::FovY(1.0),
             parallax: 1.0,
+            layers: RenderLayers::DEFAULT,
         }
     }
 
@@ -67,4 +118,164 @@ impl Camera2 {
         self.parallax = parallax;
         self
     }
+
+    pub const fn with_pixels_per_unit(mut self, pixels_per_unit: f32) -> Self {
+        self.viewport = ViewRect::PixelPerfect { pixels_per_unit };
+        self
+    }
+
+    pub const fn with_layers(mut self, layers: RenderLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Returns transform from view space to world space
+    /// for camera at `iso` and target of `width` × `height` device pixels.
+    ///
+    /// In pixel-perfect mode camera position is snapped to device pixels.
+    pub fn view_to_world(
+        &self,
+        iso: &na::Isometry2<f32>,
+        width: u32,
+        height: u32,
+    ) -> na::Affine2<f32> {
+        let mut iso = *iso;
+
+        if let ViewRect::PixelPerfect { pixels_per_unit } = self.viewport {
+            let ppu = snap_pixels_per_unit(pixels_per_unit);
+            let t = &mut iso.translation.vector;
+            t.x = snap_to_pixels(t.x, ppu, width);
+            t.y = snap_to_pixels(t.y, ppu, height);
+        }
+
+        iso * self.viewport.transform(1.0, width, height)
+    }
+
+    /// Returns effective device pixels per world unit
+    /// for target of `width` × `height` device pixels.
+    pub fn pixels_per_unit(&self, width: u32, height: u32) -> f32 {
+        self.viewport.pixels_per_unit(width, height)
+    }
+
+    /// Returns world-space rectangle visible by camera at `iso`.
+    pub fn view_bounds(&self, iso: &na::Isometry2<f32>, width: u32, height: u32) -> ViewBounds {
+        ViewBounds::new(&self.view_to_world(iso, width, height))
+    }
+}
+
+/// Perspective camera for 3D scenes.
+///
+/// Looks along negative Z axis of its `Global` transform.
+#[derive(Clone, Copy, Component)]
+pub struct Camera3 {
+    /// Vertical field of view in radians.
+    pub fovy: f32,
+
+    /// Distance to the near clipping plane.
+    pub near: f32,
+
+    /// Distance to the far clipping plane.
+    pub far: f32,
+
+    /// Render layers this camera renders.
+    pub layers: RenderLayers,
+}
+
+impl Camera3 {
+    pub const fn new() -> Self {
+        Self {
+            fovy: std::f32::consts::FRAC_PI_3,
+            near: 0.1,
+            far: 1000.0,
+            layers: RenderLayers::DEFAULT,
+        }
+    }
+
+    pub const fn with_fovy(mut self, fovy: f32) -> Self {
+        self.fovy = fovy;
+        self
+    }
+
+    pub const fn with_clip(mut self, near: f32, far: f32) -> Self {
+        self.near = near;
+        self.far = far;
+        self
+    }
+
+    pub const fn with_layers(mut self, layers: RenderLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Returns projection for target with given aspect ratio.
+    pub fn projection(&self, ratio: f32) -> na::Perspective3<f32> {
+        na::Perspective3::new(ratio, self.fovy, self.near, self.far)
+    }
+
+    /// Returns volume visible by camera at `iso`
+    /// for target with given aspect ratio.
+    pub fn frustum(&self, iso: &na::Isometry3<f32>, ratio: f32) -> Frustum {
+        let view_proj = self.projection(ratio).to_homogeneous() * iso.inverse().to_homogeneous();
+        Frustum::new(&view_proj)
+    }
+}
+
+/// Lets editor gizmos project entities into views rendered with `Camera2`.
+#[arcana::init]
+fn init_gizmo_camera(world: &mut World) {
+    world.insert_resource(GizmoCamera {
+        view_to_world: gizmo_view_to_world,
+    });
+}
+
+/// Uses camera selected by the view, otherwise picks one as render jobs do.
+fn gizmo_view_to_world(
+    world: &World,
+    camera: Option<EntityId>,
+    width: u32,
+    height: u32,
+) -> Option<na::Affine2<f32>> {
+    let (global, camera) = match camera.or_else(|| current_camera(world)) {
+        Some(entity) => {
+            let global = world.get::<Cpy<Global>>(entity).ok()?;
+            let camera = world.get::<Cpy<Camera2>>(entity).ok()?;
+            (global, camera)
+        }
+        None => world
+            .view::<(&Global, &Camera2)>()
+            .iter()
+            .next()
+            .map(|(g, c)| (*g, *c))?,
+    };
+
+    Some(camera.view_to_world(&global.iso, width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gizmo_pixel_perfect() {
+        let mut world = World::new();
+        let camera = world
+            .spawn((
+                Global::new(na::Isometry2::translation(3.0, 0.0)),
+                Camera2::new().with_pixels_per_unit(10.0),
+            ))
+            .id();
+
+        // View grows with the target, one unit is always 10 pixels.
+        for (width, height) in [(200, 100), (640, 480)] {
+            let view_to_world = gizmo_view_to_world(&world, Some(camera), width, height).unwrap();
+            let corner = view_to_world.transform_point(&na::Point2::new(1.0, 1.0));
+            assert_eq!(
+                corner,
+                na::Point2::new(3.0 + width as f32 / 20.0, height as f32 / 20.0)
+            );
+        }
+
+        // Without explicit camera first one is used.
+        assert!(gizmo_view_to_world(&world, None, 200, 100).is_some());
+    }
 }
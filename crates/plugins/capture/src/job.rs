@@ -0,0 +1,174 @@
+use arcana::{
+    edict::world::World,
+    hashbrown::HashMap,
+    mev, tracing,
+    work::{Exec, Image2D, Job, JobDesc, JobIdx, Planner},
+};
+
+use crate::recorder::{Capture, CapturedFrame};
+
+/// Number of readback buffers.
+/// Buffer is read when its turn comes again,
+/// after waiting for the frame that recorded the copy.
+/// GPU is likely done with it by then, so waiting rarely blocks.
+const READBACK_SLOTS: usize = 3;
+
+struct Readback {
+    buffer: mev::Buffer,
+    extent: mev::Extent2,
+
+    /// Target has blue and red channels swapped.
+    bgra: bool,
+
+    /// Run of the work graph that recorded the copy.
+    frame: u64,
+
+    /// Copy was recorded and not read yet.
+    pending: bool,
+}
+
+#[derive(Default)]
+struct Node {
+    readbacks: Vec<Option<Readback>>,
+    frame: usize,
+}
+
+#[arcana::job]
+pub struct CaptureFrames {
+    nodes: HashMap<JobIdx, Node>,
+}
+
+impl CaptureFrames {
+    pub fn desc() -> JobDesc {
+        arcana::job_desc! [
+            main: mut Image2D,
+        ]
+    }
+
+    pub fn new() -> Self {
+        CaptureFrames {
+            nodes: HashMap::new(),
+        }
+    }
+}
+
+impl Job for CaptureFrames {
+    fn plan(&mut self, mut planner: Planner<'_>, _world: &mut World) {
+        // Target is only copied, content is passed through unchanged.
+        planner.update::<Image2D>();
+    }
+
+    fn exec(&mut self, runner: Exec<'_>, world: &mut World) {
+        let Some(target) = runner.update::<Image2D>() else {
+            return;
+        };
+
+        let Some(mut capture) = world.get_resource_mut::<Capture>() else {
+            return;
+        };
+
+        let node = self.nodes.entry(runner.idx()).or_default();
+        node.readbacks.resize_with(READBACK_SLOTS, || None);
+        node.frame = node.frame.wrapping_add(1);
+        let slot = node.frame % READBACK_SLOTS;
+
+        // Take result of the copy recorded `READBACK_SLOTS` frames ago.
+        if let Some(readback) = &mut node.readbacks[slot] {
+            if readback.pending {
+                // Buffer is neither mapped nor reused until the copy completes.
+                if let Err(err) = runner.wait_frame(readback.frame) {
+                    tracing::error!("Failed to wait for capture readback: {err:?}");
+                    capture.stop();
+                    return;
+                }
+                readback.pending = false;
+
+                let extent = readback.extent;
+                let mut pixels = vec![0; extent.width() as usize * extent.height() as usize * 4];
+                unsafe {
+                    readback.buffer.read_unchecked(0, &mut pixels);
+                }
+
+                if readback.bgra {
+                    for pixel in pixels.chunks_exact_mut(4) {
+                        pixel.swap(0, 2);
+                    }
+                }
+
+                capture.push(CapturedFrame {
+                    width: extent.width(),
+                    height: extent.height(),
+                    pixels,
+                });
+            }
+        }
+
+        if !capture.frame_due() {
+            return;
+        }
+
+        let bgra = match target.format() {
+            mev::PixelFormat::Rgba8Unorm | mev::PixelFormat::Rgba8Srgb => false,
+            mev::PixelFormat::Bgra8Unorm | mev::PixelFormat::Bgra8Srgb => true,
+            format => {
+                tracing::error!("Can't capture target with format {format:?}");
+                capture.stop();
+                return;
+            }
+        };
+
+        if !target.usage().contains(mev::ImageUsage::TRANSFER_SRC) {
+            tracing::error!("Can't capture target without TRANSFER_SRC usage");
+            capture.stop();
+            return;
+        }
+
+        capture.frame_taken();
+
+        let extent = target.extent().expect_2d();
+        let bytes_per_line = 4 * extent.width() as usize;
+        let size = bytes_per_line * extent.height() as usize;
+        if size == 0 {
+            return;
+        }
+
+        let device = runner.device();
+        let readback = match &mut node.readbacks[slot] {
+            Some(readback) if readback.buffer.size() >= size => readback,
+            slot => slot.insert(Readback {
+                buffer: device
+                    .new_buffer(mev::BufferDesc {
+                        size,
+                        name: "capture-readback",
+                        usage: mev::BufferUsage::TRANSFER_DST,
+                        memory: mev::Memory::Download,
+                    })
+                    .unwrap(),
+                extent,
+                bgra,
+                frame: 0,
+                pending: false,
+            }),
+        };
+
+        let encoder = runner.new_encoder();
+        encoder.barrier(mev::PipelineStages::all(), mev::PipelineStages::TRANSFER);
+        encoder.copy().copy_image_to_buffer(
+            target,
+            mev::Offset3::ZERO,
+            extent.to_3d(),
+            0..1,
+            0,
+            &readback.buffer,
+            0,
+            bytes_per_line,
+            0,
+        );
+        encoder.barrier(mev::PipelineStages::TRANSFER, mev::PipelineStages::all());
+
+        readback.extent = extent;
+        readback.bgra = bgra;
+        readback.frame = runner.frame();
+        readback.pending = true;
+    }
+}
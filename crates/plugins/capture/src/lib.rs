@@ -0,0 +1,86 @@
+//! This plugin records the presented image into GIF or MP4 files
+//! for trailers and bug reports.
+//!
+//! `CaptureFrames` job copies its target into host memory at capture frame rate
+//! and passes it through unchanged, so it goes right before presenting.
+//! Copies are read back a few frames later to avoid stalling on GPU,
+//! and encoded on a background thread.
+//! GIFs are encoded in-process, MP4 is encoded by `ffmpeg` executable.
+//!
+//! Recording is controlled with `Capture` resource or `capture` console command:
+//!
+//! ```text
+//! capture start captures/bug.mp4
+//! capture stop
+//! ```
+
+use arcana::{
+    console::{add_command, Command, CommandError},
+    edict::world::World,
+};
+
+arcana::declare_plugin!();
+
+mod job;
+mod recorder;
+
+pub use self::{
+    job::CaptureFrames,
+    recorder::{Capture, CaptureError, CaptureFormat},
+};
+
+const USAGE: &str = "capture start <path.gif|path.mp4> [fps] | capture stop";
+
+#[arcana::init]
+fn init_capture(world: &mut World) {
+    world.insert_resource(Capture::new());
+
+    add_command(
+        world,
+        Command {
+            name: "capture",
+            help: "capture start <path> [fps] | stop - records presented image into GIF or MP4",
+            run: capture_command,
+            complete: Some(|_, args| match args {
+                [prefix] => ["start", "stop"]
+                    .into_iter()
+                    .filter(|c| c.starts_with(prefix))
+                    .map(str::to_owned)
+                    .collect(),
+                _ => Vec::new(),
+            }),
+        },
+    );
+}
+
+fn capture_command(world: &mut World, args: &[&str]) -> Result<String, CommandError> {
+    let mut capture = world.expect_resource_mut::<Capture>();
+
+    match args {
+        ["start", path, rest @ ..] => {
+            match rest {
+                [] => {}
+                [fps] => {
+                    let fps = fps
+                        .parse()
+                        .map_err(|_| CommandError::Failed(format!("Invalid frame rate '{fps}'")))?;
+                    capture.set_fps(fps);
+                }
+                _ => return Err(CommandError::Usage(USAGE)),
+            }
+
+            capture
+                .start(*path)
+                .map_err(|err| CommandError::Failed(err.to_string()))?;
+            Ok(format!("Capturing to '{path}' at {} fps", capture.fps()))
+        }
+        ["stop"] => {
+            if !capture.is_recording() {
+                return Err(CommandError::Failed("Capture is not running".to_owned()));
+            }
+            capture.stop();
+            Ok("Capture stopped".to_owned())
+        }
+        _ => Err(CommandError::Usage(USAGE)),
+    }
+}
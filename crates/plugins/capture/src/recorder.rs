@@ -0,0 +1,279 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Child, ChildStdin, Stdio},
+    time::{Duration, Instant},
+};
+
+use arcana::{sandbox, tracing};
+use image::{codecs::gif, Delay, Frame, RgbaImage};
+
+/// Frames waiting for encoder.
+/// Frames are dropped when encoder falls behind instead of stalling the game.
+const QUEUE_SIZE: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureFormat {
+    Gif,
+
+    /// Encoded by `ffmpeg` executable that must be in `PATH`.
+    Mp4,
+}
+
+impl CaptureFormat {
+    /// Returns format that matches extension of the path.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        match ext.to_ascii_lowercase().as_str() {
+            "gif" => Some(CaptureFormat::Gif),
+            "mp4" => Some(CaptureFormat::Mp4),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CaptureError {
+    #[error("Capture is already running")]
+    AlreadyRecording,
+
+    #[error("Unsupported capture format of '{}', expected .gif or .mp4", .0.display())]
+    UnsupportedFormat(PathBuf),
+}
+
+/// Frame pixels in RGBA8 format, rows from the top.
+pub(crate) struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+struct Recording {
+    path: PathBuf,
+    frames: flume::Sender<CapturedFrame>,
+    started: Instant,
+    captured: u64,
+}
+
+/// Resource that controls capture of the presented image.
+///
+/// `CaptureFrames` job must be in the render graph for frames to be captured.
+pub struct Capture {
+    fps: u32,
+    recording: Option<Recording>,
+}
+
+impl Capture {
+    pub fn new() -> Self {
+        Capture {
+            fps: 30,
+            recording: None,
+        }
+    }
+
+    pub fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    /// Sets capture frame rate.
+    /// Takes effect on next recording.
+    pub fn set_fps(&mut self, fps: u32) {
+        self.fps = fps.max(1);
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    /// Returns path of the file being recorded.
+    pub fn path(&self) -> Option<&Path> {
+        self.recording.as_ref().map(|r| r.path.as_path())
+    }
+
+    /// Starts recording into the file.
+    /// Format is chosen by file extension.
+    pub fn start(&mut self, path: impl Into<PathBuf>) -> Result<(), CaptureError> {
+        if self.recording.is_some() {
+            return Err(CaptureError::AlreadyRecording);
+        }
+
+        let path = path.into();
+        let Some(format) = CaptureFormat::from_path(&path) else {
+            return Err(CaptureError::UnsupportedFormat(path));
+        };
+
+        let (tx, rx) = flume::bounded(QUEUE_SIZE);
+        let fps = self.fps;
+        let thread_path = path.clone();
+        std::thread::Builder::new()
+            .name("capture".to_owned())
+            .spawn(move || encode(format, &thread_path, fps, rx))
+            .expect("Failed to spawn capture thread");
+
+        tracing::info!("Capturing to '{}'", path.display());
+
+        self.recording = Some(Recording {
+            path,
+            frames: tx,
+            started: Instant::now(),
+            captured: 0,
+        });
+        Ok(())
+    }
+
+    /// Stops recording.
+    /// Encoder finishes the file in background.
+    pub fn stop(&mut self) {
+        if let Some(recording) = self.recording.take() {
+            tracing::info!(
+                "Capture to '{}' stopped after {} frames",
+                recording.path.display(),
+                recording.captured
+            );
+        }
+    }
+
+    /// Returns true if next frame is due.
+    ///
+    /// Frames are taken at fixed rate of real time,
+    /// so video plays at the speed the game was seen.
+    pub(crate) fn frame_due(&self) -> bool {
+        let Some(recording) = &self.recording else {
+            return false;
+        };
+
+        let due = Duration::from_secs(recording.captured) / self.fps;
+        recording.started.elapsed() >= due
+    }
+
+    /// Marks that frame is taken.
+    pub(crate) fn frame_taken(&mut self) {
+        if let Some(recording) = &mut self.recording {
+            recording.captured += 1;
+        }
+    }
+
+    /// Sends frame to the encoder.
+    pub(crate) fn push(&mut self, frame: CapturedFrame) {
+        let Some(recording) = &self.recording else {
+            return;
+        };
+
+        match recording.frames.try_send(frame) {
+            Ok(()) => {}
+            Err(flume::TrySendError::Full(_)) => {
+                tracing::warn!("Capture encoder falls behind, frame dropped");
+            }
+            Err(flume::TrySendError::Disconnected(_)) => {
+                // Encoder failed and already logged the error.
+                self.recording = None;
+            }
+        }
+    }
+}
+
+fn encode(format: CaptureFormat, path: &Path, fps: u32, frames: flume::Receiver<CapturedFrame>) {
+    let result = match format {
+        CaptureFormat::Gif => encode_gif(path, fps, frames),
+        CaptureFormat::Mp4 => encode_mp4(path, fps, frames),
+    };
+
+    match result {
+        Ok(()) => tracing::info!("Capture saved to '{}'", path.display()),
+        Err(err) => tracing::error!("Failed to capture to '{}': {err}", path.display()),
+    }
+}
+
+fn create_file(path: &Path) -> std::io::Result<std::fs::File> {
+    let key = crate::arcana_plugin::sandbox_key();
+    if let Some(parent) = path.parent() {
+        sandbox::fs::create_dir_all(key, parent)?;
+    }
+    sandbox::fs::create(key, path)
+}
+
+fn encode_gif(path: &Path, fps: u32, frames: flume::Receiver<CapturedFrame>) -> Result<(), String> {
+    let file = create_file(path).map_err(|err| err.to_string())?;
+    let mut encoder = gif::GifEncoder::new_with_speed(std::io::BufWriter::new(file), 10);
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|err| err.to_string())?;
+
+    let delay = Delay::from_numer_denom_ms(1000, fps);
+    let mut size = None;
+
+    for frame in frames.iter() {
+        // GIF can't change size, frames after resize are skipped.
+        if *size.get_or_insert((frame.width, frame.height)) != (frame.width, frame.height) {
+            continue;
+        }
+
+        let Some(image) = RgbaImage::from_raw(frame.width, frame.height, frame.pixels) else {
+            continue;
+        };
+
+        encoder
+            .encode_frame(Frame::from_parts(image, 0, 0, delay))
+            .map_err(|err| err.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Raw frames are piped into `ffmpeg` process.
+fn encode_mp4(path: &Path, fps: u32, frames: flume::Receiver<CapturedFrame>) -> Result<(), String> {
+    let key = crate::arcana_plugin::sandbox_key();
+    if let Some(parent) = path.parent() {
+        sandbox::fs::create_dir_all(key, parent).map_err(|err| err.to_string())?;
+    }
+
+    let mut ffmpeg: Option<(Child, ChildStdin, (u32, u32))> = None;
+
+    for frame in frames.iter() {
+        let (_, stdin, size) = match &mut ffmpeg {
+            Some(ffmpeg) => ffmpeg,
+            slot => {
+                let mut child = sandbox::process::command(key, "ffmpeg")
+                    .map_err(|err| err.to_string())?
+                    .args(["-y", "-loglevel", "error", "-f", "rawvideo"])
+                    .args(["-pixel_format", "rgba"])
+                    .arg("-video_size")
+                    .arg(format!("{}x{}", frame.width, frame.height))
+                    .arg("-framerate")
+                    .arg(fps.to_string())
+                    .args(["-i", "-", "-c:v", "libx264", "-pix_fmt", "yuv420p"])
+                    // H.264 requires even dimensions.
+                    .args(["-vf", "crop=trunc(iw/2)*2:trunc(ih/2)*2"])
+                    .arg(path)
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .map_err(|err| format!("Failed to run ffmpeg: {err}"))?;
+
+                let stdin = child.stdin.take().unwrap();
+                slot.insert((child, stdin, (frame.width, frame.height)))
+            }
+        };
+
+        // Video can't change size, frames after resize are skipped.
+        if *size != (frame.width, frame.height) {
+            continue;
+        }
+
+        stdin
+            .write_all(&frame.pixels)
+            .map_err(|err| format!("Failed to write frame to ffmpeg: {err}"))?;
+    }
+
+    let Some((mut child, stdin, _)) = ffmpeg else {
+        return Err("No frames were captured".to_owned());
+    };
+
+    // Closing stdin lets ffmpeg finish the file.
+    drop(stdin);
+    let status = child.wait().map_err(|err| err.to_string())?;
+    if !status.success() {
+        return Err(format!("ffmpeg exited with {status}"));
+    }
+
+    Ok(())
+}
@@ -1,18 +1,26 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    ops::{Deref, DerefMut},
+    task::Poll,
+};
 
 use arcana::{
+    assets::{AssetId, Assets},
     blink_alloc::Blink,
-    edict::World,
-    input::{Input, InputFilter, ViewInput},
+    edict::{EntityId, Res, ResMut, View, World},
+    input::{CursorAppearance, CursorIcon, CursorImage, Input, InputFilter, ViewInput},
+    na, tracing,
+    viewport::Viewport,
 };
+use camera::Camera2;
+use physics::dim2::PhysicsResource;
+use scene::dim2::Global;
 
 arcana::export_arcana_plugin! {
     CursorPlugin {
-        resources: [MainCursor(Cursor {
-            x: 0.0,
-            y: 0.0,
-        })],
+        dependencies: [camera ..., scene ..., physics ...],
+        resources: [MainCursor::new(), WorldCursor::new()],
         filters: [cursor: CursorFilter],
+        systems: [cursor_appearance_system, world_cursor_system],
     }
 }
 
@@ -23,19 +31,195 @@ pub struct Cursor {
     pub y: f32,
 }
 
-pub struct MainCursor(Cursor);
+/// Requested change of the cursor appearance.
+enum CursorRequest {
+    Icon(CursorIcon),
+    Image(AssetId),
+    Hide,
+    Show,
+}
+
+pub struct MainCursor {
+    cursor: Cursor,
+
+    /// Requests not yet forwarded to the windowing layer.
+    requests: Vec<CursorRequest>,
+
+    /// Image asset that is being loaded.
+    loading: Option<AssetId>,
+}
 
 impl Deref for MainCursor {
     type Target = Cursor;
     fn deref(&self) -> &Cursor {
-        &self.0
+        &self.cursor
     }
 }
 
 impl DerefMut for MainCursor {
     fn deref_mut(&mut self) -> &mut Cursor {
-        &mut self.0
+        &mut self.cursor
+    }
+}
+
+impl MainCursor {
+    pub fn new() -> Self {
+        MainCursor {
+            cursor: Cursor { x: 0.0, y: 0.0 },
+            requests: Vec::new(),
+            loading: None,
+        }
+    }
+
+    /// Sets one of the system cursor icons.
+    pub fn set_icon(&mut self, icon: CursorIcon) {
+        self.requests.push(CursorRequest::Icon(icon));
+    }
+
+    /// Sets custom cursor image.
+    ///
+    /// Image is applied once the asset is loaded.
+    /// Until then previous cursor shape is kept.
+    pub fn set_image(&mut self, image: AssetId) {
+        self.requests.push(CursorRequest::Image(image));
+    }
+
+    /// Hides OS cursor over the viewport.
+    /// Useful when game renders its own cursor.
+    pub fn hide(&mut self) {
+        self.requests.push(CursorRequest::Hide);
+    }
+
+    /// Shows OS cursor again.
+    pub fn show(&mut self) {
+        self.requests.push(CursorRequest::Show);
+    }
+}
+
+/// Forwards cursor requests to the windowing layer.
+fn cursor_appearance_system(world: &mut World) {
+    let mut cursor = world.expect_resource_mut::<MainCursor>();
+    let mut appearance = world.expect_resource_mut::<CursorAppearance>();
+
+    let requests = std::mem::take(&mut cursor.requests);
+
+    for request in requests {
+        match request {
+            CursorRequest::Icon(icon) => {
+                cursor.loading = None;
+                appearance.set_icon(icon);
+            }
+            CursorRequest::Image(id) => cursor.loading = Some(id),
+            CursorRequest::Hide => appearance.hide(),
+            CursorRequest::Show => appearance.show(),
+        }
+    }
+
+    let Some(id) = cursor.loading else {
+        return;
+    };
+
+    let Some(assets) = world.get_resource::<Assets>() else {
+        tracing::warn!("Cannot load cursor image without assets");
+        cursor.loading = None;
+        return;
+    };
+
+    match assets.get::<CursorImage>(id) {
+        Poll::Pending => {}
+        Poll::Ready(Ok(image)) => {
+            cursor.loading = None;
+            appearance.set_image(image);
+        }
+        Poll::Ready(Err(err)) => {
+            tracing::error!("Failed to load cursor image {id:?}: {err}");
+            cursor.loading = None;
+        }
+    }
+}
+
+/// Cursor position in the world space.
+///
+/// Computed from `MainCursor` using transform of the camera.
+pub struct WorldCursor {
+    camera: Option<EntityId>,
+    position: Option<na::Point2<f32>>,
+    hovered: Option<EntityId>,
+}
+
+impl WorldCursor {
+    pub const fn new() -> Self {
+        WorldCursor {
+            camera: None,
+            position: None,
+            hovered: None,
+        }
+    }
+
+    /// Sets camera used to project cursor into the world.
+    /// If not set, first found `Camera2` is used.
+    pub fn set_camera(&mut self, camera: EntityId) {
+        self.camera = Some(camera);
     }
+
+    pub fn camera(&self) -> Option<EntityId> {
+        self.camera
+    }
+
+    /// Cursor position in the world space.
+    /// `None` if there is no camera or viewport is empty.
+    pub fn position(&self) -> Option<na::Point2<f32>> {
+        self.position
+    }
+
+    /// Entity under the cursor.
+    /// This is body entity if collider has one, collider entity otherwise.
+    pub fn hovered(&self) -> Option<EntityId> {
+        self.hovered
+    }
+}
+
+fn world_cursor_system(
+    cursor: Res<MainCursor>,
+    viewport: Res<Viewport>,
+    mut world_cursor: ResMut<WorldCursor>,
+    cameras: View<(&Camera2, &Global)>,
+    physics: Res<PhysicsResource>,
+) {
+    world_cursor.position = None;
+    world_cursor.hovered = None;
+
+    let extent = viewport.extent();
+
+    // Ignore when viewport is zero-sized.
+    if extent.width() == 0 || extent.height() == 0 {
+        return;
+    }
+
+    let camera = match world_cursor.camera {
+        Some(camera) => cameras.try_get(camera).ok(),
+        None => cameras.into_iter().next(),
+    };
+
+    let Some((camera, camera_global)) = camera else {
+        return;
+    };
+
+    let point = na::Point2::new(
+        cursor.x / extent.width() as f32 * 2.0 - 1.0,
+        1.0 - cursor.y / extent.height() as f32 * 2.0,
+    );
+
+    let position = camera
+        .view_to_world(&camera_global.iso, extent.width(), extent.height())
+        .transform_point(&point);
+    world_cursor.position = Some(position);
+
+    let mut hovered = None;
+    physics.intersections_with_point(&position, |collider, body| {
+        hovered.get_or_insert(body.unwrap_or(collider));
+    });
+    world_cursor.hovered = hovered;
 }
 
 struct CursorFilter;
@@ -45,7 +229,7 @@ impl InputFilter for CursorFilter {
         let mut cursor = world.expect_resource_mut::<MainCursor>();
 
         match *event {
-            Input::ViewInput { ref input } => match *input {
+            Input::ViewInput { ref input, .. } => match *input {
                 ViewInput::CursorMoved { x, y, .. } => {
                     cursor.x = x as f32;
                     cursor.y = y as f32;
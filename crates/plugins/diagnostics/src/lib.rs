@@ -0,0 +1,178 @@
+//! This plugin shows diagnostics overlay on top of the game.
+//!
+//! Overlay shows FPS, frame time graph, entity count, draw calls
+//! and render target memory read from `FrameStats` and `RenderStats` resources,
+//! as well as memory of UI textures.
+//! It is drawn with `Egui` resource if one is present in the world
+//! and toggled with a key, F3 by default.
+
+use std::time::Duration;
+
+use arcana::{
+    input::{ElementState, Input, KeyCode, PhysicalKey, ViewInput},
+    stats::{FrameStats, RenderStats},
+    ClockStep, Entities, World,
+};
+use arcana_egui::{
+    pos2, vec2, Align2, Area, Color32, Egui, Frame, Id, Order, Rect, Sense, Shape, Stroke, Ui,
+};
+
+arcana::declare_plugin!();
+
+/// Height of the frame time graph.
+const GRAPH_HEIGHT: f32 = 40.0;
+
+/// Frame time that fills the graph.
+const GRAPH_MAX: Duration = Duration::from_millis(50);
+
+/// Frame time budget of 60 FPS, marked on the graph.
+const BUDGET: Duration = Duration::from_micros(16_667);
+
+/// Resource that controls the overlay.
+pub struct Diagnostics {
+    pub visible: bool,
+
+    /// Key that toggles the overlay.
+    pub toggle_key: KeyCode,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Diagnostics {
+            visible: false,
+            toggle_key: KeyCode::F3,
+        }
+    }
+}
+
+#[arcana::init]
+fn init_diagnostics(world: &mut World) {
+    world.insert_resource(Diagnostics::new());
+}
+
+#[arcana::filter]
+fn diagnostics_filter(world: &mut World, input: &Input) -> bool {
+    let Input::ViewInput {
+        input: ViewInput::KeyboardInput { ref event, .. },
+        ..
+    } = *input
+    else {
+        return false;
+    };
+
+    if event.state != ElementState::Pressed || event.repeat {
+        return false;
+    }
+
+    let mut diagnostics = world.expect_resource_mut::<Diagnostics>();
+    if event.physical_key != PhysicalKey::Code(diagnostics.toggle_key) {
+        return false;
+    }
+
+    diagnostics.visible = !diagnostics.visible;
+    true
+}
+
+#[arcana::system]
+fn diagnostics_overlay(world: &mut World) {
+    if !world.expect_resource::<Diagnostics>().visible {
+        return;
+    }
+
+    let Some(mut egui) = world.get_resource_mut::<Egui>() else {
+        return;
+    };
+
+    let now = world.expect_resource::<ClockStep>().now;
+    let entities = world.view::<Entities>().into_iter().count();
+    let frame = world.get_resource::<FrameStats>();
+    let render = world.get_resource::<RenderStats>();
+    let ui_memory = egui.texture_memory();
+
+    egui.run(now, |cx| {
+        Area::new(Id::new("diagnostics-overlay"))
+            .order(Order::Foreground)
+            .anchor(Align2::LEFT_TOP, [8.0, 8.0])
+            .interactable(false)
+            .show(cx, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    if let Some(frame) = &frame {
+                        ui.monospace(format!(
+                            "FPS {:>6.1}  {:>6.2} ms",
+                            frame.fps(),
+                            ms(frame.average_frame_time())
+                        ));
+                        frame_graph(ui, frame);
+                        ui.monospace(format!(
+                            "Fix {:>6.2} ms ({} ticks)  Var {:>6.2} ms",
+                            ms(frame.fix_systems),
+                            frame.fix_ticks,
+                            ms(frame.var_systems)
+                        ));
+                    }
+
+                    ui.monospace(format!("Entities {entities}"));
+
+                    if let Some(render) = &render {
+                        ui.monospace(format!(
+                            "Draws {}  Culled {}  Jobs {} ({:.2} ms)",
+                            render.draw_calls,
+                            render.culled,
+                            render.jobs,
+                            ms(render.plan + render.exec)
+                        ));
+                        ui.monospace(format!(
+                            "Targets {:.1} MiB",
+                            render.target_memory as f64 / (1024.0 * 1024.0)
+                        ));
+                    }
+
+                    ui.monospace(format!(
+                        "UI textures {:.1} MiB",
+                        ui_memory as f64 / (1024.0 * 1024.0)
+                    ));
+                });
+            });
+    });
+}
+
+fn ms(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
+
+/// Draws bars of recent frame times with line at 60 FPS budget.
+fn frame_graph(ui: &mut Ui, frame: &FrameStats) {
+    let width = ui.available_width().max(120.0);
+    let (rect, _) = ui.allocate_exact_size(vec2(width, GRAPH_HEIGHT), Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    painter.rect_filled(rect, 0.0, Color32::from_black_alpha(96));
+
+    let count = frame.frame_times().len().max(1);
+    let bar = rect.width() / count as f32;
+    let height = |d: Duration| (d.as_secs_f32() / GRAPH_MAX.as_secs_f32()).min(1.0) * rect.height();
+
+    for (idx, time) in frame.frame_times().enumerate() {
+        let color = if time > BUDGET {
+            Color32::from_rgb(230, 90, 60)
+        } else {
+            Color32::from_rgb(90, 200, 120)
+        };
+
+        let x = rect.left() + idx as f32 * bar;
+        painter.rect_filled(
+            Rect::from_min_max(
+                pos2(x, rect.bottom() - height(time)),
+                pos2(x + bar, rect.bottom()),
+            ),
+            0.0,
+            color,
+        );
+    }
+
+    let y = rect.bottom() - height(BUDGET);
+    painter.add(Shape::line_segment(
+        [pos2(rect.left(), y), pos2(rect.right(), y)],
+        Stroke::new(1.0, Color32::WHITE),
+    ));
+}
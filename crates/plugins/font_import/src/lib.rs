@@ -0,0 +1,222 @@
+//! This plugin provides importer that rasterizes TTF and OTF fonts into [`Font`] glyph atlas.
+//!
+//! Font files are imported directly with default settings.
+//! To change settings, add JSON manifest with `.font` extension.
+//!
+//! ```json
+//! {
+//!     "font": "fonts/Roboto-Regular.ttf",
+//!     "size": 48,
+//!     "kind": "sdf",
+//!     "spread": 4,
+//!     "chars": "0123456789"
+//! }
+//! ```
+//!
+//! Font path is relative to the manifest.
+//! All fields except `font` are optional.
+//! `kind` is either `"sdf"` or `"bitmap"`.
+//! Printable ASCII characters are rasterized unless `chars` is specified.
+//!
+//! [`Font`]: arcana::assets::Font
+
+use std::{fmt::Display, path::Path};
+
+use arcana::{
+    assets::{
+        font::{FontData, FontMetrics, Glyph, GlyphAtlasKind, KerningPair},
+        import::{ensure, AssetDependencies, AssetSources, ImportError, Importer},
+    },
+    ident, name, Ident, Name,
+};
+
+arcana::declare_plugin!();
+
+mod raster;
+
+fn default_size() -> f32 {
+    48.0
+}
+
+fn default_spread() -> f32 {
+    4.0
+}
+
+#[derive(Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Kind {
+    #[default]
+    Sdf,
+    Bitmap,
+}
+
+#[derive(serde::Deserialize)]
+struct FontManifest {
+    font: String,
+
+    #[serde(default = "default_size")]
+    size: f32,
+
+    #[serde(default)]
+    kind: Kind,
+
+    #[serde(default = "default_spread")]
+    spread: f32,
+
+    #[serde(default)]
+    chars: Option<String>,
+}
+
+/// Rasterizes font glyphs into atlas.
+#[arcana::importer]
+#[derive(Default)]
+pub struct FontImporter;
+
+impl FontImporter {
+    pub fn new() -> Self {
+        FontImporter
+    }
+}
+
+impl Importer for FontImporter {
+    fn name(&self) -> Name {
+        name!(font)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["ttf", "otf", "font"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ttf", "otf", "font"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(font)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        sources: &mut dyn AssetSources,
+        _dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let is_manifest = source.extension().map_or(false, |ext| ext == "font");
+
+        let (manifest, font_path) = if is_manifest {
+            let manifest = std::fs::read(source).map_err(error_to_reason)?;
+            let manifest: FontManifest =
+                serde_json::from_slice(&manifest).map_err(error_to_reason)?;
+
+            let mut missing = Vec::new();
+            let path = sources.get_or_append(&manifest.font, &mut missing);
+            ensure(missing, Vec::new())?;
+
+            (manifest, path.unwrap())
+        } else {
+            let manifest = FontManifest {
+                font: String::new(),
+                size: default_size(),
+                kind: Kind::default(),
+                spread: default_spread(),
+                chars: None,
+            };
+            (manifest, source.to_owned())
+        };
+
+        let bytes = std::fs::read(&font_path).map_err(error_to_reason)?;
+        let font = fontdue::Font::from_bytes(&bytes[..], fontdue::FontSettings::default())
+            .map_err(error_to_reason)?;
+
+        let name = match font.name() {
+            Some(name) => name.to_owned(),
+            None => font_path
+                .file_stem()
+                .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned()),
+        };
+
+        let chars: Vec<char> = match &manifest.chars {
+            Some(chars) => chars.chars().collect(),
+            None => (' '..='~').collect(),
+        };
+
+        let kind = match manifest.kind {
+            Kind::Bitmap => GlyphAtlasKind::Bitmap,
+            Kind::Sdf => GlyphAtlasKind::Sdf {
+                spread: manifest.spread,
+            },
+        };
+
+        let size = manifest.size;
+        let metrics = match font.horizontal_line_metrics(size) {
+            Some(line) => FontMetrics {
+                size,
+                ascent: line.ascent,
+                descent: line.descent,
+                line_gap: line.line_gap,
+            },
+            None => {
+                return Err(ImportError::Other {
+                    reason: "Font has no horizontal metrics".to_owned(),
+                })
+            }
+        };
+
+        let chars: Vec<char> = chars
+            .into_iter()
+            .filter(|&ch| font.lookup_glyph_index(ch) != 0)
+            .collect();
+
+        let (atlas, placed) = raster::rasterize(&font, &chars, size, kind);
+
+        let glyphs = placed
+            .into_iter()
+            .map(|placed| Glyph {
+                ch: placed.ch,
+                advance: placed.advance,
+                offset: placed.offset,
+                rect: placed.rect,
+                uv: [
+                    placed.rect.x as f32 / atlas.width as f32,
+                    placed.rect.y as f32 / atlas.height as f32,
+                    (placed.rect.x as u32 + placed.rect.width) as f32 / atlas.width as f32,
+                    (placed.rect.y as u32 + placed.rect.height) as f32 / atlas.height as f32,
+                ],
+            })
+            .collect();
+
+        let mut kerning = Vec::new();
+        for &left in &chars {
+            for &right in &chars {
+                if let Some(offset) = font.horizontal_kern(left, right, size) {
+                    if offset != 0.0 {
+                        kerning.push(KerningPair {
+                            left,
+                            right,
+                            offset,
+                        });
+                    }
+                }
+            }
+        }
+
+        let data = FontData {
+            name,
+            source: bytes,
+            kind,
+            metrics,
+            atlas,
+            glyphs,
+            kerning,
+        };
+
+        std::fs::write(output, data.encode()).map_err(error_to_reason)
+    }
+}
+
+fn error_to_reason<E: Display>(error: E) -> ImportError {
+    ImportError::Other {
+        reason: error.to_string(),
+    }
+}
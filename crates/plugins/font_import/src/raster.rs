@@ -0,0 +1,184 @@
+//! Glyph rasterization and packing into atlas.
+
+use arcana::assets::{atlas::AtlasPageData, font::GlyphAtlasKind, sprite_sheet::SpriteRect};
+
+/// Padding between glyphs in the atlas.
+const PADDING: u32 = 1;
+
+pub struct PlacedGlyph {
+    pub ch: char,
+    pub advance: f32,
+    pub offset: [f32; 2],
+    pub rect: SpriteRect,
+}
+
+struct Raster {
+    width: u32,
+    height: u32,
+    values: Vec<u8>,
+}
+
+/// Rasterizes glyphs and packs them into single atlas page.
+pub fn rasterize(
+    font: &fontdue::Font,
+    chars: &[char],
+    size: f32,
+    kind: GlyphAtlasKind,
+) -> (AtlasPageData, Vec<PlacedGlyph>) {
+    let mut rasters = Vec::with_capacity(chars.len());
+    let mut glyphs = Vec::with_capacity(chars.len());
+
+    for &ch in chars {
+        let (metrics, coverage) = font.rasterize(ch, size);
+
+        let raster = Raster {
+            width: metrics.width as u32,
+            height: metrics.height as u32,
+            values: coverage,
+        };
+
+        let (raster, pad) = match kind {
+            GlyphAtlasKind::Bitmap => (raster, 0),
+            GlyphAtlasKind::Sdf { spread } => {
+                let pad = spread.ceil() as u32;
+                (distance_field(&raster, pad, spread), pad)
+            }
+        };
+
+        glyphs.push(PlacedGlyph {
+            ch,
+            advance: metrics.advance_width,
+            offset: [
+                metrics.xmin as f32 - pad as f32,
+                metrics.ymin as f32 - pad as f32,
+            ],
+            rect: SpriteRect {
+                x: 0,
+                y: 0,
+                width: raster.width,
+                height: raster.height,
+            },
+        });
+        rasters.push(raster);
+    }
+
+    let (width, height) = pack(&mut glyphs);
+
+    let mut pixels = vec![0; 4 * width as usize * height as usize];
+    for (glyph, raster) in glyphs.iter().zip(&rasters) {
+        for y in 0..raster.height {
+            for x in 0..raster.width {
+                let value = raster.values[(y * raster.width + x) as usize];
+                let px = glyph.rect.x as u32 + x;
+                let py = glyph.rect.y as u32 + y;
+                let idx = 4 * (py * width + px) as usize;
+                pixels[idx..idx + 4].copy_from_slice(&[255, 255, 255, value]);
+            }
+        }
+    }
+
+    let page = AtlasPageData {
+        width,
+        height,
+        pixels,
+    };
+
+    (page, glyphs)
+}
+
+/// Converts coverage into signed distance field padded by `pad` pixels on each side.
+///
+/// Brute force search is fine for glyph sizes.
+fn distance_field(raster: &Raster, pad: u32, spread: f32) -> Raster {
+    let width = raster.width + 2 * pad;
+    let height = raster.height + 2 * pad;
+
+    let inside = |x: i32, y: i32| {
+        if x < 0 || y < 0 || x >= raster.width as i32 || y >= raster.height as i32 {
+            return false;
+        }
+        raster.values[(y as u32 * raster.width + x as u32) as usize] >= 128
+    };
+
+    let radius = pad as i32;
+    let mut values = Vec::with_capacity((width * height) as usize);
+
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            let sx = x - radius;
+            let sy = y - radius;
+            let is_inside = inside(sx, sy);
+
+            let mut nearest = spread * spread;
+            for dy in -radius..=radius {
+                for dx in -radius..=radius {
+                    if inside(sx + dx, sy + dy) != is_inside {
+                        let d = (dx * dx + dy * dy) as f32;
+                        nearest = nearest.min(d);
+                    }
+                }
+            }
+
+            // Edge lies half way between pixel centers.
+            let distance = (nearest.sqrt() - 0.5).max(0.0);
+            let signed = if is_inside { distance } else { -distance };
+            let value = (0.5 + signed / (2.0 * spread)).clamp(0.0, 1.0);
+            values.push((value * 255.0).round() as u8);
+        }
+    }
+
+    Raster {
+        width,
+        height,
+        values,
+    }
+}
+
+/// Places glyphs on shelves.
+/// Returns atlas size.
+fn pack(glyphs: &mut [PlacedGlyph]) -> (u32, u32) {
+    let area: u32 = glyphs
+        .iter()
+        .map(|g| (g.rect.width + PADDING) * (g.rect.height + PADDING))
+        .sum();
+
+    let widest = glyphs.iter().map(|g| g.rect.width).max().unwrap_or(0);
+
+    let mut width = ((area as f32).sqrt().ceil() as u32)
+        .max(widest)
+        .max(1)
+        .next_power_of_two();
+
+    let mut order: Vec<usize> = (0..glyphs.len()).collect();
+    order.sort_by_key(|&idx| std::cmp::Reverse(glyphs[idx].rect.height));
+
+    loop {
+        let mut x = 0;
+        let mut y = 0;
+        let mut shelf = 0;
+
+        for &idx in &order {
+            let rect = &mut glyphs[idx].rect;
+
+            if x + rect.width > width {
+                x = 0;
+                y += shelf + PADDING;
+                shelf = 0;
+            }
+
+            rect.x = x as i32;
+            rect.y = y as i32;
+
+            x += rect.width + PADDING;
+            shelf = shelf.max(rect.height);
+        }
+
+        let height = (y + shelf).max(1);
+        if height <= width {
+            return (width, height);
+        }
+
+        // Keep atlas roughly square.
+        width *= 2;
+    }
+}
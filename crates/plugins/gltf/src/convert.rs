@@ -0,0 +1,223 @@
+use std::{fmt::Display, path::Path};
+
+use arcana::{
+    assets::{
+        import::{AssetDependencies, AssetDependency, AssetSources, ImportError},
+        material::{AlphaMode, Material},
+        mesh::{MeshData, PrimitiveData, Vertex},
+        AssetId,
+    },
+    ident, na, tracing,
+};
+use gltf::{buffer, image, mesh::Mode, Gltf};
+
+pub fn error_to_reason<E: Display>(error: E) -> ImportError {
+    ImportError::Other {
+        reason: error.to_string(),
+    }
+}
+
+pub fn open(source: &Path) -> Result<Gltf, ImportError> {
+    let data = std::fs::read(source).map_err(error_to_reason)?;
+    Gltf::from_slice(&data).map_err(error_to_reason)
+}
+
+/// Reads all buffers of the document.
+///
+/// External buffers are requested as sources,
+/// so that changes to them cause reimport.
+pub fn load_buffers(
+    gltf: &Gltf,
+    sources: &mut dyn AssetSources,
+    missing: &mut Vec<String>,
+) -> Result<Vec<Vec<u8>>, ImportError> {
+    let mut buffers = Vec::new();
+
+    for buffer in gltf.buffers() {
+        let data = match buffer.source() {
+            buffer::Source::Bin => match gltf.blob.as_deref() {
+                None => {
+                    return Err(ImportError::Other {
+                        reason: "glTF buffer refers to missing binary chunk".to_owned(),
+                    })
+                }
+                Some(blob) => blob.to_vec(),
+            },
+            buffer::Source::Uri(uri) => match sources.get_or_append(uri, missing) {
+                None => Vec::new(),
+                Some(path) => std::fs::read(path).map_err(error_to_reason)?,
+            },
+        };
+
+        if data.len() < buffer.length() && missing.is_empty() {
+            return Err(ImportError::Other {
+                reason: format!(
+                    "glTF buffer {} is shorter than declared {} bytes",
+                    buffer.index(),
+                    buffer.length()
+                ),
+            });
+        }
+
+        buffers.push(data);
+    }
+
+    Ok(buffers)
+}
+
+/// Collects primitives of all meshes in the default scene.
+pub fn mesh(
+    gltf: &Gltf,
+    buffers: &[Vec<u8>],
+    dependencies: &mut dyn AssetDependencies,
+    missing: &mut Vec<AssetDependency>,
+) -> Result<MeshData, ImportError> {
+    let mut mesh = MeshData::default();
+
+    let Some(scene) = gltf.default_scene().or_else(|| gltf.scenes().next()) else {
+        return Ok(mesh);
+    };
+
+    let mut stack = scene
+        .nodes()
+        .map(|node| (node, na::Matrix4::identity()))
+        .collect::<Vec<_>>();
+
+    while let Some((node, parent)) = stack.pop() {
+        let transform = parent * na::Matrix4::from(node.transform().matrix());
+
+        if let Some(node_mesh) = node.mesh() {
+            for primitive in node_mesh.primitives() {
+                if let Some(primitive) =
+                    self::primitive(primitive, &transform, buffers, dependencies, missing)?
+                {
+                    mesh.primitives.push(primitive);
+                }
+            }
+        }
+
+        stack.extend(node.children().map(|child| (child, transform)));
+    }
+
+    Ok(mesh)
+}
+
+fn primitive(
+    primitive: gltf::Primitive,
+    transform: &na::Matrix4<f32>,
+    buffers: &[Vec<u8>],
+    dependencies: &mut dyn AssetDependencies,
+    missing: &mut Vec<AssetDependency>,
+) -> Result<Option<PrimitiveData>, ImportError> {
+    if primitive.mode() != Mode::Triangles {
+        tracing::warn!(
+            "Skipping glTF primitive with {:?} mode, only triangles are supported",
+            primitive.mode()
+        );
+        return Ok(None);
+    }
+
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|b| &b[..]));
+
+    let Some(positions) = reader.read_positions() else {
+        return Err(ImportError::Other {
+            reason: "glTF primitive has no positions".to_owned(),
+        });
+    };
+
+    let linear: na::Matrix3<f32> = transform.fixed_view::<3, 3>(0, 0).into_owned();
+
+    let normal_transform = linear
+        .try_inverse()
+        .unwrap_or_else(na::Matrix3::identity)
+        .transpose();
+
+    let mut vertices = positions
+        .map(|position| Vertex {
+            position: transform
+                .transform_point(&na::Point3::from(position))
+                .coords
+                .into(),
+            ..Vertex::default()
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(normals) = reader.read_normals() {
+        for (vertex, normal) in vertices.iter_mut().zip(normals) {
+            vertex.normal = (normal_transform * na::Vector3::from(normal))
+                .normalize()
+                .into();
+        }
+    }
+
+    if let Some(uvs) = reader.read_tex_coords(0) {
+        for (vertex, uv) in vertices.iter_mut().zip(uvs.into_f32()) {
+            vertex.uv = uv;
+        }
+    }
+
+    let mut indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..vertices.len() as u32).collect(),
+    };
+
+    // Negative scale flips triangle winding.
+    if linear.determinant() < 0.0 {
+        for triangle in indices.chunks_exact_mut(3) {
+            triangle.swap(1, 2);
+        }
+    }
+
+    let material = material(primitive.material(), dependencies, missing);
+
+    Ok(Some(PrimitiveData {
+        vertices,
+        indices,
+        material: Some(material),
+    }))
+}
+
+pub fn material(
+    material: gltf::Material,
+    dependencies: &mut dyn AssetDependencies,
+    missing: &mut Vec<AssetDependency>,
+) -> Material {
+    let mut texture = |texture: gltf::Texture| -> Option<AssetId> {
+        match texture.source().source() {
+            image::Source::Uri { uri, .. } => {
+                dependencies.get_or_append(uri, ident!(texture), missing)
+            }
+            image::Source::View { .. } => {
+                tracing::warn!("Images embedded into glTF buffers are not supported");
+                None
+            }
+        }
+    };
+
+    let pbr = material.pbr_metallic_roughness();
+
+    Material {
+        base_color: pbr.base_color_factor(),
+        base_color_texture: pbr.base_color_texture().and_then(|i| texture(i.texture())),
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        metallic_roughness_texture: pbr
+            .metallic_roughness_texture()
+            .and_then(|i| texture(i.texture())),
+        normal_texture: material.normal_texture().and_then(|n| texture(n.texture())),
+        occlusion_texture: material
+            .occlusion_texture()
+            .and_then(|o| texture(o.texture())),
+        emissive: material.emissive_factor(),
+        emissive_texture: material
+            .emissive_texture()
+            .and_then(|i| texture(i.texture())),
+        alpha_mode: match material.alpha_mode() {
+            gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+            gltf::material::AlphaMode::Mask => AlphaMode::Mask,
+            gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+        },
+        alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+        double_sided: material.double_sided(),
+    }
+}
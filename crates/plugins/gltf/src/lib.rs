@@ -0,0 +1,126 @@
+//! This plugin provides importers for glTF 2.0 files.
+//!
+//! `gltf_mesh` importer produces single [`Mesh`] asset
+//! with all mesh primitives of the default scene.
+//! Node transforms are baked into vertices.
+//!
+//! `gltf_material` importer produces [`Material`] asset
+//! from the first material in the file.
+//!
+//! Images referenced by URI are registered as `texture` dependencies.
+//! Images embedded into buffers are not supported and are skipped with a warning.
+//!
+//! [`Mesh`]: arcana::assets::Mesh
+//! [`Material`]: arcana::assets::Material
+
+use std::path::Path;
+
+use arcana::{
+    assets::import::{ensure, AssetDependencies, AssetSources, ImportError, Importer},
+    ident, name, Ident, Name,
+};
+
+arcana::declare_plugin!();
+
+mod convert;
+
+/// Imports meshes from glTF files.
+#[arcana::importer]
+#[derive(Default)]
+pub struct GltfMeshImporter;
+
+impl GltfMeshImporter {
+    pub fn new() -> Self {
+        GltfMeshImporter
+    }
+}
+
+impl Importer for GltfMeshImporter {
+    fn name(&self) -> Name {
+        name!(gltf_mesh)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["gltf", "glb"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gltf", "glb"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(mesh)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        sources: &mut dyn AssetSources,
+        dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let gltf = convert::open(source)?;
+
+        let mut missing_sources = Vec::new();
+        let mut missing_dependencies = Vec::new();
+
+        let buffers = convert::load_buffers(&gltf, sources, &mut missing_sources)?;
+        ensure(missing_sources, Vec::new())?;
+
+        let mesh = convert::mesh(&gltf, &buffers, dependencies, &mut missing_dependencies)?;
+        ensure(Vec::new(), missing_dependencies)?;
+
+        std::fs::write(output, mesh.encode()).map_err(convert::error_to_reason)
+    }
+}
+
+/// Imports materials from glTF files.
+#[arcana::importer]
+#[derive(Default)]
+pub struct GltfMaterialImporter;
+
+impl GltfMaterialImporter {
+    pub fn new() -> Self {
+        GltfMaterialImporter
+    }
+}
+
+impl Importer for GltfMaterialImporter {
+    fn name(&self) -> Name {
+        name!(gltf_material)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["gltf", "glb"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gltf", "glb"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(material)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        _sources: &mut dyn AssetSources,
+        dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let gltf = convert::open(source)?;
+
+        let Some(material) = gltf.materials().next() else {
+            return Err(ImportError::Other {
+                reason: "glTF file has no materials".to_owned(),
+            });
+        };
+
+        let mut missing_dependencies = Vec::new();
+        let material = convert::material(material, dependencies, &mut missing_dependencies);
+        ensure(Vec::new(), missing_dependencies)?;
+
+        std::fs::write(output, material.encode()).map_err(convert::error_to_reason)
+    }
+}
@@ -4,7 +4,8 @@ use arcana::{
     blink_alloc::Blink,
     edict::{entity::EntityId, world::World, NoSuchEntity},
     input::{
-        DeviceId, ElementState, Input, InputFilter, KeyEvent, MouseButton, PhysicalKey, ViewInput,
+        DeviceId, DeviceInput, ElementState, Input, InputFilter, KeyEvent, MouseButton,
+        PhysicalKey, ViewInput,
     },
 };
 use hashbrown::HashMap;
@@ -66,12 +67,56 @@ impl MyInputFilter {
                 }
                 _ => {}
             },
-            _ => {}
+            Input::DeviceInput {
+                device,
+                event: DeviceInput::MouseMotion { delta_x, delta_y },
+            } => {
+                world
+                    .expect_resource_mut::<MouseMotion>()
+                    .accumulate(delta_x, delta_y);
+
+                if let Some(controller) = self.device.get_mut(&device) {
+                    controller.on_mouse_motion(world, delta_x, delta_y);
+                    return true;
+                } else if let Some(controller) = &mut self.global {
+                    controller.on_mouse_motion(world, delta_x, delta_y);
+                    return true;
+                }
+            }
         }
         false
     }
 }
 
+/// Mouse movement accumulated since last time it was taken.
+///
+/// Reported even when cursor is grabbed,
+/// which makes it suitable for FPS-style camera control.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MouseMotion {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl MouseMotion {
+    pub const fn new() -> Self {
+        MouseMotion { x: 0.0, y: 0.0 }
+    }
+
+    fn accumulate(&mut self, x: f64, y: f64) {
+        self.x += x;
+        self.y += y;
+    }
+
+    /// Returns accumulated motion and resets it.
+    pub fn take(&mut self) -> (f64, f64) {
+        let motion = (self.x, self.y);
+        self.x = 0.0;
+        self.y = 0.0;
+        motion
+    }
+}
+
 /// Choses which controller to dispatch events to.
 pub struct InputHandler {
     add_controller: HashMap<ControllerBind, Box<dyn Controller>>,
@@ -122,6 +167,9 @@ pub trait Controller: Send {
     fn on_mouse_move(&mut self, world: &mut World, x: f64, y: f64) {
         let _ = (world, x, y);
     }
+    fn on_mouse_motion(&mut self, world: &mut World, delta_x: f64, delta_y: f64) {
+        let _ = (world, delta_x, delta_y);
+    }
 }
 
 pub trait Translator: Send {
@@ -261,4 +309,5 @@ where
 
 pub fn init_world(world: &mut World) {
     world.insert_resource(InputHandler::new());
+    world.insert_resource(MouseMotion::new());
 }
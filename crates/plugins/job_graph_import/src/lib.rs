@@ -0,0 +1,70 @@
+//! This plugin provides importer for job graph presets.
+//!
+//! Presets are written in JSON when render graph is saved in the editor.
+//! Importer validates them and encodes into the format [`GraphPreset`] asset loads.
+//!
+//! [`GraphPreset`]: arcana::work::GraphPreset
+
+use std::{fmt::Display, path::Path};
+
+use arcana::{
+    assets::import::{AssetDependencies, AssetSources, ImportError, Importer},
+    ident, name,
+    work::GraphPreset,
+    Ident, Name,
+};
+
+arcana::declare_plugin!();
+
+/// Imports JSON job graph presets.
+#[arcana::importer]
+#[derive(Default)]
+pub struct JobGraphImporter;
+
+impl JobGraphImporter {
+    pub fn new() -> Self {
+        JobGraphImporter
+    }
+}
+
+impl Importer for JobGraphImporter {
+    fn name(&self) -> Name {
+        name!(job_graph)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["jobgraph"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["jobgraph"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(job_graph)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        _sources: &mut dyn AssetSources,
+        _dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let text = std::fs::read_to_string(source).map_err(error_to_reason)?;
+        let preset = GraphPreset::from_json(&text).map_err(error_to_reason)?;
+
+        // Warnings are fine, the game would render the graph anyway.
+        if let Some(err) = preset.validate().into_iter().find(|err| err.is_fatal()) {
+            return Err(error_to_reason(err));
+        }
+
+        std::fs::write(output, preset.encode()).map_err(error_to_reason)
+    }
+}
+
+fn error_to_reason<E: Display>(error: E) -> ImportError {
+    ImportError::Other {
+        reason: error.to_string(),
+    }
+}
@@ -0,0 +1,114 @@
+//! Subset of LDtk JSON format used by the importer.
+
+#[derive(serde::Deserialize)]
+pub struct Project {
+    pub defs: Defs,
+    pub levels: Vec<Level>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct Defs {
+    pub tilesets: Vec<TilesetDef>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct TilesetDef {
+    pub uid: i64,
+    pub identifier: String,
+
+    #[serde(rename = "relPath")]
+    pub rel_path: Option<String>,
+
+    #[serde(rename = "tileGridSize")]
+    pub tile_grid_size: u32,
+
+    #[serde(rename = "__cWid")]
+    pub columns: u32,
+
+    #[serde(default)]
+    pub spacing: u32,
+
+    #[serde(default)]
+    pub padding: u32,
+}
+
+#[derive(serde::Deserialize)]
+pub struct Level {
+    pub identifier: String,
+
+    #[serde(rename = "worldX")]
+    pub world_x: i32,
+
+    #[serde(rename = "worldY")]
+    pub world_y: i32,
+
+    #[serde(rename = "pxWid")]
+    pub px_wid: u32,
+
+    #[serde(rename = "pxHei")]
+    pub px_hei: u32,
+
+    /// `None` when level is saved in separate file.
+    #[serde(rename = "layerInstances")]
+    pub layer_instances: Option<Vec<LayerInstance>>,
+
+    #[serde(rename = "externalRelPath", default)]
+    pub external_rel_path: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct LayerInstance {
+    #[serde(rename = "__identifier")]
+    pub identifier: String,
+
+    #[serde(rename = "__tilesetDefUid")]
+    pub tileset_def_uid: Option<i64>,
+
+    #[serde(rename = "__pxTotalOffsetX", default)]
+    pub px_total_offset_x: i32,
+
+    #[serde(rename = "__pxTotalOffsetY", default)]
+    pub px_total_offset_y: i32,
+
+    #[serde(rename = "gridTiles", default)]
+    pub grid_tiles: Vec<TileInstance>,
+
+    #[serde(rename = "autoLayerTiles", default)]
+    pub auto_layer_tiles: Vec<TileInstance>,
+
+    #[serde(rename = "entityInstances", default)]
+    pub entity_instances: Vec<EntityInstance>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct TileInstance {
+    pub px: [i32; 2],
+
+    /// Flip bits. 1 is X flip, 2 is Y flip.
+    pub f: u8,
+
+    /// Tile ID in the tileset.
+    pub t: u32,
+}
+
+#[derive(serde::Deserialize)]
+pub struct EntityInstance {
+    #[serde(rename = "__identifier")]
+    pub identifier: String,
+
+    pub px: [i32; 2],
+    pub width: u32,
+    pub height: u32,
+
+    #[serde(rename = "fieldInstances", default)]
+    pub field_instances: Vec<FieldInstance>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct FieldInstance {
+    #[serde(rename = "__identifier")]
+    pub identifier: String,
+
+    #[serde(rename = "__value")]
+    pub value: serde_json::Value,
+}
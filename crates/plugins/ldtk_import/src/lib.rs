@@ -0,0 +1,174 @@
+//! This plugin provides importer for LDtk projects.
+//!
+//! Project is imported as a single [`TileMap`] asset with all levels.
+//! Tileset images are registered as `texture` dependencies.
+//! Entity layers are converted into spawn points.
+//! Levels saved in separate files are requested as additional sources.
+//!
+//! [`TileMap`]: arcana::assets::TileMap
+
+use std::{fmt::Display, path::Path};
+
+use arcana::{
+    assets::{
+        import::{ensure, AssetDependencies, AssetSources, ImportError, Importer},
+        tile_map::{SpawnPoint, Tile, TileLayer, TileLevel, TileMapData, Tileset},
+    },
+    hashbrown::HashMap,
+    ident, name, Ident, Name,
+};
+
+arcana::declare_plugin!();
+
+mod format;
+
+/// Imports LDtk projects.
+#[arcana::importer]
+#[derive(Default)]
+pub struct LdtkImporter;
+
+impl LdtkImporter {
+    pub fn new() -> Self {
+        LdtkImporter
+    }
+}
+
+impl Importer for LdtkImporter {
+    fn name(&self) -> Name {
+        name!(ldtk)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["ldtk"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(tile_map)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        sources: &mut dyn AssetSources,
+        dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let project = std::fs::read(source).map_err(error_to_reason)?;
+        let project: format::Project = serde_json::from_slice(&project).map_err(error_to_reason)?;
+
+        let mut missing_sources = Vec::new();
+        let mut missing_dependencies = Vec::new();
+
+        let mut tileset_indices = HashMap::new();
+        let mut tilesets = Vec::new();
+
+        for def in &project.defs.tilesets {
+            let texture = def.rel_path.as_deref().and_then(|path| {
+                dependencies.get_or_append(path, ident!(texture), &mut missing_dependencies)
+            });
+
+            tileset_indices.insert(def.uid, tilesets.len());
+            tilesets.push(Tileset {
+                name: def.identifier.clone(),
+                texture,
+                tile_size: def.tile_grid_size,
+                columns: def.columns,
+                spacing: def.spacing,
+                padding: def.padding,
+            });
+        }
+
+        let mut levels = Vec::new();
+
+        for level in project.levels {
+            let level = match level.external_rel_path.clone() {
+                Some(path) if level.layer_instances.is_none() => {
+                    let Some(path) = sources.get_or_append(&path, &mut missing_sources) else {
+                        continue;
+                    };
+
+                    let data = std::fs::read(path).map_err(error_to_reason)?;
+                    serde_json::from_slice(&data).map_err(error_to_reason)?
+                }
+                _ => level,
+            };
+
+            levels.push(convert_level(level, &tileset_indices));
+        }
+
+        ensure(missing_sources, missing_dependencies)?;
+
+        let map = TileMapData { tilesets, levels };
+        std::fs::write(output, map.encode()).map_err(error_to_reason)
+    }
+}
+
+fn convert_level(level: format::Level, tileset_indices: &HashMap<i64, usize>) -> TileLevel {
+    let mut layers = Vec::new();
+    let mut spawns = Vec::new();
+
+    // LDtk lists layers from top to bottom.
+    for layer in level.layer_instances.unwrap_or_default().into_iter().rev() {
+        let offset_x = layer.px_total_offset_x;
+        let offset_y = layer.px_total_offset_y;
+
+        for entity in layer.entity_instances {
+            spawns.push(SpawnPoint {
+                kind: entity.identifier,
+                layer: layer.identifier.clone(),
+                x: entity.px[0] + offset_x,
+                y: entity.px[1] + offset_y,
+                width: entity.width,
+                height: entity.height,
+                fields: entity
+                    .field_instances
+                    .into_iter()
+                    .map(|field| (field.identifier, field.value.to_string()))
+                    .collect(),
+            });
+        }
+
+        let tiles = layer
+            .grid_tiles
+            .iter()
+            .chain(&layer.auto_layer_tiles)
+            .map(|tile| Tile {
+                x: tile.px[0] + offset_x,
+                y: tile.px[1] + offset_y,
+                tile: tile.t,
+                flip_x: tile.f & 1 != 0,
+                flip_y: tile.f & 2 != 0,
+            })
+            .collect::<Vec<_>>();
+
+        if !tiles.is_empty() {
+            layers.push(TileLayer {
+                name: layer.identifier,
+                tileset: layer
+                    .tileset_def_uid
+                    .and_then(|uid| tileset_indices.get(&uid).copied()),
+                tiles,
+            });
+        }
+    }
+
+    TileLevel {
+        name: level.identifier,
+        x: level.world_x,
+        y: level.world_y,
+        width: level.px_wid,
+        height: level.px_hei,
+        layers,
+        spawns,
+    }
+}
+
+fn error_to_reason<E: Display>(error: E) -> ImportError {
+    ImportError::Other {
+        reason: error.to_string(),
+    }
+}
@@ -0,0 +1,375 @@
+use std::mem::size_of;
+
+use arcana::{
+    edict::world::World,
+    hashbrown::HashMap,
+    mev::{self, Arguments, DeviceRepr},
+    model::{ColorModel, ColorValue, Model, Value},
+    render::current_camera,
+    work::{Exec, Image2D, Job, JobDesc, JobIdx, Planner},
+};
+use camera::Camera2;
+use scene::dim2::Global;
+
+use crate::{
+    light::{ConeLight, PointLight},
+    occluder::{NoShadow, Occluder},
+};
+
+const WORKGROUP_SIZE: u32 = 8;
+
+const OCCLUDER_CIRCLE: u32 = 0;
+const OCCLUDER_RECT: u32 = 1;
+
+#[derive(mev::Arguments)]
+struct LightArguments {
+    #[mev(storage, shader(compute))]
+    lights: mev::Buffer,
+    #[mev(storage, shader(compute))]
+    occluders: mev::Buffer,
+    #[mev(storage, shader(compute))]
+    target: mev::Image,
+}
+
+#[derive(mev::DeviceRepr)]
+struct LightConstants {
+    camera: mev::mat3,
+    ambient: mev::vec4,
+    width: u32,
+    height: u32,
+    light_count: u32,
+    occluder_count: u32,
+}
+
+#[derive(mev::DeviceRepr)]
+struct LightDevice {
+    pos: mev::vec2,
+    dir: mev::vec2,
+    color: mev::vec4,
+    radius: f32,
+    cos_outer: f32,
+    cos_inner: f32,
+}
+
+#[derive(mev::DeviceRepr)]
+struct OccluderDevice {
+    inv_tr: mev::mat3,
+    params: mev::vec2,
+    kind: u32,
+}
+
+/// Lights and occluders prepared for single job node.
+#[derive(Default)]
+struct Node {
+    /// Maps view to world.
+    camera: Option<na::Matrix3<f32>>,
+    lights: Vec<<LightDevice as DeviceRepr>::Repr>,
+    occluders: Vec<<OccluderDevice as DeviceRepr>::Repr>,
+    lights_buffer: Option<mev::Buffer>,
+    occluders_buffer: Option<mev::Buffer>,
+}
+
+impl Node {
+    fn push_occluder(&mut self, tr: na::Matrix3<f32>, kind: u32, params: na::Vector2<f32>) {
+        // Degenerate transforms make zero-sized shapes that cast no shadows.
+        let Some(inv_tr) = tr.try_inverse() else {
+            return;
+        };
+
+        self.occluders.push(
+            OccluderDevice {
+                inv_tr: inv_tr.as_ref().into(),
+                params: mev::vec2(params.x, params.y),
+                kind,
+            }
+            .as_repr(),
+        );
+    }
+}
+
+/// Lights the image drawn by previous jobs.
+#[arcana::job]
+pub struct ApplyLights {
+    pipeline: Option<mev::ComputePipeline>,
+    nodes: HashMap<JobIdx, Node>,
+}
+
+impl ApplyLights {
+    pub fn desc() -> JobDesc {
+        arcana::job_desc! [
+            ambient: in Model::Color(ColorModel::Srgb),
+            main: mut Image2D,
+        ]
+    }
+
+    pub fn new() -> Self {
+        ApplyLights {
+            pipeline: None,
+            nodes: HashMap::new(),
+        }
+    }
+}
+
+impl Job for ApplyLights {
+    fn plan(&mut self, mut planner: Planner<'_>, world: &mut World) {
+        let Some(target) = planner.update::<Image2D>().copied() else {
+            return;
+        };
+
+        let node = self.nodes.entry(planner.idx()).or_default();
+        node.camera = None;
+        node.lights.clear();
+        node.occluders.clear();
+
+        let camera = match current_camera(world) {
+            Some(entity) => world
+                .try_view_one::<(&Global, &Camera2)>(entity)
+                .ok()
+                .and_then(|camera| camera.get().map(|(g, c)| (*g, *c))),
+            None => world
+                .view::<(&Global, &Camera2)>()
+                .iter()
+                .next()
+                .map(|(g, c)| (*g, *c)),
+        };
+        let Some((global, camera)) = camera else {
+            return;
+        };
+
+        // Shader maps pixels to world, so camera transform is used as is.
+        node.camera = Some(
+            camera
+                .view_to_world(&global.iso, target.extent.width(), target.extent.height())
+                .to_homogeneous(),
+        );
+
+        let points = world.view::<(&Global, &PointLight)>();
+        for (global, light) in points.iter() {
+            let pos = global.iso.translation.vector;
+            let color = light.color * light.intensity;
+
+            node.lights.push(
+                LightDevice {
+                    pos: mev::vec2(pos.x, pos.y),
+                    dir: mev::vec2(1.0, 0.0),
+                    color: mev::vec4(color.red, color.green, color.blue, 1.0),
+                    radius: light.radius,
+                    // Cone test always passes.
+                    cos_outer: -2.0,
+                    cos_inner: -1.0,
+                }
+                .as_repr(),
+            );
+        }
+
+        let cones = world.view::<(&Global, &ConeLight)>();
+        for (global, light) in cones.iter() {
+            let pos = global.iso.translation.vector;
+            let dir = global.iso.rotation * na::Vector2::x();
+            let color = light.color * light.intensity;
+
+            let half = light.angle.clamp(0.0, std::f32::consts::TAU) / 2.0;
+            let cos_outer = half.cos();
+            let cos_inner = (half * (1.0 - light.softness.clamp(0.0, 1.0)))
+                .cos()
+                .max(cos_outer + f32::EPSILON);
+
+            node.lights.push(
+                LightDevice {
+                    pos: mev::vec2(pos.x, pos.y),
+                    dir: mev::vec2(dir.x, dir.y),
+                    color: mev::vec4(color.red, color.green, color.blue, 1.0),
+                    radius: light.radius,
+                    cos_outer,
+                    cos_inner,
+                }
+                .as_repr(),
+            );
+        }
+
+        let occluders = world.view::<(&Global, &Occluder)>().without::<NoShadow>();
+        for (global, occluder) in occluders.iter() {
+            let tr = global.iso.to_homogeneous();
+            match *occluder {
+                Occluder::Circle { radius } => {
+                    node.push_occluder(tr, OCCLUDER_CIRCLE, na::Vector2::new(radius, 0.0))
+                }
+                Occluder::Rect { half } => node.push_occluder(tr, OCCLUDER_RECT, half),
+            }
+        }
+
+        let shapes = world
+            .view::<(&Global, &sdf::Shape)>()
+            .without::<Occluder>()
+            .without::<NoShadow>();
+        for (global, shape) in shapes.iter() {
+            let tr = global.iso.to_homogeneous() * shape.transform.matrix();
+            match shape.kind {
+                sdf::ShapeKind::Circle { radius } => {
+                    node.push_occluder(tr, OCCLUDER_CIRCLE, na::Vector2::new(radius, 0.0))
+                }
+                sdf::ShapeKind::Rect { width, height } => node.push_occluder(
+                    tr,
+                    OCCLUDER_RECT,
+                    na::Vector2::new(width / 2.0, height / 2.0),
+                ),
+            }
+        }
+
+        #[cfg(feature = "physics")]
+        {
+            use physics::dim2::Collider;
+
+            let colliders = world
+                .view::<(&Global, &Collider)>()
+                .without::<Occluder>()
+                .without::<sdf::Shape>()
+                .without::<NoShadow>();
+
+            for (global, collider) in colliders.iter() {
+                let local = collider.local_position();
+                let local = na::Isometry2::new(
+                    na::Vector2::new(local.translation.x, local.translation.y),
+                    local.rotation.angle(),
+                );
+                let tr = (global.iso * local).to_homogeneous();
+
+                // Only balls and cuboids cast shadows.
+                let shape = collider.shape();
+                if let Some(ball) = shape.as_ball() {
+                    node.push_occluder(tr, OCCLUDER_CIRCLE, na::Vector2::new(ball.radius, 0.0));
+                } else if let Some(cuboid) = shape.as_cuboid() {
+                    let half = na::Vector2::new(cuboid.half_extents.x, cuboid.half_extents.y);
+                    node.push_occluder(tr, OCCLUDER_RECT, half);
+                }
+            }
+        }
+    }
+
+    fn exec(&mut self, runner: Exec<'_>, _world: &mut World) {
+        let Some(target) = runner.update::<Image2D>() else {
+            return;
+        };
+
+        let Some(node) = self.nodes.get_mut(&runner.idx()) else {
+            return;
+        };
+
+        let Some(camera) = node.camera else {
+            return;
+        };
+
+        let ambient = match runner.param("ambient") {
+            Value::Color(ColorValue::Srgb(c)) => [c.red, c.green, c.blue],
+            _ => [0.1, 0.1, 0.1],
+        };
+
+        let pipeline = self.pipeline.get_or_insert_with(|| {
+            let library = runner
+                .device()
+                .new_shader_library(mev::LibraryDesc {
+                    name: "light2d",
+                    input: mev::include_library!("shaders/light.wgsl" as mev::ShaderLanguage::Wgsl),
+                })
+                .unwrap();
+
+            runner
+                .device()
+                .new_compute_pipeline(mev::ComputePipelineDesc {
+                    name: "light2d",
+                    shader: mev::Shader {
+                        library,
+                        entry: "main".into(),
+                    },
+                    work_group_size: [WORKGROUP_SIZE, WORKGROUP_SIZE, 1],
+                    arguments: &[LightArguments::LAYOUT],
+                    constants: LightConstants::SIZE,
+                })
+                .unwrap()
+        });
+
+        let lights = upload_buffer(
+            runner.device(),
+            &mut node.lights_buffer,
+            "lights",
+            size_of::<<LightDevice as DeviceRepr>::Repr>() * node.lights.len(),
+        );
+        let occluders = upload_buffer(
+            runner.device(),
+            &mut node.occluders_buffer,
+            "occluders",
+            size_of::<<OccluderDevice as DeviceRepr>::Repr>() * node.occluders.len(),
+        );
+
+        let encoder = runner.new_encoder();
+
+        encoder.barrier(mev::PipelineStages::all(), mev::PipelineStages::TRANSFER);
+        {
+            let mut copy = encoder.copy();
+            if !node.lights.is_empty() {
+                copy.write_buffer_slice(lights.slice(..), &node.lights);
+            }
+            if !node.occluders.is_empty() {
+                copy.write_buffer_slice(occluders.slice(..), &node.occluders);
+            }
+        }
+        encoder.barrier(mev::PipelineStages::TRANSFER, mev::PipelineStages::all());
+
+        let dims = target.extent().expect_2d();
+
+        let mut compute = encoder.compute();
+        compute.with_pipeline(pipeline);
+        compute.with_arguments(
+            0,
+            &LightArguments {
+                lights,
+                occluders,
+                target: target.0.clone(),
+            },
+        );
+        compute.with_constants(&LightConstants {
+            camera: camera.as_ref().into(),
+            ambient: mev::vec4(ambient[0], ambient[1], ambient[2], 1.0),
+            width: dims.width(),
+            height: dims.height(),
+            light_count: node.lights.len() as u32,
+            occluder_count: node.occluders.len() as u32,
+        });
+        compute.dispatch(mev::Extent3::new(
+            dims.width().div_ceil(WORKGROUP_SIZE),
+            dims.height().div_ceil(WORKGROUP_SIZE),
+            1,
+        ));
+        drop(compute);
+
+        encoder.barrier(mev::PipelineStages::all(), mev::PipelineStages::all());
+    }
+}
+
+/// Returns buffer that fits `size` bytes, growing it if necessary.
+///
+/// Buffer is never empty, as it is bound even if there is nothing to upload.
+fn upload_buffer(
+    device: &mev::Device,
+    slot: &mut Option<mev::Buffer>,
+    name: &str,
+    size: usize,
+) -> mev::Buffer {
+    let size = size.max(256);
+
+    match slot {
+        Some(buffer) if buffer.size() >= size => buffer.clone(),
+        slot => slot
+            .insert(
+                device
+                    .new_buffer(mev::BufferDesc {
+                        size: size.next_power_of_two(),
+                        name,
+                        usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
+                        memory: mev::Memory::Shared,
+                    })
+                    .unwrap(),
+            )
+            .clone(),
+    }
+}
@@ -0,0 +1,25 @@
+//! This plugin adds dynamic 2D lighting.
+//!
+//! Lights are `PointLight` and `ConeLight` components positioned by `Global` transform.
+//! Shadows are cast by occluders which are either explicit `Occluder` components
+//! or derived from SDF shapes and, with `physics` feature, from colliders.
+//! Entities marked with `NoShadow` never cast shadows.
+//!
+//! `ApplyLights` job is placed after the jobs that draw the scene.
+//! It multiplies image color by the ambient light plus the contribution
+//! of every light, tracing soft shadows through the occluders' distance field.
+
+arcana::declare_plugin!([scene ..., camera ..., sdf ...]);
+
+mod job;
+mod light;
+mod occluder;
+
+pub use self::{
+    job::ApplyLights,
+    light::{ConeLight, PointLight},
+    occluder::{NoShadow, Occluder},
+};
+
+arcana::reflect_component!(PointLight, Default);
+arcana::reflect_component!(ConeLight, Default);
@@ -0,0 +1,69 @@
+use arcana::edict::{self, Component};
+
+/// Light that shines equally in all directions.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct PointLight {
+    pub color: palette::Srgb,
+
+    /// Multiplier of the light color.
+    pub intensity: f32,
+
+    /// Distance at which light fades out completely.
+    pub radius: f32,
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        PointLight {
+            color: palette::Srgb::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+            radius: 5.0,
+        }
+    }
+}
+
+arcana::reflect_struct!(PointLight {
+    color,
+    intensity,
+    radius,
+});
+
+/// Light that shines within a cone.
+///
+/// Cone is directed along the X axis of the entity's `Global` transform.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct ConeLight {
+    pub color: palette::Srgb,
+
+    /// Multiplier of the light color.
+    pub intensity: f32,
+
+    /// Distance at which light fades out completely.
+    pub radius: f32,
+
+    /// Full angle of the cone in radians.
+    pub angle: f32,
+
+    /// Fraction of the cone angle over which light fades at the edges.
+    pub softness: f32,
+}
+
+impl Default for ConeLight {
+    fn default() -> Self {
+        ConeLight {
+            color: palette::Srgb::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+            radius: 10.0,
+            angle: std::f32::consts::FRAC_PI_3,
+            softness: 0.2,
+        }
+    }
+}
+
+arcana::reflect_struct!(ConeLight {
+    color,
+    intensity,
+    radius,
+    angle,
+    softness,
+});
@@ -0,0 +1,16 @@
+use arcana::edict::{self, Component};
+
+/// Shape that casts shadows.
+///
+/// Centered at entity's `Global` position.
+/// Entities with `sdf::Shape` cast shadows without this component,
+/// it overrides the shape when both are present.
+#[derive(Clone, Copy, Debug, Component)]
+pub enum Occluder {
+    Circle { radius: f32 },
+    Rect { half: na::Vector2<f32> },
+}
+
+/// Marks entity that never casts shadows.
+#[derive(Clone, Copy, Debug, Default, Component)]
+pub struct NoShadow;
@@ -0,0 +1,125 @@
+struct Constants {
+    camera: mat3x3f,
+    ambient: vec4f,
+    width: u32,
+    height: u32,
+    light_count: u32,
+    occluder_count: u32,
+}
+
+var<push_constant> pc: Constants;
+
+struct Light {
+    pos: vec2f,
+    dir: vec2f,
+    color: vec4f,
+    radius: f32,
+    cos_outer: f32,
+    cos_inner: f32,
+}
+
+struct Occluder {
+    inv_tr: mat3x3f,
+    params: vec2f,
+    kind: u32,
+}
+
+@group(0) @binding(0) var<storage> lights: array<Light>;
+@group(0) @binding(1) var<storage> occluders: array<Occluder>;
+@group(0) @binding(2) var target: texture_storage_2d<rgba8unorm, read_write>;
+
+const MAX_STEPS: u32 = 48u;
+const SHADOW_SOFTNESS: f32 = 16f;
+
+fn occluder_sdf(o: Occluder, p: vec2f) -> f32 {
+    let q = (o.inv_tr * vec3f(p, 1f)).xy;
+    switch o.kind {
+        case 0u: {
+            return length(q) - o.params.x;
+        }
+        default: {
+            let d = abs(q) - o.params;
+            return length(max(d, vec2f(0f))) + min(max(d.x, d.y), 0f);
+        }
+    }
+}
+
+fn scene_sdf(p: vec2f) -> f32 {
+    var d = 3.4e38f;
+    for (var i = 0u; i < pc.occluder_count; i++) {
+        d = min(d, occluder_sdf(occluders[i], p));
+    }
+    return d;
+}
+
+// Soft shadow by sphere tracing from the point towards the light.
+fn shadow(p: vec2f, light: vec2f) -> f32 {
+    let to_light = light - p;
+    let distance = length(to_light);
+    let dir = to_light / distance;
+
+    var lit = 1f;
+    var t = 0.01f;
+    for (var i = 0u; i < MAX_STEPS; i++) {
+        if t >= distance {
+            break;
+        }
+
+        let h = scene_sdf(p + dir * t);
+        if h < 0.001f {
+            return 0f;
+        }
+
+        lit = min(lit, SHADOW_SOFTNESS * h / t);
+        t += max(h, 0.01f);
+    }
+
+    return clamp(lit, 0f, 1f);
+}
+
+@compute @workgroup_size(8, 8)
+fn main(@builtin(global_invocation_id) id: vec3u) {
+    if id.x >= pc.width || id.y >= pc.height {
+        return;
+    }
+
+    // Image rows go down while world Y goes up.
+    let uv = (vec2f(id.xy) + 0.5f) / vec2f(f32(pc.width), f32(pc.height));
+    let ndc = vec2f(uv.x * 2f - 1f, 1f - uv.y * 2f);
+    let p = (pc.camera * vec3f(ndc, 1f)).xy;
+
+    // Occluders are lit, but do not shadow themselves.
+    let inside = scene_sdf(p) <= 0f;
+
+    var light = pc.ambient.rgb;
+    for (var i = 0u; i < pc.light_count; i++) {
+        let l = lights[i];
+
+        let to_point = p - l.pos;
+        let distance = length(to_point);
+        if distance >= l.radius {
+            continue;
+        }
+
+        let falloff = 1f - distance / l.radius;
+        var intensity = falloff * falloff;
+
+        if distance > 0f {
+            let cos_angle = dot(to_point / distance, l.dir);
+            intensity *= smoothstep(l.cos_outer, l.cos_inner, cos_angle);
+        }
+
+        if intensity <= 0f {
+            continue;
+        }
+
+        if !inside && pc.occluder_count > 0u {
+            intensity *= shadow(p, l.pos);
+        }
+
+        light += l.color.rgb * intensity;
+    }
+
+    let color = textureLoad(target, id.xy);
+    textureStore(target, id.xy, vec4f(color.rgb * light, color.a));
+}
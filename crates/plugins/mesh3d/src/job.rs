@@ -0,0 +1,642 @@
+use std::{mem::size_of, task::Poll};
+
+use arcana::{
+    assets::{
+        material::{AlphaMode, Material},
+        AssetId, Assets,
+    },
+    color::Color,
+    edict::world::World,
+    hashbrown::HashMap,
+    mev::{self, Arguments, DeviceRepr},
+    model::{ColorModel, ColorValue, Model, Value},
+    render::{current_camera, ArgumentCache},
+    stats::{count_culled, count_draws},
+    texture::{cached_sampler, Texture},
+    work::{Exec, Image2D, Job, JobDesc, JobIdx, Planner},
+};
+use camera::{Camera3, RenderLayers};
+use scene::dim3::Global;
+
+use crate::{
+    light::{DirectionalLight, PointLight},
+    renderer::MeshRenderer,
+};
+
+const DEPTH_FORMAT: mev::PixelFormat = mev::PixelFormat::D32Float;
+
+const FLAG_NORMAL_MAP: u32 = 1;
+
+#[derive(mev::Arguments)]
+struct MeshArguments {
+    #[mev(storage, vertex)]
+    frame: mev::Buffer,
+    #[mev(storage, fragment)]
+    view: mev::Buffer,
+    #[mev(storage, fragment)]
+    lights: mev::Buffer,
+    #[mev(fragment)]
+    sampler: mev::Sampler,
+    #[mev(fragment)]
+    base_color: mev::Image,
+    #[mev(fragment)]
+    metallic_roughness: mev::Image,
+    #[mev(fragment)]
+    normal: mev::Image,
+}
+
+#[derive(mev::DeviceRepr)]
+struct FrameDevice {
+    view_proj: mev::mat4,
+    camera: mev::vec4,
+    ambient: mev::vec4,
+}
+
+#[derive(mev::DeviceRepr)]
+struct LightDevice {
+    position: mev::vec4,
+    color: mev::vec4,
+}
+
+#[derive(mev::DeviceRepr)]
+struct MeshConstants {
+    model: mev::mat4,
+    base_color: mev::vec4,
+    emissive: mev::vec4,
+    metallic: f32,
+    roughness: f32,
+    light_count: u32,
+    flags: u32,
+}
+
+/// Primitive prepared for drawing.
+struct Draw {
+    vertices: mev::Buffer,
+    indices: mev::Buffer,
+    count: u32,
+    constants: MeshConstants,
+
+    /// Textures that are not set or not loaded yet are replaced with defaults.
+    base_color: Option<mev::Image>,
+    metallic_roughness: Option<mev::Image>,
+    normal: Option<mev::Image>,
+
+    double_sided: bool,
+    blend: bool,
+
+    /// Distance from camera, used to sort blended primitives.
+    distance: f32,
+}
+
+/// Per job node state.
+#[derive(Default)]
+struct Node {
+    frame: Option<FrameDevice>,
+    lights: Vec<<LightDevice as DeviceRepr>::Repr>,
+    draws: Vec<Draw>,
+    frame_buffer: Option<mev::Buffer>,
+    lights_buffer: Option<mev::Buffer>,
+    depth: Option<mev::Image>,
+}
+
+/// 1x1 textures used in place of missing ones.
+struct Defaults {
+    white: mev::Image,
+    normal: mev::Image,
+}
+
+#[arcana::job]
+pub struct DrawMeshes {
+    format: Option<mev::PixelFormat>,
+
+    /// Pipelines by `(double_sided, blend)`.
+    pipelines: HashMap<(bool, bool), mev::RenderPipeline>,
+    library: Option<mev::Library>,
+    defaults: Option<Defaults>,
+    nodes: HashMap<JobIdx, Node>,
+
+    /// Skips binding pipeline and material textures again
+    /// for consecutive draws that share them.
+    arguments: ArgumentCache<(bool, bool), [mev::Image; 3]>,
+}
+
+impl DrawMeshes {
+    pub fn desc() -> JobDesc {
+        arcana::job_desc! [
+            background: in Model::Color(ColorModel::Srgba),
+            ambient: in Model::Color(ColorModel::Srgb),
+            main: +Image2D,
+        ]
+    }
+
+    pub fn new() -> Self {
+        DrawMeshes {
+            format: None,
+            pipelines: HashMap::new(),
+            library: None,
+            defaults: None,
+            nodes: HashMap::new(),
+            arguments: ArgumentCache::new(),
+        }
+    }
+}
+
+impl Job for DrawMeshes {
+    fn plan(&mut self, mut planner: Planner<'_>, world: &mut World) {
+        let Some(target) = planner.create::<Image2D>().copied() else {
+            return;
+        };
+
+        let node = self.nodes.entry(planner.idx()).or_default();
+        node.frame = None;
+        node.lights.clear();
+        node.draws.clear();
+
+        let ratio = target.extent.width() as f32 / target.extent.height() as f32;
+
+        let camera = match current_camera(world) {
+            Some(entity) => world
+                .try_view_one::<(&Global, &Camera3)>(entity)
+                .ok()
+                .and_then(|camera| camera.get().map(|(g, c)| (*g, *c))),
+            None => world
+                .view::<(&Global, &Camera3)>()
+                .iter()
+                .next()
+                .map(|(g, c)| (*g, *c)),
+        };
+        let Some((camera_global, camera)) = camera else {
+            return;
+        };
+
+        let Some(view) = camera_global.iso.to_homogeneous().try_inverse() else {
+            return;
+        };
+
+        // Maps depth from [-1, 1] to [0, 1] range.
+        #[rustfmt::skip]
+        let depth_correction = na::Matrix4::new(
+            1.0, 0.0, 0.0, 0.0,
+            0.0, 1.0, 0.0, 0.0,
+            0.0, 0.0, 0.5, 0.5,
+            0.0, 0.0, 0.0, 1.0,
+        );
+
+        let view_proj = depth_correction * camera.projection(ratio).to_homogeneous() * view;
+        let camera_pos = camera_global.iso.translation.vector;
+
+        node.frame = Some(FrameDevice {
+            view_proj: view_proj.as_ref().into(),
+            camera: mev::vec4(camera_pos.x, camera_pos.y, camera_pos.z, 1.0),
+            ambient: mev::vec4(0.0, 0.0, 0.0, 1.0),
+        });
+
+        let directional = world.view::<(&Global, &DirectionalLight)>();
+        for (global, light) in directional.iter() {
+            let dir = global.iso.rotation * -na::Vector3::z();
+            let color = light.color.into_linear() * light.intensity;
+
+            node.lights.push(
+                LightDevice {
+                    position: mev::vec4(dir.x, dir.y, dir.z, 0.0),
+                    color: mev::vec4(color.red, color.green, color.blue, 0.0),
+                }
+                .as_repr(),
+            );
+        }
+
+        let points = world.view::<(&Global, &PointLight)>();
+        for (global, light) in points.iter() {
+            let pos = global.iso.translation.vector;
+            let color = light.color.into_linear() * light.intensity;
+
+            node.lights.push(
+                LightDevice {
+                    position: mev::vec4(pos.x, pos.y, pos.z, 1.0),
+                    color: mev::vec4(color.red, color.green, color.blue, light.range),
+                }
+                .as_repr(),
+            );
+        }
+
+        let assets = world.get_resource::<Assets>().map(|a| a.clone());
+        let texture = |id: Option<AssetId>| -> Option<mev::Image> {
+            match assets.as_ref()?.get::<Texture>(id?) {
+                Poll::Ready(Ok(texture)) => Some(texture.image),
+                _ => None,
+            }
+        };
+
+        let light_count = node.lights.len() as u32;
+        let frustum = camera.frustum(&camera_global.iso, ratio);
+        let mut culled = 0;
+
+        let renderers = world.view::<(&Global, &MeshRenderer, Option<&RenderLayers>)>();
+        for (global, renderer, layers) in renderers.iter() {
+            if !camera.layers.renders(layers) {
+                continue;
+            }
+
+            let Some(mesh) = renderer.mesh.get() else {
+                continue;
+            };
+
+            let center = global.iso * mesh.bounds.center;
+            if !frustum.intersects_sphere(&center, mesh.bounds.radius) {
+                culled += 1;
+                continue;
+            }
+
+            let override_material = renderer.material.as_ref().and_then(|m| m.get());
+
+            let model = global.iso.to_homogeneous();
+            let distance = (global.iso.translation.vector - camera_pos).norm();
+
+            for primitive in mesh.primitives.iter() {
+                let material = override_material
+                    .as_ref()
+                    .or(primitive.material.as_ref())
+                    .cloned()
+                    .unwrap_or_default();
+
+                let normal = texture(material.normal_texture);
+
+                let mut flags = 0;
+                if normal.is_some() {
+                    flags |= FLAG_NORMAL_MAP;
+                }
+
+                node.draws.push(Draw {
+                    vertices: primitive.vertices.clone(),
+                    indices: primitive.indices.clone(),
+                    count: primitive.count,
+                    constants: MeshConstants {
+                        model: model.as_ref().into(),
+                        base_color: mev::vec(material.base_color),
+                        emissive: emissive(&material),
+                        metallic: material.metallic,
+                        roughness: material.roughness,
+                        light_count,
+                        flags,
+                    },
+                    base_color: texture(material.base_color_texture),
+                    metallic_roughness: texture(material.metallic_roughness_texture),
+                    normal,
+                    double_sided: material.double_sided,
+                    blend: material.alpha_mode == AlphaMode::Blend,
+                    distance,
+                });
+            }
+        }
+
+        count_culled(world, culled);
+
+        // Opaque primitives go first, blended ones are drawn back to front.
+        node.draws.sort_by(|a, b| match (a.blend, b.blend) {
+            (true, true) => b.distance.total_cmp(&a.distance),
+            _ => a.blend.cmp(&b.blend),
+        });
+    }
+
+    fn exec(&mut self, runner: Exec<'_>, world: &mut World) {
+        let Some(target) = runner.create::<Image2D>() else {
+            return;
+        };
+
+        let Some(node) = self.nodes.get_mut(&runner.idx()) else {
+            return;
+        };
+
+        let background = match *runner.param("background") {
+            Value::Color(c) => Color::from(c),
+            _ => Color::BLACK,
+        };
+
+        let ambient = match runner.param("ambient") {
+            Value::Color(ColorValue::Srgb(c)) => {
+                let c = c.into_linear();
+                [c.red, c.green, c.blue]
+            }
+            _ => [0.0, 0.0, 0.0],
+        };
+
+        let device = runner.device();
+        let dims = target.extent().expect_2d();
+
+        if self.format != Some(target.format()) {
+            self.format = Some(target.format());
+            self.pipelines.clear();
+        }
+
+        let depth = match &mut node.depth {
+            Some(depth) if depth.extent().expect_2d() == dims => depth.clone(),
+            slot => slot
+                .insert(
+                    device
+                        .new_image(mev::ImageDesc {
+                            extent: dims.into(),
+                            format: DEPTH_FORMAT,
+                            usage: mev::ImageUsage::TARGET,
+                            layers: 1,
+                            levels: 1,
+                            name: "mesh-depth",
+                        })
+                        .unwrap(),
+                )
+                .clone(),
+        };
+
+        let sampler = cached_sampler(
+            world,
+            device,
+            mev::SamplerDesc {
+                min_filter: mev::Filter::Linear,
+                mag_filter: mev::Filter::Linear,
+                address_mode: [mev::AddressMode::Repeat; 3],
+                ..mev::SamplerDesc::new()
+            },
+        );
+        let sampler = match sampler {
+            Ok(sampler) => sampler,
+            Err(err) => {
+                arcana::tracing::error!("Failed to create mesh sampler: {err:?}");
+                return;
+            }
+        };
+
+        let encoder = runner.new_encoder();
+
+        let defaults = self
+            .defaults
+            .get_or_insert_with(|| create_defaults(device, encoder));
+
+        if let Some(frame) = &mut node.frame {
+            frame.ambient = mev::vec4(ambient[0], ambient[1], ambient[2], 1.0);
+        }
+
+        let frame_buffer = upload_buffer(
+            device,
+            &mut node.frame_buffer,
+            "mesh-frame",
+            size_of::<<FrameDevice as DeviceRepr>::Repr>(),
+        );
+        let lights_buffer = upload_buffer(
+            device,
+            &mut node.lights_buffer,
+            "mesh-lights",
+            size_of::<<LightDevice as DeviceRepr>::Repr>() * node.lights.len(),
+        );
+
+        encoder.barrier(mev::PipelineStages::all(), mev::PipelineStages::TRANSFER);
+        {
+            let mut copy = encoder.copy();
+            if let Some(frame) = &node.frame {
+                copy.write_buffer_slice(frame_buffer.slice(..), &[frame.as_repr()]);
+            }
+            if !node.lights.is_empty() {
+                copy.write_buffer_slice(lights_buffer.slice(..), &node.lights);
+            }
+        }
+        encoder.barrier(mev::PipelineStages::TRANSFER, mev::PipelineStages::all());
+
+        encoder.init_image(
+            mev::PipelineStages::all(),
+            mev::PipelineStages::FRAGMENT_SHADER,
+            &target,
+        );
+        encoder.init_image(
+            mev::PipelineStages::all(),
+            mev::PipelineStages::FRAGMENT_SHADER,
+            &depth,
+        );
+
+        let mut render = encoder.render(mev::RenderPassDesc {
+            color_attachments: &[mev::AttachmentDesc::new(&target).clear(background.into())],
+            depth_stencil_attachment: Some(mev::AttachmentDesc::new(&depth).clear(
+                mev::ClearDepthStencil {
+                    depth: 1.0,
+                    stencil: 0,
+                },
+            )),
+            ..Default::default()
+        });
+
+        // No camera, only clear the target.
+        if node.frame.is_none() {
+            return;
+        }
+
+        render.with_viewport(
+            mev::Offset3::ZERO,
+            mev::Extent3::new(dims.width() as f32, dims.height() as f32, 1.0),
+        );
+        render.with_scissor(mev::Offset2::ZERO, dims);
+
+        self.arguments.reset();
+        for draw in &node.draws {
+            let key = (draw.double_sided, draw.blend);
+            if !self.pipelines.contains_key(&key) {
+                let library = self.library.get_or_insert_with(|| {
+                    device
+                        .new_shader_library(mev::LibraryDesc {
+                            name: "mesh",
+                            input: mev::include_library!(
+                                "shaders/mesh.wgsl" as mev::ShaderLanguage::Wgsl
+                            ),
+                        })
+                        .unwrap()
+                });
+
+                let pipeline = create_pipeline(device, library, target.format(), key);
+                self.pipelines.insert(key, pipeline);
+            }
+            if self.arguments.bind_pipeline(key) {
+                render.with_pipeline(&self.pipelines[&key]);
+            }
+            render.with_constants(&draw.constants);
+
+            // Frame, lights and sampler are the same for all draws of the pass.
+            let textures = [
+                draw.base_color.as_ref().unwrap_or(&defaults.white).clone(),
+                draw.metallic_roughness
+                    .as_ref()
+                    .unwrap_or(&defaults.white)
+                    .clone(),
+                draw.normal.as_ref().unwrap_or(&defaults.normal).clone(),
+            ];
+
+            if self.arguments.bind_arguments(0, textures.clone()) {
+                let [base_color, metallic_roughness, normal] = textures;
+                render.with_arguments(
+                    0,
+                    &MeshArguments {
+                        frame: frame_buffer.clone(),
+                        view: frame_buffer.clone(),
+                        lights: lights_buffer.clone(),
+                        sampler: sampler.clone(),
+                        base_color,
+                        metallic_roughness,
+                        normal,
+                    },
+                );
+            }
+            render.bind_vertex_buffers(0, &[draw.vertices.slice(..)]);
+            render.bind_index_buffer(draw.indices.slice(..));
+            render.draw_indexed(0, 0..draw.count, 0..1);
+        }
+        count_draws(world, node.draws.len() as u32);
+
+        drop(render);
+    }
+}
+
+/// Packs emissive color with alpha cutoff.
+fn emissive(material: &Material) -> mev::vec4 {
+    let [r, g, b] = material.emissive;
+    let cutoff = match material.alpha_mode {
+        AlphaMode::Mask => material.alpha_cutoff,
+        _ => -1.0,
+    };
+    mev::vec4(r, g, b, cutoff)
+}
+
+/// Returns buffer that fits `size` bytes, growing it if necessary.
+///
+/// Buffer is never empty, as it is bound even if there is nothing to upload.
+fn upload_buffer(
+    device: &mev::Device,
+    slot: &mut Option<mev::Buffer>,
+    name: &str,
+    size: usize,
+) -> mev::Buffer {
+    let size = size.max(256);
+
+    match slot {
+        Some(buffer) if buffer.size() >= size => buffer.clone(),
+        slot => slot
+            .insert(
+                device
+                    .new_buffer(mev::BufferDesc {
+                        size: size.next_power_of_two(),
+                        name,
+                        usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
+                        memory: mev::Memory::Shared,
+                    })
+                    .unwrap(),
+            )
+            .clone(),
+    }
+}
+
+fn create_defaults(device: &mev::Device, encoder: &mut mev::CommandEncoder) -> Defaults {
+    let mut pixel = |color: [u8; 4], name: &str| {
+        let image = device
+            .new_image(mev::ImageDesc {
+                extent: mev::Extent2::new(1, 1).into(),
+                format: mev::PixelFormat::Rgba8Unorm,
+                usage: mev::ImageUsage::SAMPLED | mev::ImageUsage::TRANSFER_DST,
+                layers: 1,
+                levels: 1,
+                name,
+            })
+            .unwrap();
+
+        let scratch = device
+            .new_buffer_init(mev::BufferInitDesc {
+                data: &color,
+                usage: mev::BufferUsage::TRANSFER_SRC,
+                memory: mev::Memory::Upload,
+                name: "scratch",
+            })
+            .unwrap();
+
+        let mut copy = encoder.copy();
+        copy.init_image(
+            mev::PipelineStages::empty(),
+            mev::PipelineStages::all(),
+            &image,
+        );
+        copy.copy_buffer_to_image(
+            &scratch,
+            0,
+            4,
+            4,
+            &image,
+            mev::Offset3::ZERO,
+            mev::Extent3::new(1, 1, 1),
+            0..1,
+            0,
+        );
+
+        image
+    };
+
+    Defaults {
+        white: pixel([255, 255, 255, 255], "white"),
+        normal: pixel([128, 128, 255, 255], "flat-normal"),
+    }
+}
+
+fn create_pipeline(
+    device: &mev::Device,
+    library: &mev::Library,
+    format: mev::PixelFormat,
+    (double_sided, blend): (bool, bool),
+) -> mev::RenderPipeline {
+    device
+        .new_render_pipeline(mev::RenderPipelineDesc {
+            name: "mesh",
+            vertex_shader: mev::Shader {
+                library: library.clone(),
+                entry: "vs_main".into(),
+            },
+            vertex_attributes: vec![
+                mev::VertexAttributeDesc {
+                    format: mev::VertexFormat::Float32x3,
+                    offset: 0,
+                    buffer_index: 0,
+                },
+                mev::VertexAttributeDesc {
+                    format: mev::VertexFormat::Float32x3,
+                    offset: 12,
+                    buffer_index: 0,
+                },
+                mev::VertexAttributeDesc {
+                    format: mev::VertexFormat::Float32x2,
+                    offset: 24,
+                    buffer_index: 0,
+                },
+            ],
+            vertex_layouts: vec![mev::VertexLayoutDesc {
+                stride: size_of::<arcana::assets::mesh::Vertex>() as u32,
+                step_mode: mev::VertexStepMode::Vertex,
+            }],
+            primitive_topology: mev::PrimitiveTopology::Triangle,
+            raster: Some(mev::RasterDesc {
+                fragment_shader: Some(mev::Shader {
+                    library: library.clone(),
+                    entry: "fs_main".into(),
+                }),
+                color_targets: vec![mev::ColorTargetDesc {
+                    format,
+                    blend: blend.then(mev::BlendDesc::default),
+                }],
+                // Blended primitives are tested against opaque ones but do not occlude.
+                depth_stencil: Some(mev::DepthStencilDesc {
+                    format: DEPTH_FORMAT,
+                    write_enabled: !blend,
+                    compare: mev::CompareFunction::Less,
+                }),
+                // glTF uses counter-clockwise winding for front faces.
+                front_face: mev::FrontFace::CounterClockwise,
+                culling: if double_sided {
+                    mev::Culling::None
+                } else {
+                    mev::Culling::Back
+                },
+            }),
+            arguments: &[MeshArguments::LAYOUT],
+            constants: MeshConstants::SIZE,
+        })
+        .unwrap()
+}
@@ -0,0 +1,26 @@
+//! This plugin renders 3D meshes.
+//!
+//! Entities with `MeshRenderer` and `Global` transform are drawn
+//! by `DrawMeshes` job from the point of view of the first `Camera3`.
+//! Entities with `RenderLayers` are drawn only if camera's mask includes their layers.
+//! Meshes are usually imported from glTF files with `gltf_mesh` importer.
+//!
+//! Shading uses glTF metallic-roughness model with base color,
+//! metallic-roughness and normal textures.
+//! Scene is lit by `DirectionalLight` and `PointLight` components
+//! and ambient light set in job parameters.
+
+arcana::declare_plugin!([scene ..., camera ...]);
+
+mod job;
+mod light;
+mod renderer;
+
+pub use self::{
+    job::DrawMeshes,
+    light::{DirectionalLight, PointLight},
+    renderer::MeshRenderer,
+};
+
+arcana::reflect_component!(DirectionalLight, Default);
+arcana::reflect_component!(PointLight, Default);
@@ -0,0 +1,51 @@
+use arcana::edict::{self, Component};
+
+/// Light that shines in one direction everywhere in the scene, like the sun.
+///
+/// Shines along negative Z axis of the entity's `Global` transform.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct DirectionalLight {
+    pub color: palette::Srgb,
+
+    /// Multiplier of the light color.
+    pub intensity: f32,
+}
+
+impl Default for DirectionalLight {
+    fn default() -> Self {
+        DirectionalLight {
+            color: palette::Srgb::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+        }
+    }
+}
+
+arcana::reflect_struct!(DirectionalLight { color, intensity });
+
+/// Light that shines in all directions from the entity position.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct PointLight {
+    pub color: palette::Srgb,
+
+    /// Multiplier of the light color.
+    pub intensity: f32,
+
+    /// Distance at which light fades out completely.
+    pub range: f32,
+}
+
+impl Default for PointLight {
+    fn default() -> Self {
+        PointLight {
+            color: palette::Srgb::new(1.0, 1.0, 1.0),
+            intensity: 10.0,
+            range: 10.0,
+        }
+    }
+}
+
+arcana::reflect_struct!(PointLight {
+    color,
+    intensity,
+    range,
+});
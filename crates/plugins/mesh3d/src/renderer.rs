@@ -0,0 +1,28 @@
+use arcana::{
+    assets::{material::Material, mesh::Mesh, Handle},
+    edict::{self, Component},
+};
+
+/// Draws mesh at entity's `Global` transform.
+#[derive(Clone, Component)]
+pub struct MeshRenderer {
+    pub mesh: Handle<Mesh>,
+
+    /// Material used for all primitives of the mesh.
+    /// Primitives use their own materials when not set.
+    pub material: Option<Handle<Material>>,
+}
+
+impl MeshRenderer {
+    pub fn new(mesh: Handle<Mesh>) -> Self {
+        MeshRenderer {
+            mesh,
+            material: None,
+        }
+    }
+
+    pub fn with_material(mut self, material: Handle<Material>) -> Self {
+        self.material = Some(material);
+        self
+    }
+}
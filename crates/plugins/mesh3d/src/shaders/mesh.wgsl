@@ -0,0 +1,180 @@
+const PI: f32 = 3.14159265;
+
+const FLAG_NORMAL_MAP: u32 = 1u;
+
+struct Frame {
+    view_proj: mat4x4f,
+    camera: vec4f,
+    ambient: vec4f,
+}
+
+// Directional light has zero in `position.w`
+// and direction the light travels in `position.xyz`.
+// Range of point light is in `color.a`.
+struct Light {
+    position: vec4f,
+    color: vec4f,
+}
+
+struct Constants {
+    model: mat4x4f,
+    base_color: vec4f,
+    // Alpha cutoff is in `a`, negative when alpha is not tested.
+    emissive: vec4f,
+    metallic: f32,
+    roughness: f32,
+    light_count: u32,
+    flags: u32,
+}
+
+var<push_constant> pc: Constants;
+
+// Same frame buffer is bound for both stages.
+@group(0) @binding(0) var<storage> frame: Frame;
+@group(0) @binding(1) var<storage> view: Frame;
+@group(0) @binding(2) var<storage> lights: array<Light>;
+@group(0) @binding(3) var s: sampler;
+@group(0) @binding(4) var base_color_texture: texture_2d<f32>;
+@group(0) @binding(5) var metallic_roughness_texture: texture_2d<f32>;
+@group(0) @binding(6) var normal_texture: texture_2d<f32>;
+
+struct VertInput {
+    @location(0) position: vec3f,
+    @location(1) normal: vec3f,
+    @location(2) uv: vec2f,
+}
+
+struct VertOutput {
+    @builtin(position)
+    position: vec4f,
+    @location(0)
+    world: vec3f,
+    @location(1)
+    normal: vec3f,
+    @location(2)
+    uv: vec2f,
+}
+
+@vertex
+fn vs_main(input: VertInput) -> VertOutput {
+    let world = pc.model * vec4f(input.position, 1f);
+
+    // Assumes uniform scale.
+    let normal = normalize((pc.model * vec4f(input.normal, 0f)).xyz);
+
+    return VertOutput(frame.view_proj * world, world.xyz, normal, input.uv);
+}
+
+fn srgb_to_linear(c: vec3f) -> vec3f {
+    return select(pow((c + 0.055f) / 1.055f, vec3f(2.4f)), c / 12.92f, c <= vec3f(0.04045f));
+}
+
+fn linear_to_srgb(c: vec3f) -> vec3f {
+    return select(1.055f * pow(c, vec3f(1f / 2.4f)) - 0.055f, c * 12.92f, c <= vec3f(0.0031308f));
+}
+
+// Mesh vertices have no tangents,
+// so tangent frame is reconstructed from screen-space derivatives.
+fn perturb_normal(n: vec3f, world: vec3f, uv: vec2f) -> vec3f {
+    let dp1 = dpdx(world);
+    let dp2 = dpdy(world);
+    let duv1 = dpdx(uv);
+    let duv2 = dpdy(uv);
+
+    let dp2perp = cross(dp2, n);
+    let dp1perp = cross(n, dp1);
+    let t = dp2perp * duv1.x + dp1perp * duv2.x;
+    let b = dp2perp * duv1.y + dp1perp * duv2.y;
+
+    let scale = inverseSqrt(max(max(dot(t, t), dot(b, b)), 1e-12f));
+    let tbn = mat3x3f(t * scale, b * scale, n);
+
+    let m = textureSample(normal_texture, s, uv).xyz * 2f - 1f;
+    return normalize(tbn * m);
+}
+
+fn distribution_ggx(n_dot_h: f32, alpha: f32) -> f32 {
+    let a2 = alpha * alpha;
+    let d = n_dot_h * n_dot_h * (a2 - 1f) + 1f;
+    return a2 / (PI * d * d);
+}
+
+fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let r = roughness + 1f;
+    let k = r * r / 8f;
+    let gv = n_dot_v / (n_dot_v * (1f - k) + k);
+    let gl = n_dot_l / (n_dot_l * (1f - k) + k);
+    return gv * gl;
+}
+
+fn fresnel_schlick(cos_theta: f32, f0: vec3f) -> vec3f {
+    return f0 + (1f - f0) * pow(1f - cos_theta, 5f);
+}
+
+@fragment
+fn fs_main(input: VertOutput, @builtin(front_facing) front: bool) -> @location(0) vec4f {
+    let texel = textureSample(base_color_texture, s, input.uv);
+    let base = pc.base_color * vec4f(srgb_to_linear(texel.rgb), texel.a);
+
+    if pc.emissive.a >= 0f && base.a < pc.emissive.a {
+        discard;
+    }
+
+    let mr = textureSample(metallic_roughness_texture, s, input.uv);
+    let metallic = clamp(pc.metallic * mr.b, 0f, 1f);
+    let roughness = clamp(pc.roughness * mr.g, 0.04f, 1f);
+
+    var n = normalize(input.normal);
+    if !front {
+        n = -n;
+    }
+    if (pc.flags & FLAG_NORMAL_MAP) != 0u {
+        n = perturb_normal(n, input.world, input.uv);
+    }
+
+    let v = normalize(view.camera.xyz - input.world);
+    let n_dot_v = max(dot(n, v), 1e-4f);
+    let f0 = mix(vec3f(0.04f), base.rgb, metallic);
+
+    var color = view.ambient.rgb * base.rgb;
+
+    for (var i = 0u; i < pc.light_count; i++) {
+        let light = lights[i];
+
+        var l: vec3f;
+        var attenuation = 1f;
+        if light.position.w == 0f {
+            l = -light.position.xyz;
+        } else {
+            let to_light = light.position.xyz - input.world;
+            let distance = length(to_light);
+            l = to_light / max(distance, 1e-4f);
+
+            // Inverse square falloff smoothly clamped to zero at range.
+            let ratio = distance / max(light.color.a, 1e-4f);
+            let window = clamp(1f - ratio * ratio * ratio * ratio, 0f, 1f);
+            attenuation = window * window / max(distance * distance, 1e-4f);
+        }
+
+        let n_dot_l = dot(n, l);
+        if n_dot_l <= 0f || attenuation <= 0f {
+            continue;
+        }
+
+        let h = normalize(v + l);
+        let n_dot_h = max(dot(n, h), 0f);
+
+        let d = distribution_ggx(n_dot_h, roughness * roughness);
+        let g = geometry_smith(n_dot_v, n_dot_l, roughness);
+        let f = fresnel_schlick(max(dot(h, v), 0f), f0);
+
+        let specular = d * g * f / (4f * n_dot_v * n_dot_l + 1e-4f);
+        let diffuse = (1f - f) * (1f - metallic) * base.rgb / PI;
+
+        color += (diffuse + specular) * light.color.rgb * n_dot_l * attenuation;
+    }
+
+    color += pc.emissive.rgb;
+
+    return vec4f(linear_to_srgb(clamp(color, vec3f(0f), vec3f(1f))), base.a);
+}
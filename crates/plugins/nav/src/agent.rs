@@ -0,0 +1,247 @@
+use std::task::{Poll, Waker};
+
+use arcana::{
+    edict::{self, ActionEncoder, Component, Entities, Res, View, Without},
+    flow::FlowEntity,
+};
+use motion::dim2::{Motion, MoveTo};
+use na::{Point2, Vector2};
+use scene::dim2::Global;
+
+use crate::mesh::NavMesh;
+
+/// State of navigation agent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AgentStatus {
+    /// Agent has no destination.
+    Idle,
+
+    /// Agent follows path to destination.
+    Moving,
+
+    /// Agent reached its destination.
+    Arrived,
+
+    /// Destination is unreachable.
+    NoPath,
+}
+
+/// Entity that moves to destination along paths found on `NavMesh`.
+///
+/// Entity needs `Motor` component to move.
+/// Agent controls `Motion` of the entity while it has destination.
+#[derive(Component)]
+pub struct NavAgent {
+    /// Radius of the agent used to keep distance from other agents and obstacles.
+    pub radius: f32,
+
+    /// Agent switches to next waypoint when closer than this distance.
+    pub arrive_distance: f32,
+
+    destination: Option<Point2<f32>>,
+    repath: bool,
+    path: Vec<Point2<f32>>,
+    next: usize,
+    status: AgentStatus,
+    waker: Option<Waker>,
+}
+
+impl Drop for NavAgent {
+    fn drop(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl NavAgent {
+    pub fn new(radius: f32) -> Self {
+        NavAgent {
+            radius,
+            arrive_distance: radius,
+            destination: None,
+            repath: false,
+            path: Vec::new(),
+            next: 0,
+            status: AgentStatus::Idle,
+            waker: None,
+        }
+    }
+
+    pub fn with_arrive_distance(mut self, distance: f32) -> Self {
+        self.arrive_distance = distance;
+        self
+    }
+
+    /// Sets new destination.
+    /// Path is found on next update.
+    pub fn set_destination(&mut self, destination: Point2<f32>) {
+        self.destination = Some(destination);
+        self.repath = true;
+        self.status = AgentStatus::Moving;
+    }
+
+    /// Stops the agent and forgets its destination.
+    pub fn stop(&mut self) {
+        self.destination = None;
+        self.repath = false;
+        self.path.clear();
+        self.next = 0;
+        self.set_status(AgentStatus::Idle);
+    }
+
+    pub fn destination(&self) -> Option<Point2<f32>> {
+        self.destination
+    }
+
+    /// Returns waypoints of current path.
+    pub fn path(&self) -> &[Point2<f32>] {
+        &self.path
+    }
+
+    pub fn status(&self) -> AgentStatus {
+        self.status
+    }
+
+    fn set_status(&mut self, status: AgentStatus) {
+        self.status = status;
+        if status != AgentStatus::Moving {
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Dynamic obstacle that agents steer around.
+///
+/// Unlike static colliders, obstacles are not baked into `NavMesh`.
+#[derive(Clone, Copy, Debug, Component)]
+pub struct NavObstacle {
+    pub radius: f32,
+}
+
+impl NavObstacle {
+    pub fn new(radius: f32) -> Self {
+        NavObstacle { radius }
+    }
+}
+
+/// Finds paths for agents and steers them along.
+#[arcana::system]
+fn nav_agent_system(
+    mut agents: View<(Entities, &Global, &mut NavAgent, Option<&mut Motion>)>,
+    obstacles: View<(&Global, &NavObstacle), Without<NavAgent>>,
+    navmesh: Res<NavMesh>,
+    mut encoder: ActionEncoder,
+) {
+    // Agents avoid each other the same way as obstacles.
+    let mut others = obstacles
+        .iter()
+        .map(|(global, obstacle)| (None, global.iso.translation.vector, obstacle.radius))
+        .collect::<Vec<_>>();
+
+    for (e, global, agent, _) in agents.iter_mut() {
+        others.push((Some(e.id()), global.iso.translation.vector, agent.radius));
+    }
+
+    for (e, global, agent, motion) in agents {
+        let Some(destination) = agent.destination else {
+            continue;
+        };
+
+        let pos = Point2::from(global.iso.translation.vector);
+
+        if agent.repath {
+            agent.repath = false;
+            agent.next = 0;
+
+            match navmesh.find_path(pos, destination) {
+                Some(path) => {
+                    agent.path = path;
+                    agent.set_status(AgentStatus::Moving);
+                }
+                None => {
+                    agent.path.clear();
+                    agent.destination = None;
+                    agent.set_status(AgentStatus::NoPath);
+                    encoder.drop::<Motion>(e);
+                    continue;
+                }
+            }
+        }
+
+        // Skip reached waypoints.
+        while agent.next < agent.path.len()
+            && na::distance(&pos, &agent.path[agent.next]) <= agent.arrive_distance
+        {
+            agent.next += 1;
+        }
+
+        let Some(&waypoint) = agent.path.get(agent.next) else {
+            agent.destination = None;
+            agent.set_status(AgentStatus::Arrived);
+            encoder.drop::<Motion>(e);
+            continue;
+        };
+
+        // Push waypoint away from everything the agent overlaps with.
+        let mut avoid = Vector2::zeros();
+        for &(id, other, radius) in &others {
+            if id == Some(e.id()) {
+                continue;
+            }
+
+            let offset = pos.coords - other;
+            let distance = offset.norm();
+            let overlap = agent.radius + radius - distance;
+            if overlap > 0.0 && distance > f32::EPSILON {
+                avoid += offset / distance * overlap;
+            }
+        }
+
+        let target = MoveTo::new(waypoint + avoid);
+        match motion {
+            Some(motion) => *motion = Motion::To(target),
+            None => encoder.insert(e, Motion::To(target)),
+        }
+    }
+}
+
+/// Extension trait for `FlowEntity` to navigate agents.
+#[allow(async_fn_in_trait)]
+pub trait FlowEntityExt {
+    /// Sends agent to destination and waits until it stops.
+    ///
+    /// Returns `true` if the agent arrived at destination.
+    /// Returns `false` if destination is unreachable,
+    /// agent was stopped or entity has no `NavAgent` component.
+    async fn navigate_to(&mut self, destination: Point2<f32>) -> bool;
+}
+
+impl FlowEntityExt for FlowEntity<'_> {
+    async fn navigate_to(&mut self, destination: Point2<f32>) -> bool {
+        let started = self
+            .try_poll_view_mut::<&mut NavAgent, _, _>(|agent, _cx| {
+                agent.set_destination(destination);
+                Poll::Ready(())
+            })
+            .await;
+
+        if started.is_none() {
+            return false;
+        }
+
+        let status = self
+            .try_poll_view_mut::<&mut NavAgent, _, _>(|agent, cx| {
+                if agent.status == AgentStatus::Moving {
+                    agent.waker = Some(cx.waker().clone());
+                    return Poll::Pending;
+                }
+                Poll::Ready(agent.status)
+            })
+            .await;
+
+        status == Some(AgentStatus::Arrived)
+    }
+}
@@ -0,0 +1,27 @@
+//! This plugin provides navigation for 2D games.
+//!
+//! `NavMesh` resource describes walkable area as a set of convex polygons.
+//! It can be baked from colliders of static bodies with [`NavMesh::bake`]
+//! or built from imported triangles with [`NavMesh::from_triangles`].
+//!
+//! [`NavMesh::find_path`] returns shortest path between two points.
+//! Entities with `NavAgent` component follow paths to their destinations
+//! using `Motion` of the motion plugin, steering around `NavObstacle`s
+//! and other agents on the way.
+
+use arcana::World;
+
+arcana::declare_plugin!([scene ..., physics ..., motion ...]);
+
+mod agent;
+mod mesh;
+
+pub use self::{
+    agent::{AgentStatus, FlowEntityExt, NavAgent, NavObstacle},
+    mesh::{BakeSettings, NavMesh},
+};
+
+#[arcana::init]
+fn init_nav(world: &mut World) {
+    world.insert_resource(NavMesh::new());
+}
@@ -0,0 +1,532 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use arcana::edict::world::World;
+use na::{Point2, Vector2};
+use physics::dim2::{Collider, RigidBody};
+use scene::dim2::Global;
+
+use crate::agent::NavAgent;
+
+const EPSILON: f32 = 1e-4;
+
+/// Parameters of navmesh baking.
+#[derive(Clone, Copy, Debug)]
+pub struct BakeSettings {
+    /// Lower corner of the baked area.
+    pub min: Point2<f32>,
+
+    /// Upper corner of the baked area.
+    pub max: Point2<f32>,
+
+    /// Size of the grid cell used to sample colliders.
+    /// Smaller cells follow collider shapes closer but take longer to bake.
+    pub cell_size: f32,
+
+    /// Radius of agents.
+    /// Walkable area is shrunk by this value around colliders.
+    pub agent_radius: f32,
+}
+
+/// Edge shared by two polygons.
+///
+/// Left and right are as seen when crossing it from the owning polygon.
+#[derive(Clone, Copy, Debug)]
+struct Portal {
+    to: usize,
+    left: Point2<f32>,
+    right: Point2<f32>,
+}
+
+impl Portal {
+    fn midpoint(&self) -> Point2<f32> {
+        na::center(&self.left, &self.right)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Polygon {
+    /// Convex polygon with vertices in counter-clockwise order.
+    vertices: Vec<Point2<f32>>,
+    portals: Vec<Portal>,
+    min: Point2<f32>,
+    max: Point2<f32>,
+}
+
+impl Polygon {
+    fn new(mut vertices: Vec<Point2<f32>>) -> Self {
+        if signed_area(&vertices) < 0.0 {
+            vertices.reverse();
+        }
+
+        let mut min = vertices[0];
+        let mut max = vertices[0];
+        for v in &vertices[1..] {
+            min = min.inf(v);
+            max = max.sup(v);
+        }
+
+        Polygon {
+            vertices,
+            portals: Vec::new(),
+            min,
+            max,
+        }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Point2<f32>, Point2<f32>)> + '_ {
+        let n = self.vertices.len();
+        (0..n).map(move |i| (self.vertices[i], self.vertices[(i + 1) % n]))
+    }
+
+    fn contains(&self, point: &Point2<f32>) -> bool {
+        self.edges().all(|(a, b)| cross(&a, &b, point) >= -EPSILON)
+    }
+
+    fn closest_point(&self, point: &Point2<f32>) -> Point2<f32> {
+        if self.contains(point) {
+            return *point;
+        }
+
+        self.edges()
+            .map(|(a, b)| closest_on_segment(&a, &b, point))
+            .min_by(|a, b| {
+                na::distance_squared(a, point).total_cmp(&na::distance_squared(b, point))
+            })
+            .unwrap()
+    }
+}
+
+/// Walkable area made of convex polygons connected by portals.
+#[derive(Clone, Debug, Default)]
+pub struct NavMesh {
+    polygons: Vec<Polygon>,
+}
+
+impl NavMesh {
+    /// Returns empty navmesh where no path can be found.
+    pub fn new() -> Self {
+        NavMesh::default()
+    }
+
+    /// Builds navmesh from triangles, e.g. imported from a level editor.
+    ///
+    /// Triangles that share an edge are connected.
+    pub fn from_triangles(vertices: &[Point2<f32>], indices: &[[u32; 3]]) -> Self {
+        NavMesh::from_polygons(
+            indices
+                .iter()
+                .map(|tri| tri.iter().map(|&i| vertices[i as usize]).collect()),
+        )
+    }
+
+    /// Builds navmesh from convex polygons.
+    ///
+    /// Polygons that share a part of an edge are connected.
+    pub fn from_polygons(polygons: impl IntoIterator<Item = Vec<Point2<f32>>>) -> Self {
+        let mut polygons = polygons
+            .into_iter()
+            .filter(|vertices| vertices.len() >= 3)
+            .map(Polygon::new)
+            .collect::<Vec<_>>();
+
+        connect(&mut polygons);
+        NavMesh { polygons }
+    }
+
+    /// Bakes navmesh from colliders of static entities in the world.
+    ///
+    /// Colliders of entities with non-fixed rigid bodies
+    /// and of navigation agents are ignored,
+    /// as they move and are avoided at runtime instead.
+    pub fn bake(world: &World, settings: &BakeSettings) -> Self {
+        let view = world
+            .view::<(&Global, &Collider, Option<&RigidBody>)>()
+            .without::<NavAgent>();
+
+        let colliders = view
+            .iter()
+            .filter(|(_, _, body)| body.map_or(true, |body| body.is_fixed()))
+            .map(|(global, collider, _)| (global.iso, collider))
+            .collect::<Vec<_>>();
+
+        NavMesh::bake_with(settings, |point| {
+            colliders
+                .iter()
+                .map(|(iso, collider)| collider.distance_to_point(iso, point))
+                .fold(f32::INFINITY, f32::min)
+        })
+    }
+
+    /// Bakes navmesh from distance function.
+    ///
+    /// Function returns distance from a point to the closest obstacle.
+    /// Area closer than agent radius to an obstacle is not walkable.
+    pub fn bake_with(settings: &BakeSettings, distance: impl Fn(&Point2<f32>) -> f32) -> Self {
+        let cell = settings.cell_size.max(EPSILON);
+        let size = settings.max - settings.min;
+        let width = (size.x / cell).ceil().max(0.0) as usize;
+        let height = (size.y / cell).ceil().max(0.0) as usize;
+
+        let mut walkable = vec![false; width * height];
+        for y in 0..height {
+            for x in 0..width {
+                let center = settings.min + Vector2::new(x as f32 + 0.5, y as f32 + 0.5) * cell;
+                walkable[y * width + x] = distance(&center) > settings.agent_radius;
+            }
+        }
+
+        // Walkable cells are merged greedily into rectangles.
+        let mut taken = vec![false; width * height];
+        let free =
+            |taken: &[bool], x: usize, y: usize| walkable[y * width + x] && !taken[y * width + x];
+
+        let mut rects = Vec::new();
+        for y in 0..height {
+            let mut x = 0;
+            while x < width {
+                if !free(&taken, x, y) {
+                    x += 1;
+                    continue;
+                }
+
+                let x0 = x;
+                while x < width && free(&taken, x, y) {
+                    x += 1;
+                }
+
+                let mut y1 = y + 1;
+                while y1 < height && (x0..x).all(|x| free(&taken, x, y1)) {
+                    y1 += 1;
+                }
+
+                for ty in y..y1 {
+                    for tx in x0..x {
+                        taken[ty * width + tx] = true;
+                    }
+                }
+
+                let min = settings.min + Vector2::new(x0 as f32, y as f32) * cell;
+                let max = settings.min + Vector2::new(x as f32, y1 as f32) * cell;
+                rects.push(vec![
+                    min,
+                    Point2::new(max.x, min.y),
+                    max,
+                    Point2::new(min.x, max.y),
+                ]);
+            }
+        }
+
+        NavMesh::from_polygons(rects)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.polygons.is_empty()
+    }
+
+    /// Returns closest point on the navmesh.
+    pub fn closest_point(&self, point: &Point2<f32>) -> Option<Point2<f32>> {
+        let idx = self.locate(point)?;
+        Some(self.polygons[idx].closest_point(point))
+    }
+
+    /// Returns shortest path between two points.
+    ///
+    /// Points outside of the navmesh are moved to the closest walkable point.
+    /// Returned path starts and ends with these points.
+    /// Returns `None` if points are not connected.
+    pub fn find_path(&self, from: Point2<f32>, to: Point2<f32>) -> Option<Vec<Point2<f32>>> {
+        let start = self.locate(&from)?;
+        let goal = self.locate(&to)?;
+
+        let from = self.polygons[start].closest_point(&from);
+        let to = self.polygons[goal].closest_point(&to);
+
+        let portals = self.find_corridor(start, goal, from, to)?;
+        Some(funnel(from, to, &portals))
+    }
+
+    /// Returns index of polygon containing the point or closest to it.
+    fn locate(&self, point: &Point2<f32>) -> Option<usize> {
+        if let Some(idx) = self.polygons.iter().position(|p| p.contains(point)) {
+            return Some(idx);
+        }
+
+        self.polygons
+            .iter()
+            .enumerate()
+            .map(|(idx, p)| (idx, na::distance_squared(&p.closest_point(point), point)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(idx, _)| idx)
+    }
+
+    /// A* search over polygons.
+    /// Returns portals crossed on the way from start to goal.
+    fn find_corridor(
+        &self,
+        start: usize,
+        goal: usize,
+        from: Point2<f32>,
+        to: Point2<f32>,
+    ) -> Option<Vec<Portal>> {
+        struct Node {
+            cost: f32,
+            /// Point where polygon was entered.
+            point: Point2<f32>,
+            /// Polygon and portal it came from.
+            came_from: Option<(usize, usize)>,
+            closed: bool,
+        }
+
+        #[derive(PartialEq)]
+        struct Open {
+            estimate: f32,
+            idx: usize,
+        }
+
+        impl Eq for Open {}
+
+        impl PartialOrd for Open {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Open {
+            // Reversed to make the heap a min-heap.
+            fn cmp(&self, other: &Self) -> Ordering {
+                other.estimate.total_cmp(&self.estimate)
+            }
+        }
+
+        let mut nodes = self
+            .polygons
+            .iter()
+            .map(|_| Node {
+                cost: f32::INFINITY,
+                point: from,
+                came_from: None,
+                closed: false,
+            })
+            .collect::<Vec<_>>();
+
+        nodes[start].cost = 0.0;
+
+        let mut open = BinaryHeap::new();
+        open.push(Open {
+            estimate: na::distance(&from, &to),
+            idx: start,
+        });
+
+        while let Some(Open { idx, .. }) = open.pop() {
+            if idx == goal {
+                break;
+            }
+
+            if nodes[idx].closed {
+                continue;
+            }
+            nodes[idx].closed = true;
+
+            for (portal_idx, portal) in self.polygons[idx].portals.iter().enumerate() {
+                let next = &nodes[portal.to];
+                if next.closed {
+                    continue;
+                }
+
+                let point = portal.midpoint();
+                let cost = nodes[idx].cost + na::distance(&nodes[idx].point, &point);
+                if cost >= next.cost {
+                    continue;
+                }
+
+                nodes[portal.to] = Node {
+                    cost,
+                    point,
+                    came_from: Some((idx, portal_idx)),
+                    closed: false,
+                };
+
+                open.push(Open {
+                    estimate: cost + na::distance(&point, &to),
+                    idx: portal.to,
+                });
+            }
+        }
+
+        if start != goal && nodes[goal].came_from.is_none() {
+            return None;
+        }
+
+        let mut portals = Vec::new();
+        let mut idx = goal;
+        while let Some((prev, portal)) = nodes[idx].came_from {
+            portals.push(self.polygons[prev].portals[portal]);
+            idx = prev;
+        }
+        portals.reverse();
+
+        Some(portals)
+    }
+}
+
+/// Finds portals between polygons.
+fn connect(polygons: &mut [Polygon]) {
+    for a in 0..polygons.len() {
+        for b in a + 1..polygons.len() {
+            let (pa, pb) = (&polygons[a], &polygons[b]);
+
+            // Bounding boxes of neighbors touch.
+            if pa.min.x > pb.max.x + EPSILON
+                || pb.min.x > pa.max.x + EPSILON
+                || pa.min.y > pb.max.y + EPSILON
+                || pb.min.y > pa.max.y + EPSILON
+            {
+                continue;
+            }
+
+            let mut found = Vec::new();
+            for (a0, a1) in pa.edges() {
+                for (b0, b1) in pb.edges() {
+                    if let Some((p, q)) = shared_segment(&a0, &a1, &b0, &b1) {
+                        found.push((p, q));
+                    }
+                }
+            }
+
+            for (p, q) in found {
+                // `p` to `q` goes along the edge of `a` in its winding order.
+                polygons[a].portals.push(Portal {
+                    to: b,
+                    left: q,
+                    right: p,
+                });
+                polygons[b].portals.push(Portal {
+                    to: a,
+                    left: p,
+                    right: q,
+                });
+            }
+        }
+    }
+}
+
+/// Returns overlapping part of two opposite collinear edges
+/// ordered along the first edge.
+fn shared_segment(
+    a0: &Point2<f32>,
+    a1: &Point2<f32>,
+    b0: &Point2<f32>,
+    b1: &Point2<f32>,
+) -> Option<(Point2<f32>, Point2<f32>)> {
+    let dir = a1 - a0;
+    let len2 = dir.norm_squared();
+    if len2 < EPSILON * EPSILON {
+        return None;
+    }
+
+    let len = len2.sqrt();
+    if (cross(a0, a1, b0) / len).abs() > EPSILON || (cross(a0, a1, b1) / len).abs() > EPSILON {
+        return None;
+    }
+
+    if dir.dot(&(b1 - b0)) >= 0.0 {
+        return None;
+    }
+
+    let t0 = (b0 - a0).dot(&dir) / len2;
+    let t1 = (b1 - a0).dot(&dir) / len2;
+    let lo = t0.min(t1).max(0.0);
+    let hi = t0.max(t1).min(1.0);
+
+    if (hi - lo) * len <= EPSILON {
+        return None;
+    }
+
+    Some((a0 + dir * lo, a0 + dir * hi))
+}
+
+/// Simple stupid funnel algorithm.
+/// Pulls the path through the portals tight.
+fn funnel(from: Point2<f32>, to: Point2<f32>, portals: &[Portal]) -> Vec<Point2<f32>> {
+    let mut path = vec![from];
+
+    let mut sides = portals
+        .iter()
+        .map(|p| (p.left, p.right))
+        .collect::<Vec<_>>();
+    sides.push((to, to));
+
+    let mut apex = from;
+    let mut left = from;
+    let mut right = from;
+    let mut left_idx = 0;
+    let mut right_idx = 0;
+
+    let mut i = 0;
+    while i < sides.len() {
+        let (new_left, new_right) = sides[i];
+
+        // Tighten the right side.
+        if cross(&apex, &right, &new_right) >= 0.0 {
+            if apex == right || cross(&apex, &left, &new_right) < 0.0 {
+                right = new_right;
+                right_idx = i;
+            } else {
+                // Right crossed over left, left becomes the corner.
+                path.push(left);
+                apex = left;
+                right = apex;
+                right_idx = left_idx;
+                i = left_idx + 1;
+                continue;
+            }
+        }
+
+        // Tighten the left side.
+        if cross(&apex, &left, &new_left) <= 0.0 {
+            if apex == left || cross(&apex, &right, &new_left) > 0.0 {
+                left = new_left;
+                left_idx = i;
+            } else {
+                // Left crossed over right, right becomes the corner.
+                path.push(right);
+                apex = right;
+                left = apex;
+                left_idx = right_idx;
+                i = right_idx + 1;
+                continue;
+            }
+        }
+
+        i += 1;
+    }
+
+    if path.last() != Some(&to) {
+        path.push(to);
+    }
+
+    path
+}
+
+/// Positive if `p` is to the left of `a` to `b` line.
+fn cross(a: &Point2<f32>, b: &Point2<f32>, p: &Point2<f32>) -> f32 {
+    (b - a).perp(&(p - a))
+}
+
+fn signed_area(vertices: &[Point2<f32>]) -> f32 {
+    let n = vertices.len();
+    (0..n)
+        .map(|i| vertices[i].coords.perp(&vertices[(i + 1) % n].coords))
+        .sum::<f32>()
+        / 2.0
+}
+
+fn closest_on_segment(a: &Point2<f32>, b: &Point2<f32>, p: &Point2<f32>) -> Point2<f32> {
+    let ab = b - a;
+    let len2 = ab.norm_squared();
+    if len2 < EPSILON * EPSILON {
+        return *a;
+    }
+    let t = ((p - a).dot(&ab) / len2).clamp(0.0, 1.0);
+    a + ab * t
+}
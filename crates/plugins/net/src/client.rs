@@ -0,0 +1,298 @@
+use std::{
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+
+use arcana::{edict::world::World, hashbrown::HashMap, model::Value, EntityId};
+
+use crate::{
+    protocol::{ClientId, Message, Packet, PROTOCOL_VERSION},
+    replication::{NetId, Predicted, Replication},
+    transport::{Connection, NetError, Socket, TIMEOUT},
+};
+
+/// Connection request is repeated with this interval until accepted.
+const CONNECT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Something that happened on the client since events were drained last time.
+#[derive(Debug)]
+pub enum ClientEvent {
+    Connected(ClientId),
+    Disconnected(NetError),
+}
+
+enum State {
+    Connecting { last_attempt: Instant },
+    Connected { client: ClientId },
+    Disconnected,
+}
+
+struct Mirror {
+    entity: EntityId,
+
+    /// Server tick of the last applied update.
+    /// Older updates that arrive out of order are dropped.
+    tick: u64,
+}
+
+/// Client side of networking.
+///
+/// Insert as resource to connect to the server.
+/// Replicated entities are spawned with [`NetId`] component
+/// and despawned when client disconnects.
+pub struct NetClient {
+    socket: Socket,
+    connection: Connection,
+    state: State,
+    entities: HashMap<NetId, Mirror>,
+    input_tick: u64,
+    events: Vec<ClientEvent>,
+}
+
+impl NetClient {
+    pub fn connect(addr: SocketAddr) -> Result<Self, NetError> {
+        let local = match addr {
+            SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+        };
+
+        let socket = Socket::bind(local)?;
+        let mut connection = Connection::new(addr);
+        connection.send_packet(
+            &socket,
+            &Packet::Connect {
+                protocol: PROTOCOL_VERSION,
+            },
+        );
+
+        Ok(NetClient {
+            socket,
+            connection,
+            state: State::Connecting {
+                last_attempt: Instant::now(),
+            },
+            entities: HashMap::new(),
+            input_tick: 0,
+            events: Vec::new(),
+        })
+    }
+
+    /// Returns id assigned by the server.
+    /// `None` until connection is established.
+    pub fn client_id(&self) -> Option<ClientId> {
+        match self.state {
+            State::Connected { client } => Some(client),
+            _ => None,
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        matches!(self.state, State::Connected { .. })
+    }
+
+    /// Returns local entity that mirrors server entity.
+    pub fn entity(&self, id: NetId) -> Option<EntityId> {
+        Some(self.entities.get(&id)?.entity)
+    }
+
+    /// Sends input to the server reliably.
+    ///
+    /// Returns tick of the input.
+    /// Server reports last processed input tick with each update,
+    /// reconcile hook uses it to replay inputs that are not yet processed.
+    pub fn send_input(&mut self, value: Value) -> u64 {
+        self.input_tick += 1;
+        let tick = self.input_tick;
+
+        if self.is_connected() {
+            let message = Message::Input { tick, value };
+            self.connection
+                .send_reliable(&self.socket, message.encode());
+        }
+
+        tick
+    }
+
+    pub fn disconnect(&mut self) {
+        if !matches!(self.state, State::Disconnected) {
+            self.connection
+                .send_packet(&self.socket, &Packet::Disconnect);
+            self.state = State::Disconnected;
+        }
+    }
+
+    /// Takes events that happened since last call.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = ClientEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    /// Receives packets and returns delivered messages.
+    fn poll(&mut self) -> Vec<Message> {
+        let mut delivered = Vec::new();
+
+        while let Some((addr, packet)) = self.socket.recv() {
+            if addr != self.connection.addr() {
+                continue;
+            }
+
+            match self.state {
+                State::Disconnected => {}
+                State::Connecting { .. } => match packet {
+                    Packet::Accept { client } => {
+                        tracing::info!("Connected to {addr} as {client}");
+                        self.state = State::Connected { client };
+                        self.events.push(ClientEvent::Connected(client));
+                    }
+                    Packet::Refuse { reason } => {
+                        self.state = State::Disconnected;
+                        self.events
+                            .push(ClientEvent::Disconnected(NetError::Refused { reason }));
+                    }
+                    _ => {}
+                },
+                State::Connected { .. } => match packet {
+                    Packet::Disconnect => {
+                        self.state = State::Disconnected;
+                        self.events
+                            .push(ClientEvent::Disconnected(NetError::Closed));
+                    }
+                    packet => {
+                        self.connection
+                            .receive(&self.socket, packet, &mut delivered);
+                    }
+                },
+            }
+        }
+
+        match self.state {
+            State::Connecting {
+                ref mut last_attempt,
+            } => {
+                if self.connection.silence() > TIMEOUT {
+                    self.state = State::Disconnected;
+                    self.events
+                        .push(ClientEvent::Disconnected(NetError::Timeout));
+                } else if last_attempt.elapsed() >= CONNECT_INTERVAL {
+                    *last_attempt = Instant::now();
+                    self.connection.send_packet(
+                        &self.socket,
+                        &Packet::Connect {
+                            protocol: PROTOCOL_VERSION,
+                        },
+                    );
+                }
+            }
+            State::Connected { .. } => {
+                if self.connection.silence() > TIMEOUT {
+                    tracing::info!("Connection to {} timed out", self.connection.addr());
+                    self.state = State::Disconnected;
+                    self.events
+                        .push(ClientEvent::Disconnected(NetError::Timeout));
+                } else {
+                    self.connection.update(&self.socket);
+                }
+            }
+            State::Disconnected => {}
+        }
+
+        delivered
+            .iter()
+            .filter_map(|payload| {
+                let message = Message::decode(payload);
+                if message.is_none() {
+                    tracing::debug!("Malformed message from server");
+                }
+                message
+            })
+            .collect()
+    }
+
+    fn apply(&mut self, world: &mut World, replication: &Replication, message: Message) {
+        match message {
+            Message::Spawn {
+                id,
+                owned,
+                components,
+            } => {
+                let entity = match self.entities.get(&id) {
+                    Some(mirror) => mirror.entity,
+                    None => {
+                        let entity = if owned {
+                            world.spawn((id, Predicted)).id()
+                        } else {
+                            world.spawn((id,)).id()
+                        };
+                        self.entities.insert(id, Mirror { entity, tick: 0 });
+                        entity
+                    }
+                };
+
+                for (stid, value) in &components {
+                    replication.apply(world, entity, 0, *stid, value);
+                }
+            }
+            Message::Despawn { id } => {
+                if let Some(mirror) = self.entities.remove(&id) {
+                    let _ = world.despawn(mirror.entity);
+                }
+            }
+            Message::Remove { id, components } => {
+                if let Some(mirror) = self.entities.get(&id) {
+                    for stid in components {
+                        replication.remove(world, mirror.entity, stid);
+                    }
+                }
+            }
+            Message::Update {
+                tick,
+                input_tick,
+                id,
+                components,
+            } => {
+                // Update may outrun spawn of the entity, it will be synced later.
+                let Some(mirror) = self.entities.get_mut(&id) else {
+                    return;
+                };
+
+                if tick < mirror.tick {
+                    return;
+                }
+                mirror.tick = tick;
+
+                for (stid, value) in &components {
+                    replication.apply(world, mirror.entity, input_tick, *stid, value);
+                }
+            }
+            Message::Input { .. } => {
+                tracing::debug!("Unexpected message from server");
+            }
+        }
+    }
+
+    fn despawn_all(&mut self, world: &mut World) {
+        for (_, mirror) in self.entities.drain() {
+            let _ = world.despawn(mirror.entity);
+        }
+    }
+}
+
+#[arcana::system]
+fn net_client(world: &mut World) {
+    let Some(mut client) = world.remove_resource::<NetClient>() else {
+        return;
+    };
+
+    let messages = client.poll();
+
+    if matches!(client.state, State::Disconnected) {
+        client.despawn_all(world);
+    } else if let Some(replication) = world.remove_resource::<Replication>() {
+        // Replication is taken out so that reconcile hook can access the world.
+        for message in messages {
+            client.apply(world, &replication, message);
+        }
+        world.insert_resource(replication);
+    }
+
+    world.insert_resource(client);
+}
@@ -0,0 +1,38 @@
+//! This plugin provides client-server networking.
+//!
+//! Transport is UDP with lightweight reliability layer on top:
+//! reliable messages are resent until acknowledged and delivered in order,
+//! unreliable ones are sent once.
+//!
+//! Insert [`NetServer`] resource on the server and [`NetClient`] on clients.
+//! Server entities marked with [`Replicated`] are replicated to clients.
+//! Only components registered in [`Replication`] resource are replicated,
+//! they are identified by `Stid` and transferred as reflected values,
+//! so registry must be the same on both sides.
+//!
+//! [`Replication::set_interest`] limits which entities each client receives.
+//! Entities owned by the client (see [`Owner`]) are marked [`Predicted`] on that client,
+//! and their updates go through [`Replication::set_reconcile`] hook.
+
+use arcana::World;
+
+arcana::declare_plugin!();
+
+mod client;
+mod protocol;
+mod replication;
+mod server;
+mod transport;
+
+pub use self::{
+    client::{ClientEvent, NetClient},
+    protocol::ClientId,
+    replication::{InterestFn, NetId, Owner, Predicted, ReconcileFn, Replicated, Replication},
+    server::{NetServer, ServerEvent},
+    transport::NetError,
+};
+
+#[arcana::init]
+fn init_net(world: &mut World) {
+    world.insert_resource(Replication::new());
+}
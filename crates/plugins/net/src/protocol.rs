@@ -0,0 +1,93 @@
+use arcana::{model::Value, Stid};
+
+use crate::replication::NetId;
+
+/// Incremented on incompatible changes of the protocol.
+pub(crate) const PROTOCOL_VERSION: u32 = 1;
+
+/// Identifies client connected to the server.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub struct ClientId(pub u32);
+
+impl std::fmt::Display for ClientId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "client#{}", self.0)
+    }
+}
+
+/// Single datagram sent over UDP.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) enum Packet {
+    Connect {
+        protocol: u32,
+    },
+    Accept {
+        client: ClientId,
+    },
+    Refuse {
+        reason: String,
+    },
+    Disconnect,
+
+    /// Keeps idle connection alive.
+    Heartbeat,
+
+    /// Payload that is resent until acknowledged
+    /// and delivered in order of sequence numbers.
+    Reliable {
+        seq: u64,
+        payload: Vec<u8>,
+    },
+    Ack {
+        seq: u64,
+    },
+
+    /// Payload that may be lost or delivered out of order.
+    Unreliable {
+        payload: Vec<u8>,
+    },
+}
+
+/// Replication message carried in packet payloads.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) enum Message {
+    /// Entity became relevant to the client.
+    Spawn {
+        id: NetId,
+        owned: bool,
+        components: Vec<(Stid, Value)>,
+    },
+
+    /// Entity is no longer relevant to the client or was despawned.
+    Despawn { id: NetId },
+
+    /// Components were removed from the entity.
+    Remove { id: NetId, components: Vec<Stid> },
+
+    /// New values of components.
+    Update {
+        /// Server tick of the snapshot.
+        tick: u64,
+
+        /// Last input tick of the client processed by the server.
+        input_tick: u64,
+
+        id: NetId,
+        components: Vec<(Stid, Value)>,
+    },
+
+    /// Input of the client for a tick.
+    Input { tick: u64, value: Value },
+}
+
+impl Message {
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Message serialization cannot fail")
+    }
+
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        bincode::deserialize(data).ok()
+    }
+}
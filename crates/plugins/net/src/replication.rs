@@ -0,0 +1,176 @@
+use arcana::{
+    edict::{self, world::World, Component},
+    hashbrown::HashMap,
+    model::Value,
+    reflect::{ComponentReflect, Reflect},
+    EntityId, Stid, WithStid,
+};
+
+use crate::protocol::ClientId;
+
+/// Network identifier of replicated entity.
+/// Same on the server and all clients.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Component,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct NetId(pub u64);
+
+/// Marks server entity for replication to clients.
+///
+/// Only components registered in [`Replication`] are replicated.
+#[derive(Clone, Copy, Debug, Default, Component)]
+pub struct Replicated;
+
+/// Client that owns replicated entity.
+///
+/// Owning client receives the entity with [`Predicted`] marker.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Component)]
+pub struct Owner(pub ClientId);
+
+/// Marks client entity that is owned by this client.
+///
+/// Updates of components of such entities go through the reconcile hook
+/// instead of overwriting locally predicted values.
+#[derive(Clone, Copy, Debug, Default, Component)]
+pub struct Predicted;
+
+/// Decides whether entity is relevant to the client.
+/// Irrelevant entities are despawned on the client.
+pub type InterestFn = fn(world: &World, client: ClientId, entity: EntityId) -> bool;
+
+/// Applies server value of the component to predicted entity.
+///
+/// `input_tick` is the last client input processed by the server
+/// when the value was produced, so that client can replay newer inputs.
+/// Returns `false` if default behavior of overwriting the value should be used.
+pub type ReconcileFn = fn(
+    world: &mut World,
+    entity: EntityId,
+    input_tick: u64,
+    component: Stid,
+    value: &Value,
+) -> bool;
+
+struct ReplicatedComponent {
+    reflect: ComponentReflect,
+    remove: fn(&mut World, EntityId),
+}
+
+/// Registry of replicated components.
+///
+/// Must be populated identically on the server and clients.
+pub struct Replication {
+    components: HashMap<Stid, ReplicatedComponent>,
+    interest: Option<InterestFn>,
+    reconcile: Option<ReconcileFn>,
+}
+
+impl Replication {
+    pub fn new() -> Self {
+        Replication {
+            components: HashMap::new(),
+            interest: None,
+            reconcile: None,
+        }
+    }
+
+    /// Registers component type for replication.
+    pub fn register<T>(&mut self)
+    where
+        T: Reflect + Component + WithStid + Default + Send + Sync,
+    {
+        self.components.insert(
+            T::stid(),
+            ReplicatedComponent {
+                reflect: ComponentReflect::with_default::<T>(),
+                remove: |world, entity| {
+                    let _ = world.drop::<T>(entity);
+                },
+            },
+        );
+    }
+
+    pub fn unregister<T>(&mut self)
+    where
+        T: WithStid,
+    {
+        self.components.remove(&T::stid());
+    }
+
+    /// Sets interest management hook.
+    /// Without it all replicated entities are relevant to all clients.
+    pub fn set_interest(&mut self, interest: InterestFn) {
+        self.interest = Some(interest);
+    }
+
+    /// Sets client prediction hook.
+    pub fn set_reconcile(&mut self, reconcile: ReconcileFn) {
+        self.reconcile = Some(reconcile);
+    }
+
+    pub(crate) fn is_relevant(&self, world: &World, client: ClientId, entity: EntityId) -> bool {
+        match self.interest {
+            None => true,
+            Some(interest) => interest(world, client, entity),
+        }
+    }
+
+    /// Collects values of all replicated components of the entity.
+    pub(crate) fn snapshot(&self, world: &World, entity: EntityId) -> HashMap<Stid, Value> {
+        self.components
+            .iter()
+            .filter_map(|(&stid, component)| Some((stid, component.reflect.get(world, entity)?)))
+            .collect()
+    }
+
+    /// Applies component value received from the server.
+    pub(crate) fn apply(
+        &self,
+        world: &mut World,
+        entity: EntityId,
+        input_tick: u64,
+        stid: Stid,
+        value: &Value,
+    ) {
+        let Some(component) = self.components.get(&stid) else {
+            tracing::warn!("Received unregistered component {stid}");
+            return;
+        };
+        let reflect = &component.reflect;
+
+        if let Some(reconcile) = self.reconcile {
+            if world.get::<&Predicted>(entity).is_ok()
+                && reconcile(world, entity, input_tick, stid, value)
+            {
+                return;
+            }
+        }
+
+        let result = if reflect.has(world, entity) {
+            reflect.set(world, entity, value)
+        } else {
+            reflect.insert(world, entity, value)
+        };
+
+        if let Err(err) = result {
+            tracing::warn!("Failed to apply component {stid} to entity {entity}: {err}");
+        }
+    }
+
+    /// Removes component as instructed by the server.
+    pub(crate) fn remove(&self, world: &mut World, entity: EntityId, stid: Stid) {
+        if let Some(component) = self.components.get(&stid) {
+            (component.remove)(world, entity);
+        }
+    }
+}
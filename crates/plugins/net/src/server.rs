@@ -0,0 +1,342 @@
+use std::net::SocketAddr;
+
+use arcana::{
+    edict::{world::World, Entities},
+    hashbrown::HashMap,
+    model::Value,
+    EntityId, Stid,
+};
+
+use crate::{
+    protocol::{ClientId, Message, Packet, PROTOCOL_VERSION},
+    replication::{NetId, Owner, Replicated, Replication},
+    transport::{Connection, NetError, Socket, TIMEOUT},
+};
+
+/// All replicated values are resent to clients every this many ticks
+/// to heal lost unreliable updates.
+const FULL_SYNC_TICKS: u64 = 60;
+
+/// Something that happened on the server since events were drained last time.
+#[derive(Debug)]
+pub enum ServerEvent {
+    Connected(ClientId),
+    Disconnected(ClientId),
+
+    /// Input sent by the client with [`NetClient::send_input`](crate::NetClient::send_input).
+    Input {
+        client: ClientId,
+        tick: u64,
+        value: Value,
+    },
+}
+
+struct RemoteClient {
+    connection: Connection,
+
+    /// Values of components last sent to the client for each entity it knows about.
+    known: HashMap<NetId, HashMap<Stid, Value>>,
+
+    /// Last input tick received from the client.
+    input_tick: u64,
+}
+
+/// Server side of networking.
+///
+/// Insert as resource to start accepting clients.
+/// Entities marked with [`Replicated`] are replicated to connected clients.
+pub struct NetServer {
+    socket: Socket,
+    clients: HashMap<ClientId, RemoteClient>,
+    addrs: HashMap<SocketAddr, ClientId>,
+    max_clients: usize,
+    next_client: u32,
+    next_net_id: u64,
+    tick: u64,
+    events: Vec<ServerEvent>,
+}
+
+impl NetServer {
+    pub fn bind(addr: SocketAddr) -> Result<Self, NetError> {
+        let socket = Socket::bind(addr)?;
+        tracing::info!("Server is listening on {}", socket.local_addr()?);
+
+        Ok(NetServer {
+            socket,
+            clients: HashMap::new(),
+            addrs: HashMap::new(),
+            max_clients: usize::MAX,
+            next_client: 0,
+            next_net_id: 0,
+            tick: 0,
+            events: Vec::new(),
+        })
+    }
+
+    /// Limits number of connected clients.
+    /// Connection attempts over the limit are refused.
+    pub fn with_max_clients(mut self, max_clients: usize) -> Self {
+        self.max_clients = max_clients;
+        self
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, NetError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Returns current server tick.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Returns ids of connected clients.
+    pub fn clients(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.clients.keys().copied()
+    }
+
+    /// Returns last input tick received from the client.
+    pub fn input_tick(&self, client: ClientId) -> Option<u64> {
+        Some(self.clients.get(&client)?.input_tick)
+    }
+
+    /// Disconnects the client.
+    pub fn disconnect(&mut self, client: ClientId) {
+        if let Some(mut remote) = self.clients.remove(&client) {
+            remote
+                .connection
+                .send_packet(&self.socket, &Packet::Disconnect);
+            self.addrs.remove(&remote.connection.addr());
+            self.events.push(ServerEvent::Disconnected(client));
+        }
+    }
+
+    /// Takes events that happened since last call.
+    pub fn drain_events(&mut self) -> impl Iterator<Item = ServerEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    fn poll(&mut self) {
+        let mut delivered = Vec::new();
+
+        while let Some((addr, packet)) = self.socket.recv() {
+            let Some(&client) = self.addrs.get(&addr) else {
+                self.handshake(addr, packet);
+                continue;
+            };
+
+            match packet {
+                // Accept was lost.
+                Packet::Connect { .. } => self.socket.send(addr, &Packet::Accept { client }),
+                Packet::Disconnect => {
+                    self.clients.remove(&client);
+                    self.addrs.remove(&addr);
+                    self.events.push(ServerEvent::Disconnected(client));
+                }
+                packet => {
+                    let remote = self.clients.get_mut(&client).unwrap();
+                    remote
+                        .connection
+                        .receive(&self.socket, packet, &mut delivered);
+
+                    for payload in delivered.drain(..) {
+                        match Message::decode(&payload) {
+                            Some(Message::Input { tick, value }) => {
+                                remote.input_tick = remote.input_tick.max(tick);
+                                self.events.push(ServerEvent::Input {
+                                    client,
+                                    tick,
+                                    value,
+                                });
+                            }
+                            Some(_) => {
+                                tracing::debug!("Unexpected message from {client}");
+                            }
+                            None => {
+                                tracing::debug!("Malformed message from {client}");
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let timed_out = self
+            .clients
+            .iter()
+            .filter(|(_, remote)| remote.connection.silence() > TIMEOUT)
+            .map(|(&client, _)| client)
+            .collect::<Vec<_>>();
+
+        for client in timed_out {
+            tracing::info!("{client} timed out");
+            self.disconnect(client);
+        }
+    }
+
+    fn handshake(&mut self, addr: SocketAddr, packet: Packet) {
+        let Packet::Connect { protocol } = packet else {
+            return;
+        };
+
+        if protocol != PROTOCOL_VERSION {
+            let reason = format!(
+                "Protocol version mismatch. Server: {PROTOCOL_VERSION}, client: {protocol}"
+            );
+            self.socket.send(addr, &Packet::Refuse { reason });
+            return;
+        }
+
+        if self.clients.len() >= self.max_clients {
+            let reason = "Server is full".to_owned();
+            self.socket.send(addr, &Packet::Refuse { reason });
+            return;
+        }
+
+        let client = ClientId(self.next_client);
+        self.next_client += 1;
+
+        let mut connection = Connection::new(addr);
+        connection.send_packet(&self.socket, &Packet::Accept { client });
+
+        self.clients.insert(
+            client,
+            RemoteClient {
+                connection,
+                known: HashMap::new(),
+                input_tick: 0,
+            },
+        );
+        self.addrs.insert(addr, client);
+        self.events.push(ServerEvent::Connected(client));
+
+        tracing::info!("{client} connected from {addr}");
+    }
+
+    fn replicate(&mut self, world: &World, replication: &Replication) {
+        let entities = world
+            .view::<(Entities, &NetId)>()
+            .with::<Replicated>()
+            .iter()
+            .map(|(e, &id)| (e.id(), id))
+            .collect::<Vec<_>>();
+
+        let full_sync = self.tick % FULL_SYNC_TICKS == 0;
+
+        for (&client, remote) in self.clients.iter_mut() {
+            let mut relevant = HashMap::new();
+            for &(entity, id) in &entities {
+                if replication.is_relevant(world, client, entity) {
+                    relevant.insert(id, entity);
+                }
+            }
+
+            // Despawn entities that were despawned or are no longer relevant.
+            remote.known.retain(|id, _| {
+                if relevant.contains_key(id) {
+                    return true;
+                }
+                let message = Message::Despawn { id: *id };
+                remote
+                    .connection
+                    .send_reliable(&self.socket, message.encode());
+                false
+            });
+
+            for (&id, &entity) in &relevant {
+                let snapshot = replication.snapshot(world, entity);
+
+                let Some(known) = remote.known.get_mut(&id) else {
+                    let owned = world
+                        .get::<&Owner>(entity)
+                        .map_or(false, |owner| owner.0 == client);
+
+                    let message = Message::Spawn {
+                        id,
+                        owned,
+                        components: snapshot
+                            .iter()
+                            .map(|(&stid, value)| (stid, value.clone()))
+                            .collect(),
+                    };
+                    remote
+                        .connection
+                        .send_reliable(&self.socket, message.encode());
+                    remote.known.insert(id, snapshot);
+                    continue;
+                };
+
+                let removed = known
+                    .keys()
+                    .filter(|stid| !snapshot.contains_key(*stid))
+                    .copied()
+                    .collect::<Vec<_>>();
+
+                if !removed.is_empty() {
+                    let message = Message::Remove {
+                        id,
+                        components: removed,
+                    };
+                    remote
+                        .connection
+                        .send_reliable(&self.socket, message.encode());
+                }
+
+                let components = snapshot
+                    .iter()
+                    .filter(|&(stid, value)| full_sync || known.get(stid) != Some(value))
+                    .map(|(&stid, value)| (stid, value.clone()))
+                    .collect::<Vec<_>>();
+
+                if !components.is_empty() {
+                    let message = Message::Update {
+                        tick: self.tick,
+                        input_tick: remote.input_tick,
+                        id,
+                        components,
+                    };
+                    remote
+                        .connection
+                        .send_unreliable(&self.socket, message.encode());
+                }
+
+                *known = snapshot;
+            }
+
+            remote.connection.update(&self.socket);
+        }
+    }
+}
+
+/// Assigns network ids to entities that were marked for replication.
+fn assign_net_ids(world: &mut World, server: &mut NetServer) {
+    let new = world
+        .view::<Entities>()
+        .with::<Replicated>()
+        .without::<NetId>()
+        .iter()
+        .map(|e| e.id())
+        .collect::<Vec<EntityId>>();
+
+    for entity in new {
+        let id = NetId(server.next_net_id);
+        server.next_net_id += 1;
+        let _ = world.insert(entity, id);
+    }
+}
+
+#[arcana::system]
+fn net_server(world: &mut World) {
+    let Some(mut server) = world.remove_resource::<NetServer>() else {
+        return;
+    };
+
+    assign_net_ids(world, &mut server);
+    server.poll();
+
+    if let Some(replication) = world.get_resource::<Replication>() {
+        server.replicate(world, &replication);
+    }
+
+    server.tick += 1;
+    world.insert_resource(server);
+}
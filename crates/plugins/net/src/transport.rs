@@ -0,0 +1,216 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    net::{SocketAddr, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use crate::protocol::Packet;
+
+/// Unacknowledged reliable payloads are resent after this interval.
+const RESEND_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Heartbeat is sent when nothing else was sent for this long.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Connection is dropped when nothing is received for this long.
+pub(crate) const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest datagram that can be received.
+const MAX_DATAGRAM: usize = 65507;
+
+#[derive(Debug, thiserror::Error)]
+pub enum NetError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("Connection refused: {reason}")]
+    Refused { reason: String },
+
+    #[error("Connection closed by peer")]
+    Closed,
+
+    #[error("Connection timed out")]
+    Timeout,
+}
+
+/// Non-blocking UDP socket that sends and receives packets.
+pub(crate) struct Socket {
+    socket: UdpSocket,
+    buffer: Box<[u8]>,
+}
+
+impl Socket {
+    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let socket = arcana::sandbox::net::bind_udp(crate::arcana_plugin::sandbox_key(), addr)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Socket {
+            socket,
+            buffer: vec![0; MAX_DATAGRAM].into_boxed_slice(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn send(&self, addr: SocketAddr, packet: &Packet) {
+        let data = bincode::serialize(packet).expect("Packet serialization cannot fail");
+
+        // Lost datagrams are handled by reliability layer.
+        if let Err(err) = self.socket.send_to(&data, addr) {
+            if err.kind() != io::ErrorKind::WouldBlock {
+                tracing::warn!("Failed to send packet to {addr}: {err}");
+            }
+        }
+    }
+
+    /// Returns next received packet.
+    /// Malformed datagrams are skipped.
+    pub fn recv(&mut self) -> Option<(SocketAddr, Packet)> {
+        loop {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((len, addr)) => match bincode::deserialize(&self.buffer[..len]) {
+                    Ok(packet) => return Some((addr, packet)),
+                    Err(err) => {
+                        tracing::debug!("Malformed packet from {addr}: {err}");
+                    }
+                },
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => return None,
+                // On some platforms ICMP errors of previous sends are reported here.
+                Err(err) if err.kind() == io::ErrorKind::ConnectionReset => {}
+                Err(err) => {
+                    tracing::warn!("Failed to receive packet: {err}");
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+struct Outgoing {
+    payload: Vec<u8>,
+    sent: Instant,
+}
+
+/// Reliability state of a connection to a single peer.
+pub(crate) struct Connection {
+    addr: SocketAddr,
+    last_received: Instant,
+    last_sent: Instant,
+
+    next_seq: u64,
+    unacked: BTreeMap<u64, Outgoing>,
+
+    /// Sequence number of the next reliable payload to deliver.
+    next_expected: u64,
+
+    /// Reliable payloads received ahead of `next_expected`.
+    early: BTreeMap<u64, Vec<u8>>,
+}
+
+impl Connection {
+    pub fn new(addr: SocketAddr) -> Self {
+        let now = Instant::now();
+        Connection {
+            addr,
+            last_received: now,
+            last_sent: now,
+            next_seq: 0,
+            unacked: BTreeMap::new(),
+            next_expected: 0,
+            early: BTreeMap::new(),
+        }
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns time since last packet was received from the peer.
+    pub fn silence(&self) -> Duration {
+        self.last_received.elapsed()
+    }
+
+    pub fn send_packet(&mut self, socket: &Socket, packet: &Packet) {
+        socket.send(self.addr, packet);
+        self.last_sent = Instant::now();
+    }
+
+    pub fn send_reliable(&mut self, socket: &Socket, payload: Vec<u8>) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+
+        self.send_packet(
+            socket,
+            &Packet::Reliable {
+                seq,
+                payload: payload.clone(),
+            },
+        );
+
+        self.unacked.insert(
+            seq,
+            Outgoing {
+                payload,
+                sent: Instant::now(),
+            },
+        );
+    }
+
+    pub fn send_unreliable(&mut self, socket: &Socket, payload: Vec<u8>) {
+        self.send_packet(socket, &Packet::Unreliable { payload });
+    }
+
+    /// Handles packet received from the peer.
+    /// Delivered payloads are pushed to `delivered` in order.
+    pub fn receive(&mut self, socket: &Socket, packet: Packet, delivered: &mut Vec<Vec<u8>>) {
+        self.last_received = Instant::now();
+
+        match packet {
+            Packet::Reliable { seq, payload } => {
+                // Acknowledge duplicates too, previous ack may have been lost.
+                self.send_packet(socket, &Packet::Ack { seq });
+
+                if seq < self.next_expected {
+                    return;
+                }
+
+                self.early.insert(seq, payload);
+                while let Some(payload) = self.early.remove(&self.next_expected) {
+                    delivered.push(payload);
+                    self.next_expected += 1;
+                }
+            }
+            Packet::Ack { seq } => {
+                self.unacked.remove(&seq);
+            }
+            Packet::Unreliable { payload } => delivered.push(payload),
+            _ => {}
+        }
+    }
+
+    /// Resends unacknowledged payloads and keeps connection alive.
+    pub fn update(&mut self, socket: &Socket) {
+        let now = Instant::now();
+
+        for (&seq, outgoing) in self.unacked.iter_mut() {
+            if now - outgoing.sent >= RESEND_INTERVAL {
+                socket.send(
+                    self.addr,
+                    &Packet::Reliable {
+                        seq,
+                        payload: outgoing.payload.clone(),
+                    },
+                );
+                outgoing.sent = now;
+                self.last_sent = now;
+            }
+        }
+
+        if now - self.last_sent >= HEARTBEAT_INTERVAL {
+            self.send_packet(socket, &Packet::Heartbeat);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! This plugin provides importer for parameter sets.
+//!
+//! Parameter sets are written in JSON and are edited in the editor's parameters tool.
+//! Importer validates them and encodes into the format [`ParamSet`] asset loads.
+//!
+//! [`ParamSet`]: arcana::assets::ParamSet
+
+use std::{fmt::Display, path::Path};
+
+use arcana::{
+    assets::{
+        import::{AssetDependencies, AssetSources, ImportError, Importer},
+        ParamSet,
+    },
+    hashbrown::HashSet,
+    ident, name, Ident, Name,
+};
+
+arcana::declare_plugin!();
+
+/// Imports JSON parameter sets.
+#[arcana::importer]
+#[derive(Default)]
+pub struct ParamSetImporter;
+
+impl ParamSetImporter {
+    pub fn new() -> Self {
+        ParamSetImporter
+    }
+}
+
+impl Importer for ParamSetImporter {
+    fn name(&self) -> Name {
+        name!(param_set)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["params"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["params"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(param_set)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        _sources: &mut dyn AssetSources,
+        _dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let text = std::fs::read_to_string(source).map_err(error_to_reason)?;
+        let set = ParamSet::from_json(&text).map_err(error_to_reason)?;
+        validate(&set)?;
+        std::fs::write(output, set.encode()).map_err(error_to_reason)
+    }
+}
+
+/// Names must be unique, otherwise only the first value would be applied.
+fn validate(set: &ParamSet) -> Result<(), ImportError> {
+    let mut names = HashSet::new();
+
+    for param in &set.params {
+        if !names.insert(param.name) {
+            return Err(ImportError::Other {
+                reason: format!("Duplicate parameter '{}'", param.name),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn error_to_reason<E: Display>(error: E) -> ImportError {
+    ImportError::Other {
+        reason: error.to_string(),
+    }
+}
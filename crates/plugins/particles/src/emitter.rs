@@ -0,0 +1,107 @@
+use arcana::{
+    color::Color,
+    curve::Curve,
+    edict::{self, Component},
+};
+
+/// Emits particles from entity position.
+#[derive(Clone, Debug, Component)]
+pub struct ParticleEmitter {
+    /// Particles emitted per second over emission cycle.
+    /// Curve time is normalized to cycle duration.
+    pub rate: Curve,
+
+    /// Duration of emission cycle in seconds.
+    pub duration: f32,
+
+    /// Restart emission cycle when it ends.
+    pub looping: bool,
+
+    /// Lifetime of particles in seconds.
+    pub lifetime: f32,
+
+    /// Initial speed of particles.
+    /// Each particle gets random speed within 25% of this value.
+    pub speed: f32,
+
+    /// Emission direction.
+    pub direction: na::Vector2<f32>,
+
+    /// Angle in radians around direction within which particles are emitted.
+    pub spread: f32,
+
+    /// Acceleration applied to particles.
+    pub gravity: na::Vector2<f32>,
+
+    /// Particle size in world units.
+    pub size: f32,
+
+    /// Multiplier of particle size over lifetime.
+    pub size_over_lifetime: Curve,
+
+    pub start_color: palette::Srgba,
+    pub end_color: palette::Srgba,
+
+    /// Blend factor between start and end colors over lifetime.
+    pub color_over_lifetime: Curve,
+
+    /// Maximum number of particles alive.
+    /// Rounded up to power of two.
+    pub max_particles: u32,
+}
+
+impl Default for ParticleEmitter {
+    fn default() -> Self {
+        ParticleEmitter {
+            rate: Curve::constant(50.0),
+            duration: 1.0,
+            looping: true,
+            lifetime: 1.0,
+            speed: 1.0,
+            direction: na::Vector2::new(0.0, 1.0),
+            spread: 0.5,
+            gravity: na::Vector2::zeros(),
+            size: 0.1,
+            size_over_lifetime: Curve::linear(1.0, 0.0),
+            start_color: palette::Srgba::new(1.0, 1.0, 1.0, 1.0),
+            end_color: palette::Srgba::new(1.0, 1.0, 1.0, 0.0),
+            color_over_lifetime: Curve::linear(0.0, 1.0),
+            max_particles: 1024,
+        }
+    }
+}
+
+impl ParticleEmitter {
+    /// Number of particles in the pool.
+    pub(crate) fn capacity(&self) -> u32 {
+        self.max_particles.clamp(64, 1 << 20).next_power_of_two()
+    }
+
+    /// Returns color and size of particle at normalized age.
+    pub(crate) fn at_age(&self, t: f32) -> (Color, f32) {
+        let f = self.color_over_lifetime.sample(t).clamp(0.0, 1.0);
+        let start = Color::from(self.start_color);
+        let end = Color::from(self.end_color);
+        let color = start.mix(&end, f);
+
+        let size = self.size * self.size_over_lifetime.sample(t).max(0.0);
+        (color, size)
+    }
+}
+
+arcana::reflect_struct!(ParticleEmitter {
+    rate,
+    duration,
+    looping,
+    lifetime,
+    speed,
+    direction,
+    spread,
+    gravity,
+    size,
+    size_over_lifetime,
+    start_color,
+    end_color,
+    color_over_lifetime,
+    max_particles,
+});
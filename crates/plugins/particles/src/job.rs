@@ -0,0 +1,506 @@
+use std::mem::size_of;
+
+use arcana::{
+    edict::{world::World, EntityId},
+    gametime::ClockStep,
+    hashbrown::HashMap,
+    mev::{self, Arguments, DeviceRepr},
+    render::current_camera,
+    work::{Exec, Image2D, Job, JobDesc, JobIdx, Planner},
+    Entities, Entity,
+};
+use camera::Camera2;
+use scene::dim2::Global;
+
+use crate::emitter::ParticleEmitter;
+
+/// Size of particle in simulation buffer, see `shaders/simulate.wgsl`.
+const PARTICLE_SIZE: usize = 32;
+
+/// Number of samples of lifetime curves.
+const LUT_SIZE: usize = 64;
+
+const WORKGROUP_SIZE: u32 = 64;
+
+#[derive(mev::Arguments)]
+struct SimulateArguments {
+    #[mev(storage, shader(compute))]
+    particles: mev::Buffer,
+    #[mev(storage, shader(compute))]
+    order: mev::Buffer,
+}
+
+#[derive(Clone, Copy, mev::DeviceRepr)]
+struct SimulateConstants {
+    origin: mev::vec2,
+    direction: mev::vec2,
+    gravity: mev::vec2,
+    speed: f32,
+    spread: f32,
+    lifetime: f32,
+    dt: f32,
+    spawn_start: u32,
+    spawn_count: u32,
+    capacity: u32,
+    seed: u32,
+    sort_k: u32,
+    sort_j: u32,
+}
+
+#[derive(mev::Arguments)]
+struct DrawArguments {
+    #[mev(storage, vertex)]
+    particles: mev::Buffer,
+    #[mev(storage, vertex)]
+    order: mev::Buffer,
+    #[mev(storage, vertex)]
+    lut: mev::Buffer,
+}
+
+#[derive(mev::DeviceRepr)]
+struct DrawConstants {
+    camera: mev::mat3,
+    lut_size: u32,
+}
+
+#[derive(mev::DeviceRepr)]
+struct LutEntry {
+    color: mev::vec4,
+    size: f32,
+}
+
+/// Emission state of one emitter.
+struct EmitterState {
+    /// Time since emission cycle start.
+    time: f32,
+
+    /// Fractional particles carried to the next frame.
+    accumulated: f32,
+
+    /// Next particle slot to spawn into.
+    cursor: u32,
+
+    capacity: u32,
+    buffers: Option<EmitterBuffers>,
+
+    // Filled by planning for execution.
+    constants: SimulateConstants,
+    lut: Vec<<LutEntry as DeviceRepr>::Repr>,
+    active: bool,
+}
+
+struct EmitterBuffers {
+    particles: mev::Buffer,
+    order: mev::Buffer,
+    lut: mev::Buffer,
+}
+
+struct Pipelines {
+    update: mev::ComputePipeline,
+    spawn: mev::ComputePipeline,
+    sort: mev::ComputePipeline,
+    draw: mev::RenderPipeline,
+}
+
+/// Per job node state.
+#[derive(Default)]
+struct Node {
+    camera: Option<DrawConstants>,
+    emitters: HashMap<EntityId, EmitterState>,
+}
+
+#[arcana::job]
+pub struct DrawParticles {
+    pipelines: Option<Pipelines>,
+    nodes: HashMap<JobIdx, Node>,
+    frame: u32,
+}
+
+impl DrawParticles {
+    pub fn desc() -> JobDesc {
+        arcana::job_desc! [
+            main: mut Image2D,
+        ]
+    }
+
+    pub fn new() -> Self {
+        DrawParticles {
+            pipelines: None,
+            nodes: HashMap::new(),
+            frame: 0,
+        }
+    }
+}
+
+impl Job for DrawParticles {
+    fn plan(&mut self, mut planner: Planner<'_>, world: &mut World) {
+        let Some(target) = planner.update::<Image2D>().copied() else {
+            return;
+        };
+
+        self.frame = self.frame.wrapping_add(1);
+
+        let node = self.nodes.entry(planner.idx()).or_default();
+        node.camera = None;
+
+        let camera = match current_camera(world) {
+            Some(entity) => world
+                .try_view_one::<(&Global, &Camera2)>(entity)
+                .ok()
+                .and_then(|camera| camera.get().map(|(g, c)| (*g, *c))),
+            None => world
+                .view::<(&Global, &Camera2)>()
+                .iter()
+                .next()
+                .map(|(g, c)| (*g, *c)),
+        };
+        let Some((global, camera)) = camera else {
+            return;
+        };
+
+        let view = camera
+            .view_to_world(&global.iso, target.extent.width(), target.extent.height())
+            .to_homogeneous();
+        let Some(view) = view.try_inverse() else {
+            return;
+        };
+
+        node.camera = Some(DrawConstants {
+            camera: view.as_ref().into(),
+            lut_size: LUT_SIZE as u32,
+        });
+
+        let dt = world.expect_resource::<ClockStep>().step.as_secs_f32();
+
+        for state in node.emitters.values_mut() {
+            state.active = false;
+        }
+
+        let emitters = world.view::<(Entities, &Global, &ParticleEmitter)>();
+        for (entity, global, emitter) in emitters.iter() {
+            let capacity = emitter.capacity();
+
+            let state = node
+                .emitters
+                .entry(entity.id())
+                .or_insert_with(|| EmitterState {
+                    time: 0.0,
+                    accumulated: 0.0,
+                    cursor: 0,
+                    capacity,
+                    buffers: None,
+                    constants: SimulateConstants {
+                        origin: mev::vec2(0.0, 0.0),
+                        direction: mev::vec2(0.0, 1.0),
+                        gravity: mev::vec2(0.0, 0.0),
+                        speed: 0.0,
+                        spread: 0.0,
+                        lifetime: 0.0,
+                        dt: 0.0,
+                        spawn_start: 0,
+                        spawn_count: 0,
+                        capacity,
+                        seed: 0,
+                        sort_k: 0,
+                        sort_j: 0,
+                    },
+                    lut: Vec::with_capacity(LUT_SIZE),
+                    active: false,
+                });
+
+            if state.capacity != capacity {
+                // Pool is recreated, particles alive are lost.
+                state.capacity = capacity;
+                state.cursor = 0;
+                state.buffers = None;
+            }
+
+            let duration = emitter.duration.max(f32::EPSILON);
+
+            let mut emitting = true;
+            state.time += dt;
+            if state.time >= duration {
+                if emitter.looping {
+                    state.time %= duration;
+                } else {
+                    state.time = duration;
+                    emitting = false;
+                }
+            }
+
+            let mut spawn_count = 0;
+            if emitting {
+                let rate = emitter.rate.sample(state.time / duration).max(0.0);
+                state.accumulated += rate * dt;
+                spawn_count = (state.accumulated as u32).min(capacity);
+                state.accumulated -= spawn_count as f32;
+            }
+
+            let spawn_start = state.cursor;
+            state.cursor = (state.cursor + spawn_count) % capacity;
+
+            let origin = global.iso.translation.vector;
+            let direction = emitter.direction.try_normalize(f32::EPSILON);
+            let direction = direction.unwrap_or(na::Vector2::y());
+
+            state.constants = SimulateConstants {
+                origin: mev::vec2(origin.x, origin.y),
+                direction: mev::vec2(direction.x, direction.y),
+                gravity: mev::vec2(emitter.gravity.x, emitter.gravity.y),
+                speed: emitter.speed,
+                spread: emitter.spread,
+                lifetime: emitter.lifetime.max(f32::EPSILON),
+                dt,
+                spawn_start,
+                spawn_count,
+                capacity,
+                seed: self.frame.wrapping_mul(0x9e37_79b9) ^ entity.id().bits() as u32,
+                sort_k: 0,
+                sort_j: 0,
+            };
+
+            state.lut.clear();
+            for i in 0..LUT_SIZE {
+                let (color, size) = emitter.at_age(i as f32 / (LUT_SIZE - 1) as f32);
+                state.lut.push(
+                    LutEntry {
+                        color: color.into(),
+                        size,
+                    }
+                    .as_repr(),
+                );
+            }
+
+            state.active = true;
+        }
+
+        // Emitters that were removed.
+        node.emitters.retain(|_, state| state.active);
+    }
+
+    fn exec(&mut self, runner: Exec<'_>, _world: &mut World) {
+        let Some(target) = runner.update::<Image2D>() else {
+            return;
+        };
+
+        let Some(node) = self.nodes.get_mut(&runner.idx()) else {
+            return;
+        };
+
+        let Some(camera) = &node.camera else {
+            return;
+        };
+
+        if node.emitters.is_empty() {
+            return;
+        }
+
+        let pipelines = self
+            .pipelines
+            .get_or_insert_with(|| create_pipelines(runner.device(), target.format()));
+
+        for state in node.emitters.values_mut() {
+            if state.buffers.is_none() {
+                state.buffers = Some(create_buffers(runner.device(), state.capacity));
+            }
+        }
+
+        let encoder = runner.new_encoder();
+
+        encoder.barrier(mev::PipelineStages::all(), mev::PipelineStages::TRANSFER);
+        {
+            let mut copy = encoder.copy();
+            for state in node.emitters.values() {
+                let buffers = state.buffers.as_ref().unwrap();
+                copy.write_buffer_slice(buffers.lut.slice(..), &state.lut);
+            }
+        }
+        encoder.barrier(mev::PipelineStages::TRANSFER, mev::PipelineStages::all());
+
+        // Simulation steps depend on results of previous ones.
+        let mut dispatch = |pipeline: &mev::ComputePipeline,
+                            state: &EmitterState,
+                            constants: &SimulateConstants,
+                            count: u32| {
+            let buffers = state.buffers.as_ref().unwrap();
+
+            let mut compute = encoder.compute();
+            compute.with_pipeline(pipeline);
+            compute.with_arguments(
+                0,
+                &SimulateArguments {
+                    particles: buffers.particles.clone(),
+                    order: buffers.order.clone(),
+                },
+            );
+            compute.with_constants(constants);
+            compute.dispatch(mev::Extent3::new(count.div_ceil(WORKGROUP_SIZE), 1, 1));
+            drop(compute);
+
+            encoder.barrier(mev::PipelineStages::all(), mev::PipelineStages::all());
+        };
+
+        for state in node.emitters.values() {
+            dispatch(&pipelines.update, state, &state.constants, state.capacity);
+
+            if state.constants.spawn_count > 0 {
+                dispatch(
+                    &pipelines.spawn,
+                    state,
+                    &state.constants,
+                    state.constants.spawn_count,
+                );
+            }
+
+            // Bitonic sort of the draw order, capacity is power of two.
+            let mut k = 2;
+            while k <= state.capacity {
+                let mut j = k / 2;
+                while j > 0 {
+                    let constants = SimulateConstants {
+                        sort_k: k,
+                        sort_j: j,
+                        ..state.constants
+                    };
+                    dispatch(&pipelines.sort, state, &constants, state.capacity);
+                    j /= 2;
+                }
+                k *= 2;
+            }
+        }
+
+        let dims = target.extent().expect_2d();
+
+        let mut render = encoder.render(mev::RenderPassDesc {
+            color_attachments: &[mev::AttachmentDesc::new(&target).load_op(mev::LoadOp::Load)],
+            ..Default::default()
+        });
+
+        render.with_pipeline(&pipelines.draw);
+        render.with_constants(camera);
+        render.with_viewport(
+            mev::Offset3::ZERO,
+            mev::Extent3::new(dims.width() as f32, dims.height() as f32, 1.0),
+        );
+        render.with_scissor(mev::Offset2::ZERO, dims);
+
+        for state in node.emitters.values() {
+            let buffers = state.buffers.as_ref().unwrap();
+
+            render.with_arguments(
+                0,
+                &DrawArguments {
+                    particles: buffers.particles.clone(),
+                    order: buffers.order.clone(),
+                    lut: buffers.lut.clone(),
+                },
+            );
+            render.draw(0..6, 0..state.capacity);
+        }
+
+        drop(render);
+    }
+}
+
+fn create_pipelines(device: &mev::Device, format: mev::PixelFormat) -> Pipelines {
+    let simulate = device
+        .new_shader_library(mev::LibraryDesc {
+            name: "particles-simulate",
+            input: mev::include_library!("shaders/simulate.wgsl" as mev::ShaderLanguage::Wgsl),
+        })
+        .unwrap();
+
+    let compute = |entry: &str| {
+        device
+            .new_compute_pipeline(mev::ComputePipelineDesc {
+                name: entry,
+                shader: mev::Shader {
+                    library: simulate.clone(),
+                    entry: entry.into(),
+                },
+                work_group_size: [WORKGROUP_SIZE, 1, 1],
+                arguments: &[SimulateArguments::LAYOUT],
+                constants: SimulateConstants::SIZE,
+            })
+            .unwrap()
+    };
+
+    let draw = device
+        .new_shader_library(mev::LibraryDesc {
+            name: "particles-draw",
+            input: mev::include_library!("shaders/draw.wgsl" as mev::ShaderLanguage::Wgsl),
+        })
+        .unwrap();
+
+    let draw = device
+        .new_render_pipeline(mev::RenderPipelineDesc {
+            name: "particles-draw",
+            vertex_shader: mev::Shader {
+                library: draw.clone(),
+                entry: "vs_main".into(),
+            },
+            vertex_attributes: vec![],
+            vertex_layouts: vec![],
+            primitive_topology: mev::PrimitiveTopology::Triangle,
+            raster: Some(mev::RasterDesc {
+                fragment_shader: Some(mev::Shader {
+                    library: draw,
+                    entry: "fs_main".into(),
+                }),
+                color_targets: vec![mev::ColorTargetDesc {
+                    format,
+                    blend: Some(mev::BlendDesc::default()),
+                }],
+                depth_stencil: None,
+                front_face: mev::FrontFace::default(),
+                culling: mev::Culling::None,
+            }),
+            arguments: &[DrawArguments::LAYOUT],
+            constants: DrawConstants::SIZE,
+        })
+        .unwrap();
+
+    Pipelines {
+        update: compute("update"),
+        spawn: compute("spawn"),
+        sort: compute("sort"),
+        draw,
+    }
+}
+
+fn create_buffers(device: &mev::Device, capacity: u32) -> EmitterBuffers {
+    // Zeroed particles are dead.
+    let particles = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: &vec![0u8; PARTICLE_SIZE * capacity as usize],
+            name: "particles",
+            usage: mev::BufferUsage::STORAGE,
+            memory: mev::Memory::Device,
+        })
+        .unwrap();
+
+    let order = (0..capacity).collect::<Vec<u32>>();
+    let order = device
+        .new_buffer_init(mev::BufferInitDesc {
+            data: arcana::bytemuck::cast_slice(&order),
+            name: "particle-order",
+            usage: mev::BufferUsage::STORAGE,
+            memory: mev::Memory::Device,
+        })
+        .unwrap();
+
+    let lut = device
+        .new_buffer(mev::BufferDesc {
+            size: size_of::<<LutEntry as DeviceRepr>::Repr>() * LUT_SIZE,
+            name: "particle-lut",
+            usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
+            memory: mev::Memory::Device,
+        })
+        .unwrap();
+
+    EmitterBuffers {
+        particles,
+        order,
+        lut,
+    }
+}
@@ -0,0 +1,17 @@
+//! This plugin simulates particles on GPU.
+//!
+//! Emitters are `ParticleEmitter` components positioned by `Global` transform.
+//! Their parameters are reflected, so they can be tweaked in ed
+//! while the game is running.
+//!
+//! `DrawParticles` job spawns, updates and sorts particles in compute shaders
+//! and then draws them as instanced quads on top of the target image.
+
+arcana::declare_plugin!([scene ..., camera ...]);
+
+mod emitter;
+mod job;
+
+pub use self::{emitter::ParticleEmitter, job::DrawParticles};
+
+arcana::reflect_component!(ParticleEmitter, Default);
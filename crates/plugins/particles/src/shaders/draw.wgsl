@@ -0,0 +1,70 @@
+struct Constants {
+    camera: mat3x3f,
+    lut_size: u32,
+}
+
+var<push_constant> pc: Constants;
+
+struct Particle {
+    pos: vec2f,
+    vel: vec2f,
+    age: f32,
+    lifetime: f32,
+    alive: u32,
+}
+
+// Color and size sampled from curves over particle lifetime.
+struct LutEntry {
+    color: vec4f,
+    size: f32,
+}
+
+@group(0) @binding(0) var<storage> particles: array<Particle>;
+@group(0) @binding(1) var<storage> order: array<u32>;
+@group(0) @binding(2) var<storage> lut: array<LutEntry>;
+
+struct VertOutput {
+    @builtin(position)
+    position: vec4f,
+    @location(0)
+    uv: vec2f,
+    @location(1)
+    color: vec4f,
+}
+
+const CORNERS = array<vec2f, 6>(
+    vec2f(0f, 0f),
+    vec2f(1f, 0f),
+    vec2f(1f, 1f),
+    vec2f(0f, 0f),
+    vec2f(1f, 1f),
+    vec2f(0f, 1f),
+);
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex: u32, @builtin(instance_index) instance: u32) -> VertOutput {
+    let p = particles[order[instance]];
+    if p.alive == 0u {
+        // Degenerate triangle is discarded.
+        return VertOutput(vec4f(0f), vec2f(0f), vec4f(0f));
+    }
+
+    let t = clamp(p.age / p.lifetime, 0f, 1f);
+    let entry = lut[min(u32(t * f32(pc.lut_size - 1u) + 0.5f), pc.lut_size - 1u)];
+
+    var corners = CORNERS;
+    let corner = corners[vertex];
+
+    let world = p.pos + (corner - vec2f(0.5f)) * entry.size;
+    let view = pc.camera * vec3f(world, 1f);
+
+    return VertOutput(vec4f(view.xy, 0f, 1f), corner, entry.color);
+}
+
+@fragment
+fn fs_main(input: VertOutput) -> @location(0) vec4f {
+    // Soft round particle.
+    let d = length(input.uv * 2f - 1f);
+    let alpha = 1f - smoothstep(0.7f, 1f, d);
+    return vec4f(input.color.rgb, input.color.a * alpha);
+}
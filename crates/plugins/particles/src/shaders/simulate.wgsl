@@ -0,0 +1,107 @@
+struct Constants {
+    origin: vec2f,
+    direction: vec2f,
+    gravity: vec2f,
+    speed: f32,
+    spread: f32,
+    lifetime: f32,
+    dt: f32,
+    spawn_start: u32,
+    spawn_count: u32,
+    capacity: u32,
+    seed: u32,
+    sort_k: u32,
+    sort_j: u32,
+}
+
+var<push_constant> pc: Constants;
+
+struct Particle {
+    pos: vec2f,
+    vel: vec2f,
+    age: f32,
+    lifetime: f32,
+    alive: u32,
+}
+
+@group(0) @binding(0) var<storage, read_write> particles: array<Particle>;
+@group(0) @binding(1) var<storage, read_write> order: array<u32>;
+
+fn hash(x: u32) -> u32 {
+    var h = x * 747796405u + 2891336453u;
+    h = ((h >> ((h >> 28u) + 4u)) ^ h) * 277803737u;
+    return (h >> 22u) ^ h;
+}
+
+fn unorm16(x: u32) -> f32 {
+    return f32(x & 0xffffu) / 65535f;
+}
+
+@compute @workgroup_size(64)
+fn update(@builtin(global_invocation_id) id: vec3u) {
+    let i = id.x;
+    if i >= pc.capacity {
+        return;
+    }
+
+    var p = particles[i];
+    if p.alive == 0u {
+        return;
+    }
+
+    p.age += pc.dt;
+    if p.age >= p.lifetime {
+        p.alive = 0u;
+    } else {
+        p.vel += pc.gravity * pc.dt;
+        p.pos += p.vel * pc.dt;
+    }
+
+    particles[i] = p;
+}
+
+@compute @workgroup_size(64)
+fn spawn(@builtin(global_invocation_id) id: vec3u) {
+    let n = id.x;
+    if n >= pc.spawn_count {
+        return;
+    }
+
+    // Spawned particles overwrite the oldest ones when pool is full.
+    let i = (pc.spawn_start + n) % pc.capacity;
+
+    let h = hash(pc.seed ^ hash(n));
+    let angle = atan2(pc.direction.y, pc.direction.x) + (unorm16(h) - 0.5f) * pc.spread;
+    let speed = pc.speed * (0.75f + 0.5f * unorm16(h >> 16u));
+
+    particles[i] = Particle(pc.origin, vec2f(cos(angle), sin(angle)) * speed, 0f, pc.lifetime, 1u);
+}
+
+// Remaining life, so older particles are drawn first and newer on top.
+fn sort_key(p: Particle) -> f32 {
+    if p.alive == 0u {
+        return 3.4e38f;
+    }
+    return p.lifetime - p.age;
+}
+
+// Single compare-exchange step of bitonic sort.
+@compute @workgroup_size(64)
+fn sort(@builtin(global_invocation_id) id: vec3u) {
+    let i = id.x;
+    let l = i ^ pc.sort_j;
+    if i >= pc.capacity || l <= i {
+        return;
+    }
+
+    let a = order[i];
+    let b = order[l];
+    let ka = sort_key(particles[a]);
+    let kb = sort_key(particles[b]);
+
+    let ascending = (i & pc.sort_k) == 0u;
+    if (ascending && ka > kb) || (!ascending && ka < kb) {
+        order[i] = b;
+        order[l] = a;
+    }
+}
@@ -20,6 +20,7 @@ use rapier::{
         BroadPhaseMultiSap, ColliderBuilder, ColliderHandle, ColliderSet, ContactPair, NarrowPhase,
     },
     math::{Isometry, Point, Vector},
+    parry::query::PointQuery,
     pipeline::{PhysicsPipeline, QueryFilter, QueryPipeline},
 };
 
@@ -255,6 +256,24 @@ impl Collider {
             id: self.id,
         }
     }
+
+    pub fn shape(&self) -> &SharedShape {
+        &self.builder.shape
+    }
+
+    /// Position of the collider relative to its entity.
+    pub fn local_position(&self) -> &Isometry<f32> {
+        &self.builder.position
+    }
+
+    /// Returns distance from the collider to the point.
+    /// Zero if the point is inside.
+    ///
+    /// `position` is the position of collider's entity.
+    pub fn distance_to_point(&self, position: &Isometry<f32>, point: &Point<f32>) -> f32 {
+        let position = position * self.builder.position;
+        self.builder.shape.distance_to_point(&position, point, true)
+    }
 }
 
 /// Initializes newly added or modified colliders.
@@ -722,6 +741,34 @@ impl PhysicsResource {
             },
         )
     }
+
+    /// Calls `f` for each collider that contains the point.
+    /// Passes collider entity and its body entity if any.
+    pub fn intersections_with_point(
+        &self,
+        point: &Point<f32>,
+        mut f: impl FnMut(EntityId, Option<EntityId>),
+    ) {
+        self.query_pipeline.intersections_with_point(
+            &self.bodies,
+            &self.colliders,
+            point,
+            QueryFilter::default(),
+            |collider| {
+                if let Some(col) = self.colliders.get(collider) {
+                    if let Some(collider) = UserData::from_bits(col.user_data).entity {
+                        let body = col
+                            .parent()
+                            .and_then(|b| self.bodies.get(b))
+                            .and_then(|b| UserData::from_bits(b.user_data).entity);
+
+                        f(collider, body);
+                    }
+                }
+                true
+            },
+        )
+    }
 }
 
 #[derive(Default)]
@@ -0,0 +1,664 @@
+use std::mem::{size_of, swap};
+
+use arcana::{
+    edict::{world::World, EntityId},
+    hashbrown::HashMap,
+    mev::{self, Arguments, DeviceRepr},
+    texture::cached_sampler,
+    tracing,
+    work::{Exec, Image2D, Job, JobDesc, JobIdx, Planner},
+    Entities, Entity,
+};
+use camera::{Camera2, Camera3};
+use mesh3d::MeshRenderer;
+use sprite::Sprite;
+
+use crate::Picker;
+
+const ID_FORMAT: mev::PixelFormat = mev::PixelFormat::R32Uint;
+const DEPTH_FORMAT: mev::PixelFormat = mev::PixelFormat::D32Float;
+
+/// Number of readback buffers.
+/// Buffer is read when its turn comes again,
+/// after waiting for the frame that recorded the copy.
+/// GPU is likely done with it by then, so waiting rarely blocks.
+const READBACK_SLOTS: usize = 3;
+
+const QUAD_RECT: u32 = 0;
+const QUAD_CIRCLE: u32 = 1;
+
+#[derive(mev::DeviceRepr)]
+struct MeshConstants {
+    mvp: mev::mat4,
+    id: u32,
+}
+
+#[derive(mev::DeviceRepr)]
+struct QuadConstants {
+    camera: mev::mat3,
+}
+
+#[derive(DeviceRepr)]
+struct QuadInstance {
+    tr: mev::mat3,
+    uv: mev::vec4,
+    id: u32,
+    kind: u32,
+}
+
+#[derive(mev::Arguments)]
+struct ShapeArguments {
+    #[mev(storage, vertex)]
+    instances: mev::Buffer,
+}
+
+#[derive(mev::Arguments)]
+struct SpriteArguments {
+    #[mev(storage, vertex)]
+    instances: mev::Buffer,
+    #[mev(fragment)]
+    sampler: mev::Sampler,
+    #[mev(fragment)]
+    texture: mev::Image,
+}
+
+struct MeshDraw {
+    vertices: mev::Buffer,
+    indices: mev::Buffer,
+    count: u32,
+    constants: MeshConstants,
+}
+
+/// Range of sprite instances that use the same image.
+struct Batch {
+    image: mev::Image,
+    first: u32,
+    count: u32,
+}
+
+/// Buffer that receives copy of the id image.
+struct Readback {
+    buffer: mev::Buffer,
+    extent: mev::Extent2,
+    entities: Vec<EntityId>,
+    camera: Option<na::Matrix3<f32>>,
+
+    /// Run of the work graph that recorded the copy.
+    frame: u64,
+
+    /// Copy was recorded and not read yet.
+    pending: bool,
+}
+
+/// Per job node state.
+#[derive(Default)]
+struct Node {
+    /// Entities drawn this frame, pixel value is index plus one.
+    entities: Vec<EntityId>,
+
+    meshes: Vec<MeshDraw>,
+
+    /// SDF shapes go first, followed by sprites.
+    quads: Vec<<QuadInstance as DeviceRepr>::Repr>,
+    shape_count: u32,
+    batches: Vec<Batch>,
+    quad_constants: Option<QuadConstants>,
+    camera: Option<na::Matrix3<f32>>,
+
+    quad_buffer: Option<mev::Buffer>,
+    ids: Option<mev::Image>,
+    depth: Option<mev::Image>,
+    readbacks: Vec<Readback>,
+    frame: usize,
+}
+
+#[arcana::job]
+pub struct DrawPickIds {
+    mesh_pipeline: Option<mev::RenderPipeline>,
+    shape_pipeline: Option<mev::RenderPipeline>,
+    sprite_pipeline: Option<mev::RenderPipeline>,
+    nodes: HashMap<JobIdx, Node>,
+
+    /// Reused for sorting sprites.
+    order: Vec<(i32, f32, usize)>,
+}
+
+impl DrawPickIds {
+    pub fn desc() -> JobDesc {
+        arcana::job_desc! [
+            main: mut Image2D,
+        ]
+    }
+
+    pub fn new() -> Self {
+        DrawPickIds {
+            mesh_pipeline: None,
+            shape_pipeline: None,
+            sprite_pipeline: None,
+            nodes: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+}
+
+impl Job for DrawPickIds {
+    fn plan(&mut self, mut planner: Planner<'_>, world: &mut World) {
+        let Some(target) = planner.update::<Image2D>().copied() else {
+            return;
+        };
+
+        let node = self.nodes.entry(planner.idx()).or_default();
+        node.entities.clear();
+        node.meshes.clear();
+        node.quads.clear();
+        node.shape_count = 0;
+        node.batches.clear();
+        node.quad_constants = None;
+        node.camera = None;
+
+        let ratio = target.extent.width() as f32 / target.extent.height() as f32;
+
+        let cameras = world.view::<(&scene::dim3::Global, &Camera3)>();
+        if let Some((camera_global, camera)) = cameras.iter().next() {
+            if let Some(view) = camera_global.iso.to_homogeneous().try_inverse() {
+                // Maps depth from [-1, 1] to [0, 1] range.
+                #[rustfmt::skip]
+                let depth_correction = na::Matrix4::new(
+                    1.0, 0.0, 0.0, 0.0,
+                    0.0, 1.0, 0.0, 0.0,
+                    0.0, 0.0, 0.5, 0.5,
+                    0.0, 0.0, 0.0, 1.0,
+                );
+
+                let view_proj = depth_correction * camera.projection(ratio).to_homogeneous() * view;
+
+                let renderers = world.view::<(Entities, &scene::dim3::Global, &MeshRenderer)>();
+                for (entity, global, renderer) in renderers.iter() {
+                    let Some(mesh) = renderer.mesh.get() else {
+                        continue;
+                    };
+
+                    node.entities.push(entity.id());
+                    let id = node.entities.len() as u32;
+                    let mvp = view_proj * global.iso.to_homogeneous();
+
+                    for primitive in mesh.primitives.iter() {
+                        node.meshes.push(MeshDraw {
+                            vertices: primitive.vertices.clone(),
+                            indices: primitive.indices.clone(),
+                            count: primitive.count,
+                            constants: MeshConstants {
+                                mvp: mvp.as_ref().into(),
+                                id,
+                            },
+                        });
+                    }
+                }
+            }
+        }
+
+        let cameras = world.view::<(&scene::dim2::Global, &Camera2)>();
+        let Some((global, camera)) = cameras.iter().next() else {
+            return;
+        };
+
+        let view = camera
+            .view_to_world(&global.iso, target.extent.width(), target.extent.height())
+            .to_homogeneous();
+        let Some(view) = view.try_inverse() else {
+            return;
+        };
+        node.quad_constants = Some(QuadConstants {
+            camera: view.as_ref().into(),
+        });
+        node.camera = Some(view);
+
+        let shapes = world.view::<(Entities, &scene::dim2::Global, &sdf::Shape)>();
+        for (entity, global, shape) in shapes.iter() {
+            let (size, kind) = match shape.kind {
+                sdf::ShapeKind::Circle { radius } => {
+                    (na::Vector2::new(radius * 2.0, radius * 2.0), QUAD_CIRCLE)
+                }
+                sdf::ShapeKind::Rect { width, height } => {
+                    (na::Vector2::new(width, height), QUAD_RECT)
+                }
+            };
+
+            node.entities.push(entity.id());
+            node.quads.push(
+                QuadInstance {
+                    tr: quad_transform(
+                        global.iso.to_homogeneous() * shape.transform.matrix(),
+                        size,
+                    )
+                    .as_ref()
+                    .into(),
+                    uv: mev::vec4(0.0, 0.0, 1.0, 1.0),
+                    id: node.entities.len() as u32,
+                    kind,
+                }
+                .as_repr(),
+            );
+        }
+        node.shape_count = node.quads.len() as u32;
+
+        let sprites = world.view::<(Entities, &scene::dim2::Global, &Sprite)>();
+        let sprites = sprites.iter().collect::<Vec<_>>();
+
+        // Same order as `DrawSprites`, so the topmost sprite wins.
+        self.order.clear();
+        self.order.extend(
+            sprites
+                .iter()
+                .enumerate()
+                .map(|(idx, (_, _, sprite))| (sprite.layer, sprite.z, idx)),
+        );
+        self.order
+            .sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+        for &(_, _, idx) in &self.order {
+            let (entity, global, sprite) = sprites[idx];
+
+            let [mut u0, mut v0, mut u1, mut v1] = sprite.uv;
+            if sprite.flip_x {
+                swap(&mut u0, &mut u1);
+            }
+            if sprite.flip_y {
+                swap(&mut v0, &mut v1);
+            }
+
+            node.entities.push(entity.id());
+            node.quads.push(
+                QuadInstance {
+                    tr: quad_transform(global.iso.to_homogeneous(), sprite.size)
+                        .as_ref()
+                        .into(),
+                    uv: mev::vec4(u0, v0, u1, v1),
+                    id: node.entities.len() as u32,
+                    kind: QUAD_RECT,
+                }
+                .as_repr(),
+            );
+
+            let instance = node.quads.len() as u32 - 1;
+            match node.batches.last_mut() {
+                Some(batch) if batch.image == sprite.image => batch.count += 1,
+                _ => node.batches.push(Batch {
+                    image: sprite.image.clone(),
+                    first: instance,
+                    count: 1,
+                }),
+            }
+        }
+    }
+
+    fn exec(&mut self, runner: Exec<'_>, world: &mut World) {
+        let Some(target) = runner.update::<Image2D>() else {
+            return;
+        };
+
+        let Some(node) = self.nodes.get_mut(&runner.idx()) else {
+            return;
+        };
+
+        let device = runner.device();
+        let dims = target.extent().expect_2d();
+
+        let ids = match &mut node.ids {
+            Some(ids) if ids.extent().expect_2d() == dims => ids.clone(),
+            slot => slot
+                .insert(
+                    device
+                        .new_image(mev::ImageDesc {
+                            extent: dims.into(),
+                            format: ID_FORMAT,
+                            usage: mev::ImageUsage::TARGET | mev::ImageUsage::TRANSFER_SRC,
+                            layers: 1,
+                            levels: 1,
+                            name: "pick-ids",
+                        })
+                        .unwrap(),
+                )
+                .clone(),
+        };
+
+        let depth = match &mut node.depth {
+            Some(depth) if depth.extent().expect_2d() == dims => depth.clone(),
+            slot => slot
+                .insert(
+                    device
+                        .new_image(mev::ImageDesc {
+                            extent: dims.into(),
+                            format: DEPTH_FORMAT,
+                            usage: mev::ImageUsage::TARGET,
+                            layers: 1,
+                            levels: 1,
+                            name: "pick-depth",
+                        })
+                        .unwrap(),
+                )
+                .clone(),
+        };
+
+        // Take result of the copy recorded `READBACK_SLOTS` frames ago.
+        node.frame = node.frame.wrapping_add(1);
+        let slot = node.frame % READBACK_SLOTS;
+        if let Some(readback) = node.readbacks.get_mut(slot) {
+            if readback.pending {
+                // Buffer is neither read nor reused until the copy completes.
+                if let Err(err) = runner.wait_frame(readback.frame) {
+                    tracing::error!("Failed to wait for pick ids readback: {err:?}");
+                    return;
+                }
+                readback.pending = false;
+
+                let size = readback.extent.width() as usize * readback.extent.height() as usize * 4;
+                let mut bytes = vec![0; size];
+                unsafe {
+                    readback.buffer.read_unchecked(0, &mut bytes);
+                }
+
+                if let Some(mut picker) = world.get_resource_mut::<Picker>() {
+                    picker.update(
+                        readback.extent,
+                        &bytes,
+                        &mut readback.entities,
+                        readback.camera,
+                    );
+                }
+            }
+        }
+
+        let mesh_pipeline = self
+            .mesh_pipeline
+            .get_or_insert_with(|| create_mesh_pipeline(device));
+        let shape_pipeline = self
+            .shape_pipeline
+            .get_or_insert_with(|| create_quad_pipeline(device, false));
+        let sprite_pipeline = self
+            .sprite_pipeline
+            .get_or_insert_with(|| create_quad_pipeline(device, true));
+
+        // Transparent texels are not pickable, so sampling must match drawing.
+        let sampler = match cached_sampler(
+            world,
+            device,
+            mev::SamplerDesc {
+                min_filter: mev::Filter::Nearest,
+                mag_filter: mev::Filter::Nearest,
+                address_mode: [mev::AddressMode::ClampToEdge; 3],
+                ..mev::SamplerDesc::new()
+            },
+        ) {
+            Ok(sampler) => sampler,
+            Err(err) => {
+                tracing::error!("Failed to create picking sampler: {err:?}");
+                return;
+            }
+        };
+
+        let encoder = runner.new_encoder();
+
+        if !node.quads.is_empty() {
+            let size = size_of::<<QuadInstance as DeviceRepr>::Repr>() * node.quads.len();
+            let buffer = match &mut node.quad_buffer {
+                Some(buffer) if buffer.size() >= size => buffer,
+                slot => slot.insert(
+                    device
+                        .new_buffer(mev::BufferDesc {
+                            size: size.next_power_of_two(),
+                            name: "pick-quads",
+                            usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
+                            memory: mev::Memory::Shared,
+                        })
+                        .unwrap(),
+                ),
+            };
+
+            encoder.barrier(
+                mev::PipelineStages::VERTEX_SHADER,
+                mev::PipelineStages::TRANSFER,
+            );
+            encoder
+                .copy()
+                .write_buffer_slice(buffer.slice(..), &node.quads);
+            encoder.barrier(
+                mev::PipelineStages::TRANSFER,
+                mev::PipelineStages::VERTEX_SHADER,
+            );
+        }
+
+        encoder.init_image(
+            mev::PipelineStages::all(),
+            mev::PipelineStages::FRAGMENT_SHADER,
+            &ids,
+        );
+        encoder.init_image(
+            mev::PipelineStages::all(),
+            mev::PipelineStages::FRAGMENT_SHADER,
+            &depth,
+        );
+
+        let mut render = encoder.render(mev::RenderPassDesc {
+            color_attachments: &[
+                mev::AttachmentDesc::new(&ids).clear(mev::ClearColor(0.0, 0.0, 0.0, 0.0))
+            ],
+            depth_stencil_attachment: Some(mev::AttachmentDesc::new(&depth).clear(
+                mev::ClearDepthStencil {
+                    depth: 1.0,
+                    stencil: 0,
+                },
+            )),
+            ..Default::default()
+        });
+
+        render.with_viewport(
+            mev::Offset3::ZERO,
+            mev::Extent3::new(dims.width() as f32, dims.height() as f32, 1.0),
+        );
+        render.with_scissor(mev::Offset2::ZERO, dims);
+
+        render.with_pipeline(mesh_pipeline);
+        for draw in &node.meshes {
+            render.with_constants(&draw.constants);
+            render.bind_vertex_buffers(0, &[draw.vertices.slice(..)]);
+            render.bind_index_buffer(draw.indices.slice(..));
+            render.draw_indexed(0, 0..draw.count, 0..1);
+        }
+
+        // 2D entities are drawn over meshes, as they are in the usual render graph.
+        if let (Some(constants), Some(buffer)) = (&node.quad_constants, &node.quad_buffer) {
+            if node.shape_count > 0 {
+                render.with_pipeline(shape_pipeline);
+                render.with_constants(constants);
+                render.with_arguments(
+                    0,
+                    &ShapeArguments {
+                        instances: buffer.clone(),
+                    },
+                );
+                render.draw(0..6, 0..node.shape_count);
+            }
+
+            if !node.batches.is_empty() {
+                render.with_pipeline(sprite_pipeline);
+                render.with_constants(constants);
+                for batch in &node.batches {
+                    render.with_arguments(
+                        0,
+                        &SpriteArguments {
+                            instances: buffer.clone(),
+                            sampler: sampler.clone(),
+                            texture: batch.image.clone(),
+                        },
+                    );
+                    render.draw(0..6, batch.first..batch.first + batch.count);
+                }
+            }
+        }
+
+        drop(render);
+
+        let bytes_per_line = 4 * dims.width() as usize;
+        let size = bytes_per_line * dims.height() as usize;
+        if size == 0 {
+            return;
+        }
+
+        if node.readbacks.len() <= slot {
+            node.readbacks.resize_with(READBACK_SLOTS, || Readback {
+                buffer: device
+                    .new_buffer(mev::BufferDesc {
+                        size,
+                        name: "pick-readback",
+                        usage: mev::BufferUsage::TRANSFER_DST,
+                        memory: mev::Memory::Download,
+                    })
+                    .unwrap(),
+                extent: dims,
+                entities: Vec::new(),
+                camera: None,
+                frame: 0,
+                pending: false,
+            });
+        }
+
+        let readback = &mut node.readbacks[slot];
+        if readback.buffer.size() < size {
+            readback.buffer = device
+                .new_buffer(mev::BufferDesc {
+                    size,
+                    name: "pick-readback",
+                    usage: mev::BufferUsage::TRANSFER_DST,
+                    memory: mev::Memory::Download,
+                })
+                .unwrap();
+        }
+
+        encoder.barrier(mev::PipelineStages::all(), mev::PipelineStages::TRANSFER);
+        encoder.copy().copy_image_to_buffer(
+            &ids,
+            mev::Offset3::ZERO,
+            dims.to_3d(),
+            0..1,
+            0,
+            &readback.buffer,
+            0,
+            bytes_per_line,
+            0,
+        );
+
+        readback.extent = dims;
+        readback.camera = node.camera;
+        readback.frame = runner.frame();
+        readback.pending = true;
+        swap(&mut readback.entities, &mut node.entities);
+    }
+}
+
+/// Returns transform of unit quad centered at origin into a quad of the size.
+fn quad_transform(tr: na::Matrix3<f32>, size: na::Vector2<f32>) -> na::Matrix3<f32> {
+    tr * na::Matrix3::from_diagonal(&na::Vector3::new(size.x, size.y, 1.0))
+}
+
+fn create_mesh_pipeline(device: &mev::Device) -> mev::RenderPipeline {
+    let library = device
+        .new_shader_library(mev::LibraryDesc {
+            name: "pick-meshes",
+            input: mev::include_library!("shaders/mesh.wgsl" as mev::ShaderLanguage::Wgsl),
+        })
+        .unwrap();
+
+    device
+        .new_render_pipeline(mev::RenderPipelineDesc {
+            name: "pick-meshes",
+            vertex_shader: mev::Shader {
+                library: library.clone(),
+                entry: "vs_mesh".into(),
+            },
+            // Only position is needed.
+            vertex_attributes: vec![mev::VertexAttributeDesc {
+                format: mev::VertexFormat::Float32x3,
+                offset: 0,
+                buffer_index: 0,
+            }],
+            vertex_layouts: vec![mev::VertexLayoutDesc {
+                stride: size_of::<arcana::assets::mesh::Vertex>() as u32,
+                step_mode: mev::VertexStepMode::Vertex,
+            }],
+            primitive_topology: mev::PrimitiveTopology::Triangle,
+            raster: Some(mev::RasterDesc {
+                fragment_shader: Some(mev::Shader {
+                    library: library.clone(),
+                    entry: "fs_mesh".into(),
+                }),
+                color_targets: vec![mev::ColorTargetDesc {
+                    format: ID_FORMAT,
+                    blend: None,
+                }],
+                depth_stencil: Some(mev::DepthStencilDesc {
+                    format: DEPTH_FORMAT,
+                    write_enabled: true,
+                    compare: mev::CompareFunction::Less,
+                }),
+                front_face: mev::FrontFace::CounterClockwise,
+                // Back faces are pickable for double sided materials.
+                culling: mev::Culling::None,
+            }),
+            arguments: &[],
+            constants: MeshConstants::SIZE,
+        })
+        .unwrap()
+}
+
+/// Creates pipeline for SDF shapes or textured sprites.
+fn create_quad_pipeline(device: &mev::Device, textured: bool) -> mev::RenderPipeline {
+    let library = device
+        .new_shader_library(mev::LibraryDesc {
+            name: "pick-quads",
+            input: mev::include_library!("shaders/quad.wgsl" as mev::ShaderLanguage::Wgsl),
+        })
+        .unwrap();
+
+    let (name, fragment) = match textured {
+        false => ("pick-shapes", "fs_shape"),
+        true => ("pick-sprites", "fs_sprite"),
+    };
+
+    device
+        .new_render_pipeline(mev::RenderPipelineDesc {
+            name,
+            vertex_shader: mev::Shader {
+                library: library.clone(),
+                entry: "vs_quad".into(),
+            },
+            vertex_attributes: vec![],
+            vertex_layouts: vec![],
+            primitive_topology: mev::PrimitiveTopology::Triangle,
+            raster: Some(mev::RasterDesc {
+                fragment_shader: Some(mev::Shader {
+                    library: library.clone(),
+                    entry: fragment.into(),
+                }),
+                color_targets: vec![mev::ColorTargetDesc {
+                    format: ID_FORMAT,
+                    blend: None,
+                }],
+                // Later quads overwrite earlier ones and meshes.
+                depth_stencil: Some(mev::DepthStencilDesc {
+                    format: DEPTH_FORMAT,
+                    write_enabled: false,
+                    compare: mev::CompareFunction::Always,
+                }),
+                front_face: mev::FrontFace::default(),
+                culling: mev::Culling::None,
+            }),
+            arguments: if textured {
+                &[SpriteArguments::LAYOUT]
+            } else {
+                &[ShapeArguments::LAYOUT]
+            },
+            constants: QuadConstants::SIZE,
+        })
+        .unwrap()
+}
@@ -0,0 +1,116 @@
+//! This plugin picks entities under the cursor on GPU.
+//!
+//! `DrawPickIds` job renders ids of entities with `sdf::Shape`, `Sprite`
+//! and `MeshRenderer` components into an `R32Uint` image
+//! and copies it back to host memory.
+//! It passes its target through unchanged, so it can be inserted
+//! anywhere in the render graph after the node that sizes the view.
+//!
+//! Readback is asynchronous, so `Picker` answers with data of a frame
+//! rendered a few frames ago. This is unnoticeable for hover and clicks,
+//! and keeps rendering from stalling on GPU.
+//!
+//! Plugin also provides `GizmoPicker`, so ed selects exactly what is drawn
+//! under the cursor instead of the entity with closest origin.
+
+use arcana::{
+    edict::{world::World, EntityId},
+    gizmo::GizmoPicker,
+    mev,
+};
+
+arcana::declare_plugin!([scene ..., camera ..., sdf ..., sprite ..., mesh3d ...]);
+
+mod job;
+
+pub use self::job::DrawPickIds;
+
+/// Resource with ids of entities from the last completed readback.
+pub struct Picker {
+    extent: mev::Extent2,
+
+    /// Pixel values, row by row from the top.
+    /// Zero is background, other values are indices into `entities` plus one.
+    ids: Vec<u32>,
+    entities: Vec<EntityId>,
+
+    /// Transform from 2D world space into normalized view coordinates
+    /// used to draw 2D entities.
+    camera: Option<na::Matrix3<f32>>,
+}
+
+impl Picker {
+    pub fn new() -> Self {
+        Picker {
+            extent: mev::Extent2::ZERO,
+            ids: Vec::new(),
+            entities: Vec::new(),
+            camera: None,
+        }
+    }
+
+    /// Returns extent of the view picking data was rendered for.
+    pub fn extent(&self) -> mev::Extent2 {
+        self.extent
+    }
+
+    /// Returns entity drawn at the pixel of the view.
+    ///
+    /// Coordinates are in pixels from top-left corner,
+    /// the same as cursor position in view input events.
+    /// Returned entity may have been despawned since the frame was rendered.
+    pub fn pick(&self, x: f32, y: f32) -> Option<EntityId> {
+        if x < 0.0 || y < 0.0 {
+            return None;
+        }
+
+        let (x, y) = (x as u32, y as u32);
+        if x >= self.extent.width() || y >= self.extent.height() {
+            return None;
+        }
+
+        let idx = y as usize * self.extent.width() as usize + x as usize;
+        let id = *self.ids.get(idx)?;
+        let entity = id.checked_sub(1)?;
+        self.entities.get(entity as usize).copied()
+    }
+
+    /// Returns entity drawn at the point of 2D world.
+    ///
+    /// Uses camera 2D entities were drawn with.
+    pub fn pick_world(&self, point: na::Point2<f32>) -> Option<EntityId> {
+        let camera = self.camera?;
+        let ndc = camera.transform_point(&point);
+
+        let x = (ndc.x + 1.0) * 0.5 * self.extent.width() as f32;
+        let y = (1.0 - ndc.y) * 0.5 * self.extent.height() as f32;
+        self.pick(x, y)
+    }
+
+    /// Replaces picking data with completed readback.
+    fn update(
+        &mut self,
+        extent: mev::Extent2,
+        bytes: &[u8],
+        entities: &mut Vec<EntityId>,
+        camera: Option<na::Matrix3<f32>>,
+    ) {
+        self.extent = extent;
+        self.ids.clear();
+        self.ids.extend(
+            bytes
+                .chunks_exact(4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        );
+        std::mem::swap(&mut self.entities, entities);
+        self.camera = camera;
+    }
+}
+
+#[arcana::init]
+fn init_picking(world: &mut World) {
+    world.insert_resource(Picker::new());
+    world.insert_resource(GizmoPicker {
+        pick: |world, point| world.get_resource::<Picker>()?.pick_world(point),
+    });
+}
@@ -0,0 +1,16 @@
+struct MeshConstants {
+    mvp: mat4x4f,
+    id: u32,
+}
+
+var<push_constant> pc: MeshConstants;
+
+@vertex
+fn vs_mesh(@location(0) position: vec3f) -> @builtin(position) vec4f {
+    return pc.mvp * vec4f(position, 1f);
+}
+
+@fragment
+fn fs_mesh() -> @location(0) u32 {
+    return pc.id;
+}
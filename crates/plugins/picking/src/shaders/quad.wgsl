@@ -0,0 +1,74 @@
+const QUAD_CIRCLE: u32 = 1u;
+
+struct QuadConstants {
+    camera: mat3x3f,
+}
+
+var<push_constant> pc: QuadConstants;
+
+struct Quad {
+    tr: mat3x3f,
+    uv: vec4f,
+    id: u32,
+    kind: u32,
+}
+
+@group(0) @binding(0) var<storage> quads: array<Quad>;
+@group(0) @binding(1) var s: sampler;
+@group(0) @binding(2) var t: texture_2d<f32>;
+
+struct QuadOutput {
+    @builtin(position)
+    position: vec4f,
+    // Position within the quad, from -0.5 to 0.5.
+    @location(0)
+    local: vec2f,
+    @location(1)
+    uv: vec2f,
+    @location(2) @interpolate(flat)
+    id: u32,
+    @location(3) @interpolate(flat)
+    kind: u32,
+}
+
+// Two triangles of unit quad.
+const CORNERS = array<vec2f, 6>(
+    vec2f(0f, 0f),
+    vec2f(1f, 0f),
+    vec2f(1f, 1f),
+    vec2f(0f, 0f),
+    vec2f(1f, 1f),
+    vec2f(0f, 1f),
+);
+
+@vertex
+fn vs_quad(@builtin(vertex_index) vertex: u32, @builtin(instance_index) instance: u32) -> QuadOutput {
+    var corners = CORNERS;
+    let corner = corners[vertex];
+    let quad = quads[instance];
+
+    let local = corner - vec2f(0.5f);
+    let view = pc.camera * quad.tr * vec3f(local, 1f);
+
+    // Texture rows go down while world Y goes up.
+    let uv = mix(quad.uv.xy, quad.uv.zw, vec2f(corner.x, 1f - corner.y));
+
+    return QuadOutput(vec4f(view.xy, 0f, 1f), local, uv, quad.id, quad.kind);
+}
+
+@fragment
+fn fs_shape(input: QuadOutput) -> @location(0) u32 {
+    if input.kind == QUAD_CIRCLE && length(input.local) > 0.5f {
+        discard;
+    }
+    return input.id;
+}
+
+// Transparent texels let entities below to be picked.
+@fragment
+fn fs_sprite(input: QuadOutput) -> @location(0) u32 {
+    if textureSample(t, s, input.uv).a < 0.5f {
+        discard;
+    }
+    return input.id;
+}
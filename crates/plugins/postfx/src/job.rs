@@ -0,0 +1,422 @@
+use std::task::Poll;
+
+use arcana::{
+    assets::{AssetId, Assets},
+    edict::world::World,
+    hashbrown::HashMap,
+    mev::{self, Arguments, DeviceRepr},
+    model::{Model, Value},
+    name,
+    texture::{cached_sampler, Texture},
+    work::{Exec, Image2D, Image2DInfo, Job, JobDesc, JobIdx, Planner},
+};
+
+/// Maximum number of bloom downsample levels.
+const BLOOM_LEVELS: u32 = 6;
+
+#[derive(mev::Arguments)]
+struct PostFxArguments {
+    #[mev(fragment)]
+    sampler: mev::Sampler,
+    #[mev(fragment)]
+    source: mev::Image,
+    #[mev(fragment)]
+    extra: mev::Image,
+    #[mev(fragment)]
+    lut: mev::Image,
+}
+
+#[derive(Clone, Copy, mev::DeviceRepr)]
+struct PostFxConstants {
+    texel: mev::vec2,
+    threshold: f32,
+    bloom_intensity: f32,
+    exposure: f32,
+    tonemapping: u32,
+    vignette: f32,
+    aberration: f32,
+    lut_size: f32,
+}
+
+struct Pipelines {
+    format: mev::PixelFormat,
+    prefilter: mev::RenderPipeline,
+    downsample: mev::RenderPipeline,
+    upsample: mev::RenderPipeline,
+    composite: mev::RenderPipeline,
+}
+
+/// Bloom mip chain of single job node.
+struct Bloom {
+    extent: mev::Extent2,
+    format: mev::PixelFormat,
+
+    /// Each level is half the size of the previous one,
+    /// first is half the size of the source.
+    down: Vec<mev::Image>,
+
+    /// Upsampled levels accumulating lower ones.
+    /// One less than `down`, as the lowest level is used as is.
+    up: Vec<mev::Image>,
+}
+
+/// Post-processing stack.
+///
+/// Reads `src` image and writes processed image to `main`.
+#[arcana::job]
+pub struct PostFx {
+    pipelines: Option<Pipelines>,
+    bloom: HashMap<JobIdx, Bloom>,
+}
+
+impl PostFx {
+    pub fn desc() -> JobDesc {
+        arcana::job_desc! [
+            bloom_threshold: in Model::Float,
+            bloom_intensity: in Model::Float,
+            exposure: in Model::Float,
+            tonemapping: in Model::Enum(vec![
+                (name!(none), Some(Model::Unit)),
+                (name!(reinhard), Some(Model::Unit)),
+                (name!(aces), Some(Model::Unit)),
+            ]),
+            lut: in Model::Option(Some(Box::new(Model::Asset))),
+            chromatic_aberration: in Model::Float,
+            vignette: in Model::Float,
+            src: Image2D,
+            main: +Image2D,
+        ]
+    }
+
+    pub fn new() -> Self {
+        PostFx {
+            pipelines: None,
+            bloom: HashMap::new(),
+        }
+    }
+}
+
+impl Job for PostFx {
+    fn plan(&mut self, mut planner: Planner<'_>, _world: &mut World) {
+        let Some(main) = planner.create::<Image2D>().copied() else {
+            return;
+        };
+
+        planner.read::<Image2D>(Image2DInfo {
+            usage: mev::ImageUsage::SAMPLED,
+            ..main
+        });
+    }
+
+    fn exec(&mut self, runner: Exec<'_>, world: &mut World) {
+        let Some(main) = runner.create::<Image2D>() else {
+            return;
+        };
+
+        let Some(src) = runner.read::<Image2D>() else {
+            return;
+        };
+
+        let lut = match runner.param("lut") {
+            Value::Option(Some(lut)) => match **lut {
+                Value::Asset(id) => load_lut(world, id),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let mut constants = PostFxConstants {
+            texel: mev::vec2(0.0, 0.0),
+            threshold: float_param(&runner, "bloom_threshold"),
+            bloom_intensity: float_param(&runner, "bloom_intensity"),
+            exposure: float_param(&runner, "exposure"),
+            tonemapping: match runner.param("tonemapping") {
+                Value::Enum(tonemapping, _) => match tonemapping.as_str() {
+                    "reinhard" => 1,
+                    "aces" => 2,
+                    _ => 0,
+                },
+                _ => 0,
+            },
+            vignette: float_param(&runner, "vignette").clamp(0.0, 1.0),
+            aberration: float_param(&runner, "chromatic_aberration"),
+            lut_size: match &lut {
+                Some(lut) => lut.extent().expect_2d().height() as f32,
+                None => 0.0,
+            },
+        };
+
+        let device = runner.device();
+        let format = main.format();
+
+        let pipelines = match &mut self.pipelines {
+            Some(pipelines) if pipelines.format == format => pipelines,
+            slot => slot.insert(create_pipelines(device, format)),
+        };
+
+        let sampler = match cached_sampler(
+            world,
+            device,
+            mev::SamplerDesc {
+                min_filter: mev::Filter::Linear,
+                mag_filter: mev::Filter::Linear,
+                address_mode: [mev::AddressMode::ClampToEdge; 3],
+                ..mev::SamplerDesc::new()
+            },
+        ) {
+            Ok(sampler) => sampler,
+            Err(err) => {
+                arcana::tracing::error!("Failed to create postfx sampler: {err:?}");
+                return;
+            }
+        };
+
+        let extent = src.extent().expect_2d();
+
+        // Unused images are bound to the source.
+        let arguments = |source: &mev::Image, extra: Option<&mev::Image>| PostFxArguments {
+            sampler: sampler.clone(),
+            source: source.clone(),
+            extra: extra.unwrap_or(&src.0).clone(),
+            lut: lut.as_ref().unwrap_or(&src.0).clone(),
+        };
+
+        let encoder = runner.new_encoder();
+
+        let mut bloom_result = None;
+        if constants.bloom_intensity > 0.0 {
+            let stale = match self.bloom.get(&runner.idx()) {
+                Some(bloom) => bloom.extent != extent || bloom.format != format,
+                None => true,
+            };
+            if stale {
+                let bloom = create_bloom(device, extent, format);
+                self.bloom.insert(runner.idx(), bloom);
+            }
+            let bloom = &self.bloom[&runner.idx()];
+
+            constants.texel = texel(&src);
+            pass(
+                encoder,
+                &pipelines.prefilter,
+                &bloom.down[0],
+                &arguments(&src, None),
+                &constants,
+            );
+
+            for i in 1..bloom.down.len() {
+                constants.texel = texel(&bloom.down[i - 1]);
+                pass(
+                    encoder,
+                    &pipelines.downsample,
+                    &bloom.down[i],
+                    &arguments(&bloom.down[i - 1], None),
+                    &constants,
+                );
+            }
+
+            for i in (0..bloom.up.len()).rev() {
+                let lower = bloom.up.get(i + 1).unwrap_or(&bloom.down[i + 1]);
+                constants.texel = texel(lower);
+                pass(
+                    encoder,
+                    &pipelines.upsample,
+                    &bloom.up[i],
+                    &arguments(lower, Some(&bloom.down[i])),
+                    &constants,
+                );
+            }
+
+            bloom_result = Some(bloom.up.first().unwrap_or(&bloom.down[0]).clone());
+        } else {
+            // Free bloom chain when bloom is disabled.
+            self.bloom.remove(&runner.idx());
+        }
+
+        constants.texel = texel(&src);
+        pass(
+            encoder,
+            &pipelines.composite,
+            &main,
+            &arguments(&src, bloom_result.as_ref()),
+            &constants,
+        );
+    }
+}
+
+fn float_param(runner: &Exec<'_>, name: &str) -> f32 {
+    match *runner.param(name) {
+        Value::Float(value) => value as f32,
+        Value::Int(value) => value as f32,
+        _ => 0.0,
+    }
+}
+
+/// Returns LUT image if the asset is loaded.
+fn load_lut(world: &World, id: AssetId) -> Option<mev::Image> {
+    let assets = world.get_resource::<Assets>()?;
+
+    match assets.get::<Texture>(id) {
+        Poll::Ready(Ok(texture)) => Some(texture.image),
+        Poll::Ready(Err(err)) => {
+            arcana::tracing::warn!("Failed to load color LUT {id}: {err}");
+            None
+        }
+        Poll::Pending => None,
+    }
+}
+
+fn texel(image: &mev::Image) -> mev::vec2 {
+    let extent = image.extent().expect_2d();
+    mev::vec2(1.0 / extent.width() as f32, 1.0 / extent.height() as f32)
+}
+
+/// Draws full-screen triangle into the target.
+fn pass(
+    encoder: &mut mev::CommandEncoder,
+    pipeline: &mev::RenderPipeline,
+    target: &mev::Image,
+    arguments: &PostFxArguments,
+    constants: &PostFxConstants,
+) {
+    encoder.init_image(
+        mev::PipelineStages::all(),
+        mev::PipelineStages::FRAGMENT_SHADER,
+        target,
+    );
+
+    let mut render = encoder.render(mev::RenderPassDesc {
+        color_attachments: &[
+            mev::AttachmentDesc::new(target).clear(mev::ClearColor(0.0, 0.0, 0.0, 0.0))
+        ],
+        ..Default::default()
+    });
+
+    let dims = target.extent().expect_2d();
+
+    render.with_pipeline(pipeline);
+    render.with_arguments(0, arguments);
+    render.with_constants(constants);
+    render.with_viewport(
+        mev::Offset3::ZERO,
+        mev::Extent3::new(dims.width() as f32, dims.height() as f32, 1.0),
+    );
+    render.with_scissor(mev::Offset2::ZERO, dims);
+    render.draw(0..3, 0..1);
+    drop(render);
+
+    // Next pass samples this target.
+    encoder.barrier(
+        mev::PipelineStages::COLOR_OUTPUT,
+        mev::PipelineStages::FRAGMENT_SHADER,
+    );
+}
+
+fn create_bloom(device: &mev::Device, extent: mev::Extent2, format: mev::PixelFormat) -> Bloom {
+    let mut down = Vec::new();
+
+    let mut width = extent.width() / 2;
+    let mut height = extent.height() / 2;
+    while down.len() < BLOOM_LEVELS as usize && width > 0 && height > 0 {
+        down.push(
+            device
+                .new_image(mev::ImageDesc {
+                    extent: mev::Extent2::new(width, height).into(),
+                    format,
+                    usage: mev::ImageUsage::TARGET | mev::ImageUsage::SAMPLED,
+                    layers: 1,
+                    levels: 1,
+                    name: "bloom-down",
+                })
+                .unwrap(),
+        );
+        width /= 2;
+        height /= 2;
+    }
+
+    // Tiny source images still get single level.
+    if down.is_empty() {
+        down.push(
+            device
+                .new_image(mev::ImageDesc {
+                    extent: mev::Extent2::new(1, 1).into(),
+                    format,
+                    usage: mev::ImageUsage::TARGET | mev::ImageUsage::SAMPLED,
+                    layers: 1,
+                    levels: 1,
+                    name: "bloom-down",
+                })
+                .unwrap(),
+        );
+    }
+
+    let up = down[..down.len() - 1]
+        .iter()
+        .map(|image| {
+            device
+                .new_image(mev::ImageDesc {
+                    extent: image.extent(),
+                    format,
+                    usage: mev::ImageUsage::TARGET | mev::ImageUsage::SAMPLED,
+                    layers: 1,
+                    levels: 1,
+                    name: "bloom-up",
+                })
+                .unwrap()
+        })
+        .collect();
+
+    Bloom {
+        extent,
+        format,
+        down,
+        up,
+    }
+}
+
+fn create_pipelines(device: &mev::Device, format: mev::PixelFormat) -> Pipelines {
+    let library = device
+        .new_shader_library(mev::LibraryDesc {
+            name: "postfx",
+            input: mev::include_library!("shaders/postfx.wgsl" as mev::ShaderLanguage::Wgsl),
+        })
+        .unwrap();
+
+    let pipeline = |entry: &str| {
+        device
+            .new_render_pipeline(mev::RenderPipelineDesc {
+                name: entry,
+                vertex_shader: mev::Shader {
+                    library: library.clone(),
+                    entry: "vs_main".into(),
+                },
+                vertex_attributes: vec![],
+                vertex_layouts: vec![],
+                primitive_topology: mev::PrimitiveTopology::Triangle,
+                raster: Some(mev::RasterDesc {
+                    fragment_shader: Some(mev::Shader {
+                        library: library.clone(),
+                        entry: entry.into(),
+                    }),
+                    color_targets: vec![mev::ColorTargetDesc {
+                        format,
+                        blend: None,
+                    }],
+                    depth_stencil: None,
+                    front_face: mev::FrontFace::default(),
+                    culling: mev::Culling::None,
+                }),
+                arguments: &[PostFxArguments::LAYOUT],
+                constants: PostFxConstants::SIZE,
+            })
+            .unwrap()
+    };
+
+    Pipelines {
+        format,
+        prefilter: pipeline("fs_prefilter"),
+        downsample: pipeline("fs_downsample"),
+        upsample: pipeline("fs_upsample"),
+        composite: pipeline("fs_composite"),
+    }
+}
@@ -0,0 +1,14 @@
+//! This plugin provides post-processing stack.
+//!
+//! `PostFx` job reads the scene image and writes processed image
+//! that is meant to be presented.
+//! Effects are applied in fixed order:
+//! bloom, exposure and tonemapping, color LUT, chromatic aberration and vignette.
+//! Each effect is configured with job parameters and is skipped
+//! when its parameters have default values.
+
+arcana::declare_plugin!();
+
+mod job;
+
+pub use self::job::PostFx;
@@ -0,0 +1,145 @@
+struct Constants {
+    // Size of source texel in UV units.
+    texel: vec2f,
+    threshold: f32,
+    bloom_intensity: f32,
+    exposure: f32,
+    tonemapping: u32,
+    vignette: f32,
+    aberration: f32,
+    // Zero when there is no LUT.
+    lut_size: f32,
+}
+
+var<push_constant> pc: Constants;
+
+@group(0) @binding(0) var s: sampler;
+@group(0) @binding(1) var source: texture_2d<f32>;
+@group(0) @binding(2) var extra: texture_2d<f32>;
+@group(0) @binding(3) var lut: texture_2d<f32>;
+
+struct VertOutput {
+    @builtin(position)
+    position: vec4f,
+    @location(0)
+    uv: vec2f,
+}
+
+// Single triangle that covers the whole target.
+@vertex
+fn vs_main(@builtin(vertex_index) vertex: u32) -> VertOutput {
+    let uv = vec2f(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    let position = vec4f(uv.x * 2f - 1f, 1f - uv.y * 2f, 0f, 1f);
+    return VertOutput(position, uv);
+}
+
+fn sample_source(uv: vec2f) -> vec4f {
+    return textureSampleLevel(source, s, uv, 0f);
+}
+
+// Average of 16 texels using 4 bilinear taps.
+fn box4(uv: vec2f) -> vec4f {
+    let d = pc.texel;
+    return (sample_source(uv + vec2f(-d.x, -d.y))
+        + sample_source(uv + vec2f(d.x, -d.y))
+        + sample_source(uv + vec2f(-d.x, d.y))
+        + sample_source(uv + vec2f(d.x, d.y))) * 0.25f;
+}
+
+// 3x3 tent filter.
+fn tent9(uv: vec2f) -> vec4f {
+    let d = pc.texel;
+    var sum = sample_source(uv) * 4f;
+    sum += (sample_source(uv + vec2f(-d.x, 0f))
+        + sample_source(uv + vec2f(d.x, 0f))
+        + sample_source(uv + vec2f(0f, -d.y))
+        + sample_source(uv + vec2f(0f, d.y))) * 2f;
+    sum += sample_source(uv + vec2f(-d.x, -d.y))
+        + sample_source(uv + vec2f(d.x, -d.y))
+        + sample_source(uv + vec2f(-d.x, d.y))
+        + sample_source(uv + vec2f(d.x, d.y));
+    return sum / 16f;
+}
+
+// Keeps only parts of the image brighter than threshold.
+@fragment
+fn fs_prefilter(input: VertOutput) -> @location(0) vec4f {
+    let color = box4(input.uv).rgb;
+    let brightness = max(color.r, max(color.g, color.b));
+    let contribution = max(brightness - pc.threshold, 0f) / max(brightness, 1e-4f);
+    return vec4f(color * contribution, 1f);
+}
+
+@fragment
+fn fs_downsample(input: VertOutput) -> @location(0) vec4f {
+    return vec4f(box4(input.uv).rgb, 1f);
+}
+
+// Upsamples lower level in source and adds higher level in extra.
+@fragment
+fn fs_upsample(input: VertOutput) -> @location(0) vec4f {
+    let high = textureSampleLevel(extra, s, input.uv, 0f).rgb;
+    return vec4f(tent9(input.uv).rgb + high, 1f);
+}
+
+fn tonemap(color: vec3f) -> vec3f {
+    switch pc.tonemapping {
+        // Reinhard
+        case 1u: {
+            return color / (color + 1f);
+        }
+        // ACES filmic approximation by Krzysztof Narkowicz.
+        case 2u: {
+            let c = color * 0.6f;
+            return (c * (2.51f * c + 0.03f)) / (c * (2.43f * c + 0.59f) + 0.14f);
+        }
+        default: {
+            return color;
+        }
+    }
+}
+
+// LUT is a horizontal strip of square slices, blue selects the slice.
+fn apply_lut(color: vec3f) -> vec3f {
+    let n = pc.lut_size;
+    let blue = color.b * (n - 1f);
+    let slice = floor(blue);
+    let next = min(slice + 1f, n - 1f);
+
+    let x = (color.r * (n - 1f) + 0.5f) / (n * n);
+    let y = (color.g * (n - 1f) + 0.5f) / n;
+
+    let a = textureSampleLevel(lut, s, vec2f(x + slice / n, y), 0f).rgb;
+    let b = textureSampleLevel(lut, s, vec2f(x + next / n, y), 0f).rgb;
+    return mix(a, b, blue - slice);
+}
+
+@fragment
+fn fs_composite(input: VertOutput) -> @location(0) vec4f {
+    let uv = input.uv;
+    let centered = uv - 0.5f;
+
+    // Channels are shifted apart towards the edges.
+    let offset = centered * pc.aberration;
+    let base = sample_source(uv);
+    var color = vec3f(
+        sample_source(uv - offset).r,
+        base.g,
+        sample_source(uv + offset).b,
+    );
+
+    if pc.bloom_intensity > 0f {
+        color += textureSampleLevel(extra, s, uv, 0f).rgb * pc.bloom_intensity;
+    }
+
+    color = tonemap(color * exp2(pc.exposure));
+    color = clamp(color, vec3f(0f), vec3f(1f));
+
+    if pc.lut_size > 0f {
+        color = apply_lut(color);
+    }
+
+    color *= 1f - pc.vignette * smoothstep(0.25f, 0.75f, length(centered));
+
+    return vec4f(color, base.a);
+}
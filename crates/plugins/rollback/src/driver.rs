@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+
+use arcana::edict::world::World;
+
+use crate::snapshot::{RollbackComponents, WorldSnapshot};
+
+/// Number of confirmed checksums kept for desync detection.
+const CHECKSUM_HISTORY: u64 = 256;
+
+/// Drives deterministic simulation with input delay and rollback.
+///
+/// Each frame is simulated with inputs of all players.
+/// Missing remote inputs are predicted by repeating last known input of the player.
+/// When actual input arrives and differs from the prediction,
+/// world is restored from the snapshot of that frame and all frames since
+/// are simulated again with corrected inputs.
+///
+/// Local inputs are scheduled `input_delay` frames ahead,
+/// which hides latency up to that many frames without any rollback.
+/// Setting `max_rollback` to zero turns this into plain lockstep
+/// that waits for all inputs before simulating a frame.
+///
+/// Inputs are whatever game sends between peers,
+/// typically actions drained from the player's action queue during the frame.
+pub struct RollbackDriver<I> {
+    players: usize,
+    input_delay: u64,
+    max_rollback: u64,
+
+    /// Next frame to simulate.
+    frame: u64,
+
+    /// First frame for which some input is still missing.
+    confirmed: u64,
+
+    /// Inputs received for unconfirmed frames.
+    inputs: BTreeMap<u64, Vec<Option<I>>>,
+
+    /// Inputs of the last confirmed frame.
+    /// Used to predict inputs of players that were not received yet.
+    last_confirmed: Vec<I>,
+
+    /// Inputs each simulated frame used, including predicted ones.
+    used: BTreeMap<u64, Vec<I>>,
+
+    /// World state before each unconfirmed frame.
+    snapshots: BTreeMap<u64, WorldSnapshot>,
+
+    /// Checksums of world state before confirmed frames.
+    checksums: BTreeMap<u64, u64>,
+
+    /// Earliest frame simulated with mispredicted input.
+    rollback_to: Option<u64>,
+}
+
+impl<I> RollbackDriver<I>
+where
+    I: Clone + PartialEq + Default,
+{
+    pub fn new(players: usize) -> Self {
+        RollbackDriver {
+            players,
+            input_delay: 2,
+            max_rollback: 8,
+            frame: 0,
+            confirmed: 0,
+            inputs: BTreeMap::new(),
+            last_confirmed: vec![I::default(); players],
+            used: BTreeMap::new(),
+            snapshots: BTreeMap::new(),
+            checksums: BTreeMap::new(),
+            rollback_to: None,
+        }
+    }
+
+    pub fn with_input_delay(mut self, frames: u64) -> Self {
+        self.input_delay = frames;
+        self
+    }
+
+    /// Sets how many frames simulation may run ahead of confirmed inputs
+    /// with predicted ones.
+    pub fn with_max_rollback(mut self, frames: u64) -> Self {
+        self.max_rollback = frames;
+        self
+    }
+
+    /// Returns next frame to simulate.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Returns first frame for which some input is still missing.
+    pub fn confirmed_frame(&self) -> u64 {
+        self.confirmed
+    }
+
+    /// Returns checksum of the world at the start of the confirmed frame.
+    ///
+    /// Peers exchange checksums of the same frames to detect desync.
+    pub fn checksum(&self, frame: u64) -> Option<u64> {
+        self.checksums.get(&frame).copied()
+    }
+
+    /// Adds input of the local player.
+    ///
+    /// Returns frame the input is scheduled for,
+    /// it should be sent to other peers along with the input.
+    pub fn add_local_input(&mut self, player: usize, input: I) -> u64 {
+        let frame = self.frame + self.input_delay;
+        self.add_input(player, frame, input);
+        frame
+    }
+
+    /// Adds input of the player for the frame.
+    pub fn add_input(&mut self, player: usize, frame: u64, input: I) {
+        assert!(player < self.players, "Player {player} is out of range");
+
+        if frame < self.confirmed {
+            tracing::warn!("Input of player {player} for confirmed frame {frame} is ignored");
+            return;
+        }
+
+        if let Some(used) = self.used.get(&frame) {
+            if used[player] != input {
+                self.rollback_to = Some(self.rollback_to.map_or(frame, |f| f.min(frame)));
+            }
+        }
+
+        let players = self.players;
+        self.inputs
+            .entry(frame)
+            .or_insert_with(|| vec![None; players])[player] = Some(input);
+
+        while let Some(inputs) = self.inputs.get(&self.confirmed) {
+            if inputs.iter().any(Option::is_none) {
+                break;
+            }
+
+            let inputs = self.inputs.remove(&self.confirmed).unwrap();
+            self.last_confirmed = inputs.into_iter().map(Option::unwrap).collect();
+            self.used
+                .insert(self.confirmed, self.last_confirmed.clone());
+            self.confirmed += 1;
+        }
+    }
+
+    /// Returns true if next frame can be simulated
+    /// without running too far ahead of confirmed inputs.
+    pub fn can_advance(&self) -> bool {
+        self.frame < self.confirmed + self.max_rollback
+    }
+
+    /// Rolls back if needed and simulates next frame.
+    ///
+    /// `step` must advance the world by one frame using the inputs of all players,
+    /// running the same systems in the same order on all peers.
+    /// World must contain [`RollbackComponents`] resource.
+    ///
+    /// Returns false if simulation is stalled waiting for inputs.
+    pub fn advance(
+        &mut self,
+        world: &mut World,
+        mut step: impl FnMut(&mut World, u64, &[I]),
+    ) -> bool {
+        if let Some(to) = self.rollback_to.take() {
+            tracing::debug!("Rolling back from frame {} to {to}", self.frame);
+
+            let mut components = world
+                .remove_resource::<RollbackComponents>()
+                .expect("RollbackComponents resource is missing");
+            components.restore(world, &self.snapshots[&to]);
+            world.insert_resource(components);
+
+            for frame in to..self.frame {
+                self.simulate(world, frame, &mut step);
+            }
+        }
+
+        let advanced = self.can_advance();
+        if advanced {
+            self.simulate(world, self.frame, &mut step);
+            self.frame += 1;
+        }
+
+        self.prune();
+        advanced
+    }
+
+    fn simulate(
+        &mut self,
+        world: &mut World,
+        frame: u64,
+        step: &mut impl FnMut(&mut World, u64, &[I]),
+    ) {
+        let snapshot = world
+            .expect_resource::<RollbackComponents>()
+            .snapshot(world);
+        self.snapshots.insert(frame, snapshot);
+
+        let inputs = match self.inputs.get(&frame) {
+            None if frame < self.confirmed => self.used[&frame].clone(),
+            None => self.predict(frame),
+            Some(inputs) => {
+                let predicted = self.predict(frame);
+                inputs
+                    .iter()
+                    .zip(predicted)
+                    .map(|(input, predicted)| input.clone().unwrap_or(predicted))
+                    .collect()
+            }
+        };
+
+        step(world, frame, &inputs);
+        self.used.insert(frame, inputs);
+    }
+
+    /// Predicts inputs for the frame from latest known inputs of each player.
+    fn predict(&self, frame: u64) -> Vec<I> {
+        let mut predicted = self.last_confirmed.clone();
+
+        for (_, inputs) in self.inputs.range(..frame) {
+            for (predicted, input) in predicted.iter_mut().zip(inputs) {
+                if let Some(input) = input {
+                    *predicted = input.clone();
+                }
+            }
+        }
+
+        predicted
+    }
+
+    /// Drops data of frames that can't be rolled back anymore.
+    fn prune(&mut self) {
+        let keep = self.confirmed.min(self.frame);
+
+        while let Some(entry) = self.snapshots.first_entry() {
+            if *entry.key() >= keep {
+                break;
+            }
+            let (frame, snapshot) = entry.remove_entry();
+            self.checksums.insert(frame, snapshot.checksum());
+        }
+
+        self.used = self.used.split_off(&keep);
+
+        while self.checksums.len() as u64 > CHECKSUM_HISTORY {
+            self.checksums.pop_first();
+        }
+    }
+}
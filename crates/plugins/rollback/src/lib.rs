@@ -0,0 +1,29 @@
+//! This plugin provides infrastructure for deterministic lockstep and rollback.
+//!
+//! Entities with [`RollbackId`] take part in rollback.
+//! Components registered in [`RollbackComponents`] resource are saved into
+//! [`WorldSnapshot`]s and hashed into checksums that peers compare to detect desync.
+//!
+//! [`RollbackDriver`] simulates frames with inputs of all players,
+//! predicting late ones and rolling back to saved snapshot when prediction fails.
+//!
+//! Simulation stays deterministic only if it uses `arcana::fixed::Fixed`
+//! instead of floats for game state, and does not depend on anything
+//! besides the world and the inputs.
+
+use arcana::World;
+
+arcana::declare_plugin!();
+
+mod driver;
+mod snapshot;
+
+pub use self::{
+    driver::RollbackDriver,
+    snapshot::{RollbackComponents, RollbackId, WorldSnapshot},
+};
+
+#[arcana::init]
+fn init_rollback(world: &mut World) {
+    world.insert_resource(RollbackComponents::new());
+}
@@ -0,0 +1,233 @@
+use std::{
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+};
+
+use arcana::{
+    edict::{self, world::World, Component, Entities},
+    hash::stable_hasher,
+    model::Value,
+    reflect::{ComponentReflect, Reflect},
+    EntityId, Stid, WithStid,
+};
+
+/// Identifies entity that takes part in rollback.
+///
+/// Unlike `EntityId` it is allocated deterministically
+/// with [`RollbackComponents::allocate_id`], so it is the same on all peers
+/// and survives restoring of snapshots.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Component)]
+pub struct RollbackId(pub u64);
+
+struct RollbackComponent {
+    stid: Stid,
+    reflect: ComponentReflect,
+    remove: fn(&mut World, EntityId),
+}
+
+/// Reflected components of rollback entities at some frame.
+#[derive(Clone)]
+pub struct WorldSnapshot {
+    entities: BTreeMap<RollbackId, Vec<(Stid, Value)>>,
+    next_id: u64,
+}
+
+impl WorldSnapshot {
+    /// Returns checksum of the snapshot.
+    ///
+    /// Equal on all peers as long as their simulations did not diverge.
+    /// References to entities are not hashed, as their ids are local to the world.
+    pub fn checksum(&self) -> u64 {
+        let mut hasher = stable_hasher();
+        self.next_id.hash(&mut hasher);
+
+        for (id, components) in &self.entities {
+            id.hash(&mut hasher);
+            for (stid, value) in components {
+                stid.hash(&mut hasher);
+                hash_value(value, &mut hasher);
+            }
+        }
+
+        hasher.finish()
+    }
+}
+
+/// Registry of components that are saved into snapshots and hashed into checksums.
+///
+/// Must be populated identically on all peers.
+/// Entities take part in rollback when they have [`RollbackId`] component.
+pub struct RollbackComponents {
+    /// Sorted by stid, so that snapshots are ordered the same way everywhere.
+    components: Vec<RollbackComponent>,
+    next_id: u64,
+}
+
+impl RollbackComponents {
+    pub fn new() -> Self {
+        RollbackComponents {
+            components: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Registers component type for rollback.
+    pub fn register<T>(&mut self)
+    where
+        T: Reflect + Component + WithStid + Default + Send + Sync,
+    {
+        let component = RollbackComponent {
+            stid: T::stid(),
+            reflect: ComponentReflect::with_default::<T>(),
+            remove: |world, entity| {
+                let _ = world.drop::<T>(entity);
+            },
+        };
+
+        match self
+            .components
+            .binary_search_by_key(&T::stid(), |component| component.stid)
+        {
+            Ok(idx) => self.components[idx] = component,
+            Err(idx) => self.components.insert(idx, component),
+        }
+    }
+
+    /// Allocates id for new rollback entity.
+    ///
+    /// Ids are part of snapshots, so entities spawned in the same frame
+    /// on different peers, or while resimulating, get the same ids.
+    pub fn allocate_id(&mut self) -> RollbackId {
+        let id = RollbackId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Saves registered components of rollback entities.
+    pub fn snapshot(&self, world: &World) -> WorldSnapshot {
+        let entities = world
+            .view::<(Entities, &RollbackId)>()
+            .iter()
+            .map(|(e, &id)| (id, e.id()))
+            .collect::<Vec<_>>();
+
+        let entities = entities
+            .into_iter()
+            .map(|(id, entity)| {
+                let components = self
+                    .components
+                    .iter()
+                    .filter_map(|component| {
+                        Some((component.stid, component.reflect.get(world, entity)?))
+                    })
+                    .collect();
+                (id, components)
+            })
+            .collect();
+
+        WorldSnapshot {
+            entities,
+            next_id: self.next_id,
+        }
+    }
+
+    /// Returns checksum of registered components of rollback entities.
+    pub fn checksum(&self, world: &World) -> u64 {
+        self.snapshot(world).checksum()
+    }
+
+    /// Brings rollback entities back to the state saved in the snapshot.
+    ///
+    /// Entities spawned after the snapshot was taken are despawned,
+    /// despawned ones are spawned again with new `EntityId`.
+    /// Components that are not registered are left intact.
+    pub fn restore(&mut self, world: &mut World, snapshot: &WorldSnapshot) {
+        let mut current = world
+            .view::<(Entities, &RollbackId)>()
+            .iter()
+            .map(|(e, &id)| (id, e.id()))
+            .collect::<BTreeMap<_, _>>();
+
+        for (&id, components) in &snapshot.entities {
+            let entity = match current.remove(&id) {
+                Some(entity) => entity,
+                None => world.spawn((id,)).id(),
+            };
+
+            let mut saved = components.iter().peekable();
+
+            // Both are sorted by stid.
+            for component in &self.components {
+                match saved.next_if(|(stid, _)| *stid == component.stid) {
+                    None => (component.remove)(world, entity),
+                    Some((stid, value)) => {
+                        let result = if component.reflect.has(world, entity) {
+                            component.reflect.set(world, entity, value)
+                        } else {
+                            component.reflect.insert(world, entity, value)
+                        };
+
+                        if let Err(err) = result {
+                            tracing::error!(
+                                "Failed to restore component {stid} of entity {entity}: {err}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        for (_, entity) in current {
+            let _ = world.despawn(entity);
+        }
+
+        self.next_id = snapshot.next_id;
+    }
+}
+
+/// Hashes value in a way that does not depend on order of map entries.
+fn hash_value(value: &Value, hasher: &mut impl Hasher) {
+    std::mem::discriminant(value).hash(hasher);
+
+    match value {
+        Value::Unit | Value::Entity(_) => {}
+        Value::Bool(v) => v.hash(hasher),
+        Value::Int(v) => v.hash(hasher),
+        Value::Uint(v) => v.hash(hasher),
+        Value::Float(v) => v.to_bits().hash(hasher),
+        Value::String(v) => v.hash(hasher),
+        Value::Vec2(v) => v.iter().for_each(|c| c.to_bits().hash(hasher)),
+        Value::Vec3(v) => v.iter().for_each(|c| c.to_bits().hash(hasher)),
+        Value::Vec4(v) => v.iter().for_each(|c| c.to_bits().hash(hasher)),
+        Value::Mat2(v) => v.iter().for_each(|c| c.to_bits().hash(hasher)),
+        Value::Mat3(v) => v.iter().for_each(|c| c.to_bits().hash(hasher)),
+        Value::Mat4(v) => v.iter().for_each(|c| c.to_bits().hash(hasher)),
+        Value::Option(v) => {
+            if let Some(v) = v {
+                hash_value(v, hasher);
+            }
+        }
+        Value::Array(v) => {
+            v.len().hash(hasher);
+            v.iter().for_each(|v| hash_value(v, hasher));
+        }
+        Value::Map(v) => {
+            let mut entries = v.iter().collect::<Vec<_>>();
+            entries.sort_by_key(|&(key, _)| key);
+
+            entries.len().hash(hasher);
+            for (key, value) in entries {
+                key.hash(hasher);
+                hash_value(value, hasher);
+            }
+        }
+        Value::Enum(name, v) => {
+            name.as_str().hash(hasher);
+            hash_value(v, hasher);
+        }
+        Value::Color(_) | Value::Asset(_) | Value::Curve(_) => {
+            let bytes = bincode::serialize(value).expect("Value serialization cannot fail");
+            hasher.write(&bytes);
+        }
+    }
+}
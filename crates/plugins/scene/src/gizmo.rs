@@ -0,0 +1,43 @@
+use arcana::gizmo::{GizmoTransform, Transform2};
+
+/// Lets editor gizmos move entities.
+///
+/// Children get `Local` updated too, otherwise `scene_system`
+/// would overwrite edited `Global` from parent on next run.
+#[arcana::init]
+fn init_gizmo_transform(world: &mut World) {
+    world.insert_resource(GizmoTransform {
+        get: get_gizmo_transform,
+        set: set_gizmo_transform,
+    });
+}
+
+fn get_gizmo_transform(world: &World, entity: EntityId) -> Option<Transform2> {
+    let global = world
+        .get::<arcana::edict::query::Cpy<Global>>(entity)
+        .ok()?;
+    Some(Transform2::from_isometry(&global.iso))
+}
+
+fn set_gizmo_transform(world: &mut World, entity: EntityId, transform: Transform2) {
+    let iso = transform.to_isometry();
+
+    let parent = world
+        .get::<RelatesExclusive<&Local>>(entity)
+        .ok()
+        .map(|(_, parent)| parent);
+
+    if let Some(parent) = parent {
+        let Ok(parent_global) = world.get::<arcana::edict::query::Cpy<Global>>(parent) else {
+            return;
+        };
+
+        if let Ok((local, _)) = world.get::<RelatesExclusive<&mut Local>>(entity) {
+            local.iso = parent_global.iso.inverse() * iso;
+        }
+    }
+
+    if let Ok(global) = world.get::<&mut Global>(entity) {
+        global.iso = iso;
+    }
+}
@@ -10,6 +10,8 @@ pub mod dim2 {
     pub type AngVector<T> = T;
 
     std::include!("impl.rs");
+    std::include!("spatial.rs");
+    std::include!("gizmo.rs");
 }
 
 #[cfg(feature = "dim3")]
@@ -22,4 +24,5 @@ pub mod dim3 {
     pub type AngVector<T> = na::Vector3<T>;
 
     std::include!("impl.rs");
+    std::include!("spatial.rs");
 }
@@ -0,0 +1,367 @@
+use arcana::{
+    edict::{entity::EntityId, world::World, ResMut},
+    hashbrown::HashMap,
+};
+
+/// Coordinates of the spatial index cell.
+type Cell = Vector<i32>;
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Point<f32>,
+    pub max: Point<f32>,
+}
+
+impl Aabb {
+    pub fn new(min: Point<f32>, max: Point<f32>) -> Self {
+        Aabb { min, max }
+    }
+
+    pub fn from_center(center: Point<f32>, half_extents: Vector<f32>) -> Self {
+        Aabb {
+            min: center - half_extents,
+            max: center + half_extents,
+        }
+    }
+
+    pub fn contains(&self, point: &Point<f32>) -> bool {
+        self.min
+            .iter()
+            .zip(self.max.iter())
+            .zip(point.iter())
+            .all(|((min, max), p)| min <= p && p <= max)
+    }
+}
+
+struct Entry {
+    position: Point<f32>,
+    cell: Cell,
+    epoch: u64,
+}
+
+/// Resource that indexes positions of entities with `Global`
+/// in a uniform grid for proximity queries.
+///
+/// Updated by `spatial_index_system` once per frame.
+/// Only entities that crossed cell boundary are moved between cells.
+/// Queries see positions as of the last update.
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<EntityId>>,
+    entries: HashMap<EntityId, Entry>,
+    epoch: u64,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        SpatialIndex::new(SpatialIndex::DEFAULT_CELL_SIZE)
+    }
+}
+
+impl SpatialIndex {
+    pub const DEFAULT_CELL_SIZE: f32 = 8.0;
+
+    /// Returns empty index with given cell size.
+    ///
+    /// Cell size close to typical query radius works best.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "Cell size must be positive");
+
+        SpatialIndex {
+            cell_size,
+            cells: HashMap::new(),
+            entries: HashMap::new(),
+            epoch: 0,
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Returns number of indexed entities.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns indexed position of the entity.
+    pub fn position(&self, entity: EntityId) -> Option<Point<f32>> {
+        self.entries.get(&entity).map(|entry| entry.position)
+    }
+
+    /// Calls `f` for each entity inside the box.
+    pub fn query_aabb(&self, aabb: &Aabb, mut f: impl FnMut(EntityId, Point<f32>)) {
+        let min = self.cell(&aabb.min);
+        let max = self.cell(&aabb.max);
+
+        let mut visit = |entities: &Vec<EntityId>| {
+            for &entity in entities {
+                let position = self.entries[&entity].position;
+                if aabb.contains(&position) {
+                    f(entity, position);
+                }
+            }
+        };
+
+        // Large boxes cover mostly empty cells,
+        // it's cheaper to check occupied ones.
+        let count = min
+            .iter()
+            .zip(max.iter())
+            .map(|(min, max)| (*max as f64 - *min as f64 + 1.0).max(0.0))
+            .product::<f64>();
+
+        if count > self.cells.len() as f64 {
+            for entities in self.cells.values() {
+                visit(entities);
+            }
+        } else {
+            for_each_cell(min, max, |cell| {
+                if let Some(entities) = self.cells.get(&cell) {
+                    visit(entities);
+                }
+            });
+        }
+    }
+
+    /// Calls `f` for each entity within `radius` from `center`
+    /// with distance to it.
+    pub fn query_radius(&self, center: &Point<f32>, radius: f32, mut f: impl FnMut(EntityId, f32)) {
+        let half = Vector::repeat(radius);
+        let aabb = Aabb::from_center(*center, half);
+
+        self.query_aabb(&aabb, |entity, position| {
+            let distance = na::distance(center, &position);
+            if distance <= radius {
+                f(entity, distance);
+            }
+        });
+    }
+
+    /// Returns up to `k` entities closest to `point`
+    /// with distances to them, nearest first.
+    pub fn k_nearest(&self, point: &Point<f32>, k: usize) -> Vec<(EntityId, f32)> {
+        let mut result = Vec::new();
+
+        if k == 0 || self.entries.is_empty() {
+            return result;
+        }
+
+        // Grow search radius until it holds `k` entities.
+        // Entities outside the radius can't be closer than those inside.
+        let mut radius = self.cell_size;
+        loop {
+            result.clear();
+            self.query_radius(point, radius, |entity, distance| {
+                result.push((entity, distance));
+            });
+
+            if result.len() >= k || result.len() == self.entries.len() {
+                break;
+            }
+
+            radius *= 2.0;
+        }
+
+        result.sort_by(|a, b| a.1.total_cmp(&b.1));
+        result.truncate(k);
+        result
+    }
+
+    fn cell(&self, point: &Point<f32>) -> Cell {
+        point.coords.map(|c| (c / self.cell_size).floor() as i32)
+    }
+
+    fn update(&mut self, entity: EntityId, position: Point<f32>) {
+        let cell = self.cell(&position);
+        let epoch = self.epoch;
+
+        match self.entries.get_mut(&entity) {
+            Some(entry) => {
+                entry.position = position;
+                entry.epoch = epoch;
+
+                if entry.cell != cell {
+                    remove_from_cell(&mut self.cells, entry.cell, entity);
+                    self.cells.entry(cell).or_default().push(entity);
+                    entry.cell = cell;
+                }
+            }
+            None => {
+                self.entries.insert(
+                    entity,
+                    Entry {
+                        position,
+                        cell,
+                        epoch,
+                    },
+                );
+                self.cells.entry(cell).or_default().push(entity);
+            }
+        }
+    }
+
+    /// Removes entities that were not updated in current epoch.
+    fn remove_stale(&mut self) {
+        let epoch = self.epoch;
+        let cells = &mut self.cells;
+
+        self.entries.retain(|&entity, entry| {
+            if entry.epoch == epoch {
+                return true;
+            }
+            remove_from_cell(cells, entry.cell, entity);
+            false
+        });
+    }
+}
+
+fn remove_from_cell(cells: &mut HashMap<Cell, Vec<EntityId>>, cell: Cell, entity: EntityId) {
+    let Some(entities) = cells.get_mut(&cell) else {
+        return;
+    };
+
+    if let Some(idx) = entities.iter().position(|&e| e == entity) {
+        entities.swap_remove(idx);
+    }
+
+    if entities.is_empty() {
+        cells.remove(&cell);
+    }
+}
+
+/// Calls `f` for each cell in the inclusive range.
+fn for_each_cell(min: Cell, max: Cell, mut f: impl FnMut(Cell)) {
+    if min.iter().zip(max.iter()).any(|(min, max)| min > max) {
+        return;
+    }
+
+    let mut cell = min;
+    loop {
+        f(cell);
+
+        let mut axis = 0;
+        loop {
+            if axis == cell.len() {
+                return;
+            }
+            if cell[axis] < max[axis] {
+                cell[axis] += 1;
+                break;
+            }
+            cell[axis] = min[axis];
+            axis += 1;
+        }
+    }
+}
+
+#[arcana::init]
+fn init_spatial_index(world: &mut World) {
+    world.insert_resource(SpatialIndex::default());
+}
+
+#[arcana::system]
+pub fn spatial_index_system(globals: View<(Entities, &Global)>, mut index: ResMut<SpatialIndex>) {
+    index.epoch += 1;
+
+    for (entity, global) in globals {
+        let position = Point::from(global.iso.translation.vector);
+        index.update(entity.id(), position);
+    }
+
+    index.remove_stale();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(x: f32) -> Point<f32> {
+        let mut point = Point::origin();
+        point.x = x;
+        point
+    }
+
+    #[test]
+    fn test_aabb_contains() {
+        let aabb = Aabb::from_center(Point::origin(), Vector::repeat(1.0));
+        assert!(aabb.contains(&Point::origin()));
+        assert!(aabb.contains(&Point::from(Vector::repeat(1.0))));
+        assert!(aabb.contains(&Point::from(Vector::repeat(-1.0))));
+        assert!(!aabb.contains(&point(1.5)));
+        assert!(!aabb.contains(&point(-1.5)));
+    }
+
+    #[test]
+    fn test_for_each_cell() {
+        let mut count = 0;
+        for_each_cell(Cell::repeat(-1), Cell::repeat(1), |_| count += 1);
+        assert_eq!(count, 3usize.pow(Cell::zeros().len() as u32));
+
+        let mut count = 0;
+        for_each_cell(Cell::repeat(1), Cell::repeat(0), |_| count += 1);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut world = World::new();
+        let a = world.spawn(()).id();
+        let b = world.spawn(()).id();
+        let c = world.spawn(()).id();
+
+        let mut index = SpatialIndex::new(4.0);
+        index.epoch += 1;
+        index.update(a, point(1.0));
+        index.update(b, point(-3.0));
+        index.update(c, point(20.0));
+        index.remove_stale();
+        assert_eq!(index.len(), 3);
+
+        let mut found = Vec::new();
+        index.query_radius(&Point::origin(), 3.0, |entity, distance| {
+            found.push((entity, distance))
+        });
+        found.sort_by(|a, b| a.1.total_cmp(&b.1));
+        assert_eq!(found, [(a, 1.0), (b, 3.0)]);
+
+        // Box larger than number of occupied cells visits them instead.
+        let mut found = Vec::new();
+        let aabb = Aabb::from_center(Point::origin(), Vector::repeat(1000.0));
+        index.query_aabb(&aabb, |entity, _| found.push(entity));
+        assert_eq!(found.len(), 3);
+
+        assert_eq!(index.k_nearest(&point(18.0), 2), [(c, 2.0), (a, 17.0)]);
+        assert_eq!(index.k_nearest(&Point::origin(), 5).len(), 3);
+        assert!(index.k_nearest(&Point::origin(), 0).is_empty());
+    }
+
+    #[test]
+    fn test_update_moves_and_removes() {
+        let mut world = World::new();
+        let a = world.spawn(()).id();
+        let b = world.spawn(()).id();
+
+        let mut index = SpatialIndex::new(4.0);
+        index.epoch += 1;
+        index.update(a, point(1.0));
+        index.update(b, point(2.0));
+        index.remove_stale();
+
+        // `a` crosses cell boundary, `b` is not updated and gets removed.
+        index.epoch += 1;
+        index.update(a, point(-5.0));
+        index.remove_stale();
+
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.position(a), Some(point(-5.0)));
+        assert_eq!(index.position(b), None);
+        assert_eq!(index.cells.len(), 1);
+        assert_eq!(index.cells[&index.cell(&point(-5.0))], [a]);
+    }
+}
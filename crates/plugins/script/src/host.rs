@@ -0,0 +1,308 @@
+//! Host API available to scripts.
+//!
+//! Functions are imported from `arcana` module.
+//! Strings and component values are passed as pointer and length
+//! into script's exported `memory`.
+//! Component values are JSON-encoded `Value`s.
+//!
+//! Functions that write into script memory take output buffer pointer and capacity,
+//! and return number of bytes required.
+//! Nothing is written if buffer is too small, so script can retry with larger one.
+//! Negative result means failure.
+
+use std::num::NonZeroU64;
+
+use arcana::{
+    edict::{world::World, Entities},
+    events::{Event, EventId, Events},
+    model::Value,
+    EntityId,
+};
+use wasmi::{Caller, Engine, Extern, Linker, Memory};
+
+use crate::script::ScriptComponents;
+
+/// Returned by host functions on failure.
+const FAILURE: i32 = -1;
+
+/// Data available to host functions during script call.
+pub(crate) struct HostState {
+    /// Game world, swapped in for the duration of the call.
+    pub world: World,
+
+    /// Entity the script is attached to.
+    pub entity: Option<EntityId>,
+
+    pub delta_time: f32,
+}
+
+fn entity_bits(entity: EntityId) -> i64 {
+    entity.bits() as i64
+}
+
+fn entity_from_bits(bits: i64) -> Option<EntityId> {
+    EntityId::from_bits(bits as u64)
+}
+
+fn memory(caller: &Caller<'_, HostState>) -> Option<Memory> {
+    caller.get_export("memory").and_then(Extern::into_memory)
+}
+
+fn read_bytes(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> Option<Vec<u8>> {
+    let memory = memory(caller)?;
+    let mut bytes = vec![0; usize::try_from(len).ok()?];
+    memory
+        .read(caller, usize::try_from(ptr).ok()?, &mut bytes)
+        .ok()?;
+    Some(bytes)
+}
+
+fn read_str(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> Option<String> {
+    String::from_utf8(read_bytes(caller, ptr, len)?).ok()
+}
+
+/// Writes data if it fits into the buffer.
+/// Returns data length or failure.
+fn write_bytes(caller: &mut Caller<'_, HostState>, ptr: i32, cap: i32, data: &[u8]) -> i32 {
+    let Ok(len) = i32::try_from(data.len()) else {
+        return FAILURE;
+    };
+
+    if len > cap {
+        return len;
+    }
+
+    let Some(memory) = memory(caller) else {
+        return FAILURE;
+    };
+
+    let Ok(ptr) = usize::try_from(ptr) else {
+        return FAILURE;
+    };
+
+    match memory.write(caller, ptr, data) {
+        Ok(()) => len,
+        Err(_) => FAILURE,
+    }
+}
+
+fn log(caller: Caller<'_, HostState>, level: i32, ptr: i32, len: i32) {
+    let Some(message) = read_str(&caller, ptr, len) else {
+        return;
+    };
+
+    let entity = caller.data().entity;
+    match level {
+        0 => tracing::error!("Script of {entity:?}: {message}"),
+        1 => tracing::warn!("Script of {entity:?}: {message}"),
+        2 => tracing::info!("Script of {entity:?}: {message}"),
+        _ => tracing::debug!("Script of {entity:?}: {message}"),
+    }
+}
+
+fn entity(caller: Caller<'_, HostState>) -> i64 {
+    caller.data().entity.map_or(0, entity_bits)
+}
+
+fn delta_time(caller: Caller<'_, HostState>) -> f32 {
+    caller.data().delta_time
+}
+
+fn spawn(mut caller: Caller<'_, HostState>) -> i64 {
+    entity_bits(caller.data_mut().world.spawn(()).id())
+}
+
+fn despawn(mut caller: Caller<'_, HostState>, entity: i64) -> i32 {
+    let Some(entity) = entity_from_bits(entity) else {
+        return FAILURE;
+    };
+
+    match caller.data_mut().world.despawn(entity) {
+        Ok(()) => 0,
+        Err(_) => FAILURE,
+    }
+}
+
+fn get_component(
+    mut caller: Caller<'_, HostState>,
+    entity: i64,
+    name_ptr: i32,
+    name_len: i32,
+    out_ptr: i32,
+    out_cap: i32,
+) -> i32 {
+    let Some(entity) = entity_from_bits(entity) else {
+        return FAILURE;
+    };
+    let Some(name) = read_str(&caller, name_ptr, name_len) else {
+        return FAILURE;
+    };
+
+    let world = &caller.data().world;
+    let Some(component) = world
+        .get_resource::<ScriptComponents>()
+        .and_then(|components| components.get(&name))
+    else {
+        return FAILURE;
+    };
+
+    let Some(value) = component.reflect.get(world, entity) else {
+        return FAILURE;
+    };
+
+    let json = serde_json::to_vec(&value).expect("Value serialization cannot fail");
+    write_bytes(&mut caller, out_ptr, out_cap, &json)
+}
+
+fn set_component(
+    mut caller: Caller<'_, HostState>,
+    entity: i64,
+    name_ptr: i32,
+    name_len: i32,
+    json_ptr: i32,
+    json_len: i32,
+) -> i32 {
+    let Some(entity) = entity_from_bits(entity) else {
+        return FAILURE;
+    };
+    let Some(name) = read_str(&caller, name_ptr, name_len) else {
+        return FAILURE;
+    };
+    let Some(json) = read_bytes(&caller, json_ptr, json_len) else {
+        return FAILURE;
+    };
+
+    let value: Value = match serde_json::from_slice(&json) {
+        Ok(value) => value,
+        Err(err) => {
+            tracing::warn!("Script sent malformed value of component {name}: {err}");
+            return FAILURE;
+        }
+    };
+
+    let world = &mut caller.data_mut().world;
+    let Some(component) = world
+        .get_resource::<ScriptComponents>()
+        .and_then(|components| components.get(&name))
+    else {
+        return FAILURE;
+    };
+
+    let result = if component.reflect.has(world, entity) {
+        component.reflect.set(world, entity, &value)
+    } else {
+        component.reflect.insert(world, entity, &value)
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(err) => {
+            tracing::warn!("Script failed to set component {name} of {entity}: {err}");
+            FAILURE
+        }
+    }
+}
+
+fn remove_component(
+    mut caller: Caller<'_, HostState>,
+    entity: i64,
+    name_ptr: i32,
+    name_len: i32,
+) -> i32 {
+    let Some(entity) = entity_from_bits(entity) else {
+        return FAILURE;
+    };
+    let Some(name) = read_str(&caller, name_ptr, name_len) else {
+        return FAILURE;
+    };
+
+    let world = &mut caller.data_mut().world;
+    let Some(component) = world
+        .get_resource::<ScriptComponents>()
+        .and_then(|components| components.get(&name))
+    else {
+        return FAILURE;
+    };
+
+    (component.remove)(world, entity);
+    0
+}
+
+/// Writes ids of entities that have the component as little-endian `i64`s.
+/// Returns number of such entities, which may be larger than what fits into the buffer.
+fn query(
+    mut caller: Caller<'_, HostState>,
+    name_ptr: i32,
+    name_len: i32,
+    out_ptr: i32,
+    out_cap: i32,
+) -> i32 {
+    let Some(name) = read_str(&caller, name_ptr, name_len) else {
+        return FAILURE;
+    };
+
+    let world = &caller.data().world;
+    let Some(component) = world
+        .get_resource::<ScriptComponents>()
+        .and_then(|components| components.get(&name))
+    else {
+        return FAILURE;
+    };
+
+    let entities = world
+        .view::<Entities>()
+        .iter()
+        .map(|e| e.id())
+        .filter(|&e| component.reflect.has(world, e))
+        .collect::<Vec<_>>();
+
+    let fits = usize::try_from(out_cap).unwrap_or(0) / 8;
+    let bytes = entities
+        .iter()
+        .take(fits)
+        .flat_map(|&e| entity_bits(e).to_le_bytes())
+        .collect::<Vec<u8>>();
+
+    if write_bytes(&mut caller, out_ptr, out_cap, &bytes) < 0 {
+        return FAILURE;
+    }
+
+    i32::try_from(entities.len()).unwrap_or(i32::MAX)
+}
+
+fn emit_event(mut caller: Caller<'_, HostState>, event: i64, entity: i64) -> i32 {
+    let Some(id) = NonZeroU64::new(event as u64) else {
+        return FAILURE;
+    };
+    let Some(entity) = entity_from_bits(entity) else {
+        return FAILURE;
+    };
+
+    match caller.data_mut().world.get_resource_mut::<Events>() {
+        Some(mut events) => {
+            events.emit(Event::new(EventId::new(id), entity));
+            0
+        }
+        None => FAILURE,
+    }
+}
+
+/// Creates linker with all host functions.
+pub(crate) fn linker(engine: &Engine) -> Linker<HostState> {
+    let mut linker = Linker::new(engine);
+
+    linker
+        .func_wrap("arcana", "log", log)
+        .and_then(|l| l.func_wrap("arcana", "entity", entity))
+        .and_then(|l| l.func_wrap("arcana", "delta_time", delta_time))
+        .and_then(|l| l.func_wrap("arcana", "spawn", spawn))
+        .and_then(|l| l.func_wrap("arcana", "despawn", despawn))
+        .and_then(|l| l.func_wrap("arcana", "get_component", get_component))
+        .and_then(|l| l.func_wrap("arcana", "set_component", set_component))
+        .and_then(|l| l.func_wrap("arcana", "remove_component", remove_component))
+        .and_then(|l| l.func_wrap("arcana", "query", query))
+        .and_then(|l| l.func_wrap("arcana", "emit_event", emit_event))
+        .expect("Host function names are unique");
+
+    linker
+}
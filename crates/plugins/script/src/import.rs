@@ -0,0 +1,60 @@
+use std::{fmt::Display, path::Path};
+
+use arcana::{
+    assets::{
+        import::{AssetDependencies, AssetSources, ImportError, Importer},
+        ScriptModule,
+    },
+    ident, name, Ident, Name,
+};
+use wasmi::{Engine, Module};
+
+/// Imports WebAssembly modules as scripts.
+///
+/// Modules are validated on import, so broken ones are reported early.
+#[arcana::importer]
+#[derive(Default)]
+pub struct ScriptImporter;
+
+impl ScriptImporter {
+    pub fn new() -> Self {
+        ScriptImporter
+    }
+}
+
+impl Importer for ScriptImporter {
+    fn name(&self) -> Name {
+        name!(wasm_script)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["wasm"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["wasm"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(script)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        _sources: &mut dyn AssetSources,
+        _dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let wasm = std::fs::read(source).map_err(error_to_reason)?;
+        let module = ScriptModule::new(&wasm).map_err(error_to_reason)?;
+        Module::new(&Engine::default(), module.wasm()).map_err(error_to_reason)?;
+        std::fs::write(output, &wasm).map_err(error_to_reason)
+    }
+}
+
+fn error_to_reason<E: Display>(error: E) -> ImportError {
+    ImportError::Other {
+        reason: error.to_string(),
+    }
+}
@@ -0,0 +1,43 @@
+//! This plugin runs gameplay scripts compiled to WebAssembly.
+//!
+//! Attach [`Script`] component with handle to a `.wasm` module to an entity.
+//! Module exports `update` function called every frame and optional `init`.
+//! Scripts are sandboxed: they access the world only through the host API
+//! imported from `arcana` module, and run with limited fuel per call.
+//!
+//! Host API:
+//!
+//! - `log(level: i32, ptr: i32, len: i32)`
+//! - `entity() -> i64` - entity the script is attached to.
+//! - `delta_time() -> f32`
+//! - `spawn() -> i64`
+//! - `despawn(entity: i64) -> i32`
+//! - `get_component(entity: i64, name_ptr: i32, name_len: i32, out_ptr: i32, out_cap: i32) -> i32`
+//! - `set_component(entity: i64, name_ptr: i32, name_len: i32, json_ptr: i32, json_len: i32) -> i32`
+//! - `remove_component(entity: i64, name_ptr: i32, name_len: i32) -> i32`
+//! - `query(name_ptr: i32, name_len: i32, out_ptr: i32, out_cap: i32) -> i32`
+//! - `emit_event(event: i64, entity: i64) -> i32`
+//!
+//! Components are accessible by names they are registered with
+//! in [`ScriptComponents`] resource, values are JSON-encoded.
+//! Scripts are restarted when their module is reloaded,
+//! so designers can iterate without recompiling plugins.
+
+use arcana::World;
+
+arcana::declare_plugin!();
+
+mod host;
+mod import;
+mod script;
+
+pub use self::{
+    import::ScriptImporter,
+    script::{Script, ScriptComponents},
+};
+
+#[arcana::init]
+fn init_script(world: &mut World) {
+    world.insert_resource(ScriptComponents::new());
+    world.insert_resource(script::ScriptEngine::new());
+}
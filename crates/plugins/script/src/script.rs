@@ -0,0 +1,254 @@
+use arcana::{
+    assets::{Handle, ReloadedAssets, ScriptModule},
+    edict::{self, world::World, Component, Entities},
+    gametime::ClockStep,
+    hashbrown::HashMap,
+    reflect::{ComponentReflect, Reflect},
+    EntityId,
+};
+use wasmi::{Config, Engine, Linker, Module, Store, TypedFunc};
+
+use crate::host::{linker, HostState};
+
+/// Fuel given to a script for each call.
+/// Script that runs out of fuel is stopped,
+/// so endless loop can't hang the game.
+const FUEL_PER_CALL: u64 = 10_000_000;
+
+#[derive(Clone, Copy)]
+pub(crate) struct ScriptComponent {
+    pub reflect: ComponentReflect,
+    pub remove: fn(&mut World, EntityId),
+}
+
+/// Components that scripts can access by name.
+pub struct ScriptComponents {
+    components: HashMap<String, ScriptComponent>,
+}
+
+impl ScriptComponents {
+    pub fn new() -> Self {
+        ScriptComponents {
+            components: HashMap::new(),
+        }
+    }
+
+    /// Makes component accessible to scripts under the name.
+    pub fn register<T>(&mut self, name: impl Into<String>)
+    where
+        T: Reflect + Component + Default + Send + Sync,
+    {
+        self.components.insert(
+            name.into(),
+            ScriptComponent {
+                reflect: ComponentReflect::with_default::<T>(),
+                remove: |world, entity| {
+                    let _ = world.drop::<T>(entity);
+                },
+            },
+        );
+    }
+
+    pub fn unregister(&mut self, name: &str) {
+        self.components.remove(name);
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<ScriptComponent> {
+        self.components.get(name).copied()
+    }
+}
+
+/// Compiles script modules and links them with host API.
+pub(crate) struct ScriptEngine {
+    engine: Engine,
+    linker: Linker<HostState>,
+}
+
+impl ScriptEngine {
+    pub fn new() -> Self {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+
+        let engine = Engine::new(&config);
+        let linker = linker(&engine);
+        ScriptEngine { engine, linker }
+    }
+
+    fn instantiate(&self, module: &ScriptModule) -> Result<ScriptInstance, wasmi::Error> {
+        let module = Module::new(&self.engine, module.wasm())?;
+
+        let mut store = Store::new(
+            &self.engine,
+            HostState {
+                world: World::new(),
+                entity: None,
+                delta_time: 0.0,
+            },
+        );
+
+        store.set_fuel(FUEL_PER_CALL)?;
+        let instance = self
+            .linker
+            .instantiate(&mut store, &module)?
+            .start(&mut store)?;
+
+        Ok(ScriptInstance {
+            init: instance.get_typed_func(&store, "init").ok(),
+            update: instance.get_typed_func(&store, "update").ok(),
+            store,
+        })
+    }
+}
+
+struct ScriptInstance {
+    store: Store<HostState>,
+
+    /// Called once after module is instantiated.
+    init: Option<TypedFunc<(), ()>>,
+
+    /// Called every frame.
+    update: Option<TypedFunc<(), ()>>,
+}
+
+impl ScriptInstance {
+    fn run(
+        &mut self,
+        world: &mut World,
+        entity: EntityId,
+        delta_time: f32,
+    ) -> Result<(), wasmi::Error> {
+        let state = self.store.data_mut();
+        state.entity = Some(entity);
+        state.delta_time = delta_time;
+
+        // World is moved into the store so that host functions can access it.
+        std::mem::swap(world, &mut state.world);
+        let result = self.call();
+        std::mem::swap(world, &mut self.store.data_mut().world);
+
+        result
+    }
+
+    fn call(&mut self) -> Result<(), wasmi::Error> {
+        if let Some(init) = self.init.take() {
+            self.store.set_fuel(FUEL_PER_CALL)?;
+            init.call(&mut self.store, ())?;
+        }
+
+        if let Some(update) = self.update {
+            self.store.set_fuel(FUEL_PER_CALL)?;
+            update.call(&mut self.store, ())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs WebAssembly script attached to the entity.
+///
+/// Script exports `update` function that is called every frame,
+/// and optionally `init` function called once before first `update`.
+/// Scripts are restarted when module asset is reloaded, losing their state.
+/// Script that traps is stopped until reloaded.
+#[derive(Component)]
+pub struct Script {
+    handle: Handle<ScriptModule>,
+    instance: Option<ScriptInstance>,
+    failed: bool,
+}
+
+impl Script {
+    pub fn new(module: Handle<ScriptModule>) -> Self {
+        Script {
+            handle: module,
+            instance: None,
+            failed: false,
+        }
+    }
+
+    /// Returns true if script trapped or failed to instantiate.
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+
+    /// Restarts the script, also clearing failure.
+    pub fn restart(&mut self) {
+        self.instance = None;
+        self.failed = false;
+    }
+}
+
+#[arcana::system]
+fn run_scripts(world: &mut World) {
+    let delta_time = world.expect_resource::<ClockStep>().step.as_secs_f32();
+
+    let reloaded = world
+        .get_resource::<ReloadedAssets>()
+        .map_or_else(Vec::new, |reloaded| reloaded.iter().collect());
+
+    let entities = world
+        .view::<Entities>()
+        .with::<Script>()
+        .iter()
+        .map(|e| e.id())
+        .collect::<Vec<_>>();
+
+    for entity in entities {
+        // Instance is taken out of the component while script runs.
+        let mut instance = {
+            let Ok(script) = world.get::<&mut Script>(entity) else {
+                continue;
+            };
+
+            if script
+                .handle
+                .id()
+                .map_or(false, |id| reloaded.contains(&id))
+            {
+                tracing::info!("Script of entity {entity} is reloaded");
+                script.restart();
+            }
+
+            if script.failed {
+                continue;
+            }
+
+            match script.instance.take() {
+                Some(instance) => instance,
+                None => {
+                    let Some(module) = script.handle.get() else {
+                        continue;
+                    };
+
+                    let result = world.expect_resource::<ScriptEngine>().instantiate(&module);
+
+                    match result {
+                        Ok(instance) => instance,
+                        Err(err) => {
+                            tracing::error!(
+                                "Failed to instantiate script of entity {entity}: {err}"
+                            );
+                            if let Ok(script) = world.get::<&mut Script>(entity) {
+                                script.failed = true;
+                            }
+                            continue;
+                        }
+                    }
+                }
+            }
+        };
+
+        let result = instance.run(world, entity, delta_time);
+
+        // Script may have removed the component or despawned its entity.
+        if let Ok(script) = world.get::<&mut Script>(entity) {
+            match result {
+                Ok(()) => script.instance = Some(instance),
+                Err(err) => {
+                    tracing::error!("Script of entity {entity} failed: {err}");
+                    script.failed = true;
+                }
+            }
+        }
+    }
+}
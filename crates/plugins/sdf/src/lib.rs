@@ -1,9 +1,14 @@
-use std::mem::size_of;
+use std::{mem::size_of, ops::Range};
 
 use arcana::{
-    edict::{self, Component, EntityId, World},
+    color::Color,
+    edict::{self, epoch::EpochId, Component, EntityId, World},
     mev::{self, Arguments, DeviceRepr},
-    render::{Render, RenderBuilderContext, RenderContext, RenderError, RenderGraph, TargetId},
+    render::{
+        Render, RenderBuilderContext, RenderContext, RenderError, RenderGraph, TargetId,
+        FRAMES_IN_FLIGHT,
+    },
+    stats::count_culled,
 };
 
 // macro_rules! print_layout {
@@ -21,7 +26,7 @@ use arcana::{
 //     };
 // }
 
-use camera::Camera2;
+use camera::{Camera2, RenderLayers, ViewBounds};
 use scene::dim2::Global;
 
 arcana::export_arcana_plugin! {
@@ -31,34 +36,79 @@ arcana::export_arcana_plugin! {
     }
 }
 
+/// Color of shapes created without explicit color.
+const DEFAULT_COLOR: Color = Color::linear(0.604, 0.033, 1.0, 1.0);
+
+/// Color of the area not covered by shapes.
+const BACKGROUND: Color = Color::linear(0.214, 0.033, 0.01, 1.0);
+
 #[derive(Clone, Copy, Component)]
 pub struct Shape {
-    pub color: [f32; 4],
+    pub color: Color,
     pub transform: na::Affine2<f32>,
     pub kind: ShapeKind,
+
+    /// How shape color is combined with shapes below.
+    pub blend: BlendMode,
+
+    /// Shapes on greater layers are drawn on top.
+    pub layer: i32,
+
+    /// Order of shapes within the layer.
+    /// Shapes with greater z are drawn on top.
+    pub z: f32,
 }
 
 impl Shape {
     pub fn rect(width: f32, height: f32) -> Self {
         Self {
-            color: [0.8, 0.2, 1.0, 1.0],
+            color: DEFAULT_COLOR,
             transform: na::Affine2::identity(),
             kind: ShapeKind::Rect { width, height },
+            blend: BlendMode::Alpha,
+            layer: 0,
+            z: 0.0,
         }
     }
 
     pub fn circle(radius: f32) -> Self {
         Self {
-            color: [0.8, 0.2, 1.0, 1.0],
+            color: DEFAULT_COLOR,
             transform: na::Affine2::identity(),
             kind: ShapeKind::Circle { radius },
+            blend: BlendMode::Alpha,
+            layer: 0,
+            z: 0.0,
         }
     }
 
-    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+    pub fn with_color(mut self, color: Color) -> Self {
         self.color = color;
         self
     }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_z(mut self, z: f32) -> Self {
+        self.z = z;
+        self
+    }
+
+    /// Returns radius of the circle around the shape in shape space.
+    pub fn bounding_radius(&self) -> f32 {
+        match self.kind {
+            ShapeKind::Circle { radius } => radius,
+            ShapeKind::Rect { width, height } => (width * width + height * height).sqrt() * 0.5,
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -67,6 +117,37 @@ pub enum ShapeKind {
     Rect { width: f32, height: f32 },
 }
 
+/// Blending of the shape with shapes below it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Color with straight alpha is drawn over shapes below.
+    #[default]
+    Alpha,
+
+    /// Color with alpha premultiplied is drawn over shapes below.
+    Premultiplied,
+
+    /// Color scaled by alpha is added to shapes below.
+    /// Useful for glows and energy effects.
+    Additive,
+
+    /// Shapes below are multiplied by color.
+    /// Useful for shadows and tinting.
+    Multiply,
+}
+
+impl BlendMode {
+    /// Returns code of the mode used in shader.
+    fn code(&self) -> u32 {
+        match self {
+            BlendMode::Alpha => 0,
+            BlendMode::Premultiplied => 1,
+            BlendMode::Additive => 2,
+            BlendMode::Multiply => 3,
+        }
+    }
+}
+
 #[derive(DeviceRepr)]
 struct ShapeDevice {
     tr: mev::mat3,
@@ -74,7 +155,32 @@ struct ShapeDevice {
     color: mev::vec4,
     kind: u32,
     payload: u32,
-    layer: u32,
+    blend: u32,
+}
+
+/// Shape data as written to the device buffer.
+#[derive(Clone, Copy, PartialEq)]
+struct ShapeEntry {
+    tr: na::Matrix3<f32>,
+    color: Color,
+    kind: u32,
+    payload: u32,
+    blend: u32,
+}
+
+impl ShapeEntry {
+    fn as_device(&self) -> ShapeDevice {
+        let inv_tr = self.tr.try_inverse().unwrap();
+
+        ShapeDevice {
+            tr: self.tr.as_ref().into(),
+            inv_tr: inv_tr.as_ref().into(),
+            color: self.color.into(),
+            kind: self.kind,
+            payload: self.payload,
+            blend: self.blend,
+        }
+    }
 }
 
 #[derive(DeviceRepr)]
@@ -104,16 +210,41 @@ pub struct MainConstants {
     pub shape_count: u32,
 }
 
+/// Buffers of one of [`FRAMES_IN_FLIGHT`] frames
+/// with copies of the data written to them.
+struct Frame {
+    arguments: MainArguments,
+    shapes: Vec<ShapeEntry>,
+    circles: Vec<f32>,
+    rects: Vec<na::Vector2<f32>>,
+}
+
 pub struct SdfRender {
     camera: EntityId,
     target: TargetId<mev::Image>,
     pipeline: Option<mev::RenderPipeline>,
-    arguments: Option<MainArguments>,
     constants: MainConstants,
 
+    frames: Vec<Frame>,
+    frame: usize,
+
+    /// World epoch and target extent shapes were collected for.
+    /// Static scenes skip collecting shapes entirely.
+    collected: Option<(EpochId, mev::Extent2)>,
+
+    /// Number of shapes culled when shapes were collected.
+    culled: u32,
+
+    shapes: Vec<ShapeEntry>,
+    circles: Vec<f32>,
+    rects: Vec<na::Vector2<f32>>,
+
     shapes_device: Vec<<ShapeDevice as DeviceRepr>::Repr>,
     circles_device: Vec<<CirleDevice as DeviceRepr>::Repr>,
     rects_device: Vec<<RectDevice as DeviceRepr>::Repr>,
+
+    /// Visible shapes with their transforms, sorted back to front.
+    visible: Vec<(na::Matrix3<f32>, Shape)>,
 }
 
 impl SdfRender {
@@ -130,25 +261,110 @@ impl SdfRender {
             camera,
             target,
             pipeline: None,
-            arguments: None,
             constants: MainConstants {
-                background: mev::vec4(0.5, 0.2, 0.1, 1.0),
+                background: BACKGROUND.into(),
                 shape_count: 0,
                 camera: mev::mat3::from([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
             },
+            frames: Vec::new(),
+            frame: 0,
+            collected: None,
+            culled: 0,
+            shapes: Vec::new(),
+            circles: Vec::new(),
+            rects: Vec::new(),
             shapes_device: Vec::new(),
             circles_device: Vec::new(),
             rects_device: Vec::new(),
+            visible: Vec::new(),
         });
         target
     }
+
+    /// Collects visible shapes into `shapes`, `circles` and `rects`.
+    fn collect(&mut self, world: &World, dims: mev::Extent2) {
+        let camera = world
+            .try_view_one::<(&Global, &Camera2)>(self.camera)
+            .expect("Camera is missing");
+
+        let (camera, bounds, mask) = {
+            let (g, c) = camera.get().unwrap();
+
+            let view = c.view_to_world(&g.iso, dims.width(), dims.height());
+            (
+                <[[f32; 3]; 3]>::from(view.to_homogeneous()),
+                ViewBounds::new(&view),
+                c.layers,
+            )
+        };
+
+        self.visible.clear();
+
+        self.culled = 0;
+        let shapes = world.view::<(&Global, &Shape, Option<&RenderLayers>)>();
+        for (global, shape, layers) in shapes.iter() {
+            if !mask.renders(layers) {
+                continue;
+            }
+
+            let tr = global.iso.to_homogeneous() * shape.transform.matrix();
+
+            // Largest scale of the transform bounds the shape's circle.
+            let scale = tr.column(0).xy().norm().max(tr.column(1).xy().norm());
+            let center = na::Point2::new(tr[(0, 2)], tr[(1, 2)]);
+            if !bounds.intersects_circle(&center, shape.bounding_radius() * scale) {
+                self.culled += 1;
+                continue;
+            }
+
+            self.visible.push((tr, *shape));
+        }
+
+        // Shader composes shapes in order, so transparent ones blend
+        // with everything below them.
+        self.visible
+            .sort_by(|(_, a), (_, b)| a.layer.cmp(&b.layer).then(a.z.total_cmp(&b.z)));
+
+        self.shapes.clear();
+        self.circles.clear();
+        self.rects.clear();
+
+        for (tr, shape) in &self.visible {
+            self.shapes.push(ShapeEntry {
+                tr: *tr,
+                color: shape.color,
+                kind: match shape.kind {
+                    ShapeKind::Circle { .. } => 0,
+                    ShapeKind::Rect { .. } => 1,
+                },
+                payload: match shape.kind {
+                    ShapeKind::Circle { .. } => self.circles.len() as u32,
+                    ShapeKind::Rect { .. } => self.rects.len() as u32,
+                },
+                blend: shape.blend.code(),
+            });
+
+            match shape.kind {
+                ShapeKind::Circle { radius } => self.circles.push(radius),
+                ShapeKind::Rect { width, height } => {
+                    self.rects.push(na::Vector2::new(width / 2.0, height / 2.0))
+                }
+            }
+        }
+
+        self.constants = MainConstants {
+            background: BACKGROUND.into(),
+            camera: mev::mat3::from(camera),
+            shape_count: self.shapes.len() as u32,
+        };
+    }
 }
 
 impl Render for SdfRender {
     fn render(&mut self, world: &World, mut cx: RenderContext<'_, '_>) -> Result<(), RenderError> {
         let mut encoder = cx.new_command_encoder()?;
         let target = cx.write_target(self.target, &mut encoder).clone();
-        let pipeline = self.pipeline.get_or_insert_with(|| {
+        self.pipeline.get_or_insert_with(|| {
             let main_library = cx
                 .device()
                 .new_shader_library(mev::LibraryDesc {
@@ -188,164 +404,119 @@ impl Render for SdfRender {
 
         let dims = target.extent().expect_2d();
 
-        let camera = world
-            .try_view_one::<(&Global, &Camera2)>(self.camera)
-            .expect("Camera is missing");
-
-        let camera = {
-            let (g, c) = camera.get().unwrap();
-
-            let viewport = c
-                .viewport
-                .transform(1.0, dims.width() as f32 / dims.height() as f32);
-
-            <[[f32; 3]; 3]>::from((g.iso * viewport).to_homogeneous())
-        };
-
-        let shapes = world.view::<(&Global, &Shape)>();
-        let shapes_count = shapes.iter().count();
+        // Any mutable access to components advances world epoch,
+        // so unchanged epoch means shapes and camera are the same.
+        let collected = (world.epoch(), dims);
+        if self.collected != Some(collected) {
+            self.collect(world, dims);
+            self.collected = Some(collected);
+        }
 
-        let arguments = self.arguments.get_or_insert_with(|| {
-            let shapes = cx
-                .device()
-                .new_buffer(mev::BufferDesc {
-                    size: size_of::<<ShapeDevice as DeviceRepr>::Repr>()
-                        * shapes_count.next_power_of_two(),
-                    name: "shapes",
-                    usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
-                    memory: mev::Memory::Shared,
-                })
-                .unwrap();
+        count_culled(world, self.culled);
+
+        let idx = self.frame % FRAMES_IN_FLIGHT;
+        self.frame = self.frame.wrapping_add(1);
+
+        if self.frames.len() <= idx {
+            self.frames.push(Frame {
+                arguments: MainArguments {
+                    shapes: new_storage(cx.device(), "shapes", 0),
+                    circles: new_storage(cx.device(), "circles", 0),
+                    rects: new_storage(cx.device(), "rects", 0),
+                },
+                shapes: Vec::new(),
+                circles: Vec::new(),
+                rects: Vec::new(),
+            });
+        }
 
-            let circles = cx
-                .device()
-                .new_buffer(mev::BufferDesc {
-                    size: size_of::<<CirleDevice as DeviceRepr>::Repr>()
-                        * shapes_count.next_power_of_two(),
-                    name: "circles",
-                    usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
-                    memory: mev::Memory::Shared,
-                })
-                .unwrap();
+        let frame = &mut self.frames[idx];
 
-            let rects = cx
-                .device()
-                .new_buffer(mev::BufferDesc {
-                    size: size_of::<<RectDevice as DeviceRepr>::Repr>()
-                        * shapes_count.next_power_of_two(),
-                    name: "rects",
-                    usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
-                    memory: mev::Memory::Shared,
-                })
-                .unwrap();
-            MainArguments {
-                shapes,
-                circles,
-                rects,
-            }
-        });
+        let shape_size = size_of::<<ShapeDevice as DeviceRepr>::Repr>();
+        let circle_size = size_of::<<CirleDevice as DeviceRepr>::Repr>();
+        let rect_size = size_of::<<RectDevice as DeviceRepr>::Repr>();
 
-        if arguments.shapes.size() < size_of::<<ShapeDevice as DeviceRepr>::Repr>() * shapes_count {
-            arguments.shapes = cx
-                .device()
-                .new_buffer(mev::BufferDesc {
-                    size: size_of::<<ShapeDevice as DeviceRepr>::Repr>()
-                        * shapes_count.next_power_of_two(),
-                    name: "shapes",
-                    usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
-                    memory: mev::Memory::Shared,
-                })
-                .unwrap();
+        // Reallocated buffers have nothing written yet.
+        if reserve(
+            cx.device(),
+            &mut frame.arguments.shapes,
+            "shapes",
+            shape_size * self.shapes.len(),
+        ) {
+            frame.shapes.clear();
         }
-
-        if arguments.circles.size() < size_of::<<CirleDevice as DeviceRepr>::Repr>() * shapes_count
-        {
-            arguments.circles = cx
-                .device()
-                .new_buffer(mev::BufferDesc {
-                    size: size_of::<<CirleDevice as DeviceRepr>::Repr>()
-                        * shapes_count.next_power_of_two(),
-                    name: "circles",
-                    usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
-                    memory: mev::Memory::Shared,
-                })
-                .unwrap();
+        if reserve(
+            cx.device(),
+            &mut frame.arguments.circles,
+            "circles",
+            circle_size * self.circles.len(),
+        ) {
+            frame.circles.clear();
         }
-
-        if arguments.rects.size() < size_of::<<RectDevice as DeviceRepr>::Repr>() * shapes_count {
-            arguments.rects = cx
-                .device()
-                .new_buffer(mev::BufferDesc {
-                    size: size_of::<<RectDevice as DeviceRepr>::Repr>()
-                        * shapes_count.next_power_of_two(),
-                    name: "rects",
-                    usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
-                    memory: mev::Memory::Shared,
-                })
-                .unwrap();
+        if reserve(
+            cx.device(),
+            &mut frame.arguments.rects,
+            "rects",
+            rect_size * self.rects.len(),
+        ) {
+            frame.rects.clear();
         }
 
-        self.constants = MainConstants {
-            background: mev::vec4(0.5, 0.2, 0.1, 1.0),
-            camera: mev::mat3::from(camera),
-            shape_count: shapes_count as u32,
-        };
+        {
+            let mut copy = encoder.copy();
 
-        self.shapes_device.clear();
-        self.circles_device.clear();
-        self.rects_device.clear();
-        for (global, shape) in shapes.iter() {
-            let tr = global.iso.to_homogeneous() * shape.transform.matrix();
-            let inv_tr = tr.try_inverse().unwrap();
+            if let Some(range) = changed_range(&frame.shapes, &self.shapes) {
+                self.shapes_device.clear();
+                self.shapes_device.extend(
+                    self.shapes[range.clone()]
+                        .iter()
+                        .map(|shape| shape.as_device().as_repr()),
+                );
+                copy.write_buffer_slice(
+                    frame.arguments.shapes.slice(range.start * shape_size..),
+                    &self.shapes_device,
+                );
+            }
 
-            self.shapes_device.push(
-                ShapeDevice {
-                    kind: match shape.kind {
-                        ShapeKind::Circle { .. } => 0,
-                        ShapeKind::Rect { .. } => 1,
-                    },
-                    payload: match shape.kind {
-                        ShapeKind::Circle { .. } => self.circles_device.len() as u32,
-                        ShapeKind::Rect { .. } => self.rects_device.len() as u32,
-                    },
-                    color: mev::vec(shape.color),
-                    tr: tr.as_ref().into(),
-                    inv_tr: inv_tr.as_ref().into(),
-                    layer: 0,
-                }
-                .as_repr(),
-            );
+            if let Some(range) = changed_range(&frame.circles, &self.circles) {
+                self.circles_device.clear();
+                self.circles_device.extend(
+                    self.circles[range.clone()]
+                        .iter()
+                        .map(|&radius| CirleDevice { radius }.as_repr()),
+                );
+                copy.write_buffer_slice(
+                    frame.arguments.circles.slice(range.start * circle_size..),
+                    &self.circles_device,
+                );
+            }
 
-            match shape.kind {
-                ShapeKind::Circle { radius } => {
-                    self.circles_device.push(CirleDevice { radius }.as_repr());
-                }
-                ShapeKind::Rect { width, height } => {
-                    self.rects_device.push(
+            if let Some(range) = changed_range(&frame.rects, &self.rects) {
+                self.rects_device.clear();
+                self.rects_device
+                    .extend(self.rects[range.clone()].iter().map(|half| {
                         RectDevice {
-                            half: mev::vec2(width / 2.0, height / 2.0),
+                            half: mev::vec2(half.x, half.y),
                         }
-                        .as_repr(),
-                    );
-                }
+                        .as_repr()
+                    }));
+                copy.write_buffer_slice(
+                    frame.arguments.rects.slice(range.start * rect_size..),
+                    &self.rects_device,
+                );
             }
         }
 
-        {
-            let mut copy = encoder.copy();
-            copy.write_buffer_slice(&arguments.shapes, &self.shapes_device);
-            copy.write_buffer_slice(&arguments.circles, &self.circles_device);
-            copy.write_buffer_slice(&arguments.rects, &self.rects_device);
-        }
+        frame.shapes.clone_from(&self.shapes);
+        frame.circles.clone_from(&self.circles);
+        frame.rects.clone_from(&self.rects);
 
         let mut render = encoder.render(mev::RenderPassDesc {
-            color_attachments: &[
-                mev::AttachmentDesc::new(&target).clear(mev::ClearColor(0.0, 0.0, 0.0, 1.0))
-            ],
+            color_attachments: &[mev::AttachmentDesc::new(&target).clear(Color::BLACK.into())],
             ..Default::default()
         });
-        render.with_pipeline(pipeline);
-        render.with_arguments(0, arguments);
+        render.with_pipeline(self.pipeline.as_ref().unwrap());
+        render.with_arguments(0, &frame.arguments);
         render.with_constants(&self.constants);
 
         render.with_viewport(
@@ -359,3 +530,54 @@ impl Render for SdfRender {
         Ok(())
     }
 }
+
+fn new_storage(device: &mev::Device, name: &str, size: usize) -> mev::Buffer {
+    device
+        .new_buffer(mev::BufferDesc {
+            size: size.next_power_of_two(),
+            name,
+            usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
+            memory: mev::Memory::Shared,
+        })
+        .unwrap()
+}
+
+/// Grows buffer to fit `size` bytes.
+/// Returns true if buffer was reallocated.
+fn reserve(device: &mev::Device, buffer: &mut mev::Buffer, name: &str, size: usize) -> bool {
+    if buffer.size() >= size {
+        return false;
+    }
+    *buffer = new_storage(device, name, size);
+    true
+}
+
+/// Returns range of items that differ from items written before.
+///
+/// Items past the new length are left in the buffer,
+/// shader never reads them.
+fn changed_range<T: PartialEq>(written: &[T], items: &[T]) -> Option<Range<usize>> {
+    let start = written
+        .iter()
+        .zip(items)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    if start == items.len() {
+        return None;
+    }
+
+    let end = if written.len() == items.len() {
+        let same = written
+            .iter()
+            .rev()
+            .zip(items.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        items.len() - same
+    } else {
+        items.len()
+    };
+
+    Some(start..end)
+}
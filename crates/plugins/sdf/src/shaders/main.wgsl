@@ -30,7 +30,7 @@ struct Shape {
     color: vec4f,
     kind: u32,
     payload: u32,
-    layer: u32,
+    blend: u32,
 }
 
 
@@ -70,8 +70,32 @@ fn rect_sdf(rect: Rect, sample: vec2f) -> f32 {
     return length(max(d, vec2f(0f))) + min(max(d.x, d.y), 0f);
 }
 
+// Composes shape color over color below it.
+fn compose(mode: u32, src: vec4f, dst: vec4f) -> vec4f {
+    switch mode {
+        // Premultiplied alpha.
+        case 1u: {
+            return vec4f(src.rgb + dst.rgb * (1f - src.a), src.a + dst.a * (1f - src.a));
+        }
+        // Additive.
+        case 2u: {
+            return vec4f(dst.rgb + src.rgb * src.a, dst.a);
+        }
+        // Multiply.
+        case 3u: {
+            return vec4f(dst.rgb * mix(vec3f(1f), src.rgb, src.a), dst.a);
+        }
+        // Straight alpha.
+        default: {
+            return vec4f(src.rgb * src.a + dst.rgb * (1f - src.a), src.a + dst.a * (1f - src.a));
+        }
+    }
+}
+
 @fragment
 fn fs_main(@location(0) sample: vec2f) -> @location(0) vec4f {
+    // Shapes are sorted back to front.
+    var color = pc.background;
     for (var i = 0u; i < pc.shape_count; i++) {
         let shape = shapes[i];
         let shape_sample = shape.inv_tr * vec3f(sample, 1f);
@@ -89,12 +113,13 @@ fn fs_main(@location(0) sample: vec2f) -> @location(0) vec4f {
 
             let dd_w = shape.tr * vec3f(ddd, 0f);
 
+            var shape_color = shape.color;
             if length(dd_w) < 0.1f {
-                return vec4f(0f, 0f, 0f, 1f);
+                shape_color = vec4f(0f, 0f, 0f, 1f);
             }
-            return shape.color;
+            color = compose(shape.blend, shape_color, color);
         }
     }
 
-    return pc.background;
+    return color;
 }
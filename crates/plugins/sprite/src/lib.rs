@@ -0,0 +1,438 @@
+//! This plugin draws textured sprites.
+//!
+//! All sprites are drawn by `DrawSprites` job with single instanced pipeline.
+//! Sprites are sorted by layer and then by z,
+//! consecutive sprites that use the same image are drawn in one batch.
+//! Packing sprites into atlases keeps number of batches low.
+//!
+//! It is an alternative to SDF shapes for texture-heavy 2D games.
+
+use std::mem::size_of;
+
+use arcana::{
+    assets::{Atlas, SpriteSheet},
+    color::Color,
+    edict::{self, world::World, Component},
+    hashbrown::HashMap,
+    mev::{self, Arguments, DeviceRepr},
+    model::{ColorModel, Model, Value},
+    render::current_camera,
+    stats::{count_culled, count_draws},
+    texture::cached_sampler,
+    work::{Exec, Image2D, Job, JobDesc, JobIdx, Planner},
+};
+use camera::{Camera2, RenderLayers, ViewBounds};
+use scene::dim2::Global;
+
+arcana::declare_plugin!([scene ..., camera ...]);
+
+/// Textured quad centered at entity's `Global` position.
+#[derive(Clone, Component)]
+pub struct Sprite {
+    pub image: mev::Image,
+
+    /// Region of the image as `[u0, v0, u1, v1]`.
+    pub uv: [f32; 4],
+
+    /// Size of the sprite in world units.
+    pub size: na::Vector2<f32>,
+
+    /// Multiplied with texture color.
+    pub color: Color,
+
+    pub flip_x: bool,
+    pub flip_y: bool,
+
+    /// Sprites on greater layers are drawn on top.
+    pub layer: i32,
+
+    /// Order of sprites within the layer.
+    /// Sprites with greater z are drawn on top.
+    pub z: f32,
+}
+
+impl Sprite {
+    /// Returns sprite that shows whole image.
+    pub fn new(image: mev::Image, size: na::Vector2<f32>) -> Self {
+        Sprite {
+            image,
+            uv: [0.0, 0.0, 1.0, 1.0],
+            size,
+            color: Color::WHITE,
+            flip_x: false,
+            flip_y: false,
+            layer: 0,
+            z: 0.0,
+        }
+    }
+
+    /// Returns sprite that shows named region of the atlas.
+    ///
+    /// Size is derived from region size in pixels.
+    pub fn from_atlas(atlas: &Atlas, name: &str, pixels_per_unit: f32) -> Option<Self> {
+        let region = atlas.region(name)?;
+        let size = na::Vector2::new(region.rect.width as f32, region.rect.height as f32);
+
+        Some(Sprite {
+            uv: region.uv,
+            ..Sprite::new(atlas.page(region).clone(), size / pixels_per_unit)
+        })
+    }
+
+    /// Returns sprite that shows frame of the sprite sheet.
+    ///
+    /// Size is derived from frame size in pixels.
+    pub fn from_sheet(sheet: &SpriteSheet, frame: u32, pixels_per_unit: f32) -> Option<Self> {
+        let uv = sheet.frame_uv(frame)?;
+        let rect = sheet.frames[frame as usize].rect;
+        let size = na::Vector2::new(rect.width as f32, rect.height as f32);
+
+        Some(Sprite {
+            uv,
+            ..Sprite::new(sheet.image.clone(), size / pixels_per_unit)
+        })
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn with_z(mut self, z: f32) -> Self {
+        self.z = z;
+        self
+    }
+
+    pub fn flipped(mut self, x: bool, y: bool) -> Self {
+        self.flip_x = x;
+        self.flip_y = y;
+        self
+    }
+
+    /// Region of the image with flips applied.
+    fn flipped_uv(&self) -> [f32; 4] {
+        let [mut u0, mut v0, mut u1, mut v1] = self.uv;
+        if self.flip_x {
+            std::mem::swap(&mut u0, &mut u1);
+        }
+        if self.flip_y {
+            std::mem::swap(&mut v0, &mut v1);
+        }
+        [u0, v0, u1, v1]
+    }
+}
+
+#[derive(DeviceRepr)]
+struct SpriteInstance {
+    tr: mev::mat3,
+    uv: mev::vec4,
+    color: mev::vec4,
+}
+
+#[derive(mev::Arguments)]
+struct SpriteArguments {
+    #[mev(storage, vertex)]
+    instances: mev::Buffer,
+    #[mev(fragment)]
+    sampler: mev::Sampler,
+    #[mev(fragment)]
+    texture: mev::Image,
+}
+
+#[derive(mev::DeviceRepr)]
+struct SpriteConstants {
+    camera: mev::mat3,
+}
+
+/// Range of instances that use the same image.
+struct Batch {
+    image: mev::Image,
+    first: u32,
+    count: u32,
+}
+
+/// Sprites prepared for single job node.
+struct Frame {
+    constants: SpriteConstants,
+    instances: Vec<<SpriteInstance as DeviceRepr>::Repr>,
+    batches: Vec<Batch>,
+    buffer: Option<mev::Buffer>,
+}
+
+#[arcana::job]
+pub struct DrawSprites {
+    pipeline: Option<mev::RenderPipeline>,
+    frames: HashMap<JobIdx, Frame>,
+
+    /// Reused for sorting.
+    order: Vec<(i32, f32, usize)>,
+}
+
+impl DrawSprites {
+    pub fn desc() -> JobDesc {
+        arcana::job_desc! [
+            background: in Model::Color(ColorModel::Srgba),
+            main: +Image2D,
+        ]
+    }
+
+    pub fn new() -> Self {
+        DrawSprites {
+            pipeline: None,
+            frames: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+}
+
+impl Job for DrawSprites {
+    fn plan(&mut self, mut planner: Planner<'_>, world: &mut World) {
+        let idx = planner.idx();
+
+        let Some(target) = planner.create::<Image2D>().copied() else {
+            return;
+        };
+
+        let frame = self.frames.entry(idx).or_insert_with(|| Frame {
+            constants: SpriteConstants {
+                camera: mev::mat3::from([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]),
+            },
+            instances: Vec::new(),
+            batches: Vec::new(),
+            buffer: None,
+        });
+
+        frame.instances.clear();
+        frame.batches.clear();
+
+        let camera = match current_camera(world) {
+            Some(entity) => world
+                .try_view_one::<(&Global, &Camera2)>(entity)
+                .ok()
+                .and_then(|camera| camera.get().map(|(g, c)| (*g, *c))),
+            None => world
+                .view::<(&Global, &Camera2)>()
+                .iter()
+                .next()
+                .map(|(g, c)| (*g, *c)),
+        };
+        let Some((global, camera)) = camera else {
+            return;
+        };
+
+        let view_to_world =
+            camera.view_to_world(&global.iso, target.extent.width(), target.extent.height());
+        let bounds = ViewBounds::new(&view_to_world);
+
+        // Camera transform maps view to world, sprites need the opposite.
+        let view = view_to_world.to_homogeneous();
+        let Some(view) = view.try_inverse() else {
+            return;
+        };
+        frame.constants.camera = view.as_ref().into();
+
+        let sprites = world.view::<(&Global, &Sprite, Option<&RenderLayers>)>();
+        let sprites = sprites
+            .iter()
+            .filter(|(_, _, layers)| camera.layers.renders(*layers))
+            .map(|(global, sprite, _)| (global, sprite))
+            .collect::<Vec<_>>();
+
+        let mut culled = 0;
+
+        self.order.clear();
+        self.order.extend(
+            sprites
+                .iter()
+                .enumerate()
+                .filter(|(_, (global, sprite))| {
+                    let center = na::Point2::from(global.iso.translation.vector);
+                    let visible = bounds.intersects_circle(&center, sprite.size.norm() * 0.5);
+                    culled += u32::from(!visible);
+                    visible
+                })
+                .map(|(idx, (_, sprite))| (sprite.layer, sprite.z, idx)),
+        );
+
+        count_culled(world, culled);
+        self.order
+            .sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+        for &(_, _, idx) in &self.order {
+            let (global, sprite) = sprites[idx];
+
+            let scale =
+                na::Matrix3::from_diagonal(&na::Vector3::new(sprite.size.x, sprite.size.y, 1.0));
+            let tr = global.iso.to_homogeneous() * scale;
+
+            frame.instances.push(
+                SpriteInstance {
+                    tr: tr.as_ref().into(),
+                    uv: mev::vec(sprite.flipped_uv()),
+                    color: sprite.color.into(),
+                }
+                .as_repr(),
+            );
+
+            let instance = frame.instances.len() as u32 - 1;
+            match frame.batches.last_mut() {
+                Some(batch) if batch.image == sprite.image => batch.count += 1,
+                _ => frame.batches.push(Batch {
+                    image: sprite.image.clone(),
+                    first: instance,
+                    count: 1,
+                }),
+            }
+        }
+    }
+
+    fn exec(&mut self, runner: Exec<'_>, world: &mut World) {
+        let Some(target) = runner.create::<Image2D>() else {
+            return;
+        };
+
+        let Some(frame) = self.frames.get_mut(&runner.idx()) else {
+            return;
+        };
+
+        let pipeline = self.pipeline.get_or_insert_with(|| {
+            let library = runner
+                .device()
+                .new_shader_library(mev::LibraryDesc {
+                    name: "sprite",
+                    input: mev::include_library!(
+                        "shaders/sprite.wgsl" as mev::ShaderLanguage::Wgsl
+                    ),
+                })
+                .unwrap();
+
+            runner
+                .device()
+                .new_render_pipeline(mev::RenderPipelineDesc {
+                    name: "sprite",
+                    vertex_shader: mev::Shader {
+                        library: library.clone(),
+                        entry: "vs_main".into(),
+                    },
+                    vertex_attributes: vec![],
+                    vertex_layouts: vec![],
+                    primitive_topology: mev::PrimitiveTopology::Triangle,
+                    raster: Some(mev::RasterDesc {
+                        fragment_shader: Some(mev::Shader {
+                            library,
+                            entry: "fs_main".into(),
+                        }),
+                        color_targets: vec![mev::ColorTargetDesc {
+                            format: target.format(),
+                            blend: Some(mev::BlendDesc::default()),
+                        }],
+                        depth_stencil: None,
+                        front_face: mev::FrontFace::default(),
+                        culling: mev::Culling::None,
+                    }),
+                    arguments: &[SpriteArguments::LAYOUT],
+                    constants: SpriteConstants::SIZE,
+                })
+                .unwrap()
+        });
+
+        // Sprites are usually pixel art, so texels are not blurred.
+        let sampler = match cached_sampler(
+            world,
+            runner.device(),
+            mev::SamplerDesc {
+                min_filter: mev::Filter::Nearest,
+                mag_filter: mev::Filter::Nearest,
+                address_mode: [mev::AddressMode::ClampToEdge; 3],
+                ..mev::SamplerDesc::new()
+            },
+        ) {
+            Ok(sampler) => sampler,
+            Err(err) => {
+                arcana::tracing::error!("Failed to create sprite sampler: {err:?}");
+                return;
+            }
+        };
+
+        let background = match *runner.param("background") {
+            Value::Color(c) => Color::from(c),
+            _ => Color::BLACK,
+        };
+
+        let encoder = runner.new_encoder();
+
+        let size = size_of::<<SpriteInstance as DeviceRepr>::Repr>() * frame.instances.len();
+
+        if !frame.instances.is_empty() {
+            let buffer = match &mut frame.buffer {
+                Some(buffer) if buffer.size() >= size => buffer,
+                slot => slot.insert(
+                    runner
+                        .device()
+                        .new_buffer(mev::BufferDesc {
+                            size: size.next_power_of_two(),
+                            name: "sprites",
+                            usage: mev::BufferUsage::STORAGE | mev::BufferUsage::TRANSFER_DST,
+                            memory: mev::Memory::Shared,
+                        })
+                        .unwrap(),
+                ),
+            };
+
+            encoder.barrier(
+                mev::PipelineStages::VERTEX_SHADER,
+                mev::PipelineStages::TRANSFER,
+            );
+            encoder
+                .copy()
+                .write_buffer_slice(buffer.slice(..), &frame.instances);
+            encoder.barrier(
+                mev::PipelineStages::TRANSFER,
+                mev::PipelineStages::VERTEX_SHADER,
+            );
+        }
+
+        encoder.init_image(
+            mev::PipelineStages::all(),
+            mev::PipelineStages::FRAGMENT_SHADER,
+            &target,
+        );
+
+        let mut render = encoder.render(mev::RenderPassDesc {
+            color_attachments: &[mev::AttachmentDesc::new(&target).clear(background.into())],
+            ..Default::default()
+        });
+
+        let dims = target.extent().expect_2d();
+
+        render.with_pipeline(pipeline);
+        render.with_constants(&frame.constants);
+        render.with_viewport(
+            mev::Offset3::ZERO,
+            mev::Extent3::new(dims.width() as f32, dims.height() as f32, 1.0),
+        );
+        render.with_scissor(mev::Offset2::ZERO, dims);
+
+        if let Some(buffer) = &frame.buffer {
+            for batch in &frame.batches {
+                render.with_arguments(
+                    0,
+                    &SpriteArguments {
+                        instances: buffer.clone(),
+                        sampler: sampler.clone(),
+                        texture: batch.image.clone(),
+                    },
+                );
+                render.draw(0..6, batch.first..batch.first + batch.count);
+            }
+            count_draws(world, frame.batches.len() as u32);
+        }
+
+        drop(render);
+    }
+}
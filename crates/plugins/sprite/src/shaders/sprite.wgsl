@@ -0,0 +1,55 @@
+struct Constants {
+    camera: mat3x3f,
+}
+
+var<push_constant> pc: Constants;
+
+struct Instance {
+    tr: mat3x3f,
+    uv: vec4f,
+    color: vec4f,
+}
+
+@group(0) @binding(0) var<storage> instances: array<Instance>;
+@group(0) @binding(1) var s: sampler;
+@group(0) @binding(2) var t: texture_2d<f32>;
+
+struct VertOutput {
+    @builtin(position)
+    position: vec4f,
+    @location(0)
+    uv: vec2f,
+    @location(1)
+    color: vec4f,
+}
+
+// Two triangles of unit quad.
+const CORNERS = array<vec2f, 6>(
+    vec2f(0f, 0f),
+    vec2f(1f, 0f),
+    vec2f(1f, 1f),
+    vec2f(0f, 0f),
+    vec2f(1f, 1f),
+    vec2f(0f, 1f),
+);
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex: u32, @builtin(instance_index) instance: u32) -> VertOutput {
+    var corners = CORNERS;
+    let corner = corners[vertex];
+    let sprite = instances[instance];
+
+    // Sprite quad is centered at its origin.
+    let world = sprite.tr * vec3f(corner - vec2f(0.5f), 1f);
+    let view = pc.camera * world;
+
+    // Texture rows go down while world Y goes up.
+    let uv = mix(sprite.uv.xy, sprite.uv.zw, vec2f(corner.x, 1f - corner.y));
+
+    return VertOutput(vec4f(view.xy, 0f, 1f), uv, sprite.color);
+}
+
+@fragment
+fn fs_main(input: VertOutput) -> @location(0) vec4f {
+    return textureSample(t, s, input.uv) * input.color;
+}
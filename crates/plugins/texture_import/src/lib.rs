@@ -0,0 +1,252 @@
+//! This plugin provides importer that encodes images into Basis Universal textures.
+//!
+//! Images are encoded in UASTC mode with full mip chain.
+//! At runtime [`Texture`] transcodes them into the best format supported by the device,
+//! so the same artifact works on desktop and mobile GPUs.
+//!
+//! Artifacts use `.basis` container since it is the one supported by the transcoder.
+//!
+//! Images may also be imported as [`WindowIcon`] which keeps raw RGBA8 pixels.
+//!
+//! Images imported as [`CursorImage`] have hotspot in the top-left corner.
+//! To set hotspot, add JSON manifest with `.cursor` extension.
+//!
+//! ```json
+//! {
+//!     "image": "cursors/crosshair.png",
+//!     "hotspot": [16, 16]
+//! }
+//! ```
+//!
+//! Image path is relative to the manifest.
+//!
+//! [`Texture`]: arcana::texture::Texture
+//! [`WindowIcon`]: arcana::window::WindowIcon
+//! [`CursorImage`]: arcana::input::CursorImage
+
+use std::{fmt::Display, path::Path};
+
+use arcana::{
+    assets::import::{ensure, AssetDependencies, AssetSources, ImportError, Importer},
+    ident,
+    input::CursorImage,
+    name,
+    window::WindowIcon,
+    Ident, Name,
+};
+use basis_universal::{
+    BasisTextureFormat, ColorSpace, Compressor, CompressorParams, UASTC_QUALITY_DEFAULT,
+};
+
+arcana::declare_plugin!();
+
+/// Encodes images into Basis Universal textures.
+#[arcana::importer]
+#[derive(Default)]
+pub struct BasisTextureImporter;
+
+impl BasisTextureImporter {
+    pub fn new() -> Self {
+        BasisTextureImporter
+    }
+}
+
+impl Importer for BasisTextureImporter {
+    fn name(&self) -> Name {
+        name!(basis_texture)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["png", "jpeg", "bmp", "tga", "qoi"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["png", "jpg", "jpeg", "bmp", "tga", "qoi"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(texture)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        _sources: &mut dyn AssetSources,
+        _dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let image = image::open(source).map_err(error_to_reason)?.to_rgba8();
+
+        let mut params = CompressorParams::new();
+        params.set_basis_format(BasisTextureFormat::UASTC4x4);
+        params.set_uastc_quality_level(UASTC_QUALITY_DEFAULT);
+        params.set_color_space(ColorSpace::Srgb);
+        params.set_generate_mipmaps(true);
+        params
+            .source_image_mut(0)
+            .init(image.as_raw(), image.width(), image.height(), 4);
+
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get() as u32);
+        let mut compressor = Compressor::new(threads);
+
+        // Safety: params are fully initialized above.
+        unsafe {
+            if !compressor.init(&params) {
+                return Err(ImportError::Other {
+                    reason: "Failed to initialize Basis Universal compressor".to_owned(),
+                });
+            }
+
+            compressor
+                .process()
+                .map_err(|code| error_to_reason(format!("{code:?}")))?;
+        }
+
+        std::fs::write(output, compressor.basis_file()).map_err(error_to_reason)
+    }
+}
+
+/// Imports images as window icons.
+#[arcana::importer]
+#[derive(Default)]
+pub struct WindowIconImporter;
+
+impl WindowIconImporter {
+    pub fn new() -> Self {
+        WindowIconImporter
+    }
+}
+
+impl Importer for WindowIconImporter {
+    fn name(&self) -> Name {
+        name!(window_icon)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["png", "ico", "bmp"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["png", "ico", "bmp"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(window_icon)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        _sources: &mut dyn AssetSources,
+        _dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let image = image::open(source).map_err(error_to_reason)?.to_rgba8();
+
+        let icon = WindowIcon {
+            width: image.width(),
+            height: image.height(),
+            rgba: image.into_raw(),
+        };
+
+        std::fs::write(output, icon.encode()).map_err(error_to_reason)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct CursorManifest {
+    image: String,
+
+    /// Hotspot position in pixels from the top-left corner.
+    #[serde(default)]
+    hotspot: [u16; 2],
+}
+
+/// Imports images as cursor images.
+#[arcana::importer]
+#[derive(Default)]
+pub struct CursorImporter;
+
+impl CursorImporter {
+    pub fn new() -> Self {
+        CursorImporter
+    }
+}
+
+impl Importer for CursorImporter {
+    fn name(&self) -> Name {
+        name!(cursor)
+    }
+
+    fn formats(&self) -> &[&str] {
+        &["png", "ico", "bmp", "cursor"]
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["png", "ico", "bmp", "cursor"]
+    }
+
+    fn target(&self) -> Ident {
+        ident!(cursor)
+    }
+
+    fn import(
+        &self,
+        source: &Path,
+        output: &Path,
+        sources: &mut dyn AssetSources,
+        _dependencies: &mut dyn AssetDependencies,
+    ) -> Result<(), ImportError> {
+        let is_manifest = source.extension().map_or(false, |ext| ext == "cursor");
+
+        let (hotspot, image_path) = if is_manifest {
+            let manifest = std::fs::read(source).map_err(error_to_reason)?;
+            let manifest: CursorManifest =
+                serde_json::from_slice(&manifest).map_err(error_to_reason)?;
+
+            let mut missing = Vec::new();
+            let path = sources.get_or_append(&manifest.image, &mut missing);
+            ensure(missing, Vec::new())?;
+
+            (manifest.hotspot, path.unwrap())
+        } else {
+            ([0, 0], source.to_owned())
+        };
+
+        let image = image::open(&image_path)
+            .map_err(error_to_reason)?
+            .to_rgba8();
+
+        let (Ok(width), Ok(height)) = (u16::try_from(image.width()), u16::try_from(image.height()))
+        else {
+            return Err(error_to_reason(format!(
+                "Cursor image {}x{} is too large",
+                image.width(),
+                image.height()
+            )));
+        };
+
+        let [hotspot_x, hotspot_y] = hotspot;
+        if hotspot_x >= width || hotspot_y >= height {
+            return Err(error_to_reason(format!(
+                "Cursor hotspot {hotspot_x}x{hotspot_y} is out of {width}x{height} image bounds"
+            )));
+        }
+
+        let cursor = CursorImage {
+            width,
+            height,
+            hotspot_x,
+            hotspot_y,
+            rgba: image.into_raw(),
+        };
+
+        std::fs::write(output, cursor.encode()).map_err(error_to_reason)
+    }
+}
+
+fn error_to_reason<E: Display>(error: E) -> ImportError {
+    ImportError::Other {
+        reason: error.to_string(),
+    }
+}
@@ -81,6 +81,9 @@ impl Job for DrawTriangle {
 
         constants.width = target.extent.width();
         constants.height = target.extent.height();
+
+        // Triangle is only redrawn when it rotates or target is resized.
+        planner.cache_inputs(&(constants.angle.to_bits(), constants.width, constants.height));
     }
 
     fn exec(&mut self, runner: Exec<'_>, _world: &mut World) {
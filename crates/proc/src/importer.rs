@@ -0,0 +1,36 @@
+use proc_macro2::TokenStream;
+
+pub fn importer(attr: proc_macro::TokenStream, item: syn::ItemStruct) -> syn::Result<TokenStream> {
+    if !attr.is_empty() {
+        return Err(syn::Error::new_spanned(
+            TokenStream::from(attr),
+            "unexpected attribute",
+        ));
+    }
+
+    let ident = &item.ident;
+    Ok(quote::quote! {
+        ::arcana::plugin_ctor_add!(plugin => {
+            let id: ::arcana::assets::import::ImporterId = ::arcana::local_name_hash_id!(#ident);
+
+            let add = |hub: &mut ::arcana::plugin::PluginsHub| {
+                let id: ::arcana::assets::import::ImporterId = ::arcana::local_name_hash_id!(#ident);
+                hub.add_importer(id, < #ident >::new());
+            };
+
+            let info = ::arcana::plugin::ImporterInfo {
+                id,
+                name: ::arcana::assets::import::Importer::name(&< #ident >::new()),
+                location: ::std::option::Option::Some(::arcana::plugin::Location {
+                    file: std::string::String::from(::std::file!()),
+                    line: ::std::line!(),
+                    column: ::std::column!(),
+                }),
+            };
+
+            plugin.add_importer(info, add);
+        });
+
+        #item
+    })
+}
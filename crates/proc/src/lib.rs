@@ -1,6 +1,7 @@
 // extern crate proc_macro;
 
 mod filter;
+mod importer;
 mod init;
 mod job;
 mod stable_hasher;
@@ -98,6 +99,9 @@ pub fn filter(attr: TokenStream, item: TokenStream) -> TokenStream {
 }
 
 /// Exports function as system.
+///
+/// `#[system(var)]` puts the system into variable-rate schedule by default,
+/// otherwise it runs at fixed rate.
 #[proc_macro_attribute]
 pub fn system(attr: TokenStream, item: TokenStream) -> TokenStream {
     let item = syn::parse_macro_input!(item as syn::ItemFn);
@@ -127,6 +131,16 @@ pub fn init(attr: TokenStream, item: TokenStream) -> TokenStream {
     }
 }
 
+/// Exports struct as asset importer.
+#[proc_macro_attribute]
+pub fn importer(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = syn::parse_macro_input!(item as syn::ItemStruct);
+    match importer::importer(attr, item) {
+        Ok(output) => output.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 // /// Exports function as filter.
 // #[proc_macro]
 // pub fn plugin(_tokens: TokenStream) -> TokenStream {
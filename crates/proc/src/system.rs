@@ -1,11 +1,19 @@
 use proc_macro2::TokenStream;
 
 pub fn system(attr: proc_macro::TokenStream, item: syn::ItemFn) -> syn::Result<TokenStream> {
+    // Systems run at fixed rate unless declared otherwise.
+    let mut fixed = true;
+
     if !attr.is_empty() {
-        return Err(syn::Error::new_spanned(
-            TokenStream::from(attr),
-            "unexpected attribute",
-        ));
+        let attr = syn::parse::<syn::Ident>(attr)?;
+
+        if attr == "fixed" {
+            fixed = true;
+        } else if attr == "var" {
+            fixed = false;
+        } else {
+            return Err(syn::Error::new_spanned(attr, "expected `fixed` or `var`"));
+        }
     }
 
     let ident = &item.sig.ident;
@@ -21,6 +29,7 @@ pub fn system(attr: proc_macro::TokenStream, item: syn::ItemFn) -> syn::Result<T
             let info = ::arcana::plugin::SystemInfo {
                 id,
                 name: ::arcana::name!(#ident),
+                fixed: #fixed,
                 location: ::std::option::Option::Some(::arcana::plugin::Location {
                     file: std::string::String::from(::std::file!()),
                     line: ::std::line!(),
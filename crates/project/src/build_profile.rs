@@ -0,0 +1,192 @@
+//! Cargo profile settings declared in project manifest.
+//!
+//! ```toml
+//! [profile.debug]
+//! plugins-opt-level = 2
+//! dependencies-opt-level = 3
+//!
+//! [profile.cook]
+//! lto = "fat"
+//! panic = "abort"
+//! codegen-units = 1
+//! ```
+//!
+//! `debug` and `release` sections map to cargo `dev` and `release` profiles.
+//! `cook` section maps to profile release games are cooked with.
+
+use std::fmt::{self, Write};
+
+use arcana_names::Ident;
+
+/// Optimization level.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum OptLevel {
+    /// Level from 0 to 3.
+    Level(u8),
+
+    /// Size optimization, either `"s"` or `"z"`.
+    Size(String),
+}
+
+/// Link time optimization.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum Lto {
+    Enabled(bool),
+
+    /// One of `"thin"`, `"fat"` or `"off"`.
+    Mode(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PanicStrategy {
+    Unwind,
+    Abort,
+}
+
+/// Settings of one cargo profile.
+/// Unset values are left to cargo defaults.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProfileSettings {
+    /// Optimization level of all crates unless overridden below.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub opt_level: Option<OptLevel>,
+
+    /// Optimization level of plugin crates.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub plugins_opt_level: Option<OptLevel>,
+
+    /// Optimization level of all dependencies, including engine.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub dependencies_opt_level: Option<OptLevel>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub lto: Option<Lto>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub panic: Option<PanicStrategy>,
+
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub codegen_units: Option<u32>,
+
+    /// Generate debug info.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub debug: Option<bool>,
+}
+
+impl ProfileSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == ProfileSettings::default()
+    }
+}
+
+/// Profile settings of the project.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct BuildProfiles {
+    #[serde(skip_serializing_if = "ProfileSettings::is_empty", default)]
+    pub debug: ProfileSettings,
+
+    #[serde(skip_serializing_if = "ProfileSettings::is_empty", default)]
+    pub release: ProfileSettings,
+
+    /// Settings applied on top of `release` when game is cooked.
+    #[serde(skip_serializing_if = "ProfileSettings::is_empty", default)]
+    pub cook: ProfileSettings,
+}
+
+impl BuildProfiles {
+    pub fn is_empty(&self) -> bool {
+        self.debug.is_empty() && self.release.is_empty() && self.cook.is_empty()
+    }
+}
+
+impl fmt::Display for OptLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptLevel::Level(level) => write!(f, "{level}"),
+            OptLevel::Size(size) => write!(f, "\"{}\"", size.escape_default()),
+        }
+    }
+}
+
+impl fmt::Display for Lto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lto::Enabled(enabled) => write!(f, "{enabled}"),
+            Lto::Mode(mode) => write!(f, "\"{}\"", mode.escape_default()),
+        }
+    }
+}
+
+impl fmt::Display for PanicStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PanicStrategy::Unwind => f.write_str("\"unwind\""),
+            PanicStrategy::Abort => f.write_str("\"abort\""),
+        }
+    }
+}
+
+/// Writes cargo profile sections for workspace `Cargo.toml`.
+pub(crate) fn write_cargo_profiles(
+    out: &mut String,
+    profiles: &BuildProfiles,
+    plugins: &[Ident],
+) -> fmt::Result {
+    write_cargo_profile(out, "dev", None, &profiles.debug, plugins)?;
+    write_cargo_profile(out, "release", None, &profiles.release, plugins)?;
+
+    // Cook profile always exists since cook builds use it.
+    write_cargo_profile(out, "cook", Some("release"), &profiles.cook, plugins)?;
+
+    Ok(())
+}
+
+fn write_cargo_profile(
+    out: &mut String,
+    name: &str,
+    inherits: Option<&str>,
+    settings: &ProfileSettings,
+    plugins: &[Ident],
+) -> fmt::Result {
+    if settings.is_empty() && inherits.is_none() {
+        return Ok(());
+    }
+
+    writeln!(out, "\n[profile.{name}]")?;
+    if let Some(inherits) = inherits {
+        writeln!(out, "inherits = \"{inherits}\"")?;
+    }
+    if let Some(opt_level) = &settings.opt_level {
+        writeln!(out, "opt-level = {opt_level}")?;
+    }
+    if let Some(lto) = &settings.lto {
+        writeln!(out, "lto = {lto}")?;
+    }
+    if let Some(panic) = &settings.panic {
+        writeln!(out, "panic = {panic}")?;
+    }
+    if let Some(codegen_units) = &settings.codegen_units {
+        writeln!(out, "codegen-units = {codegen_units}")?;
+    }
+    if let Some(debug) = &settings.debug {
+        writeln!(out, "debug = {debug}")?;
+    }
+
+    if let Some(opt_level) = &settings.dependencies_opt_level {
+        writeln!(out, "\n[profile.{name}.package.\"*\"]")?;
+        writeln!(out, "opt-level = {opt_level}")?;
+    }
+
+    if let Some(opt_level) = &settings.plugins_opt_level {
+        for plugin in plugins {
+            writeln!(out, "\n[profile.{name}.package.{plugin}]")?;
+            writeln!(out, "opt-level = {opt_level}")?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Crash reports written by a crashed editor or game process.
+//!
+//! The process that panics writes a report into the project's workspace directory
+//! and the launcher picks it up after the child process exits.
+
+use std::path::{Path, PathBuf};
+
+use crate::WORKSPACE_DIR_NAME;
+
+const CRASH_REPORT_FILE_NAME: &'static str = "crash.json";
+
+/// Information about a panic that killed the process.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CrashReport {
+    /// Panic message.
+    pub message: String,
+
+    /// Source location of the panic.
+    pub location: Option<String>,
+
+    /// Name of the panicking thread.
+    pub thread: Option<String>,
+
+    pub backtrace: String,
+
+    /// Last log lines before the panic, oldest first.
+    pub log: Vec<String>,
+}
+
+impl CrashReport {
+    /// Returns path to the crash report of the project at `root`.
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(WORKSPACE_DIR_NAME).join(CRASH_REPORT_FILE_NAME)
+    }
+
+    /// Writes the report for the project at `root`, replacing previous one.
+    pub fn write(&self, root: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(Self::path(root), json)
+    }
+
+    /// Reads and removes crash report of the project at `root` if there is one.
+    pub fn take(root: &Path) -> Option<CrashReport> {
+        let path = Self::path(root);
+        let json = std::fs::read(&path).ok()?;
+        let _ = std::fs::remove_file(&path);
+
+        match serde_json::from_slice(&json) {
+            Ok(report) => Some(report),
+            Err(err) => {
+                tracing::warn!("Failed to parse crash report '{}': {err}", path.display());
+                None
+            }
+        }
+    }
+}
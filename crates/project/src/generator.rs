@@ -2,7 +2,14 @@ use std::{fmt, path::Path};
 
 use camino::Utf8Path;
 
-use crate::{dependency::Dependency, path::make_relative, plugin::Plugin, WORKSPACE_DIR_NAME};
+use crate::{
+    build_profile::{write_cargo_profiles, BuildProfiles},
+    dependency::Dependency,
+    lock::{LockedPlugin, ProjectLock},
+    path::make_relative,
+    plugin::Plugin,
+    WORKSPACE_DIR_NAME,
+};
 
 struct ArcanaDependency<'a>(&'a Dependency);
 
@@ -25,22 +32,28 @@ impl fmt::Display for ArcanaDependency<'_> {
 }
 
 /// Dependency on a plugin crate.
+/// Pinned to locked version if there is one.
 struct PluginDependency<'a> {
     dep: &'a Dependency,
+    locked: Option<&'a LockedPlugin>,
 }
 
 impl fmt::Display for PluginDependency<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.dep {
-            Dependency::Crates(version) => write!(f, "\"{}\"", version),
-            Dependency::Git { git, branch } => {
+        match (self.dep, self.locked) {
+            (Dependency::Crates(_), Some(locked)) => write!(f, "\"={}\"", locked.version),
+            (Dependency::Git { git, .. }, Some(LockedPlugin { rev: Some(rev), .. })) => {
+                write!(f, "{{ git = \"{git}\", rev = \"{rev}\" }}")
+            }
+            (Dependency::Crates(version), _) => write!(f, "\"{}\"", version),
+            (Dependency::Git { git, branch }, _) => {
                 if let Some(branch) = branch {
                     write!(f, "{{ git = \"{git}\", branch = \"{branch}\" }}",)
                 } else {
                     write!(f, "{{ git = \"{git}\" }}")
                 }
             }
-            Dependency::Path { path } => {
+            (Dependency::Path { path }, _) => {
                 write!(f, "{{ path = \"{}\" }}", path.as_str().escape_default(),)
             }
         }
@@ -138,6 +151,8 @@ pub fn init_workspace(
     name: &str,
     engine: &Dependency,
     plugins: &[Plugin],
+    profiles: &BuildProfiles,
+    lock: &ProjectLock,
 ) -> miette::Result<()> {
     let workspace = root.join(WORKSPACE_DIR_NAME);
     std::fs::create_dir_all(&*workspace).map_err(|err| {
@@ -159,7 +174,7 @@ pub fn init_workspace(
     let engine = engine.clone().make_relative_from(".", WORKSPACE_DIR_NAME)?;
 
     #[rustfmt::skip]
-    let cargo_toml = format!(
+    let mut cargo_toml = format!(
 r#"# This file is automatically generated for Arcana Project.
 # It should not require manual editing.
 # If manual editing is required, consider posting your motivation in new GitHub issue
@@ -176,6 +191,10 @@ arcana = {arcana}
         arcana = ArcanaDependency(&engine),
     );
 
+    let plugin_names: Vec<_> = plugins.iter().map(|p| p.name).collect();
+    write_cargo_profiles(&mut cargo_toml, profiles, &plugin_names)
+        .expect("Writing to string cannot fail");
+
     let cargo_toml_path = workspace.join("Cargo.toml");
     write_file(&cargo_toml_path, &cargo_toml).map_err(|err| {
         miette::miette!(
@@ -197,8 +216,8 @@ channel = "nightly"
     })?;
 
     init_ed_crate(root, &workspace)?;
-    init_plugins_crate(root, &workspace, plugins)?;
-    init_game_crate(root, &workspace, name, plugins)?;
+    init_plugins_crate(root, &workspace, plugins, lock)?;
+    init_game_crate(root, &workspace, name, plugins, lock)?;
 
     Ok(())
 }
@@ -266,7 +285,11 @@ fn main() {{
     let _exe_path = args.next().unwrap();
     let project_path = args.next().unwrap();
 
-    arcana::ed::run(&project_path);
+    match (args.next(), args.next()) {{
+        (Some(flag), Some(output)) if flag == "--cook" => arcana::ed::cook(&project_path, &output),
+        (Some(flag), Some(script)) if flag == "--headless" => arcana::ed::headless(&project_path, &script),
+        _ => arcana::ed::run(&project_path),
+    }}
 }}
 "#,
         gh_issue = github_autogen_issue_template("ed/src/main.rs")
@@ -286,7 +309,12 @@ fn main() {{
 /// Generates plugins crate.
 ///
 /// Plugins crate is a cdylib that links all plugin crates together.
-fn init_plugins_crate(root: &Path, workspace: &Path, plugins: &[Plugin]) -> miette::Result<()> {
+fn init_plugins_crate(
+    root: &Path,
+    workspace: &Path,
+    plugins: &[Plugin],
+    lock: &ProjectLock,
+) -> miette::Result<()> {
     let plugins_path = workspace.join("plugins");
 
     std::fs::create_dir_all(&plugins_path).map_err(|err| {
@@ -326,7 +354,10 @@ arcana = {{ workspace = true }}
         cargo_toml.push_str(&format!(
             "{name} = {dependency}\n",
             name = &plugin.name,
-            dependency = PluginDependency { dep: &dep }
+            dependency = PluginDependency {
+                dep: &dep,
+                locked: lock.get(plugin.name),
+            }
         ));
     }
 
@@ -403,6 +434,7 @@ fn init_game_crate(
     workspace: &Path,
     name: &str,
     plugins: &[Plugin],
+    lock: &ProjectLock,
 ) -> miette::Result<()> {
     let game_path = workspace.join("game");
 
@@ -443,7 +475,10 @@ arcana = {{ workspace = true }}
         cargo_toml.push_str(&format!(
             "{name} = {dependency}\n",
             name = &plugin.name,
-            dependency = PluginDependency { dep: &dep }
+            dependency = PluginDependency {
+                dep: &dep,
+                locked: lock.get(plugin.name),
+            }
         ));
     }
 
@@ -505,6 +540,39 @@ fn main() {{
 
 /// Writes content to a file.
 /// If new content is the same as old content the file is not modified.
+/// Writes HTML page that loads game WASM module through generated bindings.
+pub fn write_web_shell(out_dir: &Path, name: &str) -> miette::Result<()> {
+    let index_html = format!(
+        r#"<!DOCTYPE html>
+<!-- This file is automatically generated by Arcana cook. -->
+<html>
+<head>
+    <meta charset="utf-8">
+    <title>{name}</title>
+    <style>
+        html, body {{ margin: 0; height: 100%; overflow: hidden; background: black; }}
+        canvas {{ width: 100%; height: 100%; display: block; }}
+    </style>
+</head>
+<body>
+    <script type="module">
+        import init from "./{name}.js";
+        init();
+    </script>
+</body>
+</html>
+"#
+    );
+
+    let index_html_path = out_dir.join("index.html");
+    write_file(&index_html_path, &index_html).map_err(|err| {
+        miette::miette!(
+            "Failed to write web shell '{}'. {err:?}",
+            index_html_path.display()
+        )
+    })
+}
+
 fn write_file<P, C>(path: P, content: C) -> std::io::Result<()>
 where
     P: AsRef<Path>,
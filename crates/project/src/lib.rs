@@ -15,31 +15,49 @@ use std::{
 use arcana_names::{Ident, Name};
 use camino::{Utf8Path, Utf8PathBuf};
 
+mod build_profile;
+mod crash;
 mod dependency;
 mod generator;
+mod lock;
 mod manifest;
+mod migrate;
 mod path;
+mod permissions;
 mod plugin;
+mod window;
 mod wrapper;
 
-use generator::init_workspace;
+use generator::{init_workspace, write_web_shell};
 use manifest::serialize_manifest;
 use miette::{Context, IntoDiagnostic};
 use path::{normalized_path, normalizing_join};
 
 pub use self::{
+    build_profile::{BuildProfiles, Lto, OptLevel, PanicStrategy, ProfileSettings},
+    crash::CrashReport,
     dependency::Dependency,
     generator::new_plugin_crate,
+    lock::{LockedPlugin, ProjectLock, LOCK_FILE_NAME},
     manifest::ProjectManifest,
+    migrate::{
+        backup_before_migration, migrate_manifest, migrate_project_data, MANIFEST_VERSION,
+        PROJECT_DATA_VERSION,
+    },
     path::{make_relative, real_path},
+    permissions::{Permission, Permissions},
     plugin::Plugin,
-    wrapper::{game_bin_path, BuildProcess, Profile},
+    window::{WindowConfig, WindowMode},
+    wrapper::{game_bin_path, game_wasm_path, BuildProcess, Profile, WEB_TARGET},
 };
 
 const MANIFEST_FILE_EXT: &'static str = "arcana";
 const CARGO_TOML_NAME: &'static str = "Cargo.toml";
 const WORKSPACE_DIR_NAME: &'static str = "crates";
 
+/// Name of the packed assets archive placed next to cooked game binary.
+pub const COOKED_ASSETS_FILE: &'static str = "assets.arcpack";
+
 /// An open project object.
 ///
 /// It contains project manifest,
@@ -127,9 +145,12 @@ impl Project {
 
         /// Construct project manifest.
         let manifest = ProjectManifest {
+            version: MANIFEST_VERSION,
             name,
             engine,
             plugins: Vec::new(),
+            profile: BuildProfiles::default(),
+            window: WindowConfig::default(),
         };
 
         let manifest_str = match toml::to_string(&manifest) {
@@ -246,7 +267,24 @@ impl Project {
             }
         };
 
-        let manifest: ProjectManifest = match toml::from_str(&arcana_toml) {
+        let mut document: toml::Table = match toml::from_str(&arcana_toml) {
+            Ok(document) => document,
+            Err(err) => {
+                miette::bail!(
+                    "Cannot parse project manifest '{}': {err:?}",
+                    manifest_path.display()
+                );
+            }
+        };
+
+        let version = migrate_manifest(&mut document).wrap_err_with(|| {
+            format!(
+                "Cannot migrate project manifest '{}'",
+                manifest_path.display()
+            )
+        })?;
+
+        let mut manifest: ProjectManifest = match document.try_into() {
             Ok(manifest) => manifest,
             Err(err) => {
                 if manifest_path != path {
@@ -264,11 +302,28 @@ impl Project {
             }
         };
 
-        let project = Project {
+        let lock = ProjectLock::load(manifest_path.parent().unwrap())?;
+        for name in lock.stale(&manifest.plugins) {
+            tracing::warn!(
+                "Locked version of plugin '{name}' does not match manifest. It will be resolved again"
+            );
+        }
+        lock.apply_permissions(&mut manifest.plugins);
+
+        let mut project = Project {
             manifest_path,
             manifest,
         };
 
+        if version != MANIFEST_VERSION {
+            let backup = backup_before_migration(&project.manifest_path, version)?;
+            tracing::info!(
+                "Project manifest migrated. Original saved to '{}'",
+                backup.display()
+            );
+            project.sync()?;
+        }
+
         Ok(project)
     }
 
@@ -300,13 +355,51 @@ impl Project {
     }
 
     /// Initializes all plugin wrapper libs and workspace.
+    ///
+    /// Plugins are pinned to versions from the lock file.
+    /// Plugins without lock entry are resolved by cargo and recorded in the lock file.
     pub fn init_workspace(&self) -> miette::Result<()> {
+        let loaded = ProjectLock::load(self.root_path())?;
+        let mut lock = loaded.clone();
+        lock.retain_valid(&self.manifest.plugins);
+
         init_workspace(
             self.root_path(),
             &self.manifest.name,
             &self.manifest.engine,
             &self.manifest.plugins,
-        )
+            &self.manifest.profile,
+            &lock,
+        )?;
+
+        if lock.needs_resolve(&self.manifest.plugins) {
+            let output = wrapper::resolve_workspace(self.root_path())
+                .output()
+                .map_err(|err| miette::miette!("Cannot resolve project workspace: {err:?}"))?;
+
+            if !output.status.success() {
+                miette::bail!("Failed to resolve project workspace: {}", output.status);
+            }
+
+            let cargo_lock_path = self.root_path().join(WORKSPACE_DIR_NAME).join("Cargo.lock");
+            lock.resolve(&cargo_lock_path, &self.manifest.plugins)?;
+            lock.resolve_permissions(&output.stdout)?;
+        }
+
+        if lock != loaded {
+            lock.save(self.root_path())?;
+        }
+
+        Ok(())
+    }
+
+    /// Updates permissions of released and git plugins from the lock file.
+    ///
+    /// They are known only after plugins are resolved by [`Project::init_workspace`].
+    pub fn refresh_permissions(&mut self) -> miette::Result<()> {
+        let lock = ProjectLock::load(self.root_path())?;
+        lock.apply_permissions(&mut self.manifest.plugins);
+        Ok(())
     }
 
     pub fn build_plugins_library(&self, profile: Profile) -> miette::Result<BuildProcess> {
@@ -343,9 +436,9 @@ impl Project {
         &mut self.manifest.plugins
     }
 
-    pub fn run_editor(self, profile: Profile) -> miette::Result<()> {
+    pub fn run_editor(self, profile: Profile, watch: bool) -> miette::Result<()> {
         self.init_workspace()?;
-        let status = wrapper::run_editor(self.root_path(), &self.manifest_path, profile)
+        let status = wrapper::run_editor(self.root_path(), &self.manifest_path, profile, watch)
             .status()
             .map_err(|err| {
                 miette::miette!(
@@ -361,6 +454,35 @@ impl Project {
         }
     }
 
+    /// Runs editor without windows, executing commands from the script.
+    /// Fails if any script command fails.
+    pub fn run_editor_headless(self, profile: Profile, script: &Path) -> miette::Result<()> {
+        self.init_workspace()?;
+
+        let script = match script.canonicalize() {
+            Ok(script) => script,
+            Err(err) => {
+                miette::bail!("Cannot find script '{}': {err:?}", script.display());
+            }
+        };
+
+        let status =
+            wrapper::run_editor_headless(self.root_path(), &self.manifest_path, &script, profile)
+                .status()
+                .map_err(|err| {
+                    miette::miette!(
+                        "Cannot run headless \"ed\" on \"{}\": {err:?}",
+                        self.manifest_path.display()
+                    )
+                })?;
+
+        match status.code() {
+            Some(0) => Ok(()),
+            Some(code) => miette::bail!("Headless \"ed\" exited with code {}", code),
+            None => miette::bail!("Headless \"ed\" terminated by signal"),
+        }
+    }
+
     pub fn build_editor_non_blocking(&self, profile: Profile) -> miette::Result<Child> {
         self.init_workspace()?;
         match wrapper::build_editor(self.root_path(), profile).spawn() {
@@ -374,9 +496,9 @@ impl Project {
         }
     }
 
-    pub fn run_editor_non_blocking(&self, profile: Profile) -> miette::Result<Child> {
+    pub fn run_editor_non_blocking(&self, profile: Profile, watch: bool) -> miette::Result<Child> {
         self.init_workspace()?;
-        match wrapper::run_editor(self.root_path(), &self.manifest_path, profile).spawn() {
+        match wrapper::run_editor(self.root_path(), &self.manifest_path, profile, watch).spawn() {
             Ok(child) => Ok(child),
             Err(err) => {
                 miette::bail!(
@@ -387,9 +509,13 @@ impl Project {
         }
     }
 
-    pub fn build_game(self, profile: Profile) -> miette::Result<PathBuf> {
+    pub fn build_game(&self, profile: Profile) -> miette::Result<PathBuf> {
+        self.build_game_impl(profile, false)
+    }
+
+    fn build_game_impl(&self, profile: Profile, cook: bool) -> miette::Result<PathBuf> {
         self.init_workspace()?;
-        let status = wrapper::build_game(self.root_path(), profile)
+        let status = wrapper::build_game(self.root_path(), profile, cook)
             .status()
             .map_err(|err| {
                 miette::miette!(
@@ -404,7 +530,146 @@ impl Project {
             None => miette::bail!("Game build terminated by signal"),
         }
 
-        Ok(game_bin_path(&self.manifest.name, self.root_path()))
+        Ok(game_bin_path(
+            &self.manifest.name,
+            self.root_path(),
+            profile,
+            cook,
+        ))
+    }
+
+    /// Cooks the game for distribution.
+    ///
+    /// Builds the game, imports all assets and packs them into archive.
+    /// Results are laid out in `target/cook/<profile>/<platform>` under project root.
+    /// Returns path to that directory.
+    pub fn cook(&self, profile: Profile) -> miette::Result<PathBuf> {
+        let platform = format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS);
+        let cook_path = self.prepare_cook_dir(profile, &platform)?;
+
+        tracing::info!("Building game");
+        let game_bin = self.build_game_impl(profile, true)?;
+
+        tracing::info!("Cooking assets");
+        self.cook_assets(&cook_path, profile)?;
+
+        let Some(bin_name) = game_bin.file_name() else {
+            miette::bail!("Invalid game binary path \"{}\"", game_bin.display());
+        };
+
+        let bin_path = cook_path.join(bin_name);
+        std::fs::copy(&game_bin, &bin_path).map_err(|err| {
+            miette::miette!(
+                "Cannot copy game binary \"{}\" to \"{}\": {err:?}",
+                game_bin.display(),
+                bin_path.display()
+            )
+        })?;
+
+        Ok(cook_path)
+    }
+
+    /// Cooks the game for the web.
+    ///
+    /// Builds the game for WASM target, generates JS bindings and `index.html` page
+    /// that loads them.
+    /// Assets are packed into the same archive as for native builds.
+    /// Archive keeps index at known offset and blobs independent from each other,
+    /// so it can be streamed over HTTP with range requests.
+    ///
+    /// Results are laid out in `target/cook/<profile>/web`.
+    /// Directory can be served by any static HTTP server.
+    pub fn cook_web(&self, profile: Profile) -> miette::Result<PathBuf> {
+        let cook_path = self.prepare_cook_dir(profile, "web")?;
+
+        tracing::info!("Building game for {WEB_TARGET}");
+        self.init_workspace()?;
+        let status = wrapper::build_game_web(self.root_path(), profile)
+            .status()
+            .map_err(|err| {
+                miette::miette!(
+                    "Cannot build game \"{}\" for web: {err:?}",
+                    self.manifest_path.display(),
+                )
+            })?;
+
+        match status.code() {
+            Some(0) => {}
+            Some(code) => miette::bail!("Game web build exited with code {}", code),
+            None => miette::bail!("Game web build terminated by signal"),
+        }
+
+        tracing::info!("Generating bindings");
+        let wasm_path = game_wasm_path(&self.manifest.name, self.root_path(), profile);
+        let status = wrapper::wasm_bindgen(&wasm_path, &cook_path)
+            .status()
+            .map_err(|err| {
+                miette::miette!(
+                    "Cannot run wasm-bindgen on \"{}\". Make sure wasm-bindgen-cli is installed: {err:?}",
+                    wasm_path.display(),
+                )
+            })?;
+
+        match status.code() {
+            Some(0) => {}
+            Some(code) => miette::bail!("wasm-bindgen exited with code {}", code),
+            None => miette::bail!("wasm-bindgen terminated by signal"),
+        }
+
+        write_web_shell(&cook_path, &self.manifest.name)?;
+
+        tracing::info!("Cooking assets");
+        self.cook_assets(&cook_path, profile)?;
+
+        Ok(cook_path)
+    }
+
+    /// Creates empty `target/cook/<profile>/<platform>` directory.
+    fn prepare_cook_dir(&self, profile: Profile, platform: &str) -> miette::Result<PathBuf> {
+        let mut cook_path = self.root_path().join("target");
+        cook_path.push("cook");
+        cook_path.push(profile.as_str());
+        cook_path.push(platform);
+
+        if cook_path.exists() {
+            std::fs::remove_dir_all(&cook_path).map_err(|err| {
+                miette::miette!(
+                    "Cannot clear cook directory \"{}\": {err:?}",
+                    cook_path.display()
+                )
+            })?;
+        }
+
+        std::fs::create_dir_all(&cook_path).map_err(|err| {
+            miette::miette!(
+                "Cannot create cook directory \"{}\": {err:?}",
+                cook_path.display()
+            )
+        })?;
+
+        Ok(cook_path)
+    }
+
+    /// Imports and packs assets into archive in the cook directory.
+    fn cook_assets(&self, cook_path: &Path, profile: Profile) -> miette::Result<()> {
+        let assets_path = cook_path.join(COOKED_ASSETS_FILE);
+        let status =
+            wrapper::cook_assets(self.root_path(), &self.manifest_path, &assets_path, profile)
+                .status()
+                .map_err(|err| {
+                    miette::miette!(
+                        "Cannot cook assets of \"{}\": {err:?}",
+                        self.manifest_path.display()
+                    )
+                })?;
+
+        match status.code() {
+            Some(0) => {}
+            Some(code) => miette::bail!("Asset cooking exited with code {}", code),
+            None => miette::bail!("Asset cooking terminated by signal"),
+        }
+
+        Ok(())
     }
 
     pub fn run_game(self, profile: Profile) -> miette::Result<()> {
@@ -441,6 +706,19 @@ impl Project {
         self.manifest.plugins.push(plugin);
         Ok(true)
     }
+
+    /// Removes plugin from the project.
+    /// Returns false if project has no plugin with this name.
+    pub fn remove_plugin(&mut self, name: Ident) -> bool {
+        let Some(idx) = self.manifest.plugins.iter().position(|p| p.name == name) else {
+            return false;
+        };
+
+        self.manifest.remove_plugin_idx(idx);
+
+        tracing::info!("Plugin '{}' removed", name);
+        true
+    }
 }
 
 fn is_in_cargo_workspace(path: &Path) -> bool {
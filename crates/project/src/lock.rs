@@ -0,0 +1,425 @@
+//! Plugin versions resolved for the project.
+//!
+//! Cargo lockfile of generated workspace is not part of the project,
+//! so resolved versions of released and git plugins are recorded in `Arcana.lock`
+//! next to the manifest.
+//! Generated workspace pins plugins to locked versions,
+//! so project built on another machine uses identical plugin code.
+//!
+//! Path plugins are not locked, their code is whatever is on disk.
+
+use std::path::Path;
+
+use arcana_names::Ident;
+
+use crate::{dependency::Dependency, permissions::Permissions, plugin::Plugin};
+
+pub const LOCK_FILE_NAME: &'static str = "Arcana.lock";
+
+/// Resolved version of a plugin.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LockedPlugin {
+    pub name: Ident,
+
+    /// Dependency from the manifest this version was resolved for.
+    /// When manifest changes plugin dependency, lock entry is discarded.
+    pub dependency: Dependency,
+
+    /// Resolved crate version.
+    pub version: String,
+
+    /// Resolved commit for git plugins.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub rev: Option<String>,
+
+    /// Permissions declared by resolved package in its `Cargo.toml`.
+    #[serde(skip_serializing_if = "Permissions::is_empty", default)]
+    pub permissions: Permissions,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ProjectLock {
+    #[serde(rename = "plugin", skip_serializing_if = "Vec::is_empty", default)]
+    pub plugins: Vec<LockedPlugin>,
+}
+
+#[derive(serde::Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<CargoLockPackage>,
+}
+
+#[derive(serde::Deserialize)]
+struct CargoLockPackage {
+    name: String,
+    version: String,
+    #[serde(default)]
+    source: Option<String>,
+}
+
+/// Output of `cargo metadata`.
+#[derive(serde::Deserialize)]
+struct CargoMetadata {
+    packages: Vec<CargoMetadataPackage>,
+}
+
+#[derive(serde::Deserialize)]
+struct CargoMetadataPackage {
+    name: String,
+    version: String,
+    #[serde(default)]
+    source: Option<String>,
+    manifest_path: String,
+}
+
+impl ProjectLock {
+    /// Loads lock from project root.
+    /// Returns empty lock if there is no lock file.
+    pub fn load(root: &Path) -> miette::Result<Self> {
+        let path = root.join(LOCK_FILE_NAME);
+
+        let s = match std::fs::read_to_string(&path) {
+            Ok(s) => s,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(ProjectLock::default())
+            }
+            Err(err) => {
+                miette::bail!("Cannot read lock file '{}': {err:?}", path.display());
+            }
+        };
+
+        match toml::from_str(&s) {
+            Ok(lock) => Ok(lock),
+            Err(err) => {
+                miette::bail!("Cannot deserialize lock file '{}': {err:?}", path.display());
+            }
+        }
+    }
+
+    pub fn save(&self, root: &Path) -> miette::Result<()> {
+        let path = root.join(LOCK_FILE_NAME);
+
+        let s = toml::to_string_pretty(self)
+            .map_err(|err| miette::miette!("Cannot serialize lock file: {err:?}"))?;
+
+        if let Err(err) = std::fs::write(&path, s) {
+            miette::bail!("Cannot write lock file '{}': {err:?}", path.display());
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, name: Ident) -> Option<&LockedPlugin> {
+        self.plugins.iter().find(|p| p.name == name)
+    }
+
+    /// Checks lock against plugins from the manifest.
+    /// Returns names of plugins whose lock entries are stale.
+    pub fn stale(&self, plugins: &[Plugin]) -> Vec<Ident> {
+        self.plugins
+            .iter()
+            .filter(|locked| {
+                !plugins
+                    .iter()
+                    .any(|p| p.name == locked.name && p.dependency == locked.dependency)
+            })
+            .map(|locked| locked.name)
+            .collect()
+    }
+
+    /// Removes lock entries that do not match plugins from the manifest.
+    pub fn retain_valid(&mut self, plugins: &[Plugin]) {
+        self.plugins.retain(|locked| {
+            plugins
+                .iter()
+                .any(|p| p.name == locked.name && p.dependency == locked.dependency)
+        });
+    }
+
+    /// Returns true if some released or git plugin is not locked yet.
+    pub fn needs_resolve(&self, plugins: &[Plugin]) -> bool {
+        plugins
+            .iter()
+            .any(|p| !matches!(p.dependency, Dependency::Path { .. }) && self.get(p.name).is_none())
+    }
+
+    /// Records versions of unlocked plugins resolved by cargo.
+    pub fn resolve(&mut self, cargo_lock_path: &Path, plugins: &[Plugin]) -> miette::Result<()> {
+        let s = match std::fs::read_to_string(cargo_lock_path) {
+            Ok(s) => s,
+            Err(err) => {
+                miette::bail!(
+                    "Cannot read workspace lock file '{}': {err:?}",
+                    cargo_lock_path.display()
+                );
+            }
+        };
+
+        let cargo_lock: CargoLock = match toml::from_str(&s) {
+            Ok(cargo_lock) => cargo_lock,
+            Err(err) => {
+                miette::bail!(
+                    "Cannot deserialize workspace lock file '{}': {err:?}",
+                    cargo_lock_path.display()
+                );
+            }
+        };
+
+        for plugin in plugins {
+            if self.get(plugin.name).is_some() {
+                continue;
+            }
+
+            let expected_source = match &plugin.dependency {
+                Dependency::Path { .. } => continue,
+                Dependency::Crates(_) => "registry+",
+                Dependency::Git { .. } => "git+",
+            };
+
+            let package = cargo_lock.package.iter().find(|package| {
+                same_package_name(&package.name, plugin.name.as_str())
+                    && package
+                        .source
+                        .as_deref()
+                        .map_or(false, |s| s.starts_with(expected_source))
+            });
+
+            let Some(package) = package else {
+                tracing::warn!(
+                    "Plugin '{}' is not found in workspace lock file",
+                    plugin.name
+                );
+                continue;
+            };
+
+            let rev = match &plugin.dependency {
+                Dependency::Git { .. } => {
+                    let source = package.source.as_deref().unwrap_or_default();
+                    match source.rsplit_once('#') {
+                        Some((_, commit)) => Some(commit.to_owned()),
+                        None => {
+                            tracing::warn!("Git source of plugin '{}' has no commit", plugin.name);
+                            continue;
+                        }
+                    }
+                }
+                _ => None,
+            };
+
+            tracing::info!(
+                "Plugin '{}' locked to version {}{}",
+                plugin.name,
+                package.version,
+                rev.as_deref()
+                    .map_or_else(String::new, |rev| format!(" ({rev})"))
+            );
+
+            self.plugins.push(LockedPlugin {
+                name: plugin.name,
+                dependency: plugin.dependency.clone(),
+                version: package.version.clone(),
+                rev,
+                permissions: Permissions::NONE,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Reads permissions of locked plugins from manifests of resolved packages.
+    ///
+    /// `metadata` is output of `cargo metadata` for the generated workspace.
+    pub fn resolve_permissions(&mut self, metadata: &[u8]) -> miette::Result<()> {
+        let metadata: CargoMetadata = match serde_json::from_slice(metadata) {
+            Ok(metadata) => metadata,
+            Err(err) => {
+                miette::bail!("Cannot deserialize workspace metadata: {err:?}");
+            }
+        };
+
+        for locked in &mut self.plugins {
+            let expected_source = match &locked.dependency {
+                Dependency::Path { .. } => continue,
+                Dependency::Crates(_) => "registry+",
+                Dependency::Git { .. } => "git+",
+            };
+
+            let package = metadata.packages.iter().find(|package| {
+                same_package_name(&package.name, locked.name.as_str())
+                    && package.version == locked.version
+                    && package
+                        .source
+                        .as_deref()
+                        .map_or(false, |s| s.starts_with(expected_source))
+            });
+
+            let Some(package) = package else {
+                tracing::warn!(
+                    "Plugin '{}' is not found in workspace metadata",
+                    locked.name
+                );
+                continue;
+            };
+
+            let manifest = match cargo_toml::Manifest::from_path(&package.manifest_path) {
+                Ok(manifest) => manifest,
+                Err(err) => {
+                    miette::bail!(
+                        "Failed to read plugin manifest '{}': {err:?}",
+                        package.manifest_path
+                    );
+                }
+            };
+
+            let metadata = manifest.package.as_ref().and_then(|p| p.metadata.as_ref());
+            locked.permissions = match Permissions::from_metadata(metadata) {
+                Ok(permissions) => permissions,
+                Err(err) => {
+                    miette::bail!(
+                        "Plugin manifest '{}' has invalid permissions: {err}",
+                        package.manifest_path
+                    );
+                }
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Replaces permissions of released and git plugins with the locked ones.
+    ///
+    /// Manifest copy of them is not trusted, it may be stale or edited by hand.
+    pub fn apply_permissions(&self, plugins: &mut [Plugin]) {
+        for plugin in plugins {
+            if let Dependency::Path { .. } = plugin.dependency {
+                continue;
+            }
+            plugin.permissions = self
+                .get(plugin.name)
+                .filter(|locked| locked.dependency == plugin.dependency)
+                .map_or(Permissions::NONE, |locked| locked.permissions);
+        }
+    }
+}
+
+/// Compares package names the way cargo does,
+/// treating `-` and `_` as the same character.
+fn same_package_name(a: &str, b: &str) -> bool {
+    let normalize = |c: char| if c == '-' { '_' } else { c };
+    a.len() == b.len() && a.chars().map(normalize).eq(b.chars().map(normalize))
+}
+
+#[cfg(test)]
+mod tests {
+    use arcana_names::ident;
+
+    use super::*;
+
+    #[test]
+    fn test_same_package_name() {
+        assert!(same_package_name("my-plugin", "my_plugin"));
+        assert!(same_package_name("my_plugin", "my-plugin"));
+        assert!(same_package_name("my-plugin", "my-plugin"));
+        assert!(!same_package_name("my-plugin", "myplugin"));
+        assert!(!same_package_name("my-plugin", "my-plugin2"));
+    }
+
+    #[test]
+    fn test_resolve_dashed_package() {
+        let dir = std::env::temp_dir().join(format!("arcana-lock-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cargo_lock_path = dir.join("Cargo.lock");
+
+        std::fs::write(
+            &cargo_lock_path,
+            r#"
+[[package]]
+name = "my-plugin"
+version = "0.3.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "git-plugin"
+version = "0.1.0"
+source = "git+https://example.com/git-plugin.git#0123abcd"
+"#,
+        )
+        .unwrap();
+
+        let plugins = [
+            Plugin::released(ident!(my_plugin), "0.3".to_owned()),
+            Plugin::from_git(
+                ident!(git_plugin),
+                "https://example.com/git-plugin.git".to_owned(),
+                None,
+            ),
+        ];
+
+        let mut lock = ProjectLock::default();
+        lock.resolve(&cargo_lock_path, &plugins).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let locked = lock.get(ident!(my_plugin)).unwrap();
+        assert_eq!(locked.version, "0.3.1");
+        assert_eq!(locked.rev, None);
+
+        let locked = lock.get(ident!(git_plugin)).unwrap();
+        assert_eq!(locked.version, "0.1.0");
+        assert_eq!(locked.rev.as_deref(), Some("0123abcd"));
+
+        assert!(!lock.needs_resolve(&plugins));
+    }
+
+    #[test]
+    fn test_resolve_permissions() {
+        let dir = std::env::temp_dir().join(format!("arcana-perm-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let manifest_path = dir.join("Cargo.toml");
+
+        std::fs::write(
+            &manifest_path,
+            r#"
+[package]
+name = "net-plugin"
+version = "0.2.0"
+
+[package.metadata.arcana]
+permissions = ["network"]
+"#,
+        )
+        .unwrap();
+
+        let metadata = serde_json::json!({
+            "packages": [{
+                "name": "net-plugin",
+                "version": "0.2.0",
+                "source": "registry+https://github.com/rust-lang/crates.io-index",
+                "manifest_path": manifest_path,
+            }],
+        });
+
+        let mut plugins = [Plugin::released(ident!(net_plugin), "0.2".to_owned())];
+
+        let mut lock = ProjectLock::default();
+        lock.plugins.push(LockedPlugin {
+            name: ident!(net_plugin),
+            dependency: plugins[0].dependency.clone(),
+            version: "0.2.0".to_owned(),
+            rev: None,
+            permissions: Permissions::NONE,
+        });
+
+        lock.resolve_permissions(&serde_json::to_vec(&metadata).unwrap())
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(lock.plugins[0]
+            .permissions
+            .contains(crate::permissions::Permission::Network));
+
+        // Permissions written into the project manifest by hand are replaced.
+        plugins[0].permissions = Permissions::NONE;
+        lock.apply_permissions(&mut plugins);
+        assert_eq!(plugins[0].permissions, lock.plugins[0].permissions);
+    }
+}
@@ -2,13 +2,20 @@ use std::path::Path;
 
 use arcana_names::{Ident, Name};
 
-use crate::{dependency::Dependency, plugin::Plugin};
+use crate::{
+    build_profile::BuildProfiles, dependency::Dependency, plugin::Plugin, window::WindowConfig,
+};
 
 /// Project manifest.
 /// Contains information about project, dependencies, systems order, etc.
 /// Put into `<project-name.arcana>` file.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 pub struct ProjectManifest {
+    /// Version of the manifest format.
+    /// Older manifests are migrated on open.
+    #[serde(default)]
+    pub version: u32,
+
     /// Name of the project.
     pub name: Ident,
 
@@ -19,6 +26,14 @@ pub struct ProjectManifest {
     /// List of plugin libraries this project depends on.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub plugins: Vec<Plugin>,
+
+    /// Cargo profile settings for generated workspace.
+    #[serde(skip_serializing_if = "BuildProfiles::is_empty", default)]
+    pub profile: BuildProfiles,
+
+    /// Settings of the game window.
+    #[serde(skip_serializing_if = "WindowConfig::is_default", default)]
+    pub window: WindowConfig,
 }
 
 impl ProjectManifest {
@@ -37,6 +52,12 @@ impl ProjectManifest {
     pub fn remove_plugin_idx(&mut self, idx: usize) {
         self.plugins.remove(idx);
     }
+
+    /// Moves plugin to another position in the list.
+    pub fn move_plugin_idx(&mut self, from: usize, to: usize) {
+        let plugin = self.plugins.remove(from);
+        self.plugins.insert(to, plugin);
+    }
 }
 
 pub(super) fn serialize_manifest(manifest: &ProjectManifest) -> Result<String, toml::ser::Error> {
@@ -0,0 +1,193 @@
+//! Upgrades project files written by older engine versions.
+//!
+//! Both project manifest and project data carry `version` field.
+//! Files without it are treated as version 0.
+//!
+//! Each migration upgrades document by exactly one version.
+//! Migrations operate on untyped documents, so they keep working
+//! after typed structures change again.
+//!
+//! Original file is copied to `<file>.v<version>.bak` before migrated one is written.
+
+use std::path::{Path, PathBuf};
+
+/// Current version of project manifest format.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Current version of project data format.
+pub const PROJECT_DATA_VERSION: u32 = 1;
+
+type ManifestMigration = fn(&mut toml::Table) -> miette::Result<()>;
+type ProjectDataMigration =
+    fn(&mut serde_json::Map<String, serde_json::Value>) -> miette::Result<()>;
+
+/// Manifest migrations.
+/// Migration at index N upgrades manifest from version N to N + 1.
+const MANIFEST_MIGRATIONS: [ManifestMigration; MANIFEST_VERSION as usize] = [
+    // Version field introduced.
+    |_| Ok(()),
+];
+
+/// Project data migrations.
+/// Migration at index N upgrades project data from version N to N + 1.
+const PROJECT_DATA_MIGRATIONS: [ProjectDataMigration; PROJECT_DATA_VERSION as usize] = [
+    // Version field introduced.
+    |_| Ok(()),
+];
+
+/// Upgrades manifest document to current version.
+/// Returns version manifest had before migration.
+pub fn migrate_manifest(manifest: &mut toml::Table) -> miette::Result<u32> {
+    let version = match manifest.get("version") {
+        None => 0,
+        Some(toml::Value::Integer(version)) => match u32::try_from(*version) {
+            Ok(version) => version,
+            Err(_) => miette::bail!("Invalid project manifest version {version}"),
+        },
+        Some(value) => miette::bail!("Invalid project manifest version {value}"),
+    };
+
+    if version > MANIFEST_VERSION {
+        miette::bail!(
+            "Project manifest version {version} is newer than supported {MANIFEST_VERSION}. Update the engine"
+        );
+    }
+
+    for (idx, migration) in MANIFEST_MIGRATIONS[version as usize..].iter().enumerate() {
+        let from = version + idx as u32;
+        tracing::info!(
+            "Migrating project manifest from version {from} to {}",
+            from + 1
+        );
+
+        migration(manifest)?;
+        manifest.insert("version".to_owned(), toml::Value::Integer(from as i64 + 1));
+    }
+
+    Ok(version)
+}
+
+/// Upgrades project data document to current version.
+/// Returns version project data had before migration.
+pub fn migrate_project_data(data: &mut serde_json::Value) -> miette::Result<u32> {
+    let Some(data) = data.as_object_mut() else {
+        miette::bail!("Project data is not an object");
+    };
+
+    let version = match data.get("version") {
+        None => 0,
+        Some(value) => match value.as_u64().and_then(|v| u32::try_from(v).ok()) {
+            Some(version) => version,
+            None => miette::bail!("Invalid project data version {value}"),
+        },
+    };
+
+    if version > PROJECT_DATA_VERSION {
+        miette::bail!(
+            "Project data version {version} is newer than supported {PROJECT_DATA_VERSION}. Update the engine"
+        );
+    }
+
+    for (idx, migration) in PROJECT_DATA_MIGRATIONS[version as usize..]
+        .iter()
+        .enumerate()
+    {
+        let from = version + idx as u32;
+        tracing::info!("Migrating project data from version {from} to {}", from + 1);
+
+        migration(data)?;
+        data.insert("version".to_owned(), serde_json::Value::from(from + 1));
+    }
+
+    Ok(version)
+}
+
+/// Copies file that is about to be overwritten by migrated version.
+/// Returns path to the backup.
+pub fn backup_before_migration(path: &Path, version: u32) -> miette::Result<PathBuf> {
+    let mut file_name = path.file_name().unwrap_or_default().to_owned();
+    file_name.push(format!(".v{version}.bak"));
+    let backup = path.with_file_name(file_name);
+
+    if let Err(err) = std::fs::copy(path, &backup) {
+        miette::bail!(
+            "Failed to backup '{}' before migration to '{}': {err:?}",
+            path.display(),
+            backup.display()
+        );
+    }
+
+    Ok(backup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_manifest() {
+        let mut manifest: toml::Table = toml::from_str(r#"name = "game""#).unwrap();
+        assert_eq!(migrate_manifest(&mut manifest).unwrap(), 0);
+        assert_eq!(
+            manifest.get("version"),
+            Some(&toml::Value::Integer(MANIFEST_VERSION as i64))
+        );
+        assert_eq!(
+            manifest.get("name"),
+            Some(&toml::Value::String("game".to_owned()))
+        );
+
+        // Current version is left as is.
+        let before = manifest.clone();
+        assert_eq!(migrate_manifest(&mut manifest).unwrap(), MANIFEST_VERSION);
+        assert_eq!(manifest, before);
+    }
+
+    #[test]
+    fn test_migrate_manifest_invalid_version() {
+        let mut manifest: toml::Table =
+            toml::from_str(&format!("version = {}", MANIFEST_VERSION + 1)).unwrap();
+        assert!(migrate_manifest(&mut manifest).is_err());
+
+        let mut manifest: toml::Table = toml::from_str("version = -1").unwrap();
+        assert!(migrate_manifest(&mut manifest).is_err());
+
+        let mut manifest: toml::Table = toml::from_str(r#"version = "1""#).unwrap();
+        assert!(migrate_manifest(&mut manifest).is_err());
+    }
+
+    #[test]
+    fn test_migrate_project_data() {
+        let mut data = serde_json::json!({ "systems": [] });
+        assert_eq!(migrate_project_data(&mut data).unwrap(), 0);
+        assert_eq!(data["version"], PROJECT_DATA_VERSION);
+        assert_eq!(data["systems"], serde_json::json!([]));
+
+        let before = data.clone();
+        assert_eq!(
+            migrate_project_data(&mut data).unwrap(),
+            PROJECT_DATA_VERSION
+        );
+        assert_eq!(data, before);
+
+        let mut data = serde_json::json!({ "version": PROJECT_DATA_VERSION + 1 });
+        assert!(migrate_project_data(&mut data).is_err());
+
+        let mut data = serde_json::json!([]);
+        assert!(migrate_project_data(&mut data).is_err());
+    }
+
+    #[test]
+    fn test_backup_before_migration() {
+        let dir = std::env::temp_dir().join(format!("arcana-migrate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Arcana.toml");
+        std::fs::write(&path, "name = \"game\"").unwrap();
+
+        let backup = backup_before_migration(&path, 0).unwrap();
+        assert_eq!(backup, dir.join("Arcana.toml.v0.bak"));
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), "name = \"game\"");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,121 @@
+//! Capabilities plugins request from the engine.
+//!
+//! Plugin declares them in its `Cargo.toml`:
+//!
+//! ```toml
+//! [package.metadata.arcana]
+//! permissions = ["filesystem", "network"]
+//! ```
+
+/// Single capability.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    /// Access files outside of assets.
+    Filesystem,
+
+    /// Open network connections.
+    Network,
+
+    /// Spawn processes.
+    Subprocess,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 3] = [
+        Permission::Filesystem,
+        Permission::Network,
+        Permission::Subprocess,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Permission::Filesystem => "filesystem",
+            Permission::Network => "network",
+            Permission::Subprocess => "subprocess",
+        }
+    }
+
+    /// Returns what the permission allows, for showing to the user.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Permission::Filesystem => "Read and write files on this computer",
+            Permission::Network => "Connect to other computers over the network",
+            Permission::Subprocess => "Run other programs",
+        }
+    }
+
+    fn bit(&self) -> u8 {
+        match self {
+            Permission::Filesystem => 1,
+            Permission::Network => 2,
+            Permission::Subprocess => 4,
+        }
+    }
+}
+
+/// Set of permissions.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(from = "Vec<Permission>", into = "Vec<Permission>")]
+pub struct Permissions {
+    bits: u8,
+}
+
+impl Permissions {
+    pub const NONE: Self = Permissions { bits: 0 };
+
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn contains(&self, permission: Permission) -> bool {
+        self.bits & permission.bit() != 0
+    }
+
+    /// Returns true if all permissions of `other` are in this set.
+    pub fn includes(&self, other: &Permissions) -> bool {
+        self.bits & other.bits == other.bits
+    }
+
+    pub fn insert(&mut self, permission: Permission) {
+        self.bits |= permission.bit();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Permission> + '_ {
+        Permission::ALL.into_iter().filter(|p| self.contains(*p))
+    }
+
+    /// Reads permissions from `package.metadata.arcana.permissions`.
+    pub fn from_metadata(metadata: Option<&cargo_toml::Value>) -> Result<Self, String> {
+        let Some(permissions) = metadata
+            .and_then(|m| m.get("arcana"))
+            .and_then(|a| a.get("permissions"))
+        else {
+            return Ok(Permissions::NONE);
+        };
+
+        permissions
+            .clone()
+            .try_into::<Vec<Permission>>()
+            .map(Permissions::from)
+            .map_err(|err| err.to_string())
+    }
+}
+
+impl From<Vec<Permission>> for Permissions {
+    fn from(list: Vec<Permission>) -> Self {
+        let mut permissions = Permissions::NONE;
+        for permission in list {
+            permissions.insert(permission);
+        }
+        permissions
+    }
+}
+
+impl From<Permissions> for Vec<Permission> {
+    fn from(permissions: Permissions) -> Self {
+        permissions.iter().collect()
+    }
+}
@@ -2,7 +2,7 @@ use std::path::Path;
 
 use camino::Utf8PathBuf;
 
-use crate::{dependency::Dependency, real_path, Ident, CARGO_TOML_NAME};
+use crate::{dependency::Dependency, permissions::Permissions, real_path, Ident, CARGO_TOML_NAME};
 
 /// Contains information about plugin.
 ///
@@ -28,6 +28,12 @@ pub struct Plugin {
     pub name: Ident,
     pub description: String,
     pub dependency: Dependency,
+
+    /// Permissions plugin requests.
+    /// Read from plugin manifest for local plugins,
+    /// otherwise from manifest of the resolved package recorded in the lock file.
+    #[serde(default, skip_serializing_if = "Permissions::is_empty")]
+    pub permissions: Permissions,
 }
 
 impl Plugin {
@@ -55,6 +61,7 @@ impl Plugin {
             name,
             description: String::new(),
             dependency: Dependency::Crates(version),
+            permissions: Permissions::NONE,
         }
     }
 
@@ -64,6 +71,7 @@ impl Plugin {
             name,
             description: String::new(),
             dependency: Dependency::Git { git, branch },
+            permissions: Permissions::NONE,
         }
     }
 
@@ -108,12 +116,22 @@ impl Plugin {
             None => String::new(),
         };
 
+        let permissions = match Permissions::from_metadata(package.metadata.as_ref()) {
+            Ok(permissions) => permissions,
+            Err(err) => {
+                miette::bail!(
+                    "Plugin manifest '{path}/{CARGO_TOML_NAME}' has invalid permissions: {err}",
+                );
+            }
+        };
+
         let dependency = Dependency::Path { path };
 
         Ok(Plugin {
             name,
             description,
             dependency,
+            permissions,
         })
     }
 }
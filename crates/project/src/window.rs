@@ -0,0 +1,75 @@
+//! Game window settings declared in project manifest.
+//!
+//! ```toml
+//! [window]
+//! title = "My Game"
+//! size = [1280, 720]
+//! mode = "borderless"
+//! resizable = false
+//! min-size = [640, 360]
+//! icon = "4d0a6ef2c2b1a9e3"
+//! ```
+
+/// How game window occupies the screen.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+
+    /// Exclusive fullscreen with the largest video mode of the monitor.
+    Fullscreen,
+
+    /// Borderless window that covers the whole monitor.
+    Borderless,
+}
+
+/// Settings of the game window.
+///
+/// Game bootstrap creates window with these settings
+/// and keeps it in sync with the resource of the same type,
+/// so systems may change window at runtime.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct WindowConfig {
+    /// Window title.
+    /// Project name is used if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// Initial inner size in logical pixels.
+    pub size: [u32; 2],
+
+    pub mode: WindowMode,
+
+    pub resizable: bool,
+
+    /// Minimal inner size in logical pixels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_size: Option<[u32; 2]>,
+
+    /// Id of the window icon asset in hex.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            title: None,
+            size: [1280, 720],
+            mode: WindowMode::Windowed,
+            resizable: true,
+            min_size: None,
+            icon: None,
+        }
+    }
+}
+
+impl WindowConfig {
+    pub fn is_default(&self) -> bool {
+        *self == WindowConfig::default()
+    }
+}
@@ -4,21 +4,72 @@ use std::{
     env::consts::{DLL_PREFIX, DLL_SUFFIX, EXE_SUFFIX},
     fmt,
     path::{Path, PathBuf},
-    process::{Child, Command},
+    process::{Child, Command, Stdio},
 };
 
 use crate::{path::make_relative, WORKSPACE_DIR_NAME};
 
 use super::Dependency;
 
+/// Target triple games are built for to run in browsers.
+pub const WEB_TARGET: &'static str = "wasm32-unknown-unknown";
+
+/// Cargo profile release games are cooked with.
+/// Inherits `release` and applies `profile.cook` settings from the manifest.
+pub const COOK_PROFILE: &'static str = "cook";
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Profile {
     Release,
     Debug,
 }
 
+impl Profile {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Profile::Release => "release",
+            Profile::Debug => "debug",
+        }
+    }
+}
+
+/// Adds cargo profile selection arguments.
+/// With `cook` release builds use cook profile.
+fn profile_args(cmd: &mut Command, profile: Profile, cook: bool) {
+    match (profile, cook) {
+        (Profile::Debug, _) => {}
+        (Profile::Release, false) => {
+            cmd.arg("--release");
+        }
+        (Profile::Release, true) => {
+            cmd.arg(format!("--profile={COOK_PROFILE}"));
+        }
+    }
+}
+
+/// Returns name of the directory in cargo target dir with artifacts of the profile.
+fn profile_dir(profile: Profile, cook: bool) -> &'static str {
+    match (profile, cook) {
+        (Profile::Release, true) => COOK_PROFILE,
+        _ => profile.as_str(),
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Construct a command to run ed for arcana project.
-pub fn run_editor(root_path: &Path, manifest_path: &Path, profile: Profile) -> Command {
+///
+/// With `watch` ed rebuilds plugins library when plugin sources change.
+pub fn run_editor(
+    root_path: &Path,
+    manifest_path: &Path,
+    profile: Profile,
+    watch: bool,
+) -> Command {
     let workspace = root_path.join(WORKSPACE_DIR_NAME);
     let mut cmd = Command::new("cargo");
     cmd.arg("run").arg("--package=ed");
@@ -32,6 +83,10 @@ pub fn run_editor(root_path: &Path, manifest_path: &Path, profile: Profile) -> C
         }
     }
 
+    if watch {
+        cmd.env("ARCANA_WATCH", "1");
+    }
+
     cmd.arg("--");
     cmd.arg(manifest_path.as_os_str());
 
@@ -71,14 +126,104 @@ pub fn run_game(root: &Path, profile: Profile) -> Command {
 }
 
 /// Construct a command to run ed for arcana project.
-pub fn build_game(root: &Path, profile: Profile) -> Command {
+pub fn build_game(root: &Path, profile: Profile, cook: bool) -> Command {
     let workspace = root.join(WORKSPACE_DIR_NAME);
     let mut cmd = Command::new("cargo");
     cmd.arg("build").arg("--package=game");
+    profile_args(&mut cmd, profile, cook);
+    cmd.env("RUSTFLAGS", "-Zshare-generics=off")
+        .current_dir(&workspace);
+    cmd
+}
+
+/// Construct a command that resolves workspace dependencies and writes `Cargo.lock`.
+///
+/// Existing `Cargo.lock` entries are kept.
+/// Workspace metadata is written to stdout.
+pub fn resolve_workspace(root: &Path) -> Command {
+    let workspace = root.join(WORKSPACE_DIR_NAME);
+    let mut cmd = Command::new("cargo");
+    cmd.arg("metadata")
+        .arg("--format-version=1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .current_dir(&workspace);
+    cmd
+}
+
+/// Construct a command to build game for the web.
+pub fn build_game_web(root: &Path, profile: Profile) -> Command {
+    let workspace = root.join(WORKSPACE_DIR_NAME);
+    let mut cmd = Command::new("cargo");
+    cmd.arg("build")
+        .arg("--package=game")
+        .arg(format!("--target={WEB_TARGET}"));
+    profile_args(&mut cmd, profile, true);
+    cmd.current_dir(&workspace);
+    cmd
+}
+
+/// Construct a command to generate JS bindings for game WASM module.
+///
+/// Requires `wasm-bindgen-cli` to be installed.
+pub fn wasm_bindgen(wasm_path: &Path, out_dir: &Path) -> Command {
+    let mut cmd = Command::new("wasm-bindgen");
+    cmd.arg("--target=web")
+        .arg("--no-typescript")
+        .arg("--out-dir")
+        .arg(out_dir.as_os_str())
+        .arg(wasm_path.as_os_str());
+    cmd
+}
+
+/// Construct a command to run ed without windows executing the script.
+pub fn run_editor_headless(
+    root_path: &Path,
+    manifest_path: &Path,
+    script: &Path,
+    profile: Profile,
+) -> Command {
+    let workspace = root_path.join(WORKSPACE_DIR_NAME);
+    let mut cmd = Command::new("cargo");
+    cmd.arg("run").arg("--package=ed");
     if profile == Profile::Release {
         cmd.arg("--release");
     }
-    cmd.env("RUSTFLAGS", "-Zshare-generics=off")
+    cmd.env("ARCANA_PROFILE", profile.as_str());
+
+    cmd.arg("--");
+    cmd.arg(manifest_path.as_os_str());
+    cmd.arg("--headless");
+    cmd.arg(script.as_os_str());
+
+    cmd.env("RUSTFLAGS", "-Zshare-generics=off -Cprefer-dynamic=yes")
+        .current_dir(&workspace);
+    cmd
+}
+
+/// Construct a command to import and pack project assets into archive.
+///
+/// Runs ed in cook mode, so importers from project plugins are available.
+pub fn cook_assets(
+    root_path: &Path,
+    manifest_path: &Path,
+    output: &Path,
+    profile: Profile,
+) -> Command {
+    let workspace = root_path.join(WORKSPACE_DIR_NAME);
+    let mut cmd = Command::new("cargo");
+    cmd.arg("run").arg("--package=ed");
+    if profile == Profile::Release {
+        cmd.arg("--release");
+    }
+    cmd.env("ARCANA_PROFILE", profile.as_str());
+
+    cmd.arg("--");
+    cmd.arg(manifest_path.as_os_str());
+    cmd.arg("--cook");
+    cmd.arg(output.as_os_str());
+
+    cmd.env("RUSTFLAGS", "-Zshare-generics=off -Cprefer-dynamic=yes")
         .current_dir(&workspace);
     cmd
 }
@@ -115,10 +260,7 @@ pub fn build_plugins(root: &Path, profile: Profile) -> miette::Result<BuildProce
 /// Construct expected plugin build artifact path.
 fn plugins_lib_path(workspace: &Path, profile: Profile) -> PathBuf {
     let mut lib_path = workspace.join("target");
-    lib_path.push(match profile {
-        Profile::Release => "release",
-        Profile::Debug => "debug",
-    }); // Hardcoded for now.
+    lib_path.push(profile.as_str()); // Hardcoded for now.
     lib_path.push(format!("{DLL_PREFIX}plugins{DLL_SUFFIX}"));
     lib_path
 }
@@ -161,11 +303,20 @@ impl BuildProcess {
     }
 }
 
-/// Construct expected plugin build artifact path.
-pub fn game_bin_path(name: &str, root: &Path) -> PathBuf {
+/// Construct expected game build artifact path.
+pub fn game_wasm_path(name: &str, root: &Path, profile: Profile) -> PathBuf {
+    let mut wasm_path = root.join(WORKSPACE_DIR_NAME);
+    wasm_path.push("target");
+    wasm_path.push(WEB_TARGET);
+    wasm_path.push(profile_dir(profile, true));
+    wasm_path.push(format!("{name}.wasm"));
+    wasm_path
+}
+
+pub fn game_bin_path(name: &str, root: &Path, profile: Profile, cook: bool) -> PathBuf {
     let mut bin_path = root.join(WORKSPACE_DIR_NAME);
     bin_path.push("target");
-    bin_path.push("debug"); // Hardcoded for now.
+    bin_path.push(profile_dir(profile, cook));
     bin_path.push(format!("{name}{EXE_SUFFIX}"));
     bin_path
 }